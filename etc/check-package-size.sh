@@ -41,6 +41,14 @@ echo "in root: gitoxide CLI"
 (enter gix-features && indent cargo diet -n --package-size-limit 65KB)
 (enter gix-ref && indent cargo diet -n --package-size-limit 55KB)
 (enter gix-diff && indent cargo diet -n --package-size-limit 35KB)
+(enter gix-merge && indent cargo diet -n --package-size-limit 35KB)
+(enter gix-blame && indent cargo diet -n --package-size-limit 25KB)
+(enter gix-note && indent cargo diet -n --package-size-limit 20KB)
+(enter gix-lfs && indent cargo diet -n --package-size-limit 25KB)
+(enter gix-sequencer && indent cargo diet -n --package-size-limit 15KB)
+(enter gix-rebase && indent cargo diet -n --package-size-limit 15KB)
+(enter gix-fetchhead && indent cargo diet -n --package-size-limit 10KB)
+(enter gix-tix && indent cargo diet -n --package-size-limit 20KB)
 (enter gix-traverse && indent cargo diet -n --package-size-limit 15KB)
 (enter gix-url && indent cargo diet -n --package-size-limit 35KB)
 (enter gix-validate && indent cargo diet -n --package-size-limit 10KB)
@@ -80,6 +80,11 @@ pub struct Context<W> {
     /// This is a total, shared among all threads if `thread_limit` permits.
     /// Only used when known to be effective, namely when `expansion == ObjectExpansion::TreeDiff`.
     pub object_cache_size_in_bytes: usize,
+    /// If not 0, objects that aren't yet deltified in a pack are delta-compressed against up to this amount of
+    /// similar objects, similar to `git pack-objects --window`.
+    pub delta_window: usize,
+    /// The maximum length of delta chains created when delta-compressing objects, similar to `git pack-objects --depth`.
+    pub delta_depth: usize,
     /// The output stream for use of additional information
     pub out: W,
 }
@@ -98,6 +103,8 @@ pub fn create<W, P>(
         statistics,
         pack_cache_size_in_bytes,
         object_cache_size_in_bytes,
+        delta_window,
+        delta_depth,
         mut out,
     }: Context<W>,
 ) -> anyhow::Result<()>
@@ -229,10 +236,16 @@ where
             Box::new(progress),
             pack::data::output::entry::iter_from_counts::Options {
                 thread_limit,
-                mode: pack::data::output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+                mode: if delta_window == 0 {
+                    pack::data::output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects
+                } else {
+                    pack::data::output::entry::iter_from_counts::Mode::PackCopyAndDeltaCompression
+                },
                 allow_thin_pack: thin,
                 chunk_size,
                 version: Default::default(),
+                delta_window,
+                delta_depth,
             },
         ))
     };
@@ -319,6 +332,7 @@ fn human_output(
                 missing_objects,
                 objects_copied_from_pack,
                 ref_delta_objects,
+                delta_compressed_objects,
             },
     }: Statistics,
     mut out: impl std::io::Write,
@@ -339,10 +353,11 @@ fn human_output(
     #[rustfmt::skip]
     writeln!(
         out,
-        "\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}",
+        "\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}",
         "decoded and recompressed", decoded_and_recompressed_objects,
         "pack-to-pack copies", objects_copied_from_pack,
         "ref-delta-objects", ref_delta_objects,
+        "delta-compressed objects", delta_compressed_objects,
         "missing objects", missing_objects,
         width = width
     )?;
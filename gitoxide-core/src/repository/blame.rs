@@ -0,0 +1,258 @@
+use std::{ops::Range, path::PathBuf};
+
+use crate::OutputFormat;
+
+pub struct Options {
+    pub format: OutputFormat,
+    pub output: Output,
+    /// Zero-based ranges of lines to blame, or the whole file if empty.
+    pub ranges: Vec<Range<u32>>,
+    /// Revisions whose changes should be attributed to their parents instead.
+    pub ignore_revs: Vec<String>,
+    /// Files with revisions to ignore, in addition to the ones configured in `blame.ignoreRevsFile`.
+    pub ignore_revs_files: Vec<PathBuf>,
+    /// Print additional information about the blame operation to `err`.
+    pub statistics: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Output {
+    /// One line per blamed line, prefixed with the commit, author and date, like `git blame` does by default.
+    Human,
+    /// The machine-readable format of `git blame --porcelain`.
+    Porcelain,
+    /// The machine-readable format of `git blame --incremental`, which doesn't include the lines themselves.
+    Incremental,
+}
+
+pub(crate) mod function {
+    use std::{
+        collections::{btree_map::Entry, BTreeMap},
+        ffi::OsStr,
+    };
+
+    use anyhow::{bail, Context};
+    use gix::{
+        bstr::{BStr, BString, ByteSlice},
+        ObjectId,
+    };
+
+    use super::{Options, Output};
+    use crate::OutputFormat;
+
+    pub fn blame_file(
+        mut repo: gix::Repository,
+        rev_spec: Option<&OsStr>,
+        file: &OsStr,
+        mut out: impl std::io::Write,
+        mut err: impl std::io::Write,
+        Options {
+            format,
+            output,
+            ranges,
+            ignore_revs,
+            ignore_revs_files,
+            statistics,
+        }: Options,
+    ) -> anyhow::Result<()> {
+        if format != OutputFormat::Human {
+            bail!("Only 'human' format is currently supported");
+        }
+        repo.object_cache_size_if_unset(4 * 1024 * 1024);
+
+        let suspect = match rev_spec {
+            Some(spec) => {
+                let spec = gix::path::os_str_into_bstr(spec)?;
+                repo.rev_parse_single(spec)?
+                    .object()?
+                    .peel_to_kind(gix::object::Kind::Commit)
+                    .with_context(|| format!("'{spec}' must resolve to a commit"))?
+                    .id
+            }
+            None => repo.head_id()?.detach(),
+        };
+        let file_path = gix::path::to_unix_separators_on_windows(gix::path::os_str_into_bstr(file)?).into_owned();
+
+        let mut options = gix::blame::file::Options {
+            ranges,
+            rewrites: Some(gix::diff::Rewrites {
+                copies: None,
+                ..Default::default()
+            }),
+            ignore_revs: repo.blame_ignore_revs(ignore_revs_files)?,
+        };
+        for spec in ignore_revs {
+            options.ignore_revs.insert(
+                repo.rev_parse_single(spec.as_str())?
+                    .object()?
+                    .peel_to_kind(gix::object::Kind::Commit)
+                    .with_context(|| format!("'{spec}' must resolve to a commit"))?
+                    .id,
+            );
+        }
+
+        let outcome = repo.blame_file(file_path.as_ref(), suspect, options)?;
+        let mut commits = BTreeMap::new();
+        for entry in &outcome.entries {
+            if let Entry::Vacant(vacant) = commits.entry(entry.commit_id) {
+                vacant.insert(CommitInfo::new(&repo, entry.commit_id)?);
+            }
+        }
+
+        match output {
+            Output::Human => write_human(&outcome, &commits, &mut out)?,
+            Output::Porcelain | Output::Incremental => write_porcelain(
+                &outcome,
+                &commits,
+                file_path.as_ref(),
+                output == Output::Porcelain,
+                &mut out,
+            )?,
+        }
+
+        if statistics {
+            writeln!(err, "{:#?}", outcome.statistics)?;
+        }
+        Ok(())
+    }
+
+    struct CommitInfo {
+        author: gix::actor::Signature,
+        committer: gix::actor::Signature,
+        summary: BString,
+        is_boundary: bool,
+    }
+
+    impl CommitInfo {
+        fn new(repo: &gix::Repository, id: ObjectId) -> anyhow::Result<Self> {
+            let commit = repo.find_object(id)?.try_into_commit()?;
+            let is_boundary = commit.parent_ids().next().is_none();
+            Ok(CommitInfo {
+                author: commit.author()?.into(),
+                committer: commit.committer()?.into(),
+                summary: commit.message()?.summary().into_owned(),
+                is_boundary,
+            })
+        }
+    }
+
+    fn write_human(
+        outcome: &gix::blame::file::Outcome,
+        commits: &BTreeMap<ObjectId, CommitInfo>,
+        out: &mut dyn std::io::Write,
+    ) -> anyhow::Result<()> {
+        let show_file_names = outcome.entries.iter().any(|entry| entry.source_file_name.is_some());
+        let file_name_width = outcome
+            .entries
+            .iter()
+            .filter_map(|entry| entry.source_file_name.as_ref().map(|name| name.chars().count()))
+            .max()
+            .unwrap_or_default();
+        let author_width = commits
+            .values()
+            .map(|info| info.author.name.chars().count())
+            .max()
+            .unwrap_or_default();
+        let line_number_width = outcome
+            .entries
+            .last()
+            .map_or(1, |entry| entry.range_in_blamed_file.end.to_string().len());
+
+        for (entry, lines) in outcome.entries_with_lines() {
+            let info = &commits[&entry.commit_id];
+            let id = if info.is_boundary {
+                format!("^{}", entry.commit_id.to_hex_with_len(7))
+            } else {
+                entry.commit_id.to_hex_with_len(8).to_string()
+            };
+            let file_name = if show_file_names {
+                let name: &BStr = entry.source_file_name.as_ref().map_or("".into(), |name| name.as_ref());
+                format!(" {:file_name_width$}", name.to_str_lossy())
+            } else {
+                String::new()
+            };
+            for (line_number, line) in (entry.range_in_blamed_file.start + 1..).zip(lines) {
+                write!(
+                    out,
+                    "{id}{file_name} ({:author_width$} {} {line_number:>line_number_width$}) ",
+                    info.author.name.to_str_lossy(),
+                    info.author.time.format(gix::date::time::format::ISO8601),
+                )?;
+                out.write_all(line)?;
+                if !line.ends_with(b"\n") {
+                    writeln!(out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn file_name_of<'a>(entry: &'a gix::blame::file::BlameEntry, file_path: &'a BStr) -> &'a BStr {
+        entry.source_file_name.as_ref().map_or(file_path, |name| name.as_ref())
+    }
+
+    fn write_porcelain(
+        outcome: &gix::blame::file::Outcome,
+        commits: &BTreeMap<ObjectId, CommitInfo>,
+        file_path: &BStr,
+        with_lines: bool,
+        out: &mut dyn std::io::Write,
+    ) -> anyhow::Result<()> {
+        let mut file_names = BTreeMap::<ObjectId, &BStr>::new();
+        let mut has_more_than_one_path = gix::hashtable::HashSet::default();
+        for entry in &outcome.entries {
+            if *file_names
+                .entry(entry.commit_id)
+                .or_insert_with(|| file_name_of(entry, file_path))
+                != file_name_of(entry, file_path)
+            {
+                has_more_than_one_path.insert(entry.commit_id);
+            }
+        }
+
+        let mut seen = gix::hashtable::HashSet::default();
+        for (entry, lines) in outcome.entries_with_lines() {
+            let info = &commits[&entry.commit_id];
+            let source_lines = entry.range_in_source_file.start + 1..;
+            let blamed_lines = entry.range_in_blamed_file.start + 1..;
+            for (idx, ((source_line, blamed_line), line)) in source_lines.zip(blamed_lines).zip(lines).enumerate() {
+                if idx == 0 {
+                    writeln!(out, "{} {source_line} {blamed_line} {}", entry.commit_id, entry.len())?;
+                    let is_first = seen.insert(entry.commit_id);
+                    if is_first {
+                        for (role, signature) in [("author", &info.author), ("committer", &info.committer)] {
+                            let time = signature.time.format(gix::date::time::format::RAW);
+                            let (seconds, tz) = time.split_once(' ').unwrap_or((&time, "+0000"));
+                            writeln!(out, "{role} {}", signature.name)?;
+                            writeln!(out, "{role}-mail <{}>", signature.email)?;
+                            writeln!(out, "{role}-time {seconds}")?;
+                            writeln!(out, "{role}-tz {tz}")?;
+                        }
+                        writeln!(out, "summary {}", info.summary)?;
+                        if info.is_boundary {
+                            writeln!(out, "boundary")?;
+                        }
+                    }
+                    // Like `git`, only repeat the file name if it could be ambiguous, unless it's the incremental format.
+                    if is_first || !with_lines || has_more_than_one_path.contains(&entry.commit_id) {
+                        if let Some((previous_id, previous_path)) = &entry.previous {
+                            writeln!(out, "previous {previous_id} {previous_path}")?;
+                        }
+                        writeln!(out, "filename {}", file_name_of(entry, file_path))?;
+                    }
+                } else if with_lines {
+                    writeln!(out, "{} {source_line} {blamed_line}", entry.commit_id)?;
+                }
+                if !with_lines {
+                    break;
+                }
+                out.write_all(b"\t")?;
+                out.write_all(line)?;
+                if !line.ends_with(b"\n") {
+                    writeln!(out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context};
+use gix::bstr::{BString, ByteSlice};
+
+use crate::OutputFormat;
+
+/// The options for use in [`create()`].
+pub struct Options {
+    pub format: OutputFormat,
+    /// The bundle version to write, or `None` to use the lowest version that can store the objects of the repository.
+    pub version: Option<gix::bundle::Version>,
+    /// Add `HEAD` and all references to the bundle, in addition to `specs`.
+    pub all: bool,
+}
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
+
+/// Write a bundle to `path` with all objects and references selected by `specs`.
+pub fn create<P>(
+    repo: gix::Repository,
+    mut progress: P,
+    path: PathBuf,
+    mut specs: Vec<BString>,
+    mut out: impl std::io::Write,
+    Options { format, version, all }: Options,
+) -> anyhow::Result<()>
+where
+    P: gix::NestedProgress,
+    P::SubProgress: 'static,
+{
+    if format != OutputFormat::Human {
+        bail!("JSON output isn't yet supported for creating bundles.");
+    }
+    if all {
+        if repo.head_id().is_ok() {
+            specs.push("HEAD".into());
+        }
+        for reference in repo.references()?.all()? {
+            let reference = reference.map_err(|err| anyhow::anyhow!("{err}"))?;
+            specs.push(reference.name().as_bstr().to_owned());
+        }
+    }
+
+    let mut file = std::io::BufWriter::new(
+        std::fs::File::create(&path).with_context(|| format!("Could not create bundle at '{}'", path.display()))?,
+    );
+    let outcome = match repo.create_bundle(
+        &specs,
+        &mut file,
+        gix::bundle::create::Options {
+            version,
+            thread_limit: None,
+        },
+        &mut progress,
+        &gix::interrupt::IS_INTERRUPTED,
+    ) {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            drop(file);
+            std::fs::remove_file(&path).ok();
+            return Err(err.into());
+        }
+    };
+    writeln!(
+        out,
+        "wrote {} objects and {} references into \"{}\"",
+        outcome.num_objects,
+        outcome.header.refs.len(),
+        path.display()
+    )?;
+    if !outcome.header.prerequisites.is_empty() {
+        writeln!(
+            out,
+            "the bundle requires {} commits to be present",
+            outcome.header.prerequisites.len()
+        )?;
+    }
+    Ok(())
+}
+
+/// Check if the bundle at `path` can be fetched from in `repo`.
+pub fn verify(repo: gix::Repository, path: PathBuf, mut out: impl std::io::Write) -> anyhow::Result<()> {
+    let bundle = gix::bundle::File::at(path)?;
+    repo.verify_bundle(&bundle.header)?;
+    let header = &bundle.header;
+    if !header.refs.is_empty() {
+        writeln!(out, "The bundle contains {} references:", header.refs.len())?;
+        for r in &header.refs {
+            writeln!(out, "{} {}", r.id, r.name)?;
+        }
+    }
+    if header.prerequisites.is_empty() {
+        writeln!(out, "The bundle records a complete history.")?;
+    } else {
+        writeln!(out, "The bundle requires these {} commits:", header.prerequisites.len())?;
+        for prerequisite in &header.prerequisites {
+            writeln!(out, "{} {}", prerequisite.id, prerequisite.comment)?;
+        }
+    }
+    writeln!(out, "{} is okay", bundle.path.display())?;
+    Ok(())
+}
+
+/// List the references in the bundle at `path`, optionally limited to those whose name ends with one of `names`.
+pub fn list_heads(
+    path: PathBuf,
+    names: Vec<BString>,
+    format: OutputFormat,
+    mut out: impl std::io::Write,
+) -> anyhow::Result<()> {
+    let bundle = gix::bundle::File::at(path)?;
+    let refs = bundle
+        .header
+        .refs
+        .iter()
+        .filter(|r| names.is_empty() || names.iter().any(|name| r.name.ends_with(name)));
+    match format {
+        OutputFormat::Human => {
+            for r in refs {
+                writeln!(out, "{} {}", r.id, r.name)?;
+            }
+        }
+        #[cfg(feature = "serde")]
+        OutputFormat::Json => {
+            #[derive(serde::Serialize)]
+            struct JsonRef {
+                id: String,
+                name: String,
+            }
+            serde_json::to_writer_pretty(
+                out,
+                &refs
+                    .map(|r| JsonRef {
+                        id: r.id.to_string(),
+                        name: r.name.to_str_lossy().into_owned(),
+                    })
+                    .collect::<Vec<_>>(),
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -46,7 +46,9 @@ pub(crate) mod function {
         let directory = directory.map_or_else(
             || {
                 let path = gix::path::from_bstr(Cow::Borrowed(url.path.as_ref()));
-                if !bare && path.extension() == Some(OsStr::new("git")) {
+                if (!bare && path.extension() == Some(OsStr::new("git")))
+                    || path.extension() == Some(OsStr::new("bundle"))
+                {
                     path.file_stem().map(Into::into)
                 } else {
                     path.file_name().map(Into::into)
@@ -0,0 +1,94 @@
+use crate::OutputFormat;
+
+/// The options for use in [`write()`][function::write()].
+pub struct Options {
+    /// Write a new file on top of a chain of commit-graph files instead of rewriting a single file with all commits.
+    pub split: bool,
+    /// Merge the top of the chain into the new file if it doesn't have more than this multiple of its commits.
+    pub size_multiple: Option<u32>,
+    /// Merge the top of the chain into the new file as long as it has more than this amount of commits.
+    pub max_commits: Option<u32>,
+    /// Compute and write changed-path Bloom filters.
+    pub changed_paths: bool,
+    /// If `false`, don't write corrected commit dates.
+    pub generation_data: bool,
+    pub format: OutputFormat,
+}
+
+pub(crate) mod function {
+    use std::ffi::OsString;
+
+    use anyhow::{bail, Context};
+    use gix::commitgraph::{bloom, write};
+
+    use super::Options;
+    use crate::OutputFormat;
+
+    pub fn write(
+        repo: gix::Repository,
+        specs: Vec<OsString>,
+        mut out: impl std::io::Write,
+        Options {
+            split,
+            size_multiple,
+            max_commits,
+            changed_paths,
+            generation_data,
+            format,
+        }: Options,
+    ) -> anyhow::Result<()> {
+        if format != OutputFormat::Human {
+            bail!("Only human output is currently supported");
+        }
+        let mut tips = Vec::new();
+        if specs.is_empty() {
+            for reference in repo.references()?.all()? {
+                let mut reference = reference.map_err(|err| anyhow::anyhow!(err))?;
+                let id = reference.peel_to_id_in_place()?;
+                if let Ok(commit) = id.object()?.peel_to_kind(gix::object::Kind::Commit) {
+                    tips.push(commit.id);
+                }
+            }
+        } else {
+            for spec in specs {
+                let spec = gix::path::os_str_into_bstr(&spec)?;
+                let id = repo
+                    .rev_parse_single(spec)
+                    .with_context(|| format!("Could not resolve '{spec}' to a single revision"))?;
+                tips.push(
+                    id.object()?
+                        .peel_to_kind(gix::object::Kind::Commit)
+                        .with_context(|| format!("'{spec}' doesn't point to a commit"))?
+                        .id,
+                );
+            }
+        }
+
+        let outcome = repo.write_commit_graph(
+            tips,
+            write::Options {
+                object_hash: repo.object_hash(),
+                generation_data,
+                changed_paths: changed_paths.then(bloom::Settings::default),
+                split: split.then(|| {
+                    let defaults = write::Split::default();
+                    write::Split {
+                        size_multiple: size_multiple.unwrap_or(defaults.size_multiple),
+                        max_commits: max_commits.or(defaults.max_commits),
+                    }
+                }),
+            },
+        )?;
+        match outcome.path {
+            Some(path) => writeln!(
+                out,
+                "Wrote {} commits to {} ({} file(s) in total)",
+                outcome.num_commits,
+                path.display(),
+                outcome.num_files
+            )?,
+            None => writeln!(out, "The commit-graph is up to date")?,
+        }
+        Ok(())
+    }
+}
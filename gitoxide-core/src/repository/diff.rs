@@ -0,0 +1,61 @@
+use crate::OutputFormat;
+
+/// The options for use in [`diff()`][function::diff()].
+pub struct Options {
+    pub format: OutputFormat,
+    /// The amount of unchanged lines to show around each change, or `None` to use the default of 3.
+    pub context_lines: Option<u32>,
+    /// Show changed words instead of changed lines.
+    pub word_diff: bool,
+    /// Don't detect renames and copies, even if configured.
+    pub no_renames: bool,
+}
+
+pub(crate) mod function {
+    use anyhow::bail;
+    use gix::{
+        bstr::{BString, ByteSlice},
+        diff::blob::{pipeline::Mode, unified_diff::ContextSize},
+        object::tree::diff::patch,
+    };
+
+    use super::Options;
+    use crate::OutputFormat;
+
+    /// Write a patch with the changes needed to turn the tree of the `old` revision into the tree of the `new` one to `out`,
+    /// like `git diff <old> <new>` would.
+    pub fn diff(
+        repo: gix::Repository,
+        old: BString,
+        new: BString,
+        mut out: impl std::io::Write,
+        Options {
+            format,
+            context_lines,
+            word_diff,
+            no_renames,
+        }: Options,
+    ) -> anyhow::Result<()> {
+        if format != OutputFormat::Human {
+            bail!("JSON output isn't yet supported for diffs.");
+        }
+        let old_tree = repo.rev_parse_single(old.as_bstr())?.object()?.peel_to_tree()?;
+        let new_tree = repo.rev_parse_single(new.as_bstr())?.object()?.peel_to_tree()?;
+        let mut resource_cache =
+            repo.diff_resource_cache(Mode::ToGitUnlessBinaryToTextIsPresent, Default::default())?;
+        let mut changes = old_tree.changes()?;
+        if no_renames {
+            changes.track_rewrites(None);
+        }
+        changes.write_patch_to(
+            &new_tree,
+            &mut resource_cache,
+            &mut out,
+            patch::Options {
+                context_size: context_lines.map(ContextSize::symmetrical).unwrap_or_default(),
+                word_diff,
+            },
+        )?;
+        Ok(())
+    }
+}
@@ -65,8 +65,18 @@ pub(crate) mod function {
             .connect(gix::remote::Direction::Fetch)?
             .prepare_fetch(&mut progress, Default::default())?
             .with_dry_run(dry_run)
+            .with_write_fetch_head(if ref_specs.is_empty() {
+                gix::remote::fetch::WriteFetchHead::Configured
+            } else {
+                gix::remote::fetch::WriteFetchHead::AllForMerge
+            })
             .with_shallow(shallow)
             .receive(&mut progress, &gix::interrupt::IS_INTERRUPTED)?;
+        let auto_gc = if dry_run {
+            None
+        } else {
+            repo.gc_auto(&mut progress, &gix::interrupt::IS_INTERRUPTED)?
+        };
 
         if handshake_info {
             writeln!(out, "Handshake Information")?;
@@ -124,6 +134,15 @@ pub(crate) mod function {
         if dry_run {
             writeln!(out, "DRY-RUN: No ref was updated and no pack was received.").ok();
         }
+        if let Some(outcome) = auto_gc {
+            writeln!(
+                out,
+                "auto-gc: packed {} objects and removed {} packs",
+                outcome.repack.num_objects,
+                outcome.repack.removed_packs.len()
+            )
+            .ok();
+        }
         Ok(())
     }
 
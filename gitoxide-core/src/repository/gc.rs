@@ -0,0 +1,140 @@
+use crate::OutputFormat;
+
+/// The options for use in [`gc()`][function::gc()].
+pub struct Options {
+    pub format: OutputFormat,
+    /// Only perform maintenance if the thresholds of `gc.auto` or `gc.autoPackLimit` are exceeded.
+    pub auto: bool,
+    /// Prune unreachable loose objects older than this date instead of `gc.pruneExpire`.
+    pub prune: Option<String>,
+    /// Don't prune any unreachable object.
+    pub no_prune: bool,
+    /// Write unreachable objects as loose objects instead of putting them into a cruft pack.
+    pub no_cruft: bool,
+    /// Roll up packs geometrically with the given factor instead of repacking everything into one pack.
+    pub geometric: Option<u32>,
+}
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
+
+pub(crate) mod function {
+    use std::time::SystemTime;
+
+    use anyhow::bail;
+    use gix::{
+        bstr::BStr,
+        config::tree::Gc,
+        gc::{repack, Trigger},
+    };
+
+    use super::Options;
+    use crate::OutputFormat;
+
+    pub fn gc<P>(
+        repo: gix::Repository,
+        mut progress: P,
+        mut out: impl std::io::Write,
+        Options {
+            format,
+            auto,
+            prune,
+            no_prune,
+            no_cruft,
+            geometric,
+        }: Options,
+    ) -> anyhow::Result<()>
+    where
+        P: gix::NestedProgress,
+        P::SubProgress: 'static,
+    {
+        if format != OutputFormat::Human {
+            bail!("JSON output isn't yet supported for garbage collection.");
+        }
+        let trigger = if auto {
+            match repo.needs_gc()? {
+                Some(trigger) => Some(trigger),
+                None => {
+                    writeln!(out, "No maintenance needed")?;
+                    return Ok(());
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut options = repo.gc_options()?;
+        if no_prune {
+            options.prune_expire = None;
+        } else if let Some(prune) = prune {
+            options.prune_expire = Gc::PRUNE_EXPIRE.try_into_expiry(BStr::new(&prune).into(), SystemTime::now())?;
+        }
+        let expire = options.prune_expire;
+        options.repack.mode = match (trigger, geometric) {
+            (_, Some(factor)) => repack::Mode::Geometric { factor },
+            (Some(Trigger::TooManyLooseObjects { .. }), None) => repack::Mode::Incremental,
+            (_, None) => repack::Mode::AllIntoOne {
+                unreachable: match options.repack.mode {
+                    repack::Mode::AllIntoOne {
+                        unreachable: repack::Unreachable::Cruft { .. },
+                    } if !no_cruft => repack::Unreachable::Cruft { expire },
+                    _ => repack::Unreachable::Loosen { expire },
+                },
+            },
+        };
+
+        let outcome = repo.gc(options, &mut progress, &gix::interrupt::IS_INTERRUPTED)?;
+        if let Some(pack_refs) = outcome.pack_refs {
+            if pack_refs.compacted_reftables {
+                writeln!(out, "compacted reftables")?;
+            } else {
+                writeln!(out, "packed {} references", pack_refs.num_packed_refs)?;
+            }
+        }
+        writeln!(
+            out,
+            "removed {} entries from {} reflogs",
+            outcome.reflog.num_removed_entries, outcome.reflog.num_reflogs
+        )?;
+        let repack = &outcome.repack;
+        if let Some(pack) = &repack.pack {
+            writeln!(out, "wrote {} objects into \"{}\"", repack.num_objects, pack.display())?;
+        }
+        if let Some(bitmap) = &repack.bitmap {
+            writeln!(out, "wrote bitmap \"{}\"", bitmap.display())?;
+        }
+        if let Some(cruft_pack) = &repack.cruft_pack {
+            writeln!(
+                out,
+                "wrote {} unreachable objects into cruft pack \"{}\"",
+                repack.num_cruft_objects,
+                cruft_pack.display()
+            )?;
+        }
+        if repack.num_loosened_objects != 0 {
+            writeln!(out, "loosened {} unreachable objects", repack.num_loosened_objects)?;
+        }
+        for pack in &repack.removed_packs {
+            writeln!(out, "removed \"{}\"", pack.display())?;
+        }
+        writeln!(
+            out,
+            "removed {} loose objects that are packed now",
+            repack.num_removed_loose_objects
+        )?;
+        if let Some(prune) = outcome.prune {
+            writeln!(
+                out,
+                "pruned {} unreachable loose objects, keeping {} recent ones",
+                prune.num_removed_objects, prune.num_kept_objects
+            )?;
+        }
+        if let Some(commit_graph) = outcome.commit_graph {
+            writeln!(
+                out,
+                "wrote {} commits into the commit-graph, which now consists of {} files",
+                commit_graph.num_commits, commit_graph.num_files
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -19,13 +19,18 @@ pub enum PathsOrPatterns {
 
 #[cfg(feature = "archive")]
 pub mod archive;
+pub mod blame;
+pub use blame::function::blame_file;
 pub mod commit;
 pub mod config;
 mod credential;
 pub use credential::function as credential;
 pub mod attributes;
+pub mod bundle;
 #[cfg(feature = "clean")]
 pub mod clean;
+pub mod diff;
+pub use diff::function::diff;
 pub mod dirty;
 #[cfg(feature = "clean")]
 pub use clean::function::clean;
@@ -42,11 +47,16 @@ pub use fetch::function::fetch;
 pub mod commitgraph;
 mod fsck;
 pub use fsck::function as fsck;
+pub mod gc;
+pub use gc::function::gc;
 pub mod index;
 pub mod mailmap;
 pub mod odb;
 pub mod remote;
 pub mod revision;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod sparse_checkout;
 pub mod status;
 pub mod submodule;
 pub mod tree;
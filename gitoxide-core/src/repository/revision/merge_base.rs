@@ -0,0 +1,89 @@
+use crate::OutputFormat;
+
+pub struct Options {
+    pub format: OutputFormat,
+    /// Print all merge-bases, not only the best one.
+    pub all: bool,
+    pub mode: Mode,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Mode {
+    /// Find the merge-bases between the first commit and all others, as if these were merged.
+    Default,
+    /// Find the merge-bases of all commits, as needed for an octopus merge.
+    Octopus,
+    /// Print all commits that can't be reached by any other commit.
+    Independent,
+    /// Fail if the first commit isn't an ancestor of the second one.
+    IsAncestor,
+}
+
+pub(crate) mod function {
+    use std::ffi::OsString;
+
+    use anyhow::{bail, Context};
+    use gix::revision::plumbing;
+
+    use super::{Mode, Options};
+    use crate::OutputFormat;
+
+    pub fn merge_base(
+        mut repo: gix::Repository,
+        specs: Vec<OsString>,
+        mut out: impl std::io::Write,
+        Options { format, all, mode }: Options,
+    ) -> anyhow::Result<()> {
+        if format != OutputFormat::Human {
+            bail!("Only 'human' format is currently supported");
+        }
+        repo.object_cache_size_if_unset(4 * 1024 * 1024);
+
+        let commits = specs
+            .iter()
+            .map(|spec| -> anyhow::Result<_> {
+                let spec = gix::path::os_str_into_bstr(spec)?;
+                Ok(repo
+                    .rev_parse_single(spec)?
+                    .object()?
+                    .peel_to_kind(gix::object::Kind::Commit)
+                    .with_context(|| format!("'{spec}' must resolve to a commit"))?
+                    .id)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut graph = repo.revision_graph();
+        let bases = match mode {
+            Mode::Default => {
+                let Some((first, others)) = commits.split_first().filter(|(_, others)| !others.is_empty()) else {
+                    bail!("Need at least two commits to find a merge-base")
+                };
+                plumbing::merge_base(*first, others, &mut graph)?.unwrap_or_default()
+            }
+            Mode::Octopus => plumbing::merge_base::octopus(&commits, &mut graph)?.unwrap_or_default(),
+            Mode::Independent => {
+                for id in plumbing::merge_base::independent(&commits, &mut graph)? {
+                    writeln!(out, "{id}")?;
+                }
+                return Ok(());
+            }
+            Mode::IsAncestor => {
+                let &[ancestor, descendant] = commits.as_slice() else {
+                    bail!("Need exactly two commits to check if the first is an ancestor of the second")
+                };
+                if !plumbing::merge_base::is_ancestor(ancestor, descendant, &mut graph)? {
+                    bail!("{ancestor} is not an ancestor of {descendant}");
+                }
+                return Ok(());
+            }
+        };
+
+        if bases.is_empty() {
+            bail!("No merge-base found");
+        }
+        for id in bases.iter().take(if all { bases.len() } else { 1 }) {
+            writeln!(out, "{id}")?;
+        }
+        Ok(())
+    }
+}
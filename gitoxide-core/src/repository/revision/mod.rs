@@ -3,6 +3,9 @@ pub use list::function::list;
 mod explain;
 pub use explain::explain;
 
+pub mod merge_base;
+pub use merge_base::function::merge_base;
+
 pub mod resolve;
 pub use resolve::function::resolve;
 
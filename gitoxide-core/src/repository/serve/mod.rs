@@ -0,0 +1,36 @@
+/// The protocol version a client asked for.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum Protocol {
+    /// The original protocol, which is used if nothing else was requested.
+    #[default]
+    V0,
+    /// Like `V0`, but with a version line before the reference advertisement.
+    V1,
+    /// The command-based protocol.
+    V2,
+}
+
+impl Protocol {
+    /// Parse the value of the `GIT_PROTOCOL` environment variable, a colon-separated list of `key=value` pairs,
+    /// and return the highest version we support.
+    pub fn from_env_value(value: &str) -> Self {
+        value
+            .split(':')
+            .filter_map(|param| match param {
+                "version=2" => Some(Protocol::V2),
+                "version=1" => Some(Protocol::V1),
+                _ => None,
+            })
+            .fold(Protocol::V0, |acc, version| match (acc, version) {
+                (Protocol::V2, _) | (_, Protocol::V2) => Protocol::V2,
+                (Protocol::V1, _) | (_, Protocol::V1) => Protocol::V1,
+                _ => Protocol::V0,
+            })
+    }
+}
+
+pub mod upload_pack;
+pub use upload_pack::function::upload_pack;
+
+pub mod receive_pack;
+pub use receive_pack::function::receive_pack;
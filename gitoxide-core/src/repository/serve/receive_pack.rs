@@ -0,0 +1,500 @@
+use super::Protocol;
+
+pub struct Options {
+    /// The protocol version the client asked for.
+    pub protocol: Protocol,
+    /// Only advertise references and exit, as used by the first request of stateless clients.
+    pub advertise_refs: bool,
+    /// Handle a single request without advertising references first, as used by stateless clients like the HTTP transport.
+    pub stateless_rpc: bool,
+}
+
+pub(crate) mod function {
+    use std::{
+        io::{BufRead, Write},
+        path::PathBuf,
+        process::Stdio,
+    };
+
+    use anyhow::{bail, Context};
+    use gix::{
+        bstr::{BString, ByteSlice},
+        config::tree::Key,
+        hashtable::HashSet,
+        interrupt,
+        odb::pack,
+        refs::{
+            transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+            Target,
+        },
+        ObjectId,
+    };
+    use gix_protocol::server::{
+        self,
+        packetline::{encode, PacketLineRef, StreamingPeekableIter},
+        receive_pack::{read_push_options, write_report_status, Command, RefStatus, Request},
+        Ref,
+    };
+
+    use super::{Options, Protocol};
+
+    /// Accept a push of a client into `repo`, reading its commands and the pack from `input` and writing our responses to `out`.
+    ///
+    /// The output of hooks is written to `err` unless the client can receive it as progress messages.
+    pub fn receive_pack(
+        repo: gix::Repository,
+        input: impl BufRead,
+        mut out: impl Write,
+        mut err: impl Write,
+        Options {
+            protocol,
+            advertise_refs,
+            stateless_rpc,
+        }: Options,
+    ) -> anyhow::Result<()> {
+        if advertise_refs || !stateless_rpc {
+            if protocol == Protocol::V1 {
+                encode::text_to_write(b"version 1", &mut out)?;
+            }
+            server::write_v1_advertisement(
+                &mut out,
+                &advertised_refs(&repo)?,
+                &capabilities(&repo),
+                repo.object_hash(),
+            )?;
+            out.flush()?;
+        }
+        if advertise_refs {
+            return Ok(());
+        }
+
+        let mut lines = StreamingPeekableIter::new(input, &[PacketLineRef::Flush], false);
+        let Some(request) = Request::read_from(&mut lines)? else {
+            return Ok(());
+        };
+        let push_options = if request.push_options {
+            read_push_options(&mut lines)?
+        } else {
+            Vec::new()
+        };
+        let mut input = lines.into_inner();
+        let unpack = if request.needs_pack() {
+            write_pack(&repo, &mut input).map_err(|err| BString::from(format!("{err:#}")))
+        } else {
+            Ok(())
+        };
+
+        let mut rejections = check_commands(&repo, &request.commands, unpack.is_ok())?;
+        let mut messages = Messages {
+            out: &mut out,
+            err: &mut err,
+            sideband: request.sideband,
+        };
+        let hooks = Hooks::new(&repo, &push_options);
+        let accepted = |rejections: &[Option<BString>]| -> Vec<Command> {
+            request
+                .commands
+                .iter()
+                .zip(rejections)
+                .filter(|(_, rejection)| rejection.is_none())
+                .map(|(command, _)| command.clone())
+                .collect()
+        };
+
+        let candidates = accepted(&rejections);
+        if !candidates.is_empty() && !hooks.run("pre-receive", &[], &candidates, &mut messages)? {
+            reject_remaining(&mut rejections, "pre-receive hook declined");
+        }
+        for (command, rejection) in request.commands.iter().zip(rejections.iter_mut()) {
+            if rejection.is_some() {
+                continue;
+            }
+            let args = [
+                command.ref_name.to_str_lossy().into_owned(),
+                command.old_id.to_string(),
+                command.new_id.to_string(),
+            ];
+            if !hooks.run("update", &args, &[], &mut messages)? {
+                *rejection = Some("hook declined".into());
+            }
+        }
+        update_refs(&repo, &request, &mut rejections);
+
+        if request.report_status.is_some() {
+            let status: Vec<_> = request
+                .commands
+                .iter()
+                .zip(&rejections)
+                .map(|(command, rejection)| RefStatus {
+                    ref_name: command.ref_name.clone(),
+                    rejection: rejection.clone(),
+                })
+                .collect();
+            let unpack = unpack.as_ref().map(|_| ()).map_err(|err| err.as_bstr());
+            if request.sideband {
+                write_report_status(server::SidebandWriter::new(&mut *messages.out), unpack, &status)?;
+            } else {
+                write_report_status(&mut *messages.out, unpack, &status)?;
+            }
+        }
+        let updated = accepted(&rejections);
+        if !updated.is_empty() {
+            hooks.run("post-receive", &[], &updated, &mut messages)?;
+        }
+        if request.sideband {
+            encode::flush_to_write(&mut *messages.out)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    fn capabilities(repo: &gix::Repository) -> Vec<gix_protocol::command::Feature> {
+        let config = repo.config_snapshot();
+        let mut out: Vec<gix_protocol::command::Feature> = vec![
+            ("report-status", None),
+            ("report-status-v2", None),
+            ("delete-refs", None),
+            ("side-band-64k", None),
+            ("quiet", None),
+        ];
+        if config.boolean("receive.advertiseAtomic").unwrap_or(true) {
+            out.push(("atomic", None));
+        }
+        out.push(("ofs-delta", None));
+        if config.boolean("receive.advertisePushOptions").unwrap_or(false) {
+            out.push(("push-options", None));
+        }
+        out.push((
+            "object-format",
+            Some(repo.object_hash().to_string().to_lowercase().into()),
+        ));
+        out.push(("agent", Some(gix_protocol::agent(gix::env::agent()).into())));
+        out
+    }
+
+    /// Collect all references that point to an object, which are the ones a client may want to update.
+    fn advertised_refs(repo: &gix::Repository) -> anyhow::Result<Vec<Ref>> {
+        let mut out = Vec::new();
+        for reference in repo.references()?.all()? {
+            let reference = reference.map_err(|err| anyhow::anyhow!(err))?;
+            if let gix::refs::TargetRef::Peeled(id) = reference.target() {
+                out.push(Ref {
+                    name: reference.name().as_bstr().to_owned(),
+                    target: Some(id.to_owned()),
+                    peeled: None,
+                    symref_target: None,
+                });
+            }
+        }
+        Ok(out)
+    }
+
+    /// Index the pack sent by the client and place it into the object database of `repo`.
+    fn write_pack(repo: &gix::Repository, input: &mut dyn BufRead) -> anyhow::Result<()> {
+        pack::Bundle::write_to_directory(
+            input,
+            Some(&repo.objects.store_ref().path().join("pack")),
+            &mut gix::progress::Discard,
+            &interrupt::IS_INTERRUPTED,
+            Some(repo.objects.clone()),
+            pack::bundle::write::Options {
+                thread_limit: None,
+                iteration_mode: pack::data::input::Mode::Verify,
+                index_version: Default::default(),
+                object_hash: repo.object_hash(),
+                write_reverse_index: repo
+                    .config_snapshot()
+                    .boolean(gix::config::tree::Pack::WRITE_REVERSE_INDEX.logical_name().as_str())
+                    .unwrap_or(false),
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Validate each command, and return the reason for rejecting it, or `None` if it may be performed.
+    fn check_commands(
+        repo: &gix::Repository,
+        commands: &[Command],
+        unpacked: bool,
+    ) -> anyhow::Result<Vec<Option<BString>>> {
+        let config = repo.config_snapshot();
+        let deny_deletes = config.boolean("receive.denyDeletes").unwrap_or(false);
+        let deny_non_fast_forwards = config.boolean("receive.denyNonFastForwards").unwrap_or(false);
+        let is_denied = |key: &str| {
+            !repo.is_bare()
+                && match config.string(key) {
+                    None => true,
+                    Some(value) => !matches!(value.as_bytes(), b"ignore" | b"warn" | b"false"),
+                }
+        };
+        let deny_current_branch = is_denied("receive.denyCurrentBranch");
+        let deny_delete_current = is_denied("receive.denyDeleteCurrent");
+        let head_name = repo.head_name()?;
+
+        let mut known_commits = None;
+        let mut out = Vec::with_capacity(commands.len());
+        for command in commands {
+            let is_current_branch = matches!(&head_name, Some(name) if name.as_bstr() == command.ref_name);
+            let rejection = if !command.ref_name.starts_with(b"refs/")
+                || gix::refs::FullName::try_from(command.ref_name.clone()).is_err()
+            {
+                Some("funny refname")
+            } else if !unpacked {
+                Some("unpacker error")
+            } else if command.is_delete() {
+                if deny_deletes {
+                    Some("deletion prohibited")
+                } else if is_current_branch && deny_delete_current {
+                    Some("deletion of the current branch prohibited")
+                } else {
+                    None
+                }
+            } else if is_current_branch && deny_current_branch {
+                Some("branch is currently checked out")
+            } else {
+                let known_commits = match known_commits.as_ref() {
+                    Some(known) => known,
+                    None => known_commits.insert(commits_reachable_from_refs(repo)?),
+                };
+                if !is_connected(repo, command.new_id, known_commits) {
+                    Some("missing necessary objects")
+                } else if deny_non_fast_forwards
+                    && !command.is_create()
+                    && !is_fast_forward(repo, command.old_id, command.new_id)
+                {
+                    Some("non-fast-forward")
+                } else {
+                    None
+                }
+            };
+            out.push(rejection.map(Into::into));
+        }
+        Ok(out)
+    }
+
+    fn commits_reachable_from_refs(repo: &gix::Repository) -> anyhow::Result<HashSet<ObjectId>> {
+        let mut tips = Vec::new();
+        for reference in repo.references()?.all()? {
+            let mut reference = reference.map_err(|err| anyhow::anyhow!(err))?;
+            let Some(object) = reference
+                .peel_to_id_in_place()
+                .ok()
+                .and_then(|id| id.object().ok())
+                .and_then(|object| object.peel_tags_to_end().ok())
+            else {
+                continue;
+            };
+            if object.kind == gix::object::Kind::Commit {
+                tips.push(object.id);
+            }
+        }
+        Ok(repo
+            .rev_walk(tips)
+            .all()?
+            .filter_map(Result::ok)
+            .map(|info| info.id)
+            .collect())
+    }
+
+    /// Return `true` if `id` and all objects reachable from it are present, assuming that everything reachable from
+    /// `known_commits` is complete.
+    fn is_connected(repo: &gix::Repository, id: ObjectId, known_commits: &HashSet<ObjectId>) -> bool {
+        let Ok(object) = repo.find_object(id) else {
+            return false;
+        };
+        let Ok(object) = object.peel_tags_to_end() else {
+            return false;
+        };
+        if object.kind != gix::object::Kind::Commit {
+            return true;
+        }
+        let Ok(walk) = repo
+            .rev_walk(Some(object.id))
+            .selected(|id| !known_commits.contains(id))
+        else {
+            return false;
+        };
+        let mut is_complete = true;
+        let mut connectivity = gix_fsck::Connectivity::new(&repo.objects, |_, _| is_complete = false);
+        for info in walk {
+            let Ok(info) = info else {
+                return false;
+            };
+            if known_commits.contains(&info.id) {
+                continue;
+            }
+            if connectivity.check_commit(&info.id).is_err() {
+                return false;
+            }
+        }
+        drop(connectivity);
+        is_complete
+    }
+
+    fn is_fast_forward(repo: &gix::Repository, old: ObjectId, new: ObjectId) -> bool {
+        match repo.rev_walk(Some(new)).all() {
+            Ok(mut walk) => walk.any(|info| matches!(info, Ok(info) if info.id == old)),
+            Err(_) => false,
+        }
+    }
+
+    fn reject_remaining(rejections: &mut [Option<BString>], reason: &str) {
+        for rejection in rejections.iter_mut().filter(|r| r.is_none()) {
+            *rejection = Some(reason.into());
+        }
+    }
+
+    /// Perform all commands that weren't rejected yet, and record the rejection of those that couldn't be performed.
+    fn update_refs(repo: &gix::Repository, request: &Request, rejections: &mut [Option<BString>]) {
+        let edits: Vec<_> = request
+            .commands
+            .iter()
+            .zip(rejections.iter())
+            .map(|(command, rejection)| rejection.is_none().then(|| to_edit(command)).flatten())
+            .collect();
+        if request.atomic {
+            if rejections.iter().any(Option::is_some) {
+                reject_remaining(rejections, "atomic push failure");
+            } else if repo.edit_references(edits.into_iter().flatten()).is_err() {
+                reject_remaining(rejections, "failed to update ref");
+            }
+            return;
+        }
+        for (edit, rejection) in edits.into_iter().zip(rejections.iter_mut()) {
+            let Some(edit) = edit else { continue };
+            if repo.edit_reference(edit).is_err() {
+                *rejection = Some("failed to update ref".into());
+            }
+        }
+    }
+
+    fn to_edit(command: &Command) -> Option<RefEdit> {
+        let expected = if command.is_create() {
+            PreviousValue::MustNotExist
+        } else {
+            PreviousValue::MustExistAndMatch(Target::Peeled(command.old_id))
+        };
+        let change = if command.is_delete() {
+            Change::Delete {
+                expected,
+                log: RefLog::AndReference,
+            }
+        } else {
+            Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: "push".into(),
+                },
+                expected,
+                new: Target::Peeled(command.new_id),
+            }
+        };
+        Some(RefEdit {
+            change,
+            name: command.ref_name.clone().try_into().ok()?,
+            deref: false,
+        })
+    }
+
+    /// Where to send messages meant for the user, like the output of hooks.
+    struct Messages<'a, O, E> {
+        out: &'a mut O,
+        err: &'a mut E,
+        sideband: bool,
+    }
+
+    impl<O: Write, E: Write> Messages<'_, O, E> {
+        fn send(&mut self, message: &[u8]) -> std::io::Result<()> {
+            if message.is_empty() {
+                return Ok(());
+            }
+            if self.sideband {
+                server::SidebandWriter::new(&mut *self.out).progress(message)
+            } else {
+                self.err.write_all(message)
+            }
+        }
+    }
+
+    /// The hooks that are run while receiving a push.
+    struct Hooks {
+        directory: PathBuf,
+        working_directory: PathBuf,
+        git_dir: PathBuf,
+        push_options: Vec<BString>,
+    }
+
+    impl Hooks {
+        fn new(repo: &gix::Repository, push_options: &[BString]) -> Self {
+            let directory = repo
+                .config_snapshot()
+                .trusted_path("core.hooksPath")
+                .and_then(Result::ok)
+                .map_or_else(|| repo.git_dir().join("hooks"), std::borrow::Cow::into_owned);
+            let git_dir = gix::path::realpath(repo.git_dir()).unwrap_or_else(|_| repo.git_dir().to_owned());
+            Hooks {
+                working_directory: repo.work_dir().map_or_else(|| git_dir.clone(), ToOwned::to_owned),
+                directory,
+                git_dir,
+                push_options: push_options.to_vec(),
+            }
+        }
+
+        /// Run the hook called `name` with `args` and `commands` passed on stdin, and forward its output as message.
+        /// Return `true` if it succeeded or doesn't exist.
+        fn run<O: Write, E: Write>(
+            &self,
+            name: &str,
+            args: &[String],
+            commands: &[Command],
+            messages: &mut Messages<'_, O, E>,
+        ) -> anyhow::Result<bool> {
+            let path = self.directory.join(name);
+            if !is_executable(&path) {
+                return Ok(true);
+            }
+            let mut cmd = std::process::Command::new(&path);
+            cmd.args(args)
+                .current_dir(&self.working_directory)
+                .env("GIT_DIR", &self.git_dir)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+            if !self.push_options.is_empty() {
+                cmd.env("GIT_PUSH_OPTION_COUNT", self.push_options.len().to_string());
+                for (idx, option) in self.push_options.iter().enumerate() {
+                    cmd.env(format!("GIT_PUSH_OPTION_{idx}"), option.to_str_lossy().into_owned());
+                }
+            }
+            let mut child = cmd.spawn().with_context(|| format!("Could not run the {name} hook"))?;
+            let mut stdin = child.stdin.take().expect("configured");
+            let mut input = Vec::new();
+            for command in commands {
+                writeln!(input, "{} {} {}", command.old_id, command.new_id, command.ref_name)?;
+            }
+            let writer = std::thread::spawn(move || stdin.write_all(&input));
+            let output = child.wait_with_output()?;
+            if let Ok(Err(err)) = writer.join() {
+                if err.kind() != std::io::ErrorKind::BrokenPipe {
+                    bail!(err);
+                }
+            }
+            messages.send(&output.stdout)?;
+            messages.send(&output.stderr)?;
+            Ok(output.status.success())
+        }
+    }
+
+    fn is_executable(path: &std::path::Path) -> bool {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            matches!(path.metadata(), Ok(meta) if meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        }
+        #[cfg(not(unix))]
+        {
+            path.is_file()
+        }
+    }
+}
@@ -0,0 +1,683 @@
+use super::Protocol;
+
+pub struct Options {
+    /// The protocol version the client asked for.
+    pub protocol: Protocol,
+    /// Only advertise references (or capabilities) and exit, as used by the first request of stateless clients.
+    pub advertise_refs: bool,
+    /// Handle a single request without advertising references first, as used by stateless clients like the HTTP transport.
+    pub stateless_rpc: bool,
+}
+
+pub(crate) mod function {
+    use std::{
+        collections::VecDeque,
+        io::{Read, Write},
+        sync::atomic::Ordering,
+    };
+
+    use anyhow::{bail, Context};
+    use gix::{bstr::ByteSlice, hashtable::HashSet, interrupt, odb::pack, parallel::InOrderIter, ObjectId};
+    use gix_protocol::server::{
+        self, ls_refs,
+        packetline::{encode, PacketLineRef, StreamingPeekableIter},
+        upload_pack::{self as proto, Acknowledgement, Arguments, MultiAck},
+        Ref,
+    };
+
+    use super::{Options, Protocol};
+
+    /// Serve a client that wants to fetch from `repo`, reading its requests from `input` and writing our responses to `out`.
+    pub fn upload_pack(
+        repo: gix::Repository,
+        input: impl Read,
+        mut out: impl Write,
+        Options {
+            protocol,
+            advertise_refs,
+            stateless_rpc,
+        }: Options,
+    ) -> anyhow::Result<()> {
+        let refs = advertised_refs(&repo)?;
+        let mut lines = StreamingPeekableIter::new(input, &[PacketLineRef::Flush], false);
+        if protocol == Protocol::V2 {
+            if advertise_refs || !stateless_rpc {
+                server::write_v2_capabilities(&mut out, &v2_capabilities(&repo))?;
+                out.flush()?;
+            }
+            if advertise_refs {
+                return Ok(());
+            }
+            while let Some(command) = server::v2::read_command(&mut lines)? {
+                match command.name.as_bytes() {
+                    b"ls-refs" => {
+                        let arguments = ls_refs::Arguments::from_arguments(&command.arguments)?;
+                        ls_refs::write_response(&mut out, &refs, &arguments)?;
+                    }
+                    b"fetch" => {
+                        let arguments = Arguments::from_v2_arguments(&command.arguments)?;
+                        fetch_v2(&repo, &refs, arguments, &mut out)?;
+                    }
+                    _ => bail!("Unknown command: {:?}", command.name),
+                }
+                out.flush()?;
+                if stateless_rpc {
+                    break;
+                }
+            }
+            return Ok(());
+        }
+
+        if advertise_refs || !stateless_rpc {
+            if protocol == Protocol::V1 {
+                encode::text_to_write(b"version 1", &mut out)?;
+            }
+            server::write_v1_advertisement(&mut out, &refs, &v1_capabilities(&repo, &refs), repo.object_hash())?;
+            out.flush()?;
+        }
+        if advertise_refs {
+            return Ok(());
+        }
+        let Some(arguments) = Arguments::read_v1_request(&mut lines)? else {
+            return Ok(());
+        };
+        for want in &arguments.wants {
+            if !refs.iter().any(|r| r.target == Some(*want) || r.peeled == Some(*want)) {
+                encode::error_to_write(format!("upload-pack: not our ref {want}").as_bytes(), &mut out)?;
+                bail!("The client wanted {want}, which isn't advertised");
+            }
+        }
+        let shallow = shallow_update(&repo, &arguments)?;
+        if let Some(shallow) = &shallow {
+            proto::write_v1_shallow_update(&mut out, &shallow.shallow, &shallow.unshallow)?;
+            out.flush()?;
+        }
+        let Some(common) = negotiate_v1(&repo, &mut lines, &mut out, &arguments, stateless_rpc)? else {
+            return Ok(());
+        };
+        let selection = select_objects(&repo, &refs, &arguments, &common, shallow.as_ref())?;
+        send_pack(
+            &repo,
+            selection,
+            &mut out,
+            arguments.sideband.map(|sideband| sideband.max_line_len()),
+            !arguments.no_progress,
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+
+    fn v1_capabilities(repo: &gix::Repository, refs: &[Ref]) -> Vec<gix_protocol::command::Feature> {
+        let mut out: Vec<gix_protocol::command::Feature> = [
+            "multi_ack",
+            "multi_ack_detailed",
+            "no-done",
+            "side-band",
+            "side-band-64k",
+            "ofs-delta",
+            "shallow",
+            "deepen-since",
+            "deepen-not",
+            "deepen-relative",
+            "no-progress",
+            "include-tag",
+        ]
+        .into_iter()
+        .map(|name| (name, None))
+        .collect();
+        if let Some(head_target) = refs
+            .iter()
+            .find(|r| r.name == "HEAD" && r.target.is_some())
+            .and_then(|r| r.symref_target.as_ref())
+        {
+            out.push(("symref", Some(format!("HEAD:{head_target}").into())));
+        }
+        out.push((
+            "object-format",
+            Some(repo.object_hash().to_string().to_lowercase().into()),
+        ));
+        out.push(("agent", Some(gix_protocol::agent(gix::env::agent()).into())));
+        out
+    }
+
+    fn v2_capabilities(repo: &gix::Repository) -> Vec<gix_protocol::command::Feature> {
+        vec![
+            ("agent", Some(gix_protocol::agent(gix::env::agent()).into())),
+            ("ls-refs", Some("unborn".into())),
+            ("fetch", Some("shallow wait-for-done".into())),
+            (
+                "object-format",
+                Some(repo.object_hash().to_string().to_lowercase().into()),
+            ),
+        ]
+    }
+
+    /// Collect `HEAD` and all references, resolved to the object they point to.
+    fn advertised_refs(repo: &gix::Repository) -> anyhow::Result<Vec<Ref>> {
+        let mut out = Vec::new();
+        let head = repo.head()?;
+        out.push(Ref {
+            name: "HEAD".into(),
+            target: head.id().map(gix::Id::detach),
+            peeled: head.id().map(|id| peeled(id)).transpose()?.flatten(),
+            symref_target: head.referent_name().map(|name| name.as_bstr().to_owned()),
+        });
+        for reference in repo.references()?.all()? {
+            let reference = reference.map_err(|err| anyhow::anyhow!(err))?;
+            let symref_target = match reference.target() {
+                gix::refs::TargetRef::Symbolic(name) => Some(name.as_bstr().to_owned()),
+                gix::refs::TargetRef::Peeled(_) => None,
+            };
+            let mut direct = reference.clone();
+            while let Some(next) = direct.follow() {
+                direct = next?;
+            }
+            let Some(target) = direct.try_id() else { continue };
+            out.push(Ref {
+                name: reference.name().as_bstr().to_owned(),
+                target: Some(target.detach()),
+                peeled: peeled(target)?,
+                symref_target,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Return the object `id` ultimately points to if it's an annotated tag.
+    fn peeled(id: gix::Id<'_>) -> anyhow::Result<Option<ObjectId>> {
+        let object = id.object()?;
+        Ok(if object.kind == gix::object::Kind::Tag {
+            Some(object.peel_tags_to_end()?.id)
+        } else {
+            None
+        })
+    }
+
+    fn fetch_v2(
+        repo: &gix::Repository,
+        refs: &[Ref],
+        arguments: Arguments,
+        out: &mut impl Write,
+    ) -> anyhow::Result<()> {
+        for want in &arguments.wants {
+            if !repo.has_object(want) {
+                encode::error_to_write(format!("upload-pack: not our ref {want}").as_bytes(), &mut *out)?;
+                bail!("The client wanted {want}, which doesn't exist");
+            }
+        }
+        let common: Vec<_> = arguments
+            .haves
+            .iter()
+            .filter(|id| repo.has_object(id))
+            .copied()
+            .collect();
+        if !arguments.done {
+            let ready = !arguments.wait_for_done && ok_to_give_up(repo, &arguments.wants, &common)?;
+            proto::write_acknowledgments(&mut *out, &common, ready)?;
+            if !ready {
+                return Ok(());
+            }
+        }
+        let shallow = shallow_update(repo, &arguments)?;
+        if let Some(shallow) = &shallow {
+            proto::write_shallow_info(&mut *out, &shallow.shallow, &shallow.unshallow)?;
+        }
+        let selection = select_objects(repo, refs, &arguments, &common, shallow.as_ref())?;
+        proto::write_packfile_header(&mut *out)?;
+        send_pack(
+            repo,
+            selection,
+            out,
+            Some(proto::Sideband::Large.max_line_len()),
+            !arguments.no_progress,
+        )
+    }
+
+    /// Receive the haves of the client and acknowledge them until it is done, and return all common objects,
+    /// or `None` if the client hung up as it's stateless and will try again.
+    fn negotiate_v1<R: Read>(
+        repo: &gix::Repository,
+        lines: &mut StreamingPeekableIter<R>,
+        out: &mut impl Write,
+        arguments: &Arguments,
+        stateless_rpc: bool,
+    ) -> anyhow::Result<Option<Vec<ObjectId>>> {
+        let multi_ack = arguments.multi_ack;
+        let mut common = Vec::new();
+        let mut last_common = None;
+        let mut sent_ready = false;
+        loop {
+            let Some(haves) = proto::read_v1_haves(lines)? else {
+                return Ok(None);
+            };
+            let (mut got_common, mut got_other) = (false, false);
+            for id in haves.ids {
+                if repo.has_object(id) {
+                    got_common = true;
+                    last_common = Some(id);
+                    let is_new = !common.contains(&id);
+                    if is_new {
+                        common.push(id);
+                    }
+                    let ack = match multi_ack {
+                        MultiAck::Detailed => Some(Acknowledgement::Common(id)),
+                        MultiAck::Basic => Some(Acknowledgement::Continue(id)),
+                        MultiAck::None => (is_new && common.len() == 1).then_some(Acknowledgement::Ack(id)),
+                    };
+                    if let Some(ack) = ack {
+                        ack.write_to(&mut *out)?;
+                    }
+                } else {
+                    got_other = true;
+                    if multi_ack != MultiAck::None && ok_to_give_up(repo, &arguments.wants, &common)? {
+                        if multi_ack == MultiAck::Detailed {
+                            sent_ready = true;
+                            Acknowledgement::Ready(id).write_to(&mut *out)?;
+                        } else {
+                            Acknowledgement::Continue(id).write_to(&mut *out)?;
+                        }
+                    }
+                }
+            }
+
+            if haves.done {
+                match last_common {
+                    Some(id) if multi_ack != MultiAck::None => Acknowledgement::Ack(id).write_to(&mut *out)?,
+                    Some(_) => {}
+                    None => Acknowledgement::Nak.write_to(&mut *out)?,
+                }
+                return Ok(Some(common));
+            }
+
+            if let Some(id) =
+                last_common.filter(|_| multi_ack == MultiAck::Detailed && got_common && !got_other && !sent_ready)
+            {
+                if ok_to_give_up(repo, &arguments.wants, &common)? {
+                    sent_ready = true;
+                    Acknowledgement::Ready(id).write_to(&mut *out)?;
+                }
+            }
+            if common.is_empty() || multi_ack != MultiAck::None {
+                Acknowledgement::Nak.write_to(&mut *out)?;
+            }
+            if let Some(id) = last_common.filter(|_| arguments.no_done && sent_ready) {
+                Acknowledgement::Ack(id).write_to(&mut *out)?;
+                return Ok(Some(common));
+            }
+            out.flush()?;
+            if stateless_rpc {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Return `true` if each of the `wants` can reach one of the `common` commits, so there is no need to negotiate further.
+    fn ok_to_give_up(repo: &gix::Repository, wants: &[ObjectId], common: &[ObjectId]) -> anyhow::Result<bool> {
+        if common.is_empty() {
+            return Ok(false);
+        }
+        let common_commits: Vec<_> = common.iter().filter_map(|id| find_commit(repo, *id).ok()).collect();
+        let Some(oldest) = common_commits
+            .iter()
+            .filter_map(|commit| commit.time().ok())
+            .map(|time| time.seconds)
+            .min()
+        else {
+            return Ok(false);
+        };
+        let common: HashSet<_> = common_commits.iter().map(|commit| commit.id).collect();
+        for want in commits_of(repo, wants.iter().copied())? {
+            let reachable = walk(
+                repo,
+                Some(want),
+                &HashSet::default(),
+                |commit| !matches!(commit.time(), Ok(time) if time.seconds < oldest),
+            )?;
+            if !reachable.iter().any(|id| common.contains(id)) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// The outcome of deepening or shallowing the history of the client.
+    struct Shallow {
+        /// The commits that are the new shallow boundary of the client.
+        shallow: Vec<ObjectId>,
+        /// The commits that are not shallow anymore.
+        unshallow: Vec<ObjectId>,
+        /// The commits whose parents are not going to be sent.
+        boundary: HashSet<ObjectId>,
+        /// Commits the client doesn't have yet even though it has their children.
+        extra_tips: Vec<ObjectId>,
+        /// The commits at the shallow boundary of the client before the fetch.
+        client_shallow: HashSet<ObjectId>,
+    }
+
+    /// Compute the shallow boundary of the client after it received the pack, or `None` if the client isn't shallow and
+    /// doesn't want to be.
+    fn shallow_update(repo: &gix::Repository, arguments: &Arguments) -> anyhow::Result<Option<Shallow>> {
+        if !arguments.deepens() && arguments.shallow.is_empty() {
+            return Ok(None);
+        }
+        let client_shallow: HashSet<_> = arguments
+            .shallow
+            .iter()
+            .filter(|id| repo.has_object(id))
+            .copied()
+            .collect();
+        let by_rev_list = arguments.deepen_since.is_some() || !arguments.deepen_not.is_empty();
+        let (new_boundary, not_shallow) = match arguments.depth {
+            Some(_) if by_rev_list => {
+                bail!("'deepen' can't be used together with 'deepen-since' or 'deepen-not'")
+            }
+            Some(depth) if arguments.deepen_relative => {
+                shallow_by_depth(repo, client_shallow.iter().copied(), depth.saturating_add(1))?
+            }
+            Some(depth) => shallow_by_depth(repo, commits_of(repo, arguments.wants.iter().copied())?, depth)?,
+            None if by_rev_list => shallow_by_rev_list(repo, arguments)?,
+            None => (Vec::new(), HashSet::default()),
+        };
+
+        let shallow: Vec<_> = new_boundary
+            .iter()
+            .filter(|id| !client_shallow.contains(*id))
+            .copied()
+            .collect();
+        let mut unshallow = Vec::new();
+        let mut extra_tips = Vec::new();
+        for id in arguments.shallow.iter().filter(|id| not_shallow.contains(*id)) {
+            unshallow.push(*id);
+            extra_tips.extend(find_commit(repo, *id)?.parent_ids().map(gix::Id::detach));
+        }
+        let boundary = new_boundary
+            .into_iter()
+            .chain(client_shallow.iter().filter(|id| !unshallow.contains(id)).copied())
+            .collect();
+        Ok(Some(Shallow {
+            shallow,
+            unshallow,
+            boundary,
+            extra_tips,
+            client_shallow,
+        }))
+    }
+
+    /// Return the commits at `depth` from `tips` as boundary, along with all commits in front of them.
+    fn shallow_by_depth(
+        repo: &gix::Repository,
+        tips: impl IntoIterator<Item = ObjectId>,
+        depth: u32,
+    ) -> anyhow::Result<(Vec<ObjectId>, HashSet<ObjectId>)> {
+        let mut boundary = Vec::new();
+        let mut not_shallow = HashSet::default();
+        let mut seen = HashSet::default();
+        let mut queue: VecDeque<_> = tips.into_iter().map(|id| (id, 1)).collect();
+        while let Some((id, current_depth)) = queue.pop_front() {
+            if !seen.insert(id) {
+                continue;
+            }
+            if current_depth >= depth {
+                boundary.push(id);
+                continue;
+            }
+            not_shallow.insert(id);
+            for parent in find_commit(repo, id)?.parent_ids() {
+                queue.push_back((parent.detach(), current_depth + 1));
+            }
+        }
+        Ok((boundary, not_shallow))
+    }
+
+    /// Return the commits whose parents are too old or excluded by reference as boundary, along with all commits in front of them.
+    fn shallow_by_rev_list(
+        repo: &gix::Repository,
+        arguments: &Arguments,
+    ) -> anyhow::Result<(Vec<ObjectId>, HashSet<ObjectId>)> {
+        let mut excluded_tips = Vec::new();
+        for name in &arguments.deepen_not {
+            let mut reference = repo
+                .try_find_reference(name.as_bstr())?
+                .with_context(|| format!("deepen-not is not a reference: {name}"))?;
+            excluded_tips.push(reference.peel_to_id_in_place()?.detach());
+        }
+        let excluded = walk(repo, commits_of(repo, excluded_tips)?, &HashSet::default(), |_| true)?;
+        let mut included = walk(
+            repo,
+            commits_of(repo, arguments.wants.iter().copied())?,
+            &HashSet::default(),
+            |commit| {
+                !excluded.contains(&commit.id)
+                    && match arguments.deepen_since {
+                        None => true,
+                        Some(since) => !matches!(commit.time(), Ok(time) if time.seconds < since),
+                    }
+            },
+        )?;
+        let mut boundary = Vec::new();
+        for id in &included {
+            if find_commit(repo, *id)?
+                .parent_ids()
+                .any(|parent| !included.contains(&parent.detach()))
+            {
+                boundary.push(*id);
+            }
+        }
+        for id in &boundary {
+            included.remove(id);
+        }
+        Ok((boundary, included))
+    }
+
+    /// The objects to put into the pack.
+    struct Selection {
+        /// Commits and tags whose trees only need to be sent where they differ from the ones of their parents.
+        incremental: Vec<ObjectId>,
+        /// Objects that need their whole tree sent, like commits at the shallow boundary.
+        complete: Vec<ObjectId>,
+    }
+
+    fn select_objects(
+        repo: &gix::Repository,
+        refs: &[Ref],
+        arguments: &Arguments,
+        common: &[ObjectId],
+        shallow: Option<&Shallow>,
+    ) -> anyhow::Result<Selection> {
+        let no_boundary = HashSet::default();
+        let (boundary, client_shallow) = shallow.map_or((&no_boundary, &no_boundary), |shallow| {
+            (&shallow.boundary, &shallow.client_shallow)
+        });
+        let known_to_client = walk(repo, commits_of(repo, common.iter().copied())?, client_shallow, |_| {
+            true
+        })?;
+
+        let mut selection = Selection {
+            incremental: Vec::new(),
+            complete: Vec::new(),
+        };
+        let mut tips = Vec::new();
+        for want in &arguments.wants {
+            match repo.find_object(*want)?.kind {
+                gix::object::Kind::Commit => tips.push(*want),
+                gix::object::Kind::Tag => {
+                    selection.complete.push(*want);
+                    if let Some(commit) = commits_of(repo, Some(*want))?.into_iter().next() {
+                        tips.push(commit);
+                    }
+                }
+                gix::object::Kind::Tree | gix::object::Kind::Blob => selection.complete.push(*want),
+            }
+        }
+        if let Some(shallow) = shallow {
+            tips.extend(shallow.extra_tips.iter().copied());
+        }
+        tips.retain(|id| !known_to_client.contains(id));
+        let commits = walk(repo, tips, boundary, |commit| !known_to_client.contains(&commit.id))?;
+        for id in &commits {
+            if boundary.contains(id) {
+                selection.complete.push(*id);
+            } else {
+                selection.incremental.push(*id);
+            }
+        }
+
+        if arguments.include_tag {
+            for r in refs {
+                let (Some(tag), Some(peeled)) = (r.target, r.peeled) else {
+                    continue;
+                };
+                if commits.contains(&peeled) && !arguments.wants.contains(&tag) {
+                    selection.complete.push(tag);
+                }
+            }
+        }
+        Ok(selection)
+    }
+
+    /// Peel all `ids` to the commits they point to, skipping those that don't point to a commit.
+    fn commits_of(repo: &gix::Repository, ids: impl IntoIterator<Item = ObjectId>) -> anyhow::Result<Vec<ObjectId>> {
+        let mut out = Vec::new();
+        for id in ids {
+            let Some(object) = repo.try_find_object(id)? else {
+                continue;
+            };
+            if let Ok(commit) = object.peel_tags_to_end()?.try_into_commit() {
+                out.push(commit.id);
+            }
+        }
+        Ok(out)
+    }
+
+    fn find_commit(repo: &gix::Repository, id: ObjectId) -> anyhow::Result<gix::Commit<'_>> {
+        Ok(repo.find_object(id)?.try_into_commit()?)
+    }
+
+    /// Return all commits reachable from `tips` for which `keep` returns `true`, without traversing past the
+    /// parents of `boundary` commits or commits that aren't kept.
+    fn walk(
+        repo: &gix::Repository,
+        tips: impl IntoIterator<Item = ObjectId>,
+        boundary: &HashSet<ObjectId>,
+        mut keep: impl FnMut(&gix::Commit<'_>) -> bool,
+    ) -> anyhow::Result<HashSet<ObjectId>> {
+        let mut out = HashSet::default();
+        let mut seen = HashSet::default();
+        let mut queue: VecDeque<_> = tips.into_iter().collect();
+        while let Some(id) = queue.pop_front() {
+            if !seen.insert(id) {
+                continue;
+            }
+            if interrupt::is_triggered() {
+                bail!("Interrupted while traversing commits");
+            }
+            let commit = find_commit(repo, id)?;
+            if !keep(&commit) {
+                continue;
+            }
+            out.insert(id);
+            if !boundary.contains(&id) {
+                queue.extend(commit.parent_ids().map(gix::Id::detach));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Create a pack with all `selection` objects and write it to `out`, multiplexed with progress messages if
+    /// `sideband_max_line_len` is set.
+    fn send_pack(
+        repo: &gix::Repository,
+        selection: Selection,
+        out: &mut impl Write,
+        sideband_max_line_len: Option<usize>,
+        send_progress: bool,
+    ) -> anyhow::Result<()> {
+        let Some(max_line_len) = sideband_max_line_len else {
+            let counts = count_objects(repo, selection)?;
+            return write_pack(repo, counts, out);
+        };
+        let mut writer = server::SidebandWriter::with_max_line_len(&mut *out, max_line_len);
+        let res = count_objects(repo, selection).and_then(|counts| {
+            let num_objects = counts.len();
+            if send_progress {
+                writer.progress(format!("Counting objects: {num_objects}, done.\n").as_bytes())?;
+            }
+            write_pack(repo, counts, &mut writer)?;
+            if send_progress {
+                writer.progress(format!("Total {num_objects}\n").as_bytes())?;
+            }
+            Ok(())
+        });
+        if let Err(err) = res {
+            writer.error(format!("{err:#}\n").as_bytes()).ok();
+            return Err(err);
+        }
+        encode::flush_to_write(&mut *out)?;
+        Ok(())
+    }
+
+    fn count_objects(repo: &gix::Repository, selection: Selection) -> anyhow::Result<Vec<pack::data::output::Count>> {
+        use pack::data::output::count::objects::ObjectExpansion;
+        let db = object_database(repo)?;
+        let mut seen = HashSet::default();
+        let mut out = Vec::new();
+        for (ids, expansion) in [
+            (selection.incremental, ObjectExpansion::TreeAdditionsComparedToAncestor),
+            (selection.complete, ObjectExpansion::TreeContents),
+        ] {
+            let (counts, _outcome) = pack::data::output::count::objects_unthreaded(
+                &db,
+                &mut ids
+                    .into_iter()
+                    .map(Ok::<_, Box<dyn std::error::Error + Send + Sync + 'static>>),
+                &gix::progress::Discard,
+                &interrupt::IS_INTERRUPTED,
+                expansion,
+            )?;
+            out.extend(counts.into_iter().filter(|count| seen.insert(count.id)));
+        }
+        Ok(out)
+    }
+
+    fn write_pack(
+        repo: &gix::Repository,
+        counts: Vec<pack::data::output::Count>,
+        out: &mut dyn Write,
+    ) -> anyhow::Result<()> {
+        let num_objects = counts.len();
+        let entries = InOrderIter::from(pack::data::output::entry::iter_from_counts(
+            counts,
+            object_database(repo)?,
+            Box::new(gix::progress::Discard),
+            pack::data::output::entry::iter_from_counts::Options {
+                thread_limit: None,
+                mode: pack::data::output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+                allow_thin_pack: false,
+                chunk_size: 1000,
+                ..Default::default()
+            },
+        ));
+        for written in pack::data::output::bytes::FromEntriesIter::new(
+            entries,
+            out,
+            num_objects as u32,
+            pack::data::Version::default(),
+            repo.object_hash(),
+        ) {
+            written?;
+            if interrupt::IS_INTERRUPTED.load(Ordering::Relaxed) {
+                bail!("Interrupted while sending the pack");
+            }
+        }
+        Ok(())
+    }
+
+    fn object_database(
+        repo: &gix::Repository,
+    ) -> std::io::Result<gix::odb::Cache<gix::odb::store::Handle<std::sync::Arc<gix::odb::Store>>>> {
+        let mut db = repo.objects.clone().into_arc()?;
+        db.prevent_pack_unload();
+        db.ignore_replacements = true;
+        Ok(db)
+    }
+}
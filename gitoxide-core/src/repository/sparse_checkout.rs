@@ -0,0 +1,89 @@
+use anyhow::bail;
+use gix::bstr::BString;
+
+use crate::OutputFormat;
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
+
+/// Enable the sparse checkout with `patterns`, which are directories if `cone` is `true`, and update the worktree accordingly.
+pub fn set<P>(
+    mut repo: gix::Repository,
+    mut progress: P,
+    patterns: Vec<BString>,
+    cone: bool,
+    out: impl std::io::Write,
+    format: OutputFormat,
+) -> anyhow::Result<()>
+where
+    P: gix::NestedProgress,
+    P::SubProgress: 'static,
+{
+    if format != OutputFormat::Human {
+        bail!("JSON output isn't implemented yet");
+    }
+    let outcome = repo.sparse_checkout_set(&patterns, cone, &mut progress, &gix::interrupt::IS_INTERRUPTED)?;
+    print_outcome(outcome, out)
+}
+
+/// Add `patterns` to the current sparse checkout and update the worktree accordingly.
+pub fn add<P>(
+    mut repo: gix::Repository,
+    mut progress: P,
+    patterns: Vec<BString>,
+    out: impl std::io::Write,
+    format: OutputFormat,
+) -> anyhow::Result<()>
+where
+    P: gix::NestedProgress,
+    P::SubProgress: 'static,
+{
+    if format != OutputFormat::Human {
+        bail!("JSON output isn't implemented yet");
+    }
+    let outcome = repo.sparse_checkout_add(&patterns, &mut progress, &gix::interrupt::IS_INTERRUPTED)?;
+    print_outcome(outcome, out)
+}
+
+/// Print the directories of the sparse checkout in cone mode, or its patterns otherwise.
+pub fn list(repo: gix::Repository, mut out: impl std::io::Write, format: OutputFormat) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("JSON output isn't implemented yet");
+    }
+    match repo.sparse_checkout_patterns()? {
+        None => bail!("This worktree isn't sparse"),
+        Some(gix::sparse::Patterns::Cone(cone)) => {
+            for dir in cone.directories() {
+                writeln!(out, "{dir}")?;
+            }
+        }
+        Some(gix::sparse::Patterns::NonCone(_)) => {
+            let path = repo.sparse_checkout_path();
+            match std::fs::read(&path) {
+                Ok(content) => out.write_all(&content)?,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_outcome(outcome: gix::sparse::set::Outcome, mut out: impl std::io::Write) -> anyhow::Result<()> {
+    let worktree = outcome.worktree;
+    if !worktree.modified_paths_kept.is_empty() {
+        writeln!(
+            out,
+            "The following paths are not up to date and were left despite sparse patterns:"
+        )?;
+        for path in &worktree.modified_paths_kept {
+            writeln!(out, "\t{path}")?;
+        }
+    }
+    for err in &worktree.checkout.errors {
+        writeln!(out, "{}: {}", err.path, err.error)?;
+    }
+    for collision in &worktree.checkout.collisions {
+        writeln!(out, "{}: collision ({:?})", collision.path, collision.error_kind)?;
+    }
+    Ok(())
+}
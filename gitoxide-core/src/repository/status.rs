@@ -1,7 +1,10 @@
 use anyhow::bail;
-use gix::bstr::{BStr, BString};
-use gix::status::index_worktree::iter::Item;
+use gix::bstr::{BStr, BString, ByteSlice};
+use gix::status::index_worktree::iter::{Item, RewriteSource};
 use gix_status::index_as_worktree::{Change, Conflict, EntryStatus};
+use gix_status::tree_index;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use crate::OutputFormat;
@@ -26,6 +29,7 @@ pub enum Ignored {
 #[derive(Copy, Clone)]
 pub enum Format {
     Simplified,
+    PorcelainV1,
     PorcelainV2,
 }
 
@@ -60,9 +64,6 @@ pub fn show(
     if output_format != OutputFormat::Human {
         bail!("Only human format is supported right now");
     }
-    if !matches!(format, Format::Simplified) {
-        bail!("Only the simplified format is currently implemented");
-    }
 
     let start = std::time::Instant::now();
     let prefix = repo.prefix()?.unwrap_or(Path::new(""));
@@ -108,79 +109,124 @@ pub fn show(
             }
             None => gix::status::Submodule::AsConfigured { check_dirty: false },
         })
-        .into_index_worktree_iter(pathspecs)?;
+        .into_iter(pathspecs)?;
 
+    let mut porcelain = Porcelain {
+        quote_non_ascii: repo.config_snapshot().boolean("core.quotePath").unwrap_or(true),
+        ..Default::default()
+    };
     for item in iter.by_ref() {
         let item = item?;
-        match item {
-            Item::Modification {
-                entry: _,
-                entry_index: _,
-                rela_path,
-                status,
-            } => print_index_entry_status(&mut out, prefix, rela_path.as_ref(), status)?,
-            Item::DirectoryContents {
-                entry,
-                collapsed_directory_status,
-            } => {
-                if collapsed_directory_status.is_none() {
-                    writeln!(
-                        out,
-                        "{status: >3} {rela_path}{slash}",
-                        status = "?",
-                        rela_path =
-                            gix::path::relativize_with_prefix(&gix::path::from_bstr(entry.rela_path), prefix).display(),
-                        slash = if entry.disk_kind.unwrap_or(gix::dir::entry::Kind::File).is_dir() {
-                            "/"
-                        } else {
-                            ""
-                        }
-                    )?;
-                }
-            }
-            Item::Rewrite {
-                source,
-                dirwalk_entry,
-                copy: _, // TODO: how to visualize copies?
-                ..
-            } => {
-                // TODO: handle multi-status characters, there can also be modifications at the same time as determined by their ID and potentially diffstats.
-                writeln!(
-                    out,
-                    "{status: >3} {source_rela_path} → {dest_rela_path}",
-                    status = "R",
-                    source_rela_path =
-                        gix::path::relativize_with_prefix(&gix::path::from_bstr(source.rela_path()), prefix).display(),
-                    dest_rela_path = gix::path::relativize_with_prefix(
-                        &gix::path::from_bstr(dirwalk_entry.rela_path.as_ref()),
-                        prefix
-                    )
-                    .display(),
-                )?;
-            }
+        match format {
+            Format::Simplified => match item {
+                gix::status::iter::Item::TreeIndex(change) => print_tree_index_change(&mut out, prefix, change)?,
+                gix::status::iter::Item::IndexWorktree(item) => print_index_worktree_item(&mut out, prefix, item)?,
+            },
+            Format::PorcelainV1 | Format::PorcelainV2 => porcelain.record(item),
         }
     }
     if gix::interrupt::is_triggered() {
         bail!("interrupted by user");
     }
 
-    let out = iter.outcome_mut().expect("successful iteration has outcome");
+    let tree_index_outcome = iter.tree_index_outcome().clone();
+    let outcome = iter
+        .index_worktree_outcome_mut()
+        .expect("successful iteration has outcome");
+    match format {
+        Format::Simplified => {}
+        Format::PorcelainV1 => porcelain.write_v1(&mut out)?,
+        Format::PorcelainV2 => porcelain.write_v2(
+            &mut out,
+            &outcome.index,
+            repo.work_dir().unwrap_or(Path::new("")),
+            repo.object_hash(),
+        )?,
+    }
 
-    if out.has_changes() && allow_write {
-        out.write_changes().transpose()?;
+    if outcome.has_changes() && allow_write {
+        outcome.write_changes().transpose()?;
     }
 
     if statistics {
-        writeln!(err, "{outcome:#?}", outcome = out.index_worktree).ok();
+        writeln!(err, "{tree_index_outcome:#?}").ok();
+        writeln!(err, "{outcome:#?}", outcome = outcome.index_worktree).ok();
     }
 
-    writeln!(err, "\nhead -> index isn't implemented yet")?;
-    progress.init(Some(out.index.entries().len()), gix::progress::count("files"));
-    progress.set(out.index.entries().len());
+    progress.init(Some(outcome.index.entries().len()), gix::progress::count("files"));
+    progress.set(outcome.index.entries().len());
     progress.show_throughput(start);
     Ok(())
 }
 
+fn print_tree_index_change(
+    out: &mut dyn std::io::Write,
+    prefix: &Path,
+    change: tree_index::Change,
+) -> std::io::Result<()> {
+    let status = summary_to_char(change.summary()) as char;
+    let location = gix::path::relativize_with_prefix(&gix::path::from_bstr(change.location()), prefix).into_owned();
+    match change {
+        tree_index::Change::Rewrite { source_location, .. } => writeln!(
+            out,
+            "{status: <3} {source_rela_path} → {dest_rela_path}",
+            source_rela_path =
+                gix::path::relativize_with_prefix(&gix::path::from_bstr(source_location.as_bstr()), prefix).display(),
+            dest_rela_path = location.display(),
+        ),
+        _ => writeln!(out, "{status: <3} {}", location.display()),
+    }
+}
+
+fn print_index_worktree_item(out: &mut dyn std::io::Write, prefix: &Path, item: Item) -> std::io::Result<()> {
+    match item {
+        Item::Modification {
+            entry: _,
+            entry_index: _,
+            rela_path,
+            status,
+        } => print_index_entry_status(out, prefix, rela_path.as_ref(), status)?,
+        Item::DirectoryContents {
+            entry,
+            collapsed_directory_status,
+        } => {
+            if collapsed_directory_status.is_none() {
+                writeln!(
+                    out,
+                    "{status: >3} {rela_path}{slash}",
+                    status = "?",
+                    rela_path =
+                        gix::path::relativize_with_prefix(&gix::path::from_bstr(entry.rela_path), prefix).display(),
+                    slash = if entry.disk_kind.unwrap_or(gix::dir::entry::Kind::File).is_dir() {
+                        "/"
+                    } else {
+                        ""
+                    }
+                )?;
+            }
+        }
+        Item::Rewrite {
+            source,
+            dirwalk_entry,
+            copy: _, // TODO: how to visualize copies?
+            ..
+        } => {
+            // TODO: handle multi-status characters, there can also be modifications at the same time as determined by their ID and potentially diffstats.
+            writeln!(
+                out,
+                "{status: >3} {source_rela_path} → {dest_rela_path}",
+                status = "R",
+                source_rela_path =
+                    gix::path::relativize_with_prefix(&gix::path::from_bstr(source.rela_path()), prefix).display(),
+                dest_rela_path =
+                    gix::path::relativize_with_prefix(&gix::path::from_bstr(dirwalk_entry.rela_path.as_ref()), prefix)
+                        .display(),
+            )?;
+        }
+    }
+    Ok(())
+}
+
 fn print_index_entry_status(
     out: &mut dyn std::io::Write,
     prefix: &Path,
@@ -234,3 +280,350 @@ fn change_to_char(change: &Change<(), gix::submodule::Status>) -> u8 {
         }
     }
 }
+
+fn summary_to_char(summary: tree_index::Summary) -> u8 {
+    match summary {
+        tree_index::Summary::Added => b'A',
+        tree_index::Summary::Removed => b'D',
+        tree_index::Summary::Modified => b'M',
+        tree_index::Summary::TypeChange => b'T',
+        tree_index::Summary::Renamed => b'R',
+        tree_index::Summary::Copied => b'C',
+    }
+}
+
+/// Collects all status items so they can be printed in the order and shape of `git status --porcelain`,
+/// which merges staged and unstaged changes of the same path into one line.
+#[derive(Default)]
+struct Porcelain {
+    tracked: BTreeMap<BString, Tracked>,
+    untracked: Vec<(BString, bool)>,
+    ignored: Vec<(BString, bool)>,
+    /// If `true`, paths with non-ASCII characters are quoted, as controlled by `core.quotePath`.
+    quote_non_ascii: bool,
+}
+
+#[derive(Default)]
+struct Tracked {
+    /// The change between the `HEAD` tree and the index.
+    staged: Option<tree_index::Change>,
+    /// The change between the index and the worktree.
+    unstaged: Option<Item>,
+}
+
+impl Porcelain {
+    fn record(&mut self, item: gix::status::iter::Item) {
+        let item = match item {
+            gix::status::iter::Item::TreeIndex(change) => {
+                let rela_path = change.location().to_owned();
+                self.tracked.entry(rela_path).or_default().staged = Some(change);
+                return;
+            }
+            gix::status::iter::Item::IndexWorktree(item) => item,
+        };
+        let rela_path = match &item {
+            Item::Modification {
+                status: EntryStatus::NeedsUpdate(_),
+                ..
+            } => return,
+            Item::Modification { rela_path, .. } => rela_path.clone(),
+            Item::Rewrite { dirwalk_entry, .. } => dirwalk_entry.rela_path.clone(),
+            Item::DirectoryContents {
+                entry,
+                collapsed_directory_status: None,
+            } => {
+                let is_dir = matches!(entry.disk_kind, Some(kind) if kind.is_dir());
+                match entry.status {
+                    gix::dir::entry::Status::Untracked => self.untracked.push((entry.rela_path.clone(), is_dir)),
+                    gix::dir::entry::Status::Ignored(_) => self.ignored.push((entry.rela_path.clone(), is_dir)),
+                    gix::dir::entry::Status::Tracked | gix::dir::entry::Status::Pruned => {}
+                }
+                return;
+            }
+            Item::DirectoryContents { .. } => return,
+        };
+        self.tracked.entry(rela_path).or_default().unstaged = Some(item);
+    }
+
+    fn write_v1(&mut self, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+        let quote = |path: &BStr| quote_path(path, self.quote_non_ascii, true).into_owned();
+        for (rela_path, tracked) in &self.tracked {
+            let (x, y) = tracked.xy(b' ');
+            let (x, y) = (x as char, y as char);
+            let rela_path = quote(rela_path.as_bstr());
+            match tracked.rewrite() {
+                Some((source, _diff, _copy)) => {
+                    writeln!(out, "{x}{y} {source} -> {rela_path}", source = quote(source))?
+                }
+                None => writeln!(out, "{x}{y} {rela_path}")?,
+            }
+        }
+        self.untracked.sort();
+        for (rela_path, is_dir) in &self.untracked {
+            writeln!(out, "?? {}", quote(with_slash(rela_path.as_bstr(), *is_dir).as_ref()))?;
+        }
+        self.ignored.sort();
+        for (rela_path, is_dir) in &self.ignored {
+            writeln!(out, "!! {}", quote(with_slash(rela_path.as_bstr(), *is_dir).as_ref()))?;
+        }
+        Ok(())
+    }
+
+    fn write_v2(
+        &mut self,
+        out: &mut dyn std::io::Write,
+        index: &gix::index::State,
+        workdir: &Path,
+        object_hash: gix::hash::Kind,
+    ) -> std::io::Result<()> {
+        let null = gix::ObjectId::null(object_hash);
+        let quote = |path: &BStr| quote_path(path, self.quote_non_ascii, false).into_owned();
+        for (rela_path, tracked) in &self.tracked {
+            let (x, y) = tracked.xy(b'.');
+            let (x, y) = (x as char, y as char);
+            let worktree_mode = |index_mode: u32| match y {
+                '.' => index_mode,
+                'D' => 0,
+                _ => worktree_mode(&workdir.join(gix::path::from_bstr(rela_path.as_bstr()))).unwrap_or(index_mode),
+            };
+            let quoted_path = quote(rela_path.as_bstr());
+            if tracked.conflict().is_some() {
+                let mut stages = [(0, null); 3];
+                for entry in index
+                    .entry_range(rela_path.as_bstr())
+                    .map(|range| &index.entries()[range])
+                    .unwrap_or_default()
+                {
+                    if let Some(stage) = (entry.stage() as usize)
+                        .checked_sub(1)
+                        .and_then(|idx| stages.get_mut(idx))
+                    {
+                        *stage = (entry.mode.bits(), entry.id);
+                    }
+                }
+                let [(m1, h1), (m2, h2), (m3, h3)] = stages;
+                writeln!(
+                    out,
+                    "u {x}{y} N... {m1:06o} {m2:06o} {m3:06o} {mw:06o} {h1} {h2} {h3} {quoted_path}",
+                    mw = worktree_mode(m2)
+                )?;
+                continue;
+            }
+
+            let ((mode_head, id_head), (mode_index, id_index)) = tracked.head_and_index(null);
+            let sub = tracked.submodule_state(mode_head, mode_index);
+            let mode_worktree = worktree_mode(mode_index);
+            match tracked.rewrite() {
+                Some((source, diff, copy)) => {
+                    let score = diff.map_or(100, |diff| (diff.similarity * 100.0).round() as u32);
+                    writeln!(
+                        out,
+                        "2 {x}{y} {sub} {mode_head:06o} {mode_index:06o} {mode_worktree:06o} {id_head} {id_index} {kind}{score} {quoted_path}\t{source}",
+                        kind = if copy { 'C' } else { 'R' },
+                        source = quote(source)
+                    )?;
+                }
+                None => writeln!(
+                    out,
+                    "1 {x}{y} {sub} {mode_head:06o} {mode_index:06o} {mode_worktree:06o} {id_head} {id_index} {quoted_path}"
+                )?,
+            }
+        }
+        self.untracked.sort();
+        for (rela_path, is_dir) in &self.untracked {
+            writeln!(out, "? {}", quote(with_slash(rela_path.as_bstr(), *is_dir).as_ref()))?;
+        }
+        self.ignored.sort();
+        for (rela_path, is_dir) in &self.ignored {
+            writeln!(out, "! {}", quote(with_slash(rela_path.as_bstr(), *is_dir).as_ref()))?;
+        }
+        Ok(())
+    }
+}
+
+impl Tracked {
+    fn conflict(&self) -> Option<Conflict> {
+        match self.unstaged {
+            Some(Item::Modification {
+                status: EntryStatus::Conflict(conflict),
+                ..
+            }) => Some(conflict),
+            _ => None,
+        }
+    }
+
+    /// Return the status characters for the staged and the unstaged change, using `unchanged` if there is none.
+    fn xy(&self, unchanged: u8) -> (u8, u8) {
+        if let Some(conflict) = self.conflict() {
+            let xy = as_str(conflict).as_bytes();
+            return (xy[0], xy[1]);
+        }
+        let x = self
+            .staged
+            .as_ref()
+            .map_or(unchanged, |change| summary_to_char(change.summary()));
+        let y = match &self.unstaged {
+            Some(Item::Modification { status, .. }) => match status {
+                EntryStatus::Change(Change::Removed) => b'D',
+                EntryStatus::Change(Change::Type) => b'T',
+                EntryStatus::Change(_) => b'M',
+                EntryStatus::IntentToAdd => b'A',
+                EntryStatus::Conflict(_) | EntryStatus::NeedsUpdate(_) => unchanged,
+            },
+            Some(Item::Rewrite { copy, .. }) => {
+                if *copy {
+                    b'C'
+                } else {
+                    b'R'
+                }
+            }
+            Some(Item::DirectoryContents { .. }) | None => unchanged,
+        };
+        (x, y)
+    }
+
+    /// Return the source location of a rewrite, along with its similarity and whether it's a copy.
+    fn rewrite(&self) -> Option<(&BStr, Option<gix::diff::blob::DiffLineStats>, bool)> {
+        match (&self.staged, &self.unstaged) {
+            (
+                Some(tree_index::Change::Rewrite {
+                    source_location,
+                    diff,
+                    copy,
+                    ..
+                }),
+                _,
+            ) => Some((source_location.as_bstr(), *diff, *copy)),
+            (_, Some(Item::Rewrite { source, diff, copy, .. })) => Some((source.rela_path(), *diff, *copy)),
+            _ => None,
+        }
+    }
+
+    /// Return the mode and id in the `HEAD` tree, and the mode and id in the index, using `null` for ids that don't exist.
+    fn head_and_index(&self, null: gix::ObjectId) -> ((u32, gix::ObjectId), (u32, gix::ObjectId)) {
+        match &self.staged {
+            Some(tree_index::Change::Addition { entry_mode, id, .. }) => ((0, null), (entry_mode.0.into(), *id)),
+            Some(tree_index::Change::Deletion { entry_mode, id, .. }) => ((entry_mode.0.into(), *id), (0, null)),
+            Some(tree_index::Change::Modification {
+                previous_entry_mode,
+                previous_id,
+                entry_mode,
+                id,
+                ..
+            }) => ((previous_entry_mode.0.into(), *previous_id), (entry_mode.0.into(), *id)),
+            Some(tree_index::Change::Rewrite {
+                source_entry_mode,
+                source_id,
+                entry_mode,
+                id,
+                ..
+            }) => ((source_entry_mode.0.into(), *source_id), (entry_mode.0.into(), *id)),
+            None => {
+                let unchanged = match &self.unstaged {
+                    Some(Item::Modification {
+                        status: EntryStatus::IntentToAdd,
+                        ..
+                    }) => (0, null),
+                    Some(
+                        Item::Modification { entry, .. }
+                        | Item::Rewrite {
+                            source:
+                                RewriteSource::RewriteFromIndex {
+                                    source_entry: entry, ..
+                                },
+                            ..
+                        },
+                    ) => (entry.mode.bits(), entry.id),
+                    _ => (0, null),
+                };
+                (unchanged, unchanged)
+            }
+        }
+    }
+
+    /// Return the `<sub>` field of porcelain v2 output, which is `N...` for anything but submodules.
+    fn submodule_state(&self, mode_head: u32, mode_index: u32) -> String {
+        const SUBMODULE: u32 = 0o160000;
+        if mode_head != SUBMODULE && mode_index != SUBMODULE {
+            return "N...".into();
+        }
+        let mut state = String::from("S...");
+        if let Some(Item::Modification {
+            status: EntryStatus::Change(Change::SubmoduleModification(status)),
+            ..
+        }) = &self.unstaged
+        {
+            let changes = status.changes.as_deref().unwrap_or_default();
+            let commit_changed = status.checked_out_head_id.is_some() && status.checked_out_head_id != status.index_id;
+            let has_modifications = changes
+                .iter()
+                .any(|change| !matches!(change, Item::DirectoryContents { .. }));
+            let has_untracked = changes.iter().any(|change| {
+                matches!(change, Item::DirectoryContents { entry, .. } if entry.status == gix::dir::entry::Status::Untracked)
+            });
+            state = format!(
+                "S{}{}{}",
+                if commit_changed { 'C' } else { '.' },
+                if has_modifications { 'M' } else { '.' },
+                if has_untracked { 'U' } else { '.' }
+            );
+        }
+        state
+    }
+}
+
+/// Return `rela_path` with a trailing slash if it `is_dir`.
+fn with_slash(rela_path: &BStr, is_dir: bool) -> Cow<'_, BStr> {
+    if is_dir {
+        let mut path = rela_path.to_owned();
+        path.push(b'/');
+        path.into()
+    } else {
+        rela_path.into()
+    }
+}
+
+/// Return `path` quoted and escaped like `git status` does if it contains control characters, quotes or backslashes,
+/// or non-ASCII characters if `quote_non_ascii` is set as controlled by `core.quotePath`.
+/// If `quote_space` is set, paths with spaces are quoted as well, as done in porcelain v1.
+fn quote_path(path: &BStr, quote_non_ascii: bool, quote_space: bool) -> Cow<'_, BStr> {
+    let needs_escape = |b: u8| b < 0x20 || b == b'"' || b == b'\\' || b == 0x7f || (quote_non_ascii && b >= 0x80);
+    if !path.iter().any(|b| needs_escape(*b) || (quote_space && *b == b' ')) {
+        return path.into();
+    }
+    let mut out = BString::from("\"");
+    for &b in path.iter() {
+        match b {
+            b'\x07' => out.extend_from_slice(b"\\a"),
+            b'\x08' => out.extend_from_slice(b"\\b"),
+            b'\t' => out.extend_from_slice(b"\\t"),
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\x0b' => out.extend_from_slice(b"\\v"),
+            b'\x0c' => out.extend_from_slice(b"\\f"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            b'"' | b'\\' => out.extend_from_slice(&[b'\\', b]),
+            b if needs_escape(b) => out.extend_from_slice(format!("\\{b:03o}").as_bytes()),
+            b => out.push(b),
+        }
+    }
+    out.push(b'"');
+    out.into()
+}
+
+/// Return the mode of the file at `path` the way it would be stored in the index, or `None` if it doesn't exist
+/// or is a directory that isn't a repository, which `git` considers removed.
+fn worktree_mode(path: &Path) -> Option<u32> {
+    let meta = std::fs::symlink_metadata(path).ok()?;
+    Some(if meta.is_symlink() {
+        0o120000
+    } else if meta.is_dir() {
+        if !path.join(".git").exists() {
+            return None;
+        }
+        0o160000
+    } else if gix::fs::is_executable(&meta) {
+        0o100755
+    } else {
+        0o100644
+    })
+}
@@ -50,6 +50,30 @@ pub fn decode(data: &[u8]) -> Result<(Vec, &[u8]), decode::Error> {
     ))
 }
 
+mod write {
+    use super::Vec;
+
+    impl Vec {
+        /// Write this bitmap to `out` in the format understood by [`decode()`][super::decode()].
+        pub fn write_to(&self, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+            out.write_all(&self.num_bits.to_be_bytes())?;
+            out.write_all(&(self.bits.len() as u32).to_be_bytes())?;
+            for word in &self.bits {
+                out.write_all(&word.to_be_bytes())?;
+            }
+            out.write_all(&(self.rlw as u32).to_be_bytes())
+        }
+
+        /// The amount of bytes [`write_to()`](Self::write_to()) would produce.
+        pub fn serialized_size(&self) -> usize {
+            let num_bits = 4;
+            let num_words = 4;
+            let rlw = 4;
+            num_bits + num_words + self.bits.len() * std::mem::size_of::<u64>() + rlw
+        }
+    }
+}
+
 mod access {
     use super::Vec;
 
@@ -91,6 +115,73 @@ mod access {
         pub fn num_bits(&self) -> usize {
             self.num_bits.try_into().expect("we are not on 16 bit systems")
         }
+
+        /// Return all bits uncompressed as words of 64 bits each, with the first bit being the least significant bit
+        /// of the first word.
+        ///
+        /// Note that the last word may be followed by more words if a run of set bits exceeds [`num_bits()`](Self::num_bits()).
+        pub fn to_words(&self) -> std::vec::Vec<u64> {
+            let mut out = std::vec::Vec::with_capacity((self.num_bits() + 63) / 64);
+            let mut iter = self.bits.iter();
+            while let Some(word) = iter.next() {
+                let run_word = if rlw_runbit_is_set(word) { u64::MAX } else { 0 };
+                out.extend(std::iter::repeat(run_word).take(rlw_running_len(word) as usize));
+                for _ in 0..rlw_literal_words(word) {
+                    let word = iter
+                        .next()
+                        .expect("BUG: ran out of words while going through uncompressed portion");
+                    out.push(*word);
+                }
+            }
+            out
+        }
+    }
+
+    impl Vec {
+        /// Compress the uncompressed `words`, with the first bit being the least significant bit of the first word.
+        pub fn from_words(words: &[u64]) -> Self {
+            let num_words = words.iter().rposition(|word| *word != 0).map_or(0, |pos| pos + 1);
+            let words = &words[..num_words];
+            let mut bits = std::vec::Vec::with_capacity(words.len() + 1);
+            let mut rlw;
+            let mut pos = 0;
+            loop {
+                rlw = bits.len();
+                bits.push(0);
+                let Some(first) = words.get(pos) else { break };
+                let is_clean = |word: &u64| *word == 0 || *word == u64::MAX;
+                let mut marker = 0;
+                if is_clean(first) {
+                    let run_len = words[pos..]
+                        .iter()
+                        .take(RLW_LARGEST_RUNNING_COUNT as usize)
+                        .take_while(|word| *word == first)
+                        .count();
+                    marker |= u64::from(*first == u64::MAX) | (run_len as u64) << 1;
+                    pos += run_len;
+                }
+                let num_literals = words[pos..]
+                    .iter()
+                    .take(RLW_LARGEST_LITERAL_COUNT as usize)
+                    .take_while(|word| !is_clean(word))
+                    .count();
+                marker |= (num_literals as u64) << (1 + RLW_RUNNING_BITS);
+                bits[rlw] = marker;
+                bits.extend_from_slice(&words[pos..][..num_literals]);
+                pos += num_literals;
+                if pos == words.len() {
+                    break;
+                }
+            }
+            let num_bits = words
+                .last()
+                .map_or(0, |last| (words.len() * 64) as u32 - last.leading_zeros());
+            Vec {
+                num_bits,
+                bits,
+                rlw: rlw as u64,
+            }
+        }
     }
 
     #[inline]
@@ -115,10 +206,10 @@ mod access {
 
     const RLW_RUNNING_BITS: u64 = 4 * 8;
     const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
+    const RLW_LARGEST_LITERAL_COUNT: u64 = (1 << (64 - RLW_RUNNING_BITS - 1)) - 1;
 }
 
 /// A growable collection of u64 that are seen as stream of individual bits.
-#[allow(dead_code)]
 #[derive(Clone)]
 pub struct Vec {
     num_bits: u32,
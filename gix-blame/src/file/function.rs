@@ -0,0 +1,588 @@
+use std::ops::Range;
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_diff::blob::{platform::prepare_diff::Operation, ResourceKind};
+use gix_hash::{oid, ObjectId};
+use gix_hashtable::HashMap;
+use gix_object::{tree::EntryMode, FindExt};
+
+use super::{BlameEntry, Error, Options, Outcome, Statistics};
+
+/// Attribute each line of the file at `file_path` to the commit that introduced it, starting at the first commit
+/// yielded by `traverse` and walking its history by passing lines to each parent that has them unchanged.
+///
+/// * `odb` is used to find commits, trees and blobs.
+/// * `traverse` yields all commits to consider, each before its parents, just like [`Ancestors`](gix_traverse::commit::Ancestors)
+///   does when [sorting by commit time](gix_traverse::commit::Sorting::ByCommitTimeNewestFirst). Its first commit is
+///   the one to start the blame from. Lines that remain with a commit that isn't yielded by the traversal, which can happen
+///   if it's limited, are attributed to the commit they were last passed to.
+/// * `resource_cache` is used to diff blobs with the diff-algorithm it's configured with.
+/// * `options` control which lines to blame, and how to find them in parent commits.
+///
+/// The traversal stops early once all lines have been attributed.
+///
+/// ### Deviation
+///
+/// * Lines are only followed into the source of a rename if the blamed file doesn't exist in a parent, similar
+///   to `git blame` without `-C`. Copies are not detected.
+/// * Lines changed by [ignored commits](Options::ignore_revs) are passed to the first parent at the same offset
+///   within the changed hunk, without trying to find the most similar line like `git` does.
+pub fn file<E>(
+    odb: impl gix_object::FindObjectOrHeader,
+    traverse: impl IntoIterator<Item = Result<gix_traverse::commit::Info, E>>,
+    resource_cache: &mut gix_diff::blob::Platform,
+    file_path: &BStr,
+    options: Options,
+) -> Result<Outcome, Error>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let _span = gix_trace::coarse!("gix_blame::file()", ?file_path);
+    resource_cache.options.skip_internal_diff_if_external_is_configured = false;
+
+    let mut traverse = traverse.into_iter();
+    let first = traverse
+        .next()
+        .ok_or(Error::EmptyTraversal)?
+        .map_err(|err| Error::Traverse(err.into()))?;
+
+    let mut ctx = Context {
+        odb: &odb,
+        file_path,
+        resource_cache,
+        rewrites: options.rewrites,
+        buf: Vec::new(),
+        buf2: Vec::new(),
+        tree_state: Default::default(),
+        stats: Statistics {
+            commits_traversed: 1,
+            ..Default::default()
+        },
+    };
+
+    let tree_id = ctx.tree_of_commit(&first.id)?.ok_or(Error::FileMissing {
+        file_path: file_path.to_owned(),
+        commit_id: first.id,
+    })?;
+    let (_, blob_id) = ctx.find_entry(tree_id, file_path)?.ok_or_else(|| Error::FileMissing {
+        file_path: file_path.to_owned(),
+        commit_id: first.id,
+    })?;
+    let blob = odb.find_blob(&blob_id, &mut ctx.buf)?.data.to_vec();
+    let lines_in_blob = gix_diff::blob::sources::byte_lines_with_terminator(&blob).count() as u32;
+
+    let mut hunks = Vec::new();
+    for range in merge_ranges(options.ranges, lines_in_blob)? {
+        hunks.push(UnblamedHunk {
+            suspect_start: range.start,
+            range_in_blamed_file: range,
+            path: file_path.to_owned(),
+        });
+    }
+
+    let mut suspects = HashMap::<ObjectId, Vec<UnblamedHunk>>::default();
+    if !hunks.is_empty() {
+        suspects.insert(first.id, hunks);
+    }
+    let mut parents_of = HashMap::<ObjectId, gix_traverse::commit::ParentIds>::default();
+    let mut entries = Vec::new();
+    let mut todo = Vec::new();
+
+    let mut next = Some(first);
+    while let Some(info) = next.take() {
+        if suspects.is_empty() {
+            break;
+        }
+        parents_of.insert(info.id, info.parent_ids);
+        todo.push(info.id);
+        // Lines may be passed to commits we have seen already if commit times are skewed, so process these right away.
+        while let Some(commit_id) = todo.pop() {
+            let Some(hunks) = suspects.remove(&commit_id) else {
+                continue;
+            };
+            let parent_ids = &parents_of[&commit_id];
+            for (parent_id, hunks) in ctx.process_commit(
+                commit_id,
+                parent_ids,
+                hunks,
+                options.ignore_revs.contains(&commit_id),
+                &mut entries,
+            )? {
+                if parents_of.contains_key(&parent_id) {
+                    todo.push(parent_id);
+                }
+                suspects.entry(parent_id).or_default().extend(hunks);
+            }
+        }
+
+        next = traverse.next().transpose().map_err(|err| Error::Traverse(err.into()))?;
+        if next.is_some() {
+            ctx.stats.commits_traversed += 1;
+        }
+    }
+
+    for (commit_id, hunks) in suspects {
+        entries.extend(
+            hunks
+                .into_iter()
+                .map(|hunk| hunk.into_entry(commit_id, file_path, None)),
+        );
+    }
+
+    Ok(Outcome {
+        entries: coalesce(entries),
+        blob,
+        statistics: ctx.stats,
+    })
+}
+
+/// Lines of the blamed file that still need to be attributed to a commit.
+#[derive(Debug, Clone)]
+struct UnblamedHunk {
+    /// The lines in the blamed file.
+    range_in_blamed_file: Range<u32>,
+    /// The line at which `range_in_blamed_file` starts in the version of the file in the suspect commit.
+    suspect_start: u32,
+    /// The path of the file in the suspect commit.
+    path: BString,
+}
+
+impl UnblamedHunk {
+    fn suspect_range(&self) -> Range<u32> {
+        self.suspect_start..self.suspect_start + (self.range_in_blamed_file.end - self.range_in_blamed_file.start)
+    }
+
+    /// Return the part of this hunk that covers `suspect_range`, which must be contained in our own suspect range.
+    fn slice(&self, suspect_range: Range<u32>) -> UnblamedHunk {
+        let start = self.range_in_blamed_file.start + (suspect_range.start - self.suspect_start);
+        UnblamedHunk {
+            range_in_blamed_file: start..start + (suspect_range.end - suspect_range.start),
+            suspect_start: suspect_range.start,
+            path: self.path.clone(),
+        }
+    }
+
+    fn into_entry(self, commit_id: ObjectId, file_path: &BStr, previous: Option<(ObjectId, BString)>) -> BlameEntry {
+        BlameEntry {
+            range_in_source_file: self.suspect_range(),
+            range_in_blamed_file: self.range_in_blamed_file,
+            commit_id,
+            source_file_name: (self.path != file_path).then_some(self.path),
+            previous,
+        }
+    }
+}
+
+/// A region of the file in a commit, as compared to the version of the file in one of its parents.
+#[derive(Debug, Clone)]
+enum Segment {
+    /// The lines in `after` are the same as the ones starting at `before_start` in the parent.
+    Unchanged { after: Range<u32>, before_start: u32 },
+    /// The lines in `after` replaced the lines in `before` of the parent.
+    Changed { after: Range<u32>, before: Range<u32> },
+}
+
+struct Context<'a, Find> {
+    odb: &'a Find,
+    file_path: &'a BStr,
+    resource_cache: &'a mut gix_diff::blob::Platform,
+    rewrites: Option<gix_diff::Rewrites>,
+    buf: Vec<u8>,
+    buf2: Vec<u8>,
+    tree_state: gix_diff::tree::State,
+    stats: Statistics,
+}
+
+impl<'a, Find> Context<'a, Find>
+where
+    Find: gix_object::FindObjectOrHeader,
+{
+    /// Attribute the lines of `hunks` that the commit at `commit_id` changed to it by adding them to `entries`,
+    /// and return all other lines along with the parent they were passed to.
+    fn process_commit(
+        &mut self,
+        commit_id: ObjectId,
+        parent_ids: &[ObjectId],
+        mut hunks: Vec<UnblamedHunk>,
+        is_ignored: bool,
+        entries: &mut Vec<BlameEntry>,
+    ) -> Result<Vec<(ObjectId, Vec<UnblamedHunk>)>, Error> {
+        let mut out = Vec::<(ObjectId, Vec<UnblamedHunk>)>::new();
+        let Some(tree_id) = self.tree_of_commit(&commit_id)? else {
+            unreachable!("BUG: commits we pass lines to are always present")
+        };
+        let mut parent_tree_ids = Vec::with_capacity(parent_ids.len());
+        for parent_id in parent_ids {
+            // Parents may be missing in shallow repositories, which makes them boundary commits.
+            if let Some(parent_tree_id) = self.tree_of_commit(parent_id)? {
+                parent_tree_ids.push((*parent_id, parent_tree_id));
+            }
+        }
+
+        hunks.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut hunks = hunks.into_iter().peekable();
+        while let Some(first) = hunks.next() {
+            let path = first.path.clone();
+            let mut remaining = vec![first];
+            while let Some(hunk) = hunks.next_if(|hunk| hunk.path == path) {
+                remaining.push(hunk);
+            }
+
+            let Some((mode, blob_id)) = self.find_entry(tree_id, path.as_ref())? else {
+                entries.extend(
+                    remaining
+                        .into_iter()
+                        .map(|hunk| hunk.into_entry(commit_id, self.file_path, None)),
+                );
+                continue;
+            };
+
+            let mut parents = Vec::with_capacity(parent_tree_ids.len());
+            for (parent_id, parent_tree_id) in &parent_tree_ids {
+                let source = match self.find_entry(*parent_tree_id, path.as_ref())? {
+                    Some((mode, id)) => Some((path.clone(), mode, id)),
+                    None => self.find_rename_source(*parent_tree_id, tree_id, path.as_ref())?,
+                };
+                if let Some((parent_path, parent_mode, parent_blob_id)) = source {
+                    parents.push((*parent_id, parent_path, parent_mode, parent_blob_id));
+                }
+            }
+
+            if let Some((parent_id, parent_path, _, _)) = parents.iter().find(|p| p.3 == blob_id) {
+                for hunk in &mut remaining {
+                    hunk.path = parent_path.clone();
+                }
+                add_to(&mut out, *parent_id, remaining);
+                continue;
+            }
+
+            let mut first_parent_segments = None;
+            for (parent_id, parent_path, parent_mode, parent_blob_id) in &parents {
+                if remaining.is_empty() {
+                    break;
+                }
+                let segments = self.diff_blobs(
+                    (*parent_blob_id, *parent_mode, parent_path.as_ref()),
+                    (blob_id, mode, path.as_ref()),
+                )?;
+                let (passed, left) = split(remaining, &segments, false, parent_path);
+                add_to(&mut out, *parent_id, passed);
+                remaining = left;
+                if first_parent_segments.is_none() {
+                    first_parent_segments = Some((*parent_id, parent_path, segments));
+                }
+            }
+
+            if is_ignored {
+                if let Some((parent_id, parent_path, segments)) = first_parent_segments {
+                    let (passed, left) = split(remaining, &segments, true, parent_path);
+                    add_to(&mut out, parent_id, passed);
+                    remaining = left;
+                }
+            }
+            // Like `git`, the first parent that has the file is the one the remaining lines are compared against.
+            let previous = parents
+                .first()
+                .map(|(parent_id, parent_path, _, _)| (*parent_id, parent_path.clone()));
+            entries.extend(
+                remaining
+                    .into_iter()
+                    .map(|hunk| hunk.into_entry(commit_id, self.file_path, previous.clone())),
+            );
+        }
+        self.resource_cache.clear_resource_cache();
+        Ok(out)
+    }
+
+    /// Return the tree of the commit at `id`, or `None` if the commit doesn't exist or isn't a commit.
+    fn tree_of_commit(&mut self, id: &oid) -> Result<Option<ObjectId>, Error> {
+        let Some(data) = self.odb.try_find(id, &mut self.buf)? else {
+            return Ok(None);
+        };
+        Ok(data
+            .try_into_commit_iter()
+            .map(|mut commit| commit.tree_id())
+            .transpose()?)
+    }
+
+    /// Find the blob or symlink at `path` in the tree at `tree_id`.
+    fn find_entry(&mut self, mut tree_id: ObjectId, path: &BStr) -> Result<Option<(EntryMode, ObjectId)>, Error> {
+        let mut components = path.split_str("/").peekable();
+        while let Some(component) = components.next() {
+            let is_last = components.peek().is_none();
+            let tree = self.odb.find_tree_iter(&tree_id, &mut self.buf)?;
+            let mut found = None;
+            for entry in tree {
+                let entry = entry?;
+                if entry.filename == component {
+                    found = Some((entry.mode, entry.oid.to_owned()));
+                    break;
+                }
+            }
+            match found {
+                Some((mode, id)) if is_last && mode.is_blob_or_symlink() => return Ok(Some((mode, id))),
+                Some((mode, id)) if !is_last && mode.is_tree() => tree_id = id,
+                _ => break,
+            }
+        }
+        Ok(None)
+    }
+
+    /// Find the path of the blob in the tree at `parent_tree_id` that was renamed to `path` in the tree at `tree_id`,
+    /// if rename tracking is enabled.
+    fn find_rename_source(
+        &mut self,
+        parent_tree_id: ObjectId,
+        tree_id: ObjectId,
+        path: &BStr,
+    ) -> Result<Option<(BString, EntryMode, ObjectId)>, Error> {
+        use gix_diff::rewrites::tracker::{visit::SourceKind, ChangeKind};
+        use gix_diff::tree::recorder::Change;
+
+        let Some(mut rewrites) = self.rewrites else {
+            return Ok(None);
+        };
+        rewrites.copies = None;
+
+        let mut recorder = gix_diff::tree::Recorder::default();
+        let parent_tree = self.odb.find_tree_iter(&parent_tree_id, &mut self.buf)?;
+        let tree = self.odb.find_tree_iter(&tree_id, &mut self.buf2)?;
+        gix_diff::tree::Changes::from(parent_tree).needed_to_obtain(
+            tree,
+            &mut self.tree_state,
+            self.odb,
+            &mut recorder,
+        )?;
+        self.stats.trees_diffed += 1;
+
+        let mut tracker = gix_diff::rewrites::Tracker::new(rewrites);
+        for change in recorder.records {
+            let (kind, mode, id, location) = match change {
+                Change::Deletion {
+                    entry_mode,
+                    oid,
+                    path: location,
+                } => (ChangeKind::Deletion, entry_mode, oid, location),
+                Change::Addition {
+                    entry_mode,
+                    oid,
+                    path: location,
+                } if location == path => (ChangeKind::Addition, entry_mode, oid, location),
+                _ => continue,
+            };
+            tracker.try_push_change(TrackedChange { kind, mode, id }, location.as_ref());
+        }
+
+        let mut out = None;
+        tracker
+            .emit(
+                |destination, source| {
+                    if let Some(source) =
+                        source.filter(|source| source.kind == SourceKind::Rename && destination.location == path)
+                    {
+                        out = Some((source.location.to_owned(), source.change.mode, source.change.id));
+                        return gix_diff::tree::visit::Action::Cancel;
+                    }
+                    gix_diff::tree::visit::Action::Continue
+                },
+                self.resource_cache,
+                self.odb,
+                |_push| Ok::<_, std::convert::Infallible>(()),
+            )
+            .map_err(|err| Error::TrackRenames(Box::new(err)))?;
+        Ok(out)
+    }
+
+    /// Diff the blobs of `old` and `new` and return all segments of `new` as compared to `old`, covering all lines.
+    fn diff_blobs(
+        &mut self,
+        (old_id, old_mode, old_path): (ObjectId, EntryMode, &BStr),
+        (new_id, new_mode, new_path): (ObjectId, EntryMode, &BStr),
+    ) -> Result<Vec<Segment>, Error> {
+        self.resource_cache
+            .set_resource(old_id, old_mode.kind(), old_path, ResourceKind::OldOrSource, self.odb)
+            .map_err(|err| Error::SetBlobResource(Box::new(err)))?;
+        self.resource_cache
+            .set_resource(
+                new_id,
+                new_mode.kind(),
+                new_path,
+                ResourceKind::NewOrDestination,
+                self.odb,
+            )
+            .map_err(|err| Error::SetBlobResource(Box::new(err)))?;
+        let prep = self.resource_cache.prepare_diff()?;
+        self.stats.blobs_diffed += 1;
+
+        match prep.operation {
+            Operation::InternalDiff { algorithm } => Ok(segments(algorithm, &prep.interned_input())),
+            Operation::ExternalCommand { .. } => {
+                unreachable!("we disabled that")
+            }
+            Operation::SourceOrDestinationIsBinary => {
+                // Like `git`, diff binary files by their lines as well, but without any conversion.
+                let algorithm = self.resource_cache.options.algorithm.unwrap_or_default();
+                let old = self.odb.find_blob(&old_id, &mut self.buf)?.data;
+                let new = self.odb.find_blob(&new_id, &mut self.buf2)?.data;
+                let input = gix_diff::blob::intern::InternedInput::new(
+                    gix_diff::blob::sources::byte_lines_with_terminator(old),
+                    gix_diff::blob::sources::byte_lines_with_terminator(new),
+                );
+                Ok(segments(algorithm, &input))
+            }
+        }
+    }
+}
+
+/// Diff `input` with `algorithm` and return all segments of the new version as compared to the old one, covering all lines.
+fn segments(
+    algorithm: gix_diff::blob::Algorithm,
+    input: &gix_diff::blob::intern::InternedInput<&[u8]>,
+) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let (mut after_end, mut before_end) = (0, 0);
+    gix_diff::blob::diff(algorithm, input, |before: Range<u32>, after: Range<u32>| {
+        if after_end < after.start {
+            segments.push(Segment::Unchanged {
+                after: after_end..after.start,
+                before_start: before_end,
+            });
+        }
+        (after_end, before_end) = (after.end, before.end);
+        if !after.is_empty() {
+            segments.push(Segment::Changed { after, before });
+        }
+    });
+    segments.push(Segment::Unchanged {
+        after: after_end..u32::MAX,
+        before_start: before_end,
+    });
+    segments
+}
+
+fn add_to(out: &mut Vec<(ObjectId, Vec<UnblamedHunk>)>, parent_id: ObjectId, hunks: Vec<UnblamedHunk>) {
+    if hunks.is_empty() {
+        return;
+    }
+    match out.iter_mut().find(|(id, _)| *id == parent_id) {
+        Some((_, existing)) => existing.extend(hunks),
+        None => out.push((parent_id, hunks)),
+    }
+}
+
+/// Split `hunks` along `segments` and return all parts that can be passed to the parent, with their suspect lines
+/// in terms of the parent at `parent_path`, along with all parts that remain.
+/// If `map_changed` is `true`, changed lines are passed to the line at the same offset within the changed hunk
+/// of the parent, if there is one.
+fn split(
+    hunks: Vec<UnblamedHunk>,
+    segments: &[Segment],
+    map_changed: bool,
+    parent_path: &BString,
+) -> (Vec<UnblamedHunk>, Vec<UnblamedHunk>) {
+    let (mut passed, mut left) = (Vec::new(), Vec::new());
+    for hunk in hunks {
+        let suspect_range = hunk.suspect_range();
+        for segment in segments {
+            let (after, parent_start, mappable_end) = match segment {
+                Segment::Unchanged { after, before_start } => (after, Some(*before_start), after.end),
+                Segment::Changed { after, before } => (
+                    after,
+                    map_changed.then_some(before.start),
+                    after.start + (before.end - before.start).min(after.end - after.start),
+                ),
+            };
+            let start = suspect_range.start.max(after.start);
+            let end = suspect_range.end.min(after.end);
+            if start >= end {
+                continue;
+            }
+            let mapped_end = end.min(mappable_end).max(start);
+            match parent_start {
+                Some(parent_start) if start < mapped_end => {
+                    let mut part = hunk.slice(start..mapped_end);
+                    part.suspect_start = parent_start + (start - after.start);
+                    part.path = parent_path.clone();
+                    passed.push(part);
+                    if mapped_end < end {
+                        left.push(hunk.slice(mapped_end..end));
+                    }
+                }
+                _ => left.push(hunk.slice(start..end)),
+            }
+        }
+    }
+    (passed, left)
+}
+
+/// Sort and validate `ranges`, merging overlapping ones, or return the whole file if there are none.
+fn merge_ranges(mut ranges: Vec<Range<u32>>, lines_in_blob: u32) -> Result<Vec<Range<u32>>, Error> {
+    if ranges.is_empty() {
+        return Ok((lines_in_blob != 0).then_some(0..lines_in_blob).into_iter().collect());
+    }
+    if let Some(range) = ranges
+        .iter()
+        .find(|range| range.start >= range.end || range.end > lines_in_blob)
+    {
+        return Err(Error::InvalidLineRange {
+            range: range.clone(),
+            lines_in_blob,
+        });
+    }
+    ranges.sort_by_key(|range| range.start);
+    let mut out: Vec<Range<u32>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match out.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => out.push(range),
+        }
+    }
+    Ok(out)
+}
+
+/// Sort `entries` by their position in the blamed file and merge adjacent ones that are contiguous in the same source.
+fn coalesce(mut entries: Vec<BlameEntry>) -> Vec<BlameEntry> {
+    entries.sort_by_key(|entry| entry.range_in_blamed_file.start);
+    let mut out: Vec<BlameEntry> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match out.last_mut() {
+            Some(last)
+                if last.commit_id == entry.commit_id
+                    && last.source_file_name == entry.source_file_name
+                    && last.previous == entry.previous
+                    && last.range_in_blamed_file.end == entry.range_in_blamed_file.start
+                    && last.range_in_source_file.end == entry.range_in_source_file.start =>
+            {
+                last.range_in_blamed_file.end = entry.range_in_blamed_file.end;
+                last.range_in_source_file.end = entry.range_in_source_file.end;
+            }
+            _ => out.push(entry),
+        }
+    }
+    out
+}
+
+#[derive(Clone)]
+struct TrackedChange {
+    kind: gix_diff::rewrites::tracker::ChangeKind,
+    mode: EntryMode,
+    id: ObjectId,
+}
+
+impl gix_diff::rewrites::tracker::Change for TrackedChange {
+    fn id(&self) -> &oid {
+        &self.id
+    }
+
+    fn kind(&self) -> gix_diff::rewrites::tracker::ChangeKind {
+        self.kind
+    }
+
+    fn entry_mode(&self) -> EntryMode {
+        self.mode
+    }
+
+    fn id_and_entry_mode(&self) -> (&oid, EntryMode) {
+        (&self.id, self.mode)
+    }
+}
@@ -0,0 +1,123 @@
+use std::ops::Range;
+
+use bstr::{BStr, BString};
+use gix_hash::ObjectId;
+
+/// Options for use in [`file()`](crate::file()).
+#[derive(Default, Debug, Clone)]
+pub struct Options {
+    /// The zero-based, half-open ranges of lines in the blamed file to attribute, or all lines if empty.
+    ///
+    /// Overlapping ranges are merged, and each range must end within the blamed file.
+    pub ranges: Vec<Range<u32>>,
+    /// If set, lines are followed into the source of a rename when the blamed file doesn't exist in a parent.
+    ///
+    /// Note that copies are never tracked.
+    pub rewrites: Option<gix_diff::Rewrites>,
+    /// Commits to skip when attributing lines, similar to `git blame --ignore-rev`.
+    ///
+    /// Lines changed by such a commit are passed on to the line at the same position within the changed hunk
+    /// of its first parent, if there is one, and are attributed to the ignored commit only otherwise.
+    pub ignore_revs: gix_hashtable::HashSet<ObjectId>,
+}
+
+/// A range of lines in the blamed file which were all introduced by the same commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameEntry {
+    /// The zero-based lines in the blamed file which are attributed to `commit_id`.
+    pub range_in_blamed_file: Range<u32>,
+    /// The zero-based lines in the version of the file as it was introduced in `commit_id`,
+    /// which has the same length as `range_in_blamed_file`.
+    pub range_in_source_file: Range<u32>,
+    /// The commit that introduced the lines.
+    pub commit_id: ObjectId,
+    /// The path of the file in `commit_id` if it differs from the path of the blamed file, which is the case
+    /// if the lines were followed across a rename.
+    pub source_file_name: Option<BString>,
+    /// The first parent of `commit_id` that contains the file, along with the path of the file in it,
+    /// which is what `git blame --porcelain` shows as `previous`.
+    ///
+    /// It's `None` if the file was added in `commit_id`, or if `commit_id` is a boundary commit.
+    pub previous: Option<(ObjectId, BString)>,
+}
+
+impl BlameEntry {
+    /// Return the amount of lines in this entry.
+    pub fn len(&self) -> u32 {
+        self.range_in_blamed_file.end - self.range_in_blamed_file.start
+    }
+
+    /// Return `true` if this entry doesn't contain any line, which never happens for entries produced by [`file()`](crate::file()).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Statistics gathered during a [blame operation](crate::file()).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Statistics {
+    /// The amount of commits that were obtained from the traversal.
+    pub commits_traversed: usize,
+    /// The amount of trees that were diffed to find the source of a rename.
+    pub trees_diffed: usize,
+    /// The amount of blobs that were diffed to find unchanged lines.
+    pub blobs_diffed: usize,
+}
+
+/// The outcome of a [blame operation](crate::file()).
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// One entry per contiguous range of lines introduced by the same commit, sorted by their position in the blamed file
+    /// and covering all requested lines.
+    pub entries: Vec<BlameEntry>,
+    /// The content of the blamed file in the first commit of the traversal.
+    pub blob: Vec<u8>,
+    /// Additional information about the operation.
+    pub statistics: Statistics,
+}
+
+impl Outcome {
+    /// Return an iterator over all entries along with the lines of the blamed file they cover, including their line terminator.
+    pub fn entries_with_lines(&self) -> impl Iterator<Item = (&BlameEntry, Vec<&BStr>)> + '_ {
+        let lines: Vec<&BStr> = gix_diff::blob::sources::byte_lines_with_terminator(&self.blob)
+            .map(Into::into)
+            .collect();
+        self.entries.iter().map(move |entry| {
+            let lines =
+                lines[entry.range_in_blamed_file.start as usize..entry.range_in_blamed_file.end as usize].to_vec();
+            (entry, lines)
+        })
+    }
+}
+
+/// The error returned by [`file()`](crate::file()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The traversal didn't yield any commit to start the blame from")]
+    EmptyTraversal,
+    #[error("The file '{file_path}' to blame could not be found in commit {commit_id}")]
+    FileMissing { file_path: BString, commit_id: ObjectId },
+    #[error("Line range {}..{} is invalid for a file with {lines_in_blob} lines", range.start, range.end)]
+    InvalidLineRange { range: Range<u32>, lines_in_blob: u32 },
+    #[error("Failed to traverse the commit graph")]
+    Traverse(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    FindObject(#[from] gix_object::find::existing_object::Error),
+    #[error(transparent)]
+    FindObjectIter(#[from] gix_object::find::existing_iter::Error),
+    #[error(transparent)]
+    TryFindObject(#[from] gix_object::find::Error),
+    #[error(transparent)]
+    DecodeObject(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    DiffTree(#[from] gix_diff::tree::changes::Error),
+    #[error("Could not detect renames")]
+    TrackRenames(#[source] Box<gix_diff::rewrites::tracker::emit::Error>),
+    #[error("Could not obtain a blob to diff")]
+    SetBlobResource(#[source] Box<gix_diff::blob::platform::set_resource::Error>),
+    #[error(transparent)]
+    PrepareBlobDiff(#[from] gix_diff::blob::platform::prepare_diff::Error),
+}
+
+pub(crate) mod function;
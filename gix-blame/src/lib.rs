@@ -0,0 +1,11 @@
+//! Attribute each line of a file to the commit that introduced it, similar to what `git blame` does.
+//!
+//! History is walked from a starting commit, and lines that remain unchanged in a parent are passed on to it
+//! until they are found to be changed, following renames if configured.
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+///
+#[allow(clippy::empty_docs)]
+pub mod file;
+pub use file::function::file;
@@ -0,0 +1,305 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use gix_blame::file::{BlameEntry, Options};
+use gix_hash::ObjectId;
+use gix_object::bstr::{BString, ByteSlice};
+
+pub use gix_testtools::Result;
+
+fn fixture_path() -> PathBuf {
+    gix_testtools::scripted_fixture_read_only("make_blame_repo.sh").expect("script succeeds")
+}
+
+fn rev(root: &Path, name: &str) -> ObjectId {
+    let path = if name == "HEAD" {
+        root.join(".git/refs/heads/main")
+    } else {
+        root.join(".git/refs/tags").join(name)
+    };
+    ObjectId::from_hex(std::fs::read_to_string(path).expect("loose ref").trim().as_bytes()).expect("valid hex")
+}
+
+/// One `(commit, line-in-source-file, source-file-name)` per line of the blamed file, as parsed from `git blame --porcelain`.
+type Lines = Vec<(ObjectId, u32, BString)>;
+
+fn baseline(root: &Path, name: &str) -> Lines {
+    let data = std::fs::read(root.join(".git").join(format!("{name}.baseline"))).expect("baseline exists");
+    let mut out = Vec::new();
+    let mut current = None;
+    let mut file_names = std::collections::HashMap::<ObjectId, BString>::new();
+    for line in data.lines() {
+        if let Some(file_name) = line.strip_prefix(b"filename ") {
+            let (id, _) = current.expect("header comes first");
+            file_names.insert(id, file_name.into());
+        } else if line.starts_with(b"\t") {
+            let (id, source_line) = current.take().expect("header comes first");
+            out.push((id, source_line, file_names[&id].clone()));
+        } else {
+            let mut tokens = line.split_str(" ");
+            let Some(id) = tokens.next().and_then(|hex| ObjectId::from_hex(hex).ok()) else {
+                continue;
+            };
+            let source_line: u32 = tokens
+                .next()
+                .and_then(|n| n.to_str().ok())
+                .and_then(|n| n.parse().ok())
+                .expect("source line number");
+            current = Some((id, source_line - 1));
+        }
+    }
+    out
+}
+
+/// The `previous` commit and path of each blamed commit, as parsed from `git blame --porcelain`.
+type Previous = BTreeMap<ObjectId, Option<(ObjectId, BString)>>;
+
+fn baseline_previous(root: &Path, name: &str) -> Previous {
+    let data = std::fs::read(root.join(".git").join(format!("{name}.baseline"))).expect("baseline exists");
+    let mut out = Previous::new();
+    let mut current = None;
+    for line in data.lines() {
+        if let Some(previous) = line.strip_prefix(b"previous ") {
+            let (id, path) = previous.split_once_str(" ").expect("commit and path");
+            let id = ObjectId::from_hex(id).expect("valid hex");
+            out.insert(current.expect("header comes first"), Some((id, path.into())));
+        } else if let Some(id) = line
+            .split_str(" ")
+            .next()
+            .filter(|_| !line.starts_with(b"\t"))
+            .and_then(|hex| ObjectId::from_hex(hex).ok())
+        {
+            out.entry(id).or_insert(None);
+            current = Some(id);
+        }
+    }
+    out
+}
+
+fn previous_of(entries: &[BlameEntry]) -> Previous {
+    entries
+        .iter()
+        .map(|entry| (entry.commit_id, entry.previous.clone()))
+        .collect()
+}
+
+fn blame(root: &Path, file_path: &str, options: Options) -> gix_testtools::Result<gix_blame::file::Outcome> {
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let traverse = gix_traverse::commit::Ancestors::new(
+        Some(rev(root, "HEAD")),
+        gix_traverse::commit::ancestors::State::default(),
+        &odb,
+    )
+    .sorting(gix_traverse::commit::Sorting::ByCommitTimeNewestFirst)?;
+    let mut resource_cache = new_diff_platform(root);
+    Ok(gix_blame::file(
+        &odb,
+        traverse,
+        &mut resource_cache,
+        file_path.into(),
+        options,
+    )?)
+}
+
+fn lines_of(entries: &[BlameEntry], file_path: &str) -> Lines {
+    let mut out = Vec::new();
+    for entry in entries {
+        for offset in 0..entry.len() {
+            out.push((
+                entry.commit_id,
+                entry.range_in_source_file.start + offset,
+                entry.source_file_name.clone().unwrap_or_else(|| file_path.into()),
+            ));
+        }
+    }
+    out
+}
+
+#[test]
+fn whole_file_matches_git() -> crate::Result {
+    let root = fixture_path();
+    for (name, file_path) in [("simple", "simple.txt"), ("binary", "binary.bin")] {
+        let outcome = blame(&root, file_path, Options::default())?;
+        assert_eq!(
+            lines_of(&outcome.entries, file_path),
+            baseline(&root, name),
+            "{name}: each line is attributed to the same commit and source line as git does"
+        );
+        assert_eq!(
+            outcome
+                .entries_with_lines()
+                .map(|(_, lines)| lines.len())
+                .sum::<usize>(),
+            outcome.blob.lines_with_terminator().count(),
+            "all lines are covered"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn previous_commits_match_git() -> crate::Result {
+    let root = fixture_path();
+    for (name, file_path, rewrites) in [
+        ("simple", "simple.txt", None),
+        ("binary", "binary.bin", None),
+        ("renamed", "dir/renamed-twice.txt", Some(Default::default())),
+    ] {
+        let outcome = blame(
+            &root,
+            file_path,
+            Options {
+                rewrites,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            previous_of(&outcome.entries),
+            baseline_previous(&root, name),
+            "{name}: the first parent with the file and its path in it are the same as the ones git shows"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn renames_are_not_followed_by_default() -> crate::Result {
+    let root = fixture_path();
+    let outcome = blame(&root, "dir/renamed-twice.txt", Options::default())?;
+    assert_eq!(
+        outcome.entries,
+        [BlameEntry {
+            range_in_blamed_file: 0..5,
+            range_in_source_file: 0..5,
+            commit_id: rev(&root, "c5"),
+            source_file_name: None,
+            previous: None,
+        }],
+        "all lines are attributed to the commit that moved the file, in a single entry"
+    );
+    assert_eq!(outcome.statistics.trees_diffed, 0);
+    Ok(())
+}
+
+#[test]
+fn renames_are_followed() -> crate::Result {
+    let root = fixture_path();
+    let outcome = blame(
+        &root,
+        "dir/renamed-twice.txt",
+        Options {
+            rewrites: Some(Default::default()),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        lines_of(&outcome.entries, "dir/renamed-twice.txt"),
+        baseline(&root, "renamed")
+    );
+    assert_eq!(
+        outcome.entries.len(),
+        3,
+        "contiguous lines of the same commit are merged"
+    );
+    assert_eq!(outcome.entries[0].source_file_name.as_ref().unwrap(), "renamed.txt");
+    assert!(outcome.statistics.trees_diffed > 0);
+    Ok(())
+}
+
+#[test]
+fn line_ranges() -> crate::Result {
+    let root = fixture_path();
+    let outcome = blame(
+        &root,
+        "simple.txt",
+        Options {
+            ranges: vec![6..8, 1..4, 2..3],
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        lines_of(&outcome.entries, "simple.txt"),
+        baseline(&root, "simple-range")
+    );
+    assert_eq!(
+        outcome
+            .entries
+            .iter()
+            .map(|entry| entry.range_in_blamed_file.clone())
+            .collect::<Vec<_>>(),
+        [1..2, 2..3, 3..4, 6..7, 7..8],
+        "ranges are sorted, merged and retain their position in the blamed file"
+    );
+
+    let err = blame(
+        &root,
+        "simple.txt",
+        Options {
+            ranges: Some(5..10).into_iter().collect(),
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line range 5..10 is invalid for a file with 9 lines",
+        "ranges must not exceed the file"
+    );
+    Ok(())
+}
+
+#[test]
+fn ignored_revisions_pass_lines_to_their_parent() -> crate::Result {
+    let root = fixture_path();
+    let outcome = blame(
+        &root,
+        "simple.txt",
+        Options {
+            ignore_revs: Some(rev(&root, "ignored")).into_iter().collect(),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        lines_of(&outcome.entries, "simple.txt"),
+        baseline(&root, "simple-ignored")
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_file_is_an_error() {
+    let root = fixture_path();
+    let err = blame(&root, "does-not-exist", Options::default()).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("The file 'does-not-exist' to blame could not be found in commit"));
+}
+
+fn new_diff_platform(root: &Path) -> gix_diff::blob::Platform {
+    let attributes = gix_worktree::Stack::new(
+        root,
+        gix_worktree::stack::State::AttributesStack(gix_worktree::stack::state::Attributes::new(
+            Default::default(),
+            None,
+            gix_worktree::stack::state::attributes::Source::IdMapping,
+            Default::default(),
+        )),
+        gix_worktree::glob::pattern::Case::Sensitive,
+        Vec::new(),
+        Vec::new(),
+    );
+    let filter = gix_diff::blob::Pipeline::new(
+        Default::default(),
+        gix_filter::Pipeline::default(),
+        Vec::new(),
+        Default::default(),
+    );
+    gix_diff::blob::Platform::new(
+        Default::default(),
+        filter,
+        gix_diff::blob::pipeline::Mode::ToGit,
+        attributes,
+    )
+}
@@ -0,0 +1,74 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Commit with a distinct, monotonically increasing timestamp to make the history predictable.
+time=0
+function tick () {
+  time=$((time + 60))
+  export GIT_AUTHOR_DATE="@$((946684800 + time)) +0000"
+  export GIT_COMMITTER_DATE="$GIT_AUTHOR_DATE"
+}
+
+function commit () {
+  tick
+  git add -A
+  git commit -q -m "$1"
+  git tag "$1"
+}
+
+function baseline () {
+  local name=${1:?first argument is the name of the baseline}
+  shift
+  git blame --porcelain "$@" > ".git/$name.baseline"
+}
+
+git init -q
+git checkout -q -b main
+
+printf '1\n2\n3\n4\n5\n' > simple.txt
+printf 'a\nb\nc\n' > renamed.txt
+commit c1
+
+printf '1\n2\n2.5\n3\n4\n5\n' > simple.txt
+commit c2
+
+printf 'one\n2\n2.5\n3\n5\n6\n' > simple.txt
+printf 'a\nb\nc\nd\n' > renamed.txt
+commit c3
+
+git mv renamed.txt renamed-once.txt
+printf 'a\nb\nc\nd\ne\n' > renamed-once.txt
+commit c4
+
+mkdir dir
+git mv renamed-once.txt dir/renamed-twice.txt
+commit c5
+
+git checkout -q -b other c3
+printf 'one\n2\n2.5\n3\n5\n6\n7\n' > simple.txt
+commit o1
+
+git checkout -q main
+printf 'zero\none\n2\n2.5\n3\n5\n6\n' > simple.txt
+commit c6
+
+tick
+git merge -q --no-ff -m merge other
+git tag merge
+
+printf 'zero\nONE\n2\n2.5\n3;\n5\n6\n7\n' > simple.txt
+commit ignored
+
+printf 'zero\nONE\n2\n2.5\n3;\n5\n6\n7\n8\n' > simple.txt
+commit c7
+
+printf 'binary\0\n' > binary.bin
+commit b1
+printf 'binary\0\nchanged\n' > binary.bin
+commit b2
+
+baseline simple simple.txt
+baseline simple-range -L 2,4 -L 7,8 simple.txt
+baseline simple-ignored --ignore-rev ignored simple.txt
+baseline renamed dir/renamed-twice.txt
+baseline binary binary.bin
@@ -0,0 +1,144 @@
+use std::io::BufRead;
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::{Header, Prerequisite, Ref, Version};
+
+/// The error returned by [`Header::from_read()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read the bundle header")]
+    Io(#[from] std::io::Error),
+    #[error("Not a bundle as the first line was {line:?}")]
+    Signature { line: BString },
+    #[error("The bundle header ended before the empty line that separates it from the pack")]
+    UnexpectedEof,
+    #[error("The bundle capability {name:?} isn't supported")]
+    UnknownCapability { name: BString },
+    #[error("The object format {value:?} isn't supported")]
+    ObjectFormat { value: BString },
+    #[error("Capabilities aren't allowed in bundles of version 2: {line:?}")]
+    CapabilityInV2 { line: BString },
+    #[error("Line {line:?} does not start with a valid object id")]
+    InvalidObjectId {
+        line: BString,
+        source: gix_hash::decode::Error,
+    },
+    #[error("Line {line:?} refers to an object of kind {actual}, but the bundle contains {expected} objects")]
+    ObjectHashMismatch {
+        line: BString,
+        actual: gix_hash::Kind,
+        expected: gix_hash::Kind,
+    },
+    #[error("Line {line:?} lacks the name of the reference")]
+    MissingRefName { line: BString },
+}
+
+/// Decoding
+impl Header {
+    /// Read a bundle header from `read`, which is left positioned at the start of the pack data.
+    pub fn from_read(read: &mut dyn BufRead) -> Result<Header, Error> {
+        let mut buf = Vec::new();
+        let mut next_line = |buf: &mut Vec<u8>| -> Result<BString, Error> {
+            buf.clear();
+            read.read_until(b'\n', buf)?;
+            if buf.pop() != Some(b'\n') {
+                return Err(Error::UnexpectedEof);
+            }
+            if buf.last() == Some(&b'\r') {
+                buf.pop();
+            }
+            Ok(buf.as_bstr().to_owned())
+        };
+
+        let signature = next_line(&mut buf).map_err(|err| match err {
+            Error::UnexpectedEof => Error::Signature {
+                line: buf.as_bstr().to_owned(),
+            },
+            err => err,
+        })?;
+        let version = [Version::V2, Version::V3]
+            .into_iter()
+            .find(|version| version.signature().strip_suffix(b"\n") == Some(signature.as_slice()))
+            .ok_or(Error::Signature { line: signature })?;
+
+        let mut header = Header {
+            version,
+            object_hash: gix_hash::Kind::Sha1,
+            filter: None,
+            prerequisites: Vec::new(),
+            refs: Vec::new(),
+        };
+        let mut lines = Vec::new();
+        loop {
+            let line = next_line(&mut buf)?;
+            if line.is_empty() {
+                break;
+            }
+            match line.strip_prefix(b"@") {
+                Some(capability) => {
+                    if version == Version::V2 {
+                        return Err(Error::CapabilityInV2 { line });
+                    }
+                    let (name, value) = capability
+                        .split_once_str("=")
+                        .map_or((capability, None), |(name, value)| (name, Some(value.as_bstr())));
+                    match (name, value) {
+                        (b"object-format", Some(value)) => {
+                            header.object_hash = match value.as_bytes() {
+                                b"sha1" => gix_hash::Kind::Sha1,
+                                b"sha256" => gix_hash::Kind::Sha256,
+                                _ => {
+                                    return Err(Error::ObjectFormat {
+                                        value: value.to_owned(),
+                                    })
+                                }
+                            }
+                        }
+                        (b"filter", Some(value)) => header.filter = Some(value.to_owned()),
+                        _ => return Err(Error::UnknownCapability { name: name.into() }),
+                    }
+                }
+                None => lines.push(line),
+            }
+        }
+
+        for line in lines {
+            let (is_prerequisite, rest) = match line.strip_prefix(b"-") {
+                Some(rest) => (true, rest.as_bstr()),
+                None => (false, line.as_bstr()),
+            };
+            let (hex, tail) = rest
+                .split_once_str(" ")
+                .map_or((rest.as_bytes(), None), |(hex, tail)| (hex, Some(tail.as_bstr())));
+            let id = ObjectId::from_hex(hex).map_err(|source| Error::InvalidObjectId {
+                line: line.clone(),
+                source,
+            })?;
+            if id.kind() != header.object_hash {
+                return Err(Error::ObjectHashMismatch {
+                    actual: id.kind(),
+                    expected: header.object_hash,
+                    line,
+                });
+            }
+            if is_prerequisite {
+                header.prerequisites.push(Prerequisite {
+                    id,
+                    comment: tail.map(ToOwned::to_owned).unwrap_or_default(),
+                });
+            } else {
+                let name = tail
+                    .filter(|name| !name.is_empty())
+                    .ok_or_else(|| Error::MissingRefName { line: line.clone() })?;
+                header.refs.push(Ref {
+                    name: name.to_owned(),
+                    id,
+                });
+            }
+        }
+        Ok(header)
+    }
+}
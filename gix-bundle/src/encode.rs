@@ -0,0 +1,59 @@
+use std::io::Write;
+
+use crate::{Header, Version};
+
+/// The error returned by [`Header::write_to()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Bundles of version 2 can't store {object_hash} objects or filtered packs, version 3 is required")]
+    VersionTooLow { object_hash: gix_hash::Kind },
+}
+
+/// Encoding
+impl Header {
+    /// Write this header to `out` in the format `git` uses, including the empty line after which the pack data is expected.
+    ///
+    /// Note that bundles of [version 2](Version::V2) can only be written if objects are hashed with SHA1 and if there is no filter.
+    pub fn write_to(&self, out: &mut dyn Write) -> Result<(), Error> {
+        out.write_all(self.version.signature())?;
+        match self.version {
+            Version::V2 => {
+                if self.object_hash != gix_hash::Kind::Sha1 || self.filter.is_some() {
+                    return Err(Error::VersionTooLow {
+                        object_hash: self.object_hash,
+                    });
+                }
+            }
+            Version::V3 => {
+                let object_format = match self.object_hash {
+                    gix_hash::Kind::Sha1 => "sha1",
+                    gix_hash::Kind::Sha256 => "sha256",
+                };
+                writeln!(out, "@object-format={object_format}")?;
+                if let Some(filter) = &self.filter {
+                    out.write_all(b"@filter=")?;
+                    out.write_all(filter)?;
+                    out.write_all(b"\n")?;
+                }
+            }
+        }
+        for prerequisite in &self.prerequisites {
+            write!(out, "-{}", prerequisite.id)?;
+            if !prerequisite.comment.is_empty() {
+                out.write_all(b" ")?;
+                out.write_all(&prerequisite.comment)?;
+            }
+            out.write_all(b"\n")?;
+        }
+        for r in &self.refs {
+            write!(out, "{} ", r.id)?;
+            out.write_all(&r.name)?;
+            out.write_all(b"\n")?;
+        }
+        out.write_all(b"\n")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    io::{BufReader, Seek, SeekFrom},
+    path::PathBuf,
+};
+
+use crate::Header;
+
+/// The error returned by [`File::at()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open bundle at '{}'", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+    #[error("Could not decode the header of the bundle at '{}'", path.display())]
+    Decode {
+        source: crate::decode::Error,
+        path: PathBuf,
+    },
+}
+
+/// A bundle on disk, with its header already read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct File {
+    /// The path to the bundle file.
+    pub path: PathBuf,
+    /// The decoded header of the bundle.
+    pub header: Header,
+    /// The offset in bytes at which the pack data starts.
+    pub pack_offset: u64,
+}
+
+/// Initialization
+impl File {
+    /// Open the bundle at `path` and read its header.
+    pub fn at(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let io_err = |source, path: &PathBuf| Error::Io {
+            source,
+            path: path.clone(),
+        };
+        let mut read = BufReader::new(std::fs::File::open(&path).map_err(|err| io_err(err, &path))?);
+        let header = Header::from_read(&mut read).map_err(|source| Error::Decode {
+            source,
+            path: path.clone(),
+        })?;
+        let pack_offset = read.stream_position().map_err(|err| io_err(err, &path))?;
+        Ok(File {
+            path,
+            header,
+            pack_offset,
+        })
+    }
+}
+
+/// Access
+impl File {
+    /// Open the bundle once more and return a reader positioned at the beginning of its pack data, which extends
+    /// to the end of the file.
+    pub fn pack_data(&self) -> std::io::Result<BufReader<std::fs::File>> {
+        let mut file = std::fs::File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.pack_offset))?;
+        Ok(BufReader::new(file))
+    }
+}
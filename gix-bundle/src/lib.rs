@@ -0,0 +1,120 @@
+//! Read and write git bundle files, which contain a pack along with the references it provides and the commits it
+//! requires to already be present, so repositories can be transferred without a network connection.
+//!
+//! A bundle starts with a [`Header`] in a line-based text format, directly followed by the pack data.
+//! Both the `v2` and the `v3` formats are supported, where the latter can also transport SHA256 objects or filtered packs
+//! by means of capabilities.
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+/// The version of a bundle, as indicated by its signature.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Version {
+    /// The original format which can only store SHA1 objects and has no capabilities.
+    #[default]
+    V2,
+    /// A format with capabilities like `object-format` and `filter`.
+    V3,
+}
+
+impl Version {
+    /// Return the first line of a bundle of this version, including the trailing newline.
+    pub fn signature(&self) -> &'static [u8] {
+        match self {
+            Version::V2 => b"# v2 git bundle\n",
+            Version::V3 => b"# v3 git bundle\n",
+        }
+    }
+}
+
+/// A commit which has to exist in a repository before the objects of the bundle can be added to it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Prerequisite {
+    /// The id of the commit that must be present.
+    pub id: ObjectId,
+    /// Information to help humans identify the commit, typically its subject line, or an empty string.
+    pub comment: BString,
+}
+
+/// A reference provided by the bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ref {
+    /// The full name of the reference, like `refs/heads/main` or `HEAD`.
+    pub name: BString,
+    /// The object the reference points to.
+    pub id: ObjectId,
+}
+
+/// The header of a bundle which describes the pack that follows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// The version of the bundle.
+    pub version: Version,
+    /// The kind of hash used for all objects in the bundle, as communicated by the `object-format` capability.
+    pub object_hash: gix_hash::Kind,
+    /// The object filter that was used to create the pack, like `blob:none`, as communicated by the `filter` capability.
+    pub filter: Option<BString>,
+    /// The commits that must be present for the pack to be complete.
+    pub prerequisites: Vec<Prerequisite>,
+    /// The references along with the objects they point to.
+    pub refs: Vec<Ref>,
+}
+
+impl Header {
+    /// Create an empty header for a bundle with objects hashed with `object_hash`, using the lowest version that can represent it.
+    pub fn new(object_hash: gix_hash::Kind) -> Self {
+        Header {
+            version: if object_hash == gix_hash::Kind::Sha1 {
+                Version::V2
+            } else {
+                Version::V3
+            },
+            object_hash,
+            filter: None,
+            prerequisites: Vec::new(),
+            refs: Vec::new(),
+        }
+    }
+
+    /// Return all prerequisites for which `contains(id)` returns `false`, i.e. which aren't present in the repository
+    /// that should receive the objects of the bundle.
+    pub fn missing_prerequisites(
+        &self,
+        mut contains: impl FnMut(&gix_hash::oid) -> bool,
+    ) -> impl Iterator<Item = &Prerequisite> {
+        self.prerequisites
+            .iter()
+            .filter(move |prerequisite| !contains(&prerequisite.id))
+    }
+}
+
+/// Return `true` if the file at `path` starts with the signature of a bundle of any supported version.
+pub fn is_bundle(path: &std::path::Path) -> bool {
+    use std::io::Read;
+    let mut buf = [0; 16];
+    if std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut buf))
+        .is_err()
+    {
+        return false;
+    }
+    [Version::V2, Version::V3]
+        .iter()
+        .any(|version| version.signature() == buf)
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod decode;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod encode;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod file;
+pub use file::File;
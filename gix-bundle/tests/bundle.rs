@@ -0,0 +1,200 @@
+use std::path::PathBuf;
+
+use bstr::ByteSlice;
+use gix_bundle::{decode, encode, File, Header, Prerequisite, Version};
+
+pub use gix_testtools::Result;
+
+fn fixture(name: &str) -> PathBuf {
+    gix_testtools::scripted_fixture_read_only("make_bundles.sh")
+        .expect("script succeeds")
+        .join(name)
+}
+
+fn hex_to_id(hex: &str) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from_hex(hex.as_bytes()).expect("valid hex")
+}
+
+#[test]
+fn v2_with_all_references() -> Result {
+    let bundle = File::at(fixture("all.bundle"))?;
+    assert_eq!(bundle.header.version, Version::V2);
+    assert_eq!(bundle.header.object_hash, gix_hash::Kind::Sha1);
+    assert_eq!(bundle.header.filter, None);
+    assert!(bundle.header.prerequisites.is_empty());
+
+    let list_heads = std::fs::read(fixture("all.list-heads"))?;
+    let expected: Vec<_> = list_heads
+        .lines()
+        .map(|line| {
+            let (hex, name) = line.split_once_str(" ").expect("id and name");
+            (hex_to_id(hex.to_str().expect("ascii")), name.as_bstr().to_owned())
+        })
+        .collect();
+    let actual: Vec<_> = bundle.header.refs.iter().map(|r| (r.id, r.name.clone())).collect();
+    assert_eq!(
+        actual, expected,
+        "references are read in order, just like `git bundle list-heads` shows them"
+    );
+
+    let mut pack = bundle.pack_data()?;
+    let mut signature = [0; 4];
+    std::io::Read::read_exact(&mut pack, &mut signature)?;
+    assert_eq!(&signature, b"PACK", "the pack follows the header directly");
+    Ok(())
+}
+
+#[test]
+fn v2_with_prerequisites() -> Result {
+    let bundle = File::at(fixture("incremental.bundle"))?;
+    assert_eq!(bundle.header.prerequisites.len(), 1);
+    assert_eq!(
+        bundle.header.prerequisites[0].comment, "c2",
+        "git writes the subject of the commit"
+    );
+    assert_eq!(bundle.header.refs.len(), 1);
+    assert_eq!(bundle.header.refs[0].name, "refs/heads/main");
+
+    let prerequisite = bundle.header.prerequisites[0].id;
+    assert_eq!(
+        bundle.header.missing_prerequisites(|_| true).count(),
+        0,
+        "nothing is missing if everything is present"
+    );
+    let missing: Vec<_> = bundle
+        .header
+        .missing_prerequisites(|id| id != prerequisite)
+        .map(|p| p.id)
+        .collect();
+    assert_eq!(missing, [prerequisite]);
+    Ok(())
+}
+
+#[test]
+fn v3_with_object_format() -> Result {
+    let bundle = File::at(fixture("v3.bundle"))?;
+    assert_eq!(bundle.header.version, Version::V3);
+    assert_eq!(bundle.header.object_hash, gix_hash::Kind::Sha1);
+
+    let bundle = File::at(fixture("sha256.bundle"))?;
+    assert_eq!(bundle.header.version, Version::V3);
+    assert_eq!(bundle.header.object_hash, gix_hash::Kind::Sha256);
+    assert_eq!(bundle.header.refs.len(), 1);
+    assert_eq!(bundle.header.refs[0].name, "HEAD");
+    assert_eq!(bundle.header.refs[0].id.kind(), gix_hash::Kind::Sha256);
+    Ok(())
+}
+
+#[test]
+fn write_to_round_trips_all_headers_written_by_git() -> Result {
+    for name in ["all.bundle", "incremental.bundle", "v3.bundle", "sha256.bundle"] {
+        let path = fixture(name);
+        let bundle = File::at(&path)?;
+        let data = std::fs::read(&path)?;
+        let mut out = Vec::new();
+        bundle.header.write_to(&mut out)?;
+        assert_eq!(out.as_bstr(), data[..bundle.pack_offset as usize].as_bstr(), "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn write_to_needs_v3_for_capabilities() -> Result {
+    let mut header = Header::new(gix_hash::Kind::Sha256);
+    assert_eq!(header.version, Version::V3, "the lowest possible version is chosen");
+    header.version = Version::V2;
+    assert!(matches!(
+        header.write_to(&mut Vec::new()),
+        Err(encode::Error::VersionTooLow { .. })
+    ));
+
+    let mut header = Header::new(gix_hash::Kind::Sha1);
+    assert_eq!(header.version, Version::V2);
+    header.filter = Some("blob:none".into());
+    assert!(matches!(
+        header.write_to(&mut Vec::new()),
+        Err(encode::Error::VersionTooLow { .. })
+    ));
+    header.version = Version::V3;
+    header.prerequisites.push(Prerequisite {
+        id: gix_hash::Kind::Sha1.null(),
+        comment: Default::default(),
+    });
+    let mut out = Vec::new();
+    header.write_to(&mut out).expect("v3 can store filters");
+    assert_eq!(
+        out.as_bstr(),
+        "# v3 git bundle\n@object-format=sha1\n@filter=blob:none\n-0000000000000000000000000000000000000000\n\n"
+    );
+    assert_eq!(Header::from_read(&mut out.as_slice())?, header, "it round-trips");
+    Ok(())
+}
+
+mod from_read {
+    use gix_bundle::{decode, Header};
+
+    fn decode(data: &str) -> Result<Header, decode::Error> {
+        Header::from_read(&mut data.as_bytes())
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(matches!(
+            decode("# v1 git bundle\n\n"),
+            Err(decode::Error::Signature { .. })
+        ));
+        assert!(matches!(decode(""), Err(decode::Error::Signature { .. })));
+        assert!(matches!(decode("# v2 git bundle\n"), Err(decode::Error::UnexpectedEof)));
+        assert!(matches!(
+            decode("# v2 git bundle\n@object-format=sha1\n\n"),
+            Err(decode::Error::CapabilityInV2 { .. })
+        ));
+        assert!(matches!(
+            decode("# v3 git bundle\n@unknown\n\n"),
+            Err(decode::Error::UnknownCapability { .. })
+        ));
+        assert!(matches!(
+            decode("# v3 git bundle\n@object-format=md5\n\n"),
+            Err(decode::Error::ObjectFormat { .. })
+        ));
+        assert!(matches!(
+            decode("# v2 git bundle\n0000000000000000000000000000000000000000\n\n"),
+            Err(decode::Error::MissingRefName { .. })
+        ));
+        assert!(matches!(
+            decode("# v2 git bundle\n-000000000000000000000000000000000000000 comment\n\n"),
+            Err(decode::Error::InvalidObjectId { .. })
+        ));
+        assert!(matches!(
+            decode(
+                "# v3 git bundle\n@object-format=sha256\n0000000000000000000000000000000000000000 refs/heads/main\n\n"
+            ),
+            Err(decode::Error::ObjectHashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn the_reader_is_left_at_the_pack() -> crate::Result {
+        let data = b"# v2 git bundle\n0000000000000000000000000000000000000000 refs/heads/main\n\nPACK";
+        let mut read = &data[..];
+        let header = Header::from_read(&mut read)?;
+        assert_eq!(header.refs.len(), 1);
+        assert_eq!(read, b"PACK");
+        Ok(())
+    }
+}
+
+#[test]
+fn non_bundles_are_detected() {
+    let path = fixture("not-a-bundle");
+    assert!(!gix_bundle::is_bundle(&path));
+    assert!(!gix_bundle::is_bundle(&fixture("does-not-exist")));
+    assert!(gix_bundle::is_bundle(&fixture("all.bundle")));
+    assert!(matches!(
+        File::at(path),
+        Err(gix_bundle::file::Error::Decode {
+            source: decode::Error::Signature { .. },
+            ..
+        })
+    ));
+}
@@ -0,0 +1,25 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  git commit -q --allow-empty -m "c1"
+  git commit -q --allow-empty -m "c2"
+  git tag -a -m "annotated" v1
+  git commit -q --allow-empty -m "c3"
+  git branch other
+
+  git bundle create -q ../all.bundle --all
+  git bundle list-heads ../all.bundle > ../all.list-heads
+  git bundle create -q ../incremental.bundle v1..main
+  git bundle create -q --version=3 ../v3.bundle main
+)
+
+git init -q --object-format=sha256 sha256
+(cd sha256
+  git commit -q --allow-empty -m "initial"
+  git bundle create -q ../sha256.bundle HEAD
+)
+
+echo "not a bundle" > not-a-bundle
@@ -0,0 +1,203 @@
+//! Changed-path Bloom filters, which allow to quickly decide that a commit didn't change a given path.
+//!
+//! Each commit in a commit-graph may have a filter that contains all paths that changed compared to its first parent,
+//! along with all of their leading directories.
+use std::collections::BTreeSet;
+
+use bstr::{BStr, ByteSlice};
+
+/// The amount of bits in each byte of filter data.
+const BITS_PER_WORD: u64 = 8;
+
+/// The seeds of the two hashes that are combined to produce all hashes of a [`Key`].
+const SEED_0: u32 = 0x293a_e76f;
+const SEED_1: u32 = 0x7e64_6e2c;
+
+/// The parameters of all Bloom filters of a commit-graph file, as stored in the header of its `BDAT` chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Settings {
+    /// The version of the hash function, `1` for the original implementation of `git`, which sign-extends bytes
+    /// with the high bit set, or `2` for the correct implementation of the murmur3 hash.
+    pub hash_version: u32,
+    /// The number of hashes to compute for each path.
+    pub num_hashes: u32,
+    /// The number of bits to use in a filter per path it contains.
+    pub bits_per_entry: u32,
+    /// The maximum amount of paths, including leading directories, that a filter may contain before it is replaced
+    /// by a filter that contains everything. This value isn't stored on disk.
+    pub max_changed_paths: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            hash_version: 1,
+            num_hashes: 7,
+            bits_per_entry: 10,
+            max_changed_paths: 512,
+        }
+    }
+}
+
+/// The hashes of a path, which can be checked for in any filter using the same [`Settings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    hashes: Vec<u32>,
+}
+
+impl Key {
+    /// Compute the key of `path`, a slash-separated path relative to the root of the repository without trailing slash.
+    pub fn new(path: &BStr, settings: &Settings) -> Self {
+        let sign_extend = settings.hash_version == 1;
+        let hash0 = murmur3(SEED_0, path, sign_extend);
+        let hash1 = murmur3(SEED_1, path, sign_extend);
+        Key {
+            hashes: (0..settings.num_hashes)
+                .map(|i| hash0.wrapping_add(i.wrapping_mul(hash1)))
+                .collect(),
+        }
+    }
+}
+
+/// A Bloom filter as stored in a commit-graph file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilterRef<'a> {
+    /// The bits of the filter.
+    pub data: &'a [u8],
+}
+
+/// An owned Bloom filter, typically created to be written into a commit-graph file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Filter {
+    /// The bits of the filter.
+    pub data: Vec<u8>,
+}
+
+impl<'a> FilterRef<'a> {
+    /// Return `Some(false)` if the path of `key` is definitely not in this filter, `Some(true)` if it might be,
+    /// or `None` if this filter wasn't computed and thus can't tell.
+    pub fn contains(&self, key: &Key) -> Option<bool> {
+        let bits = self.data.len() as u64 * BITS_PER_WORD;
+        if bits == 0 {
+            return None;
+        }
+        Some(key.hashes.iter().all(|hash| {
+            let pos = u64::from(*hash) % bits;
+            self.data[(pos / BITS_PER_WORD) as usize] & bitmask(pos) != 0
+        }))
+    }
+
+    /// Return `true` if this filter contains everything as too many paths were changed.
+    pub fn is_large(&self) -> bool {
+        self.data == [0xff]
+    }
+
+    /// Copy this instance into a [`Filter`].
+    pub fn to_owned(&self) -> Filter {
+        Filter {
+            data: self.data.to_owned(),
+        }
+    }
+}
+
+impl Filter {
+    /// Create a filter for the given changed file `paths`, which also contains all of their leading directories.
+    ///
+    /// If there are more than [`Settings::max_changed_paths`] paths after adding the directories, the filter
+    /// will contain all paths.
+    pub fn from_changed_paths<'a>(paths: impl IntoIterator<Item = &'a BStr>, settings: &Settings) -> Self {
+        let mut all_paths = BTreeSet::new();
+        for path in paths {
+            let mut path = path;
+            loop {
+                if !all_paths.insert(path) {
+                    break;
+                }
+                match path.rfind_byte(b'/') {
+                    Some(pos) => path = path[..pos].as_bstr(),
+                    None => break,
+                }
+            }
+        }
+        if all_paths.len() > settings.max_changed_paths as usize {
+            return Filter::large();
+        }
+        let len =
+            ((all_paths.len() as u64 * u64::from(settings.bits_per_entry) + BITS_PER_WORD - 1) / BITS_PER_WORD).max(1);
+        let mut filter = Filter {
+            data: vec![0; len as usize],
+        };
+        for path in all_paths {
+            filter.add(&Key::new(path, settings));
+        }
+        filter
+    }
+
+    /// Create a filter that contains everything, as used for commits that change too many paths.
+    pub fn large() -> Self {
+        Filter { data: vec![0xff] }
+    }
+
+    /// Create a filter that doesn't know anything, as used for commits whose filter wasn't computed.
+    pub fn unknown() -> Self {
+        Filter { data: Vec::new() }
+    }
+
+    /// Add `key` to this filter, which must not be empty.
+    pub fn add(&mut self, key: &Key) {
+        let bits = self.data.len() as u64 * BITS_PER_WORD;
+        for hash in &key.hashes {
+            let pos = u64::from(*hash) % bits;
+            self.data[(pos / BITS_PER_WORD) as usize] |= bitmask(pos);
+        }
+    }
+
+    /// Return a borrowed version of this instance.
+    pub fn as_ref(&self) -> FilterRef<'_> {
+        FilterRef { data: &self.data }
+    }
+}
+
+fn bitmask(pos: u64) -> u8 {
+    1 << (pos & (BITS_PER_WORD - 1))
+}
+
+/// The 32 bit murmur3 hash of `data`, which like `git` treats bytes as signed if `sign_extend` is `true`.
+fn murmur3(seed: u32, data: &[u8], sign_extend: bool) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    let byte = |b: u8| -> u32 {
+        if sign_extend {
+            b as i8 as i32 as u32
+        } else {
+            u32::from(b)
+        }
+    };
+
+    let mut h = seed;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in chunks.by_ref() {
+        let mut k = byte(chunk[0]) | byte(chunk[1]) << 8 | byte(chunk[2]) << 16 | byte(chunk[3]) << 24;
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        h ^= k;
+        h = h.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut k = 0;
+        for (shift, b) in tail.iter().enumerate().rev() {
+            k ^= byte(*b) << (shift * 8);
+        }
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        h ^= k;
+    }
+
+    h ^= data.len() as u32;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2_ae35);
+    h ^= h >> 16;
+    h
+}
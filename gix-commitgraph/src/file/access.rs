@@ -4,7 +4,8 @@ use std::{
 };
 
 use crate::{
-    file::{self, commit::Commit, COMMIT_DATA_ENTRY_SIZE_SANS_HASH},
+    bloom,
+    file::{self, commit::Commit, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, GENERATION_DATA_OVERFLOW_MASK},
     File,
 };
 
@@ -15,6 +16,16 @@ impl File {
         self.base_graph_count
     }
 
+    /// The settings of the changed-path Bloom filters of all commits in this file, if it has any.
+    pub fn bloom_filter_settings(&self) -> Option<&bloom::Settings> {
+        self.bloom_filters.as_ref().map(|filters| &filters.settings)
+    }
+
+    /// Returns `true` if this file stores corrected commit dates, also known as generation numbers v2.
+    pub fn has_generation_data(&self) -> bool {
+        self.generation_data_offset.is_some()
+    }
+
     /// Returns the commit data for the commit located at the given lexicographical position.
     ///
     /// `pos` must range from 0 to `self.num_commits()`.
@@ -127,6 +138,37 @@ impl File {
         &self.data[start..][..entry_size]
     }
 
+    /// Returns the offset of the corrected commit date to the commit date of the commit at `pos`
+    /// from the Generation Data (GDA2) chunk, if present.
+    pub(crate) fn generation_offset_at(&self, pos: file::Position) -> Option<u64> {
+        let start = self.generation_data_offset? + pos.0 as usize * 4;
+        let offset = u32::from_be_bytes(self.data[start..][..4].try_into().expect("4 bytes"));
+        if offset & GENERATION_DATA_OVERFLOW_MASK == 0 {
+            return Some(offset.into());
+        }
+        let overflow = &self.data[self.generation_data_overflow_range.clone()?];
+        let start = (offset & !GENERATION_DATA_OVERFLOW_MASK) as usize * 8;
+        overflow
+            .get(start..start + 8)
+            .map(|bytes| u64::from_be_bytes(bytes.try_into().expect("8 bytes")))
+    }
+
+    /// Returns the changed-path Bloom filter of the commit at `pos` from the Bloom Filter Index (BIDX) and Data (BDAT) chunks,
+    /// if present.
+    pub(crate) fn bloom_filter_at(&self, pos: file::Position) -> Option<bloom::FilterRef<'_>> {
+        let filters = self.bloom_filters.as_ref()?;
+        let end_at = |pos: u32| {
+            let start = filters.indexes_offset + pos as usize * 4;
+            u32::from_be_bytes(self.data[start..][..4].try_into().expect("4 bytes")) as usize
+        };
+        let start = if pos.0 == 0 { 0 } else { end_at(pos.0 - 1) };
+        let end = end_at(pos.0);
+        let data = &self.data[filters.data_range.clone()];
+        Some(bloom::FilterRef {
+            data: data.get(start..end)?,
+        })
+    }
+
     /// Returns the byte slice for this file's entire Extra Edge List (EDGE) chunk.
     pub(crate) fn extra_edges_data(&self) -> Option<&[u8]> {
         Some(&self.data[self.extra_edges_list_range.clone()?])
@@ -66,6 +66,20 @@ impl<'a> Commit<'a> {
         self.commit_timestamp
     }
 
+    /// Returns the corrected commit date of this commit, also known as generation number v2, if the file stores them.
+    ///
+    /// It is the largest of the committer timestamp and the corrected commit dates of all parents plus one.
+    pub fn corrected_commit_date(&self) -> Option<u64> {
+        self.file
+            .generation_offset_at(self.pos)
+            .map(|offset| self.commit_timestamp + offset)
+    }
+
+    /// Returns the changed-path Bloom filter of this commit, if the file stores them.
+    pub fn changed_paths_filter(&self) -> Option<crate::bloom::FilterRef<'a>> {
+        self.file.bloom_filter_at(self.pos)
+    }
+
     /// Returns the generation number of this commit.
     ///
     /// Commits without parents have generation number 1. Commits with parents have a generation
@@ -3,8 +3,10 @@ use std::path::PathBuf;
 
 use crate::{
     file::{
-        ChunkId, BASE_GRAPHS_LIST_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
-        EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN, HEADER_LEN, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+        BloomFilters, ChunkId, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_DATA_CHUNK_ID, BLOOM_DATA_HEADER_LEN,
+        BLOOM_INDEXES_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, EXTENDED_EDGES_LIST_CHUNK_ID,
+        FAN_LEN, GENERATION_DATA_CHUNK_ID, GENERATION_DATA_OVERFLOW_CHUNK_ID, HEADER_LEN, OID_FAN_CHUNK_ID,
+        OID_LOOKUP_CHUNK_ID, SIGNATURE,
     },
     File,
 };
@@ -184,6 +186,61 @@ impl File {
 
         let extra_edges_list_range = chunks.usize_offset_by_id(EXTENDED_EDGES_LIST_CHUNK_ID).ok();
 
+        let generation_data_offset = chunks
+            .validated_usize_offset_by_id(GENERATION_DATA_CHUNK_ID, |chunk_range| {
+                let chunk_size = chunk_range.len();
+                if chunk_size != commit_data_count as usize * 4 {
+                    return Err(Error::InvalidChunkSize {
+                        id: GENERATION_DATA_CHUNK_ID,
+                        msg: format!(
+                            "expected chunk length {}, got {chunk_size}",
+                            commit_data_count as usize * 4
+                        ),
+                    });
+                }
+                Ok(chunk_range.start)
+            })
+            .ok()
+            .transpose()?;
+        let generation_data_overflow_range = chunks.usize_offset_by_id(GENERATION_DATA_OVERFLOW_CHUNK_ID).ok();
+
+        let bloom_filters = match (
+            chunks.usize_offset_by_id(BLOOM_INDEXES_CHUNK_ID).ok(),
+            chunks.usize_offset_by_id(BLOOM_DATA_CHUNK_ID).ok(),
+        ) {
+            (Some(indexes), Some(data_range)) => {
+                if indexes.len() != commit_data_count as usize * 4 {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_INDEXES_CHUNK_ID,
+                        msg: format!(
+                            "expected chunk length {}, got {}",
+                            commit_data_count as usize * 4,
+                            indexes.len()
+                        ),
+                    });
+                }
+                if data_range.len() < BLOOM_DATA_HEADER_LEN {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_DATA_CHUNK_ID,
+                        msg: format!("chunk is too small for its header: {}", data_range.len()),
+                    });
+                }
+                let header = &data[data_range.start..][..BLOOM_DATA_HEADER_LEN];
+                let read_u32 = |ofs: usize| u32::from_be_bytes(header[ofs..][..4].try_into().expect("4 bytes"));
+                Some(BloomFilters {
+                    indexes_offset: indexes.start,
+                    data_range: data_range.start + BLOOM_DATA_HEADER_LEN..data_range.end,
+                    settings: crate::bloom::Settings {
+                        hash_version: read_u32(0),
+                        num_hashes: read_u32(4),
+                        bits_per_entry: read_u32(8),
+                        ..Default::default()
+                    },
+                })
+            }
+            _ => None,
+        };
+
         let trailer = &data[chunks.highest_offset() as usize..];
         if trailer.len() != object_hash.len_in_bytes() {
             return Err(Error::Trailer(format!(
@@ -220,10 +277,13 @@ impl File {
         Ok(File {
             base_graph_count,
             base_graphs_list_offset,
+            bloom_filters,
             commit_data_offset,
             data,
             extra_edges_list_range,
             fan,
+            generation_data_offset,
+            generation_data_overflow_range,
             oid_lookup_offset,
             path,
             hash_len: object_hash.len_in_bytes(),
@@ -8,6 +8,7 @@ mod access;
 pub mod commit;
 mod init;
 pub mod verify;
+mod write;
 
 const COMMIT_DATA_ENTRY_SIZE_SANS_HASH: usize = 16;
 pub(crate) const FAN_LEN: usize = 256;
@@ -17,16 +18,25 @@ const SIGNATURE: &[u8] = b"CGPH";
 
 type ChunkId = gix_chunk::Id;
 const BASE_GRAPHS_LIST_CHUNK_ID: ChunkId = *b"BASE";
+const BLOOM_DATA_CHUNK_ID: ChunkId = *b"BDAT";
+const BLOOM_INDEXES_CHUNK_ID: ChunkId = *b"BIDX";
 const COMMIT_DATA_CHUNK_ID: ChunkId = *b"CDAT";
 const EXTENDED_EDGES_LIST_CHUNK_ID: ChunkId = *b"EDGE";
+const GENERATION_DATA_CHUNK_ID: ChunkId = *b"GDA2";
+const GENERATION_DATA_OVERFLOW_CHUNK_ID: ChunkId = *b"GDO2";
 const OID_FAN_CHUNK_ID: ChunkId = *b"OIDF";
 const OID_LOOKUP_CHUNK_ID: ChunkId = *b"OIDL";
 
+/// The size of the header of the `BDAT` chunk, holding the [bloom filter settings](crate::bloom::Settings).
+const BLOOM_DATA_HEADER_LEN: usize = 12;
+
 // Note that git's commit-graph-format.txt as of v2.28.0 gives an incorrect value 0x0700_0000 for
 // NO_PARENT. Fixed in https://github.com/git/git/commit/4d515253afcef985e94400adbfed7044959f9121 .
 const NO_PARENT: u32 = 0x7000_0000;
 const EXTENDED_EDGES_MASK: u32 = 0x8000_0000;
 const LAST_EXTENDED_EDGE_MASK: u32 = 0x8000_0000;
+/// Marks an entry of the `GDA2` chunk as index into the `GDO2` chunk.
+const GENERATION_DATA_OVERFLOW_MASK: u32 = 0x8000_0000;
 
 /// The position of a given commit within a graph file, starting at 0.
 ///
@@ -44,3 +54,13 @@ impl Display for Position {
         self.0.fmt(f)
     }
 }
+
+/// The location of the changed-path Bloom filters of a file, along with their settings.
+#[derive(Debug, Clone)]
+pub(crate) struct BloomFilters {
+    /// The offset of the `BIDX` chunk.
+    pub indexes_offset: usize,
+    /// The range of the filter data in the `BDAT` chunk, past its header.
+    pub data_range: std::ops::Range<usize>,
+    pub settings: crate::bloom::Settings,
+}
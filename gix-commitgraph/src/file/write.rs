@@ -0,0 +1,316 @@
+use std::io::Write;
+
+use gix_hash::ObjectId;
+
+use crate::{
+    bloom,
+    file::{
+        BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_DATA_CHUNK_ID, BLOOM_DATA_HEADER_LEN, BLOOM_INDEXES_CHUNK_ID,
+        COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, EXTENDED_EDGES_LIST_CHUNK_ID, EXTENDED_EDGES_MASK,
+        FAN_LEN, GENERATION_DATA_CHUNK_ID, GENERATION_DATA_OVERFLOW_CHUNK_ID, GENERATION_DATA_OVERFLOW_MASK,
+        LAST_EXTENDED_EDGE_MASK, NO_PARENT, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+    },
+    write::{base_has_generation_data, Commit, Error, Options},
+    File, Graph, GENERATION_NUMBER_MAX, MAX_COMMITS,
+};
+
+/// The largest offset of a corrected commit date to the commit date that fits into the `GDA2` chunk.
+const GENERATION_DATA_OFFSET_MAX: u64 = 0x7fff_ffff;
+
+/// Writing
+impl File {
+    /// Write a commit-graph file with all `commits` to `out` and return its checksum, configured by `options`.
+    ///
+    /// If `base` is given, the file becomes the next one in its chain, and all parents of `commits` must either be
+    /// in `base` or in `commits` themselves. Commits that are already in `base` are skipped.
+    /// [`Options::split`] is ignored.
+    pub fn write_from_commits(
+        mut commits: Vec<Commit>,
+        base: Option<&Graph>,
+        out: &mut dyn Write,
+        options: Options,
+    ) -> Result<ObjectId, Error> {
+        commits.sort_by_key(|commit| commit.id);
+        commits.dedup_by(|a, b| a.id == b.id);
+        if let Some(base) = base {
+            commits.retain(|commit| base.lookup(commit.id).is_none());
+        }
+        let base_commits = base.map_or(0, Graph::num_commits);
+        if commits.len() as u64 + u64::from(base_commits) > u64::from(MAX_COMMITS) {
+            return Err(Error::TooManyCommits(commits.len() + base_commits as usize));
+        }
+        let num_base_graphs = base.map_or(0, |base| base.files.len());
+        let num_base_graphs = u8::try_from(num_base_graphs).map_err(|_| Error::TooManyBaseGraphs(num_base_graphs))?;
+
+        let positions = ParentPositions {
+            commits: &commits,
+            base,
+            base_commits,
+        };
+        let generations = compute_generations(&commits, &positions)?;
+        let write_generation_data = options.generation_data && base_has_generation_data(base);
+
+        let hash_len = options.object_hash.len_in_bytes();
+        let mut extra_edges = Vec::<u32>::new();
+        let mut generation_data = Vec::with_capacity(commits.len());
+        let mut generation_data_overflow = Vec::<u64>::new();
+        let mut parent_edges = Vec::with_capacity(commits.len());
+        for (commit, generation) in commits.iter().zip(&generations) {
+            let parent = |id: &ObjectId| {
+                positions
+                    .get(id)
+                    .expect("presence was checked when computing generations")
+            };
+            let parent1 = commit.parents.first().map_or(NO_PARENT, parent);
+            let parent2 = match commit.parents.len() {
+                0 | 1 => NO_PARENT,
+                2 => parent(&commit.parents[1]),
+                _ => {
+                    let edge_index = extra_edges.len() as u32;
+                    extra_edges.extend(commit.parents[1..].iter().map(parent));
+                    *extra_edges.last_mut().expect("at least two extra parents") |= LAST_EXTENDED_EDGE_MASK;
+                    EXTENDED_EDGES_MASK | edge_index
+                }
+            };
+            parent_edges.push((parent1, parent2));
+
+            let offset = generation.corrected_commit_date - commit.commit_time;
+            generation_data.push(if offset > GENERATION_DATA_OFFSET_MAX {
+                generation_data_overflow.push(offset);
+                GENERATION_DATA_OVERFLOW_MASK | (generation_data_overflow.len() as u32 - 1)
+            } else {
+                offset as u32
+            });
+        }
+        let bloom_filters = options.changed_paths.map(|settings| {
+            let unknown = bloom::Filter::unknown();
+            let filters: Vec<_> = commits
+                .iter()
+                .map(|commit| commit.changed_paths.as_ref().unwrap_or(&unknown).data.clone())
+                .collect();
+            (settings, filters)
+        });
+
+        let mut chunks = gix_chunk::file::Index::for_writing();
+        chunks.plan_chunk(OID_FAN_CHUNK_ID, (FAN_LEN * 4) as u64);
+        chunks.plan_chunk(OID_LOOKUP_CHUNK_ID, (commits.len() * hash_len) as u64);
+        chunks.plan_chunk(
+            COMMIT_DATA_CHUNK_ID,
+            (commits.len() * (hash_len + COMMIT_DATA_ENTRY_SIZE_SANS_HASH)) as u64,
+        );
+        if write_generation_data {
+            chunks.plan_chunk(GENERATION_DATA_CHUNK_ID, (commits.len() * 4) as u64);
+            if !generation_data_overflow.is_empty() {
+                chunks.plan_chunk(
+                    GENERATION_DATA_OVERFLOW_CHUNK_ID,
+                    (generation_data_overflow.len() * 8) as u64,
+                );
+            }
+        }
+        if !extra_edges.is_empty() {
+            chunks.plan_chunk(EXTENDED_EDGES_LIST_CHUNK_ID, (extra_edges.len() * 4) as u64);
+        }
+        if let Some((_, filters)) = &bloom_filters {
+            chunks.plan_chunk(BLOOM_INDEXES_CHUNK_ID, (commits.len() * 4) as u64);
+            chunks.plan_chunk(
+                BLOOM_DATA_CHUNK_ID,
+                (BLOOM_DATA_HEADER_LEN + filters.iter().map(Vec::len).sum::<usize>()) as u64,
+            );
+        }
+        if let Some(base) = base {
+            chunks.plan_chunk(BASE_GRAPHS_LIST_CHUNK_ID, (base.files.len() * hash_len) as u64);
+        }
+
+        let mut out = HashWrite {
+            inner: out,
+            hasher: gix_features::hash::hasher(options.object_hash),
+        };
+        out.write_all(SIGNATURE)?;
+        out.write_all(&[1 /* version */, options.object_hash as u8])?;
+        out.write_all(&[
+            chunks.num_chunks().try_into().expect("less than 256 chunks"),
+            num_base_graphs,
+        ])?;
+        let mut out = chunks.into_write(out, SIGNATURE.len() + 4)?;
+        while let Some(chunk) = out.next_chunk() {
+            match chunk {
+                OID_FAN_CHUNK_ID => {
+                    let mut fan = [0u32; FAN_LEN];
+                    for commit in &commits {
+                        fan[usize::from(commit.id.first_byte())] += 1;
+                    }
+                    let mut count = 0;
+                    for entry in fan {
+                        count += entry;
+                        out.write_all(&count.to_be_bytes())?;
+                    }
+                }
+                OID_LOOKUP_CHUNK_ID => {
+                    for commit in &commits {
+                        out.write_all(commit.id.as_slice())?;
+                    }
+                }
+                COMMIT_DATA_CHUNK_ID => {
+                    for ((commit, generation), (parent1, parent2)) in
+                        commits.iter().zip(&generations).zip(&parent_edges)
+                    {
+                        out.write_all(commit.tree.as_slice())?;
+                        out.write_all(&parent1.to_be_bytes())?;
+                        out.write_all(&parent2.to_be_bytes())?;
+                        let time_high_bits = ((commit.commit_time >> 32) & 0x3) as u32;
+                        out.write_all(&(generation.level << 2 | time_high_bits).to_be_bytes())?;
+                        out.write_all(&(commit.commit_time as u32).to_be_bytes())?;
+                    }
+                }
+                GENERATION_DATA_CHUNK_ID => {
+                    for offset in &generation_data {
+                        out.write_all(&offset.to_be_bytes())?;
+                    }
+                }
+                GENERATION_DATA_OVERFLOW_CHUNK_ID => {
+                    for offset in &generation_data_overflow {
+                        out.write_all(&offset.to_be_bytes())?;
+                    }
+                }
+                EXTENDED_EDGES_LIST_CHUNK_ID => {
+                    for edge in &extra_edges {
+                        out.write_all(&edge.to_be_bytes())?;
+                    }
+                }
+                BLOOM_INDEXES_CHUNK_ID => {
+                    let (_, filters) = bloom_filters.as_ref().expect("planned only with filters");
+                    let mut end = 0u32;
+                    for filter in filters {
+                        end += filter.len() as u32;
+                        out.write_all(&end.to_be_bytes())?;
+                    }
+                }
+                BLOOM_DATA_CHUNK_ID => {
+                    let (settings, filters) = bloom_filters.as_ref().expect("planned only with filters");
+                    for value in [settings.hash_version, settings.num_hashes, settings.bits_per_entry] {
+                        out.write_all(&value.to_be_bytes())?;
+                    }
+                    for filter in filters {
+                        out.write_all(filter)?;
+                    }
+                }
+                BASE_GRAPHS_LIST_CHUNK_ID => {
+                    for file in &base.expect("planned only with base").files {
+                        out.write_all(file.checksum().as_bytes())?;
+                    }
+                }
+                unknown => unreachable!("BUG: forgot to implement chunk {:?}", std::str::from_utf8(&unknown)),
+            }
+        }
+
+        let HashWrite { inner, hasher } = out.into_inner();
+        let checksum = hasher.digest();
+        inner.write_all(checksum.as_slice())?;
+        Ok(checksum)
+    }
+}
+
+/// The generation numbers of a commit.
+#[derive(Debug, Clone, Copy, Default)]
+struct Generation {
+    /// The topological level, or generation number v1, capped at [`GENERATION_NUMBER_MAX`].
+    level: u32,
+    /// The corrected commit date, or generation number v2.
+    corrected_commit_date: u64,
+}
+
+/// Translates commit ids to graph positions, with the commits to write being placed after all commits of `base`.
+struct ParentPositions<'a> {
+    commits: &'a [Commit],
+    base: Option<&'a Graph>,
+    base_commits: u32,
+}
+
+enum Parent<'a> {
+    New(usize),
+    Base(crate::file::Commit<'a>),
+}
+
+impl<'a> ParentPositions<'a> {
+    fn find(&self, id: &ObjectId) -> Option<Parent<'a>> {
+        match self.commits.binary_search_by(|commit| commit.id.cmp(id)) {
+            Ok(idx) => Some(Parent::New(idx)),
+            Err(_) => self.base.and_then(|base| base.commit_by_id(id)).map(Parent::Base),
+        }
+    }
+
+    fn get(&self, id: &ObjectId) -> Option<u32> {
+        Some(match self.find(id)? {
+            Parent::New(idx) => self.base_commits + idx as u32,
+            Parent::Base(_) => self.base?.lookup(id)?.0,
+        })
+    }
+}
+
+/// Compute the generation numbers of all `commits`, which must be sorted by id, without recursion.
+fn compute_generations(commits: &[Commit], positions: &ParentPositions<'_>) -> Result<Vec<Generation>, Error> {
+    let mut generations: Vec<Option<Generation>> = vec![None; commits.len()];
+    let mut stack = Vec::new();
+    for start in 0..commits.len() {
+        if generations[start].is_some() {
+            continue;
+        }
+        stack.push(start);
+        'next_on_stack: while let Some(&idx) = stack.last() {
+            let commit = &commits[idx];
+            let mut generation = Generation {
+                level: 1,
+                corrected_commit_date: commit.commit_time,
+            };
+            for parent_id in &commit.parents {
+                let parent = match positions.find(parent_id).ok_or(Error::MissingParent {
+                    id: commit.id,
+                    parent: *parent_id,
+                })? {
+                    Parent::New(parent_idx) => match generations[parent_idx] {
+                        Some(parent) => parent,
+                        None => {
+                            stack.push(parent_idx);
+                            continue 'next_on_stack;
+                        }
+                    },
+                    Parent::Base(parent) => Generation {
+                        level: parent.generation(),
+                        corrected_commit_date: parent
+                            .corrected_commit_date()
+                            .unwrap_or_else(|| parent.committer_timestamp()),
+                    },
+                };
+                generation.level = generation
+                    .level
+                    .max(parent.level.saturating_add(1).min(GENERATION_NUMBER_MAX));
+                generation.corrected_commit_date =
+                    generation.corrected_commit_date.max(parent.corrected_commit_date + 1);
+            }
+            generations[idx] = Some(generation);
+            stack.pop();
+        }
+    }
+    Ok(generations
+        .into_iter()
+        .map(|generation| generation.expect("all computed"))
+        .collect())
+}
+
+/// A writer which hashes everything written to `inner`.
+struct HashWrite<'a> {
+    inner: &'a mut dyn Write,
+    hasher: gix_features::hash::Hasher,
+}
+
+impl<'a> Write for HashWrite<'a> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use gix_hash::ObjectId;
+
+use crate::{bloom, File, Graph};
+
+/// A commit to write into a commit-graph file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// The id of the commit.
+    pub id: ObjectId,
+    /// The id of the tree the commit points to.
+    pub tree: ObjectId,
+    /// The ids of all parents of the commit, in order.
+    pub parents: Vec<ObjectId>,
+    /// The committer timestamp in seconds since the unix epoch.
+    pub commit_time: u64,
+    /// The filter of all paths changed compared to the first parent, or `None` if it wasn't computed.
+    ///
+    /// It's only written if [`Options::changed_paths`] is set.
+    pub changed_paths: Option<bloom::Filter>,
+}
+
+/// Configure how a commit-graph is split into multiple files, like `git commit-graph write --split` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Split {
+    /// A layer is merged into the new one if it doesn't have more than `size_multiple` times the commits
+    /// of the new layer.
+    ///
+    /// Defaults to 2.
+    pub size_multiple: u32,
+    /// If set, layers are merged into the new one as long as it has more than this amount of commits.
+    pub max_commits: Option<u32>,
+}
+
+impl Default for Split {
+    fn default() -> Self {
+        Split {
+            size_multiple: 2,
+            max_commits: None,
+        }
+    }
+}
+
+/// Options for use in [`File::write_from_commits()`] and [`Graph::write_to_info_dir()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The kind of hash of all object ids.
+    pub object_hash: gix_hash::Kind,
+    /// If `true`, write corrected commit dates, also known as generation numbers v2, unless a base graph lacks them.
+    pub generation_data: bool,
+    /// If set, write the changed-path Bloom filters of commits with these settings.
+    pub changed_paths: Option<bloom::Settings>,
+    /// If set, add a new file to a chain of commit-graph files instead of writing a single file with all commits.
+    pub split: Option<Split>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            object_hash: gix_hash::Kind::Sha1,
+            generation_data: true,
+            changed_paths: None,
+            split: None,
+        }
+    }
+}
+
+/// The result of [`Graph::write_to_info_dir()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The path of the file that was written, or `None` if there were no new commits to write into a split graph.
+    pub path: Option<PathBuf>,
+    /// The amount of commits in the written file.
+    pub num_commits: u32,
+    /// The amount of files the commit-graph consists of after writing.
+    pub num_files: usize,
+}
+
+mod error {
+    use gix_hash::ObjectId;
+
+    /// The error returned by [`File::write_from_commits()`][crate::File::write_from_commits()] and
+    /// [`Graph::write_to_info_dir()`][crate::Graph::write_to_info_dir()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Parent {parent} of commit {id} is neither part of the commits to write nor of the base graph")]
+        MissingParent { id: ObjectId, parent: ObjectId },
+        #[error("A commit-graph can only have up to {} commits, got {0}", crate::MAX_COMMITS)]
+        TooManyCommits(usize),
+        #[error("A commit-graph can only be built on top of up to 255 files, got {0}")]
+        TooManyBaseGraphs(usize),
+        #[error(transparent)]
+        Commit(#[from] crate::file::commit::Error),
+        #[error(transparent)]
+        Init(#[from] crate::init::Error),
+        #[error("Could not lock a commit-graph file for writing")]
+        LockAcquire(#[from] gix_lock::acquire::Error),
+        #[error("Could not commit a commit-graph file")]
+        LockCommit(#[from] gix_lock::commit::Error<gix_lock::File>),
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+    }
+}
+pub use error::Error;
+
+const CHAIN_FILE_NAME: &str = "commit-graph-chain";
+
+/// Writing
+impl Graph {
+    /// Write `commits` as commit-graph into the `objects/info` directory at `info_dir`, configured by `options`.
+    ///
+    /// Without [`Options::split`], a single `commit-graph` file with all `commits` is written, which must include all
+    /// their ancestors, and a previous chain of commit-graph files is removed.
+    ///
+    /// With [`Options::split`], the `commits` that aren't already part of the commit-graph are written into a new file
+    /// on top of the chain in `info_dir/commit-graphs`, merging the files at the top of the chain into it if these
+    /// aren't sufficiently larger. An existing single `commit-graph` file is always merged into the new file.
+    /// Files that are no longer part of the chain are deleted right away.
+    pub fn write_to_info_dir(info_dir: &Path, commits: Vec<Commit>, options: Options) -> Result<Outcome, Error> {
+        let graphs_dir = info_dir.join("commit-graphs");
+        let single_graph_path = info_dir.join("commit-graph");
+        let chain_path = graphs_dir.join(CHAIN_FILE_NAME);
+        let Some(split) = options.split else {
+            let mut lock = gix_lock::File::acquire_to_update_resource(
+                &single_graph_path,
+                gix_lock::acquire::Fail::Immediately,
+                None,
+            )?;
+            let mut buf = Vec::new();
+            let num_commits = commits.len();
+            File::write_from_commits(commits, None, &mut buf, options)?;
+            lock.write_all(&buf)?;
+            lock.commit()?;
+            if chain_path.is_file() {
+                remove_chain(&graphs_dir, &chain_path)?;
+            }
+            return Ok(Outcome {
+                path: Some(single_graph_path),
+                num_commits: num_commits as u32,
+                num_files: 1,
+            });
+        };
+
+        std::fs::create_dir_all(&graphs_dir)?;
+        let mut lock =
+            gix_lock::File::acquire_to_update_resource(&chain_path, gix_lock::acquire::Fail::Immediately, None)?;
+        let (mut layers, must_merge_all) = if chain_path.is_file() {
+            (Graph::from_commit_graphs_dir(&graphs_dir)?.files, false)
+        } else if single_graph_path.is_file() {
+            (Graph::from_file(&single_graph_path)?.files, true)
+        } else {
+            (Vec::new(), false)
+        };
+
+        let existing = Graph { files: layers };
+        let mut commits: Vec<_> = commits
+            .into_iter()
+            .filter(|commit| existing.lookup(commit.id).is_none())
+            .collect();
+        if commits.is_empty() {
+            return Ok(Outcome {
+                path: None,
+                num_commits: 0,
+                num_files: existing.files.len(),
+            });
+        }
+
+        let mut num_kept = existing.files.len();
+        let mut num_commits = commits.len() as u64;
+        while num_kept > 0 {
+            let top = &existing.files[num_kept - 1];
+            let should_merge = must_merge_all
+                || u64::from(top.num_commits()) <= u64::from(split.size_multiple) * num_commits
+                || matches!(split.max_commits, Some(max) if num_commits > u64::from(max));
+            if !should_merge {
+                break;
+            }
+            num_commits += u64::from(top.num_commits());
+            num_kept -= 1;
+        }
+
+        for layer in &existing.files[num_kept..] {
+            let reusable_filters = match (layer.bloom_filter_settings(), options.changed_paths.as_ref()) {
+                (Some(layer), Some(wanted)) => {
+                    (layer.hash_version, layer.num_hashes, layer.bits_per_entry)
+                        == (wanted.hash_version, wanted.num_hashes, wanted.bits_per_entry)
+                }
+                _ => false,
+            };
+            for commit in layer.iter_commits() {
+                commits.push(Commit {
+                    id: commit.id().to_owned(),
+                    tree: commit.root_tree_id().to_owned(),
+                    parents: commit
+                        .iter_parents()
+                        .map(|pos| pos.map(|pos| existing.id_at(pos).to_owned()))
+                        .collect::<Result<_, _>>()?,
+                    commit_time: commit.committer_timestamp(),
+                    changed_paths: reusable_filters
+                        .then(|| commit.changed_paths_filter().map(|filter| filter.to_owned()))
+                        .flatten(),
+                });
+            }
+        }
+
+        layers = existing.files;
+        let merged: Vec<_> = layers.drain(num_kept..).map(|file| file.path().to_owned()).collect();
+        let base = Graph { files: layers };
+        let mut buf = Vec::new();
+        let num_commits = commits.len();
+        let checksum = File::write_from_commits(commits, (!base.files.is_empty()).then_some(&base), &mut buf, options)?;
+
+        let graph_path = graphs_dir.join(format!("graph-{}.graph", checksum.to_hex()));
+        let mut graph_lock =
+            gix_lock::File::acquire_to_update_resource(&graph_path, gix_lock::acquire::Fail::Immediately, None)?;
+        graph_lock.write_all(&buf)?;
+        graph_lock.commit()?;
+
+        for file in &base.files {
+            writeln!(lock, "{}", file.checksum())?;
+        }
+        writeln!(lock, "{checksum}")?;
+        lock.commit()?;
+
+        for path in merged {
+            std::fs::remove_file(path).ok();
+        }
+        Ok(Outcome {
+            path: Some(graph_path),
+            num_commits: num_commits as u32,
+            num_files: base.files.len() + 1,
+        })
+    }
+}
+
+/// Remove the chain file at `chain_path` along with all graph files in `graphs_dir`.
+fn remove_chain(graphs_dir: &Path, chain_path: &Path) -> std::io::Result<()> {
+    std::fs::remove_file(chain_path)?;
+    for entry in std::fs::read_dir(graphs_dir)? {
+        let path = entry?.path();
+        let is_graph = matches!(
+            path.file_name().and_then(|name| name.to_str()),
+            Some(name) if name.starts_with("graph-") && name.ends_with(".graph")
+        );
+        if is_graph {
+            std::fs::remove_file(path)?;
+        }
+    }
+    std::fs::remove_dir(graphs_dir).ok();
+    Ok(())
+}
+
+/// Return `true` if commits written on top of `base` can store corrected commit dates.
+pub(crate) fn base_has_generation_data(base: Option<&Graph>) -> bool {
+    match base {
+        None => true,
+        Some(graph) => graph.files.iter().all(File::has_generation_data),
+    }
+}
@@ -0,0 +1,44 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commitGraph.generationVersion 2
+
+mkdir -p a/b
+echo 1 > a/b/c
+echo 1 > d
+git add .
+git commit -q -m root
+git tag root
+
+echo 2 > a/b/c
+git commit -q -am nested
+git tag nested
+
+mkdir many
+for i in $(seq 600); do echo $i > many/$i; done
+git add many
+git commit -q -m many
+git tag many
+
+git rm -q d
+git commit -q -m delete
+git tag delete
+
+git checkout -q -b side nested
+echo 3 > e
+git add e
+git commit -q -m side
+git tag side
+
+git checkout -q -b other nested
+echo 4 > f
+git add f
+git commit -q -m other
+git tag other
+
+git checkout -q main 2>/dev/null || git checkout -q master
+git merge -q -m octopus --no-ff side other >/dev/null
+git tag octopus
+
+git commit-graph write --no-progress --reachable --changed-paths
@@ -0,0 +1,251 @@
+use std::{path::Path, process::Command};
+
+use gix_commitgraph::{bloom, write, File, Graph};
+use gix_testtools::{scripted_fixture_read_only, scripted_fixture_writable};
+
+use crate::graph_and_expected;
+
+fn commits(graph: &Graph) -> Vec<write::Commit> {
+    graph
+        .iter_commits()
+        .map(|commit| write::Commit {
+            id: commit.id().to_owned(),
+            tree: commit.root_tree_id().to_owned(),
+            parents: commit
+                .iter_parents()
+                .map(|pos| graph.id_at(pos.expect("valid parent")).to_owned())
+                .collect(),
+            commit_time: commit.committer_timestamp(),
+            changed_paths: commit.changed_paths_filter().map(|filter| filter.to_owned()),
+        })
+        .collect()
+}
+
+fn options_like(file: &File) -> write::Options {
+    write::Options {
+        object_hash: file.object_hash(),
+        generation_data: file.has_generation_data(),
+        changed_paths: file.bloom_filter_settings().copied(),
+        split: None,
+    }
+}
+
+fn assert_git_verifies(repo_dir: &Path) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo_dir)
+        .args(["commit-graph", "verify", "--no-progress"])
+        .env_remove("GIT_DIR")
+        .status()
+        .expect("git can be executed");
+    assert!(status.success(), "git considers the commit-graph valid");
+}
+
+#[test]
+fn rewriting_graphs_of_git_preserves_all_commits() -> gix_testtools::Result {
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    for (script, name) in [
+        ("single_commit.sh", ""),
+        ("single_parent.sh", ""),
+        ("two_parents.sh", ""),
+        ("octopus_merges.sh", ""),
+        ("single_commit_huge_dates.sh", "v1"),
+        ("single_commit_huge_dates.sh", "max-date"),
+    ] {
+        let expected = Graph::from_info_dir(&scripted_fixture_read_only(script)?.join(name).join(".git/objects/info"))?;
+        let path = tmp.path().join("commit-graph");
+        let mut buf = Vec::new();
+        let checksum = File::write_from_commits(commits(&expected), None, &mut buf, write::Options::default())?;
+        std::fs::write(&path, buf)?;
+
+        let actual = Graph::from_file(&path)?;
+        actual.verify_integrity(|_| Ok::<_, std::convert::Infallible>(()))?;
+        assert_eq!(File::at(&path)?.checksum(), checksum);
+        assert_eq!(commits(&actual), commits(&expected), "{script} {name}");
+        for (actual, expected) in actual.iter_commits().zip(expected.iter_commits()) {
+            assert_eq!(actual.generation(), expected.generation(), "{script} {name}");
+            assert!(
+                actual
+                    .corrected_commit_date()
+                    .expect("generation data is written by default")
+                    >= actual.committer_timestamp(),
+                "{script} {name}"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn rewriting_a_graph_of_git_yields_the_same_file() -> gix_testtools::Result {
+    let path = scripted_fixture_read_only("changed_paths.sh")?.join(".git/objects/info/commit-graph");
+    let expected = std::fs::read(&path)?;
+    let file = File::at(&path)?;
+    assert!(file.has_generation_data());
+    assert!(file.bloom_filter_settings().is_some());
+
+    let mut actual = Vec::new();
+    let checksum = File::write_from_commits(
+        commits(&Graph::from_file(&path)?),
+        None,
+        &mut actual,
+        options_like(&file),
+    )?;
+    assert_eq!(checksum, file.checksum());
+    assert!(actual == expected, "written files are byte-identical");
+    Ok(())
+}
+
+#[test]
+fn changed_path_filters_are_computed_like_git() {
+    let (graph, refs) = graph_and_expected(
+        "changed_paths.sh",
+        &["root", "nested", "many", "delete", "side", "other", "octopus"],
+    );
+    let settings = bloom::Settings::default();
+    for (name, paths) in [
+        ("root", &["a/b/c", "d"][..]),
+        ("nested", &["a/b/c"]),
+        ("delete", &["d"]),
+        ("side", &["e"]),
+        ("other", &["f"]),
+        ("octopus", &["e", "f"]),
+    ] {
+        let actual = graph
+            .commit_at(refs[name].pos())
+            .changed_paths_filter()
+            .expect("git computed all filters");
+        let expected = bloom::Filter::from_changed_paths(paths.iter().map(|path| (*path).into()), &settings);
+        assert_eq!(actual.to_owned(), expected, "{name}");
+        for path in paths {
+            assert_eq!(
+                actual.contains(&bloom::Key::new((*path).into(), &settings)),
+                Some(true),
+                "{name}: {path}"
+            );
+        }
+    }
+
+    let nested = graph
+        .commit_at(refs["nested"].pos())
+        .changed_paths_filter()
+        .expect("present");
+    assert_eq!(
+        nested.contains(&bloom::Key::new("a/b".into(), &settings)),
+        Some(true),
+        "leading directories are contained as well"
+    );
+    assert_eq!(nested.contains(&bloom::Key::new("d".into(), &settings)), Some(false));
+
+    let many = graph
+        .commit_at(refs["many"].pos())
+        .changed_paths_filter()
+        .expect("present");
+    assert!(many.is_large(), "more than 512 paths were changed");
+    assert_eq!(many.contains(&bloom::Key::new("d".into(), &settings)), Some(true));
+    assert_eq!(
+        bloom::Filter::unknown()
+            .as_ref()
+            .contains(&bloom::Key::new("d".into(), &settings)),
+        None
+    );
+}
+
+#[test]
+fn split_graphs_are_chained_and_merged() -> gix_testtools::Result {
+    let tmp = scripted_fixture_writable("changed_paths.sh")?;
+    let info_dir = tmp.path().join(".git/objects/info");
+    let single_graph_path = info_dir.join("commit-graph");
+    let expected = std::fs::read(&single_graph_path)?;
+    let graph = Graph::from_file(&single_graph_path)?;
+    let all_commits = commits(&graph);
+    let generations: Vec<_> = graph.iter_commits().map(|commit| commit.generation()).collect();
+    drop(graph);
+    std::fs::remove_file(&single_graph_path)?;
+
+    let options = write::Options {
+        changed_paths: Some(bloom::Settings::default()),
+        split: Some(write::Split {
+            size_multiple: 0,
+            max_commits: None,
+        }),
+        ..Default::default()
+    };
+    for max_generation in 1..=4 {
+        let commits = all_commits
+            .iter()
+            .zip(&generations)
+            .filter(|(_, generation)| **generation <= max_generation)
+            .map(|(commit, _)| commit.clone())
+            .collect();
+        let outcome = Graph::write_to_info_dir(&info_dir, commits, options)?;
+        assert_eq!(outcome.num_files, max_generation as usize, "nothing is merged");
+    }
+    let graph = Graph::from_info_dir(&info_dir)?;
+    assert_eq!(graph.num_commits(), 6);
+    graph.verify_integrity(|_| Ok::<_, std::convert::Infallible>(()))?;
+    drop(graph);
+    assert_git_verifies(tmp.path());
+
+    let options = write::Options {
+        split: Some(write::Split::default()),
+        ..options
+    };
+    let outcome = Graph::write_to_info_dir(&info_dir, all_commits.clone(), options)?;
+    assert_eq!(
+        outcome.num_commits, 7,
+        "all layers are small enough to be merged into the new one"
+    );
+    assert_eq!(outcome.num_files, 1);
+    let path = outcome.path.expect("written");
+    assert!(
+        std::fs::read(&path)? == expected,
+        "a chain of one file is the same as a single commit-graph"
+    );
+    assert_eq!(
+        std::fs::read_dir(info_dir.join("commit-graphs"))?.count(),
+        2,
+        "merged files were deleted, leaving the chain and the new file"
+    );
+    assert_git_verifies(tmp.path());
+
+    let outcome = Graph::write_to_info_dir(&info_dir, all_commits, options)?;
+    assert_eq!(outcome.path, None, "there was nothing to write");
+    assert_eq!(outcome.num_files, 1);
+    Ok(())
+}
+
+#[test]
+fn corrected_commit_dates_with_large_offsets_overflow_into_their_own_chunk() -> gix_testtools::Result {
+    let id = |byte: u8| gix_hash::ObjectId::from([byte; 20]);
+    let future = write::Commit {
+        id: id(1),
+        tree: id(3),
+        parents: Vec::new(),
+        commit_time: 4_147_483_646,
+        changed_paths: None,
+    };
+    let old = write::Commit {
+        id: id(2),
+        tree: id(3),
+        parents: vec![future.id],
+        commit_time: 0,
+        changed_paths: None,
+    };
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let path = tmp.path().join("commit-graph");
+    let mut buf = Vec::new();
+    File::write_from_commits(vec![old, future], None, &mut buf, write::Options::default())?;
+    std::fs::write(&path, buf)?;
+
+    let graph = Graph::from_file(&path)?;
+    graph.verify_integrity(|_| Ok::<_, std::convert::Infallible>(()))?;
+    let old = graph.commit_by_id(id(2)).expect("present");
+    assert_eq!(old.generation(), 2);
+    assert_eq!(old.corrected_commit_date(), Some(4_147_483_647));
+    assert_eq!(
+        graph.commit_by_id(id(1)).expect("present").corrected_commit_date(),
+        Some(4_147_483_646)
+    );
+    Ok(())
+}
@@ -0,0 +1,522 @@
+use std::{io::Write, ops::Range};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use super::{
+    intern::{InternedInput, Interner, Token},
+    Sink,
+};
+
+/// The maximum length of function names in hunk headers, in bytes, as imposed by `git`.
+const MAX_FUNCTION_NAME_LEN: usize = 80;
+
+/// Defines the size of the context printed before and after each change.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ContextSize {
+    /// Defines the size of the context printed before and after each change, i.e. the amount of unchanged lines.
+    pub symmetrical: u32,
+}
+
+impl Default for ContextSize {
+    /// Use 3 lines of context, just like `git` does by default.
+    fn default() -> Self {
+        ContextSize::symmetrical(3)
+    }
+}
+
+impl ContextSize {
+    /// Create a context of `n` lines before and after each change.
+    pub fn symmetrical(n: u32) -> Self {
+        ContextSize { symmetrical: n }
+    }
+}
+
+/// A line in a [`Hunk`], with its content including the line terminator, if present.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Line<'a> {
+    /// A line that is the same before and after the change.
+    Context(&'a BStr),
+    /// A line that only exists before the change.
+    Removed(&'a BStr),
+    /// A line that only exists after the change.
+    Added(&'a BStr),
+}
+
+impl<'a> Line<'a> {
+    /// Return the content of the line, including its terminator if it has one.
+    pub fn content(&self) -> &'a BStr {
+        match self {
+            Line::Context(line) | Line::Removed(line) | Line::Added(line) => line,
+        }
+    }
+
+    /// Return the character `git` prints before the line in a unified diff.
+    pub fn prefix(&self) -> u8 {
+        match self {
+            Line::Context(_) => b' ',
+            Line::Removed(_) => b'-',
+            Line::Added(_) => b'+',
+        }
+    }
+}
+
+/// A group of changes along with their context, as it would be printed in a unified diff after a single `@@` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk<'a> {
+    /// The 0-based range of lines in the old version of the file that this hunk covers.
+    pub before: Range<u32>,
+    /// The 0-based range of lines in the new version of the file that this hunk covers.
+    pub after: Range<u32>,
+    /// The name of the function the hunk is in, as found by looking at the lines of the old version
+    /// preceding the hunk with a [`FunctionName`] matcher.
+    pub function_name: Option<&'a BStr>,
+    /// All lines of the hunk, which are context lines as well as removed and added lines.
+    pub lines: &'a [Line<'a>],
+}
+
+impl Hunk<'_> {
+    /// Write the `@@ -a,b +c,d @@ function` header of this hunk into `out`, just like `git` does, including the newline.
+    pub fn write_header_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        fn write_range(out: &mut dyn Write, range: &Range<u32>) -> std::io::Result<()> {
+            let len = range.end - range.start;
+            // If there are no lines, the range refers to the line *before* the (empty) hunk.
+            let start = if len == 0 { range.start } else { range.start + 1 };
+            if len == 1 {
+                write!(out, "{start}")
+            } else {
+                write!(out, "{start},{len}")
+            }
+        }
+        out.write_all(b"@@ -")?;
+        write_range(out, &self.before)?;
+        out.write_all(b" +")?;
+        write_range(out, &self.after)?;
+        out.write_all(b" @@")?;
+        if let Some(name) = self.function_name.filter(|name| !name.is_empty()) {
+            out.write_all(b" ")?;
+            out.write_all(name)?;
+        }
+        out.write_all(b"\n")
+    }
+
+    /// Write this hunk into `out` just like `git diff` would print it, starting with its [header](Self::write_header_to()).
+    ///
+    /// Lines without a terminator are followed by the `\ No newline at end of file` marker.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        self.write_header_to(out)?;
+        for line in self.lines {
+            let content = line.content();
+            out.write_all(&[line.prefix()])?;
+            out.write_all(content)?;
+            if content.last() != Some(&b'\n') {
+                out.write_all(b"\n\\ No newline at end of file\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write this hunk into `out` like `git diff --word-diff=plain` would print it, starting with its
+    /// [header](Self::write_header_to()).
+    ///
+    /// Words are runs of non-whitespace characters, and consecutive removed and added lines are diffed word by word,
+    /// printing removed words as `[-word-]` and added words as `{+word+}`. Context lines are printed as is.
+    /// A missing newline at the end of the hunk is added without marker.
+    pub fn write_words_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        self.write_header_to(out)?;
+        let mut words = WordDiff::default();
+        for line in self.lines {
+            match line {
+                Line::Removed(content) => words.minus.extend_from_slice(content),
+                Line::Added(content) => words.plus.extend_from_slice(content),
+                Line::Context(content) => {
+                    words.flush(out)?;
+                    out.write_all(content)?;
+                }
+            }
+        }
+        // Like `git`, always end with a newline, even if the last line doesn't have one.
+        let last_text = if !words.plus.is_empty() {
+            words.plus.as_slice()
+        } else if !words.minus.is_empty() {
+            words.minus.as_slice()
+        } else {
+            self.lines.last().map_or(&[][..], |line| line.content().as_bytes())
+        };
+        let missing_newline = !last_text.is_empty() && last_text.last() != Some(&b'\n');
+        words.flush(out)?;
+        if missing_newline {
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// A trait for receiving the hunks of a [`UnifiedDiff`] one at a time.
+pub trait ConsumeHunk {
+    /// The value produced once all hunks were consumed.
+    type Out;
+
+    /// Consume a single `hunk`, or return an error to stop the operation.
+    fn consume_hunk(&mut self, hunk: &Hunk<'_>) -> std::io::Result<()>;
+
+    /// Called after the last hunk was consumed to produce the final result.
+    fn finish(self) -> Self::Out;
+}
+
+impl<F> ConsumeHunk for F
+where
+    F: FnMut(&Hunk<'_>) -> std::io::Result<()>,
+{
+    type Out = ();
+
+    fn consume_hunk(&mut self, hunk: &Hunk<'_>) -> std::io::Result<()> {
+        self(hunk)
+    }
+
+    fn finish(self) -> Self::Out {}
+}
+
+/// A way to find the name of the function that contains a hunk, for display in the hunk header.
+///
+/// By default, just like in `git`, it matches lines starting with an alphabetic character, `_` or `$`,
+/// but it can also be configured with the regular expressions of `diff.<driver>.xfuncname`.
+#[derive(Default, Debug, Clone)]
+pub struct FunctionName {
+    /// The patterns to try in order, or no pattern to use the default heuristic.
+    patterns: Vec<Pattern>,
+}
+
+#[derive(Debug, Clone)]
+struct Pattern {
+    /// If `true`, a line matching this pattern is not a function line.
+    negate: bool,
+    regex: regex::bytes::Regex,
+}
+
+impl FunctionName {
+    /// Create an instance from `patterns` as found in the value of `diff.<driver>.xfuncname`, i.e. extended regular
+    /// expressions separated by newlines.
+    ///
+    /// Patterns are tried in order and the first one to match decides. Patterns prefixed with `!` mark lines as not being
+    /// function lines. The first capture group of a matching pattern is used as function name, or the whole match if
+    /// there is no such group.
+    ///
+    /// ### Deviation
+    ///
+    /// The regular expressions are compiled with the [`regex`] crate, which doesn't support back-references.
+    /// Matching is byte-wise, so `.` and `\w` never match multi-byte characters as a whole.
+    pub fn from_xfuncname(patterns: &BStr) -> Result<Self, regex::Error> {
+        let patterns = patterns
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (negate, pattern) = match line.strip_prefix(b"!") {
+                    Some(pattern) => (true, pattern),
+                    None => (false, line),
+                };
+                Ok(Pattern {
+                    negate,
+                    regex: regex::bytes::RegexBuilder::new(&pattern.to_str_lossy())
+                        .unicode(false)
+                        .build()?,
+                })
+            })
+            .collect::<Result<_, regex::Error>>()?;
+        Ok(FunctionName { patterns })
+    }
+
+    /// Return the function name contained in `line`, which may include its line terminator, or `None` if `line`
+    /// isn't a function line.
+    ///
+    /// Note that the returned name may be empty.
+    pub fn find<'a>(&self, line: &'a [u8]) -> Option<&'a BStr> {
+        let name = if self.patterns.is_empty() {
+            match line.first() {
+                Some(b) if b.is_ascii_alphabetic() || *b == b'_' || *b == b'$' => {
+                    &line[..line.len().min(MAX_FUNCTION_NAME_LEN)]
+                }
+                _ => return None,
+            }
+        } else {
+            let line = line
+                .strip_suffix(b"\r\n")
+                .or_else(|| line.strip_suffix(b"\n"))
+                .unwrap_or(line);
+            let (pattern, captures) = self
+                .patterns
+                .iter()
+                .find_map(|pattern| pattern.regex.captures(line).map(|captures| (pattern, captures)))?;
+            if pattern.negate {
+                return None;
+            }
+            let name = captures
+                .get(1)
+                .or_else(|| captures.get(0))
+                .expect("the whole match is always present")
+                .as_bytes();
+            &name[..name.len().min(MAX_FUNCTION_NAME_LEN)]
+        };
+        let end = name.iter().rposition(|b| !is_space(*b)).map_or(0, |pos| pos + 1);
+        Some(name[..end].as_bstr())
+    }
+}
+
+/// A [`Sink`] that groups changes into hunks with context, like `git diff` does, and passes them to a [`ConsumeHunk`]
+/// delegate.
+///
+/// Use it with an [`InternedInput`] whose tokens are lines *including* their terminator, which is what
+/// [`platform::Resource::intern_source()`](super::platform::Resource::intern_source()) produces.
+pub struct UnifiedDiff<'a, D>
+where
+    D: ConsumeHunk,
+{
+    before: &'a [Token],
+    after: &'a [Token],
+    interner: &'a Interner<&'a [u8]>,
+    context_size: ContextSize,
+    function_name: Option<&'a FunctionName>,
+    delegate: D,
+
+    /// The changes of the current hunk, which are close enough to each other to share their context.
+    changes: Vec<(Range<u32>, Range<u32>)>,
+    /// The lines of the current hunk, to be passed to the delegate.
+    lines: Vec<Line<'a>>,
+    /// The last function name that was found, which remains valid for all following hunks until a new one is found.
+    current_function_name: Option<BString>,
+    /// The line in `before` up to which function names were searched, exclusive.
+    function_name_search_end: Option<u32>,
+    err: Option<std::io::Error>,
+}
+
+impl<'a, D> UnifiedDiff<'a, D>
+where
+    D: ConsumeHunk,
+{
+    /// Create a new instance to produce hunks with `context_size` lines of context from `input`, passing each hunk to `delegate`.
+    pub fn new(input: &'a InternedInput<&'a [u8]>, delegate: D, context_size: ContextSize) -> Self {
+        UnifiedDiff {
+            before: &input.before,
+            after: &input.after,
+            interner: &input.interner,
+            context_size,
+            function_name: None,
+            delegate,
+            changes: Vec::new(),
+            lines: Vec::new(),
+            current_function_name: None,
+            function_name_search_end: None,
+            err: None,
+        }
+    }
+
+    /// Search the lines before each hunk for a function name with `function_name` to show it in the hunk header.
+    pub fn with_function_name(mut self, function_name: &'a FunctionName) -> Self {
+        self.function_name = Some(function_name);
+        self
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let (Some((first_before, first_after)), Some((last_before, last_after))) =
+            (self.changes.first(), self.changes.last())
+        else {
+            return Ok(());
+        };
+        let context = self.context_size.symmetrical;
+        let before =
+            first_before.start.saturating_sub(context)..(last_before.end + context).min(self.before.len() as u32);
+        let after = first_after.start.saturating_sub(context)..(last_after.end + context).min(self.after.len() as u32);
+
+        if let Some(function_name) = self.function_name {
+            let search_end = self.function_name_search_end.unwrap_or(0);
+            if let Some(name) = (search_end..before.start)
+                .rev()
+                .find_map(|line| function_name.find(self.interner[self.before[line as usize]]))
+            {
+                self.current_function_name = Some(name.to_owned());
+            }
+            self.function_name_search_end = Some(before.start);
+        }
+
+        self.lines.clear();
+        let mut pos = before.start;
+        for (change_before, change_after) in &self.changes {
+            self.lines.extend(
+                self.before[pos as usize..change_before.start as usize]
+                    .iter()
+                    .map(|&token| Line::Context(self.interner[token].as_bstr())),
+            );
+            self.lines.extend(
+                self.before[change_before.start as usize..change_before.end as usize]
+                    .iter()
+                    .map(|&token| Line::Removed(self.interner[token].as_bstr())),
+            );
+            self.lines.extend(
+                self.after[change_after.start as usize..change_after.end as usize]
+                    .iter()
+                    .map(|&token| Line::Added(self.interner[token].as_bstr())),
+            );
+            pos = change_before.end;
+        }
+        self.lines.extend(
+            self.before[pos as usize..before.end as usize]
+                .iter()
+                .map(|&token| Line::Context(self.interner[token].as_bstr())),
+        );
+        self.changes.clear();
+
+        self.delegate.consume_hunk(&Hunk {
+            before,
+            after,
+            function_name: self.current_function_name.as_ref().map(|name| name.as_bstr()),
+            lines: &self.lines,
+        })
+    }
+}
+
+impl<D> Sink for UnifiedDiff<'_, D>
+where
+    D: ConsumeHunk,
+{
+    type Out = std::io::Result<D::Out>;
+
+    fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+        if self.err.is_some() {
+            return;
+        }
+        let is_separate_hunk = self
+            .changes
+            .last()
+            .map(|(previous_before, _)| before.start - previous_before.end > 2 * self.context_size.symmetrical)
+            .unwrap_or_default();
+        if is_separate_hunk {
+            if let Err(err) = self.flush() {
+                self.err = Some(err);
+                return;
+            }
+        }
+        self.changes.push((before, after));
+    }
+
+    fn finish(mut self) -> Self::Out {
+        if let Some(err) = self.err.take() {
+            return Err(err);
+        }
+        self.flush()?;
+        Ok(self.delegate.finish())
+    }
+}
+
+/// Buffers for the removed and added text of consecutive lines to diff word by word.
+#[derive(Default)]
+struct WordDiff {
+    minus: Vec<u8>,
+    plus: Vec<u8>,
+}
+
+impl WordDiff {
+    /// Write the word-diff of the buffered text to `out` and clear the buffers.
+    fn flush(&mut self, out: &mut dyn Write) -> std::io::Result<()> {
+        if self.minus.is_empty() && self.plus.is_empty() {
+            return Ok(());
+        }
+        if self.plus.is_empty() {
+            write_words(out, b"[-", b"-]", &self.minus)?;
+            self.minus.clear();
+            return Ok(());
+        }
+
+        let (minus_words, plus_words) = (words(&self.minus), words(&self.plus));
+        let mut input = InternedInput::default();
+        input.update_before(minus_words.iter().map(|word| &self.minus[word.clone()]));
+        input.update_after(plus_words.iter().map(|word| &self.plus[word.clone()]));
+
+        // Like in `git`, the boundaries of changes are determined by the words they cover, or for empty changes,
+        // by the end of the word before them.
+        let bounds = |words: &[Range<usize>], range: Range<u32>| {
+            if range.is_empty() {
+                let pos = match range.start {
+                    0 => 0,
+                    start => words[start as usize - 1].end,
+                };
+                pos..pos
+            } else {
+                words[range.start as usize].start..words[range.end as usize - 1].end
+            }
+        };
+        let mut plus_pos = 0;
+        let mut res = std::io::Result::Ok(());
+        super::diff(
+            super::Algorithm::Myers,
+            &input,
+            |before: Range<u32>, after: Range<u32>| {
+                if res.is_err() {
+                    return;
+                }
+                let (minus, plus) = (bounds(&minus_words, before), bounds(&plus_words, after));
+                res = (|| {
+                    if plus_pos != plus.start {
+                        write_words(out, b"", b"", &self.plus[plus_pos..plus.start])?;
+                    }
+                    if !minus.is_empty() {
+                        write_words(out, b"[-", b"-]", &self.minus[minus])?;
+                    }
+                    if !plus.is_empty() {
+                        write_words(out, b"{+", b"+}", &self.plus[plus.clone()])?;
+                    }
+                    Ok(())
+                })();
+                plus_pos = plus.end;
+            },
+        );
+        res?;
+        if plus_pos != self.plus.len() {
+            write_words(out, b"", b"", &self.plus[plus_pos..])?;
+        }
+        self.minus.clear();
+        self.plus.clear();
+        Ok(())
+    }
+}
+
+/// Return the ranges of all runs of non-whitespace characters in `text`.
+fn words(text: &[u8]) -> Vec<Range<usize>> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while let Some(start) = text[pos..].iter().position(|b| !is_space(*b)).map(|start| pos + start) {
+        let end = text[start..]
+            .iter()
+            .position(|b| is_space(*b))
+            .map_or(text.len(), |end| start + end);
+        out.push(start..end);
+        pos = end;
+    }
+    out
+}
+
+/// Write `text` to `out` with each of its lines, except for the newline itself, enclosed by `prefix` and `suffix`.
+fn write_words(out: &mut dyn Write, prefix: &[u8], suffix: &[u8], mut text: &[u8]) -> std::io::Result<()> {
+    while !text.is_empty() {
+        let (segment, rest) = match text.find_byte(b'\n') {
+            Some(pos) => (&text[..pos], Some(&text[pos + 1..])),
+            None => (text, None),
+        };
+        if !segment.is_empty() {
+            out.write_all(prefix)?;
+            out.write_all(segment)?;
+            out.write_all(suffix)?;
+        }
+        match rest {
+            Some(rest) => {
+                out.write_all(b"\n")?;
+                text = rest;
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+/// Whitespace as understood by `git`.
+fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+}
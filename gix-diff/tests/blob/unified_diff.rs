@@ -0,0 +1,181 @@
+use gix_diff::blob::{
+    intern::InternedInput,
+    unified_diff::{ContextSize, FunctionName, Hunk},
+    Algorithm, UnifiedDiff,
+};
+use gix_object::bstr::{BString, ByteSlice};
+
+const OLD: &str = "fn a() {\n  one\n  two\n  three\n  four\n  five\n  six\n  seven\n}\nfn b() {\n  eight\n  nine\n  ten\n  eleven\n  twelve\n}";
+const NEW: &str = "fn a() {\n  one\n  two\n  THREE\n  four\n  five\n  six\n  seven\n}\nfn b() {\n  eight\n  nine\n  ten\n  eleven more\n  twelve\n}\n";
+
+fn diff(
+    old: &str,
+    new: &str,
+    context_size: ContextSize,
+    function_name: Option<&FunctionName>,
+    write: fn(&Hunk<'_>, &mut Vec<u8>) -> std::io::Result<()>,
+) -> std::io::Result<BString> {
+    let input = InternedInput::new(
+        gix_diff::blob::sources::byte_lines_with_terminator(old.as_bytes()),
+        gix_diff::blob::sources::byte_lines_with_terminator(new.as_bytes()),
+    );
+    let mut out = Vec::new();
+    let mut sink = UnifiedDiff::new(&input, |hunk: &Hunk<'_>| write(hunk, &mut out), context_size);
+    if let Some(function_name) = function_name {
+        sink = sink.with_function_name(function_name);
+    }
+    gix_diff::blob::diff(Algorithm::Myers, &input, sink)?;
+    Ok(out.into())
+}
+
+#[test]
+fn hunks_with_default_function_names_and_missing_newline() -> std::io::Result<()> {
+    let actual = diff(
+        OLD,
+        NEW,
+        ContextSize::default(),
+        Some(&FunctionName::default()),
+        |hunk, out| hunk.write_to(out),
+    )?;
+    assert_eq!(
+        actual,
+        "@@ -1,7 +1,7 @@
+ fn a() {
+   one
+   two
+-  three
++  THREE
+   four
+   five
+   six
+@@ -11,6 +11,6 @@ fn b() {
+   eight
+   nine
+   ten
+-  eleven
++  eleven more
+   twelve
+-}
+\\ No newline at end of file
++}
+",
+        "this is what `git diff` produces"
+    );
+    Ok(())
+}
+
+#[test]
+fn smaller_context_and_no_function_names() -> std::io::Result<()> {
+    let actual = diff(OLD, NEW, ContextSize::symmetrical(1), None, |hunk, out| {
+        hunk.write_to(out)
+    })?;
+    assert_eq!(
+        actual,
+        "@@ -3,3 +3,3 @@
+   two
+-  three
++  THREE
+   four
+@@ -13,4 +13,4 @@
+   ten
+-  eleven
++  eleven more
+   twelve
+-}
+\\ No newline at end of file
++}
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn close_changes_share_a_hunk_and_empty_ranges_refer_to_the_previous_line() -> std::io::Result<()> {
+    let actual = diff(
+        "a\nb\nc\nd\n",
+        "a\nc\nd\ne\n",
+        ContextSize::symmetrical(1),
+        None,
+        |hunk, out| hunk.write_to(out),
+    )?;
+    assert_eq!(actual, "@@ -1,4 +1,4 @@\n a\n-b\n c\n d\n+e\n");
+
+    let actual = diff("", "a\n", ContextSize::default(), None, |hunk, out| hunk.write_to(out))?;
+    assert_eq!(actual, "@@ -0,0 +1 @@\n+a\n");
+    Ok(())
+}
+
+#[test]
+fn word_diff() -> std::io::Result<()> {
+    let actual = diff(
+        OLD,
+        NEW,
+        ContextSize::default(),
+        Some(&FunctionName::default()),
+        |hunk, out| hunk.write_words_to(out),
+    )?;
+    assert_eq!(
+        actual,
+        "@@ -1,7 +1,7 @@
+fn a() {
+  one
+  two
+  [-three-]{+THREE+}
+  four
+  five
+  six
+@@ -11,6 +11,6 @@ fn b() {
+  eight
+  nine
+  ten
+  eleven {+more+}
+  twelve
+}
+",
+        "this is what `git diff --word-diff=plain` produces"
+    );
+
+    let actual = diff("a\nb c\nd\n", "a\nd\n", ContextSize::default(), None, |hunk, out| {
+        hunk.write_words_to(out)
+    })?;
+    assert_eq!(actual, "@@ -1,3 +1,2 @@\na\n[-b c-]\nd\n", "only removals");
+
+    let actual = diff("a\n", "b", ContextSize::default(), None, |hunk, out| {
+        hunk.write_words_to(out)
+    })?;
+    assert_eq!(actual, "@@ -1 +1 @@\n[-a-]{+b+}\n", "a missing newline is added");
+    Ok(())
+}
+
+#[test]
+fn function_names_from_xfuncname() -> crate::Result {
+    let function_name = FunctionName::from_xfuncname("!^fn ignored\n^fn ([a-z_]+)\n^impl .*".into())?;
+    assert_eq!(function_name.find(b"fn main() {\n"), Some("main".into()), "first group");
+    assert_eq!(
+        function_name.find(b"impl Trait for Type  \r\n"),
+        Some("impl Trait for Type".into()),
+        "the whole match without trailing whitespace"
+    );
+    assert_eq!(function_name.find(b"fn ignored() {\n"), None, "negated pattern");
+    assert_eq!(function_name.find(b"  let a = 1;\n"), None);
+
+    let default = FunctionName::default();
+    assert_eq!(default.find(b"$var = 1\n"), Some("$var = 1".into()));
+    assert_eq!(default.find(b" indented\n"), None);
+    let long = "a".repeat(100);
+    assert_eq!(
+        default.find(long.as_bytes()).map(|name| name.len()),
+        Some(80),
+        "function names are truncated like in git"
+    );
+
+    let actual = diff(
+        "fn first() {\n1\n2\n3\n4\n5\n}\n",
+        "fn first() {\n1\n2\n3\n4\n5 changed\n}\n",
+        ContextSize::symmetrical(1),
+        Some(&function_name),
+        |hunk, out| hunk.write_header_to(out),
+    )?;
+    assert_eq!(actual.to_str_lossy(), "@@ -5,3 +5,3 @@ first\n");
+    Ok(())
+}
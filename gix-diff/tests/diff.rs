@@ -40,6 +40,7 @@ mod util {
                     Ok(Some(gix_object::Data {
                         kind: gix_object::Kind::Blob,
                         data: buffer.as_slice(),
+                        object_hash: id.kind(),
                     }))
                 }
                 None => Ok(None),
@@ -0,0 +1,47 @@
+use bstr::ByteSlice;
+use gix_hash::ObjectId;
+
+use crate::{decode::Error, Entry};
+
+/// Parse all entries from `data` in the format of `FETCH_HEAD`, with one entry per line, in order.
+///
+/// Empty lines are ignored.
+pub fn decode(data: &[u8]) -> Result<Vec<Entry>, Error> {
+    let mut out = Vec::new();
+    for (line_number, line) in data.lines().enumerate().map(|(idx, line)| (idx + 1, line.as_bstr())) {
+        if line.is_empty() {
+            continue;
+        }
+        let malformed = || Error::MalformedLine {
+            line_number,
+            line: line.into(),
+        };
+        let mut tokens = line.splitn(3, |b| *b == b'\t');
+        let (hex, marker, description) = match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(hex), Some(marker), Some(description)) => (hex, marker, description),
+            _ => return Err(malformed()),
+        };
+        let id = ObjectId::from_hex(hex).map_err(|source| Error::InvalidObjectId {
+            line_number,
+            line: line.into(),
+            source,
+        })?;
+        let for_merge = match marker {
+            b"" => true,
+            b"not-for-merge" => false,
+            _ => {
+                return Err(Error::UnknownMergeMarker {
+                    line_number,
+                    line: line.into(),
+                })
+            }
+        };
+        out.push(Entry {
+            id,
+            for_merge,
+            description: description.into(),
+        });
+    }
+    Ok(out)
+}
+
@@ -0,0 +1,21 @@
+use bstr::BString;
+
+/// The error returned by [`decode()`](crate::decode()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Line {line_number} does not start with a valid object id: {line:?}")]
+    InvalidObjectId {
+        line_number: usize,
+        line: BString,
+        source: gix_hash::decode::Error,
+    },
+    #[error(
+        "Line {line_number} must contain an object id, a merge marker and a description separated by tabs: {line:?}"
+    )]
+    MalformedLine { line_number: usize, line: BString },
+    #[error("Line {line_number} has an unknown merge marker: {line:?}")]
+    UnknownMergeMarker { line_number: usize, line: BString },
+}
+
+pub(crate) mod function;
@@ -0,0 +1,28 @@
+use std::io::Write;
+
+use bstr::ByteSlice;
+
+use crate::Entry;
+
+/// Write all `entries` to `out` in the format of `FETCH_HEAD`, with one entry per line.
+///
+/// Like `git`, all entries that are [marked for merge](Entry::for_merge) are written first, followed by all other entries,
+/// each in the order in which they appear in `entries`. Descriptions are written as is, except for newlines
+/// which can't be represented and are removed.
+pub fn encode(entries: &[Entry], out: &mut dyn Write) -> std::io::Result<()> {
+    for entry in entries
+        .iter()
+        .filter(|entry| entry.for_merge)
+        .chain(entries.iter().filter(|entry| !entry.for_merge))
+    {
+        write!(
+            out,
+            "{}\t{}\t",
+            entry.id,
+            if entry.for_merge { "" } else { "not-for-merge" }
+        )?;
+        out.write_all(&entry.description.replace(b"\n", b""))?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
@@ -1,2 +1,79 @@
-#![deny(rust_2018_idioms)]
+//! Read and write `.git/FETCH_HEAD`, the file in which `git fetch` records which objects it fetched from where.
+//!
+//! The format is the one `git` uses, so `git merge FETCH_HEAD` or `git pull` can use what `gitoxide` fetched.
+//! Each line refers to one fetched remote reference, and only the ones that are [marked for merge](Entry::for_merge)
+//! are merged by `git merge FETCH_HEAD`.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::ObjectId;
+
+/// The name of the file in the `.git` directory that contains the [entries](Entry) of the last fetch.
+pub const FILE_NAME: &str = "FETCH_HEAD";
+
+/// A single line in the `FETCH_HEAD` file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry {
+    /// The object the remote reference pointed to when it was fetched.
+    pub id: ObjectId,
+    /// If `true`, the entry is merged by `git merge FETCH_HEAD`, otherwise it's marked as `not-for-merge`.
+    pub for_merge: bool,
+    /// A description of where the object was fetched from, like `branch 'main' of https://example.com/repo`.
+    ///
+    /// Use [`description()`] to create it the way `git` does.
+    pub description: BString,
+}
+
+impl Entry {
+    /// Create a new entry for `id` which was fetched from the reference named `remote_ref_name` on the remote at `url`,
+    /// with a [description](description()) like the one `git` would produce.
+    pub fn new(id: ObjectId, for_merge: bool, remote_ref_name: &BStr, url: &BStr) -> Self {
+        Entry {
+            id,
+            for_merge,
+            description: description(remote_ref_name, url),
+        }
+    }
+}
+
+/// Produce a description of the remote reference named `remote_ref_name` on the remote at `url` like `git` does,
+/// for use in an [`Entry`].
+///
+/// Well-known prefixes of the reference name are replaced with words, so `refs/heads/main` becomes `branch 'main' of <url>`,
+/// and `HEAD` just becomes `<url>`.
+/// `url` is used as is, except for trailing slashes and a trailing `.git` which are removed. Note that `url` should not contain
+/// credentials as these would be visible to anyone who can read the file.
+pub fn description(remote_ref_name: &BStr, url: &BStr) -> BString {
+    let mut out = BString::default();
+    if remote_ref_name != "HEAD" {
+        let (kind, name) = [
+            ("branch ", "refs/heads/"),
+            ("tag ", "refs/tags/"),
+            ("remote-tracking branch ", "refs/remotes/"),
+        ]
+        .iter()
+        .find_map(|(kind, prefix)| {
+            remote_ref_name
+                .strip_prefix(prefix.as_bytes())
+                .map(|name| (*kind, name.as_bstr()))
+        })
+        .unwrap_or(("", remote_ref_name));
+        out.extend_from_slice(kind.as_bytes());
+        out.push(b'\'');
+        out.extend_from_slice(name);
+        out.extend_from_slice(b"' of ");
+    }
+    let url = url.trim_end_with(|c| c == '/');
+    let url = url.strip_suffix(b".git").unwrap_or(url);
+    out.extend_from_slice(url);
+    out
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod decode;
+pub use decode::function::decode;
+
+mod encode;
+pub use encode::encode;
@@ -0,0 +1,160 @@
+use std::path::PathBuf;
+
+use gix_fetchhead::{decode, description, encode, Entry};
+
+pub use gix_testtools::Result;
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path: PathBuf = gix_testtools::scripted_fixture_read_only("make_fetch_heads.sh")
+        .expect("script succeeds")
+        .join(name);
+    std::fs::read(path).expect("fixture file exists")
+}
+
+fn url_of(entry: &Entry) -> &bstr::BStr {
+    use bstr::ByteSlice;
+    let pos = entry.description.find(" of ").expect("description with url");
+    entry.description[pos + 4..].as_bstr()
+}
+
+#[test]
+fn decode_keeps_backslashes_in_descriptions() -> Result {
+    let decoded = decode(b"0000000000000000000000000000000000000000\t\tbranch 'a\\nb' of url\n")?;
+    assert_eq!(
+        decoded[0].description, "branch 'a\\nb' of url",
+        "like git, descriptions are never escaped"
+    );
+    Ok(())
+}
+
+#[test]
+fn decode_and_encode_round_trips_all_files_written_by_git() -> Result {
+    for name in ["configured", "with-new-tag", "command-line", "url", "detached"] {
+        let data = fixture(name);
+        let entries = decode(&data)?;
+        assert!(!entries.is_empty(), "{name}");
+        let mut out = Vec::new();
+        encode(&entries, &mut out)?;
+        assert_eq!(bstr::BStr::new(&out), bstr::BStr::new(&data), "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn decode_configured_fetch() -> Result {
+    let entries = decode(&fixture("with-new-tag"))?;
+    assert_eq!(
+        entries.iter().map(|entry| entry.for_merge).collect::<Vec<_>>(),
+        [true, false, false, false],
+        "only the branch configured with `branch.main.merge` is merged"
+    );
+    assert_eq!(
+        entries[0].id, entries[3].id,
+        "the lightweight tag points to the same commit"
+    );
+    let url = url_of(&entries[0]).to_owned();
+    for (entry, remote_ref_name) in entries.iter().zip([
+        "refs/heads/main",
+        "refs/heads/other",
+        "refs/heads/with-slash/sub",
+        "refs/tags/v2",
+    ]) {
+        assert_eq!(
+            entry.description,
+            description(remote_ref_name.into(), url.as_ref()),
+            "descriptions are created exactly like git does"
+        );
+    }
+    assert!(entries[3].description.starts_with(b"tag 'v2' of "));
+    Ok(())
+}
+
+#[test]
+fn decode_detached_and_command_line_fetch() -> Result {
+    let entries = decode(&fixture("detached"))?;
+    assert!(
+        entries.iter().all(|entry| !entry.for_merge),
+        "nothing is merged if there is no branch to merge into"
+    );
+
+    let entries = decode(&fixture("command-line"))?;
+    assert!(
+        entries.iter().all(|entry| entry.for_merge),
+        "all refspecs on the command-line are merged"
+    );
+    assert!(entries[0].description.starts_with(b"branch 'other' of "));
+    assert!(entries[1].description.starts_with(b"branch 'main' of "));
+    Ok(())
+}
+
+#[test]
+fn decode_fetch_by_url() -> Result {
+    let entries = decode(&fixture("url"))?;
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].for_merge);
+    assert_eq!(
+        entries[0].description, "../remote/",
+        "HEAD isn't mentioned, and only the last trailing slash and `.git` are removed"
+    );
+    assert_eq!(
+        entries[0].description,
+        description("HEAD".into(), "../remote/.git/".into())
+    );
+    Ok(())
+}
+
+#[test]
+fn decode_invalid_input() {
+    assert!(matches!(
+        decode(b"0000000000000000000000000000000000000000\tdescription\n"),
+        Err(gix_fetchhead::decode::Error::MalformedLine { line_number: 1, .. })
+    ));
+    assert!(matches!(
+        decode(b"\n0000000000000000000000000000000000000000\tfor-merge\tdescription\n"),
+        Err(gix_fetchhead::decode::Error::UnknownMergeMarker { line_number: 2, .. })
+    ));
+    assert!(matches!(
+        decode(b"abc\t\tdescription\n"),
+        Err(gix_fetchhead::decode::Error::InvalidObjectId { line_number: 1, .. })
+    ));
+}
+
+#[test]
+fn description_of_various_ref_names() {
+    let url = "https://example.com/repo.git".into();
+    for (name, expected) in [
+        ("refs/heads/main", "branch 'main' of https://example.com/repo"),
+        ("refs/tags/v1.0", "tag 'v1.0' of https://example.com/repo"),
+        (
+            "refs/remotes/origin/main",
+            "remote-tracking branch 'origin/main' of https://example.com/repo",
+        ),
+        ("refs/pull/1/head", "'refs/pull/1/head' of https://example.com/repo"),
+        ("HEAD", "https://example.com/repo"),
+    ] {
+        assert_eq!(description(name.into(), url), expected);
+    }
+}
+
+#[test]
+fn encode_writes_entries_for_merge_first_and_strips_newlines() -> Result {
+    let id = gix_hash::ObjectId::null(gix_hash::Kind::Sha1);
+    let entries = [
+        Entry::new(id, false, "refs/heads/other".into(), "url".into()),
+        Entry::new(id, true, "refs/heads/main".into(), "url\nwith newline".into()),
+    ];
+    let mut out = Vec::new();
+    encode(&entries, &mut out)?;
+    assert_eq!(
+        bstr::BStr::new(&out),
+        "0000000000000000000000000000000000000000\t\tbranch 'main' of urlwith newline\n\
+         0000000000000000000000000000000000000000\tnot-for-merge\tbranch 'other' of url\n"
+    );
+    let decoded = decode(&out)?;
+    assert_eq!(
+        decoded[0].description, "branch 'main' of urlwith newline",
+        "newlines can't be represented and are removed"
+    );
+    assert_eq!(decoded[1], entries[0]);
+    Ok(())
+}
@@ -0,0 +1,37 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q remote
+(cd remote
+  git checkout -q -b main
+  git commit -q --allow-empty -m "initial"
+  git branch other
+  git tag -a -m "annotated" v1
+  git checkout -q -b "with-slash/sub"
+  git commit -q --allow-empty -m "on branch with slash"
+  git checkout -q main
+)
+
+git clone -q remote local
+(cd local
+  git config remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+  git fetch -q
+  cp .git/FETCH_HEAD ../configured
+
+  (cd ../remote
+    git commit -q --allow-empty -m "second"
+    git tag v2
+  )
+  git fetch -q
+  cp .git/FETCH_HEAD ../with-new-tag
+
+  git fetch -q origin other main
+  cp .git/FETCH_HEAD ../command-line
+
+  git fetch -q ../remote/.git/
+  cp .git/FETCH_HEAD ../url
+
+  git checkout -q --detach
+  git fetch -q
+  cp .git/FETCH_HEAD ../detached
+)
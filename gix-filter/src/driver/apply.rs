@@ -44,6 +44,11 @@ pub enum Error {
         status: driver::process::Status,
         command: String,
     },
+    #[error("The builtin '{name}' filter failed")]
+    Builtin {
+        name: BString,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
 }
 
 /// Additional information for use in the [`State::apply()`] method.
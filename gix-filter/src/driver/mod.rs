@@ -59,6 +59,27 @@ impl Operation {
     }
 }
 
+/// A filter that is implemented in-process, and which is used for all paths whose `filter` attribute names it.
+///
+/// It takes precedence over [driver programs](crate::Driver) of the same name.
+pub trait Builtin: Send + Sync {
+    /// The name of the filter as used in the `filter` attribute, like `lfs` for `filter=lfs`.
+    fn name(&self) -> &BStr;
+
+    /// Apply `operation` to all bytes read from `src` and write the result to `out`, using `ctx` to learn more about
+    /// the entry at hand.
+    ///
+    /// Return `Ok(false)` if the filter doesn't handle `operation`, in which case `src` must not be consumed
+    /// and `out` must remain unchanged.
+    fn apply(
+        &self,
+        operation: Operation,
+        src: &mut dyn std::io::Read,
+        out: &mut Vec<u8>,
+        ctx: apply::Context<'_, '_>,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync + 'static>>;
+}
+
 /// State required to handle `process` filters, which are running until all their work is done.
 ///
 /// These can be significantly faster on some platforms as they are launched only once, while supporting asynchronous processing.
@@ -82,6 +82,7 @@ impl Pipeline {
         let bstr_path = gix_path::into_bstr(rela_path);
         let Configuration {
             driver,
+            builtin,
             digest,
             _attr_digest: _,
             encoding,
@@ -89,6 +90,7 @@ impl Pipeline {
         } = Configuration::at_path(
             bstr_path.as_ref(),
             &self.options.drivers,
+            &self.options.builtin_drivers,
             &mut self.attrs,
             attributes,
             self.options.eol_config,
@@ -107,6 +109,20 @@ impl Pipeline {
             },
         )?;
 
+        if let Some(builtin) = builtin {
+            self.bufs.clear();
+            in_buffer = builtin
+                .apply(
+                    driver::Operation::Clean,
+                    &mut src,
+                    &mut self.bufs.src,
+                    self.context.with_path(bstr_path.as_ref()),
+                )
+                .map_err(|source| driver::apply::Error::Builtin {
+                    name: builtin.name().to_owned(),
+                    source,
+                })?;
+        }
         if let Some(driver) = driver {
             if let Some(mut read) = self.processes.apply(
                 driver,
@@ -184,6 +200,7 @@ impl Pipeline {
     ) -> Result<ToWorktreeOutcome<'input, '_>, to_worktree::Error> {
         let Configuration {
             driver,
+            builtin,
             digest,
             _attr_digest: _,
             encoding,
@@ -191,6 +208,7 @@ impl Pipeline {
         } = Configuration::at_path(
             rela_path,
             &self.options.drivers,
+            &self.options.builtin_drivers,
             &mut self.attrs,
             attributes,
             self.options.eol_config,
@@ -213,6 +231,24 @@ impl Pipeline {
             bufs.swap();
         }
 
+        if let Some(builtin) = builtin {
+            let (mut src, dest) = bufs.src_and_dest();
+            if builtin
+                .apply(
+                    driver::Operation::Smudge,
+                    &mut src,
+                    dest,
+                    self.context.with_path(rela_path),
+                )
+                .map_err(|source| driver::apply::Error::Builtin {
+                    name: builtin.name().to_owned(),
+                    source,
+                })?
+            {
+                bufs.swap();
+            }
+        }
+
         if let Some(driver) = driver {
             let (mut src, _dest) = bufs.src_and_dest();
             if let Some(maybe_delayed) = self.processes.apply_delayed(
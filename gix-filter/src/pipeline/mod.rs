@@ -21,6 +21,8 @@ pub enum CrlfRoundTripCheck {
 pub struct Options {
     /// Available (external) driver programs to invoke if attributes for path configure them.
     pub drivers: Vec<Driver>,
+    /// Filters implemented in-process, which are used instead of `drivers` of the same name.
+    pub builtin_drivers: Vec<std::sync::Arc<dyn driver::Builtin>>,
     /// Global options to configure end-of-line conversions, to worktree or to git.
     pub eol_config: eol::Configuration,
     /// How to perform round-trip checks during end-of-line conversions to git.
@@ -61,6 +61,7 @@ fn pipeline(
         Default::default(),
         gix_filter::pipeline::Options {
             drivers,
+            builtin_drivers: Vec::new(),
             eol_config,
             encodings_with_roundtrip_check,
             crlf_roundtrip_check,
@@ -69,3 +70,79 @@ fn pipeline(
     );
     Ok((cache, pipe))
 }
+
+mod builtin {
+    use std::{io::Read, sync::Arc};
+
+    use bstr::{BStr, ByteSlice};
+    use gix_filter::{
+        driver,
+        driver::{apply::Delay, Operation},
+        pipeline::CrlfRoundTripCheck,
+    };
+
+    use crate::{driver::apply::driver_with_process, pipeline::pipeline};
+
+    /// Upper-case everything for the worktree, and lower-case it for storage in git.
+    struct Case;
+
+    impl driver::Builtin for Case {
+        fn name(&self) -> &BStr {
+            "arrow".into()
+        }
+
+        fn apply(
+            &self,
+            operation: Operation,
+            src: &mut dyn Read,
+            out: &mut Vec<u8>,
+            ctx: driver::apply::Context<'_, '_>,
+        ) -> Result<bool, Box<dyn std::error::Error + Send + Sync + 'static>> {
+            assert_eq!(ctx.rela_path, "any.txt");
+            src.read_to_end(out)?;
+            match operation {
+                Operation::Clean => out.make_ascii_lowercase(),
+                Operation::Smudge => out.make_ascii_uppercase(),
+            }
+            Ok(true)
+        }
+    }
+
+    #[test]
+    fn takes_precedence_over_driver_programs() -> crate::Result {
+        let (mut cache, mut pipe) = pipeline("driver-only", || {
+            (
+                vec![driver_with_process()],
+                Vec::new(),
+                CrlfRoundTripCheck::Skip,
+                Default::default(),
+            )
+        })?;
+        pipe.options_mut().builtin_drivers.push(Arc::new(Case));
+
+        let mut attributes = |path: &BStr, attrs: &mut gix_attributes::search::Outcome| {
+            cache
+                .at_entry(path, Some(false), &gix_object::find::Never)
+                .expect("cannot fail")
+                .matching_attributes(attrs);
+        };
+        let out = pipe.convert_to_worktree(b"hello", "any.txt".into(), &mut attributes, Delay::Forbid)?;
+        assert_eq!(
+            out.as_bytes().expect("builtin filters produce buffers").as_bstr(),
+            "HELLO"
+        );
+        drop(out);
+
+        let out = pipe.convert_to_git(
+            &b"HELLO"[..],
+            std::path::Path::new("any.txt"),
+            &mut attributes,
+            &mut |_| Ok(None),
+        )?;
+        assert_eq!(
+            out.as_bytes().expect("builtin filters produce buffers").as_bstr(),
+            "hello"
+        );
+        Ok(())
+    }
+}
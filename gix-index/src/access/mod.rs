@@ -444,6 +444,18 @@ impl State {
         (self.entries, self.path_backing)
     }
 
+    /// Replace all entries along with their paths with `entries` and `path_backing`, as obtained from
+    /// [`into_entries()`](Self::into_entries()) of another state, while keeping all extensions.
+    ///
+    /// The `fsmonitor` extension is removed as it refers to entries by position. Call [`invalidate_path()`](Self::invalidate_path())
+    /// with the path of each entry that was added, removed or changed to keep the other extensions up to date.
+    pub fn replace_entries(&mut self, entries: Vec<Entry>, path_backing: PathStorage) {
+        self.is_sparse = entries.iter().any(|entry| entry.mode.is_sparse());
+        self.entries = entries;
+        self.path_backing = path_backing;
+        self.fs_monitor = None;
+    }
+
     /// Sometimes it's needed to remove the path backing to allow certain mutation to happen in the state while supporting reading the entry's
     /// path.
     pub fn take_path_backing(&mut self) -> PathStorage {
@@ -557,6 +569,18 @@ impl State {
     pub fn fs_monitor(&self) -> Option<&extension::FsMonitor> {
         self.fs_monitor.as_ref()
     }
+    /// Mark what extensions know about the entry at `path` as outdated, as it was added, removed or changed.
+    ///
+    /// This invalidates all trees leading to `path` in the [`tree`](Self::tree()) extension, and the directory
+    /// of `path` in the [`untracked`](Self::untracked()) cache.
+    pub fn invalidate_path(&mut self, path: &BStr) {
+        if let Some(tree) = self.tree.as_mut() {
+            tree.invalidate_path(path);
+        }
+        if let Some(untracked) = self.untracked.as_mut() {
+            untracked.invalidate_path(path);
+        }
+    }
     /// Return `true` if the end-of-index extension was present when decoding this index.
     pub fn had_end_of_index_marker(&self) -> bool {
         self.end_of_index_at_decode_time
@@ -57,3 +57,177 @@ pub enum Mode {
     /// index with all entries, non is excluded, `.git/info/sparse-checkout` file is not considered, a regular index.
     Disabled,
 }
+
+mod expand_and_collapse {
+    use bstr::{BStr, BString, ByteSlice, ByteVec};
+    use gix_object::tree::EntryKind;
+
+    use crate::{
+        entry::{Flags, Mode, Stat},
+        Entry, State,
+    };
+
+    /// Sparse directories
+    impl State {
+        /// Replace all [sparse directory entries](Mode::DIR) with the entries of the trees they point to, which are obtained
+        /// from `objects`, and return the amount of directories that were expanded.
+        ///
+        /// All new entries are marked with the [`SKIP_WORKTREE`](Flags::SKIP_WORKTREE) flag, and the index is
+        /// [not sparse](State::is_sparse()) afterwards.
+        pub fn expand_sparse_directories<Find>(
+            &mut self,
+            objects: Find,
+        ) -> Result<usize, gix_traverse::tree::breadthfirst::Error>
+        where
+            Find: gix_object::Find,
+        {
+            let directories: Vec<_> = self
+                .entries
+                .iter()
+                .filter(|entry| entry.mode.is_sparse())
+                .map(|entry| (entry.path(self).to_owned(), entry.id))
+                .collect();
+            for (dir, id) in &directories {
+                let tree = State::from_tree(id, &objects)?;
+                let mut path = dir.clone();
+                if !path.ends_with(b"/") {
+                    path.push_byte(b'/');
+                }
+                let prefix_len = path.len();
+                for entry in tree.entries() {
+                    path.truncate(prefix_len);
+                    path.push_str(entry.path(&tree));
+                    self.dangerously_push_entry(
+                        Stat::default(),
+                        entry.id,
+                        entry.flags | Flags::SKIP_WORKTREE | Flags::EXTENDED,
+                        entry.mode,
+                        path.as_bstr(),
+                    );
+                }
+            }
+            if !directories.is_empty() {
+                self.remove_entries(|_, _, entry| entry.mode.is_sparse());
+                self.sort_entries();
+            }
+            self.is_sparse = false;
+            Ok(directories.len())
+        }
+
+        /// Replace all entries in directories for which `is_excluded_dir(rela_dir)` returns `true` with a single
+        /// [sparse directory entry](Mode::DIR) and return the amount of directories that were collapsed.
+        ///
+        /// Directories are only collapsed if all of their entries are marked with the [`SKIP_WORKTREE`](Flags::SKIP_WORKTREE)
+        /// flag and none of them is unmerged. The trees that the new entries point to are written with `write_tree`.
+        /// Directories are visited from the top, so only the top-most directory of multiple excluded ones is collapsed.
+        pub fn collapse_sparse_directories<E>(
+            &mut self,
+            mut is_excluded_dir: impl FnMut(&BStr) -> bool,
+            mut write_tree: impl FnMut(&gix_object::Tree) -> Result<gix_hash::ObjectId, E>,
+        ) -> Result<usize, E> {
+            let mut entries = Vec::with_capacity(self.entries.len());
+            let mut new_dirs = Vec::new();
+            let mut idx = 0;
+            while idx < self.entries.len() {
+                let path = self.entries[idx].path(self);
+                let mut collapsed = None;
+                let mut dir_end = 0;
+                while let Some(pos) = path[dir_end..].find_byte(b'/') {
+                    dir_end += pos;
+                    let dir = path[..dir_end].as_bstr();
+                    dir_end += 1;
+                    if !is_excluded_dir(dir) {
+                        continue;
+                    }
+                    let prefix = &path[..dir_end];
+                    let end = self.entries[idx..]
+                        .iter()
+                        .position(|entry| !entry.path(self).starts_with(prefix))
+                        .map_or(self.entries.len(), |len| idx + len);
+                    let range = &self.entries[idx..end];
+                    if range
+                        .iter()
+                        .all(|entry| entry.stage() == 0 && entry.flags.contains(Flags::SKIP_WORKTREE))
+                    {
+                        collapsed = Some((BString::from(prefix), end));
+                        break;
+                    }
+                }
+                match collapsed {
+                    Some((dir, end)) => {
+                        let children: Vec<_> = self.entries[idx..end]
+                            .iter()
+                            .map(|entry| {
+                                let path = entry.path(self)[dir.len()..].as_bstr();
+                                (path.strip_suffix(b"/").unwrap_or(path).as_bstr(), entry.mode, entry.id)
+                            })
+                            .collect();
+                        let id = write_tree_recursive(&children, &mut write_tree)?;
+                        new_dirs.push((entries.len(), dir));
+                        entries.push(Entry {
+                            stat: Stat::default(),
+                            id,
+                            flags: Flags::SKIP_WORKTREE | Flags::EXTENDED,
+                            mode: Mode::DIR,
+                            path: 0..0,
+                        });
+                        idx = end;
+                    }
+                    None => {
+                        entries.push(self.entries[idx].clone());
+                        idx += 1;
+                    }
+                }
+            }
+            for (entry_idx, dir) in &new_dirs {
+                let start = self.path_backing.len();
+                self.path_backing.extend_from_slice(dir);
+                entries[*entry_idx].path = start..self.path_backing.len();
+            }
+            self.entries = entries;
+            self.is_sparse |= !new_dirs.is_empty();
+            Ok(new_dirs.len())
+        }
+    }
+
+    /// Write a tree for `entries` with paths relative to it, sorted like they are in the index, and return its id.
+    fn write_tree_recursive<E>(
+        entries: &[(&BStr, Mode, gix_hash::ObjectId)],
+        write_tree: &mut impl FnMut(&gix_object::Tree) -> Result<gix_hash::ObjectId, E>,
+    ) -> Result<gix_hash::ObjectId, E> {
+        let mut tree = gix_object::Tree::empty();
+        let mut idx = 0;
+        while idx < entries.len() {
+            let (path, mode, id) = entries[idx];
+            match path.find_byte(b'/') {
+                None => {
+                    tree.entries.push(gix_object::tree::Entry {
+                        mode: mode.to_tree_entry_mode().unwrap_or_else(|| EntryKind::Blob.into()),
+                        filename: path.to_owned(),
+                        oid: id,
+                    });
+                    idx += 1;
+                }
+                Some(pos) => {
+                    let prefix = &path[..=pos];
+                    let end = entries[idx..]
+                        .iter()
+                        .position(|(path, _, _)| !path.starts_with(prefix))
+                        .map_or(entries.len(), |len| idx + len);
+                    let children: Vec<_> = entries[idx..end]
+                        .iter()
+                        .map(|(path, mode, id)| (path[pos + 1..].as_bstr(), *mode, *id))
+                        .collect();
+                    tree.entries.push(gix_object::tree::Entry {
+                        mode: EntryKind::Tree.into(),
+                        filename: path[..pos].into(),
+                        oid: write_tree_recursive(&children, write_tree)?,
+                    });
+                    idx = end;
+                }
+            }
+        }
+        tree.entries.sort();
+        write_tree(&tree)
+    }
+}
@@ -1,4 +1,6 @@
-use crate::extension::Signature;
+use bstr::{BStr, ByteSlice};
+
+use crate::extension::{Signature, Tree};
 
 /// The signature for tree extensions
 pub const SIGNATURE: Signature = *b"TREE";
@@ -12,11 +14,32 @@ pub use decode::decode;
 
 mod write;
 
+impl Tree {
+    /// Mark this tree and all trees leading to the entry at `path` as invalid, as the entry was added, removed or changed.
+    /// A tree at `path` itself is removed as it was replaced.
+    pub(crate) fn invalidate_path(&mut self, path: &BStr) {
+        self.num_entries = None;
+        let (name, rest) = match path.find_byte(b'/') {
+            Some(pos) => (&path[..pos], Some(path[pos + 1..].as_bstr())),
+            None => (path, None),
+        };
+        let Some(pos) = self.children.iter().position(|child| child.name.as_slice() == name) else {
+            return;
+        };
+        match rest {
+            Some(rest) => self.children[pos].invalidate_path(rest),
+            None => {
+                self.children.remove(pos);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     #[test]
     fn size_of_tree() {
-        assert_eq!(std::mem::size_of::<crate::extension::Tree>(), 88);
+        assert_eq!(std::mem::size_of::<crate::extension::Tree>(), 104);
     }
 }
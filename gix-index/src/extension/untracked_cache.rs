@@ -1,4 +1,4 @@
-use bstr::BString;
+use bstr::{BStr, BString, ByteSlice};
 use gix_hash::ObjectId;
 
 use crate::{
@@ -37,6 +37,45 @@ pub struct Directory {
 /// Only used as an indicator
 pub const SIGNATURE: Signature = *b"UNTR";
 
+/// If set in the directory flags, untracked directories are listed as well, which is why parent directories are
+/// invalidated along with their children.
+const DIR_SHOW_OTHER_DIRECTORIES: u32 = 1 << 1;
+
+impl UntrackedCache {
+    /// Invalidate the directory containing the entry at `path` so its untracked files are computed again,
+    /// along with its parent directories if untracked directories are listed.
+    pub(crate) fn invalidate_path(&mut self, path: &BStr) {
+        if self.directories.is_empty() {
+            return;
+        }
+        let mut chain = vec![0];
+        let mut components: Vec<_> = path.split_str("/").collect();
+        components.pop();
+        for name in components {
+            let dir = &self.directories[*chain.last().expect("root")];
+            match dir
+                .sub_directories
+                .iter()
+                .copied()
+                .find(|idx| matches!(self.directories.get(*idx), Some(dir) if dir.name == name))
+            {
+                Some(idx) => chain.push(idx),
+                None => break,
+            }
+        }
+        let num_dirs = if self.dir_flags & DIR_SHOW_OTHER_DIRECTORIES == 0 {
+            1
+        } else {
+            chain.len()
+        };
+        for idx in chain.into_iter().rev().take(num_dirs) {
+            let dir = &mut self.directories[idx];
+            dir.stat = None;
+            dir.untracked_entries.clear();
+        }
+    }
+}
+
 // #[allow(unused)]
 /// Decode an untracked cache extension from `data`, assuming object hashes are of type `object_hash`.
 pub fn decode(data: &[u8], object_hash: gix_hash::Kind) -> Option<UntrackedCache> {
@@ -213,6 +213,36 @@ fn remove_entries() {
     file.remove_entries(|_, _, _| unreachable!("should not be called"));
 }
 
+#[test]
+fn replace_entries_keeps_extensions_and_invalidate_path_invalidates_trees() {
+    let mut file = Fixture::Generated("v2_more_files").open();
+    let entries = file.entries().to_vec();
+    let path_backing = file.path_backing().to_vec();
+    let tree = file.tree().expect("present").clone();
+
+    file.replace_entries(entries, path_backing);
+    assert_eq!(file.entries().len(), 6);
+    assert_eq!(file.entries()[3].path(&file), "d/a");
+    let kept_tree = file.tree().expect("still present");
+    assert_eq!(kept_tree.num_entries, tree.num_entries);
+    assert_eq!(kept_tree.id, tree.id);
+
+    file.invalidate_path("d/a".into());
+    let tree = file.tree().expect("present");
+    assert_eq!(tree.num_entries, None, "the root tree leads to the path");
+    assert_eq!(tree.children.len(), 1);
+    assert_eq!(
+        tree.children[0].num_entries, None,
+        "the parent tree of the path is invalid"
+    );
+
+    file.invalidate_path("d".into());
+    assert!(
+        file.tree().expect("present").children.is_empty(),
+        "trees that were replaced by an entry are removed"
+    );
+}
+
 #[test]
 fn sort_entries() {
     let mut file = Fixture::Generated("v4_more_files_IEOT").open();
@@ -310,3 +340,64 @@ fn check_prefix(index: &gix_index::State, prefix: &str, expected: &[&str]) {
         "{prefix:?}"
     );
 }
+
+#[test]
+fn sparse_directories_can_be_expanded_and_collapsed() {
+    use gix::objs::WriteTo;
+    use gix_index::entry::{Flags, Mode};
+
+    let mut index = Fixture::Generated("v3_sparse_index").open();
+    let sparse = summarize(&index);
+    let git_dir = crate::fixture_index_path("v3_sparse_index")
+        .parent()
+        .expect("index is in .git")
+        .to_owned();
+    let objects = gix::odb::at(git_dir.join("objects")).unwrap();
+
+    assert_eq!(index.expand_sparse_directories(&objects).unwrap(), 2);
+    assert!(!index.is_sparse());
+    assert_eq!(
+        summarize(&index),
+        summarize(&Fixture::Generated("v3_skip_worktree").open()),
+        "expanding yields what git writes if the index isn't sparse"
+    );
+    assert!(index.entries().iter().all(|e| e.mode != Mode::DIR));
+
+    let num_collapsed = index
+        .collapse_sparse_directories(
+            |dir| !(dir == "c1" || dir.starts_with(b"c1/c2")),
+            |tree| {
+                let mut buf = Vec::new();
+                tree.write_to(&mut buf)?;
+                Ok::<_, std::io::Error>(gix::objs::compute_hash(gix_hash::Kind::Sha1, tree.kind(), &buf))
+            },
+        )
+        .unwrap();
+    assert_eq!(num_collapsed, 2, "c1/c3 and d");
+    assert!(index.is_sparse());
+    assert_eq!(
+        summarize(&index),
+        sparse,
+        "collapsing restores the sparse directories with the same tree ids"
+    );
+    assert!(index
+        .entries()
+        .iter()
+        .filter(|e| e.mode == Mode::DIR)
+        .all(|e| e.flags.contains(Flags::SKIP_WORKTREE)));
+}
+
+fn summarize(
+    index: &gix_index::State,
+) -> Vec<(
+    BString,
+    gix_index::entry::Mode,
+    gix_hash::ObjectId,
+    gix_index::entry::Flags,
+)> {
+    index
+        .entries()
+        .iter()
+        .map(|e| (e.path(index).to_owned(), e.mode, e.id, e.flags))
+        .collect()
+}
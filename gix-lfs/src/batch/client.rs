@@ -0,0 +1,205 @@
+use std::io::{Read, Write};
+
+use base64::Engine;
+use gix_credentials::{helper, protocol};
+use gix_transport::client::http::{GetResponse, Http, PostBodyDataKind, PostResponse};
+
+use crate::{
+    batch::{download, request, Client, Object, Operation, Request, Response, MEDIA_TYPE},
+    Pointer, Store,
+};
+
+/// Return the URL of the LFS server for the remote repository at `url`, which is `<url>.git/info/lfs`
+/// unless configured otherwise.
+pub fn endpoint(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+    if url.ends_with(".git") {
+        format!("{url}/info/lfs")
+    } else {
+        format!("{url}.git/info/lfs")
+    }
+}
+
+/// Lifecycle
+impl<H: Http> Client<H> {
+    /// Create a new instance to talk to the LFS server at `url` using `http`, like `https://example.com/repo.git/info/lfs`.
+    ///
+    /// Use [`endpoint()`](crate::batch::endpoint()) to obtain the `url` for a remote repository.
+    pub fn new(http: H, url: impl Into<String>) -> Self {
+        Client {
+            http,
+            url: url.into(),
+            identity: None,
+        }
+    }
+}
+
+/// Access
+impl<H> Client<H> {
+    /// Return the URL of the LFS server.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Return the identity used to authenticate with the server, if set.
+    pub fn identity(&self) -> Option<&gix_sec::identity::Account> {
+        self.identity.as_ref()
+    }
+
+    /// Use `identity` to authenticate with the server.
+    pub fn set_identity(&mut self, identity: gix_sec::identity::Account) {
+        self.identity = Some(identity);
+    }
+}
+
+/// Operations
+impl<H: Http> Client<H> {
+    /// Send `request` to the batch API of the server and return its response.
+    ///
+    /// If the server requires authentication, `authenticate` is used to obtain credentials, which are then stored or
+    /// erased depending on whether or not the server accepted them.
+    pub fn request(
+        &mut self,
+        request: &Request,
+        mut authenticate: impl FnMut(helper::Action) -> protocol::Result,
+    ) -> Result<Response, request::Error> {
+        let body = serde_json::to_vec(request).map_err(request::Error::Encode)?;
+        let url = format!("{}/objects/batch", self.url);
+        let response = match self.post(&url, &body) {
+            Err(request::Error::Io { source, .. }) if source.kind() == std::io::ErrorKind::PermissionDenied => {
+                let protocol::Outcome { identity, next } = authenticate(helper::Action::get_for_url(url.clone()))
+                    .map_err(Box::new)?
+                    .ok_or_else(|| request::Error::EmptyCredentials { url: url.clone() })?;
+                self.identity = Some(identity);
+                match self.post(&url, &body) {
+                    Ok(response) => {
+                        authenticate(next.store()).map_err(Box::new)?;
+                        response
+                    }
+                    Err(request::Error::Io { source, url })
+                        if source.kind() == std::io::ErrorKind::PermissionDenied =>
+                    {
+                        authenticate(next.erase()).map_err(Box::new)?;
+                        return Err(request::Error::InvalidCredentials { url, source });
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            res => res?,
+        };
+        serde_json::from_slice(&response).map_err(request::Error::Decode)
+    }
+
+    /// Download the objects of all `pointers` that aren't present in `store` yet using the `basic` transfer adapter,
+    /// and insert them into `store`.
+    ///
+    /// `authenticate` is used to obtain credentials if the server requires them.
+    pub fn download(
+        &mut self,
+        pointers: &[Pointer],
+        store: &Store,
+        authenticate: impl FnMut(helper::Action) -> protocol::Result,
+    ) -> Result<(), download::Error> {
+        let missing: Vec<_> = pointers.iter().filter(|pointer| !store.contains(pointer)).collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let request = Request {
+            operation: Operation::Download,
+            transfers: vec!["basic".into()],
+            ref_name: None,
+            objects: missing
+                .iter()
+                .map(|pointer| Object {
+                    oid: pointer.oid.to_hex(),
+                    size: pointer.size,
+                })
+                .collect(),
+            hash_algo: "sha256".into(),
+        };
+        let response = self.request(&request, authenticate)?;
+        if let Some(name) = response.transfer.filter(|name| name != "basic") {
+            return Err(download::Error::UnsupportedTransfer { name });
+        }
+
+        for pointer in missing {
+            let oid = pointer.oid;
+            let hex = oid.to_hex();
+            let object = response
+                .objects
+                .iter()
+                .find(|object| object.oid == hex)
+                .ok_or(download::Error::MissingObject { oid })?;
+            if let Some(err) = &object.error {
+                return Err(download::Error::Object {
+                    oid,
+                    code: err.code,
+                    message: err.message.clone(),
+                });
+            }
+            let action = object
+                .actions
+                .download
+                .as_ref()
+                .ok_or(download::Error::MissingAction { oid })?;
+            let mut headers: Vec<_> = action
+                .header
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}"))
+                .collect();
+            if !object.authenticated
+                && !action
+                    .header
+                    .keys()
+                    .any(|name| name.eq_ignore_ascii_case("authorization"))
+            {
+                headers.extend(self.authorization());
+            }
+            let GetResponse { headers, mut body } = self.http.get(&action.href, &action.href, headers)?;
+            check_status(headers).map_err(|source| download::Error::Io {
+                source,
+                url: action.href.clone(),
+            })?;
+            store.insert(pointer, &mut body)?;
+        }
+        Ok(())
+    }
+
+    fn post(&mut self, url: &str, body: &[u8]) -> Result<Vec<u8>, request::Error> {
+        let io_err = |source| request::Error::Io {
+            source,
+            url: url.to_owned(),
+        };
+        let mut headers = vec![format!("Accept: {MEDIA_TYPE}"), format!("Content-Type: {MEDIA_TYPE}")];
+        headers.extend(self.authorization());
+        let PostResponse {
+            mut post_body,
+            headers,
+            body: mut response_body,
+        } = self
+            .http
+            .post(url, url, headers, PostBodyDataKind::BoundedAndFitsIntoMemory)?;
+        post_body.write_all(body).map_err(io_err)?;
+        drop(post_body);
+        check_status(headers).map_err(io_err)?;
+        let mut out = Vec::new();
+        response_body.read_to_end(&mut out).map_err(io_err)?;
+        Ok(out)
+    }
+
+    fn authorization(&self) -> Option<String> {
+        self.identity
+            .as_ref()
+            .map(|gix_sec::identity::Account { username, password }| {
+                format!(
+                    "Authorization: Basic {}",
+                    base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"))
+                )
+            })
+    }
+}
+
+/// Consume all `headers`, which fails if the server responded with an error status.
+fn check_status(mut headers: impl Read) -> std::io::Result<()> {
+    std::io::copy(&mut headers, &mut std::io::sink()).map(|_| ())
+}
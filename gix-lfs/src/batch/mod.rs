@@ -0,0 +1,169 @@
+//! Types and a client for the LFS [Batch API](https://github.com/git-lfs/git-lfs/blob/main/docs/api/batch.md).
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The media type used for requests and responses of the batch API.
+pub const MEDIA_TYPE: &str = "application/vnd.git-lfs+json";
+
+/// The operation to request from the server.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    /// Download objects from the server.
+    Download,
+    /// Upload objects to the server.
+    Upload,
+}
+
+/// An object as it's sent to the server, and returned by it.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Object {
+    /// The hexadecimal id of the object.
+    pub oid: String,
+    /// The size of the object in bytes.
+    pub size: u64,
+}
+
+/// The reference that objects are requested for.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Ref {
+    /// The full name of the reference, like `refs/heads/main`.
+    pub name: String,
+}
+
+/// A request to the batch API.
+#[derive(Debug, Clone, Serialize)]
+pub struct Request {
+    /// The operation to perform.
+    pub operation: Operation,
+    /// The names of the transfer adapters we support, in order of preference.
+    pub transfers: Vec<String>,
+    /// The reference that objects are requested for, if known.
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub ref_name: Option<Ref>,
+    /// The objects to transfer.
+    pub objects: Vec<Object>,
+    /// The hash algorithm used for object ids.
+    pub hash_algo: String,
+}
+
+/// An action to take to transfer an object.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+pub struct Action {
+    /// The URL to use.
+    pub href: String,
+    /// Additional headers to send along with the request.
+    #[serde(default)]
+    pub header: BTreeMap<String, String>,
+}
+
+/// The actions to perform for an object, depending on the requested operation.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize)]
+pub struct Actions {
+    /// How to download the object.
+    pub download: Option<Action>,
+    /// How to upload the object, which is missing if the server already has it.
+    pub upload: Option<Action>,
+    /// How to verify the object after uploading it.
+    pub verify: Option<Action>,
+}
+
+/// An error the server reported for a single object.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+pub struct ObjectError {
+    /// A code similar to HTTP status codes, like `404` if the object doesn't exist.
+    pub code: u32,
+    /// A message describing the error.
+    pub message: String,
+}
+
+/// An object in the response of the server.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+pub struct ResponseObject {
+    /// The hexadecimal id of the object.
+    pub oid: String,
+    /// The size of the object in bytes.
+    pub size: u64,
+    /// If `true`, the actions don't require additional authentication.
+    #[serde(default)]
+    pub authenticated: bool,
+    /// What to do to transfer the object.
+    #[serde(default)]
+    pub actions: Actions,
+    /// An error that prevents the object from being transferred.
+    pub error: Option<ObjectError>,
+}
+
+/// The response to a [`Request`].
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+pub struct Response {
+    /// The transfer adapter chosen by the server, with `basic` being assumed if unset.
+    pub transfer: Option<String>,
+    /// The requested objects.
+    pub objects: Vec<ResponseObject>,
+}
+
+/// A client to communicate with an LFS server via HTTP.
+pub struct Client<H> {
+    http: H,
+    url: String,
+    identity: Option<gix_sec::identity::Account>,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod request {
+    /// The error returned by [Client::request()](crate::batch::Client::request()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Http(#[from] gix_transport::client::http::Error),
+        #[error("Could not receive the response from '{url}'")]
+        Io { source: std::io::Error, url: String },
+        #[error("Could not encode the batch request")]
+        Encode(#[source] serde_json::Error),
+        #[error("Could not decode the batch response")]
+        Decode(#[source] serde_json::Error),
+        #[error(transparent)]
+        Credentials(#[from] Box<gix_credentials::protocol::Error>),
+        #[error("The credentials helper did not provide credentials for '{url}'")]
+        EmptyCredentials { url: String },
+        #[error("Credentials provided for \"{url}\" were not accepted by the server")]
+        InvalidCredentials { url: String, source: std::io::Error },
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod download {
+    /// The error returned by [Client::download()](crate::batch::Client::download()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Request(#[from] super::request::Error),
+        #[error(transparent)]
+        Http(#[from] gix_transport::client::http::Error),
+        #[error("Could not download object from '{url}'")]
+        Io { source: std::io::Error, url: String },
+        #[error("The server did not return object {oid:?}")]
+        MissingObject { oid: crate::Oid },
+        #[error("The server could not provide object {oid:?}: {message} ({code})")]
+        Object {
+            oid: crate::Oid,
+            code: u32,
+            message: String,
+        },
+        #[error("The server did not say how to download object {oid:?}")]
+        MissingAction { oid: crate::Oid },
+        #[error("The server chose the unsupported transfer adapter '{name}'")]
+        UnsupportedTransfer { name: String },
+        #[error(transparent)]
+        Store(#[from] crate::store::write::Error),
+    }
+}
+
+mod client;
+pub use client::endpoint;
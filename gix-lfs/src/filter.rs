@@ -0,0 +1,150 @@
+use std::io::{Read, Write};
+
+use bstr::BStr;
+use gix_filter::driver;
+
+use crate::{pointer::MAX_SIZE, Filter, Pointer, Store};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod clean {
+    /// The error returned by [Filter::clean()](crate::Filter::clean()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read the content to clean")]
+        Read(#[source] std::io::Error),
+        #[error("Could not write the pointer file")]
+        Write(#[source] std::io::Error),
+        #[error(transparent)]
+        Store(#[from] crate::store::write::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod smudge {
+    /// The error returned by [Filter::smudge()](crate::Filter::smudge()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read the pointer file")]
+        Read(#[source] std::io::Error),
+        #[error("Could not pass on content that isn't a pointer")]
+        Passthrough(#[source] std::io::Error),
+        #[error("Could not write the content of object {oid:?}")]
+        Write { source: std::io::Error, oid: crate::Oid },
+        #[error("Object {oid:?} is not present in the LFS store")]
+        NotFound { oid: crate::Oid },
+        #[error("Could not fetch object {oid:?}")]
+        Fetch {
+            source: Box<dyn std::error::Error + Send + Sync + 'static>,
+            oid: crate::Oid,
+        },
+        #[error("Could not open object {oid:?}")]
+        Open { source: std::io::Error, oid: crate::Oid },
+    }
+}
+
+/// Lifecycle
+impl Filter {
+    /// Create a new instance which keeps objects in `store`.
+    pub fn new(store: Store) -> Self {
+        Filter { store, fetch: None }
+    }
+
+    /// Call `fetch` to obtain objects that aren't present in our store when smudging.
+    ///
+    /// Without it, smudging pointers to objects that aren't present fails.
+    pub fn with_fetch(
+        mut self,
+        fetch: impl Fn(&Pointer, &Store) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.fetch = Some(Box::new(fetch));
+        self
+    }
+}
+
+/// Access
+impl Filter {
+    /// Return the store that keeps our objects.
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+}
+
+/// Operations
+impl Filter {
+    /// Write all content read from `src` into our store and write a pointer file to it into `out`, returning the pointer.
+    ///
+    /// If `src` already is a pointer file, it's written to `out` unchanged.
+    pub fn clean(&self, src: &mut dyn Read, out: &mut dyn Write) -> Result<Pointer, clean::Error> {
+        let start = read_start(src).map_err(clean::Error::Read)?;
+        if let Ok(pointer) = Pointer::from_bytes(&start) {
+            out.write_all(&start).map_err(clean::Error::Write)?;
+            return Ok(pointer);
+        }
+        let pointer = self.store.write(&mut start.as_slice().chain(src))?;
+        pointer.write_to(out).map_err(clean::Error::Write)?;
+        Ok(pointer)
+    }
+
+    /// Read a pointer file from `src` and write the content it points to into `out`, returning the pointer.
+    ///
+    /// If `src` isn't a pointer file, it's written to `out` unchanged and `None` is returned.
+    pub fn smudge(&self, src: &mut dyn Read, out: &mut dyn Write) -> Result<Option<Pointer>, smudge::Error> {
+        let start = read_start(src).map_err(smudge::Error::Read)?;
+        let Ok(pointer) = Pointer::from_bytes(&start) else {
+            out.write_all(&start)
+                .and_then(|_| std::io::copy(src, out))
+                .map_err(smudge::Error::Passthrough)?;
+            return Ok(None);
+        };
+        let oid = pointer.oid;
+        if !self.store.contains(&pointer) {
+            let fetch = self.fetch.as_ref().ok_or(smudge::Error::NotFound { oid })?;
+            fetch(&pointer, &self.store).map_err(|source| smudge::Error::Fetch { source, oid })?;
+        }
+        let mut object = self
+            .store
+            .open(&pointer)
+            .map_err(|source| smudge::Error::Open { source, oid })?
+            .ok_or(smudge::Error::NotFound { oid })?;
+        std::io::copy(&mut object, out).map_err(|source| smudge::Error::Write { source, oid })?;
+        Ok(Some(pointer))
+    }
+}
+
+/// Read enough from `src` to be able to tell if it's a pointer file.
+fn read_start(src: &mut dyn Read) -> std::io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(MAX_SIZE + 1);
+    Read::take(src, MAX_SIZE as u64 + 1).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+impl driver::Builtin for Filter {
+    fn name(&self) -> &BStr {
+        "lfs".into()
+    }
+
+    fn apply(
+        &self,
+        operation: driver::Operation,
+        src: &mut dyn Read,
+        out: &mut Vec<u8>,
+        _ctx: driver::apply::Context<'_, '_>,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        match operation {
+            driver::Operation::Clean => {
+                self.clean(src, out)?;
+            }
+            driver::Operation::Smudge => {
+                self.smudge(src, out)?;
+            }
+        }
+        Ok(true)
+    }
+}
@@ -1,2 +1,65 @@
-#![deny(rust_2018_idioms)]
+//! Support for [Git LFS](https://git-lfs.com), which keeps the content of large files outside of the repository
+//! and stores small *pointer files* in their place.
+//!
+//! * [`Pointer`] parses and serializes pointer files.
+//! * [`Store`] keeps LFS objects on disk, typically in `.git/lfs/objects`.
+//! * [`Filter`] turns files into pointers and back, and can be used as [builtin driver](gix_filter::driver::Builtin)
+//!   of a [`gix_filter::Pipeline`] for all paths with the `filter=lfs` attribute.
+//! * [`batch::Client`] talks to LFS servers using the [Batch API](https://github.com/git-lfs/git-lfs/blob/main/docs/api/batch.md)
+//!   to download objects that aren't present locally.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+use std::path::PathBuf;
+
+use bstr::BString;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod pointer;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod store;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod filter;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod batch;
+
+/// The id of an LFS object, which is the SHA-256 digest of its content.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Oid(pub [u8; 32]);
+
+/// A pointer file, which is stored in `git` in place of the actual content of a file tracked by LFS.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Pointer {
+    /// The id of the object with the actual content.
+    pub oid: Oid,
+    /// The size of the actual content in bytes.
+    pub size: u64,
+    /// Additional keys, like `ext-0-name`, along with their values, in the order they appear in the pointer file.
+    pub extensions: Vec<(BString, BString)>,
+}
+
+/// A store for LFS objects in a directory, like `.git/lfs/objects`, with each object stored at `ab/cd/abcd…`.
+#[derive(Debug, Clone)]
+pub struct Store {
+    path: PathBuf,
+}
+
+/// A function to call to obtain the object of the given pointer and to [insert](Store::insert()) it into the given store.
+pub type FetchFn =
+    dyn Fn(&Pointer, &Store) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> + Send + Sync;
+
+/// The `lfs` filter, which turns the content of files into pointers when cleaning them, and pointers into the content
+/// they point to when smudging them.
+///
+/// It implements [`gix_filter::driver::Builtin`] to be usable in a [`gix_filter::Pipeline`].
+pub struct Filter {
+    store: Store,
+    fetch: Option<Box<FetchFn>>,
+}
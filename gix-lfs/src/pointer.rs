@@ -0,0 +1,186 @@
+use std::io::Write;
+
+use bstr::{BStr, ByteSlice};
+
+use crate::{Oid, Pointer};
+
+/// The version of the pointer file format we write.
+pub const VERSION: &str = "https://git-lfs.github.com/spec/v1";
+
+/// The version used by pre-release versions of `git-lfs`, which we accept as well.
+const VERSION_ALPHA: &str = "https://hawser.github.com/spec/v1";
+
+/// The largest possible size of a pointer file. Files that are larger are never considered pointers.
+pub const MAX_SIZE: usize = 1024;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod decode {
+    use bstr::BString;
+
+    /// The error returned by [Pointer::from_bytes()](crate::Pointer::from_bytes()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Pointer files must not be larger than {} bytes, got {size}", super::MAX_SIZE)]
+        TooLarge { size: usize },
+        #[error("The first line of a pointer file must be its version")]
+        MissingVersion,
+        #[error("Unsupported pointer file version '{version}'")]
+        UnsupportedVersion { version: BString },
+        #[error("Line {line:?} is not a key-value pair")]
+        InvalidLine { line: BString },
+        #[error("Key '{key}' appears out of order or more than once")]
+        UnsortedKey { key: BString },
+        #[error("The '{key}' key is missing")]
+        MissingKey { key: &'static str },
+        #[error("Could not parse '{oid}' as sha256 object id")]
+        InvalidOid { oid: BString },
+        #[error("Could not parse '{size}' as size")]
+        InvalidSize { size: BString },
+    }
+}
+
+/// Instantiation
+impl Pointer {
+    /// Parse a pointer file from `data`.
+    ///
+    /// Keys other than `version`, `oid` and `size` are kept in [`extensions`](Self::extensions).
+    pub fn from_bytes(data: &[u8]) -> Result<Self, decode::Error> {
+        use decode::Error;
+        if data.len() > MAX_SIZE {
+            return Err(Error::TooLarge { size: data.len() });
+        }
+        let mut lines = data.lines();
+        let version = lines
+            .next()
+            .and_then(|line| line.strip_prefix(b"version "))
+            .ok_or(Error::MissingVersion)?;
+        if version != VERSION.as_bytes() && version != VERSION_ALPHA.as_bytes() {
+            return Err(Error::UnsupportedVersion {
+                version: version.into(),
+            });
+        }
+
+        let mut oid = None;
+        let mut size = None;
+        let mut extensions = Vec::new();
+        let mut previous_key: Option<&BStr> = None;
+        for line in lines {
+            let (key, value) = line
+                .split_once_str(b" ")
+                .ok_or_else(|| Error::InvalidLine { line: line.into() })?;
+            let key = key.as_bstr();
+            if matches!(previous_key, Some(previous) if previous >= key) {
+                return Err(Error::UnsortedKey { key: key.into() });
+            }
+            previous_key = Some(key);
+            match key.as_bytes() {
+                b"oid" => {
+                    oid = Some(
+                        value
+                            .strip_prefix(b"sha256:")
+                            .and_then(Oid::from_hex)
+                            .ok_or_else(|| Error::InvalidOid { oid: value.into() })?,
+                    );
+                }
+                b"size" => {
+                    size = Some(
+                        value
+                            .to_str()
+                            .ok()
+                            .and_then(|size| size.parse().ok())
+                            .ok_or_else(|| Error::InvalidSize { size: value.into() })?,
+                    );
+                }
+                _ => extensions.push((key.into(), value.into())),
+            }
+        }
+        Ok(Pointer {
+            oid: oid.ok_or(Error::MissingKey { key: "oid" })?,
+            size: size.ok_or(Error::MissingKey { key: "size" })?,
+            extensions,
+        })
+    }
+}
+
+/// Serialization
+impl Pointer {
+    /// Write this instance as pointer file to `out`, with all keys sorted as required.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(out, "version {VERSION}")?;
+        let oid = format!("sha256:{}", self.oid);
+        let size = self.size.to_string();
+        let mut keys: Vec<(&BStr, &BStr)> = self
+            .extensions
+            .iter()
+            .map(|(key, value)| (key.as_bstr(), value.as_bstr()))
+            .collect();
+        keys.push(("oid".into(), oid.as_str().into()));
+        keys.push(("size".into(), size.as_str().into()));
+        keys.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, value) in keys {
+            out.write_all(key)?;
+            out.write_all(b" ")?;
+            out.write_all(value)?;
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Return this instance as pointer file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(130);
+        self.write_to(&mut buf).expect("writing to a vec cannot fail");
+        buf
+    }
+}
+
+/// Instantiation
+impl Oid {
+    /// Parse 64 lower-case hexadecimal characters from `hex`, or return `None` if they are invalid.
+    pub fn from_hex(hex: &[u8]) -> Option<Self> {
+        if hex.len() != 64 {
+            return None;
+        }
+        let mut out = [0_u8; 32];
+        for (byte, pair) in out.iter_mut().zip(hex.chunks(2)) {
+            let nibble = |b: u8| match b {
+                b'0'..=b'9' => Some(b - b'0'),
+                b'a'..=b'f' => Some(b - b'a' + 10),
+                _ => None,
+            };
+            *byte = (nibble(pair[0])? << 4) | nibble(pair[1])?;
+        }
+        Some(Oid(out))
+    }
+
+    /// Compute the id of `data`.
+    pub fn compute(data: &[u8]) -> Self {
+        use sha2::Digest;
+        Oid(sha2::Sha256::digest(data).into())
+    }
+}
+
+/// Access
+impl Oid {
+    /// Return the id as lower-case hexadecimal string.
+    pub fn to_hex(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl std::fmt::Display for Oid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for Oid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Oid({self})")
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use sha2::Digest;
+
+use crate::{Oid, Pointer, Store};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write {
+    use std::path::PathBuf;
+
+    /// The error returned by [Store::write()](crate::Store::write()) and [Store::insert()](crate::Store::insert()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not create a temporary file in '{}'", path.display())]
+        CreateTempfile { source: std::io::Error, path: PathBuf },
+        #[error("Could not read the object data")]
+        Read(#[source] std::io::Error),
+        #[error("Could not write the object data")]
+        Write(#[source] std::io::Error),
+        #[error("Could not move the object into place at '{}'", path.display())]
+        Persist { source: std::io::Error, path: PathBuf },
+        #[error(
+            "Expected object {expected:?} with {expected_size} bytes, but got {actual:?} with {actual_size} bytes"
+        )]
+        Mismatch {
+            expected: crate::Oid,
+            expected_size: u64,
+            actual: crate::Oid,
+            actual_size: u64,
+        },
+    }
+}
+
+/// Instantiation
+impl Store {
+    /// Create a new instance to store objects in the directory at `path`, which doesn't have to exist yet.
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Store { path: path.into() }
+    }
+
+    /// Create a new instance for the repository with the given `git_dir`, which keeps objects in `lfs/objects` like `git-lfs` does.
+    pub fn from_git_dir(git_dir: impl AsRef<Path>) -> Self {
+        Self::at(git_dir.as_ref().join("lfs").join("objects"))
+    }
+}
+
+/// Access
+impl Store {
+    /// Return the directory containing all objects.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Return the path at which the object with `oid` is stored. Note that it may not exist.
+    pub fn object_path(&self, oid: &Oid) -> PathBuf {
+        let hex = oid.to_hex();
+        self.path.join(&hex[..2]).join(&hex[2..4]).join(hex)
+    }
+
+    /// Return `true` if the object `pointer` points to is present with the expected size.
+    pub fn contains(&self, pointer: &Pointer) -> bool {
+        matches!(self.object_path(&pointer.oid).metadata(), Ok(md) if md.is_file() && md.len() == pointer.size)
+    }
+
+    /// Open the object that `pointer` points to for reading, or return `None` if it isn't present with the expected size.
+    pub fn open(&self, pointer: &Pointer) -> std::io::Result<Option<std::fs::File>> {
+        if !self.contains(pointer) {
+            return Ok(None);
+        }
+        std::fs::File::open(self.object_path(&pointer.oid)).map(Some)
+    }
+}
+
+/// Writing
+impl Store {
+    /// Write all data read from `src` as object and return a pointer to it.
+    pub fn write(&self, src: &mut dyn Read) -> Result<Pointer, write::Error> {
+        self.write_inner(src, None)
+    }
+
+    /// Write all data read from `src` as object that `pointer` points to, and fail if it doesn't match the pointer.
+    /// This is useful to store objects received from a remote.
+    pub fn insert(&self, pointer: &Pointer, src: &mut dyn Read) -> Result<(), write::Error> {
+        self.write_inner(src, Some(pointer)).map(|_| ())
+    }
+
+    fn write_inner(&self, src: &mut dyn Read, expected: Option<&Pointer>) -> Result<Pointer, write::Error> {
+        use write::Error;
+        std::fs::create_dir_all(&self.path).map_err(|source| Error::CreateTempfile {
+            source,
+            path: self.path.clone(),
+        })?;
+        let mut file = tempfile::NamedTempFile::new_in(&self.path).map_err(|source| Error::CreateTempfile {
+            source,
+            path: self.path.clone(),
+        })?;
+        let mut hasher = sha2::Sha256::new();
+        let mut size = 0_u64;
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let bytes_read = match src.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(Error::Read(err)),
+            };
+            hasher.update(&buf[..bytes_read]);
+            file.write_all(&buf[..bytes_read]).map_err(Error::Write)?;
+            size += bytes_read as u64;
+        }
+        file.flush().map_err(Error::Write)?;
+
+        let pointer = Pointer {
+            oid: Oid(hasher.finalize().into()),
+            size,
+            extensions: Vec::new(),
+        };
+        if let Some(expected) = expected {
+            if expected.oid != pointer.oid || expected.size != pointer.size {
+                return Err(Error::Mismatch {
+                    expected: expected.oid,
+                    expected_size: expected.size,
+                    actual: pointer.oid,
+                    actual_size: pointer.size,
+                });
+            }
+        }
+
+        let object_path = self.object_path(&pointer.oid);
+        let object_dir = object_path.parent().expect("objects are always in a directory");
+        std::fs::create_dir_all(object_dir).map_err(|source| Error::Persist {
+            source,
+            path: object_path.clone(),
+        })?;
+        file.persist(&object_path).map_err(|err| Error::Persist {
+            source: err.error,
+            path: object_path,
+        })?;
+        Ok(pointer)
+    }
+}
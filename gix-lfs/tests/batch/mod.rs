@@ -0,0 +1,184 @@
+use std::{
+    io::{Read, Write},
+    net::{Shutdown, SocketAddr},
+    sync::mpsc::Receiver,
+    time::Duration,
+};
+
+use bstr::ByteSlice;
+use gix_credentials::{helper, protocol};
+use gix_lfs::{
+    batch::{download, endpoint, Client},
+    Store,
+};
+use gix_transport::client::http::Impl;
+
+/// A server that answers each connection with the next of its responses, and which sends each request it receives.
+struct Server {
+    addr: SocketAddr,
+    requests: Receiver<Vec<u8>>,
+}
+
+impl Server {
+    /// Create a new server which obtains its responses by calling `responses` with its own URL.
+    fn new(responses: impl FnOnce(&str) -> Vec<Vec<u8>>) -> Self {
+        let listener = std::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).expect("can bind");
+        let addr = listener.local_addr().expect("a local address");
+        let responses = responses(&format!("http://{addr}"));
+        let (send_request, requests) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().expect("accept to always work");
+                stream
+                    .set_read_timeout(Some(Duration::from_millis(50)))
+                    .expect("timeout to always work");
+                let mut request = Vec::new();
+                stream.read_to_end(&mut request).ok();
+                stream.write_all(&response).expect("write to always work");
+                stream.flush().expect("flush to work");
+                stream.shutdown(Shutdown::Both).ok();
+                if send_request.send(request).is_err() {
+                    break;
+                }
+            }
+        });
+        Server { addr, requests }
+    }
+
+    fn lfs_url(&self) -> String {
+        endpoint(&format!("http://{}/repo", self.addr))
+    }
+
+    fn next_request(&self) -> Vec<u8> {
+        self.requests.recv().expect("server to answer")
+    }
+}
+
+fn response(status: &str, body: &str) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/vnd.git-lfs+json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+    .into_bytes()
+}
+
+fn batch_response(server_url: &str) -> String {
+    let pointer = crate::hello_pointer();
+    format!(
+        r#"{{"transfer":"basic","objects":[{{"oid":"{oid}","size":{size},"actions":{{"download":{{"href":"{server_url}/objects/{oid}","header":{{"X-Custom":"value"}}}}}}}}]}}"#,
+        oid = pointer.oid,
+        size = pointer.size,
+    )
+}
+
+fn no_credentials(action: helper::Action) -> protocol::Result {
+    panic!("no credentials should be requested, got {action:?}")
+}
+
+#[test]
+fn endpoint_of_remote() {
+    assert_eq!(
+        endpoint("https://example.com/repo"),
+        "https://example.com/repo.git/info/lfs"
+    );
+    assert_eq!(
+        endpoint("https://example.com/repo.git/"),
+        "https://example.com/repo.git/info/lfs"
+    );
+}
+
+#[test]
+fn download() -> crate::Result {
+    let server = Server::new(|url| vec![response("200 OK", &batch_response(url)), response("200 OK", "hello\n")]);
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let store = Store::at(tmp.path());
+    let pointer = crate::hello_pointer();
+
+    let mut client = Client::new(Impl::default(), server.lfs_url());
+    client.download(std::slice::from_ref(&pointer), &store, no_credentials)?;
+    assert!(store.contains(&pointer));
+
+    let request = server.next_request();
+    assert!(request.starts_with(b"POST /repo.git/info/lfs/objects/batch "));
+    assert!(request.contains_str("Accept: application/vnd.git-lfs+json"));
+    assert!(request.contains_str(r#""operation":"download""#));
+    assert!(request.contains_str(format!(r#""oid":"{}""#, pointer.oid)));
+    let request = server.next_request();
+    assert!(request.starts_with(format!("GET /objects/{} ", pointer.oid).as_bytes()));
+    assert!(
+        request.contains_str("X-Custom: value"),
+        "headers of the action are passed on"
+    );
+
+    client.download(&[pointer], &store, no_credentials)?;
+    assert!(
+        server.requests.recv_timeout(Duration::from_millis(100)).is_err(),
+        "objects that are present aren't downloaded again"
+    );
+    Ok(())
+}
+
+#[test]
+fn download_with_authentication() -> crate::Result {
+    let server = Server::new(|url| {
+        vec![
+            response("401 Unauthorized", ""),
+            response("200 OK", &batch_response(url)),
+            response("200 OK", "hello\n"),
+        ]
+    });
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let store = Store::at(tmp.path());
+
+    let mut actions = Vec::new();
+    let mut client = Client::new(Impl::default(), server.lfs_url());
+    client.download(&[crate::hello_pointer()], &store, |action| {
+        let outcome = match &action {
+            helper::Action::Get(ctx) => Some(protocol::Outcome {
+                identity: gix_sec::identity::Account {
+                    username: "user".into(),
+                    password: "pass".into(),
+                },
+                next: ctx.clone().into(),
+            }),
+            _ => None,
+        };
+        actions.push(action);
+        Ok(outcome)
+    })?;
+    assert!(store.contains(&crate::hello_pointer()));
+    assert_eq!(actions.len(), 2);
+    assert!(
+        matches!(&actions[0], helper::Action::Get(ctx) if matches!(&ctx.url, Some(url) if url.ends_with(b"/repo.git/info/lfs/objects/batch"))),
+        "credentials are requested for the batch endpoint"
+    );
+    assert!(
+        matches!(actions[1], helper::Action::Store(_)),
+        "accepted credentials are stored"
+    );
+
+    assert!(!server.next_request().contains_str("Authorization:"));
+    assert!(server.next_request().contains_str("Authorization: Basic dXNlcjpwYXNz"));
+    assert!(
+        server.next_request().contains_str("Authorization: Basic dXNlcjpwYXNz"),
+        "credentials are used for downloads as well"
+    );
+    Ok(())
+}
+
+#[test]
+fn download_with_object_error() -> crate::Result {
+    let pointer = crate::hello_pointer();
+    let body = format!(
+        r#"{{"objects":[{{"oid":"{}","size":6,"error":{{"code":404,"message":"Object does not exist"}}}}]}}"#,
+        pointer.oid
+    );
+    let server = Server::new(|_| vec![response("200 OK", &body)]);
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let mut client = Client::new(Impl::default(), server.lfs_url());
+    let err = client
+        .download(&[pointer], &Store::at(tmp.path()), no_credentials)
+        .unwrap_err();
+    assert!(matches!(err, download::Error::Object { code: 404, .. }));
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use std::{path::PathBuf, sync::Arc};
+
+use bstr::ByteSlice;
+use gix_filter::driver::apply::Delay;
+use gix_lfs::{filter::smudge, Filter, Store};
+
+#[test]
+fn clean_and_smudge() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let filter = Filter::new(Store::at(tmp.path()));
+    let pointer_file = crate::hello_pointer().to_bytes();
+
+    let mut out = Vec::new();
+    let pointer = filter.clean(&mut &b"hello\n"[..], &mut out)?;
+    assert_eq!(pointer, crate::hello_pointer());
+    assert_eq!(out.as_bstr(), pointer_file.as_bstr());
+    assert!(filter.store().contains(&pointer));
+
+    out.clear();
+    filter.clean(&mut pointer_file.as_slice(), &mut out)?;
+    assert_eq!(
+        out.as_bstr(),
+        pointer_file.as_bstr(),
+        "pointers are passed through as they are already clean"
+    );
+
+    out.clear();
+    assert_eq!(filter.smudge(&mut pointer_file.as_slice(), &mut out)?, Some(pointer));
+    assert_eq!(out.as_bstr(), "hello\n");
+
+    out.clear();
+    assert_eq!(
+        filter.smudge(&mut &b"not a pointer"[..], &mut out)?,
+        None,
+        "content that isn't a pointer is passed through"
+    );
+    assert_eq!(out.as_bstr(), "not a pointer");
+    Ok(())
+}
+
+#[test]
+fn smudge_with_missing_objects() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let pointer_file = crate::hello_pointer().to_bytes();
+    let filter = Filter::new(Store::at(tmp.path()));
+    let err = filter
+        .smudge(&mut pointer_file.as_slice(), &mut Vec::new())
+        .unwrap_err();
+    assert!(matches!(err, smudge::Error::NotFound { .. }));
+
+    let filter = filter.with_fetch(|pointer, store| {
+        store.insert(pointer, &mut &b"hello\n"[..])?;
+        Ok(())
+    });
+    let mut out = Vec::new();
+    filter.smudge(&mut pointer_file.as_slice(), &mut out)?;
+    assert_eq!(out.as_bstr(), "hello\n", "missing objects are fetched on demand");
+    Ok(())
+}
+
+#[test]
+fn as_builtin_driver_in_pipeline() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let mut pipeline = gix_filter::Pipeline::default();
+    pipeline
+        .options_mut()
+        .builtin_drivers
+        .push(Arc::new(Filter::new(Store::at(tmp.path()))));
+
+    let mut collection = Default::default();
+    let mut search = gix_attributes::Search::default();
+    search.add_patterns_buffer(
+        b"*.bin filter=lfs",
+        PathBuf::from(".gitattributes"),
+        None,
+        &mut collection,
+        true,
+    );
+    let mut attributes = |path: &bstr::BStr, out: &mut gix_attributes::search::Outcome| {
+        out.initialize(&collection);
+        search.pattern_matching_relative_path(path, gix_glob::pattern::Case::Sensitive, Some(false), out);
+    };
+
+    let out = pipeline.convert_to_git(
+        &b"hello\n"[..],
+        std::path::Path::new("file.bin"),
+        &mut attributes,
+        &mut |_| Ok(None),
+    )?;
+    let pointer_file = out.as_bytes().expect("builtin drivers produce buffers").to_owned();
+    drop(out);
+    assert_eq!(pointer_file.as_bstr(), crate::hello_pointer().to_bytes().as_bstr());
+
+    let out = pipeline.convert_to_worktree(&pointer_file, "file.bin".into(), &mut attributes, Delay::Forbid)?;
+    assert_eq!(out.as_bytes().expect("buffer").as_bstr(), "hello\n");
+    drop(out);
+
+    let out = pipeline.convert_to_worktree(&pointer_file, "file.txt".into(), &mut attributes, Delay::Forbid)?;
+    assert!(!out.is_changed(), "the filter only applies to paths with filter=lfs");
+    Ok(())
+}
@@ -0,0 +1,17 @@
+pub use gix_testtools::Result;
+
+#[cfg(any(feature = "http-client-curl", feature = "http-client-reqwest"))]
+mod batch;
+mod filter;
+mod pointer;
+mod store;
+
+/// The pointer to `hello\n`.
+fn hello_pointer() -> gix_lfs::Pointer {
+    gix_lfs::Pointer {
+        oid: gix_lfs::Oid::from_hex(b"5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03")
+            .expect("valid"),
+        size: 6,
+        extensions: Vec::new(),
+    }
+}
@@ -0,0 +1,91 @@
+use bstr::ByteSlice;
+use gix_lfs::{pointer::decode::Error, Oid, Pointer};
+
+const SPEC_EXAMPLE: &str = "version https://git-lfs.github.com/spec/v1
+oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393
+size 12345
+";
+
+#[test]
+fn round_trip() -> crate::Result {
+    let pointer = Pointer::from_bytes(SPEC_EXAMPLE.as_bytes())?;
+    assert_eq!(
+        pointer.oid.to_hex(),
+        "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393"
+    );
+    assert_eq!(pointer.size, 12345);
+    assert!(pointer.extensions.is_empty());
+    assert_eq!(pointer.to_bytes().as_bstr(), SPEC_EXAMPLE);
+    Ok(())
+}
+
+#[test]
+fn extensions_are_kept_in_order() -> crate::Result {
+    let input = "version https://git-lfs.github.com/spec/v1
+ext-0-foo sha256:ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff
+ext-1-bar sha256:eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee
+oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393
+size 12345
+";
+    let pointer = Pointer::from_bytes(input.as_bytes())?;
+    assert_eq!(pointer.extensions.len(), 2);
+    assert_eq!(pointer.extensions[0].0, "ext-0-foo");
+    assert_eq!(pointer.to_bytes().as_bstr(), input);
+    Ok(())
+}
+
+#[test]
+fn the_legacy_version_is_accepted() -> crate::Result {
+    let input = SPEC_EXAMPLE.replace("git-lfs.github.com", "hawser.github.com");
+    let pointer = Pointer::from_bytes(input.as_bytes())?;
+    assert_eq!(pointer.size, 12345);
+    assert_eq!(
+        pointer.to_bytes().as_bstr(),
+        SPEC_EXAMPLE,
+        "the current version is always written"
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid() {
+    for (input, expected) in [
+        ("", "MissingVersion"),
+        ("hello world\n", "MissingVersion"),
+        ("version https://example.com/v2\n", "UnsupportedVersion"),
+        ("version https://git-lfs.github.com/spec/v1\noid\n", "InvalidLine"),
+        (
+            "version https://git-lfs.github.com/spec/v1\nsize 1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n",
+            "UnsortedKey",
+        ),
+        ("version https://git-lfs.github.com/spec/v1\nsize 1\nsize 1\n", "UnsortedKey"),
+        ("version https://git-lfs.github.com/spec/v1\noid sha1:abc\nsize 1\n", "InvalidOid"),
+        (
+            "version https://git-lfs.github.com/spec/v1\noid sha256:4D7A214614AB2935C943F9E0FF69D22EADBB8F32B1258DAAA5E2CA24D17E2393\nsize 1\n",
+            "InvalidOid",
+        ),
+        (
+            "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize -1\n",
+            "InvalidSize",
+        ),
+        ("version https://git-lfs.github.com/spec/v1\nsize 1\n", "MissingKey"),
+    ] {
+        let err = Pointer::from_bytes(input.as_bytes()).expect_err(input);
+        assert!(format!("{err:?}").starts_with(expected), "{input:?}: {err:?}");
+    }
+
+    let mut too_large = SPEC_EXAMPLE.as_bytes().to_vec();
+    too_large.resize(gix_lfs::pointer::MAX_SIZE + 1, b'\n');
+    assert!(matches!(
+        Pointer::from_bytes(&too_large),
+        Err(Error::TooLarge { size: 1025 })
+    ));
+}
+
+#[test]
+fn oid() {
+    let oid = Oid::compute(b"hello\n");
+    assert_eq!(oid, crate::hello_pointer().oid);
+    assert_eq!(Oid::from_hex(oid.to_hex().as_bytes()), Some(oid));
+    assert_eq!(Oid::from_hex(b"abc"), None);
+}
@@ -0,0 +1,43 @@
+use gix_lfs::{store::write::Error, Store};
+
+#[test]
+fn write_and_read() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let store = Store::from_git_dir(tmp.path());
+    let expected = crate::hello_pointer();
+    assert!(!store.contains(&expected));
+    assert!(store.open(&expected)?.is_none());
+
+    let pointer = store.write(&mut &b"hello\n"[..])?;
+    assert_eq!(pointer, expected);
+    assert!(store.contains(&pointer));
+    assert_eq!(
+        store.object_path(&pointer.oid),
+        tmp.path()
+            .join("lfs/objects/58/91/5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03"),
+        "the same layout as git-lfs is used"
+    );
+    assert_eq!(std::fs::read(store.object_path(&pointer.oid))?, b"hello\n");
+
+    let mut wrong_size = pointer.clone();
+    wrong_size.size += 1;
+    assert!(
+        !store.contains(&wrong_size),
+        "objects with a different size than expected are considered missing"
+    );
+    Ok(())
+}
+
+#[test]
+fn insert_verifies_the_object() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let store = Store::at(tmp.path());
+    let pointer = crate::hello_pointer();
+    let err = store.insert(&pointer, &mut &b"hello"[..]).unwrap_err();
+    assert!(matches!(err, Error::Mismatch { actual_size: 5, .. }));
+    assert!(!store.contains(&pointer));
+
+    store.insert(&pointer, &mut &b"hello\n"[..])?;
+    assert!(store.contains(&pointer));
+    Ok(())
+}
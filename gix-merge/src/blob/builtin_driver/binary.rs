@@ -0,0 +1,49 @@
+use crate::blob::Resolution;
+
+/// The side to pick if a binary file has a conflict.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResolveWith {
+    /// Use the ancestor, i.e. undo the changes of both sides.
+    Ancestor,
+    /// Use our version.
+    Ours,
+    /// Use their version.
+    Theirs,
+}
+
+/// The version to use as result of a binary merge.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Pick {
+    /// Use the ancestor.
+    Ancestor,
+    /// Use our version.
+    Ours,
+    /// Use their version.
+    Theirs,
+}
+
+/// Merge binary data `current`, `ancestor` and `other` without looking at its content, apart from determining if
+/// the versions are equal.
+///
+/// If only one side changed, that side is picked, and if both changed in different ways, there is a conflict which
+/// is resolved according to `on_conflict`, or keeps our version if `None`.
+pub fn merge(current: &[u8], ancestor: &[u8], other: &[u8], on_conflict: Option<ResolveWith>) -> (Pick, Resolution) {
+    if current == other || ancestor == other {
+        (Pick::Ours, Resolution::Complete)
+    } else if ancestor == current {
+        (Pick::Theirs, Resolution::Complete)
+    } else {
+        match on_conflict {
+            None => (Pick::Ours, Resolution::Conflict),
+            Some(resolve) => (
+                match resolve {
+                    ResolveWith::Ancestor => Pick::Ancestor,
+                    ResolveWith::Ours => Pick::Ours,
+                    ResolveWith::Theirs => Pick::Theirs,
+                },
+                Resolution::CompleteWithAutoResolvedConflict,
+            ),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+///
+#[allow(clippy::empty_docs)]
+pub mod text;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod binary;
+
+/// Return `true` if `data` is considered binary, which is the case if it contains a null-byte in the first 8000 bytes,
+/// just like `git` does it.
+pub fn is_binary(data: &[u8]) -> bool {
+    data[..data.len().min(8000)].contains(&0)
+}
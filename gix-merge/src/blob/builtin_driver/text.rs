@@ -0,0 +1,439 @@
+use std::ops::Range;
+
+use bstr::BStr;
+use gix_diff::blob::{
+    intern::{Interner, Token},
+    Algorithm,
+};
+
+use crate::blob::{Labels, Resolution};
+
+/// The way conflicting hunks are presented.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConflictStyle {
+    /// Only show the lines of *ours* and *theirs*, after shrinking the conflict to the lines that actually differ.
+    ///
+    /// This is the default, and it's selected with `merge.conflictStyle=merge`.
+    #[default]
+    Merge,
+    /// Show the lines of *ours*, the ancestor and *theirs*, without shrinking the conflict.
+    ///
+    /// It's selected with `merge.conflictStyle=diff3`.
+    Diff3,
+    /// Like [`Diff3`](Self::Diff3), but lines at the beginning and at the end of the conflict that are the same in both
+    /// sides are moved out of the conflict.
+    ///
+    /// It's selected with `merge.conflictStyle=zdiff3`.
+    ZealousDiff3,
+}
+
+/// What to do with conflicting hunks.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Conflict {
+    /// Keep the conflict and mark it with conflict markers in the given `style`, each of which is `marker_size`
+    /// characters long.
+    Keep {
+        /// How to present the conflict.
+        style: ConflictStyle,
+        /// The amount of characters of each conflict marker, which is `7` by default.
+        marker_size: u8,
+    },
+    /// Resolve the conflict by using the lines of *ours*, like `git merge-file --ours`.
+    ResolveWithOurs,
+    /// Resolve the conflict by using the lines of *theirs*, like `git merge-file --theirs`.
+    ResolveWithTheirs,
+    /// Resolve the conflict by using the lines of *ours* followed by the lines of *theirs*, like `git merge-file --union`.
+    ResolveWithUnion,
+}
+
+impl Conflict {
+    /// The size of conflict markers used by `git` by default.
+    pub const DEFAULT_MARKER_SIZE: u8 = 7;
+
+    /// Return the size of conflict markers, if conflicts are kept.
+    pub fn marker_size(&self) -> Option<u8> {
+        match self {
+            Conflict::Keep { marker_size, .. } => Some(*marker_size),
+            Conflict::ResolveWithOurs | Conflict::ResolveWithTheirs | Conflict::ResolveWithUnion => None,
+        }
+    }
+}
+
+impl Default for Conflict {
+    fn default() -> Self {
+        Conflict::Keep {
+            style: ConflictStyle::default(),
+            marker_size: Self::DEFAULT_MARKER_SIZE,
+        }
+    }
+}
+
+/// Options for the builtin [text driver](merge()).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Options {
+    /// The algorithm to use to find changes of each side compared to the ancestor.
+    pub diff_algorithm: Algorithm,
+    /// What to do with conflicting hunks.
+    pub conflict: Conflict,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            diff_algorithm: Algorithm::Myers,
+            conflict: Default::default(),
+        }
+    }
+}
+
+/// Merge `current`, the version of *ours*, and `other`, the version of *theirs*, using `ancestor` as their common
+/// base, and write the result into `out`, which is cleared first.
+///
+/// Lines are used as tokens, and changes of both sides that overlap or touch each other are conflicts, which
+/// are handled according to `options`. `labels` are used in conflict markers.
+pub fn merge(
+    out: &mut Vec<u8>,
+    labels: Labels<'_>,
+    current: &[u8],
+    ancestor: &[u8],
+    other: &[u8],
+    options: Options,
+) -> Resolution {
+    out.clear();
+    let ours = Lines::new(current);
+    let base = Lines::new(ancestor);
+    let theirs = Lines::new(other);
+    let mut interner = Interner::new(ours.lines.len() + base.lines.len() + theirs.lines.len());
+    let ours_tokens = ours.tokens(&mut interner);
+    let base_tokens = base.tokens(&mut interner);
+    let theirs_tokens = theirs.tokens(&mut interner);
+
+    let mut changes = hunks(
+        options.diff_algorithm,
+        &base_tokens,
+        &ours_tokens,
+        &interner,
+        Side::Ours,
+    );
+    changes.extend(hunks(
+        options.diff_algorithm,
+        &base_tokens,
+        &theirs_tokens,
+        &interner,
+        Side::Theirs,
+    ));
+    changes.sort_by_key(|hunk| (hunk.before.start, hunk.side));
+
+    let mut resolution = Resolution::Complete;
+    let mut out = Output {
+        out,
+        labels,
+        conflict: options.conflict,
+        pending: None,
+        common: Vec::new(),
+    };
+    let mut base_pos = 0;
+    let mut changes = changes.into_iter().peekable();
+    while let Some(first) = changes.next() {
+        let start = first.before.start;
+        let mut end = first.before.end;
+        let mut group = vec![first];
+        while let Some(next) = changes.next_if(|next| next.before.start <= end) {
+            end = end.max(next.before.end);
+            group.push(next);
+        }
+
+        out.common(base.range(base_pos..start));
+        base_pos = end;
+        match (
+            side_range(&group, Side::Ours, start..end),
+            side_range(&group, Side::Theirs, start..end),
+        ) {
+            (Some(range), None) => out.resolved(ours.range(range)),
+            (None, Some(range)) => out.resolved(theirs.range(range)),
+            (Some(ours_range), Some(theirs_range)) => {
+                if ours_tokens[as_usize(&ours_range)] == theirs_tokens[as_usize(&theirs_range)] {
+                    out.resolved(ours.range(ours_range));
+                    continue;
+                }
+                let hunk = ConflictHunk {
+                    ours: ours.range(ours_range.clone()),
+                    ours_tokens: &ours_tokens[as_usize(&ours_range)],
+                    base: base.range(start..end),
+                    theirs: theirs.range(theirs_range.clone()),
+                    theirs_tokens: &theirs_tokens[as_usize(&theirs_range)],
+                };
+                match options.conflict {
+                    Conflict::Keep {
+                        style: style @ (ConflictStyle::Diff3 | ConflictStyle::ZealousDiff3),
+                        marker_size,
+                    } => {
+                        out.flush();
+                        let markers = Markers::new(hunk.ours, hunk.base, hunk.theirs, labels, marker_size);
+                        if style == ConflictStyle::Diff3 {
+                            markers.write(out.out, hunk.ours, Some(hunk.base), hunk.theirs);
+                        } else {
+                            write_zealous_conflict(out.out, &hunk, &markers);
+                        }
+                    }
+                    _ => write_refined_conflict(&mut out, &hunk, &interner, options.diff_algorithm),
+                }
+                resolution = match options.conflict {
+                    Conflict::Keep { .. } => Resolution::Conflict,
+                    _ => Resolution::CompleteWithAutoResolvedConflict,
+                };
+            }
+            (None, None) => unreachable!("groups have at least one hunk"),
+        }
+    }
+    out.common(base.range(base_pos..base.lines.len() as u32));
+    out.flush();
+    resolution
+}
+
+/// The input split into lines, each with its line terminator.
+struct Lines<'a> {
+    lines: Vec<&'a [u8]>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Lines {
+            lines: data.split_inclusive(|b| *b == b'\n').collect(),
+        }
+    }
+
+    fn tokens(&self, interner: &mut Interner<&'a [u8]>) -> Vec<Token> {
+        self.lines.iter().map(|line| interner.intern(line)).collect()
+    }
+
+    fn range(&self, range: Range<u32>) -> &[&'a [u8]] {
+        &self.lines[as_usize(&range)]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+enum Side {
+    Ours,
+    Theirs,
+}
+
+/// A change of `side` which replaces the lines in `before` of the ancestor with the lines in `after` of `side`.
+struct Hunk {
+    before: Range<u32>,
+    after: Range<u32>,
+    side: Side,
+}
+
+fn hunks(algorithm: Algorithm, before: &[Token], after: &[Token], interner: &Interner<&[u8]>, side: Side) -> Vec<Hunk> {
+    let mut out = Vec::new();
+    gix_diff::blob::diff_with_tokens(
+        algorithm,
+        before,
+        after,
+        interner.num_tokens(),
+        |before: Range<u32>, after: Range<u32>| out.push(Hunk { before, after, side }),
+    );
+    out
+}
+
+/// Return the range of lines in `side` that correspond to `base_range` of the ancestor, or `None` if `side` has
+/// no changes in `group`.
+fn side_range(group: &[Hunk], side: Side, base_range: Range<u32>) -> Option<Range<u32>> {
+    let mut hunks = group.iter().filter(|hunk| hunk.side == side);
+    let first = hunks.next()?;
+    let last = hunks.next_back().unwrap_or(first);
+    Some(
+        first.after.start - (first.before.start - base_range.start)
+            ..last.after.end + (base_range.end - last.before.end),
+    )
+}
+
+fn as_usize(range: &Range<u32>) -> Range<usize> {
+    range.start as usize..range.end as usize
+}
+
+struct ConflictHunk<'a, 'data> {
+    ours: &'a [&'data [u8]],
+    ours_tokens: &'a [Token],
+    base: &'a [&'data [u8]],
+    theirs: &'a [&'data [u8]],
+    theirs_tokens: &'a [Token],
+}
+
+/// Split the conflict into the parts that actually differ between both sides, with common lines between them.
+fn write_refined_conflict<'data>(
+    out: &mut Output<'_, 'data>,
+    hunk: &ConflictHunk<'_, 'data>,
+    interner: &Interner<&[u8]>,
+    algorithm: Algorithm,
+) {
+    if hunk.ours.is_empty() || hunk.theirs.is_empty() {
+        out.conflict(hunk.ours, hunk.theirs);
+        return;
+    }
+    let mut ours_pos = 0;
+    for refined in hunks(algorithm, hunk.ours_tokens, hunk.theirs_tokens, interner, Side::Ours) {
+        out.common(&hunk.ours[ours_pos..refined.before.start as usize]);
+        out.conflict(
+            &hunk.ours[as_usize(&refined.before)],
+            &hunk.theirs[as_usize(&refined.after)],
+        );
+        ours_pos = refined.before.end as usize;
+    }
+    out.common(&hunk.ours[ours_pos..]);
+}
+
+/// Conflicts separated by no more than this amount of common lines are combined into one.
+const MAX_COMMON_LINES_BETWEEN_CONFLICTS: usize = 3;
+
+/// The lines of *ours* and *theirs* in a conflict.
+type ConflictLines<'data> = (Vec<&'data [u8]>, Vec<&'data [u8]>);
+
+/// The merge result, which holds back conflicts without base to be able to combine them with the next one if only a few
+/// lines are between them, as this is easier to resolve, just like `git` does it.
+struct Output<'a, 'data> {
+    out: &'a mut Vec<u8>,
+    labels: Labels<'a>,
+    conflict: Conflict,
+    /// The lines of *ours* and *theirs* of a conflict which wasn't written yet.
+    pending: Option<ConflictLines<'data>>,
+    /// The lines that are common to both sides and follow the `pending` conflict.
+    common: Vec<&'data [u8]>,
+}
+
+impl<'data> Output<'_, 'data> {
+    /// Write `lines` which are the same on both sides.
+    fn common(&mut self, lines: &[&'data [u8]]) {
+        if self.pending.is_some() {
+            self.common.extend_from_slice(lines);
+        } else {
+            self.out.extend(lines.iter().copied().flatten());
+        }
+    }
+
+    /// Write `lines` which were changed by only one side, or in the same way by both.
+    fn resolved(&mut self, lines: &[&'data [u8]]) {
+        self.flush();
+        self.out.extend(lines.iter().copied().flatten());
+    }
+
+    /// Write a conflict between `ours` and `theirs`, possibly combining it with the previous one.
+    fn conflict(&mut self, ours: &[&'data [u8]], theirs: &[&'data [u8]]) {
+        match &mut self.pending {
+            Some((pending_ours, pending_theirs)) if self.common.len() <= MAX_COMMON_LINES_BETWEEN_CONFLICTS => {
+                pending_ours.extend(self.common.iter().chain(ours));
+                pending_theirs.extend(self.common.drain(..).chain(theirs.iter().copied()));
+            }
+            _ => {
+                self.flush();
+                self.pending = Some((ours.to_vec(), theirs.to_vec()));
+            }
+        }
+    }
+
+    /// Write the pending conflict, if there is one, along with the common lines that follow it.
+    fn flush(&mut self) {
+        if let Some((ours, theirs)) = self.pending.take() {
+            match self.conflict {
+                Conflict::Keep { marker_size, .. } => {
+                    Markers::new(&ours, &[], &theirs, self.labels, marker_size).write(self.out, &ours, None, &theirs);
+                }
+                Conflict::ResolveWithOurs => self.out.extend(ours.into_iter().flatten()),
+                Conflict::ResolveWithTheirs => self.out.extend(theirs.into_iter().flatten()),
+                Conflict::ResolveWithUnion => {
+                    write_lines(self.out, &ours, b"\n");
+                    self.out.extend(theirs.into_iter().flatten());
+                }
+            }
+        }
+        self.out.extend(self.common.drain(..).flatten());
+    }
+}
+
+/// Move lines that are the same at the beginning and the end of both sides out of the conflict.
+fn write_zealous_conflict(out: &mut Vec<u8>, hunk: &ConflictHunk<'_, '_>, markers: &Markers) {
+    let prefix = hunk
+        .ours_tokens
+        .iter()
+        .zip(hunk.theirs_tokens)
+        .take_while(|(ours, theirs)| ours == theirs)
+        .count();
+    let max_suffix = hunk.ours.len().min(hunk.theirs.len()) - prefix;
+    let suffix = hunk
+        .ours_tokens
+        .iter()
+        .rev()
+        .zip(hunk.theirs_tokens.iter().rev())
+        .take(max_suffix)
+        .take_while(|(ours, theirs)| ours == theirs)
+        .count();
+    out.extend(hunk.ours[..prefix].iter().copied().flatten());
+    markers.write(
+        out,
+        &hunk.ours[prefix..hunk.ours.len() - suffix],
+        Some(hunk.base),
+        &hunk.theirs[prefix..hunk.theirs.len() - suffix],
+    );
+    out.extend(hunk.ours[hunk.ours.len() - suffix..].iter().copied().flatten());
+}
+
+/// Write all `lines` to `out` and make sure the last one is terminated with `newline`.
+fn write_lines(out: &mut Vec<u8>, lines: &[&[u8]], newline: &[u8]) {
+    out.extend(lines.iter().copied().flatten());
+    if matches!(lines.last(), Some(line) if !line.ends_with(b"\n")) {
+        out.extend_from_slice(newline);
+    }
+}
+
+/// The conflict markers to write, fully prepared with labels and line terminator.
+struct Markers {
+    ours: Vec<u8>,
+    base: Vec<u8>,
+    separator: Vec<u8>,
+    theirs: Vec<u8>,
+    newline: &'static [u8],
+}
+
+impl Markers {
+    fn new(ours: &[&[u8]], base: &[&[u8]], theirs: &[&[u8]], labels: Labels<'_>, marker_size: u8) -> Self {
+        let newline: &'static [u8] = if matches!(
+            ours.first().or(theirs.first()).or(base.first()),
+            Some(line) if line.ends_with(b"\r\n")
+        ) {
+            b"\r\n"
+        } else {
+            b"\n"
+        };
+        let marker = |char: u8, label: Option<&BStr>| {
+            let mut buf = vec![char; marker_size as usize];
+            if let Some(label) = label {
+                buf.push(b' ');
+                buf.extend_from_slice(label);
+            }
+            buf.extend_from_slice(newline);
+            buf
+        };
+        Markers {
+            ours: marker(b'<', labels.current),
+            base: marker(b'|', labels.ancestor),
+            separator: marker(b'=', None),
+            theirs: marker(b'>', labels.other),
+            newline,
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>, ours: &[&[u8]], base: Option<&[&[u8]]>, theirs: &[&[u8]]) {
+        out.extend_from_slice(&self.ours);
+        write_lines(out, ours, self.newline);
+        if let Some(base) = base {
+            out.extend_from_slice(&self.base);
+            write_lines(out, base, self.newline);
+        }
+        out.extend_from_slice(&self.separator);
+        write_lines(out, theirs, self.newline);
+        out.extend_from_slice(&self.theirs);
+    }
+}
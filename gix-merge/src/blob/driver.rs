@@ -0,0 +1,143 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+
+use crate::blob::{Driver, Labels, Resolution};
+
+/// The information passed to a merge driver, in addition to the content of each side.
+#[derive(Debug, Copy, Clone)]
+pub struct Context<'a> {
+    /// The path of the merged file, relative to the root of the worktree.
+    pub rela_path: &'a BStr,
+    /// The size of conflict markers the driver should use.
+    pub marker_size: u8,
+    /// The names of each side of the merge.
+    pub labels: Labels<'a>,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod run {
+    use bstr::BString;
+
+    /// The error returned by [Driver::run()](crate::blob::Driver::run()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not create temporary file to hold the {side} version")]
+        CreateTempfile { side: &'static str, source: std::io::Error },
+        #[error("Failed to spawn merge driver '{name}' as '{command}'")]
+        Spawn {
+            name: BString,
+            command: BString,
+            source: std::io::Error,
+        },
+        #[error("Merge driver '{name}' was terminated by a signal")]
+        Signal { name: BString },
+        #[error("Could not read the result of merge driver '{name}'")]
+        ReadResult { name: BString, source: std::io::Error },
+    }
+}
+
+impl Driver {
+    /// Run this driver to merge `current`, `ancestor` and `other` in the context of `ctx`, and place the result into `out`
+    /// which is cleared beforehand.
+    ///
+    /// Each version is written to a temporary file, and the driver is expected to place the result into the file of `current`.
+    /// If it exits with a non-zero status, the result is considered to be conflicting.
+    pub fn run(
+        &self,
+        out: &mut Vec<u8>,
+        current: &[u8],
+        ancestor: &[u8],
+        other: &[u8],
+        ctx: Context<'_>,
+    ) -> Result<Resolution, run::Error> {
+        let (_current_file, current_path) = tempfile_with(current, "current")?;
+        let (_ancestor_file, ancestor_path) = tempfile_with(ancestor, "ancestor")?;
+        let (_other_file, other_path) = tempfile_with(other, "other")?;
+        let command = self.substitute_placeholders(&current_path, &ancestor_path, &other_path, ctx);
+
+        let status = gix_command::prepare(gix_path::from_bstr(command.as_bstr()).into_owned())
+            .with_shell()
+            .stdin(Stdio::null())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .and_then(|mut child| child.wait())
+            .map_err(|source| run::Error::Spawn {
+                name: self.name.clone(),
+                command: command.clone(),
+                source,
+            })?;
+        if status.code().is_none() {
+            return Err(run::Error::Signal {
+                name: self.name.clone(),
+            });
+        }
+
+        out.clear();
+        out.extend(std::fs::read(&current_path).map_err(|source| run::Error::ReadResult {
+            name: self.name.clone(),
+            source,
+        })?);
+        Ok(if status.success() {
+            Resolution::Complete
+        } else {
+            Resolution::Conflict
+        })
+    }
+
+    fn substitute_placeholders(&self, current: &Path, ancestor: &Path, other: &Path, ctx: Context<'_>) -> BString {
+        let quoted_path = |path: &Path| gix_quote::single(gix_path::into_bstr(path).as_ref());
+        let quoted_label = |label: Option<&BStr>| gix_quote::single(label.unwrap_or_default());
+        let mut out = BString::default();
+        let mut chars = self.command.iter().copied();
+        while let Some(b) = chars.next() {
+            if b != b'%' {
+                out.push(b);
+                continue;
+            }
+            match chars.next() {
+                Some(b'O') => out.push_str(quoted_path(ancestor)),
+                Some(b'A') => out.push_str(quoted_path(current)),
+                Some(b'B') => out.push_str(quoted_path(other)),
+                Some(b'L') => out.push_str(ctx.marker_size.to_string()),
+                Some(b'P') => out.push_str(gix_quote::single(ctx.rela_path)),
+                Some(b'S') => out.push_str(quoted_label(ctx.labels.ancestor)),
+                Some(b'X') => out.push_str(quoted_label(ctx.labels.current)),
+                Some(b'Y') => out.push_str(quoted_label(ctx.labels.other)),
+                Some(b'%') => out.push(b'%'),
+                Some(other) => {
+                    out.push(b'%');
+                    out.push(other);
+                }
+                None => out.push(b'%'),
+            }
+        }
+        out
+    }
+}
+
+/// Write `data` into a new temporary file, and return it along with its path. It's removed once the handle is dropped.
+fn tempfile_with(
+    data: &[u8],
+    side: &'static str,
+) -> Result<(gix_tempfile::Handle<gix_tempfile::handle::Writable>, PathBuf), run::Error> {
+    gix_tempfile::new(
+        std::env::temp_dir(),
+        gix_tempfile::ContainingDirectory::Exists,
+        gix_tempfile::AutoRemove::Tempfile,
+    )
+    .and_then(|mut file| {
+        file.write_all(data)?;
+        file.flush()?;
+        let path = file.with_mut(|file| file.path().to_owned())?;
+        Ok((file, path))
+    })
+    .map_err(|source| run::Error::CreateTempfile { side, source })
+}
@@ -0,0 +1,110 @@
+//! Merge the content of blobs, based on the built-in algorithms of `git` or on user-defined merge drivers.
+use bstr::{BStr, BString};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod builtin_driver;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod platform;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod driver;
+
+/// The outcome of a three-way merge of blobs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Resolution {
+    /// The merge completed without any conflict.
+    Complete,
+    /// Conflicts were encountered, but they were resolved automatically as configured, for instance by
+    /// favoring one side or by taking the union of both.
+    CompleteWithAutoResolvedConflict,
+    /// The merge produced conflicts which need to be resolved by the user, and the result contains conflict markers
+    /// or is one of the sides as is, if the content is binary.
+    Conflict,
+}
+
+/// A way to refer to the drivers that are built into `git`, as they can be selected with the `merge` attribute.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BuiltinDriver {
+    /// Perform a three-way merge of text with lines as tokens, which is the default for any file
+    /// and selected with `merge` or `merge=text`.
+    #[default]
+    Text,
+    /// Keep the current version of the file and report a conflict if the other side changed it too,
+    /// as selected with `-merge` or `merge=binary`.
+    Binary,
+    /// Perform a text-merge, but resolve conflicts by keeping the lines of both sides, as selected with `merge=union`.
+    Union,
+}
+
+impl BuiltinDriver {
+    /// All the built-in drivers.
+    pub const ALL: &'static [Self] = &[BuiltinDriver::Text, BuiltinDriver::Binary, BuiltinDriver::Union];
+
+    /// Return the name of this driver as used in the `merge` attribute.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuiltinDriver::Text => "text",
+            BuiltinDriver::Binary => "binary",
+            BuiltinDriver::Union => "union",
+        }
+    }
+
+    /// Find the built-in driver with `name`, as used in the `merge` attribute.
+    pub fn by_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|driver| driver.as_str() == name).copied()
+    }
+}
+
+/// A user-defined merge driver, configured in the `merge.<name>` section of the git configuration.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Driver {
+    /// The name of the driver, as referred to by `merge=<name>` in git-attributes.
+    pub name: BString,
+    /// A human-readable description of the driver, as set by `merge.<name>.name`.
+    pub display_name: BString,
+    /// The command to run to perform the merge, as set by `merge.<name>.driver`.
+    ///
+    /// It's executed by the shell after replacing the following placeholders:
+    ///
+    /// * `%O` - the path to a file with the content of the ancestor.
+    /// * `%A` - the path to a file with the content of the current version, which is also where the result is expected.
+    /// * `%B` - the path to a file with the content of the other version.
+    /// * `%L` - the size of conflict markers.
+    /// * `%P` - the path of the merged file in the worktree.
+    /// * `%S`, `%X` and `%Y` - the labels of the ancestor, the current and the other version respectively.
+    ///
+    /// If the command exits with a non-zero status, the result is considered conflicting.
+    pub command: BString,
+    /// The name of the driver to use when performing an internal merge of merge-bases, as set by `merge.<name>.recursive`.
+    pub recursive: Option<BString>,
+}
+
+/// The names of the three sides of a merge, as they appear in conflict markers.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Labels<'a> {
+    /// The name of the merge-base, shown in the `diff3` and `zdiff3` conflict styles.
+    pub ancestor: Option<&'a BStr>,
+    /// The name of the current version, or *ours*.
+    pub current: Option<&'a BStr>,
+    /// The name of the other version, or *theirs*.
+    pub other: Option<&'a BStr>,
+}
+
+/// A utility to merge blobs while respecting the `merge` and `conflict-marker-size` git-attributes, along with
+/// user-defined merge drivers.
+#[derive(Clone)]
+pub struct Platform {
+    /// All user-defined drivers, sorted by name for lookup.
+    drivers: Vec<Driver>,
+    /// Pre-initialized attributes to query the driver and the size of conflict markers.
+    attrs: gix_attributes::search::Outcome,
+    /// Options to control how merges are performed.
+    pub options: platform::Options,
+}
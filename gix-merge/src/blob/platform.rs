@@ -0,0 +1,177 @@
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::blob::{
+    builtin_driver,
+    builtin_driver::{binary, text},
+    driver, BuiltinDriver, Driver, Labels, Platform, Resolution,
+};
+
+/// Options for use in [`Platform::merge()`].
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct Options {
+    /// Options for the builtin text driver, which is also used by the `union` driver.
+    pub text: text::Options,
+    /// How to resolve conflicts of binary files, or `None` to keep our version and report a conflict.
+    pub resolve_binary_with: Option<binary::ResolveWith>,
+    /// The name of the driver to use if the `merge` attribute is set or unspecified, as configured with `merge.default`.
+    ///
+    /// It may be the name of a user-defined or of a built-in driver. If `None`, the `text` driver is used.
+    pub default_driver: Option<BString>,
+}
+
+/// The driver chosen to merge a resource, as determined by its git-attributes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum DriverChoice {
+    /// Use a built-in driver.
+    BuiltIn(BuiltinDriver),
+    /// Use the user-defined driver at the given index into the [drivers](Platform::drivers()) of the platform.
+    Index(usize),
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod merge {
+    /// The error returned by [Platform::merge()](crate::blob::Platform::merge()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        RunDriver(#[from] crate::blob::driver::run::Error),
+    }
+}
+
+/// Lifecycle
+impl Platform {
+    /// Create a new instance which knows all user-defined `drivers` and uses `options` to control the merge.
+    pub fn new(mut drivers: Vec<Driver>, options: Options) -> Self {
+        drivers.sort_by(|a, b| a.name.cmp(&b.name));
+        Platform {
+            drivers,
+            attrs: {
+                let mut out = gix_attributes::search::Outcome::default();
+                out.initialize_with_selection(&Default::default(), ["merge", "conflict-marker-size"]);
+                out
+            },
+            options,
+        }
+    }
+}
+
+/// Access
+impl Platform {
+    /// Return all drivers that this instance was initialized with, sorted by name.
+    pub fn drivers(&self) -> &[Driver] {
+        &self.drivers
+    }
+}
+
+/// Merging
+impl Platform {
+    /// Merge `current`, `ancestor` and `other` which are all versions of the file at `rela_path`, and write the result into `out`.
+    ///
+    /// `attributes` must fill in the git-attributes at `rela_path` to determine the driver to use, as well as the size of
+    /// conflict markers. `labels` are used in conflict markers, or are passed to user-defined drivers.
+    ///
+    /// Note that text that appears to be binary is merged with the `binary` driver, and that user-defined drivers are only
+    /// used if they are known to this instance.
+    #[allow(clippy::too_many_arguments)]
+    pub fn merge(
+        &mut self,
+        out: &mut Vec<u8>,
+        rela_path: &BStr,
+        labels: Labels<'_>,
+        current: &[u8],
+        ancestor: &[u8],
+        other: &[u8],
+        attributes: &mut dyn FnMut(&BStr, &mut gix_attributes::search::Outcome),
+    ) -> Result<Resolution, merge::Error> {
+        let (driver, marker_size) = self.driver_for(rela_path, attributes);
+        let builtin = match driver {
+            DriverChoice::Index(idx) => {
+                return Ok(self.drivers[idx].run(
+                    out,
+                    current,
+                    ancestor,
+                    other,
+                    driver::Context {
+                        rela_path,
+                        marker_size,
+                        labels,
+                    },
+                )?)
+            }
+            DriverChoice::BuiltIn(builtin) => builtin,
+        };
+
+        let is_binary = [current, ancestor, other]
+            .iter()
+            .any(|data| builtin_driver::is_binary(data));
+        Ok(match builtin {
+            BuiltinDriver::Text | BuiltinDriver::Union if !is_binary => {
+                let mut options = self.options.text;
+                options.conflict = match options.conflict {
+                    _ if builtin == BuiltinDriver::Union => text::Conflict::ResolveWithUnion,
+                    text::Conflict::Keep { style, .. } => text::Conflict::Keep { style, marker_size },
+                    other => other,
+                };
+                text::merge(out, labels, current, ancestor, other, options)
+            }
+            BuiltinDriver::Text | BuiltinDriver::Union | BuiltinDriver::Binary => {
+                let (pick, resolution) = binary::merge(current, ancestor, other, self.options.resolve_binary_with);
+                out.clear();
+                out.extend_from_slice(match pick {
+                    binary::Pick::Ancestor => ancestor,
+                    binary::Pick::Ours => current,
+                    binary::Pick::Theirs => other,
+                });
+                resolution
+            }
+        })
+    }
+
+    /// Return the driver to use for the resource at `rela_path` along with the size of conflict markers, using `attributes` to
+    /// fill in the git-attributes at `rela_path`.
+    pub fn driver_for(
+        &mut self,
+        rela_path: &BStr,
+        attributes: &mut dyn FnMut(&BStr, &mut gix_attributes::search::Outcome),
+    ) -> (DriverChoice, u8) {
+        attributes(rela_path, &mut self.attrs);
+        let mut selected = self.attrs.iter_selected();
+        let merge = selected.next().expect("pre-initialized with 'merge'").assignment.state;
+        let marker_size = selected
+            .next()
+            .expect("pre-initialized with 'conflict-marker-size'")
+            .assignment
+            .state
+            .as_bstr()
+            .and_then(|size| size.to_str().ok()?.parse::<u8>().ok())
+            .filter(|size| *size > 0)
+            .or(self.options.text.conflict.marker_size())
+            .unwrap_or(text::Conflict::DEFAULT_MARKER_SIZE);
+
+        let driver = if merge.is_unset() {
+            DriverChoice::BuiltIn(BuiltinDriver::Binary)
+        } else {
+            merge
+                .as_bstr()
+                .or(self.options.default_driver.as_ref().map(|name| name.as_bstr()))
+                .and_then(|name| self.driver_by_name(name))
+                .unwrap_or(DriverChoice::BuiltIn(BuiltinDriver::Text))
+        };
+        (driver, marker_size)
+    }
+
+    fn driver_by_name(&self, name: &BStr) -> Option<DriverChoice> {
+        self.drivers
+            .binary_search_by(|driver| driver.name.as_bstr().cmp(name))
+            .ok()
+            .map(DriverChoice::Index)
+            .or_else(|| {
+                name.to_str()
+                    .ok()
+                    .and_then(BuiltinDriver::by_name)
+                    .map(DriverChoice::BuiltIn)
+            })
+    }
+}
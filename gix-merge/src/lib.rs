@@ -0,0 +1,19 @@
+//! Implement three-way merges of blobs and trees, similar to what `git merge-file` and `git merge-tree` do.
+//!
+//! ## Feature Flags
+#![cfg_attr(
+    all(doc, feature = "document-features"),
+    doc = ::document_features::document_features!()
+)]
+#![cfg_attr(all(doc, feature = "document-features"), feature(doc_cfg, doc_auto_cfg))]
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+///
+#[allow(clippy::empty_docs)]
+pub mod blob;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod tree;
+pub use tree::tree;
@@ -0,0 +1,548 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_hash::{oid, ObjectId};
+use gix_object::{
+    tree::{EntryKind, EntryMode},
+    FindExt, WriteTo,
+};
+
+use crate::{
+    blob,
+    tree::{Conflict, ConflictKind, Entry, Error, Options, Outcome},
+};
+
+type Version = (EntryMode, ObjectId);
+type Flat = BTreeMap<BString, Version>;
+
+/// Perform a three-way merge of `our_tree` and `their_tree`, using `ancestor_tree` as their merge-base, and return the
+/// merged tree along with an index that records all conflicts, similar to `git merge-tree --write-tree`.
+///
+/// * `labels` are used in conflict markers of blobs, and to name entries that are moved out of the way of directories.
+/// * `objects` provide access to all trees and blobs, while `write` is called to write all merged blobs and trees.
+/// * `blob_merge` is used to merge blobs that were changed by both sides, with `attributes` filling in the git-attributes
+///   at a given path to choose the merge driver.
+/// * `diff_resource_cache` is used to detect renames if enabled in `options`.
+///
+/// Changes to an entry are merged into its renamed location, and entries that conflict are resolved as well as possible
+/// in the merged tree, favoring our side, while each side is recorded in its own stage in the returned index.
+#[allow(clippy::too_many_arguments)]
+pub fn tree<E>(
+    ancestor_tree: &oid,
+    our_tree: &oid,
+    their_tree: &oid,
+    labels: blob::Labels<'_>,
+    objects: &impl gix_object::FindObjectOrHeader,
+    mut write: impl FnMut(&dyn WriteTo) -> Result<ObjectId, E>,
+    blob_merge: &mut blob::Platform,
+    attributes: &mut dyn FnMut(&BStr, &mut gix_attributes::search::Outcome),
+    diff_resource_cache: &mut gix_diff::blob::Platform,
+    options: Options,
+) -> Result<Outcome, Error>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let ancestor = flatten(ancestor_tree, objects)?;
+    let ours = flatten(our_tree, objects)?;
+    let theirs = flatten(their_tree, objects)?;
+    let (our_renames, their_renames) = match options.rewrites {
+        Some(rewrites) => (
+            renames(&ancestor, &ours, rewrites, objects, diff_resource_cache)?,
+            renames(&ancestor, &theirs, rewrites, objects, diff_resource_cache)?,
+        ),
+        None => Default::default(),
+    };
+
+    let mut write = |object: &dyn WriteTo| write(object).map_err(|err| Error::WriteObject(Box::new(err)));
+    let mut state = State {
+        result: Default::default(),
+        stages: Default::default(),
+        conflicts: Vec::new(),
+        labels,
+        objects,
+        write: &mut write,
+        blob_merge,
+        attributes,
+        bufs: Default::default(),
+    };
+
+    let mut seen_ours = HashSet::<&BStr>::new();
+    let mut seen_theirs = HashSet::<&BStr>::new();
+    for (location, base) in &ancestor {
+        let our_location = our_renames.get(location).unwrap_or(location);
+        let their_location = their_renames.get(location).unwrap_or(location);
+        seen_ours.insert(our_location.as_bstr());
+        seen_theirs.insert(their_location.as_bstr());
+
+        let base = entry(location, *base);
+        let ours = ours.get(our_location).map(|v| entry(our_location, *v));
+        let theirs = theirs.get(their_location).map(|v| entry(their_location, *v));
+        let (we_renamed, they_renamed) = (our_location != location, their_location != location);
+        match (ours, theirs) {
+            (Some(ours), Some(theirs)) if we_renamed && they_renamed && our_location != their_location => {
+                state.rename_rename(base, ours, theirs);
+            }
+            (Some(ours), None) if we_renamed => state.rename_delete(base, Some(ours), None),
+            (None, Some(theirs)) if they_renamed => state.rename_delete(base, None, Some(theirs)),
+            (ours, theirs) => {
+                let destination = if we_renamed { our_location } else { their_location };
+                state.merge_entry(destination.as_bstr(), Some(base), ours, theirs)?;
+            }
+        }
+    }
+
+    let added: BTreeSet<&BString> = ours
+        .keys()
+        .filter(|location| !seen_ours.contains(location.as_bstr()))
+        .chain(
+            theirs
+                .keys()
+                .filter(|location| !seen_theirs.contains(location.as_bstr())),
+        )
+        .collect();
+    for location in added {
+        let lookup = |side: &Flat, seen: &HashSet<&BStr>| {
+            (!seen.contains(location.as_bstr()))
+                .then(|| side.get(location))
+                .flatten()
+                .map(|v| entry(location, *v))
+        };
+        let mut ours = lookup(&ours, &seen_ours);
+        let mut theirs = lookup(&theirs, &seen_theirs);
+        // Another entry was merged into this location, so both sides effectively added an entry here.
+        if let Some(existing) = state.result.get(location).copied() {
+            if ours.is_none() {
+                ours = Some(entry(location, existing));
+            } else if theirs.is_none() {
+                theirs = Some(entry(location, existing));
+            }
+        }
+        state.merge_entry(location.as_bstr(), None, ours, theirs)?;
+    }
+
+    state.resolve_directory_file_conflicts(&ours);
+    let State {
+        result,
+        stages,
+        mut conflicts,
+        write,
+        ..
+    } = state;
+    conflicts.sort_by(|a, b| a.location.cmp(&b.location));
+
+    let entries: Vec<_> = result
+        .iter()
+        .map(|(location, (mode, id))| (location.as_bstr(), *mode, *id))
+        .collect();
+    let tree = write_tree(&entries, write)?;
+
+    let mut index = gix_index::State::new(our_tree.kind());
+    for location in result.keys().chain(stages.keys()).collect::<BTreeSet<_>>() {
+        let mut push = |stage: u32, (mode, id): Version| {
+            use gix_index::entry::{Flags, Mode};
+            let mode = match mode.kind() {
+                EntryKind::Tree => unreachable!("trees are never part of a merge result"),
+                EntryKind::Blob => Mode::FILE,
+                EntryKind::BlobExecutable => Mode::FILE_EXECUTABLE,
+                EntryKind::Link => Mode::SYMLINK,
+                EntryKind::Commit => Mode::COMMIT,
+            };
+            index.dangerously_push_entry(
+                Default::default(),
+                id,
+                Flags::from_bits_retain(stage << 12),
+                mode,
+                location.as_bstr(),
+            );
+        };
+        match stages.get(location) {
+            Some(stages) => {
+                for (stage, version) in (1..).zip(stages.iter()) {
+                    if let Some(version) = version {
+                        push(stage, *version);
+                    }
+                }
+            }
+            None => push(0, result[location]),
+        }
+    }
+    index.sort_entries();
+
+    Ok(Outcome { tree, conflicts, index })
+}
+
+struct State<'a, Find> {
+    /// The merged entries, by location.
+    result: Flat,
+    /// The ancestor, our and their version of conflicting entries, by location.
+    stages: BTreeMap<BString, [Option<Version>; 3]>,
+    conflicts: Vec<Conflict>,
+    labels: blob::Labels<'a>,
+    objects: &'a Find,
+    write: &'a mut dyn FnMut(&dyn WriteTo) -> Result<ObjectId, Error>,
+    blob_merge: &'a mut blob::Platform,
+    attributes: &'a mut dyn FnMut(&BStr, &mut gix_attributes::search::Outcome),
+    bufs: [Vec<u8>; 4],
+}
+
+impl<Find> State<'_, Find>
+where
+    Find: gix_object::FindObjectOrHeader,
+{
+    fn merge_entry(
+        &mut self,
+        destination: &BStr,
+        ancestor: Option<Entry>,
+        ours: Option<Entry>,
+        theirs: Option<Entry>,
+    ) -> Result<(), Error> {
+        let version = |e: &Option<Entry>| e.as_ref().map(|e| (e.mode, e.id));
+        let (ancestor_version, our_version, their_version) = (version(&ancestor), version(&ours), version(&theirs));
+        let resolved = if our_version == their_version || ancestor_version == their_version {
+            Some(our_version)
+        } else if ancestor_version == our_version {
+            Some(their_version)
+        } else {
+            None
+        };
+        if let Some(version) = resolved {
+            if let Some(version) = version {
+                self.result.insert(destination.to_owned(), version);
+            }
+            return Ok(());
+        }
+
+        let (our_entry, their_entry) = match (ours.as_ref(), theirs.as_ref()) {
+            (Some(ours), Some(theirs)) => (ours, theirs),
+            (Some(present), None) | (None, Some(present)) => {
+                self.result.insert(destination.to_owned(), (present.mode, present.id));
+                self.record(destination, ConflictKind::ModifyDelete, ancestor, ours, theirs);
+                return Ok(());
+            }
+            (None, None) => unreachable!("both sides deleting the entry is resolved"),
+        };
+
+        let is_blob = |mode: EntryMode| matches!(mode.kind(), EntryKind::Blob | EntryKind::BlobExecutable);
+        if !is_blob(our_entry.mode) || !is_blob(their_entry.mode) {
+            let kind = if our_entry.mode.kind() != their_entry.mode.kind() {
+                ConflictKind::Type
+            } else if our_entry.mode.is_commit() {
+                ConflictKind::Submodule
+            } else if ancestor.is_none() {
+                ConflictKind::AddAdd
+            } else {
+                ConflictKind::Content
+            };
+            self.result
+                .insert(destination.to_owned(), (our_entry.mode, our_entry.id));
+            self.record(destination, kind, ancestor, ours, theirs);
+            return Ok(());
+        }
+
+        let ancestor_blob = ancestor.as_ref().filter(|e| is_blob(e.mode));
+        let ancestor_mode = ancestor_blob.map(|e| e.mode);
+        let (mode, mode_conflict) = if our_entry.mode == their_entry.mode || ancestor_mode == Some(their_entry.mode) {
+            (our_entry.mode, false)
+        } else if ancestor_mode == Some(our_entry.mode) {
+            (their_entry.mode, false)
+        } else {
+            (our_entry.mode, ancestor_mode.is_some())
+        };
+
+        let ancestor_id = ancestor_blob.map(|e| e.id);
+        let (id, content_conflict) = if our_entry.id == their_entry.id || ancestor_id == Some(their_entry.id) {
+            (our_entry.id, false)
+        } else if ancestor_id == Some(our_entry.id) {
+            (their_entry.id, false)
+        } else {
+            let [out, current, base, other] = &mut self.bufs;
+            let current = self.objects.find_blob(&our_entry.id, current)?.data;
+            let other = self.objects.find_blob(&their_entry.id, other)?.data;
+            let base = match ancestor_id {
+                Some(id) => self.objects.find_blob(&id, base)?.data,
+                None => &[],
+            };
+            let resolution = self.blob_merge.merge(
+                out,
+                destination,
+                self.labels,
+                current,
+                base,
+                other,
+                &mut *self.attributes,
+            )?;
+            let id = (self.write)(&gix_object::BlobRef { data: out })?;
+            (id, resolution == blob::Resolution::Conflict)
+        };
+
+        self.result.insert(destination.to_owned(), (mode, id));
+        if content_conflict || mode_conflict {
+            let kind = if ancestor_blob.is_none() {
+                ConflictKind::AddAdd
+            } else if content_conflict {
+                ConflictKind::Content
+            } else {
+                ConflictKind::Mode
+            };
+            self.record(destination, kind, ancestor, ours, theirs);
+        }
+        Ok(())
+    }
+
+    /// Keep both renamed entries, and record each version at its own location, just like `git` does.
+    fn rename_rename(&mut self, ancestor: Entry, ours: Entry, theirs: Entry) {
+        self.result.insert(ours.location.clone(), (ours.mode, ours.id));
+        self.result.insert(theirs.location.clone(), (theirs.mode, theirs.id));
+        self.stages.insert(
+            ancestor.location.clone(),
+            [Some((ancestor.mode, ancestor.id)), None, None],
+        );
+        self.stages
+            .insert(ours.location.clone(), [None, Some((ours.mode, ours.id)), None]);
+        self.stages
+            .insert(theirs.location.clone(), [None, None, Some((theirs.mode, theirs.id))]);
+        self.conflicts.push(Conflict {
+            kind: ConflictKind::RenameRename,
+            location: ancestor.location.clone(),
+            ancestor: Some(ancestor),
+            ours: Some(ours),
+            theirs: Some(theirs),
+        });
+    }
+
+    /// Keep the renamed entry at its new location.
+    fn rename_delete(&mut self, ancestor: Entry, ours: Option<Entry>, theirs: Option<Entry>) {
+        let renamed = ours.as_ref().or(theirs.as_ref()).expect("one side renamed");
+        let location = renamed.location.clone();
+        self.result.insert(location.clone(), (renamed.mode, renamed.id));
+        self.record(
+            location.as_bstr(),
+            ConflictKind::RenameDelete,
+            Some(ancestor),
+            ours,
+            theirs,
+        );
+    }
+
+    fn record(
+        &mut self,
+        location: &BStr,
+        kind: ConflictKind,
+        ancestor: Option<Entry>,
+        ours: Option<Entry>,
+        theirs: Option<Entry>,
+    ) {
+        let version = |e: &Option<Entry>| e.as_ref().map(|e| (e.mode, e.id));
+        self.stages.insert(
+            location.to_owned(),
+            [version(&ancestor), version(&ours), version(&theirs)],
+        );
+        self.conflicts.push(Conflict {
+            kind,
+            location: location.to_owned(),
+            ancestor,
+            ours,
+            theirs,
+        });
+    }
+
+    /// Move entries that are in the way of a directory to `<location>~<label>`, with the label of the side they are from.
+    fn resolve_directory_file_conflicts(&mut self, ours: &Flat) {
+        let in_the_way: Vec<BString> = self
+            .result
+            .keys()
+            .filter(|location| {
+                let mut directory = (*location).clone();
+                directory.push(b'/');
+                matches!(
+                    self.result.range(directory.clone()..).next(),
+                    Some((next, _)) if next.starts_with(&directory)
+                )
+            })
+            .cloned()
+            .collect();
+
+        for location in in_the_way {
+            let version = self.result.remove(&location).expect("present");
+            let is_ours = ours.get(&location) == Some(&version);
+            let label = if is_ours {
+                self.labels.current.unwrap_or("ours".into())
+            } else {
+                self.labels.other.unwrap_or("theirs".into())
+            };
+            let mut new_location = location.clone();
+            new_location.push(b'~');
+            new_location.extend(label.iter().map(|b| if *b == b'/' { b'_' } else { *b }));
+            let base_len = new_location.len();
+            let mut suffix = 0;
+            while self.result.contains_key(&new_location) {
+                suffix += 1;
+                new_location.truncate(base_len);
+                new_location.push_str(format!("_{suffix}"));
+            }
+
+            self.result.insert(new_location.clone(), version);
+            let stages = self.stages.remove(&location).unwrap_or(if is_ours {
+                [None, Some(version), None]
+            } else {
+                [None, None, Some(version)]
+            });
+            self.stages.insert(new_location.clone(), stages);
+            for conflict in self.conflicts.iter_mut().filter(|c| c.location == location) {
+                conflict.location = new_location.clone();
+            }
+            let side = Some(Entry {
+                location: location.clone(),
+                mode: version.0,
+                id: version.1,
+            });
+            let (ours, theirs) = if is_ours { (side, None) } else { (None, side) };
+            self.conflicts.push(Conflict {
+                kind: ConflictKind::DirectoryFile,
+                location: new_location,
+                ancestor: None,
+                ours,
+                theirs,
+            });
+        }
+    }
+}
+
+fn entry(location: &BString, (mode, id): Version) -> Entry {
+    Entry {
+        location: location.clone(),
+        mode,
+        id,
+    }
+}
+
+/// Return all non-tree entries of `tree`, recursively.
+fn flatten(tree: &oid, objects: &impl gix_object::Find) -> Result<Flat, Error> {
+    let mut buf = Vec::new();
+    let root = objects.find_tree_iter(tree, &mut buf)?;
+    let mut recorder = gix_traverse::tree::Recorder::default();
+    gix_traverse::tree::breadthfirst(
+        root,
+        gix_traverse::tree::breadthfirst::State::default(),
+        objects,
+        &mut recorder,
+    )?;
+    Ok(recorder
+        .records
+        .into_iter()
+        .filter(|entry| !entry.mode.is_tree())
+        .map(|entry| (entry.filepath, (entry.mode, entry.oid)))
+        .collect())
+}
+
+/// Return a mapping of locations in `ancestor` to the locations they were renamed to in `side`.
+fn renames(
+    ancestor: &Flat,
+    side: &Flat,
+    mut rewrites: gix_diff::Rewrites,
+    objects: &impl gix_object::FindObjectOrHeader,
+    diff_resource_cache: &mut gix_diff::blob::Platform,
+) -> Result<HashMap<BString, BString>, Error> {
+    use gix_diff::rewrites::tracker::{visit::SourceKind, ChangeKind};
+
+    rewrites.copies = None;
+    let mut tracker = gix_diff::rewrites::Tracker::new(rewrites);
+    let deletions = ancestor
+        .iter()
+        .filter(|(location, _)| !side.contains_key(*location))
+        .map(|entry| (entry, ChangeKind::Deletion));
+    let additions = side
+        .iter()
+        .filter(|(location, _)| !ancestor.contains_key(*location))
+        .map(|entry| (entry, ChangeKind::Addition));
+    for ((location, (mode, id)), kind) in deletions.chain(additions) {
+        let change = TrackedChange {
+            kind,
+            mode: *mode,
+            id: *id,
+        };
+        tracker.try_push_change(change, location.as_bstr());
+    }
+
+    let mut out = HashMap::new();
+    tracker
+        .emit(
+            |destination, source| {
+                if let Some(source) = source.filter(|source| source.kind == SourceKind::Rename) {
+                    out.insert(source.location.to_owned(), destination.location.to_owned());
+                }
+                gix_diff::tree::visit::Action::Continue
+            },
+            diff_resource_cache,
+            objects,
+            |_push| Ok::<_, std::convert::Infallible>(()),
+        )
+        .map_err(|err| Error::TrackRenames(Box::new(err)))?;
+    Ok(out)
+}
+
+#[derive(Clone)]
+struct TrackedChange {
+    kind: gix_diff::rewrites::tracker::ChangeKind,
+    mode: EntryMode,
+    id: ObjectId,
+}
+
+impl gix_diff::rewrites::tracker::Change for TrackedChange {
+    fn id(&self) -> &oid {
+        &self.id
+    }
+
+    fn kind(&self) -> gix_diff::rewrites::tracker::ChangeKind {
+        self.kind
+    }
+
+    fn entry_mode(&self) -> EntryMode {
+        self.mode
+    }
+
+    fn id_and_entry_mode(&self) -> (&oid, EntryMode) {
+        (&self.id, self.mode)
+    }
+}
+
+/// Write `entries`, sorted by location, as tree and return its id, writing sub-trees as needed.
+fn write_tree(
+    entries: &[(&BStr, EntryMode, ObjectId)],
+    write: &mut dyn FnMut(&dyn WriteTo) -> Result<ObjectId, Error>,
+) -> Result<ObjectId, Error> {
+    let mut tree = gix_object::Tree::empty();
+    let mut idx = 0;
+    while idx < entries.len() {
+        let (location, mode, id) = entries[idx];
+        match location.find_byte(b'/') {
+            None => {
+                tree.entries.push(gix_object::tree::Entry {
+                    mode,
+                    filename: location.to_owned(),
+                    oid: id,
+                });
+                idx += 1;
+            }
+            Some(pos) => {
+                let directory = &location[..=pos];
+                let end = entries[idx..]
+                    .iter()
+                    .position(|(location, _, _)| !location.starts_with(directory))
+                    .map_or(entries.len(), |len| idx + len);
+                let children: Vec<_> = entries[idx..end]
+                    .iter()
+                    .map(|(location, mode, id)| (location[pos + 1..].as_bstr(), *mode, *id))
+                    .collect();
+                tree.entries.push(gix_object::tree::Entry {
+                    mode: EntryKind::Tree.into(),
+                    filename: location[..pos].into(),
+                    oid: write_tree(&children, write)?,
+                });
+                idx = end;
+            }
+        }
+    }
+    tree.entries.sort();
+    write(&tree)
+}
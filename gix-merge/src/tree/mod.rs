@@ -0,0 +1,114 @@
+//! Merge trees by merging each of their entries, with support for renames and for merging blobs with the
+//! [blob-merge platform](crate::blob::Platform).
+use bstr::BString;
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+mod function;
+pub use function::tree;
+
+/// Options for use in [`tree()`].
+#[derive(Default, Debug, Clone)]
+pub struct Options {
+    /// If `Some(…)`, detect renames between the ancestor and each side so changes can be merged into renamed files.
+    ///
+    /// Note that copy-detection is always disabled. If `None`, renames are seen as deletion and addition.
+    pub rewrites: Option<gix_diff::Rewrites>,
+}
+
+/// The outcome of [`tree()`].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The id of the merged tree, which was written in full. Conflicting blobs are contained with conflict markers,
+    /// and otherwise conflicting entries use our version.
+    pub tree: ObjectId,
+    /// All conflicts that were encountered, ordered by [location](Conflict::location).
+    pub conflicts: Vec<Conflict>,
+    /// An index that represents the merged tree, with conflicting entries recorded in stages 1 (ancestor), 2 (ours) and
+    /// 3 (theirs) instead of stage 0, just like `git merge` would leave them.
+    ///
+    /// Note that entries have no stat information.
+    pub index: gix_index::State,
+}
+
+impl Outcome {
+    /// Return `true` if there are conflicts that need to be resolved by the user.
+    pub fn has_unresolved_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
+
+/// An entry in one of the trees involved in a merge, which is never a tree itself.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entry {
+    /// The location of the entry relative to the root of its tree.
+    pub location: BString,
+    /// The mode of the entry.
+    pub mode: EntryMode,
+    /// The id of the object the entry points to.
+    pub id: ObjectId,
+}
+
+/// Classify a [`Conflict`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConflictKind {
+    /// Both sides changed the content of a blob in ways that couldn't be merged.
+    Content,
+    /// Both sides changed the executable bit of a blob in different ways, and ours was used.
+    Mode,
+    /// One side modified the entry while the other side deleted it.
+    ModifyDelete,
+    /// Both sides added an entry at the same location, with different content that couldn't be merged.
+    AddAdd,
+    /// Both sides changed the entry in a way that changed its type, like from blob to symlink, and ours was used.
+    Type,
+    /// Both sides renamed the same entry to different locations, which are both kept.
+    RenameRename,
+    /// One side renamed the entry while the other side deleted it.
+    RenameDelete,
+    /// An entry was moved out of the way to make room for a directory of the same name.
+    DirectoryFile,
+    /// Both sides changed the commit a submodule points to in different ways, and ours was used.
+    Submodule,
+}
+
+/// A conflict that was encountered while merging trees.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conflict {
+    /// The kind of conflict.
+    pub kind: ConflictKind,
+    /// The location of the conflicting entry in the merged tree and index.
+    pub location: BString,
+    /// The entry in the ancestor tree, if there was one.
+    pub ancestor: Option<Entry>,
+    /// Our version of the entry, if there was one.
+    pub ours: Option<Entry>,
+    /// Their version of the entry, if there was one.
+    pub theirs: Option<Entry>,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod error {
+    /// The error returned by [`tree()`](crate::tree::tree()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not find tree to merge")]
+        FindTree(#[from] gix_object::find::existing_iter::Error),
+        #[error("Could not traverse tree to merge")]
+        Traverse(#[from] gix_traverse::tree::breadthfirst::Error),
+        #[error("Could not find blob to merge")]
+        FindBlob(#[from] gix_object::find::existing_object::Error),
+        #[error("Could not detect renames")]
+        TrackRenames(#[source] Box<gix_diff::rewrites::tracker::emit::Error>),
+        #[error(transparent)]
+        MergeBlob(#[from] crate::blob::platform::merge::Error),
+        #[error("Could not write merged object")]
+        WriteObject(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    }
+}
+pub use error::Error;
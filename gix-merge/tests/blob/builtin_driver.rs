@@ -0,0 +1,147 @@
+use gix_merge::blob::{
+    builtin_driver::{binary, binary::ResolveWith},
+    Resolution,
+};
+
+#[test]
+fn binary() {
+    assert_eq!(
+        binary::merge(b"a", b"a", b"a", None),
+        (binary::Pick::Ours, Resolution::Complete),
+        "nothing changed"
+    );
+    assert_eq!(
+        binary::merge(b"b", b"a", b"a", None),
+        (binary::Pick::Ours, Resolution::Complete),
+        "only ours changed"
+    );
+    assert_eq!(
+        binary::merge(b"a", b"a", b"b", None),
+        (binary::Pick::Theirs, Resolution::Complete),
+        "only theirs changed"
+    );
+    assert_eq!(
+        binary::merge(b"b", b"a", b"b", None),
+        (binary::Pick::Ours, Resolution::Complete),
+        "both changed in the same way"
+    );
+    assert_eq!(
+        binary::merge(b"b", b"a", b"c", None),
+        (binary::Pick::Ours, Resolution::Conflict),
+        "a conflict keeps our side"
+    );
+    for (resolve, expected) in [
+        (ResolveWith::Ancestor, binary::Pick::Ancestor),
+        (ResolveWith::Ours, binary::Pick::Ours),
+        (ResolveWith::Theirs, binary::Pick::Theirs),
+    ] {
+        assert_eq!(
+            binary::merge(b"b", b"a", b"c", Some(resolve)),
+            (expected, Resolution::CompleteWithAutoResolvedConflict),
+        );
+    }
+}
+
+mod text {
+    use bstr::ByteSlice;
+    use gix_merge::blob::{
+        builtin_driver::text::{self, Conflict, ConflictStyle},
+        Labels, Resolution,
+    };
+    use pretty_assertions::assert_str_eq;
+
+    #[test]
+    fn baseline() -> crate::Result {
+        let root = gix_testtools::scripted_fixture_read_only("text-baseline.sh")?;
+        let labels = Labels {
+            ancestor: Some("base".into()),
+            current: Some("ours".into()),
+            other: Some("theirs".into()),
+        };
+        let keep = |style| Conflict::Keep {
+            style,
+            marker_size: Conflict::DEFAULT_MARKER_SIZE,
+        };
+        let mut num_cases = 0;
+        let mut out = Vec::new();
+        for entry in std::fs::read_dir(&root)? {
+            let case = entry?.path();
+            let read = |name: &str| std::fs::read(case.join(name));
+            let (ours, base, theirs) = (read("ours")?, read("base")?, read("theirs")?);
+            for (expected, conflict) in [
+                ("merge", keep(ConflictStyle::Merge)),
+                ("diff3", keep(ConflictStyle::Diff3)),
+                ("zdiff3", keep(ConflictStyle::ZealousDiff3)),
+                ("union", Conflict::ResolveWithUnion),
+                ("resolve-ours", Conflict::ResolveWithOurs),
+                ("resolve-theirs", Conflict::ResolveWithTheirs),
+            ] {
+                let resolution = text::merge(
+                    &mut out,
+                    labels,
+                    &ours,
+                    &base,
+                    &theirs,
+                    text::Options {
+                        conflict,
+                        ..Default::default()
+                    },
+                );
+                let expected_data = read(expected)?;
+                assert_str_eq!(
+                    out.as_bstr().to_str_lossy(),
+                    expected_data.as_bstr().to_str_lossy(),
+                    "{case:?}: {expected}"
+                );
+                let has_markers = expected_data.find(b"<<<<<<<").is_some();
+                assert_eq!(
+                    resolution == Resolution::Conflict,
+                    has_markers,
+                    "{case:?}: {expected}: resolution {resolution:?} must match the presence of markers"
+                );
+            }
+            num_cases += 1;
+        }
+        assert_eq!(num_cases, 11, "all cases were checked");
+        Ok(())
+    }
+
+    #[test]
+    fn marker_size_and_missing_labels() {
+        let mut out = Vec::new();
+        let resolution = text::merge(
+            &mut out,
+            Default::default(),
+            b"ours\n",
+            b"base\n",
+            b"theirs\n",
+            text::Options {
+                conflict: Conflict::Keep {
+                    style: ConflictStyle::Diff3,
+                    marker_size: 3,
+                },
+                ..Default::default()
+            },
+        );
+        assert_eq!(resolution, Resolution::Conflict);
+        assert_eq!(out.as_bstr(), "<<<\nours\n|||\nbase\n===\ntheirs\n>>>\n");
+    }
+
+    #[test]
+    fn auto_resolved_conflicts_are_reported() {
+        let mut out = Vec::new();
+        let resolution = text::merge(
+            &mut out,
+            Default::default(),
+            b"ours\n",
+            b"base\n",
+            b"theirs\n",
+            text::Options {
+                conflict: Conflict::ResolveWithUnion,
+                ..Default::default()
+            },
+        );
+        assert_eq!(resolution, Resolution::CompleteWithAutoResolvedConflict);
+        assert_eq!(out.as_bstr(), "ours\ntheirs\n");
+    }
+}
@@ -0,0 +1,2 @@
+mod builtin_driver;
+mod platform;
@@ -0,0 +1,228 @@
+use bstr::{BStr, ByteSlice};
+use gix_merge::blob::{builtin_driver::text, platform, BuiltinDriver, Driver, Labels, Platform, Resolution};
+use gix_worktree::stack::state::attributes;
+
+#[test]
+fn driver_selection() -> crate::Result {
+    let mut attributes = attributes_at_fixture()?;
+    let mut platform = Platform::new(drivers(), Default::default());
+    let mut choice = |path: &str| platform.driver_for(path.into(), &mut attributes);
+
+    assert_eq!(
+        choice("unspecified"),
+        (platform::DriverChoice::BuiltIn(BuiltinDriver::Text), 7)
+    );
+    assert_eq!(
+        choice("unset"),
+        (platform::DriverChoice::BuiltIn(BuiltinDriver::Binary), 7)
+    );
+    assert_eq!(
+        choice("union"),
+        (platform::DriverChoice::BuiltIn(BuiltinDriver::Union), 7)
+    );
+    assert_eq!(
+        choice("binary"),
+        (platform::DriverChoice::BuiltIn(BuiltinDriver::Binary), 7)
+    );
+    assert_eq!(choice("custom"), (platform::DriverChoice::Index(0), 7));
+    assert_eq!(
+        choice("unknown"),
+        (platform::DriverChoice::BuiltIn(BuiltinDriver::Text), 7),
+        "unknown drivers fall back to the text driver"
+    );
+    assert_eq!(
+        choice("small"),
+        (platform::DriverChoice::BuiltIn(BuiltinDriver::Text), 3),
+        "the marker size can be adjusted"
+    );
+
+    let mut platform = Platform::new(
+        drivers(),
+        platform::Options {
+            default_driver: Some("union".into()),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        platform.driver_for("unspecified".into(), &mut attributes),
+        (platform::DriverChoice::BuiltIn(BuiltinDriver::Union), 7),
+        "the default driver is used if the attribute is unspecified"
+    );
+    assert_eq!(
+        platform.driver_for("unset".into(), &mut attributes),
+        (platform::DriverChoice::BuiltIn(BuiltinDriver::Binary), 7),
+        "but not if it's unset"
+    );
+    Ok(())
+}
+
+#[test]
+fn merge_with_builtin_drivers() -> crate::Result {
+    let mut attributes = attributes_at_fixture()?;
+    let mut platform = Platform::new(drivers(), Default::default());
+    let mut out = Vec::new();
+    let mut merge = |path: &str, ours: &[u8], base: &[u8], theirs: &[u8]| -> crate::Result<(Resolution, String)> {
+        let resolution = platform.merge(&mut out, path.into(), labels(), ours, base, theirs, &mut attributes)?;
+        Ok((resolution, out.to_str_lossy().into_owned()))
+    };
+
+    assert_eq!(
+        merge("unspecified", b"ours\n", b"base\n", b"theirs\n")?,
+        (
+            Resolution::Conflict,
+            "<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n".into()
+        )
+    );
+    assert_eq!(
+        merge("small", b"ours\n", b"base\n", b"theirs\n")?,
+        (Resolution::Conflict, "<<< ours\nours\n===\ntheirs\n>>> theirs\n".into())
+    );
+    assert_eq!(
+        merge("union", b"ours\n", b"base\n", b"theirs\n")?,
+        (Resolution::CompleteWithAutoResolvedConflict, "ours\ntheirs\n".into())
+    );
+    assert_eq!(
+        merge("unset", b"ours\n", b"base\n", b"theirs\n")?,
+        (Resolution::Conflict, "ours\n".into()),
+        "binary merges keep our version"
+    );
+    assert_eq!(
+        merge("unspecified", b"ours\0", b"base\n", b"theirs\n")?,
+        (Resolution::Conflict, "ours\0".into()),
+        "binary content is merged with the binary driver"
+    );
+    assert_eq!(
+        merge("unset", b"base\n", b"base\n", b"theirs\n")?,
+        (Resolution::Complete, "theirs\n".into()),
+        "a binary merge without conflict"
+    );
+    Ok(())
+}
+
+#[test]
+fn merge_with_resolution_options() -> crate::Result {
+    let mut attributes = attributes_at_fixture()?;
+    let mut platform = Platform::new(
+        Vec::new(),
+        platform::Options {
+            text: text::Options {
+                conflict: text::Conflict::ResolveWithTheirs,
+                ..Default::default()
+            },
+            resolve_binary_with: Some(gix_merge::blob::builtin_driver::binary::ResolveWith::Ancestor),
+            ..Default::default()
+        },
+    );
+    let mut out = Vec::new();
+    let resolution = platform.merge(
+        &mut out,
+        "small".into(),
+        labels(),
+        b"ours\n",
+        b"base\n",
+        b"theirs\n",
+        &mut attributes,
+    )?;
+    assert_eq!(resolution, Resolution::CompleteWithAutoResolvedConflict);
+    assert_eq!(out.as_bstr(), "theirs\n");
+
+    let resolution = platform.merge(
+        &mut out,
+        "unset".into(),
+        labels(),
+        b"ours\n",
+        b"base\n",
+        b"theirs\n",
+        &mut attributes,
+    )?;
+    assert_eq!(resolution, Resolution::CompleteWithAutoResolvedConflict);
+    assert_eq!(out.as_bstr(), "base\n");
+    Ok(())
+}
+
+#[test]
+#[cfg(not(windows))]
+fn merge_with_user_defined_drivers() -> crate::Result {
+    let mut attributes = attributes_at_fixture()?;
+    let mut platform = Platform::new(drivers(), Default::default());
+    let mut out = Vec::new();
+    let resolution = platform.merge(
+        &mut out,
+        "custom".into(),
+        labels(),
+        b"ours\n",
+        b"base\n",
+        b"theirs\n",
+        &mut attributes,
+    )?;
+    assert_eq!(resolution, Resolution::Complete);
+    assert_eq!(
+        out.as_bstr(),
+        "ours\nbase\ntheirs\n7 custom base ours theirs\n",
+        "all placeholders are substituted, and the result is read from the file of the current version"
+    );
+
+    let resolution = platform.merge(
+        &mut out,
+        "failing".into(),
+        labels(),
+        b"ours\n",
+        b"base\n",
+        b"theirs\n",
+        &mut attributes,
+    )?;
+    assert_eq!(
+        resolution,
+        Resolution::Conflict,
+        "a non-zero exit code indicates a conflict"
+    );
+    assert_eq!(out.as_bstr(), "ours\n", "the driver didn't change the current version");
+    Ok(())
+}
+
+fn labels() -> Labels<'static> {
+    Labels {
+        ancestor: Some("base".into()),
+        current: Some("ours".into()),
+        other: Some("theirs".into()),
+    }
+}
+
+fn drivers() -> Vec<Driver> {
+    vec![
+        Driver {
+            name: "failing".into(),
+            display_name: "always fails".into(),
+            command: "exit 1".into(),
+            recursive: None,
+        },
+        Driver {
+            name: "custom".into(),
+            display_name: "concatenate all versions".into(),
+            command: "cat %A %O %B > %A.tmp && echo %L %P %S %X %Y >> %A.tmp && mv %A.tmp %A".into(),
+            recursive: None,
+        },
+    ]
+}
+
+fn attributes_at_fixture() -> crate::Result<impl FnMut(&BStr, &mut gix_attributes::search::Outcome)> {
+    let root = gix_testtools::scripted_fixture_read_only("make_blob_attributes.sh")?;
+    let mut stack = gix_worktree::Stack::new(
+        root,
+        gix_worktree::stack::State::AttributesStack(gix_worktree::stack::state::Attributes::new(
+            Default::default(),
+            None,
+            attributes::Source::WorktreeThenIdMapping,
+            Default::default(),
+        )),
+        gix_worktree::glob::pattern::Case::Sensitive,
+        Vec::new(),
+        Vec::new(),
+    );
+    Ok(move |path: &BStr, out: &mut gix_attributes::search::Outcome| {
+        let platform = stack
+            .at_entry(path, Some(false), &gix_object::find::Never)
+            .expect("no IO error");
+        platform.matching_attributes(out);
+    })
+}
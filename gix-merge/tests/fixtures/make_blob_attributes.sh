@@ -0,0 +1,12 @@
+#!/bin/bash
+set -eu -o pipefail
+
+cat <<EOF >.gitattributes
+unset -merge
+union merge=union
+binary merge=binary
+custom merge=custom
+failing merge=failing
+unknown merge=unknown
+small conflict-marker-size=3
+EOF
@@ -0,0 +1,108 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Each directory contains `base`, `ours` and `theirs`, along with the result of `git merge-file`
+# for each conflict style in `merge`, `diff3` and `zdiff3`.
+function baseline() {
+  local name=${1:?first argument is the name of the case}
+  (cd "$name"
+    for style in merge diff3 zdiff3; do
+      local args=()
+      if [ "$style" != merge ]; then
+        args=("--$style")
+      fi
+      git merge-file -p ${args[@]+"${args[@]}"} -L ours -L base -L theirs ours base theirs > "$style" || true
+    done
+    git merge-file -p --union ours base theirs > union || true
+    git merge-file -p --ours ours base theirs > resolve-ours || true
+    git merge-file -p --theirs ours base theirs > resolve-theirs || true
+  )
+}
+
+mkdir clean-non-overlapping
+(cd clean-non-overlapping
+  printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > base
+  printf '1\nours\n3\n4\n5\n6\n7\n8\n9\n' > ours
+  printf '1\n2\n3\n4\n5\n6\n7\ntheirs\n9\n' > theirs
+)
+baseline clean-non-overlapping
+
+mkdir same-change
+(cd same-change
+  printf '1\n2\n3\n' > base
+  printf '1\nsame\n3\n' > ours
+  cp ours theirs
+)
+baseline same-change
+
+mkdir simple-conflict
+(cd simple-conflict
+  printf '1\n2\n3\n4\n5\n' > base
+  printf '1\n2\nours\n4\n5\n' > ours
+  printf '1\n2\ntheirs\n4\n5\n' > theirs
+)
+baseline simple-conflict
+
+mkdir conflict-with-common-lines
+(cd conflict-with-common-lines
+  printf 'a\nb\nc\nd\ne\n' > base
+  printf 'a\nX\nsame1\nsame2\nY\ne\n' > ours
+  printf 'a\nZ\nsame1\nsame2\nW\ne\n' > theirs
+)
+baseline conflict-with-common-lines
+
+mkdir conflict-with-common-prefix-and-suffix
+(cd conflict-with-common-prefix-and-suffix
+  printf '1\n2\n3\n' > base
+  printf '1\nshared\nours\nend\n3\n' > ours
+  printf '1\nshared\ntheirs\nend\n3\n' > theirs
+)
+baseline conflict-with-common-prefix-and-suffix
+
+mkdir deletion-and-modification
+(cd deletion-and-modification
+  printf '1\n2\n3\n4\n' > base
+  printf '1\n4\n' > ours
+  printf '1\n2\nchanged\n4\n' > theirs
+)
+baseline deletion-and-modification
+
+mkdir addition-at-end
+(cd addition-at-end
+  printf '1\n2\n' > base
+  printf '1\n2\nours\n' > ours
+  printf '1\n2\ntheirs\n' > theirs
+)
+baseline addition-at-end
+
+mkdir no-newline-at-end
+(cd no-newline-at-end
+  printf '1\n2' > base
+  printf '1\nours' > ours
+  printf '1\ntheirs' > theirs
+)
+baseline no-newline-at-end
+
+mkdir empty-base
+(cd empty-base
+  touch base
+  printf 'ours\n' > ours
+  printf 'theirs\n' > theirs
+)
+baseline empty-base
+
+mkdir multiple-conflicts
+(cd multiple-conflicts
+  printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > base
+  printf '1\nours-2\n3\n4\n5\n6\n7\nours-8\n9\n' > ours
+  printf '1\ntheirs-2\n3\n4\n5\n6\n7\ntheirs-8\n9\n' > theirs
+)
+baseline multiple-conflicts
+
+mkdir crlf
+(cd crlf
+  printf '1\r\n2\r\n3\r\n' > base
+  printf '1\r\nours\r\n3\r\n' > ours
+  printf '1\r\ntheirs\r\n3\r\n' > theirs
+)
+baseline crlf
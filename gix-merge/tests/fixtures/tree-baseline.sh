@@ -0,0 +1,189 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Each directory is a repository with the `base` tag and the `ours` and `theirs` branches, along with the
+# ids of their trees and the result of `git merge-tree --write-tree` in `expected`.
+function baseline() {
+  local name=${1:?first argument is the name of the case}
+  (cd "$name"
+    for rev in base ours theirs; do
+      git rev-parse "$rev^{tree}" > "../$name.$rev-tree"
+    done
+    git merge-tree --write-tree --no-messages ours theirs > "../$name.expected" || true
+  )
+}
+
+function init() {
+  local name=${1:?first argument is the name of the case}
+  git init -q "$name"
+  (cd "$name"
+    git checkout -q -b ours
+  )
+}
+
+function commit() {
+  git add -A && git commit -q -m "$1"
+}
+
+init clean
+(cd clean
+  echo a > a
+  echo b > b
+  mkdir dir && echo d > dir/d && echo e > dir/e
+  commit base && git tag base
+
+  echo ours >> a
+  git rm -q dir/d
+  commit ours
+
+  git checkout -q -b theirs base
+  echo theirs >> b
+  echo c > dir/c
+  commit theirs
+  git checkout -q ours
+)
+baseline clean
+
+init content-merge
+(cd content-merge
+  printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > a
+  commit base && git tag base
+
+  printf '1\nours\n3\n4\n5\n6\n7\n8\n9\n' > a
+  commit ours
+
+  git checkout -q -b theirs base
+  printf '1\n2\n3\n4\n5\n6\n7\ntheirs\n9\n' > a
+  commit theirs
+  git checkout -q ours
+)
+baseline content-merge
+
+init content-conflict
+(cd content-conflict
+  printf '1\n2\n3\n' > a
+  echo b > b
+  commit base && git tag base
+
+  printf '1\nours\n3\n' > a
+  commit ours
+
+  git checkout -q -b theirs base
+  printf '1\ntheirs\n3\n' > a
+  echo theirs >> b
+  commit theirs
+  git checkout -q ours
+)
+baseline content-conflict
+
+init mode
+(cd mode
+  printf '1\n2\n3\n' > a
+  printf '1\n2\n3\n' > b
+  commit base && git tag base
+
+  chmod +x a b
+  commit ours
+
+  git checkout -q -b theirs base
+  printf '1\n2\ntheirs\n' > a
+  chmod +x b
+  commit theirs
+  git checkout -q ours
+)
+baseline mode
+
+init modify-delete
+(cd modify-delete
+  echo a > a
+  echo b > b
+  commit base && git tag base
+
+  echo ours >> a
+  git rm -q b
+  commit ours
+
+  git checkout -q -b theirs base
+  git rm -q a
+  echo theirs >> b
+  commit theirs
+  git checkout -q ours
+)
+baseline modify-delete
+
+init add-add
+(cd add-add
+  echo a > a
+  commit base && git tag base
+
+  echo ours > new
+  echo same > same
+  commit ours
+
+  git checkout -q -b theirs base
+  echo theirs > new
+  echo same > same
+  commit theirs
+  git checkout -q ours
+)
+baseline add-add
+
+init rename-with-modification
+(cd rename-with-modification
+  printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > a
+  commit base && git tag base
+
+  git mv a b
+  commit ours
+
+  git checkout -q -b theirs base
+  printf '1\n2\n3\n4\n5\n6\n7\n8\ntheirs\n' > a
+  commit theirs
+  git checkout -q ours
+)
+baseline rename-with-modification
+
+init rename-rename
+(cd rename-rename
+  printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > a
+  commit base && git tag base
+
+  git mv a b
+  commit ours
+
+  git checkout -q -b theirs base
+  git mv a c
+  commit theirs
+  git checkout -q ours
+)
+baseline rename-rename
+
+init rename-delete
+(cd rename-delete
+  printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > a
+  commit base && git tag base
+
+  git mv a b
+  commit ours
+
+  git checkout -q -b theirs base
+  git rm -q a
+  commit theirs
+  git checkout -q ours
+)
+baseline rename-delete
+
+init directory-file
+(cd directory-file
+  echo a > a
+  commit base && git tag base
+
+  echo ours > d
+  commit ours
+
+  git checkout -q -b theirs base
+  mkdir d && echo theirs > d/file
+  commit theirs
+  git checkout -q ours
+)
+baseline directory-file
@@ -0,0 +1,217 @@
+use std::{cell::RefCell, collections::HashMap, path::Path};
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_merge::{
+    blob::Labels,
+    tree::{ConflictKind, Options, Outcome},
+};
+use gix_object::WriteTo;
+
+#[test]
+fn baseline() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("tree-baseline.sh")?;
+    let mut num_cases = 0;
+    for entry in std::fs::read_dir(&root)? {
+        let path = entry?.path();
+        let Some(name) = path
+            .file_name()
+            .and_then(|name| name.to_str()?.strip_suffix(".expected"))
+        else {
+            continue;
+        };
+        let expected = std::fs::read_to_string(&path)?;
+        let (outcome, _objects) = merge(&root, name)?;
+        assert_eq!(
+            format!(
+                "{}\n{}",
+                outcome.tree,
+                unmerged_entries(&outcome.index)
+                    .into_iter()
+                    .map(|line| format!("{line}\n"))
+                    .collect::<String>()
+            ),
+            expected,
+            "{name}: tree and unmerged entries must match those of `git merge-tree --write-tree`"
+        );
+        assert_eq!(
+            outcome.has_unresolved_conflicts(),
+            expected.lines().count() > 1,
+            "{name}: conflicts are reported exactly when there are unmerged entries"
+        );
+        num_cases += 1;
+    }
+    assert_eq!(num_cases, 10, "all cases were checked");
+    Ok(())
+}
+
+#[test]
+fn conflicts_are_classified() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("tree-baseline.sh")?;
+    for (name, expected) in [
+        ("clean", &[][..]),
+        ("content-merge", &[]),
+        ("mode", &[]),
+        ("rename-with-modification", &[]),
+        ("content-conflict", &[(ConflictKind::Content, "a")]),
+        (
+            "modify-delete",
+            &[(ConflictKind::ModifyDelete, "a"), (ConflictKind::ModifyDelete, "b")],
+        ),
+        ("add-add", &[(ConflictKind::AddAdd, "new")]),
+        ("rename-rename", &[(ConflictKind::RenameRename, "a")]),
+        ("rename-delete", &[(ConflictKind::RenameDelete, "b")]),
+        ("directory-file", &[(ConflictKind::DirectoryFile, "d~ours")]),
+    ] {
+        let (outcome, _objects) = merge(&root, name)?;
+        let actual: Vec<_> = outcome
+            .conflicts
+            .iter()
+            .map(|conflict| (conflict.kind, conflict.location.to_str_lossy().into_owned()))
+            .collect();
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(kind, location)| (*kind, location.to_string()))
+            .collect();
+        assert_eq!(actual, expected, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn conflicting_content_is_written_with_markers() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("tree-baseline.sh")?;
+    let (outcome, objects) = merge(&root, "content-conflict")?;
+    let conflict = &outcome.conflicts[0];
+    assert_eq!(
+        conflict.ancestor.as_ref().map(|e| e.location.as_bstr()),
+        Some("a".into())
+    );
+    let merged = outcome
+        .index
+        .entries()
+        .iter()
+        .find(|e| e.stage() == 2)
+        .expect("our version is recorded");
+    assert_eq!(
+        Some(merged.id),
+        conflict.ours.as_ref().map(|e| e.id),
+        "stage 2 is our version"
+    );
+
+    let merged_blob = objects
+        .values()
+        .find(|data| data.contains_str("<<<<<<<"))
+        .expect("merged blob with conflict markers was written");
+    assert_eq!(
+        merged_blob.as_bstr(),
+        "1\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n3\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn without_rename_tracking_renames_are_deletions_and_additions() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("tree-baseline.sh")?;
+    let (outcome, _objects) = merge_with_options(&root, "rename-with-modification", Options::default())?;
+    let actual: Vec<_> = outcome.conflicts.iter().map(|c| (c.kind, c.location.clone())).collect();
+    assert_eq!(
+        actual,
+        vec![(ConflictKind::ModifyDelete, BString::from("a"))],
+        "we deleted `a` while they modified it, and our `b` is just an addition"
+    );
+    Ok(())
+}
+
+fn merge(root: &Path, name: &str) -> crate::Result<(Outcome, HashMap<ObjectId, Vec<u8>>)> {
+    merge_with_options(
+        root,
+        name,
+        Options {
+            rewrites: Some(Default::default()),
+        },
+    )
+}
+
+fn merge_with_options(
+    root: &Path,
+    name: &str,
+    options: Options,
+) -> crate::Result<(Outcome, HashMap<ObjectId, Vec<u8>>)> {
+    let tree = |side: &str| -> crate::Result<ObjectId> {
+        let hex = std::fs::read_to_string(root.join(format!("{name}.{side}-tree")))?;
+        Ok(ObjectId::from_hex(hex.trim().as_bytes())?)
+    };
+    let odb = gix_odb::at(root.join(name).join(".git").join("objects"))?;
+    let written = RefCell::new(HashMap::new());
+    let write = |object: &dyn WriteTo| -> Result<ObjectId, std::io::Error> {
+        let mut buf = Vec::new();
+        object.write_to(&mut buf)?;
+        let id = gix_object::compute_hash(gix_hash::Kind::Sha1, object.kind(), &buf);
+        written.borrow_mut().insert(id, buf);
+        Ok(id)
+    };
+    let mut blob_merge = gix_merge::blob::Platform::new(Vec::new(), Default::default());
+    let outcome = gix_merge::tree(
+        &tree("base")?,
+        &tree("ours")?,
+        &tree("theirs")?,
+        Labels {
+            ancestor: Some("base".into()),
+            current: Some("ours".into()),
+            other: Some("theirs".into()),
+        },
+        &odb,
+        write,
+        &mut blob_merge,
+        &mut |_path, _attrs| {},
+        &mut new_diff_platform(root),
+        options,
+    )?;
+    Ok((outcome, written.into_inner()))
+}
+
+/// Format the unmerged entries of `index` like `git merge-tree` does.
+fn unmerged_entries(index: &gix_index::State) -> Vec<String> {
+    index
+        .entries()
+        .iter()
+        .filter(|entry| entry.stage() != 0)
+        .map(|entry| {
+            format!(
+                "{:06o} {} {}\t{}",
+                entry.mode.bits(),
+                entry.id,
+                entry.stage(),
+                entry.path(index)
+            )
+        })
+        .collect()
+}
+
+fn new_diff_platform(root: &Path) -> gix_diff::blob::Platform {
+    let attributes = gix_worktree::Stack::new(
+        root,
+        gix_worktree::stack::State::AttributesStack(gix_worktree::stack::state::Attributes::new(
+            Default::default(),
+            None,
+            gix_worktree::stack::state::attributes::Source::IdMapping,
+            Default::default(),
+        )),
+        gix_worktree::glob::pattern::Case::Sensitive,
+        Vec::new(),
+        Vec::new(),
+    );
+    let filter = gix_diff::blob::Pipeline::new(
+        Default::default(),
+        gix_filter::Pipeline::default(),
+        Vec::new(),
+        Default::default(),
+    );
+    gix_diff::blob::Platform::new(
+        Default::default(),
+        filter,
+        gix_diff::blob::pipeline::Mode::ToGit,
+        attributes,
+    )
+}
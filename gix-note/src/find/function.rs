@@ -0,0 +1,36 @@
+use bstr::ByteSlice;
+use gix_hash::{oid, ObjectId};
+use gix_object::FindExt;
+
+use crate::find::Error;
+
+/// Find the note for the object with id `target` in the notes tree with `tree_id`, using `objects` to find trees,
+/// and return the id of the blob containing the note, or `None` if `target` isn't annotated.
+///
+/// Only the trees leading to `target` are read, no matter which fanout is used.
+pub fn find(tree_id: &oid, target: &oid, objects: &impl gix_object::Find) -> Result<Option<ObjectId>, Error> {
+    let hex = target.to_string();
+    let mut buf = Vec::new();
+    let mut tree_id = tree_id.to_owned();
+    let mut consumed = 0;
+    loop {
+        let tree = objects.find_tree(&tree_id, &mut buf)?;
+        let remaining = &hex.as_bytes()[consumed..];
+        let mut subtree = None;
+        for entry in &tree.entries {
+            if entry.filename == remaining && !entry.mode.is_tree() {
+                return Ok(Some(entry.oid.to_owned()));
+            }
+            if remaining.len() > 2 && entry.mode.is_tree() && entry.filename == remaining[..2].as_bstr() {
+                subtree = Some(entry.oid.to_owned());
+            }
+        }
+        match subtree {
+            Some(id) => {
+                tree_id = id;
+                consumed += 2;
+            }
+            None => return Ok(None),
+        }
+    }
+}
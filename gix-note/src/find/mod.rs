@@ -0,0 +1,9 @@
+/// The error returned by [`find()`](crate::find()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindTree(#[from] gix_object::find::existing_object::Error),
+}
+
+pub(crate) mod function;
@@ -1,2 +1,59 @@
-#![deny(rust_2018_idioms)]
+//! Read, write and merge git notes, which associate arbitrary data with objects without changing them.
+//!
+//! Notes are blobs in a tree that is committed to a notes reference like `refs/notes/commits`. Each note is named
+//! after the hexadecimal id of the object it annotates, which may be split into directories named after the first
+//! two hexadecimal characters to keep trees small, the so-called *fanout*, e.g. `ab/cdef…`.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+use std::collections::BTreeMap;
+
+use bstr::BString;
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+/// All notes of a notes tree, along with all of its entries that aren't notes, for iteration and editing.
+///
+/// Use [`find()`] to look up a single note without loading all of them.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Notes {
+    /// A mapping of each annotated object to the blob containing its note.
+    pub notes: BTreeMap<ObjectId, ObjectId>,
+    /// Entries of the notes tree which aren't notes, and which are kept as they are when writing the tree.
+    pub non_notes: Vec<NonNote>,
+}
+
+/// An entry in a notes tree that isn't a note, as its name isn't the hexadecimal id of an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonNote {
+    /// The path of the entry relative to the root of the notes tree.
+    pub path: BString,
+    /// The mode of the entry.
+    pub mode: EntryMode,
+    /// The id of the object the entry points to.
+    pub id: ObjectId,
+}
+
+mod notes;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod from_tree {
+    /// The error returned by [`Notes::from_tree()`](crate::Notes::from_tree()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindTree(#[from] gix_object::find::existing_object::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod find;
+pub use find::function::find;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod merge;
+pub use merge::function::merge;
@@ -0,0 +1,98 @@
+use std::collections::BTreeSet;
+
+use gix_hash::{oid, ObjectId};
+use gix_object::{FindExt, WriteTo};
+
+use crate::{
+    merge::{cat_sort_uniq, concatenate, Conflict, Error, Outcome, Strategy},
+    Notes,
+};
+
+/// Perform a three-way merge of `our` and `their` notes, using `base` as the notes of their merge-base, and
+/// resolve notes that were changed differently on both sides according to `strategy`, similar to `git notes merge`.
+///
+/// `objects` are used to read notes which have to be combined, and `write` is called to write each combined note
+/// as blob. The non-notes of the merged notes are always our non-notes.
+pub fn merge<E>(
+    base: &Notes,
+    ours: &Notes,
+    theirs: &Notes,
+    strategy: Strategy,
+    objects: &impl gix_object::Find,
+    mut write: impl FnMut(&dyn WriteTo) -> Result<ObjectId, E>,
+) -> Result<Outcome, Error>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut out = Outcome {
+        notes: ours.clone(),
+        conflicts: Vec::new(),
+    };
+    let targets: BTreeSet<&ObjectId> = base
+        .notes
+        .keys()
+        .chain(ours.notes.keys())
+        .chain(theirs.notes.keys())
+        .collect();
+    let mut buf = Vec::new();
+    let mut buf2 = Vec::new();
+    for target in targets {
+        let (base_note, our_note, their_note) = (
+            base.notes.get(target).copied(),
+            ours.notes.get(target).copied(),
+            theirs.notes.get(target).copied(),
+        );
+        if our_note == their_note || their_note == base_note {
+            continue;
+        }
+        let merged = if our_note == base_note {
+            their_note
+        } else {
+            match strategy {
+                Strategy::Manual => {
+                    out.conflicts.push(Conflict {
+                        target: *target,
+                        base: base_note,
+                        ours: our_note,
+                        theirs: their_note,
+                    });
+                    continue;
+                }
+                Strategy::Ours => continue,
+                Strategy::Theirs => their_note,
+                Strategy::Union | Strategy::CatSortUniq => {
+                    let ours = data(our_note.as_deref(), objects, &mut buf)?;
+                    let theirs = data(their_note.as_deref(), objects, &mut buf2)?;
+                    let combined = if strategy == Strategy::Union {
+                        concatenate(ours, theirs)
+                    } else {
+                        cat_sort_uniq(ours, theirs)
+                    };
+                    if combined.is_empty() {
+                        continue;
+                    }
+                    Some(
+                        write(&gix_object::BlobRef { data: &combined })
+                            .map_err(|err| Error::WriteBlob(Box::new(err)))?,
+                    )
+                }
+            }
+        };
+        match merged {
+            Some(note) => out.notes.notes.insert(*target, note),
+            None => out.notes.notes.remove(target),
+        };
+    }
+    Ok(out)
+}
+
+fn data<'a>(
+    note: Option<&oid>,
+    objects: &impl gix_object::Find,
+    buf: &'a mut Vec<u8>,
+) -> Result<&'a [u8], gix_object::find::existing_object::Error> {
+    Ok(match note {
+        Some(id) => objects.find_blob(id, buf)?.data,
+        None => &[],
+    })
+}
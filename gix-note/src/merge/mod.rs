@@ -0,0 +1,112 @@
+use bstr::{BStr, ByteSlice};
+use gix_hash::ObjectId;
+
+/// Determines how notes that were changed differently on both sides are merged, similar to `git notes merge --strategy`.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// Record a [conflict](Conflict) for the user to resolve, and keep our note in the merged notes.
+    #[default]
+    Manual,
+    /// Use our note.
+    Ours,
+    /// Use their note.
+    Theirs,
+    /// Use the [concatenation](concatenate()) of our and their note.
+    Union,
+    /// Use the sorted and [de-duplicated lines](cat_sort_uniq()) of our and their note.
+    CatSortUniq,
+}
+
+impl Strategy {
+    /// Parse a strategy from its `name` as used in `git` configuration, or return `None` if it isn't known.
+    pub fn from_name(name: &BStr) -> Option<Self> {
+        Some(match name.as_bytes() {
+            b"manual" => Strategy::Manual,
+            b"ours" => Strategy::Ours,
+            b"theirs" => Strategy::Theirs,
+            b"union" => Strategy::Union,
+            b"cat_sort_uniq" => Strategy::CatSortUniq,
+            _ => return None,
+        })
+    }
+
+    /// Return the name of this strategy as used in `git` configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Strategy::Manual => "manual",
+            Strategy::Ours => "ours",
+            Strategy::Theirs => "theirs",
+            Strategy::Union => "union",
+            Strategy::CatSortUniq => "cat_sort_uniq",
+        }
+    }
+}
+
+/// A note that was changed differently on both sides, recorded when merging with [`Strategy::Manual`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The annotated object.
+    pub target: ObjectId,
+    /// The id of the blob with the note in the merge-base, if there was one.
+    pub base: Option<ObjectId>,
+    /// The id of the blob with our note, or `None` if we removed it.
+    pub ours: Option<ObjectId>,
+    /// The id of the blob with their note, or `None` if they removed it.
+    pub theirs: Option<ObjectId>,
+}
+
+/// The outcome of [`merge()`](crate::merge()).
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The merged notes, which contain our version of each conflicting note, along with our non-notes.
+    pub notes: crate::Notes,
+    /// All notes that couldn't be merged, which is only possible with [`Strategy::Manual`].
+    pub conflicts: Vec<Conflict>,
+}
+
+/// The error returned by [`merge()`](crate::merge()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindBlob(#[from] gix_object::find::existing_object::Error),
+    #[error("Could not write a merged note")]
+    WriteBlob(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+/// Concatenate `ours` and `theirs` with an empty line in between, like the `union` strategy of `git` does, or
+/// return the non-empty one if the other is empty.
+pub fn concatenate(ours: &[u8], theirs: &[u8]) -> Vec<u8> {
+    if ours.is_empty() {
+        return theirs.into();
+    }
+    if theirs.is_empty() {
+        return ours.into();
+    }
+    let ours = ours.strip_suffix(b"\n").unwrap_or(ours);
+    let mut out = Vec::with_capacity(ours.len() + 2 + theirs.len());
+    out.extend_from_slice(ours);
+    out.extend_from_slice(b"\n\n");
+    out.extend_from_slice(theirs);
+    out
+}
+
+/// Return all non-empty lines of `ours` and `theirs`, sorted and without duplicates, each with a trailing newline,
+/// like the `cat_sort_uniq` strategy of `git` does.
+pub fn cat_sort_uniq(ours: &[u8], theirs: &[u8]) -> Vec<u8> {
+    let mut lines: Vec<_> = ours
+        .split_str("\n")
+        .chain(theirs.split_str("\n"))
+        .filter(|line| !line.is_empty())
+        .collect();
+    lines.sort();
+    lines.dedup();
+    let mut out = Vec::new();
+    for line in lines {
+        out.extend_from_slice(line);
+        out.push(b'\n');
+    }
+    out
+}
+
+pub(crate) mod function;
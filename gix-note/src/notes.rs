@@ -0,0 +1,163 @@
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_hash::{oid, ObjectId};
+use gix_object::{
+    tree::{EntryKind, EntryMode},
+    FindExt, WriteTo,
+};
+
+use crate::{from_tree, NonNote, Notes};
+
+impl Notes {
+    /// Read all notes from the notes tree with `tree_id`, using `objects` to find all trees.
+    ///
+    /// Notes may be stored with any fanout, and entries that aren't notes are kept in [`non_notes`](Self::non_notes).
+    /// If an object has more than one note, which can happen with inconsistent fanouts, the one sorting first is used.
+    pub fn from_tree(tree_id: &oid, objects: &impl gix_object::Find) -> Result<Self, from_tree::Error> {
+        let mut out = Notes::default();
+        let mut buf = Vec::new();
+        let mut queue = vec![(tree_id.to_owned(), BString::default(), BString::default())];
+        let hex_len = tree_id.kind().len_in_hex();
+        while let Some((tree_id, hex_prefix, dir)) = queue.pop() {
+            let tree = objects.find_tree(&tree_id, &mut buf)?;
+            for entry in tree.entries {
+                let name = entry.filename;
+                let remaining = hex_len - hex_prefix.len();
+                if !entry.mode.is_tree() && name.len() == remaining && is_hex(name) {
+                    let mut hex = hex_prefix.clone();
+                    hex.push_str(name);
+                    let target = ObjectId::from_hex(&hex).expect("valid hex of correct length");
+                    out.notes.entry(target).or_insert_with(|| entry.oid.to_owned());
+                    continue;
+                }
+                let mut path = dir.clone();
+                path.push_str(name);
+                if entry.mode.is_tree() && name.len() == 2 && remaining > 2 && is_hex(name) {
+                    let mut hex = hex_prefix.clone();
+                    hex.push_str(name);
+                    path.push_byte(b'/');
+                    queue.push((entry.oid.to_owned(), hex, path));
+                } else {
+                    out.non_notes.push(NonNote {
+                        path,
+                        mode: entry.mode,
+                        id: entry.oid.to_owned(),
+                    });
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Write all notes and non-notes as tree, calling `write` for each tree to write, and return the id of the root tree.
+    ///
+    /// The fanout is chosen like `git` does, which adds another level of directories whenever each of the 16 possible
+    /// hexadecimal characters following the current prefix is shared by at least two notes.
+    pub fn write_tree<E>(&self, mut write: impl FnMut(&dyn WriteTo) -> Result<ObjectId, E>) -> Result<ObjectId, E> {
+        let notes: Vec<_> = self
+            .notes
+            .iter()
+            .map(|(target, note)| (target.to_string(), *note))
+            .collect();
+        let mut entries = Vec::with_capacity(notes.len() + self.non_notes.len());
+        assign_paths(&notes, 0, &BString::default(), &mut entries);
+        entries.extend(
+            self.non_notes
+                .iter()
+                .map(|non_note| (non_note.path.clone(), non_note.mode, non_note.id)),
+        );
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|(path, mode, id)| (path.as_bstr(), *mode, *id))
+            .collect();
+        write_tree(&entries, &mut write)
+    }
+}
+
+fn is_hex(name: &BStr) -> bool {
+    name.iter().all(u8::is_ascii_hexdigit)
+}
+
+/// Assign a path to each of the sorted `notes`, whose first `consumed` hexadecimal characters are the same and
+/// already represented by directories in `dir`.
+fn assign_paths(
+    notes: &[(String, ObjectId)],
+    consumed: usize,
+    dir: &BString,
+    out: &mut Vec<(BString, EntryMode, ObjectId)>,
+) {
+    let Some(hex_len) = notes.first().map(|(hex, _)| hex.len()) else {
+        return;
+    };
+    let fan_out = consumed + 2 < hex_len && {
+        let mut count_by_nibble = [0_usize; 16];
+        for (hex, _) in notes {
+            let nibble = (hex.as_bytes()[consumed] as char).to_digit(16).expect("hex") as usize;
+            count_by_nibble[nibble] += 1;
+        }
+        count_by_nibble.iter().all(|count| *count > 1)
+    };
+    if !fan_out {
+        out.extend(notes.iter().map(|(hex, note)| {
+            let mut path = dir.clone();
+            path.push_str(&hex[consumed..]);
+            (path, EntryKind::Blob.into(), *note)
+        }));
+        return;
+    }
+
+    let mut start = 0;
+    while start < notes.len() {
+        let prefix = &notes[start].0[consumed..consumed + 2];
+        let end = notes[start..]
+            .iter()
+            .position(|(hex, _)| &hex[consumed..consumed + 2] != prefix)
+            .map_or(notes.len(), |len| start + len);
+        let mut subdir = dir.clone();
+        subdir.push_str(prefix);
+        subdir.push_byte(b'/');
+        assign_paths(&notes[start..end], consumed + 2, &subdir, out);
+        start = end;
+    }
+}
+
+/// Write `entries`, sorted by path, as tree and return its id, writing sub-trees as needed.
+fn write_tree<E>(
+    entries: &[(&BStr, EntryMode, ObjectId)],
+    write: &mut dyn FnMut(&dyn WriteTo) -> Result<ObjectId, E>,
+) -> Result<ObjectId, E> {
+    let mut tree = gix_object::Tree::empty();
+    let mut idx = 0;
+    while idx < entries.len() {
+        let (path, mode, id) = entries[idx];
+        match path.find_byte(b'/') {
+            None => {
+                tree.entries.push(gix_object::tree::Entry {
+                    mode,
+                    filename: path.to_owned(),
+                    oid: id,
+                });
+                idx += 1;
+            }
+            Some(pos) => {
+                let directory = &path[..=pos];
+                let end = entries[idx..]
+                    .iter()
+                    .position(|(path, _, _)| !path.starts_with(directory))
+                    .map_or(entries.len(), |len| idx + len);
+                let children: Vec<_> = entries[idx..end]
+                    .iter()
+                    .map(|(path, mode, id)| (path[pos + 1..].as_bstr(), *mode, *id))
+                    .collect();
+                tree.entries.push(gix_object::tree::Entry {
+                    mode: EntryKind::Tree.into(),
+                    filename: path[..pos].into(),
+                    oid: write_tree(&children, write)?,
+                });
+                idx = end;
+            }
+        }
+    }
+    tree.entries.sort();
+    write(&tree)
+}
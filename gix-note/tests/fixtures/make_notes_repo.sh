@@ -0,0 +1,46 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+git commit -q --allow-empty -m first
+git tag first
+git commit -q --allow-empty -m second
+git tag second
+git commit -q --allow-empty -m third
+git tag third
+
+# A notes tree without fanout, the default notes ref.
+git notes add -m "a note on first" first
+git notes add -m "a note on second" -m "with a second paragraph" second
+
+# The same notes with an entry that isn't a note.
+readme=$(echo "not a note" | git hash-object -w --stdin)
+tree=$({ git ls-tree refs/notes/commits; printf '100644 blob %s\tREADME\n' "$readme"; } | git mktree)
+git update-ref refs/notes/with-non-note "$(git commit-tree -p refs/notes/commits -m "add non-note" "$tree")"
+
+# Enough notes for git to use a fanout.
+for i in $(seq 300); do
+  blob=$(echo "blob $i" | git hash-object -w --stdin)
+  git notes --ref many add -m "note $i" "$blob"
+done
+
+# Notes to merge with all strategies.
+git notes --ref base add -m "base" first
+git notes --ref base add -m "base" second
+git notes --ref base add -m "base" third
+git update-ref refs/notes/ours refs/notes/base
+git update-ref refs/notes/theirs refs/notes/base
+
+git notes --ref ours add -f -m "b" -m "shared" first
+git notes --ref ours remove third
+
+git notes --ref theirs add -f -m "a" -m "shared" first
+git notes --ref theirs add -f -m "theirs" second
+git notes --ref theirs add -m "only theirs" "$readme"
+
+for strategy in ours theirs union cat_sort_uniq; do
+  git update-ref refs/notes/result-$strategy refs/notes/ours
+  git notes --ref result-$strategy merge -q -s $strategy refs/notes/theirs
+  git notes --ref result-$strategy list > .git/result-$strategy.baseline
+done
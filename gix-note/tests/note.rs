@@ -0,0 +1,190 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use gix_hash::ObjectId;
+use gix_note::{merge::Strategy, Notes};
+use gix_object::{bstr::ByteSlice, FindExt, WriteTo};
+
+pub use gix_testtools::Result;
+
+fn fixture_path() -> PathBuf {
+    gix_testtools::scripted_fixture_read_only("make_notes_repo.sh").expect("script succeeds")
+}
+
+fn rev(root: &Path, name: &str) -> ObjectId {
+    ObjectId::from_hex(
+        std::fs::read_to_string(root.join(".git").join(name))
+            .expect("loose ref")
+            .trim()
+            .as_bytes(),
+    )
+    .expect("valid hex")
+}
+
+fn notes_tree(root: &Path, odb: &gix_odb::Handle, name: &str) -> ObjectId {
+    let commit = rev(root, &format!("refs/notes/{name}"));
+    odb.find_commit(&commit, &mut Vec::new()).expect("notes commit").tree()
+}
+
+/// Compute the id of `object` without writing it.
+fn hash(object: &dyn WriteTo) -> std::io::Result<ObjectId> {
+    let mut buf = Vec::new();
+    object.write_to(&mut buf)?;
+    Ok(gix_object::compute_hash(gix_hash::Kind::Sha1, object.kind(), &buf))
+}
+
+#[test]
+fn find_with_and_without_fanout() -> crate::Result {
+    let root = fixture_path();
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let tree = notes_tree(&root, &odb, "commits");
+    let mut buf = Vec::new();
+    for (name, expected) in [
+        ("first", "a note on first\n"),
+        ("second", "a note on second\n\nwith a second paragraph\n"),
+    ] {
+        let note = gix_note::find(&tree, &rev(&root, &format!("refs/tags/{name}")), &odb)?.expect("note exists");
+        assert_eq!(odb.find_blob(&note, &mut buf)?.data.as_bstr(), expected);
+    }
+    assert_eq!(
+        gix_note::find(&tree, &rev(&root, "refs/tags/third"), &odb)?,
+        None,
+        "unannotated objects have no note"
+    );
+
+    let tree = notes_tree(&root, &odb, "many");
+    assert!(
+        odb.find_tree(&tree, &mut buf)?
+            .entries
+            .iter()
+            .all(|e| e.filename.len() == 2),
+        "git uses a fanout for this many notes"
+    );
+    let notes = Notes::from_tree(&tree, &odb)?;
+    assert_eq!(notes.notes.len(), 300);
+    assert!(notes.non_notes.is_empty());
+    for (target, note) in &notes.notes {
+        assert_eq!(gix_note::find(&tree, target, &odb)?, Some(*note));
+    }
+    assert_eq!(gix_note::find(&tree, &rev(&root, "refs/tags/first"), &odb)?, None);
+    Ok(())
+}
+
+#[test]
+fn non_notes_are_retained() -> crate::Result {
+    let root = fixture_path();
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let notes = Notes::from_tree(&notes_tree(&root, &odb, "with-non-note"), &odb)?;
+    assert_eq!(notes.notes.len(), 2);
+    assert_eq!(notes.non_notes.len(), 1);
+    assert_eq!(notes.non_notes[0].path, "README");
+    Ok(())
+}
+
+#[test]
+fn write_tree_uses_the_same_fanout_as_git() -> crate::Result {
+    let root = fixture_path();
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    for name in ["commits", "with-non-note", "many", "result-ours", "result-union"] {
+        let tree = notes_tree(&root, &odb, name);
+        let notes = Notes::from_tree(&tree, &odb)?;
+        assert_eq!(
+            notes.write_tree(hash)?,
+            tree,
+            "{name}: unchanged notes yield the same tree"
+        );
+    }
+
+    let mut notes = Notes::from_tree(&notes_tree(&root, &odb, "many"), &odb)?;
+    let first = *notes.notes.keys().next().expect("not empty");
+    notes.notes.retain(|target, _| *target == first);
+    let mut entries = Vec::new();
+    let tree = gix_object::Tree {
+        entries: vec![gix_object::tree::Entry {
+            mode: gix_object::tree::EntryKind::Blob.into(),
+            filename: first.to_string().into(),
+            oid: notes.notes[&first],
+        }],
+    };
+    tree.write_to(&mut entries)?;
+    assert_eq!(
+        notes.write_tree(hash)?,
+        gix_object::compute_hash(gix_hash::Kind::Sha1, gix_object::Kind::Tree, &entries),
+        "a single note doesn't need a fanout"
+    );
+    Ok(())
+}
+
+#[test]
+fn merge_matches_git() -> crate::Result {
+    let root = fixture_path();
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let notes = |name: &str| Notes::from_tree(&notes_tree(&root, &odb, name), &odb);
+    let (base, ours, theirs) = (notes("base")?, notes("ours")?, notes("theirs")?);
+    for strategy in [Strategy::Ours, Strategy::Theirs, Strategy::Union, Strategy::CatSortUniq] {
+        let mut written = Vec::new();
+        let outcome = gix_note::merge(&base, &ours, &theirs, strategy, &odb, |object| {
+            written.push(object.size());
+            hash(object)
+        })?;
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(
+            outcome.notes.notes,
+            baseline(&root, strategy),
+            "{strategy:?}: the same notes are merged as by git"
+        );
+        assert_eq!(
+            written.len(),
+            usize::from(matches!(strategy, Strategy::Union | Strategy::CatSortUniq)),
+            "only combined notes are written"
+        );
+    }
+
+    let outcome = gix_note::merge(&base, &ours, &theirs, Strategy::Manual, &odb, hash)?;
+    let first = rev(&root, "refs/tags/first");
+    assert_eq!(
+        outcome.conflicts,
+        [gix_note::merge::Conflict {
+            target: first,
+            base: base.notes.get(&first).copied(),
+            ours: ours.notes.get(&first).copied(),
+            theirs: theirs.notes.get(&first).copied(),
+        }]
+    );
+    assert_eq!(
+        outcome.notes.notes,
+        baseline(&root, Strategy::Ours),
+        "conflicts keep our note, and all other changes are merged"
+    );
+    Ok(())
+}
+
+#[test]
+fn strategy_names() {
+    for strategy in [
+        Strategy::Manual,
+        Strategy::Ours,
+        Strategy::Theirs,
+        Strategy::Union,
+        Strategy::CatSortUniq,
+    ] {
+        assert_eq!(Strategy::from_name(strategy.as_str().into()), Some(strategy));
+    }
+    assert_eq!(Strategy::from_name("resolve".into()), None);
+}
+
+fn baseline(root: &Path, strategy: Strategy) -> BTreeMap<ObjectId, ObjectId> {
+    let data = std::fs::read(root.join(".git").join(format!("result-{}.baseline", strategy.as_str())))
+        .expect("baseline exists");
+    data.lines()
+        .map(|line| {
+            let (note, target) = line.split_once_str(" ").expect("two ids");
+            (
+                ObjectId::from_hex(target).expect("valid"),
+                ObjectId::from_hex(note).expect("valid"),
+            )
+        })
+        .collect()
+}
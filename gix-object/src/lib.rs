@@ -235,6 +235,8 @@ pub struct TreeRef<'a> {
 pub struct TreeRefIter<'a> {
     /// The directories and files contained in this tree.
     data: &'a [u8],
+    /// The kind of hash used for the ids of all entries, which determines their length.
+    object_hash: gix_hash::Kind,
 }
 
 /// A mutable Tree, containing other trees, blobs or commits.
@@ -261,6 +263,8 @@ pub struct Data<'a> {
     pub kind: Kind,
     /// decoded, decompressed data, owned by a backing store.
     pub data: &'a [u8],
+    /// The kind of hash used by the object database the data was obtained from, needed to decode trees.
+    pub object_hash: gix_hash::Kind,
 }
 
 /// Information about an object, which includes its kind and the amount of bytes it would have when obtained.
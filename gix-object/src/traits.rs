@@ -27,7 +27,7 @@ pub trait WriteTo {
 
 impl<T> WriteTo for &T
 where
-    T: WriteTo,
+    T: WriteTo + ?Sized,
 {
     fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
         <T as WriteTo>::write_to(self, out)
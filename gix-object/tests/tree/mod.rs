@@ -5,13 +5,17 @@ mod iter {
 
     #[test]
     fn empty() {
-        assert_eq!(TreeRefIter::from_bytes(&[]).count(), 0, "empty trees are definitely ok");
+        assert_eq!(
+            TreeRefIter::from_bytes(&[], gix_hash::Kind::Sha1).count(),
+            0,
+            "empty trees are definitely ok"
+        );
     }
 
     #[test]
     fn error_handling() {
         let data = fixture_name("tree", "everything.tree");
-        let iter = TreeRefIter::from_bytes(&data[..data.len() / 2]);
+        let iter = TreeRefIter::from_bytes(&data[..data.len() / 2], gix_hash::Kind::Sha1);
         let entries = iter.collect::<Vec<_>>();
         assert!(
             entries.last().expect("at least one token").is_err(),
@@ -22,7 +26,8 @@ mod iter {
     #[test]
     fn everything() -> crate::Result {
         assert_eq!(
-            TreeRefIter::from_bytes(&fixture_name("tree", "everything.tree")).collect::<Result<Vec<_>, _>>()?,
+            TreeRefIter::from_bytes(&fixture_name("tree", "everything.tree"), gix_hash::Kind::Sha1)
+                .collect::<Result<Vec<_>, _>>()?,
             vec![
                 EntryRef {
                     mode: tree::EntryKind::BlobExecutable.into(),
@@ -63,7 +68,7 @@ mod from_bytes {
     #[test]
     fn empty() -> crate::Result {
         assert_eq!(
-            TreeRef::from_bytes(&[])?,
+            TreeRef::from_bytes(&[], gix_hash::Kind::Sha1)?,
             TreeRef { entries: vec![] },
             "empty trees are valid despite usually rare in the wild"
         );
@@ -73,7 +78,7 @@ mod from_bytes {
     #[test]
     fn everything() -> crate::Result {
         assert_eq!(
-            TreeRef::from_bytes(&fixture_name("tree", "everything.tree"))?,
+            TreeRef::from_bytes(&fixture_name("tree", "everything.tree"), gix_hash::Kind::Sha1)?,
             TreeRef {
                 entries: vec![
                     EntryRef {
@@ -111,14 +116,18 @@ mod from_bytes {
     fn invalid() {
         let fixture = fixture_name("tree", "definitely-special.tree");
         let partial_tree = &fixture[..fixture.len() / 2];
-        let err = TreeRef::from_bytes(partial_tree).unwrap_err().to_string();
+        let err = TreeRef::from_bytes(partial_tree, gix_hash::Kind::Sha1)
+            .unwrap_err()
+            .to_string();
         if cfg!(feature = "verbose-object-parsing-errors") {
             assert!(err.starts_with("object parsing failed at `100644"), "{err}");
         } else {
             assert_eq!(err, "object parsing failed");
         }
         assert_eq!(
-            TreeRefIter::from_bytes(partial_tree).take_while(Result::is_ok).count(),
+            TreeRefIter::from_bytes(partial_tree, gix_hash::Kind::Sha1)
+                .take_while(Result::is_ok)
+                .count(),
             9,
             "we can decode about half of it before failing"
         );
@@ -126,7 +135,46 @@ mod from_bytes {
 
     #[test]
     fn fuzzed() {
-        assert!(gix_object::TreeRef::from_bytes(b"2").is_err(), "fail, but don't crash");
+        assert!(
+            gix_object::TreeRef::from_bytes(b"2", gix_hash::Kind::Sha1).is_err(),
+            "fail, but don't crash"
+        );
+    }
+
+    #[test]
+    fn sha256() -> crate::Result {
+        use gix_object::WriteTo;
+        let blob = gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha256);
+        let tree = gix_object::Tree {
+            entries: vec![
+                tree::Entry {
+                    mode: tree::EntryKind::Blob.into(),
+                    filename: "a".into(),
+                    oid: blob,
+                },
+                tree::Entry {
+                    mode: tree::EntryKind::Tree.into(),
+                    filename: "b".into(),
+                    oid: gix_hash::ObjectId::empty_tree(gix_hash::Kind::Sha256),
+                },
+            ],
+        };
+        let mut data = Vec::new();
+        tree.write_to(&mut data)?;
+
+        let decoded = TreeRef::from_bytes(&data, gix_hash::Kind::Sha256)?;
+        assert_eq!(gix_object::Tree::from(decoded), tree);
+        assert_eq!(
+            TreeRefIter::from_bytes(&data, gix_hash::Kind::Sha256)
+                .map(|e| e.map(|e| e.oid.to_owned()))
+                .collect::<Result<Vec<_>, _>>()?,
+            [blob, gix_hash::ObjectId::empty_tree(gix_hash::Kind::Sha256)]
+        );
+        assert!(
+            TreeRef::from_bytes(&data, gix_hash::Kind::Sha1).is_err(),
+            "SHA1 ids are too short to make sense of the data"
+        );
+        Ok(())
     }
 
     #[test]
@@ -142,12 +190,14 @@ mod from_bytes {
         ] {
             let fixture = fixture_name("tree", &format!("{name}.tree"));
             assert_eq!(
-                TreeRef::from_bytes(&fixture)?.entries.len(),
+                TreeRef::from_bytes(&fixture, gix_hash::Kind::Sha1)?.entries.len(),
                 expected_entry_count,
                 "{name}"
             );
             assert_eq!(
-                TreeRefIter::from_bytes(&fixture).map(Result::unwrap).count(),
+                TreeRefIter::from_bytes(&fixture, gix_hash::Kind::Sha1)
+                    .map(Result::unwrap)
+                    .count(),
                 expected_entry_count,
                 "{name}"
             );
@@ -164,7 +214,7 @@ mod entries {
         let root = gix_testtools::scripted_fixture_read_only("make_trees.sh")?;
         let input = std::fs::read(root.join("tree.baseline"))?;
 
-        let mut tree = TreeRef::from_bytes(&input)?;
+        let mut tree = TreeRef::from_bytes(&input, gix_hash::Kind::Sha1)?;
         let expected = tree.entries.clone();
 
         tree.entries.sort();
@@ -161,6 +161,7 @@ where
                                 gix_object::Data {
                                     kind: r.kind,
                                     data: buffer.as_slice(),
+                                    object_hash: self.store.object_hash,
                                 },
                                 Some(gix_pack::data::entry::Location {
                                     pack_id: pack.id,
@@ -262,6 +263,7 @@ where
                                         gix_object::Data {
                                             kind: r.kind,
                                             data: buffer.as_slice(),
+                                            object_hash: self.store.object_hash,
                                         },
                                         Some(gix_pack::data::entry::Location {
                                             pack_id: pack.id,
@@ -261,6 +261,6 @@ impl Store {
                 .expect("BUG: here the size is already confirmed to fit into memory"),
             0,
         );
-        Ok(gix_object::Data { kind, data: buf })
+        Ok(gix_object::Data::new(kind, buf, self.object_hash))
     }
 }
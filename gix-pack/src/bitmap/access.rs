@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use gix_bitmap::ewah;
+
+use crate::bitmap::{File, Set, FLAG_HASH_CACHE, FLAG_LOOKUP_TABLE};
+
+/// Access
+impl File {
+    pub(crate) const SIGNATURE: &'static [u8] = b"BITM";
+
+    /// The path from which the bitmap file was loaded.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The kind of hash used for the checksum of the pack or multi-pack index we belong to.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// The flags stored in the header, see [`FLAG_FULL_DAG`][crate::bitmap::FLAG_FULL_DAG] and others.
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    /// The checksum of the pack or multi-pack index this file belongs to.
+    pub fn checksum(&self) -> gix_hash::ObjectId {
+        gix_hash::ObjectId::from_bytes_or_panic(&self.data[12..][..self.object_hash.len_in_bytes()])
+    }
+
+    /// The amount of commits that have a bitmap.
+    pub fn num_bitmaps(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return `true` if the file contains a name-hash for each object.
+    pub fn has_hash_cache(&self) -> bool {
+        self.flags & FLAG_HASH_CACHE != 0
+    }
+
+    /// Return `true` if the file contains a lookup table, which allows to use it without reading all bitmaps first.
+    pub fn has_lookup_table(&self) -> bool {
+        self.flags & FLAG_LOOKUP_TABLE != 0
+    }
+
+    /// The amount of name-hashes in the hash cache, which should be the amount of objects in the pack.
+    pub fn num_name_hashes(&self) -> usize {
+        self.num_hashes
+    }
+
+    /// Return the positions of all commits with a bitmap in their pack index or multi-pack index, in ascending order.
+    ///
+    /// Note that these aren't bit positions.
+    pub fn commit_indices(&self) -> impl ExactSizeIterator<Item = u32> + '_ {
+        self.entries.iter().map(|entry| entry.commit_index)
+    }
+
+    /// Return the objects reachable from the commit at `commit_index` in the pack index or multi-pack index,
+    /// or `None` if it doesn't have a bitmap.
+    pub fn bitmap_by_commit_index(&self, commit_index: u32) -> Result<Option<Set>, ewah::decode::Error> {
+        let Ok(mut entry_index) = self
+            .entries
+            .binary_search_by_key(&commit_index, |entry| entry.commit_index)
+        else {
+            return Ok(None);
+        };
+        let mut set = Set::default();
+        for _ in 0..=self.entries.len() {
+            let entry = &self.entries[entry_index];
+            set.xor_with(&self.ewah_at(entry.bitmap_ofs)?);
+            match entry.xor_base {
+                Some(base) => entry_index = base,
+                None => return Ok(Some(set)),
+            }
+        }
+        Err(ewah::decode::Error::Corrupt {
+            message: "bitmaps to be XORed with form a cycle",
+        })
+    }
+
+    /// Return all objects of the given `kind`.
+    pub fn objects_of_kind(&self, kind: gix_object::Kind) -> Result<Set, ewah::decode::Error> {
+        use gix_object::Kind::*;
+        let ofs = self.type_bitmaps[match kind {
+            Commit => 0,
+            Tree => 1,
+            Blob => 2,
+            Tag => 3,
+        }];
+        self.ewah_at(ofs)
+    }
+
+    /// Return the name-hash of the object at `index` in the pack index or multi-pack index, or `None` if there is no
+    /// hash cache or no such object.
+    ///
+    /// Note that these aren't bit positions.
+    /// The name-hash is computed from the path at which the object was seen, and can be used to find good delta bases.
+    pub fn name_hash(&self, index: u32) -> Option<u32> {
+        let ofs = self.hash_cache_ofs?;
+        ((index as usize) < self.num_hashes).then(|| crate::read_u32(&self.data[ofs + index as usize * 4..][..4]))
+    }
+
+    fn ewah_at(&self, ofs: usize) -> Result<Set, ewah::decode::Error> {
+        let (bitmap, _rest) = ewah::decode(&self.data[ofs..])?;
+        Ok(Set::from_ewah(&bitmap))
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{
+    bitmap::{ObjectIndex, Order, Position},
+    index, multi_index,
+};
+
+impl ObjectIndex for index::File {
+    fn num_objects(&self) -> u32 {
+        index::File::num_objects(self)
+    }
+
+    fn oid_at_index(&self, index: u32) -> &gix_hash::oid {
+        index::File::oid_at_index(self, index)
+    }
+
+    fn lookup_index(&self, id: &gix_hash::oid) -> Option<u32> {
+        self.lookup(id)
+    }
+
+    fn bitmap_checksum(&self) -> gix_hash::ObjectId {
+        self.pack_checksum()
+    }
+}
+
+impl ObjectIndex for multi_index::File {
+    fn num_objects(&self) -> u32 {
+        multi_index::File::num_objects(self)
+    }
+
+    fn oid_at_index(&self, index: u32) -> &gix_hash::oid {
+        multi_index::File::oid_at_index(self, index)
+    }
+
+    fn lookup_index(&self, id: &gix_hash::oid) -> Option<u32> {
+        self.lookup(id)
+    }
+
+    fn bitmap_checksum(&self) -> gix_hash::ObjectId {
+        self.checksum()
+    }
+}
+
+/// Initialization
+impl Order {
+    /// Create an instance from the index of each object, ordered by bit position, or return `None` if the indices
+    /// aren't a permutation of all indices up to their amount.
+    pub fn from_indices(index_at_position: Vec<u32>) -> Option<Self> {
+        let mut position_at_index = vec![Position::MAX; index_at_position.len()];
+        for (position, index) in index_at_position.iter().enumerate() {
+            let slot = position_at_index.get_mut(*index as usize)?;
+            if *slot != Position::MAX {
+                return None;
+            }
+            *slot = position as Position;
+        }
+        Some(Order {
+            index_at_position,
+            position_at_index,
+        })
+    }
+
+    /// Create the order of objects in the pack of `index`, i.e. sorted by their offset in the pack.
+    ///
+    /// The [reverse index](index::File::reverse_index()) is used if present.
+    pub fn from_index(index: &index::File) -> Self {
+        if let Some(order) = index
+            .reverse_index()
+            .and_then(|reverse_index| Self::from_indices(reverse_index.iter().collect()))
+        {
+            return order;
+        }
+        let mut offsets: Vec<_> = index
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (entry.pack_offset, index as u32))
+            .collect();
+        offsets.sort_unstable();
+        Self::from_indices(offsets.into_iter().map(|(_offset, index)| index).collect())
+            .expect("all indices are present exactly once")
+    }
+
+    /// Create the pseudo-pack order of `index`, or `None` if the multi-pack index has no valid reverse index.
+    pub fn from_multi_index(index: &multi_index::File) -> Option<Self> {
+        let index_at_position = (0..index.num_objects())
+            .map(|position| index.index_at_pseudo_pack_position(position))
+            .collect::<Option<Vec<_>>>()?;
+        Self::from_indices(index_at_position)
+    }
+}
+
+/// Access
+impl Order {
+    /// The amount of objects.
+    pub fn len(&self) -> usize {
+        self.index_at_position.len()
+    }
+
+    /// Return `true` if there are no objects.
+    pub fn is_empty(&self) -> bool {
+        self.index_at_position.is_empty()
+    }
+
+    /// Return the index of the object at bit `position`.
+    pub fn index_at(&self, position: Position) -> u32 {
+        self.index_at_position[position as usize]
+    }
+
+    /// Return the bit position of the object at `index`.
+    pub fn position_of(&self, index: u32) -> Position {
+        self.position_at_index[index as usize]
+    }
+}
@@ -0,0 +1,215 @@
+use std::path::Path;
+
+use crate::bitmap::{Entry, File, FLAG_FULL_DAG, FLAG_HASH_CACHE, FLAG_LOOKUP_TABLE};
+
+mod error {
+    /// The error returned by [File::at()][super::File::at()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not open bitmap file at '{path}'")]
+        Io {
+            source: std::io::Error,
+            path: std::path::PathBuf,
+        },
+        #[error("{message}")]
+        Corrupt { message: &'static str },
+        #[error("Unsupported bitmap version: {version}")]
+        UnsupportedVersion { version: u16 },
+        #[error("Only bitmaps that contain all reachable objects are supported, got flags {flags:#x}")]
+        UnsupportedFlags { flags: u16 },
+    }
+}
+pub use error::Error;
+
+const HEADER_LEN: usize = 4 /* signature */ + 2 /* version */ + 2 /* flags */ + 4 /* number of entries */;
+const ENTRY_HEADER_LEN: usize = 4 /* commit position */ + 1 /* xor offset */ + 1 /* flags */;
+const LOOKUP_ROW_LEN: usize = 4 /* commit position */ + 8 /* offset */ + 4 /* xor row */;
+
+/// Initialization
+impl File {
+    /// Open the bitmap file at `path`, which belongs to a pack or multi-pack index with objects of `object_hash`.
+    pub fn at(path: impl AsRef<Path>, object_hash: gix_hash::Kind) -> Result<Self, Error> {
+        Self::at_inner(path.as_ref(), object_hash)
+    }
+
+    fn at_inner(path: &Path, object_hash: gix_hash::Kind) -> Result<Self, Error> {
+        let data = crate::mmap::read_only(path).map_err(|source| Error::Io {
+            source,
+            path: path.to_owned(),
+        })?;
+        let hash_len = object_hash.len_in_bytes();
+        if data.len() < HEADER_LEN + hash_len * 2 {
+            return Err(Error::Corrupt {
+                message: "bitmap file is truncated and too short",
+            });
+        }
+        if &data[..4] != File::SIGNATURE {
+            return Err(Error::Corrupt {
+                message: "Invalid signature",
+            });
+        }
+        let version = u16::from_be_bytes([data[4], data[5]]);
+        if version != 1 {
+            return Err(Error::UnsupportedVersion { version });
+        }
+        let flags = u16::from_be_bytes([data[6], data[7]]);
+        if flags & FLAG_FULL_DAG == 0 {
+            return Err(Error::UnsupportedFlags { flags });
+        }
+        let num_entries = crate::read_u32(&data[8..12]) as usize;
+        let trailer_ofs = data.len() - hash_len;
+
+        let mut ofs = HEADER_LEN + hash_len;
+        let mut type_bitmaps = [0; 4];
+        for type_bitmap in &mut type_bitmaps {
+            *type_bitmap = ofs;
+            ofs = ewah_end(&data[..trailer_ofs], ofs)?;
+        }
+
+        // The lookup table is located right after the bitmaps, followed by the name-hash cache, whose size we only know
+        // once we know where the bitmaps end.
+        let (entries, entries_end) = entries_from_scan(&data[..trailer_ofs], ofs, num_entries)?;
+        let (entries, hash_cache_ofs) = if flags & FLAG_LOOKUP_TABLE != 0 {
+            let table_end = entries_end + num_entries * LOOKUP_ROW_LEN;
+            if table_end > trailer_ofs {
+                return Err(Error::Corrupt {
+                    message: "bitmap file is too short to hold its lookup table",
+                });
+            }
+            (
+                entries_from_lookup_table(&data[..entries_end], &data[entries_end..table_end], ofs)?,
+                table_end,
+            )
+        } else {
+            (entries, entries_end)
+        };
+
+        let (hash_cache_ofs, num_hashes) = if flags & FLAG_HASH_CACHE != 0 {
+            let len = trailer_ofs - hash_cache_ofs;
+            if len % 4 != 0 {
+                return Err(Error::Corrupt {
+                    message: "the name-hash cache has an invalid size",
+                });
+            }
+            (Some(hash_cache_ofs), len / 4)
+        } else {
+            if hash_cache_ofs != trailer_ofs {
+                return Err(Error::Corrupt {
+                    message: "unexpected data after the last bitmap",
+                });
+            }
+            (None, 0)
+        };
+
+        Ok(File {
+            data,
+            path: path.to_owned(),
+            object_hash,
+            flags,
+            type_bitmaps,
+            entries,
+            hash_cache_ofs,
+            num_hashes,
+        })
+    }
+}
+
+/// Read all entries one after another, starting at `ofs`, and return them sorted by commit position
+/// along with the offset past the last one.
+fn entries_from_scan(data: &[u8], mut ofs: usize, num_entries: usize) -> Result<(Vec<Entry>, usize), Error> {
+    let mut entries = Vec::with_capacity(num_entries);
+    for entry_index in 0..num_entries {
+        let header = data.get(ofs..ofs + ENTRY_HEADER_LEN).ok_or(Error::Corrupt {
+            message: "bitmap file is truncated in the middle of an entry",
+        })?;
+        let xor_offset = header[4] as usize;
+        if xor_offset > entry_index {
+            return Err(Error::Corrupt {
+                message: "a bitmap refers to a non-existing bitmap to be XORed with",
+            });
+        }
+        entries.push(Entry {
+            commit_index: crate::read_u32(&header[..4]),
+            xor_base: (xor_offset != 0).then(|| entry_index - xor_offset),
+            bitmap_ofs: ofs + ENTRY_HEADER_LEN,
+        });
+        ofs = ewah_end(data, ofs + ENTRY_HEADER_LEN)?;
+    }
+
+    let mut sorted: Vec<_> = (0..entries.len()).collect();
+    sorted.sort_by_key(|entry_index| entries[*entry_index].commit_index);
+    let mut sorted_position = vec![0; entries.len()];
+    for (position, entry_index) in sorted.iter().enumerate() {
+        sorted_position[*entry_index] = position;
+    }
+    let entries = sorted
+        .into_iter()
+        .map(|entry_index| {
+            let entry = entries[entry_index];
+            Entry {
+                xor_base: entry.xor_base.map(|base| sorted_position[base]),
+                ..entry
+            }
+        })
+        .collect();
+    Ok((entries, ofs))
+}
+
+/// Read all entries from the lookup `table`, which is sorted by commit position already, with `data` ending where the
+/// last bitmap ends.
+fn entries_from_lookup_table(data: &[u8], table: &[u8], entries_ofs: usize) -> Result<Vec<Entry>, Error> {
+    let num_entries = table.len() / LOOKUP_ROW_LEN;
+    let mut entries: Vec<Entry> = Vec::with_capacity(num_entries);
+    for row in table.chunks_exact(LOOKUP_ROW_LEN) {
+        let commit_index = crate::read_u32(&row[..4]);
+        let ofs = usize::try_from(crate::read_u64(&row[4..12]))
+            .ok()
+            .filter(|ofs| *ofs >= entries_ofs && ofs + ENTRY_HEADER_LEN <= data.len())
+            .ok_or(Error::Corrupt {
+                message: "the lookup table points to a bitmap outside of the file",
+            })?;
+        if crate::read_u32(&data[ofs..][..4]) != commit_index {
+            return Err(Error::Corrupt {
+                message: "the lookup table points to the bitmap of another commit",
+            });
+        }
+        let xor_base = match crate::read_u32(&row[12..]) {
+            u32::MAX => None,
+            row if (row as usize) < num_entries => Some(row as usize),
+            _ => {
+                return Err(Error::Corrupt {
+                    message: "the lookup table refers to a non-existing bitmap to be XORed with",
+                })
+            }
+        };
+        if matches!(entries.last(), Some(previous) if previous.commit_index >= commit_index) {
+            return Err(Error::Corrupt {
+                message: "the lookup table isn't sorted by commit position",
+            });
+        }
+        entries.push(Entry {
+            commit_index,
+            xor_base,
+            bitmap_ofs: ofs + ENTRY_HEADER_LEN,
+        });
+    }
+    Ok(entries)
+}
+
+/// Return the offset past the EWAH bitmap at `ofs` in `data`, without decoding it.
+fn ewah_end(data: &[u8], ofs: usize) -> Result<usize, Error> {
+    let num_words = data
+        .get(ofs + 4..ofs + 8)
+        .map(|num_words| crate::read_u32(num_words) as usize)
+        .ok_or(Error::Corrupt {
+            message: "bitmap file is truncated",
+        })?;
+    let end = ofs + 4 /* bits */ + 4 /* words */ + num_words * 8 + 4 /* position of last marker word */;
+    if end > data.len() {
+        return Err(Error::Corrupt {
+            message: "bitmap file is truncated",
+        });
+    }
+    Ok(end)
+}
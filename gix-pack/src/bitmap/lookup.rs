@@ -0,0 +1,100 @@
+use gix_bitmap::ewah;
+
+use crate::bitmap::{File, Lookup, ObjectIndex, Order, Position, Set};
+
+mod error {
+    /// The error returned by [`Lookup::new()`][super::Lookup::new()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The bitmap belongs to {actual}, but the index has checksum {expected}")]
+        ChecksumMismatch {
+            expected: gix_hash::ObjectId,
+            actual: gix_hash::ObjectId,
+        },
+        #[error("The order has {actual} objects, but the index has {expected}")]
+        OrderMismatch { expected: usize, actual: usize },
+        #[error("The bitmap file refers to commit {commit_index}, but the index only has {num_objects} objects")]
+        CommitOutOfBounds { commit_index: u32, num_objects: u32 },
+        #[error("The name-hash cache has {actual} entries, but the index has {expected} objects")]
+        HashCacheMismatch { expected: usize, actual: usize },
+    }
+}
+pub use error::Error;
+
+/// Initialization
+impl<'a> Lookup<'a> {
+    /// Create a new instance to answer reachability queries with the bitmaps in `file`, which belong to `index`
+    /// whose objects are assigned to bit positions according to `order`.
+    pub fn new(file: &'a File, index: &'a dyn ObjectIndex, order: &'a Order) -> Result<Self, Error> {
+        let expected = index.bitmap_checksum();
+        if file.checksum() != expected {
+            return Err(Error::ChecksumMismatch {
+                expected,
+                actual: file.checksum(),
+            });
+        }
+        let num_objects = index.num_objects();
+        if order.len() != num_objects as usize {
+            return Err(Error::OrderMismatch {
+                expected: num_objects as usize,
+                actual: order.len(),
+            });
+        }
+        if let Some(commit_index) = file.commit_indices().last().filter(|index| *index >= num_objects) {
+            return Err(Error::CommitOutOfBounds {
+                commit_index,
+                num_objects,
+            });
+        }
+        if file.has_hash_cache() && file.num_name_hashes() != num_objects as usize {
+            return Err(Error::HashCacheMismatch {
+                expected: num_objects as usize,
+                actual: file.num_name_hashes(),
+            });
+        }
+        Ok(Lookup { file, index, order })
+    }
+}
+
+/// Access
+impl<'a> Lookup<'a> {
+    /// The bitmap file we use.
+    pub fn file(&self) -> &'a File {
+        self.file
+    }
+
+    /// The index the bitmap file belongs to.
+    pub fn index(&self) -> &'a dyn ObjectIndex {
+        self.index
+    }
+
+    /// The amount of objects that can be represented in bitmaps.
+    pub fn num_objects(&self) -> u32 {
+        self.index.num_objects()
+    }
+
+    /// Return the bit position of the object with `id`, or `None` if it isn't in the index.
+    pub fn position_of(&self, id: &gix_hash::oid) -> Option<Position> {
+        self.index.lookup_index(id).map(|index| self.order.position_of(index))
+    }
+
+    /// Return the id of the object at bit `position`.
+    pub fn id_at(&self, position: Position) -> &'a gix_hash::oid {
+        self.index.oid_at_index(self.order.index_at(position))
+    }
+
+    /// Return the set of all objects reachable from the commit with `id`, including the commit itself,
+    /// or `None` if the commit has no bitmap.
+    pub fn reachable_from(&self, id: &gix_hash::oid) -> Result<Option<Set>, ewah::decode::Error> {
+        match self.index.lookup_index(id) {
+            Some(index) => self.file.bitmap_by_commit_index(index),
+            None => Ok(None),
+        }
+    }
+
+    /// Return the name-hash of the object at bit `position`, or 0 if it is unknown.
+    pub fn name_hash(&self, position: Position) -> u32 {
+        self.file.name_hash(self.order.index_at(position)).unwrap_or(0)
+    }
+}
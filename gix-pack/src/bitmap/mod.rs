@@ -0,0 +1,95 @@
+//! Reachability bitmaps as stored in `.bitmap` files next to a pack or a multi-pack index.
+//!
+//! A bitmap file assigns each object of a pack a bit by its position in the pack, or in _pseudo-pack order_ for
+//! multi-pack indices, and stores the set of all objects reachable from select commits. With these, counting the objects
+//! reachable from these commits doesn't require any tree traversal.
+use std::path::PathBuf;
+
+use memmap2::Mmap;
+
+/// The flag indicating that bitmaps contain all objects reachable from their commit. It's required to be set.
+pub const FLAG_FULL_DAG: u16 = 0x1;
+/// The flag indicating that the file contains a name-hash for each object.
+pub const FLAG_HASH_CACHE: u16 = 0x4;
+/// The flag indicating that the file contains a table to quickly find the bitmap of a commit.
+pub const FLAG_LOOKUP_TABLE: u16 = 0x10;
+
+/// The type for bit positions, i.e. the position of objects in their pack or in pseudo-pack order.
+pub type Position = u32;
+
+/// A `.bitmap` file, memory-mapped for fast access.
+///
+/// On its own, it knows nothing about object ids. Use a [`Lookup`] to answer queries by object id.
+pub struct File {
+    data: Mmap,
+    path: PathBuf,
+    object_hash: gix_hash::Kind,
+    flags: u16,
+    /// The offsets of the bitmaps of all commits, trees, blobs and tags, in that order.
+    type_bitmaps: [usize; 4],
+    /// All entries sorted by their commit position.
+    entries: Vec<Entry>,
+    hash_cache_ofs: Option<usize>,
+    num_hashes: usize,
+}
+
+/// Information about a single bitmap of a commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    /// The position of the commit in its index, i.e. not its bit position.
+    commit_index: u32,
+    /// The index of the entry the bitmap is XORed with, in our sorted list of entries.
+    xor_base: Option<usize>,
+    /// The offset of the EWAH bitmap in the file.
+    bitmap_ofs: usize,
+}
+
+/// An uncompressed set of objects, each represented by its bit [`Position`].
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Set {
+    words: Vec<u64>,
+}
+
+/// An index of objects, like a pack index or multi-pack index, that a bitmap file refers to.
+pub trait ObjectIndex {
+    /// The amount of objects in the index.
+    fn num_objects(&self) -> u32;
+    /// The id of the object at `index`, ranging from 0 to [`num_objects()`](ObjectIndex::num_objects()).
+    fn oid_at_index(&self, index: u32) -> &gix_hash::oid;
+    /// The index of the object with `id`, if present.
+    fn lookup_index(&self, id: &gix_hash::oid) -> Option<u32>;
+    /// The checksum the bitmap file stores to identify the pack or multi-pack index it belongs to.
+    fn bitmap_checksum(&self) -> gix_hash::ObjectId;
+}
+
+/// The mapping of bit positions to positions in an [`ObjectIndex`], and back.
+///
+/// For packs, bit positions are the position of objects in the pack, i.e. sorted by pack offset.
+/// For multi-pack indices, bit positions are given by the _pseudo-pack order_ stored in the multi-pack index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Order {
+    index_at_position: Vec<u32>,
+    position_at_index: Vec<Position>,
+}
+
+/// Answer reachability queries by object id using a bitmap [`File`], the [`ObjectIndex`] it belongs to and its [`Order`].
+pub struct Lookup<'a> {
+    file: &'a File,
+    index: &'a dyn ObjectIndex,
+    order: &'a Order,
+}
+
+mod access;
+mod impls;
+mod set;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod init;
+///
+#[allow(clippy::empty_docs)]
+pub mod lookup;
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "generate")]
+pub mod write;
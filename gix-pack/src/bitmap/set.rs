@@ -0,0 +1,97 @@
+use crate::bitmap::{Position, Set};
+
+impl Set {
+    /// Create a set from its uncompressed `words`, with the first bit being the least significant bit of the first word.
+    pub fn from_words(words: Vec<u64>) -> Self {
+        Set { words }
+    }
+
+    /// Decompress an EWAH `bitmap` into a set.
+    pub fn from_ewah(bitmap: &gix_bitmap::ewah::Vec) -> Self {
+        Set {
+            words: bitmap.to_words(),
+        }
+    }
+
+    /// Compress this set into an EWAH bitmap.
+    pub fn to_ewah(&self) -> gix_bitmap::ewah::Vec {
+        gix_bitmap::ewah::Vec::from_words(&self.words)
+    }
+
+    /// Add the object at `position`, and return `true` if it wasn't contained yet.
+    pub fn insert(&mut self, position: Position) -> bool {
+        let (word, mask) = word_and_mask(position);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let was_unset = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+        was_unset
+    }
+
+    /// Return `true` if the object at `position` is contained.
+    pub fn contains(&self, position: Position) -> bool {
+        let (word, mask) = word_and_mask(position);
+        matches!(self.words.get(word), Some(word) if word & mask != 0)
+    }
+
+    /// Add all objects of `other` to this set.
+    pub fn union_with(&mut self, other: &Set) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    /// Remove all objects of `other` from this set.
+    pub fn difference_with(&mut self, other: &Set) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= !other;
+        }
+    }
+
+    /// Toggle all objects of `other` in this set, i.e. compute the symmetric difference.
+    pub fn xor_with(&mut self, other: &Set) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word ^= other;
+        }
+    }
+
+    /// Return the amount of objects in the set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Return `true` if there are no objects in the set.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    /// Return an iterator over the positions of all objects in the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = Position> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros();
+                    word &= word - 1;
+                    (index * 64) as Position + bit
+                })
+            })
+        })
+    }
+
+    /// Return the uncompressed words of this set.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+}
+
+fn word_and_mask(position: Position) -> (usize, u64) {
+    (position as usize / 64, 1 << (position % 64))
+}
@@ -0,0 +1,400 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_features::progress::{Count, Progress};
+use gix_hash::ObjectId;
+
+use crate::{
+    bitmap::{File, ObjectIndex, Order, Position, Set, FLAG_FULL_DAG, FLAG_HASH_CACHE, FLAG_LOOKUP_TABLE},
+    data::output::count::name_hash,
+};
+
+mod error {
+    use gix_hash::ObjectId;
+
+    /// The error returned by [`File::write_to()`][crate::bitmap::File::write_to()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The order has {actual} objects, but the index has {expected}")]
+        OrderMismatch { expected: usize, actual: usize },
+        #[error("Object {id} is part of the index, but couldn't be found in the object database")]
+        MissingObject { id: ObjectId },
+        #[error("Object {id} is reachable from commit {commit}, but isn't part of the index")]
+        NotClosed { id: ObjectId, commit: ObjectId },
+        #[error(transparent)]
+        Find(#[from] gix_object::find::Error),
+        #[error(transparent)]
+        Decode(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Interrupted")]
+        Interrupted,
+    }
+}
+pub use error::Error;
+
+/// Options for use in [`File::write_to()`].
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Commits that should get a bitmap if they are a candidate, typically the tips of references.
+    pub preferred_commits: Vec<ObjectId>,
+    /// If set, don't write more than this amount of bitmaps.
+    pub max_bitmaps: Option<usize>,
+    /// If `true`, store the name-hash of each object so that readers can find good delta bases.
+    pub hash_cache: bool,
+    /// If `true`, store a table to find the bitmap of each commit without reading all of them first.
+    pub lookup_table: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            preferred_commits: Vec::new(),
+            max_bitmaps: None,
+            hash_cache: true,
+            lookup_table: false,
+        }
+    }
+}
+
+/// The result of [`File::write_to()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The checksum of the written bitmap file.
+    pub checksum: ObjectId,
+    /// The amount of commits that received a bitmap.
+    pub num_bitmaps: usize,
+}
+
+/// The maximum amount of previous bitmaps to try when looking for one to XOR a bitmap with.
+const MAX_XOR_OFFSET_SEARCH: usize = 10;
+
+struct Commit {
+    position: Position,
+    tree: Position,
+    parents: Vec<Position>,
+    time: i64,
+}
+
+/// Writing
+impl File {
+    /// Write a bitmap file for all objects in `index` to `out`, with bit positions as defined by `order`, and return
+    /// the checksum of the written file. Objects are read from `db`.
+    ///
+    /// Commits to receive a bitmap are selected like `git` does: all of the most recent 100 commits, and increasingly
+    /// fewer of the older ones, preferring [`Options::preferred_commits`] and merges when in doubt.
+    ///
+    /// Note that all objects reachable from commits in `index` must be contained in `index` as well.
+    /// The file should be written next to the pack as `pack-<hash>.bitmap`, or next to the multi-pack index as
+    /// `multi-pack-index-<checksum>.bitmap`.
+    pub fn write_to(
+        index: &dyn ObjectIndex,
+        order: &Order,
+        db: &dyn crate::Find,
+        out: &mut dyn std::io::Write,
+        progress: &mut dyn Progress,
+        should_interrupt: &AtomicBool,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        let num_objects = index.num_objects();
+        if order.len() != num_objects as usize {
+            return Err(Error::OrderMismatch {
+                expected: num_objects as usize,
+                actual: order.len(),
+            });
+        }
+        let id_at = |position: Position| index.oid_at_index(order.index_at(position));
+        let position_of = |id: &gix_hash::oid| index.lookup_index(id).map(|index| order.position_of(index));
+
+        progress.set_name("reading objects".into());
+        progress.init(Some(num_objects as usize), gix_features::progress::count("objects"));
+        let mut buf = Vec::new();
+        let mut kinds = Vec::with_capacity(num_objects as usize);
+        let mut commits = Vec::new();
+        for position in 0..num_objects {
+            let id = id_at(position);
+            let (object, _location) = db
+                .try_find(id, &mut buf)?
+                .ok_or_else(|| Error::MissingObject { id: id.to_owned() })?;
+            kinds.push(object.kind);
+            if object.kind == gix_object::Kind::Commit {
+                let commit = gix_object::CommitRef::from_bytes(object.data)?;
+                let not_closed = |missing: ObjectId| Error::NotClosed {
+                    id: missing,
+                    commit: id.to_owned(),
+                };
+                let tree = commit.tree();
+                commits.push(Commit {
+                    position,
+                    tree: position_of(&tree).ok_or_else(|| not_closed(tree))?,
+                    parents: commit
+                        .parents()
+                        .map(|parent| position_of(&parent).ok_or_else(|| not_closed(parent)))
+                        .collect::<Result<_, _>>()?,
+                    time: commit.committer().time.seconds,
+                });
+            }
+            progress.inc();
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+        }
+
+        let commit_at: HashMap<Position, usize> = commits
+            .iter()
+            .enumerate()
+            .map(|(commit_index, commit)| (commit.position, commit_index))
+            .collect();
+        let selected = select_commits(
+            &commits,
+            &options
+                .preferred_commits
+                .iter()
+                .filter_map(|id| position_of(id))
+                .collect::<Vec<_>>(),
+            options.max_bitmaps,
+        );
+
+        progress.set_name("computing bitmaps".into());
+        progress.init(Some(selected.len()), gix_features::progress::count("commits"));
+        let mut name_hashes = vec![0u32; if options.hash_cache { num_objects as usize } else { 0 }];
+        let mut bitmaps: HashMap<Position, gix_bitmap::ewah::Vec> = HashMap::with_capacity(selected.len());
+        let mut by_age = selected.clone();
+        by_age.sort_by_key(|commit_index| (commits[*commit_index].time, commits[*commit_index].position));
+        for commit_index in by_age {
+            let tip = commits[commit_index].position;
+            let mut reachable = Set::default();
+            let mut queue = vec![tip];
+            while let Some(position) = queue.pop() {
+                if reachable.contains(position) {
+                    continue;
+                }
+                if position != tip {
+                    if let Some(bitmap) = bitmaps.get(&position) {
+                        reachable.union_with(&Set::from_ewah(bitmap));
+                        continue;
+                    }
+                }
+                reachable.insert(position);
+                let Some(commit) = commit_at.get(&position).map(|commit_index| &commits[*commit_index]) else {
+                    continue;
+                };
+                add_tree(
+                    commit.tree,
+                    &mut reachable,
+                    &mut name_hashes,
+                    db,
+                    &mut buf,
+                    &id_at,
+                    &position_of,
+                    tip,
+                )?;
+                queue.extend(commit.parents.iter().copied());
+            }
+            bitmaps.insert(tip, reachable.to_ewah());
+            progress.inc();
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+        }
+
+        let mut out = gix_features::hash::Write::new(out, index.bitmap_checksum().kind());
+        let mut flags = FLAG_FULL_DAG;
+        if options.hash_cache {
+            flags |= FLAG_HASH_CACHE;
+        }
+        if options.lookup_table {
+            flags |= FLAG_LOOKUP_TABLE;
+        }
+        let mut header = Vec::with_capacity(12);
+        header.extend_from_slice(File::SIGNATURE);
+        header.extend_from_slice(&1u16.to_be_bytes());
+        header.extend_from_slice(&flags.to_be_bytes());
+        header.extend_from_slice(&(selected.len() as u32).to_be_bytes());
+        header.extend_from_slice(index.bitmap_checksum().as_slice());
+        out.write_all(&header)?;
+        let mut bytes_written = header.len();
+
+        for kind in [
+            gix_object::Kind::Commit,
+            gix_object::Kind::Tree,
+            gix_object::Kind::Blob,
+            gix_object::Kind::Tag,
+        ] {
+            let mut objects = Set::default();
+            for (position, _) in kinds
+                .iter()
+                .enumerate()
+                .filter(|(_, object_kind)| **object_kind == kind)
+            {
+                objects.insert(position as Position);
+            }
+            let ewah = objects.to_ewah();
+            ewah.write_to(&mut out)?;
+            bytes_written += ewah.serialized_size();
+        }
+
+        progress.set_name("writing bitmaps".into());
+        progress.init(Some(selected.len()), gix_features::progress::count("bitmaps"));
+        let mut table = Vec::with_capacity(selected.len());
+        for (entry_index, commit_index) in selected.iter().enumerate() {
+            let position = commits[*commit_index].position;
+            let bitmap = Set::from_ewah(&bitmaps[&position]);
+            let mut best = (0, bitmaps[&position].clone());
+            for xor_offset in 1..=MAX_XOR_OFFSET_SEARCH.min(entry_index) {
+                let mut xored = bitmap.clone();
+                xored.xor_with(&Set::from_ewah(
+                    &bitmaps[&commits[selected[entry_index - xor_offset]].position],
+                ));
+                let ewah = xored.to_ewah();
+                if ewah.serialized_size() < best.1.serialized_size() {
+                    best = (xor_offset, ewah);
+                }
+            }
+            let (xor_offset, ewah) = best;
+            let commit_index = order.index_at(position);
+            table.push((commit_index, bytes_written as u64, xor_offset));
+            out.write_all(&commit_index.to_be_bytes())?;
+            out.write_all(&[xor_offset as u8, 0 /* flags */])?;
+            ewah.write_to(&mut out)?;
+            bytes_written += 4 + 2 + ewah.serialized_size();
+            progress.inc();
+        }
+
+        if options.lookup_table {
+            let mut rows: Vec<_> = (0..table.len()).collect();
+            rows.sort_by_key(|entry_index| table[*entry_index].0);
+            let mut row_of_entry = vec![0u32; table.len()];
+            for (row, entry_index) in rows.iter().enumerate() {
+                row_of_entry[*entry_index] = row as u32;
+            }
+            for entry_index in rows {
+                let (commit_index, offset, xor_offset) = table[entry_index];
+                out.write_all(&commit_index.to_be_bytes())?;
+                out.write_all(&offset.to_be_bytes())?;
+                let xor_row = if xor_offset == 0 {
+                    u32::MAX
+                } else {
+                    row_of_entry[entry_index - xor_offset]
+                };
+                out.write_all(&xor_row.to_be_bytes())?;
+            }
+        }
+
+        if options.hash_cache {
+            for index in 0..num_objects {
+                out.write_all(&name_hashes[order.position_of(index) as usize].to_be_bytes())?;
+            }
+        }
+        let checksum = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        Ok(Outcome {
+            checksum,
+            num_bitmaps: selected.len(),
+        })
+    }
+}
+
+/// Add `tree` and all objects reachable from it to `reachable`, skipping trees that are already contained, while recording
+/// the name-hashes of objects the first time they are seen.
+#[allow(clippy::too_many_arguments)]
+fn add_tree<'a>(
+    tree: Position,
+    reachable: &mut Set,
+    name_hashes: &mut [u32],
+    db: &dyn crate::Find,
+    buf: &mut Vec<u8>,
+    id_at: &dyn Fn(Position) -> &'a gix_hash::oid,
+    position_of: &dyn Fn(&gix_hash::oid) -> Option<Position>,
+    commit: Position,
+) -> Result<(), Error> {
+    let mut trees = vec![tree];
+    while let Some(tree) = trees.pop() {
+        if !reachable.insert(tree) {
+            continue;
+        }
+        let id = id_at(tree);
+        let (object, _location) = db
+            .try_find(id, buf)?
+            .ok_or_else(|| Error::MissingObject { id: id.to_owned() })?;
+        for entry in gix_object::TreeRefIter::from_bytes(object.data, object.object_hash) {
+            let entry = entry?;
+            if entry.mode.is_commit() {
+                continue;
+            }
+            let position = position_of(entry.oid).ok_or_else(|| Error::NotClosed {
+                id: entry.oid.to_owned(),
+                commit: id_at(commit).to_owned(),
+            })?;
+            if let Some(hash) = name_hashes.get_mut(position as usize).filter(|hash| **hash == 0) {
+                *hash = name_hash(entry.filename);
+            }
+            if entry.mode.is_tree() {
+                trees.push(position);
+            } else {
+                reachable.insert(position);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Select the commits to receive a bitmap like `git` does, and return their indices into `commits`, most recent first.
+fn select_commits(commits: &[Commit], preferred: &[Position], max_bitmaps: Option<usize>) -> Vec<usize> {
+    const MIN_COMMITS: usize = 100;
+    const MAX_COMMITS: usize = 5000;
+    const MUST_REGION: usize = 100;
+    const MIN_REGION: usize = 20000;
+
+    let mut by_date: Vec<_> = (0..commits.len()).collect();
+    by_date.sort_by(|a, b| {
+        commits[*b]
+            .time
+            .cmp(&commits[*a].time)
+            .then(commits[*a].position.cmp(&commits[*b].position))
+    });
+    if by_date.len() < MIN_COMMITS {
+        by_date.truncate(max_bitmaps.unwrap_or(usize::MAX));
+        return by_date;
+    }
+
+    let next_commit_index = |index: usize| -> usize {
+        if index <= MUST_REGION {
+            0
+        } else if index <= MIN_REGION {
+            (index - MUST_REGION).min(MIN_COMMITS)
+        } else {
+            (index - MIN_REGION).clamp(MIN_COMMITS, MAX_COMMITS)
+        }
+    };
+    let mut selected = Vec::new();
+    let mut index = 0;
+    loop {
+        let next = next_commit_index(index);
+        if index + next >= by_date.len() || matches!(max_bitmaps, Some(max) if selected.len() >= max) {
+            break;
+        }
+        let mut chosen = by_date[index + next];
+        if next != 0 {
+            for candidate in by_date[index..=index + next].iter().copied() {
+                let commit = &commits[candidate];
+                if preferred.contains(&commit.position) {
+                    chosen = candidate;
+                    break;
+                }
+                if commit.parents.len() > 1 {
+                    chosen = candidate;
+                }
+            }
+        } else {
+            chosen = by_date[index];
+        }
+        selected.push(chosen);
+        index += next + 1;
+    }
+    selected
+}
@@ -58,6 +58,7 @@ impl crate::Bundle {
                     gix_object::Data {
                         kind: r.kind,
                         data: out.as_slice(),
+                        object_hash: self.index.object_hash(),
                     },
                     crate::data::entry::Location {
                         pack_id: self.pack.id,
@@ -109,7 +109,7 @@ impl crate::Bundle {
                         writer: data_file.clone(),
                     },
                     pack_version,
-                    gix_hash::Kind::Sha1, // Thin packs imply a pack being transported, and there we only ever know SHA1 at the moment.
+                    object_hash,
                 );
                 (Box::new(pack_entries_iter), pack_version)
             }
@@ -140,6 +140,7 @@ impl crate::Bundle {
             outcome,
             data_path,
             index_path,
+            reverse_index_path,
             keep_path,
         } = crate::Bundle::inner_write(
             directory,
@@ -157,6 +158,7 @@ impl crate::Bundle {
             pack_version,
             data_path,
             index_path,
+            reverse_index_path,
             keep_path,
         })
     }
@@ -240,6 +242,7 @@ impl crate::Bundle {
             outcome,
             data_path,
             index_path,
+            reverse_index_path,
             keep_path,
         } = crate::Bundle::inner_write(
             directory,
@@ -257,6 +260,7 @@ impl crate::Bundle {
             pack_version,
             data_path,
             index_path,
+            reverse_index_path,
             keep_path,
         })
     }
@@ -269,6 +273,7 @@ impl crate::Bundle {
             iteration_mode: _,
             index_version: index_kind,
             object_hash,
+            write_reverse_index,
         }: Options,
         data_file: SharedTempFile,
         mut pack_entries_iter: Box<dyn Iterator<Item = Result<data::input::Entry, data::input::Error>> + 'a>,
@@ -305,6 +310,17 @@ impl crate::Bundle {
                 let keep_path = data_path.with_extension("keep");
 
                 std::fs::write(&keep_path, b"")?;
+                let reverse_index_path = if write_reverse_index {
+                    let mut reverse_index_file =
+                        gix_tempfile::new(directory, ContainingDirectory::Exists, AutoRemove::Tempfile)?;
+                    let index = crate::index::File::at(index_file.with_mut(|f| f.path().to_owned())?, object_hash)?;
+                    crate::index::reverse::File::write_from_index(&index, &mut reverse_index_file)?;
+                    let reverse_index_path = data_path.with_extension("rev");
+                    reverse_index_file.persist(&reverse_index_path)?;
+                    Some(reverse_index_path)
+                } else {
+                    None
+                };
                 Arc::try_unwrap(data_file)
                     .expect("only one handle left after pack was consumed")
                     .into_inner()
@@ -324,6 +340,7 @@ impl crate::Bundle {
                     outcome,
                     data_path: Some(data_path),
                     index_path: Some(index_path),
+                    reverse_index_path,
                     keep_path: Some(keep_path),
                 }
             }
@@ -341,6 +358,7 @@ impl crate::Bundle {
                 )?,
                 data_path: None,
                 index_path: None,
+                reverse_index_path: None,
                 keep_path: None,
             },
         })
@@ -367,5 +385,6 @@ struct WriteOutcome {
     outcome: crate::index::write::Outcome,
     data_path: Option<PathBuf>,
     index_path: Option<PathBuf>,
+    reverse_index_path: Option<PathBuf>,
     keep_path: Option<PathBuf>,
 }
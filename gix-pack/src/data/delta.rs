@@ -15,6 +15,12 @@ pub fn decode_header_size(d: &[u8]) -> (u64, usize) {
     (size, consumed)
 }
 
+/// Apply the delta instructions in `data`, without the header with base and result size, to `base` to produce `target`,
+/// which must have the size of the result object.
+///
+/// # Panics
+///
+/// If the instructions are invalid or don't match the size of `base` or `target`.
 pub fn apply(base: &[u8], mut target: &mut [u8], data: &[u8]) {
     let mut i = 0;
     while let Some(cmd) = data.get(i) {
@@ -68,3 +74,178 @@ pub fn apply(base: &[u8], mut target: &mut [u8], data: &[u8]) {
     assert_eq!(i, data.len());
     assert_eq!(target.len(), 0);
 }
+
+/// The amount of bytes in a block of the base object that is indexed to find matches in the target object.
+const BLOCK_SIZE: usize = 16;
+/// The most bytes a single insert instruction can carry.
+const MAX_INSERT_SIZE: usize = 0x7f;
+/// The most bytes a single copy instruction can copy.
+const MAX_COPY_SIZE: usize = 0x10000;
+/// The most candidates to try for each block in the target object.
+const MAX_CANDIDATES: usize = 64;
+/// Stop looking for better candidates once a match is at least this long.
+const GOOD_ENOUGH_MATCH: usize = 4096;
+const NO_BLOCK: u32 = u32::MAX;
+
+/// An index of the blocks of a base object that allows to quickly [encode][Index::encode()] deltas against it.
+///
+/// It's worth keeping if many target objects are tried against the same base, as it's done when searching for the best
+/// delta base within a window of objects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Index {
+    /// The size of the base object this index was created for.
+    base_size: usize,
+    /// The first block with a given hash, or [`NO_BLOCK`], indexed by the hash masked with `heads.len() - 1`.
+    heads: Vec<u32>,
+    /// The next block with the same masked hash, indexed by block, with blocks being in reverse order.
+    next: Vec<u32>,
+}
+
+impl Index {
+    /// Create a new index for `base`, the object to produce deltas against.
+    pub fn new(base: &[u8]) -> Self {
+        // Copy instructions can only refer to the first 4GB of the base object.
+        let num_blocks = (base.len() / BLOCK_SIZE).min(u32::MAX as usize / BLOCK_SIZE);
+        let mut heads = vec![NO_BLOCK; num_blocks.next_power_of_two().max(1)];
+        let mut next = vec![NO_BLOCK; num_blocks];
+        let mask = heads.len() - 1;
+        for block in (0..num_blocks).rev() {
+            let ofs = block * BLOCK_SIZE;
+            let bucket = block_hash(&base[ofs..][..BLOCK_SIZE]) as usize & mask;
+            next[block] = heads[bucket];
+            heads[bucket] = block as u32;
+        }
+        Index {
+            base_size: base.len(),
+            heads,
+            next,
+        }
+    }
+
+    /// Encode the instructions to turn `base`, which must be the object this index was created for, into `target`
+    /// into `out`, including the header with the sizes of both objects.
+    ///
+    /// Return `false` if the delta would be larger than `max_size`, in which case the content of `out` is unspecified.
+    pub fn encode(&self, base: &[u8], target: &[u8], max_size: usize, out: &mut Vec<u8>) -> bool {
+        assert_eq!(
+            base.len(),
+            self.base_size,
+            "the index must be created for the given base"
+        );
+        out.clear();
+        encode_header_size(base.len() as u64, out);
+        encode_header_size(target.len() as u64, out);
+
+        let mask = self.heads.len() - 1;
+        let mut insert_start = 0;
+        let mut pos = 0;
+        while pos + BLOCK_SIZE <= target.len() {
+            let mut best = (0, 0);
+            let mut block = self.heads[block_hash(&target[pos..][..BLOCK_SIZE]) as usize & mask];
+            let mut candidates = 0;
+            while block != NO_BLOCK && candidates < MAX_CANDIDATES {
+                let base_ofs = block as usize * BLOCK_SIZE;
+                let len = common_prefix_len(&base[base_ofs..], &target[pos..]).min(u32::MAX as usize - base_ofs);
+                if len > best.1 {
+                    best = (base_ofs, len);
+                    if len >= GOOD_ENOUGH_MATCH {
+                        break;
+                    }
+                }
+                block = self.next[block as usize];
+                candidates += 1;
+            }
+
+            let (mut base_ofs, mut len) = best;
+            if len < BLOCK_SIZE {
+                pos += 1;
+                if pos - insert_start > max_size {
+                    return false;
+                }
+                continue;
+            }
+            while pos > insert_start && base_ofs > 0 && base[base_ofs - 1] == target[pos - 1] {
+                pos -= 1;
+                base_ofs -= 1;
+                len += 1;
+            }
+            encode_insert(&target[insert_start..pos], out);
+            encode_copy(base_ofs, len, out);
+            if out.len() > max_size {
+                return false;
+            }
+            pos += len;
+            insert_start = pos;
+        }
+        encode_insert(&target[insert_start..], out);
+        out.len() <= max_size
+    }
+}
+
+/// Encode a delta to turn `base` into `target` into `out`, including the header with the sizes of both objects.
+///
+/// Use an [`Index`] directly to encode multiple targets against the same base or to limit the size of the delta.
+pub fn encode(base: &[u8], target: &[u8], out: &mut Vec<u8>) {
+    Index::new(base).encode(base, target, usize::MAX, out);
+}
+
+/// Encode `size` like [`decode_header_size()`] expects it.
+fn encode_header_size(mut size: u64, out: &mut Vec<u8>) {
+    loop {
+        let byte = (size & 0x7f) as u8;
+        size >>= 7;
+        if size == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn encode_insert(mut data: &[u8], out: &mut Vec<u8>) {
+    while !data.is_empty() {
+        let len = data.len().min(MAX_INSERT_SIZE);
+        out.push(len as u8);
+        out.extend_from_slice(&data[..len]);
+        data = &data[len..];
+    }
+}
+
+fn encode_copy(mut ofs: usize, mut len: usize, out: &mut Vec<u8>) {
+    while len > 0 {
+        let size = len.min(MAX_COPY_SIZE);
+        let cmd_pos = out.len();
+        let mut cmd = 0b1000_0000;
+        out.push(cmd);
+        for (bit, shift) in [(0b0000_0001, 0), (0b0000_0010, 8), (0b0000_0100, 16), (0b0000_1000, 24)] {
+            let byte = (ofs >> shift) as u8;
+            if byte != 0 {
+                cmd |= bit;
+                out.push(byte);
+            }
+        }
+        if size != MAX_COPY_SIZE {
+            for (bit, shift) in [(0b0001_0000, 0), (0b0010_0000, 8), (0b0100_0000, 16)] {
+                let byte = (size >> shift) as u8;
+                if byte != 0 {
+                    cmd |= bit;
+                    out.push(byte);
+                }
+            }
+        }
+        out[cmd_pos] = cmd;
+        ofs += size;
+        len -= size;
+    }
+}
+
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn block_hash(block: &[u8]) -> u32 {
+    let lo = u64::from_le_bytes(block[..8].try_into().expect("block of 16 bytes"));
+    let hi = u64::from_le_bytes(block[8..].try_into().expect("block of 16 bytes"));
+    let hash = lo.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ hi.rotate_left(31).wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+    (hash >> 32) as u32
+}
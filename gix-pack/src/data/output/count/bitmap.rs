@@ -0,0 +1,153 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gix_hash::ObjectId;
+use gix_hashtable::{HashMap, HashSet};
+
+use crate::{
+    bitmap,
+    data::output::{
+        count::{name_hash, objects::Error, objects::Outcome, PackLocation},
+        Count,
+    },
+    FindExt,
+};
+
+/// Generate [`Count`]s for all objects reachable from `wants`, but not from `haves`, using the reachability bitmaps
+/// of `lookup` to avoid traversing commits and trees where possible.
+///
+/// * `db` - the object store to read objects from which aren't covered by bitmaps.
+/// * `wants` - tips of commits, tags, trees or blobs whose reachable objects should be counted, including themselves.
+/// * `haves` - tips of objects the receiver is known to have already, so their reachable objects are excluded.
+/// * `objects` - count the amount of objects we encounter.
+/// * `should_interrupt` - a flag that is set to true if the operation should stop.
+///
+/// Objects that aren't contained in the index of `lookup` are found by traversal, and are returned after those in the index.
+/// All counts need their pack location to be looked up as they are [`PackLocation::NotLookedUp`], which is done
+/// automatically by [`iter_from_counts()`][crate::data::output::entry::iter_from_counts()].
+pub fn objects_with_bitmap(
+    db: &dyn crate::Find,
+    lookup: &bitmap::Lookup<'_>,
+    wants: &[ObjectId],
+    haves: &[ObjectId],
+    objects: &dyn gix_features::progress::Count,
+    should_interrupt: &AtomicBool,
+) -> Result<(Vec<Count>, Outcome), Error> {
+    let mut outcome = Outcome {
+        input_objects: wants.len(),
+        ..Default::default()
+    };
+    let mut names = HashMap::default();
+    let mut buf = Vec::new();
+    let mut state = State {
+        db,
+        lookup,
+        names: &mut names,
+        buf: &mut buf,
+        decoded_objects: &mut outcome.decoded_objects,
+        should_interrupt,
+    };
+    let (mut reachable, mut extra) = state.reachable_from(wants)?;
+    if !haves.is_empty() {
+        let (have, extra_have) = state.reachable_from(haves)?;
+        reachable.difference_with(&have);
+        extra.retain(|id| !extra_have.contains(id));
+    }
+
+    let mut out = Vec::with_capacity(reachable.len() + extra.len());
+    out.extend(reachable.iter().map(|position| {
+        let id = lookup.id_at(position).to_owned();
+        let name_hash = match lookup.name_hash(position) {
+            0 => names.get(&id).copied().unwrap_or(0),
+            hash => hash,
+        };
+        Count {
+            id,
+            entry_pack_location: PackLocation::NotLookedUp,
+            name_hash,
+        }
+    }));
+    let mut extra: Vec<_> = extra.into_iter().collect();
+    extra.sort();
+    out.extend(extra.into_iter().map(|id| Count {
+        name_hash: names.get(&id).copied().unwrap_or(0),
+        id,
+        entry_pack_location: PackLocation::NotLookedUp,
+    }));
+    objects.inc_by(out.len());
+
+    outcome.total_objects = out.len();
+    outcome.expanded_objects = out.len().saturating_sub(outcome.input_objects);
+    Ok((out, outcome))
+}
+
+struct State<'a> {
+    db: &'a dyn crate::Find,
+    lookup: &'a bitmap::Lookup<'a>,
+    /// The name-hashes of objects seen while traversing trees.
+    names: &'a mut HashMap<ObjectId, u32>,
+    buf: &'a mut Vec<u8>,
+    decoded_objects: &'a mut usize,
+    should_interrupt: &'a AtomicBool,
+}
+
+impl<'a> State<'a> {
+    /// Return the set of objects reachable from `tips` along with all reachable objects that aren't in the index.
+    fn reachable_from(&mut self, tips: &[ObjectId]) -> Result<(bitmap::Set, HashSet<ObjectId>), Error> {
+        let mut reachable = bitmap::Set::default();
+        let mut extra = HashSet::default();
+        let mut queue: Vec<ObjectId> = tips.to_vec();
+        let lookup = self.lookup;
+        let mut mark = |id: &gix_hash::oid, reachable: &mut bitmap::Set| match lookup.position_of(id) {
+            Some(position) => reachable.insert(position),
+            None => extra.insert(id.to_owned()),
+        };
+        while let Some(id) = queue.pop() {
+            if self.should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            let position = self.lookup.position_of(&id);
+            if matches!(position, Some(position) if reachable.contains(position)) {
+                continue;
+            }
+            if let Some(bitmap) = self.lookup.reachable_from(&id)? {
+                reachable.union_with(&bitmap);
+                continue;
+            }
+            if !mark(&id, &mut reachable) {
+                continue;
+            }
+
+            let (object, _location) = self.db.find(&id, self.buf)?;
+            *self.decoded_objects += 1;
+            match object.kind {
+                gix_object::Kind::Commit => {
+                    let mut commit = gix_object::CommitRefIter::from_bytes(object.data);
+                    queue.push(commit.tree_id().map_err(Error::CommitDecode)?);
+                    queue.extend(commit.parent_ids());
+                }
+                gix_object::Kind::Tag => {
+                    let tag = gix_object::TagRefIter::from_bytes(object.data);
+                    queue.push(tag.target_id().map_err(Error::CommitDecode)?);
+                }
+                gix_object::Kind::Tree => {
+                    for entry in gix_object::TreeRefIter::from_bytes(object.data, object.object_hash) {
+                        let entry = entry.map_err(Error::CommitDecode)?;
+                        if entry.mode.is_commit() {
+                            continue;
+                        }
+                        self.names
+                            .entry(entry.oid.to_owned())
+                            .or_insert_with(|| name_hash(entry.filename));
+                        if entry.mode.is_tree() {
+                            queue.push(entry.oid.to_owned());
+                        } else {
+                            mark(entry.oid, &mut reachable);
+                        }
+                    }
+                }
+                gix_object::Kind::Blob => {}
+            }
+        }
+        Ok((reachable, extra))
+    }
+}
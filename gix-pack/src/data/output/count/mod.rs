@@ -35,10 +35,24 @@ impl Count {
         Count {
             id: oid.into(),
             entry_pack_location: PackLocation::LookedUp(location),
+            name_hash: 0,
         }
     }
 }
 
+/// Compute a hash of `name`, the name of an object in its tree, for use in [`Count::name_hash`].
+///
+/// Like in `git`, the last characters have the most influence on the hash, so names with the same extension sort closely
+/// together.
+pub fn name_hash(name: &[u8]) -> u32 {
+    name.iter()
+        .filter(|b| !b.is_ascii_whitespace())
+        .fold(0, |hash, b| (hash >> 2).wrapping_add(u32::from(*b) << 24))
+}
+
+mod bitmap;
+pub use bitmap::objects_with_bitmap;
+
 #[path = "objects/mod.rs"]
 mod objects_impl;
 pub use objects_impl::{objects, objects_unthreaded};
@@ -204,7 +204,7 @@ mod expand {
                                     push_obj_count_unique(
                                         &mut out, seen_objs, &tree_id, location, objects, stats, true,
                                     );
-                                    gix_object::TreeRefIter::from_bytes(obj.data)
+                                    gix_object::TreeRefIter::from_bytes(obj.data, obj.object_hash)
                                 };
 
                                 let objects_ref = if parent_commit_ids.is_empty() {
@@ -242,7 +242,10 @@ mod expand {
                                                 stats,
                                                 true,
                                             );
-                                            gix_object::TreeRefIter::from_bytes(parent_tree_obj.data)
+                                            gix_object::TreeRefIter::from_bytes(
+                                                parent_tree_obj.data,
+                                                parent_tree_obj.object_hash,
+                                            )
                                         };
 
                                         changes_delegate.clear();
@@ -259,8 +262,16 @@ mod expand {
                                     }
                                     &changes_delegate.objects
                                 };
-                                for id in objects_ref.iter() {
-                                    out.push(id_to_count(db, buf2, id, objects, stats, allow_pack_lookups));
+                                for (id, name_hash) in objects_ref.iter() {
+                                    out.push(id_to_count(
+                                        db,
+                                        buf2,
+                                        id,
+                                        *name_hash,
+                                        objects,
+                                        stats,
+                                        allow_pack_lookups,
+                                    ));
                                 }
                                 break;
                             }
@@ -279,7 +290,7 @@ mod expand {
                                 {
                                     let objects = ExpandedCountingObjects::new(db, out, objects);
                                     gix_traverse::tree::breadthfirst(
-                                        gix_object::TreeRefIter::from_bytes(obj.0.data),
+                                        gix_object::TreeRefIter::from_bytes(obj.0.data, obj.0.object_hash),
                                         &mut tree_traversal_state,
                                         &objects,
                                         &mut traverse_delegate,
@@ -287,8 +298,16 @@ mod expand {
                                     .map_err(Error::TreeTraverse)?;
                                     out = objects.dissolve(stats);
                                 }
-                                for id in &traverse_delegate.non_trees {
-                                    out.push(id_to_count(db, buf1, id, objects, stats, allow_pack_lookups));
+                                for (id, name_hash) in &traverse_delegate.non_trees {
+                                    out.push(id_to_count(
+                                        db,
+                                        buf1,
+                                        id,
+                                        *name_hash,
+                                        objects,
+                                        stats,
+                                        allow_pack_lookups,
+                                    ));
                                 }
                                 break;
                             }
@@ -345,6 +364,7 @@ mod expand {
         db: &dyn crate::Find,
         buf: &mut Vec<u8>,
         id: &oid,
+        name_hash: u32,
         objects: &gix_features::progress::AtomicStep,
         statistics: &mut Outcome,
         allow_pack_lookups: bool,
@@ -358,6 +378,7 @@ mod expand {
             } else {
                 PackLocation::NotLookedUp
             },
+            name_hash,
         }
     }
 
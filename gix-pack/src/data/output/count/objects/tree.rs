@@ -6,11 +6,13 @@ pub mod changes {
     use gix_hash::ObjectId;
     use gix_object::bstr::BStr;
 
-    use crate::data::output::count::objects_impl::util::InsertImmutable;
+    use crate::data::output::count::{name_hash, objects_impl::util::InsertImmutable};
 
     pub struct AllNew<'a, H> {
-        pub objects: Vec<ObjectId>,
+        /// All new objects along with the hash of their name.
+        pub objects: Vec<(ObjectId, u32)>,
         all_seen: &'a H,
+        name_hash: u32,
     }
 
     impl<'a, H> AllNew<'a, H>
@@ -21,6 +23,7 @@ pub mod changes {
             AllNew {
                 objects: Default::default(),
                 all_seen,
+                name_hash: 0,
             }
         }
         pub fn clear(&mut self) {
@@ -34,9 +37,13 @@ pub mod changes {
     {
         fn pop_front_tracked_path_and_set_current(&mut self) {}
 
-        fn push_back_tracked_path_component(&mut self, _component: &BStr) {}
+        fn push_back_tracked_path_component(&mut self, component: &BStr) {
+            self.name_hash = name_hash(component);
+        }
 
-        fn push_path_component(&mut self, _component: &BStr) {}
+        fn push_path_component(&mut self, component: &BStr) {
+            self.name_hash = name_hash(component);
+        }
 
         fn pop_path_component(&mut self) {}
 
@@ -48,7 +55,7 @@ pub mod changes {
                     }
                     let inserted = self.all_seen.insert(oid);
                     if inserted {
-                        self.objects.push(oid);
+                        self.objects.push((oid, self.name_hash));
                     }
                 }
                 Change::Deletion { .. } => {}
@@ -63,10 +70,11 @@ pub mod traverse {
     use gix_object::{bstr::BStr, tree::EntryRef};
     use gix_traverse::tree::{visit::Action, Visit};
 
-    use crate::data::output::count::objects_impl::util::InsertImmutable;
+    use crate::data::output::count::{name_hash, objects_impl::util::InsertImmutable};
 
     pub struct AllUnseen<'a, H> {
-        pub non_trees: Vec<ObjectId>,
+        /// All unseen objects that aren't trees along with the hash of their name.
+        pub non_trees: Vec<(ObjectId, u32)>,
         all_seen: &'a H,
     }
 
@@ -112,7 +120,7 @@ pub mod traverse {
             }
             let inserted = self.all_seen.insert(entry.oid.to_owned());
             if inserted {
-                self.non_trees.push(entry.oid.to_owned());
+                self.non_trees.push((entry.oid.to_owned(), name_hash(entry.filename)));
             }
             Action::Continue
         }
@@ -91,6 +91,8 @@ pub enum Error {
     TreeTraverse(gix_traverse::tree::breadthfirst::Error),
     #[error(transparent)]
     TreeChanges(gix_diff::tree::changes::Error),
+    #[error("A reachability bitmap couldn't be decoded")]
+    Bitmap(#[from] gix_bitmap::ewah::decode::Error),
     #[error("Operation interrupted")]
     Interrupted,
 }
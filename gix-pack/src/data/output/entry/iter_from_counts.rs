@@ -10,7 +10,7 @@ pub(crate) mod function {
         },
     };
 
-    use super::{reduce, util, Error, Mode, Options, Outcome, ProgressId};
+    use super::{delta_search, reduce, util, Error, Mode, Options, Outcome, ProgressId};
     use crate::data::output;
 
     /// Given a known list of object `counts`, calculate entries ready to be put into a data pack.
@@ -52,11 +52,13 @@ pub(crate) mod function {
             allow_thin_pack,
             thread_limit,
             chunk_size,
+            delta_window,
+            delta_depth,
         }: Options,
     ) -> impl Iterator<Item = Result<(SequenceId, Vec<output::Entry>), Error>>
            + parallel::reduce::Finalize<Reduce = reduce::Statistics<Error>>
     where
-        Find: crate::Find + Send + Clone + 'static,
+        Find: crate::Find + gix_object::FindHeader + Send + Clone + 'static,
     {
         assert!(
             matches!(version, crate::data::Version::V2),
@@ -98,7 +100,7 @@ pub(crate) mod function {
             progress.lock().show_throughput(start);
         }
         let counts_range_by_pack_id = match mode {
-            Mode::PackCopyAndBaseObjects => {
+            Mode::PackCopyAndBaseObjects | Mode::PackCopyAndDeltaCompression => {
                 let mut progress = progress.add_child_with_id("sorting".into(), ProgressId::SortEntries.into());
                 progress.init(Some(counts.len()), gix_features::progress::count("counts"));
                 let start = std::time::Instant::now();
@@ -135,7 +137,26 @@ pub(crate) mod function {
             }
         };
 
+        let deltas = match mode {
+            Mode::PackCopyAndBaseObjects => Vec::new(),
+            Mode::PackCopyAndDeltaCompression => {
+                let progress = parking_lot::Mutex::new(
+                    progress.add_child_with_id("delta compression".into(), ProgressId::DeltaCompression.into()),
+                );
+                let num_unpacked = counts.partition_point(|e| e.entry_pack_location.is_none());
+                delta_search::search(
+                    &mut counts[..num_unpacked],
+                    &db,
+                    delta_window,
+                    delta_depth,
+                    thread_limit,
+                    &progress,
+                )
+            }
+        };
+
         let counts = Arc::new(counts);
+        let deltas = Arc::new(deltas);
         let progress = Arc::new(parking_lot::Mutex::new(progress));
         let chunks = util::ChunkRanges::new(chunk_size, counts.len());
 
@@ -155,14 +176,22 @@ pub(crate) mod function {
             },
             {
                 let counts = Arc::clone(&counts);
+                let deltas = Arc::clone(&deltas);
                 move |(chunk_id, chunk_range): (SequenceId, std::ops::Range<usize>), (buf, progress)| {
                     let mut out = Vec::new();
+                    let chunk_start = chunk_range.start;
                     let chunk = &counts[chunk_range];
                     let mut stats = Outcome::default();
                     let mut pack_offsets_to_id = None;
                     progress.init(Some(chunk.len()), gix_features::progress::count("objects"));
 
-                    for count in chunk.iter() {
+                    for (index, count) in (chunk_start..).zip(chunk.iter()) {
+                        if let Some(delta) = deltas.get(index).and_then(Option::as_ref) {
+                            stats.delta_compressed_objects += 1;
+                            out.push(output::Entry::from_delta(count, delta.base_index, &delta.data)?);
+                            progress.inc();
+                            continue;
+                        }
                         out.push(match count
                             .entry_pack_location
                             .as_ref()
@@ -244,6 +273,234 @@ pub(crate) mod function {
     }
 }
 
+mod delta_search {
+    use std::collections::VecDeque;
+
+    use gix_features::{parallel, progress::Progress};
+
+    use crate::data::{delta, output};
+
+    /// Objects smaller than this aren't worth delta-compressing, and are never useful as delta bases.
+    const MIN_OBJECT_SIZE: usize = 50;
+
+    /// The least amount of objects to search in one thread, as objects are never deltified against objects of other threads.
+    const MIN_OBJECTS_PER_THREAD: usize = 1_000;
+
+    /// A delta against an object that was already written.
+    pub struct Delta {
+        /// The index of the base object in the list of counts.
+        pub base_index: usize,
+        /// The delta instructions including their header.
+        pub data: Vec<u8>,
+    }
+
+    /// An object that is a delta base candidate for the objects after it, as long as it's in the window.
+    struct WindowEntry {
+        index: usize,
+        kind: gix_object::Kind,
+        data: Vec<u8>,
+        delta_index: delta::Index,
+        depth: usize,
+    }
+
+    /// Find deltas for the unpacked objects in `counts` by trying the `window` previous objects of the same kind as base,
+    /// with objects being sorted by kind, name and size beforehand, while producing delta chains of up to `max_depth`.
+    ///
+    /// Note that `counts` are reordered so that bases are placed before the objects that are deltified against them,
+    /// and that the returned list of deltas matches `counts` by index.
+    /// Objects whose header can't be found are put last, and never deltified.
+    ///
+    /// Only object headers are looked up for sorting, using up to `thread_limit` threads. The sorted list of objects is then
+    /// split into one contiguous chunk per thread to search them in parallel, which means that objects at the beginning of
+    /// a chunk can't use objects of the previous one as base.
+    /// Each object is read once it enters the window and dropped once it leaves it, so at most `window` objects
+    /// are held in memory per thread.
+    pub fn search<Find>(
+        counts: &mut [output::Count],
+        db: &Find,
+        window: usize,
+        max_depth: usize,
+        thread_limit: Option<usize>,
+        progress: &parking_lot::Mutex<impl Progress>,
+    ) -> Vec<Option<Delta>>
+    where
+        Find: crate::Find + gix_object::FindHeader + Send + Clone,
+    {
+        let mut deltas: Vec<_> = counts.iter().map(|_| None).collect();
+        if window == 0 || max_depth == 0 || counts.is_empty() {
+            return deltas;
+        }
+
+        let (chunk_size, header_thread_limit, _) =
+            parallel::optimize_chunk_size_and_thread_limit(50, Some(counts.len()), thread_limit, None);
+        let mut headers: Vec<Option<gix_object::Header>> = counts.iter().map(|_| None).collect();
+        parallel::in_parallel_if(
+            || counts.len() > MIN_OBJECTS_PER_THREAD,
+            counts.chunks(chunk_size).zip(headers.chunks_mut(chunk_size)),
+            header_thread_limit,
+            |_n| (),
+            {
+                let db = db.clone();
+                move |(counts, headers), _state| {
+                    for (count, header) in counts.iter().zip(headers) {
+                        *header = db.try_header(&count.id).ok().flatten();
+                    }
+                    Ok::<_, ()>(())
+                }
+            },
+            parallel::reduce::IdentityWithResult::<(), ()>::default(),
+        )
+        .expect("infallible - objects without header are never deltified");
+
+        let mut order: Vec<_> = (0..counts.len()).filter(|index| headers[*index].is_some()).collect();
+        let num_candidates = order.len();
+        order.sort_by(|lhs, rhs| {
+            let (lhs_header, rhs_header) = (
+                headers[*lhs].as_ref().expect("candidate"),
+                headers[*rhs].as_ref().expect("candidate"),
+            );
+            lhs_header
+                .kind
+                .cmp(&rhs_header.kind)
+                .then(counts[*lhs].name_hash.cmp(&counts[*rhs].name_hash))
+                .then(rhs_header.size.cmp(&lhs_header.size))
+        });
+        order.extend((0..counts.len()).filter(|index| headers[*index].is_none()));
+        let reordered: Vec<_> = order.iter().map(|index| counts[*index].clone()).collect();
+        counts.clone_from_slice(&reordered);
+        drop(reordered);
+        let headers: Vec<_> = order
+            .iter()
+            .take(num_candidates)
+            .map(|index| headers[*index].expect("candidate"))
+            .collect();
+        drop(order);
+
+        progress
+            .lock()
+            .init(Some(num_candidates), gix_features::progress::count("objects"));
+        let hash_len = counts[0].id.as_slice().len();
+        let num_threads = parallel::num_threads(thread_limit);
+        let chunk_size = ((num_candidates + num_threads - 1) / num_threads)
+            .max(MIN_OBJECTS_PER_THREAD)
+            .max(1);
+        parallel::in_parallel_if(
+            || num_candidates > MIN_OBJECTS_PER_THREAD,
+            counts[..num_candidates]
+                .chunks(chunk_size)
+                .zip(headers.chunks(chunk_size))
+                .zip(deltas.chunks_mut(chunk_size))
+                .enumerate(),
+            thread_limit,
+            |_n| (),
+            {
+                let db = db.clone();
+                move |(chunk_index, ((counts, headers), deltas)), _state| {
+                    find_deltas(
+                        chunk_index * chunk_size,
+                        counts,
+                        headers,
+                        deltas,
+                        &db,
+                        window,
+                        max_depth,
+                        hash_len,
+                        progress,
+                    );
+                    Ok::<_, ()>(())
+                }
+            },
+            parallel::reduce::IdentityWithResult::<(), ()>::default(),
+        )
+        .expect("infallible");
+        deltas
+    }
+
+    /// Search deltas for all objects in `counts` whose index in the list of all counts starts at `start`, storing them in
+    /// the respective slot of `deltas`. Objects are read from `db` as they enter the window, unless their size in
+    /// `headers` makes them too small to be useful.
+    #[allow(clippy::too_many_arguments)]
+    fn find_deltas(
+        start: usize,
+        counts: &[output::Count],
+        headers: &[gix_object::Header],
+        deltas: &mut [Option<Delta>],
+        db: &impl crate::Find,
+        window: usize,
+        max_depth: usize,
+        hash_len: usize,
+        progress: &parking_lot::Mutex<impl Progress>,
+    ) {
+        let mut window_entries = VecDeque::<WindowEntry>::with_capacity(window);
+        let (mut delta_buf, mut best_buf) = (Vec::new(), Vec::new());
+        for (index, ((count, header), delta)) in (start..).zip(counts.iter().zip(headers).zip(deltas)) {
+            progress.lock().inc();
+            if (header.size as usize) < MIN_OBJECT_SIZE {
+                continue;
+            }
+            let Some(data) = read_object(db, &count.id) else {
+                continue;
+            };
+            let kind = header.kind;
+            let mut best: Option<(usize, usize)> = None;
+            for base in window_entries.iter().rev() {
+                if base.kind != kind || base.depth >= max_depth || base.data.len() < data.len() / 32 {
+                    continue;
+                }
+                let mut max_size = (data.len() / 2).saturating_sub(hash_len) * (max_depth - base.depth) / max_depth;
+                if best.is_some() {
+                    max_size = max_size.min(best_buf.len() - 1);
+                }
+                if max_size == 0 {
+                    continue;
+                }
+                if base.delta_index.encode(&base.data, &data, max_size, &mut delta_buf) {
+                    std::mem::swap(&mut best_buf, &mut delta_buf);
+                    best = Some((base.index, base.depth));
+                }
+            }
+
+            let depth = match best {
+                Some((base_index, base_depth)) => {
+                    *delta = Some(Delta {
+                        base_index,
+                        data: best_buf.clone(),
+                    });
+                    base_depth + 1
+                }
+                None => 0,
+            };
+            if window_entries.len() == window {
+                window_entries.pop_front();
+            }
+            window_entries.push_back(WindowEntry {
+                index,
+                kind,
+                delta_index: delta::Index::new(&data),
+                data,
+                depth,
+            });
+        }
+    }
+
+    /// Read the object with `id` from `db`, taking ownership of the buffer it was decoded into instead of copying its data.
+    fn read_object(db: &impl crate::Find, id: &gix_hash::oid) -> Option<Vec<u8>> {
+        let mut buf = Vec::new();
+        let (data_start, data_len) = {
+            let (obj, _location) = db.try_find(id, &mut buf).ok().flatten()?;
+            (obj.data.as_ptr() as usize, obj.data.len())
+        };
+        match data_start.checked_sub(buf.as_ptr() as usize) {
+            Some(offset) if offset + data_len <= buf.len() => {
+                buf.truncate(offset + data_len);
+                buf.drain(..offset);
+            }
+            _ => buf.clear(),
+        }
+        Some(buf)
+    }
+}
+
 mod util {
     #[derive(Clone)]
     pub struct ChunkRanges {
@@ -335,6 +592,8 @@ mod types {
         pub objects_copied_from_pack: usize,
         /// The amount of objects that ref to their base as ref-delta, an indication for a thin back being created.
         pub ref_delta_objects: usize,
+        /// The amount of objects that weren't in a pack and were delta-compressed against another object in the pack.
+        pub delta_compressed_objects: usize,
     }
 
     impl Outcome {
@@ -345,12 +604,14 @@ mod types {
                 missing_objects,
                 objects_copied_from_pack,
                 ref_delta_objects,
+                delta_compressed_objects,
             }: Self,
         ) {
             self.decoded_and_recompressed_objects += decoded_objects;
             self.missing_objects += missing_objects;
             self.objects_copied_from_pack += objects_copied_from_pack;
             self.ref_delta_objects += ref_delta_objects;
+            self.delta_compressed_objects += delta_compressed_objects;
         }
     }
 
@@ -363,6 +624,10 @@ mod types {
         /// from existing pack compression and spending the smallest possible time on compressing unpacked objects at
         /// the cost of bandwidth.
         PackCopyAndBaseObjects,
+        /// Copy base objects and deltas from packs like [`PackCopyAndBaseObjects`](Mode::PackCopyAndBaseObjects), but try to
+        /// delta-compress all non-packed objects against each other, as configured by [`Options::delta_window`] and
+        /// [`Options::delta_depth`]. This produces much smaller packs at the cost of time spent looking for good delta bases.
+        PackCopyAndDeltaCompression,
     }
 
     /// Configuration options for the pack generation functions provided in [`iter_from_counts()`][crate::data::output::entry::iter_from_counts()].
@@ -385,6 +650,14 @@ mod types {
         pub chunk_size: usize,
         /// The pack data version to produce for each entry
         pub version: crate::data::Version,
+        /// The amount of previous objects to try as delta base for each object, like `pack.window` in `git`.
+        ///
+        /// Only used in [`Mode::PackCopyAndDeltaCompression`].
+        pub delta_window: usize,
+        /// The maximum length of a chain of deltas produced while delta-compressing objects, like `pack.depth` in `git`.
+        ///
+        /// Only used in [`Mode::PackCopyAndDeltaCompression`].
+        pub delta_depth: usize,
     }
 
     impl Default for Options {
@@ -395,6 +668,8 @@ mod types {
                 allow_thin_pack: false,
                 chunk_size: 10,
                 version: Default::default(),
+                delta_window: 10,
+                delta_depth: 50,
             }
         }
     }
@@ -418,6 +693,8 @@ mod types {
         ResolveCounts,
         /// Layout pack entries for placement into a pack (by pack-id and by offset).
         SortEntries,
+        /// Search delta bases for objects that aren't yet in a pack.
+        DeltaCompression,
     }
 
     impl From<ProgressId> for gix_features::progress::Id {
@@ -425,6 +702,7 @@ mod types {
             match v {
                 ProgressId::ResolveCounts => *b"ECRC",
                 ProgressId::SortEntries => *b"ECSE",
+                ProgressId::DeltaCompression => *b"ECDC",
             }
         }
     }
@@ -15,6 +15,11 @@ pub struct Count {
     pub id: ObjectId,
     /// A way to locate a pack entry in the object database, only available if the object is in a pack.
     pub entry_pack_location: count::PackLocation,
+    /// A hash of the name of the object in the tree it was found in, or `0` if it's unknown, like computed by
+    /// [`count::name_hash()`].
+    ///
+    /// Objects with similar names are more likely to be good delta bases for each other.
+    pub name_hash: u32,
 }
 
 /// An entry to be written to a file.
@@ -162,7 +162,14 @@ impl index::File {
     /// Return a vector of ascending offsets into our respective pack data file.
     ///
     /// Useful to control an iteration over all pack entries in a cache-friendly way.
+    /// If a [reverse index](index::File::reverse_index()) is present, no sorting is required.
     pub fn sorted_offsets(&self) -> Vec<data::Offset> {
+        if let Some(reverse_index) = &self.reverse_index {
+            return reverse_index
+                .iter()
+                .map(|index| self.pack_offset_at_index(index))
+                .collect();
+        }
         let mut ofs: Vec<_> = match self.version {
             index::Version::V1 => self.iter().map(|e| e.pack_offset).collect(),
             index::Version::V2 => {
@@ -179,6 +186,28 @@ impl index::File {
         ofs
     }
 
+    /// Return the index of the entry whose data starts at `pack_offset` in the pack, or `None` if there is no such entry.
+    ///
+    /// This is fast if a [reverse index](index::File::reverse_index()) is present, and requires a scan of all entries otherwise.
+    pub fn lookup_pack_offset(&self, pack_offset: data::Offset) -> Option<EntryIndex> {
+        match &self.reverse_index {
+            Some(reverse_index) => {
+                let (mut lo, mut hi) = (0, reverse_index.num_objects());
+                while lo < hi {
+                    let mid = lo + (hi - lo) / 2;
+                    let index = reverse_index.index_at(mid)?;
+                    match self.pack_offset_at_index(index).cmp(&pack_offset) {
+                        std::cmp::Ordering::Less => lo = mid + 1,
+                        std::cmp::Ordering::Greater => hi = mid,
+                        std::cmp::Ordering::Equal => return Some(index),
+                    }
+                }
+                None
+            }
+            None => (0..self.num_objects).find(|index| self.pack_offset_at_index(*index) == pack_offset),
+        }
+    }
+
     #[inline]
     fn offset_crc32_v2(&self) -> usize {
         V2_HEADER_SIZE + self.num_objects as usize * self.hash_len
@@ -70,7 +70,7 @@ impl index::File {
 
             (kind, fan, num_objects)
         };
-        Ok(index::File {
+        let mut file = index::File {
             data,
             path: path.to_owned(),
             version: kind,
@@ -78,7 +78,22 @@ impl index::File {
             fan,
             hash_len,
             object_hash,
-        })
+            reverse_index: None,
+        };
+        file.reverse_index = file.open_reverse_index();
+        Ok(file)
+    }
+}
+
+impl index::File {
+    /// Open the `.rev` file next to our `.idx` file, ignoring it unless it belongs to the same pack.
+    fn open_reverse_index(&self) -> Option<index::reverse::File> {
+        if self.path.extension()? != "idx" {
+            return None;
+        }
+        let reverse_index = index::reverse::File::at(self.path.with_extension("rev"), self.object_hash).ok()?;
+        (reverse_index.num_objects() == self.num_objects && reverse_index.pack_checksum() == self.pack_checksum())
+            .then_some(reverse_index)
     }
 }
 
@@ -86,10 +86,14 @@ pub enum Version {
 }
 
 impl Version {
-    /// The kind of hash to produce to be compatible to this kind of index
-    pub fn hash(&self) -> gix_hash::Kind {
+    /// The kind of hash to produce to be compatible to this kind of index, in a repository whose objects
+    /// are hashed with `object_hash`.
+    ///
+    /// `V1` only supports `Sha1`, whereas `V2` is used with the object hash of the repository.
+    pub fn hash(&self, object_hash: gix_hash::Kind) -> gix_hash::Kind {
         match self {
-            Version::V1 | Version::V2 => gix_hash::Kind::Sha1,
+            Version::V1 => gix_hash::Kind::Sha1,
+            Version::V2 => object_hash,
         }
     }
 }
@@ -112,6 +116,8 @@ pub struct File {
     fan: [u32; FAN_LEN],
     hash_len: usize,
     object_hash: gix_hash::Kind,
+    /// The reverse index stored next to the index file, if present and valid.
+    reverse_index: Option<reverse::File>,
 }
 
 /// Basic file information
@@ -132,6 +138,10 @@ impl File {
     pub fn object_hash(&self) -> gix_hash::Kind {
         self.object_hash
     }
+    /// The reverse index that was found next to the index file, if it exists and belongs to our pack.
+    pub fn reverse_index(&self) -> Option<&reverse::File> {
+        self.reverse_index.as_ref()
+    }
 }
 
 const V2_SIGNATURE: &[u8] = b"\xfftOc";
@@ -145,6 +155,12 @@ pub use access::Entry;
 pub(crate) mod encode;
 ///
 #[allow(clippy::empty_docs)]
+pub mod mtimes;
+///
+#[allow(clippy::empty_docs)]
+pub mod reverse;
+///
+#[allow(clippy::empty_docs)]
 pub mod traverse;
 mod util;
 ///
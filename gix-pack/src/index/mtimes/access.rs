@@ -0,0 +1,49 @@
+use std::path::Path;
+
+use crate::index::{
+    mtimes::{File, HEADER_LEN},
+    EntryIndex,
+};
+
+/// Access
+impl File {
+    /// The path from which the file was loaded.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The kind of hash used for the checksums of this file.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// The amount of objects in the pack.
+    pub fn num_objects(&self) -> u32 {
+        self.num_objects
+    }
+
+    /// Return the modification time of the object at `index` in the pack index, in seconds since the unix epoch,
+    /// or `None` if there is no such object.
+    pub fn mtime_at(&self, index: EntryIndex) -> Option<u32> {
+        (index < self.num_objects).then(|| crate::read_u32(&self.data[HEADER_LEN + index as usize * 4..][..4]))
+    }
+
+    /// Return an iterator over the modification times of all objects, in the order of the pack index.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = u32> + '_ {
+        self.data[HEADER_LEN..][..self.num_objects as usize * 4]
+            .chunks_exact(4)
+            .map(crate::read_u32)
+    }
+
+    /// The checksum of the pack this file belongs to.
+    pub fn pack_checksum(&self) -> gix_hash::ObjectId {
+        let hash_len = self.object_hash.len_in_bytes();
+        gix_hash::ObjectId::from_bytes_or_panic(&self.data[self.data.len() - hash_len * 2..][..hash_len])
+    }
+
+    /// The checksum of this file, stored in its trailing bytes.
+    pub fn checksum(&self) -> gix_hash::ObjectId {
+        let hash_len = self.object_hash.len_in_bytes();
+        gix_hash::ObjectId::from_bytes_or_panic(&self.data[self.data.len() - hash_len..])
+    }
+}
@@ -0,0 +1,29 @@
+//! The modification times of the objects of a cruft pack, stored next to it as `pack-<hash>.mtimes`.
+//!
+//! Cruft packs hold unreachable objects that aren't old enough to be deleted yet. As all of them share the modification
+//! time of the pack itself, the time at which each object was last written is kept separately, ordered like the objects
+//! in the pack index.
+use std::path::PathBuf;
+
+use memmap2::Mmap;
+
+/// A `.mtimes` file, memory-mapped for fast access.
+pub struct File {
+    data: Mmap,
+    path: PathBuf,
+    object_hash: gix_hash::Kind,
+    num_objects: u32,
+}
+
+const SIGNATURE: &[u8] = b"MTME";
+const HEADER_LEN: usize = 4 /* signature */ + 4 /* version */ + 4 /* hash id */;
+
+mod access;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod init;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write;
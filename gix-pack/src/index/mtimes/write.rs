@@ -0,0 +1,33 @@
+use std::io::Write;
+
+use crate::index::{
+    self,
+    mtimes::{File, SIGNATURE},
+};
+
+/// Writing
+impl File {
+    /// Write the modification time of each object in `index` as returned by `mtime_of`, in seconds since the unix epoch,
+    /// to `out` and return the checksum of the written file.
+    ///
+    /// The file should be written next to the pack as `pack-<hash>.mtimes`, which marks it as cruft pack.
+    pub fn write_from_index(
+        index: &index::File,
+        mtime_of: &mut dyn FnMut(&gix_hash::oid) -> u32,
+        out: &mut dyn std::io::Write,
+    ) -> std::io::Result<gix_hash::ObjectId> {
+        let object_hash = index.object_hash();
+        let mut out = gix_features::hash::Write::new(out, object_hash);
+        out.write_all(SIGNATURE)?;
+        out.write_all(&1u32.to_be_bytes())?;
+        out.write_all(&(object_hash as u32).to_be_bytes())?;
+        for entry_index in 0..index.num_objects() {
+            out.write_all(&mtime_of(index.oid_at_index(entry_index)).to_be_bytes())?;
+        }
+        out.write_all(index.pack_checksum().as_slice())?;
+
+        let checksum = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        Ok(checksum)
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use crate::index::{
+    reverse::{File, HEADER_LEN},
+    EntryIndex,
+};
+
+/// Access
+impl File {
+    /// The path from which the reverse index was loaded.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The kind of hash used for the checksums of this file.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// The amount of objects in the pack.
+    pub fn num_objects(&self) -> u32 {
+        self.num_objects
+    }
+
+    /// Return the index position of the object that is the `pack_position`th object in the pack,
+    /// or `None` if there is no such object.
+    pub fn index_at(&self, pack_position: u32) -> Option<EntryIndex> {
+        (pack_position < self.num_objects)
+            .then(|| crate::read_u32(&self.data[HEADER_LEN + pack_position as usize * 4..][..4]))
+    }
+
+    /// Return an iterator over the index positions of all objects, in the order they appear in the pack.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = EntryIndex> + '_ {
+        self.data[HEADER_LEN..][..self.num_objects as usize * 4]
+            .chunks_exact(4)
+            .map(crate::read_u32)
+    }
+
+    /// The checksum of the pack this reverse index belongs to.
+    pub fn pack_checksum(&self) -> gix_hash::ObjectId {
+        let hash_len = self.object_hash.len_in_bytes();
+        gix_hash::ObjectId::from_bytes_or_panic(&self.data[self.data.len() - hash_len * 2..][..hash_len])
+    }
+
+    /// The checksum of this file, stored in its trailing bytes.
+    pub fn checksum(&self) -> gix_hash::ObjectId {
+        let hash_len = self.object_hash.len_in_bytes();
+        gix_hash::ObjectId::from_bytes_or_panic(&self.data[self.data.len() - hash_len..])
+    }
+}
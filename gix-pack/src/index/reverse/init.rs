@@ -0,0 +1,72 @@
+use std::path::Path;
+
+use crate::index::reverse::{File, HEADER_LEN, SIGNATURE};
+
+mod error {
+    /// The error returned by [File::at()][super::File::at()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not open reverse index file at '{path}'")]
+        Io {
+            source: std::io::Error,
+            path: std::path::PathBuf,
+        },
+        #[error("{message}")]
+        Corrupt { message: &'static str },
+        #[error("Unsupported reverse index version: {version}")]
+        UnsupportedVersion { version: u32 },
+        #[error("The reverse index uses hash id {actual}, but {expected:?} was expected")]
+        ObjectHashMismatch { actual: u32, expected: gix_hash::Kind },
+    }
+}
+pub use error::Error;
+
+/// Initialization
+impl File {
+    /// Open the reverse index file at `path`, which belongs to a pack with objects of `object_hash`.
+    pub fn at(path: impl AsRef<Path>, object_hash: gix_hash::Kind) -> Result<Self, Error> {
+        Self::at_inner(path.as_ref(), object_hash)
+    }
+
+    fn at_inner(path: &Path, object_hash: gix_hash::Kind) -> Result<Self, Error> {
+        let data = crate::mmap::read_only(path).map_err(|source| Error::Io {
+            source,
+            path: path.to_owned(),
+        })?;
+        let trailer_len = object_hash.len_in_bytes() * 2;
+        if data.len() < HEADER_LEN + trailer_len {
+            return Err(Error::Corrupt {
+                message: "reverse index file is truncated and too short",
+            });
+        }
+        if &data[..4] != SIGNATURE {
+            return Err(Error::Corrupt {
+                message: "Invalid signature",
+            });
+        }
+        let version = crate::read_u32(&data[4..8]);
+        if version != 1 {
+            return Err(Error::UnsupportedVersion { version });
+        }
+        let hash_id = crate::read_u32(&data[8..12]);
+        if hash_id != object_hash as u32 {
+            return Err(Error::ObjectHashMismatch {
+                actual: hash_id,
+                expected: object_hash,
+            });
+        }
+        let table_len = data.len() - HEADER_LEN - trailer_len;
+        if table_len % 4 != 0 {
+            return Err(Error::Corrupt {
+                message: "the table of index positions has an invalid size",
+            });
+        }
+        Ok(File {
+            data,
+            path: path.to_owned(),
+            object_hash,
+            num_objects: (table_len / 4) as u32,
+        })
+    }
+}
@@ -0,0 +1,28 @@
+//! The on-disk reverse index of a pack, stored next to it as `pack-<hash>.rev`.
+//!
+//! It lists the index position of each object in the order in which they appear in the pack, which allows
+//! to map pack offsets to index positions without sorting all entries by offset first.
+use std::path::PathBuf;
+
+use memmap2::Mmap;
+
+/// A `.rev` file, memory-mapped for fast access.
+pub struct File {
+    data: Mmap,
+    path: PathBuf,
+    object_hash: gix_hash::Kind,
+    num_objects: u32,
+}
+
+const SIGNATURE: &[u8] = b"RIDX";
+const HEADER_LEN: usize = 4 /* signature */ + 4 /* version */ + 4 /* hash id */;
+
+mod access;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod init;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write;
@@ -0,0 +1,33 @@
+use std::io::Write;
+
+use crate::index::{
+    self,
+    reverse::{File, SIGNATURE},
+};
+
+/// Writing
+impl File {
+    /// Write the reverse index for `index` to `out` and return its checksum.
+    ///
+    /// The file should be written next to the pack as `pack-<hash>.rev`.
+    pub fn write_from_index(index: &index::File, out: &mut dyn std::io::Write) -> std::io::Result<gix_hash::ObjectId> {
+        let object_hash = index.object_hash();
+        let mut out = gix_features::hash::Write::new(out, object_hash);
+        out.write_all(SIGNATURE)?;
+        out.write_all(&1u32.to_be_bytes())?;
+        out.write_all(&(object_hash as u32).to_be_bytes())?;
+
+        let mut by_offset: Vec<_> = (0..index.num_objects())
+            .map(|entry_index| (index.pack_offset_at_index(entry_index), entry_index))
+            .collect();
+        by_offset.sort_unstable();
+        for (_pack_offset, entry_index) in by_offset {
+            out.write_all(&entry_index.to_be_bytes())?;
+        }
+        out.write_all(index.pack_checksum().as_slice())?;
+
+        let checksum = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        Ok(checksum)
+    }
+}
@@ -10,11 +10,25 @@ pub(crate) fn index_entries_sorted_by_offset_ascending(
     let start = Instant::now();
 
     let mut v = Vec::with_capacity(idx.num_objects as usize);
-    for entry in idx.iter() {
-        v.push(entry);
-        progress.inc();
+    match idx.reverse_index() {
+        Some(reverse_index) => {
+            for index in reverse_index.iter() {
+                v.push(crate::index::Entry {
+                    oid: idx.oid_at_index(index).to_owned(),
+                    pack_offset: idx.pack_offset_at_index(index),
+                    crc32: idx.crc32_at_index(index),
+                });
+                progress.inc();
+            }
+        }
+        None => {
+            for entry in idx.iter() {
+                v.push(entry);
+                progress.inc();
+            }
+            v.sort_by_key(|e| e.pack_offset);
+        }
     }
-    v.sort_by_key(|e| e.pack_offset);
 
     progress.show_throughput(start);
     v
@@ -53,6 +53,10 @@ impl File {
     pub fn checksum(&self) -> gix_hash::ObjectId {
         gix_hash::ObjectId::from_bytes_or_panic(&self.data[self.data.len() - self.hash_len..])
     }
+    /// Returns `true` if this file contains a reverse index, which is required to use multi-pack bitmaps.
+    pub fn has_reverse_index(&self) -> bool {
+        self.reverse_index_ofs.is_some()
+    }
     /// Return all names of index files (`*.idx`) whose objects we contain.
     ///
     /// The corresponding pack can be found by replacing the `.idx` extension with `.pack`.
@@ -70,6 +74,17 @@ impl File {
         gix_hash::oid::from_bytes_unchecked(&self.data[start..][..self.hash_len])
     }
 
+    /// Return the index of the object at `position` in pseudo-pack order, which ranges from 0 to [File::num_objects()],
+    /// or `None` if this file doesn't have a reverse index.
+    ///
+    /// In pseudo-pack order, objects are ordered as if all packs were concatenated, starting with the preferred pack,
+    /// and is used to assign bit positions to objects in multi-pack bitmaps.
+    pub fn index_at_pseudo_pack_position(&self, position: u32) -> Option<EntryIndex> {
+        debug_assert!(position < self.num_objects, "position out of bounds");
+        let start = self.reverse_index_ofs? + position as usize * 4;
+        Some(crate::read_u32(&self.data[start..][..4]))
+    }
+
     /// Given a `prefix`, find an object that matches it uniquely within this index and return `Some(Ok(entry_index))`.
     /// If there is more than one object matching the object `Some(Err(())` is returned.
     ///
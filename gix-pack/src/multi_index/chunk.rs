@@ -170,6 +170,43 @@ pub mod lookup {
     }
 }
 
+/// Information about the reverse index, which lists all objects in pseudo-pack order.
+pub mod reverse_index {
+    use std::ops::Range;
+
+    use crate::multi_index;
+
+    /// The id uniquely identifying the reverse index.
+    pub const ID: gix_chunk::Id = *b"RIDX";
+
+    /// Return the amount of bytes needed to store the pseudo-pack order of `entries`.
+    pub fn storage_size(entries: usize) -> u64 {
+        (entries * 4) as u64
+    }
+
+    /// Write the index positions of `sorted_entries` in pseudo-pack order, i.e. ordered by the pack they are in,
+    /// and by their offset within the pack.
+    pub(crate) fn write(
+        sorted_entries: &[multi_index::write::Entry],
+        out: &mut dyn std::io::Write,
+    ) -> std::io::Result<()> {
+        let mut positions: Vec<_> = (0..sorted_entries.len() as u32).collect();
+        positions.sort_by_key(|position| {
+            let entry = &sorted_entries[*position as usize];
+            (entry.pack_index, entry.pack_offset)
+        });
+        for position in positions {
+            out.write_all(&position.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Returns true if the `offset` range seems to match the size required for `num_objects`.
+    pub fn is_valid(offset: &Range<usize>, num_objects: u32) -> bool {
+        (offset.end - offset.start) == num_objects as usize * 4
+    }
+}
+
 /// Information about the offsets table.
 pub mod offsets {
     use std::ops::Range;
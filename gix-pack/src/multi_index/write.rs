@@ -36,6 +36,8 @@ pub(crate) struct Entry {
 pub struct Options {
     /// The kind of hash to use for objects and to expect in the input files.
     pub object_hash: gix_hash::Kind,
+    /// If `true`, write the reverse index chunk to list all objects in pseudo-pack order, as needed by bitmaps.
+    pub reverse_index: bool,
 }
 
 /// The result of [`multi_index::File::write_from_index_paths()`].
@@ -81,7 +83,10 @@ impl multi_index::File {
         out: &mut dyn std::io::Write,
         progress: &mut dyn DynNestedProgress,
         should_interrupt: &AtomicBool,
-        Options { object_hash }: Options,
+        Options {
+            object_hash,
+            reverse_index,
+        }: Options,
     ) -> Result<Outcome, Error> {
         let out = gix_features::hash::Write::new(out, object_hash);
         let (index_paths_sorted, index_filenames_sorted) = {
@@ -164,6 +169,13 @@ impl multi_index::File {
             );
         }
 
+        if reverse_index {
+            cf.plan_chunk(
+                multi_index::chunk::reverse_index::ID,
+                multi_index::chunk::reverse_index::storage_size(entries.len()),
+            );
+        }
+
         let mut write_progress =
             progress.add_child_with_id("Writing multi-index".into(), ProgressId::BytesWritten.into());
         let write_start = Instant::now();
@@ -203,6 +215,9 @@ impl multi_index::File {
                         num_large_offsets.expect("available if planned"),
                         &mut chunk_write,
                     )?,
+                    multi_index::chunk::reverse_index::ID => {
+                        multi_index::chunk::reverse_index::write(&entries, &mut chunk_write)?
+                    }
                     unknown => unreachable!("BUG: forgot to implement chunk {:?}", std::str::from_utf8(&unknown)),
                 }
                 progress.inc();
@@ -0,0 +1,49 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q pack
+(cd pack
+  git checkout -q -b main
+
+  for round in $(seq 150); do
+    if [ $(( round % 20 )) = 0 ]; then
+      git checkout -q -b side
+      echo "side $round" > side.txt
+      git add .
+      git commit -qm "side $round"
+      git checkout -q main
+      git merge -q --no-ff -m "merge $round" side
+      git branch -q -D side
+    fi
+    dir="dir$(( round % 7 ))"
+    mkdir -p "$dir"
+    echo "$round" > "$dir/file$(( round % 13 )).txt"
+    git add .
+    git commit -qm "$round"
+  done
+  git tag -m "a tag object" v1
+
+  git -c pack.writeReverseIndex=true repack -adbq
+)
+
+git clone -q --no-local --bare pack lookup-table
+(cd lookup-table
+  git -c pack.writeBitmapLookupTable=true repack -adbq
+)
+
+git clone -q --no-local pack multi-pack
+(cd multi-pack
+  git repack -adq
+  for round in $(seq 5); do
+    echo "$round" > "new-$round.txt"
+    git add .
+    git commit -qm "new $round"
+  done
+  git repack -dq
+  git multi-pack-index write --bitmap
+)
+
+git clone -q --no-local --bare pack no-bitmap
+(cd no-bitmap
+  git -c repack.writeBitmaps=false repack -adq
+)
@@ -0,0 +1,19 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q --object-format=sha256 pack
+(cd pack
+  git checkout -q -b main
+
+  for round in $(seq 150); do
+    dir="dir$(( round % 7 ))"
+    mkdir -p "$dir"
+    echo "$round" > "$dir/file$(( round % 13 )).txt"
+    git add .
+    git commit -qm "$round"
+  done
+  git tag -m "a tag object" v1
+
+  git repack -adbq
+)
+
@@ -0,0 +1,22 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+for round in $(seq 5); do
+  seq "$round" > "file-$round"
+  git add .
+  git commit -qm "$round"
+done
+
+git checkout -q -b forgotten
+echo "to be forgotten" > forgotten
+git add forgotten
+git commit -qm "forgotten"
+git checkout -q main
+git branch -q -D forgotten
+echo "never added" | git hash-object -w --stdin >/dev/null
+
+git reflog expire --expire-unreachable=now --all
+git repack -q --cruft -d
@@ -0,0 +1,128 @@
+use std::sync::atomic::AtomicBool;
+
+use gix_features::progress;
+use gix_pack::{bitmap, data::output};
+
+use bstr::ByteSlice;
+use gix_hash::ObjectId;
+
+use crate::pack::bitmap::{
+    db, db_with_hash, fixture, pack_index, pack_index_with_hash, pack_with_extension, rev_parse, sha256_fixture,
+};
+
+fn rev_list_objects(repo: &std::path::Path, range: &str) -> Vec<ObjectId> {
+    let out = std::process::Command::new("git")
+        .args(["rev-list", "--objects", range])
+        .current_dir(repo)
+        .output()
+        .expect("git is installed");
+    assert!(out.status.success());
+    let mut ids: Vec<_> = out
+        .stdout
+        .lines()
+        .map(|line| ObjectId::from_hex(line.split_str(" ").next().expect("id")).expect("valid hex"))
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn objects_with_bitmap() -> crate::Result {
+    let repo = fixture("pack");
+    let index = pack_index(&repo);
+    let file = bitmap::File::at(pack_with_extension(&repo, "bitmap"), gix_hash::Kind::Sha1)?;
+    let order = bitmap::Order::from_index(&index);
+    let lookup = bitmap::Lookup::new(&file, &index, &order)?;
+    let db = db(&repo)?;
+
+    let count =
+        |wants: &[&str], haves: &[&str]| -> crate::Result<(Vec<output::Count>, output::count::objects::Outcome)> {
+            let wants: Vec<_> = wants.iter().map(|spec| rev_parse(&repo, spec)).collect();
+            let haves: Vec<_> = haves.iter().map(|spec| rev_parse(&repo, spec)).collect();
+            Ok(output::count::objects_with_bitmap(
+                &db,
+                &lookup,
+                &wants,
+                &haves,
+                &progress::Discard,
+                &AtomicBool::default(),
+            )?)
+        };
+
+    let (counts, outcome) = count(&["v1"], &[])?;
+    assert_eq!(counts.len(), index.num_objects() as usize, "the tag reaches everything");
+    assert_eq!(
+        outcome.decoded_objects, 1,
+        "only the tag itself is read, as its target commit has a bitmap"
+    );
+    assert!(counts
+        .iter()
+        .all(|count| count.entry_pack_location == output::count::PackLocation::NotLookedUp));
+    let side = rev_parse(&repo, "HEAD:side.txt");
+    assert_eq!(
+        counts.iter().find(|count| count.id == side).expect("present").name_hash,
+        output::count::name_hash(b"side.txt"),
+        "name-hashes are taken from the bitmap file"
+    );
+
+    let (counts, _) = count(&["HEAD"], &["HEAD"])?;
+    assert!(counts.is_empty(), "nothing is wanted that isn't already present");
+
+    let (counts, outcome) = count(&["HEAD"], &["HEAD~1"])?;
+    assert_eq!(
+        counts.len(),
+        4,
+        "a new commit, its new root tree, the changed directory and blob"
+    );
+    assert!(outcome.decoded_objects > 0, "HEAD~1 has no bitmap and is traversed");
+
+    let (counts, _) = count(&["HEAD~2"], &["HEAD~30"])?;
+    let mut actual: Vec<_> = counts.into_iter().map(|count| count.id).collect();
+    actual.sort();
+    assert_eq!(
+        actual,
+        rev_list_objects(&repo, "HEAD~30..HEAD~2"),
+        "the traversal of commits without bitmaps finds the same objects as git"
+    );
+    Ok(())
+}
+
+#[test]
+fn sha256_objects_with_bitmap() -> crate::Result {
+    let object_hash = gix_hash::Kind::Sha256;
+    let repo = sha256_fixture("pack");
+    let index = pack_index_with_hash(&repo, object_hash);
+    let file = bitmap::File::at(pack_with_extension(&repo, "bitmap"), object_hash)?;
+    let order = bitmap::Order::from_index(&index);
+    let lookup = bitmap::Lookup::new(&file, &index, &order)?;
+    let db = db_with_hash(&repo, object_hash)?;
+
+    let count = |want: &str, have: &str| -> crate::Result<(Vec<ObjectId>, output::count::objects::Outcome)> {
+        let (counts, outcome) = output::count::objects_with_bitmap(
+            &db,
+            &lookup,
+            &[rev_parse(&repo, want)],
+            &[rev_parse(&repo, have)],
+            &progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        let mut ids: Vec<_> = counts.into_iter().map(|count| count.id).collect();
+        ids.sort();
+        Ok((ids, outcome))
+    };
+
+    for (want, have) in [("HEAD", "HEAD~1"), ("HEAD~2", "HEAD~30")] {
+        let (actual, _) = count(want, have)?;
+        assert_eq!(
+            actual,
+            rev_list_objects(&repo, &format!("{have}..{want}")),
+            "trees with SHA-256 ids are traversed just like git does"
+        );
+    }
+    let (_, outcome) = count("HEAD", "HEAD~1")?;
+    assert!(
+        outcome.decoded_objects > 0,
+        "HEAD~1 has no bitmap and its trees are decoded"
+    );
+    Ok(())
+}
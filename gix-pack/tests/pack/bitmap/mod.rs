@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+
+use bstr::ByteSlice;
+use gix_hash::ObjectId;
+use gix_pack::bitmap;
+
+fn fixture(name: &str) -> PathBuf {
+    crate::scripted_fixture_read_only("make_pack_gen_repo_bitmap.sh")
+        .expect("fixture exists")
+        .join(name)
+}
+
+fn sha256_fixture(name: &str) -> PathBuf {
+    crate::scripted_fixture_read_only("make_pack_gen_repo_bitmap_sha256.sh")
+        .expect("fixture exists")
+        .join(name)
+}
+
+fn objects_dir(repo: &Path) -> PathBuf {
+    let git_dir = repo.join(".git");
+    if git_dir.is_dir() { git_dir } else { repo.to_owned() }.join("objects")
+}
+
+fn pack_with_extension(repo: &Path, extension: &str) -> PathBuf {
+    std::fs::read_dir(objects_dir(repo).join("pack"))
+        .expect("pack directory exists")
+        .map(|entry| entry.expect("readable").path())
+        .find(|path| matches!(path.extension(), Some(ext) if ext == extension))
+        .expect("a file with the extension is present")
+}
+
+fn pack_index(repo: &Path) -> gix_pack::index::File {
+    pack_index_with_hash(repo, gix_hash::Kind::Sha1)
+}
+
+fn pack_index_with_hash(repo: &Path, object_hash: gix_hash::Kind) -> gix_pack::index::File {
+    gix_pack::index::File::at(pack_with_extension(repo, "idx"), object_hash).expect("valid index")
+}
+
+fn db(repo: &Path) -> crate::Result<gix_odb::HandleArc> {
+    db_with_hash(repo, gix_hash::Kind::Sha1)
+}
+
+fn db_with_hash(repo: &Path, object_hash: gix_hash::Kind) -> crate::Result<gix_odb::HandleArc> {
+    Ok(gix_odb::Store::at_opts(
+        objects_dir(repo),
+        &mut None.into_iter(),
+        gix_odb::store::init::Options {
+            object_hash,
+            ..Default::default()
+        },
+    )
+    .map(|store| std::sync::Arc::new(store).to_cache_arc())?)
+}
+
+fn rev_parse(repo: &Path, spec: &str) -> ObjectId {
+    let out = std::process::Command::new("git")
+        .args(["rev-parse", spec])
+        .current_dir(repo)
+        .output()
+        .expect("git is installed");
+    assert!(out.status.success(), "{spec} can be resolved");
+    ObjectId::from_hex(out.stdout.trim_end()).expect("valid hex")
+}
+
+/// Return all bitmaps of `lookup` by the id of their commit, with each set converted into object ids.
+fn bitmaps_by_commit(lookup: &bitmap::Lookup<'_>) -> crate::Result<Vec<(ObjectId, Vec<ObjectId>)>> {
+    let mut out = Vec::new();
+    for commit_index in lookup.file().commit_indices() {
+        let commit = lookup.index().oid_at_index(commit_index).to_owned();
+        let set = lookup.reachable_from(&commit)?.expect("every entry has a bitmap");
+        let mut ids: Vec<_> = set.iter().map(|position| lookup.id_at(position).to_owned()).collect();
+        ids.sort();
+        out.push((commit, ids));
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// Assert that the bitmaps of commits that have one in both `a` and `b` are equal, and that there is at least one.
+fn assert_common_bitmaps_are_equal(a: &bitmap::Lookup<'_>, b: &bitmap::Lookup<'_>) -> crate::Result {
+    let b = bitmaps_by_commit(b)?;
+    let mut num_common = 0;
+    for (commit, ids) in bitmaps_by_commit(a)? {
+        if let Ok(pos) = b.binary_search_by(|(other, _)| other.cmp(&commit)) {
+            assert_eq!(ids, b[pos].1, "bitmaps of {commit} are equal by object id");
+            num_common += 1;
+        }
+    }
+    assert_ne!(num_common, 0, "at least one commit has a bitmap in both");
+    Ok(())
+}
+
+mod count;
+mod read;
+mod write;
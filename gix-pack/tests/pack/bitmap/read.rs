@@ -0,0 +1,150 @@
+use gix_pack::bitmap;
+
+use crate::pack::bitmap::{
+    assert_common_bitmaps_are_equal, bitmaps_by_commit, fixture, pack_index, pack_with_extension, rev_parse,
+};
+
+#[test]
+fn pack_bitmap_with_hash_cache() -> crate::Result {
+    let repo = fixture("pack");
+    let index = pack_index(&repo);
+    let file = bitmap::File::at(pack_with_extension(&repo, "bitmap"), gix_hash::Kind::Sha1)?;
+    assert!(file.has_hash_cache());
+    assert!(!file.has_lookup_table());
+    assert_eq!(file.checksum(), index.pack_checksum());
+    assert_eq!(file.num_name_hashes(), index.num_objects() as usize);
+    assert!(file.num_bitmaps() > 1, "history is long enough to select a few commits");
+
+    let order = bitmap::Order::from_index(&index);
+    let lookup = bitmap::Lookup::new(&file, &index, &order)?;
+
+    let head = rev_parse(&repo, "HEAD");
+    let reachable = lookup.reachable_from(&head)?.expect("the tip of a branch has a bitmap");
+    assert_eq!(
+        reachable.len(),
+        index.num_objects() as usize - 1,
+        "all objects are reachable from HEAD, except for the annotated tag"
+    );
+    let tag = lookup.position_of(&rev_parse(&repo, "v1")).expect("tag is in pack");
+    assert!(!reachable.contains(tag));
+
+    let mut kinds = 0;
+    for kind in [
+        gix_object::Kind::Commit,
+        gix_object::Kind::Tree,
+        gix_object::Kind::Blob,
+        gix_object::Kind::Tag,
+    ] {
+        let objects = file.objects_of_kind(kind)?;
+        kinds += objects.len();
+        if kind == gix_object::Kind::Tag {
+            assert_eq!(objects.iter().collect::<Vec<_>>(), [tag]);
+        }
+    }
+    assert_eq!(kinds, index.num_objects() as usize, "each object has exactly one kind");
+
+    let readme = lookup.position_of(&rev_parse(&repo, "HEAD:side.txt")).expect("present");
+    assert_eq!(
+        lookup.name_hash(readme),
+        gix_pack::data::output::count::name_hash(b"side.txt"),
+        "name-hashes are compatible with the ones computed by git"
+    );
+
+    for (commit, ids) in bitmaps_by_commit(&lookup)? {
+        assert!(ids.binary_search(&commit).is_ok(), "commits reach themselves");
+    }
+    Ok(())
+}
+
+#[test]
+fn pack_bitmap_with_lookup_table_has_the_same_bitmaps() -> crate::Result {
+    let repo = fixture("lookup-table");
+    let index = pack_index(&repo);
+    let file = bitmap::File::at(pack_with_extension(&repo, "bitmap"), gix_hash::Kind::Sha1)?;
+    assert!(file.has_lookup_table());
+    let order = bitmap::Order::from_index(&index);
+    let lookup = bitmap::Lookup::new(&file, &index, &order)?;
+
+    let other_repo = fixture("pack");
+    let other_index = pack_index(&other_repo);
+    let other_file = bitmap::File::at(pack_with_extension(&other_repo, "bitmap"), gix_hash::Kind::Sha1)?;
+    let other_order = bitmap::Order::from_index(&other_index);
+    let other_lookup = bitmap::Lookup::new(&other_file, &other_index, &other_order)?;
+
+    assert_common_bitmaps_are_equal(&lookup, &other_lookup)
+}
+
+#[test]
+fn multi_pack_index_bitmap() -> crate::Result {
+    let repo = fixture("multi-pack");
+    let pack_dir = repo.join(".git/objects/pack");
+    let index = gix_pack::multi_index::File::at(pack_dir.join("multi-pack-index"))?;
+    assert!(index.has_reverse_index(), "git writes it along with bitmaps");
+    let file = bitmap::File::at(
+        pack_dir.join(format!("multi-pack-index-{}.bitmap", index.checksum())),
+        gix_hash::Kind::Sha1,
+    )?;
+    let order = bitmap::Order::from_multi_index(&index).expect("valid reverse index");
+    let lookup = bitmap::Lookup::new(&file, &index, &order)?;
+
+    let head = rev_parse(&repo, "HEAD");
+    let reachable = lookup.reachable_from(&head)?.expect("the tip of a branch has a bitmap");
+    assert_eq!(
+        reachable.len(),
+        index.num_objects() as usize - 1,
+        "all objects are reachable from HEAD, except for the annotated tag"
+    );
+    Ok(())
+}
+
+#[test]
+fn lookup_rejects_bitmaps_of_other_packs() -> crate::Result {
+    let file = bitmap::File::at(pack_with_extension(&fixture("pack"), "bitmap"), gix_hash::Kind::Sha1)?;
+    let index = pack_index(&fixture("lookup-table"));
+    let order = bitmap::Order::from_index(&index);
+    assert!(matches!(
+        bitmap::Lookup::new(&file, &index, &order),
+        Err(bitmap::lookup::Error::ChecksumMismatch { .. })
+    ));
+    Ok(())
+}
+
+mod set {
+    use gix_pack::bitmap::Set;
+
+    #[test]
+    fn ewah_round_trip() {
+        let mut set = Set::default();
+        for position in [0, 1, 63, 64, 1000, 1001, 5000, 100_000] {
+            assert!(set.insert(position));
+        }
+        assert!(!set.insert(1000), "already present");
+        assert_eq!(Set::from_ewah(&set.to_ewah()), set);
+        assert_eq!(set.len(), 8);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            [0, 1, 63, 64, 1000, 1001, 5000, 100_000]
+        );
+
+        let all = Set::from_words(vec![u64::MAX; 1000]);
+        assert_eq!(Set::from_ewah(&all.to_ewah()), all);
+        assert!(all.to_ewah().serialized_size() < 100, "runs of set bits compress well");
+        assert!(Set::from_ewah(&Set::default().to_ewah()).is_empty());
+    }
+
+    #[test]
+    fn operations() {
+        let mut a = Set::from_words(vec![0b1011]);
+        let b = Set::from_words(vec![0b0110, 1]);
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert_eq!(union.iter().collect::<Vec<_>>(), [0, 1, 2, 3, 64]);
+
+        let mut difference = a.clone();
+        difference.difference_with(&b);
+        assert_eq!(difference.iter().collect::<Vec<_>>(), [0, 3]);
+
+        a.xor_with(&b);
+        assert_eq!(a.iter().collect::<Vec<_>>(), [0, 2, 3, 64]);
+    }
+}
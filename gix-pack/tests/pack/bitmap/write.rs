@@ -0,0 +1,109 @@
+use std::sync::atomic::AtomicBool;
+
+use gix_features::progress;
+use gix_pack::bitmap;
+
+use crate::pack::bitmap::{
+    assert_common_bitmaps_are_equal, db, db_with_hash, fixture, pack_index, pack_index_with_hash, pack_with_extension,
+    rev_parse, sha256_fixture,
+};
+
+#[test]
+fn pack_bitmap_round_trip_and_equality_with_git() -> crate::Result {
+    let repo = fixture("no-bitmap");
+    let index = pack_index(&repo);
+    let order = bitmap::Order::from_index(&index);
+    let db = db(&repo)?;
+
+    for lookup_table in [false, true] {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let bitmap_path = tmp.path().join("pack.bitmap");
+        let mut out = std::fs::File::create(&bitmap_path)?;
+        let outcome = bitmap::File::write_to(
+            &index,
+            &order,
+            &db,
+            &mut out,
+            &mut progress::Discard,
+            &AtomicBool::default(),
+            bitmap::write::Options {
+                preferred_commits: vec![rev_parse(&repo, "HEAD")],
+                lookup_table,
+                ..Default::default()
+            },
+        )?;
+        drop(out);
+
+        let file = bitmap::File::at(&bitmap_path, gix_hash::Kind::Sha1)?;
+        assert_eq!(file.num_bitmaps(), outcome.num_bitmaps);
+        assert_eq!(file.has_lookup_table(), lookup_table);
+        assert_eq!(file.num_name_hashes(), index.num_objects() as usize);
+        let lookup = bitmap::Lookup::new(&file, &index, &order)?;
+
+        let git_repo = fixture("lookup-table");
+        let git_file = bitmap::File::at(pack_with_extension(&git_repo, "bitmap"), gix_hash::Kind::Sha1)?;
+        assert_eq!(git_file.checksum(), index.pack_checksum(), "it's the same pack");
+        let git_lookup = bitmap::Lookup::new(&git_file, &index, &order)?;
+
+        assert_common_bitmaps_are_equal(&lookup, &git_lookup)?;
+        assert!(
+            lookup.reachable_from(&rev_parse(&repo, "HEAD"))?.is_some(),
+            "preferred commits get a bitmap"
+        );
+        for kind in [
+            gix_object::Kind::Commit,
+            gix_object::Kind::Tree,
+            gix_object::Kind::Blob,
+            gix_object::Kind::Tag,
+        ] {
+            assert_eq!(file.objects_of_kind(kind)?, git_file.objects_of_kind(kind)?);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn sha256_pack_bitmap_equality_with_git() -> crate::Result {
+    let object_hash = gix_hash::Kind::Sha256;
+    let repo = sha256_fixture("pack");
+    let index = pack_index_with_hash(&repo, object_hash);
+    let order = bitmap::Order::from_index(&index);
+    let db = db_with_hash(&repo, object_hash)?;
+
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let bitmap_path = tmp.path().join("pack.bitmap");
+    let mut out = std::fs::File::create(&bitmap_path)?;
+    let outcome = bitmap::File::write_to(
+        &index,
+        &order,
+        &db,
+        &mut out,
+        &mut progress::Discard,
+        &AtomicBool::default(),
+        bitmap::write::Options {
+            preferred_commits: vec![rev_parse(&repo, "HEAD")],
+            ..Default::default()
+        },
+    )?;
+    drop(out);
+
+    let file = bitmap::File::at(&bitmap_path, object_hash)?;
+    assert_eq!(file.num_bitmaps(), outcome.num_bitmaps);
+    assert_eq!(file.num_name_hashes(), index.num_objects() as usize);
+    let lookup = bitmap::Lookup::new(&file, &index, &order)?;
+
+    let git_file = bitmap::File::at(pack_with_extension(&repo, "bitmap"), object_hash)?;
+    assert_eq!(git_file.checksum(), index.pack_checksum(), "it's the same pack");
+    let git_lookup = bitmap::Lookup::new(&git_file, &index, &order)?;
+
+    assert_common_bitmaps_are_equal(&lookup, &git_lookup)?;
+    for kind in [
+        gix_object::Kind::Commit,
+        gix_object::Kind::Tree,
+        gix_object::Kind::Blob,
+        gix_object::Kind::Tag,
+    ] {
+        assert_eq!(file.objects_of_kind(kind)?, git_file.objects_of_kind(kind)?);
+    }
+    Ok(())
+}
@@ -110,6 +110,7 @@ mod write_to_directory {
             pack_version: pack::data::Version::V2,
             index_path: None,
             data_path: None,
+            reverse_index_path: None,
             keep_path: None,
             object_hash: gix_hash::Kind::Sha1,
         })
@@ -117,7 +118,7 @@ mod write_to_directory {
 
     #[test]
     fn without_providing_one() -> Result<(), Box<dyn std::error::Error>> {
-        let res = write_pack(None::<&Path>, SMALL_PACK)?;
+        let res = write_pack(None::<&Path>, SMALL_PACK, false)?;
         assert_eq!(res, expected_outcome()?);
         assert_eq!(
             res.index.index_hash,
@@ -130,7 +131,7 @@ mod write_to_directory {
     #[test]
     fn given_a_directory() -> Result<(), Box<dyn std::error::Error>> {
         let dir = TempDir::new()?;
-        let mut res = write_pack(Some(&dir), SMALL_PACK)?;
+        let mut res = write_pack(Some(&dir), SMALL_PACK, false)?;
         let (index_path, data_path, keep_path) = (res.index_path.take(), res.data_path.take(), res.keep_path.take());
         assert_eq!(res, expected_outcome()?);
         let mut sorted_entries = fs::read_dir(&dir)?.filter_map(Result::ok).collect::<Vec<_>>();
@@ -154,6 +155,33 @@ mod write_to_directory {
         Ok(())
     }
 
+    #[test]
+    fn given_a_directory_with_reverse_index() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = TempDir::new()?;
+        let res = write_pack(Some(&dir), SMALL_PACK, true)?;
+        let pack_hash = res.index.data_hash.to_hex();
+        let reverse_index_path = res.reverse_index_path.clone().expect("written on request");
+        assert_eq!(reverse_index_path, dir.path().join(format!("pack-{pack_hash}.rev")));
+
+        let reverse_index = pack::index::reverse::File::at(&reverse_index_path, gix_hash::Kind::Sha1)?;
+        assert_eq!(reverse_index.num_objects(), 42);
+        assert_eq!(reverse_index.pack_checksum(), res.index.data_hash);
+
+        let index = pack::index::File::at(res.index_path.as_ref().expect("present"), gix_hash::Kind::Sha1)?;
+        assert!(index.reverse_index().is_some(), "it's picked up automatically");
+        let mut expected_offsets: Vec<_> = index.iter().map(|e| e.pack_offset).collect();
+        expected_offsets.sort();
+        assert_eq!(index.sorted_offsets(), expected_offsets);
+        for offset in expected_offsets {
+            let entry_index = index
+                .lookup_pack_offset(offset)
+                .expect("every entry can be found by offset");
+            assert_eq!(index.pack_offset_at_index(entry_index), offset);
+        }
+        assert_eq!(index.lookup_pack_offset(1), None, "there is no object at this offset");
+        Ok(())
+    }
+
     fn file_name(entry: &fs::DirEntry) -> String {
         entry.path().file_name().unwrap().to_str().unwrap().to_owned()
     }
@@ -161,6 +189,7 @@ mod write_to_directory {
     fn write_pack(
         directory: Option<impl AsRef<Path>>,
         pack_file: &str,
+        write_reverse_index: bool,
     ) -> Result<pack::bundle::write::Outcome, Box<dyn std::error::Error>> {
         let pack_file = fs::File::open(fixture_path(pack_file))?;
         static SHOULD_INTERRUPT: AtomicBool = AtomicBool::new(false);
@@ -176,6 +205,7 @@ mod write_to_directory {
                 iteration_mode: pack::data::input::Mode::Verify,
                 index_version: pack::index::Version::V2,
                 object_hash: gix_hash::Kind::Sha1,
+                write_reverse_index,
             },
         )
         .map_err(Into::into)
@@ -0,0 +1,90 @@
+use gix_pack::data::delta;
+
+fn apply(base: &[u8], delta: &[u8]) -> Vec<u8> {
+    let (base_size, consumed) = delta::decode_header_size(delta);
+    assert_eq!(base_size as usize, base.len(), "the base size is encoded first");
+    let (target_size, consumed_target) = delta::decode_header_size(&delta[consumed..]);
+    let mut target = vec![0; target_size as usize];
+    delta::apply(base, &mut target, &delta[consumed + consumed_target..]);
+    target
+}
+
+fn round_trip(base: &[u8], target: &[u8]) -> usize {
+    let mut out = Vec::new();
+    delta::encode(base, target, &mut out);
+    assert_eq!(apply(base, &out), target, "the delta reproduces the target");
+    out.len()
+}
+
+/// A deterministic stream of pseudo-random bytes that doesn't compress or match by accident.
+fn pseudo_random(len: usize, mut seed: u32) -> Vec<u8> {
+    (0..len)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as u8
+        })
+        .collect()
+}
+
+#[test]
+fn empty_base_and_target() {
+    assert_eq!(round_trip(b"", b""), 2, "just the header");
+    round_trip(b"", b"only inserts");
+    round_trip(b"a base without any use", b"");
+}
+
+#[test]
+fn identical_objects_are_a_single_copy() {
+    let data = pseudo_random(1000, 1);
+    assert_eq!(round_trip(&data, &data), 2 + 2 + 3, "header and one copy instruction");
+}
+
+#[test]
+fn edits_in_the_middle_only_insert_what_changed() {
+    let base = pseudo_random(4000, 2);
+    let mut target = base.clone();
+    target.splice(1000..1010, b"something entirely new".iter().copied());
+    target.drain(3000..3100);
+    target.extend_from_slice(b"appended");
+    let size = round_trip(&base, &target);
+    assert!(size < 60, "only edits are inserted, got a delta of {size} bytes");
+}
+
+#[test]
+fn unrelated_objects_are_all_inserts() {
+    let base = pseudo_random(300, 3);
+    let target = pseudo_random(500, 4);
+    let size = round_trip(&base, &target);
+    assert!(size > target.len(), "inserts have overhead, got {size} bytes");
+}
+
+#[test]
+fn large_copies_are_split() {
+    let base = pseudo_random(200_000, 5);
+    let mut target = base[1..].to_vec();
+    target.extend_from_slice(&base[..100]);
+    let size = round_trip(&base, &target);
+    assert!(size < 40, "a handful of copy instructions, got {size} bytes");
+}
+
+#[test]
+fn index_can_be_reused_and_limits_the_delta_size() {
+    let base = pseudo_random(2000, 6);
+    let index = delta::Index::new(&base);
+    let mut out = Vec::new();
+
+    let mut similar = base.clone();
+    similar[500] ^= 0xff;
+    assert!(index.encode(&base, &similar, 100, &mut out));
+    assert_eq!(apply(&base, &out), similar);
+
+    let unrelated = pseudo_random(2000, 7);
+    assert!(
+        !index.encode(&base, &unrelated, 100, &mut out),
+        "the delta exceeds the maximum size"
+    );
+    assert!(index.encode(&base, &unrelated, usize::MAX, &mut out));
+    assert_eq!(apply(&base, &out), unrelated);
+}
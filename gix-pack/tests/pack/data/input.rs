@@ -33,6 +33,7 @@ mod lookup_ref_delta_objects {
         let obj = gix_object::Data {
             kind: header.as_kind().unwrap_or(gix_object::Kind::Blob),
             data,
+            object_hash: gix_hash::Kind::Sha1,
         };
         let mut entry = input::Entry::from_data_obj(&obj, 0).expect("valid object");
         entry.header = header;
@@ -86,6 +87,7 @@ mod lookup_ref_delta_objects {
                 Ok(Some(gix_object::Data {
                     kind: gix_object::Kind::Blob,
                     data: buf.as_slice(),
+                    object_hash: gix_hash::Kind::Sha1,
                 }))
             } else {
                 Ok(None)
@@ -1,3 +1,4 @@
+mod delta;
 mod file;
 mod header;
 mod input;
@@ -122,6 +122,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 16,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("b920bbb055e1efb9080592a409d3975738b6efb3"),
                 None,
@@ -155,6 +156,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 103,
                     ref_delta_objects: 74,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("25114bd8820b393c402cd53ad8ec7f6a84bb0633"),
                 Some(hex_to_id("29ab9797aff1ca826afb699680356695d19c5acb")),
@@ -188,6 +190,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 29,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("d83d42128e40957c5174920189a0390b5a70f446"),
                 None,
@@ -209,6 +212,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 868,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("542ad1d1c7c762ea4e36907570ff9e4b5b7dde1b"),
                 None,
@@ -230,6 +234,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 868,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("542ad1d1c7c762ea4e36907570ff9e4b5b7dde1b"),
                 None,
@@ -332,6 +337,67 @@ fn empty_pack_is_allowed() {
     .unwrap();
 }
 
+#[test]
+fn loose_objects_are_delta_compressed() -> crate::Result {
+    use gix_odb::Write;
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let loose = gix_odb::loose::Store::at(tmp.path(), gix_hash::Kind::Sha1);
+    let mut content: Vec<u8> = (0..200u32)
+        .flat_map(|line| format!("line {line} of a file that is changed in each revision\n").into_bytes())
+        .collect();
+    let mut counts = Vec::new();
+    for revision in 0..20 {
+        content.splice(
+            revision * 100..revision * 100,
+            format!("revision {revision}\n").into_bytes(),
+        );
+        let mut count = output::Count::from_data(loose.write_buf(gix_object::Kind::Blob, &content)?, None);
+        count.name_hash = count::name_hash(b"file.txt");
+        counts.push(count);
+    }
+    counts.push(output::Count::from_data(
+        loose.write_buf(gix_object::Kind::Blob, b"an unrelated small object")?,
+        None,
+    ));
+    let db = gix_odb::Store::at_opts(tmp.path().to_owned(), &mut None.into_iter(), Default::default())
+        .map(|store| std::sync::Arc::new(store).to_cache_arc())?;
+
+    let num_counts = counts.len();
+    let mut entries_iter = output::entry::iter_from_counts(
+        counts,
+        db.clone(),
+        Box::new(progress::Discard),
+        output::entry::iter_from_counts::Options {
+            mode: output::entry::iter_from_counts::Mode::PackCopyAndDeltaCompression,
+            delta_depth: 5,
+            ..Default::default()
+        },
+    );
+    let entries: Vec<_> = InOrderIter::from(entries_iter.by_ref())
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+    let stats = entries_iter.finalize()?;
+    assert_eq!(entries.len(), num_counts);
+    assert_eq!(
+        stats.delta_compressed_objects + stats.decoded_and_recompressed_objects,
+        num_counts
+    );
+    assert!(
+        stats.delta_compressed_objects >= 16,
+        "all but the first revision of each chain and the unrelated object are deltified, got {}",
+        stats.delta_compressed_objects
+    );
+    let total_size: usize = entries.iter().map(|entry| entry.decompressed_size).sum();
+    assert!(
+        total_size < content.len() * 3,
+        "deltas are small, got {total_size} bytes"
+    );
+
+    write_and_verify(db, entries, gix_hash::Kind::Sha1.null(), None)
+}
+
 fn write_and_verify(
     db: gix_odb::HandleArc,
     entries: Vec<output::Entry>,
@@ -6,7 +6,7 @@ use gix_pack::data::output;
 fn size_of_entry() {
     assert_eq!(
         std::mem::size_of::<output::Entry>(),
-        80,
+        112,
         "The size of the structure shouldn't change unexpectedly"
     )
 }
@@ -15,11 +15,23 @@ fn size_of_entry() {
 fn size_of_count() {
     assert_eq!(
         std::mem::size_of::<output::Count>(),
-        56,
+        72,
         "The size of the structure shouldn't change unexpectedly"
     )
 }
 
+#[test]
+fn name_hash_is_dominated_by_the_last_characters() {
+    use output::count::name_hash;
+    assert_eq!(name_hash(b""), 0);
+    assert_eq!(name_hash(b"a file.c"), name_hash(b"afile.c"), "whitespace is ignored");
+    let (c, other_c, rust) = (name_hash(b"main.c"), name_hash(b"lib.c"), name_hash(b"main.rs"));
+    assert!(
+        c.abs_diff(other_c) < c.abs_diff(rust),
+        "files with the same extension sort closely"
+    );
+}
+
 enum DbKind {
     DeterministicGeneratedContent,
     DeterministicGeneratedContentMultiIndex,
@@ -489,3 +489,102 @@ fn iter() -> Result<(), Box<dyn std::error::Error>> {
     }
     Ok(())
 }
+
+mod reverse {
+    use gix_pack::index;
+
+    fn pack_dir() -> std::path::PathBuf {
+        crate::scripted_fixture_read_only("make_pack_gen_repo_bitmap.sh")
+            .expect("fixture exists")
+            .join("pack/.git/objects/pack")
+    }
+
+    fn path_with_extension(extension: &str) -> std::path::PathBuf {
+        std::fs::read_dir(pack_dir())
+            .expect("pack directory exists")
+            .map(|entry| entry.expect("readable").path())
+            .find(|path| matches!(path.extension(), Some(ext) if ext == extension))
+            .expect("a file with the extension is present")
+    }
+
+    #[test]
+    fn read_and_write_are_compatible_with_git() -> crate::Result {
+        let index = index::File::at(path_with_extension("idx"), gix_hash::Kind::Sha1)?;
+        let reverse_index = index
+            .reverse_index()
+            .expect("git wrote it, and we load it automatically");
+        assert_eq!(reverse_index.num_objects(), index.num_objects());
+        assert_eq!(reverse_index.pack_checksum(), index.pack_checksum());
+
+        let mut offsets: Vec<_> = index.iter().map(|e| e.pack_offset).collect();
+        offsets.sort();
+        assert_eq!(
+            reverse_index
+                .iter()
+                .map(|entry_index| index.pack_offset_at_index(entry_index))
+                .collect::<Vec<_>>(),
+            offsets,
+            "index positions are listed in pack order"
+        );
+
+        let mut buf = Vec::new();
+        let checksum = index::reverse::File::write_from_index(&index, &mut buf)?;
+        assert_eq!(checksum, reverse_index.checksum());
+        assert_eq!(
+            buf,
+            std::fs::read(path_with_extension("rev"))?,
+            "we write exactly what git writes"
+        );
+        Ok(())
+    }
+}
+
+mod mtimes {
+    use gix_pack::index;
+
+    fn path_with_extension(extension: &str) -> std::path::PathBuf {
+        let pack_dir = crate::scripted_fixture_read_only("make_pack_gen_repo_cruft.sh")
+            .expect("fixture exists")
+            .join(".git/objects/pack");
+        std::fs::read_dir(pack_dir)
+            .expect("pack directory exists")
+            .map(|entry| entry.expect("readable").path())
+            .find(|path| matches!(path.extension(), Some(ext) if ext == extension))
+            .expect("a file with the extension is present")
+    }
+
+    #[test]
+    fn read_and_write_are_compatible_with_git() -> crate::Result {
+        let mtimes_path = path_with_extension("mtimes");
+        let mtimes = index::mtimes::File::at(&mtimes_path, gix_hash::Kind::Sha1)?;
+        let index = index::File::at(mtimes_path.with_extension("idx"), gix_hash::Kind::Sha1)?;
+        assert_eq!(
+            mtimes.num_objects(),
+            4,
+            "the commit, its tree and blob on the deleted branch, and the blob that was never added"
+        );
+        assert_eq!(mtimes.num_objects(), index.num_objects());
+        assert_eq!(mtimes.pack_checksum(), index.pack_checksum());
+        assert!(mtimes.iter().all(|mtime| mtime > 0));
+        assert_eq!(mtimes.mtime_at(mtimes.num_objects()), None);
+
+        let mut buf = Vec::new();
+        let mut entry_index = 0;
+        let checksum = index::mtimes::File::write_from_index(
+            &index,
+            &mut |id| {
+                assert_eq!(
+                    id,
+                    index.oid_at_index(entry_index),
+                    "objects are visited in index order"
+                );
+                entry_index += 1;
+                mtimes.mtime_at(entry_index - 1).expect("in bounds")
+            },
+            &mut buf,
+        )?;
+        assert_eq!(checksum, mtimes.checksum());
+        assert_eq!(buf, std::fs::read(mtimes_path)?, "we write exactly what git writes");
+        Ok(())
+    }
+}
@@ -28,6 +28,7 @@ fn from_paths() -> crate::Result {
         &AtomicBool::new(false),
         gix_pack::multi_index::write::Options {
             object_hash: gix_hash::Kind::Sha1,
+            reverse_index: false,
         },
     )?;
 
@@ -66,3 +67,50 @@ fn from_paths() -> crate::Result {
     assert_eq!(outcome, file.checksum());
     Ok(())
 }
+
+#[test]
+fn from_paths_with_reverse_index() -> crate::Result {
+    let dir = gix_testtools::tempfile::TempDir::new()?;
+    let mut input_indices = std::fs::read_dir(fixture_path_standalone("objects/pack"))?
+        .filter_map(|r| {
+            r.ok()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(std::ffi::OsStr::to_str).unwrap_or("") == "idx")
+        })
+        .collect::<Vec<_>>();
+    input_indices.sort();
+    let output_path = dir.path().join("multi-pack-index");
+    let mut out = std::fs::File::create(&output_path)?;
+    gix_pack::multi_index::File::write_from_index_paths(
+        input_indices,
+        &mut out,
+        &mut progress::Discard,
+        &AtomicBool::new(false),
+        gix_pack::multi_index::write::Options {
+            object_hash: gix_hash::Kind::Sha1,
+            reverse_index: true,
+        },
+    )?;
+
+    let file = gix_pack::multi_index::File::at(output_path)?;
+    assert!(file.has_reverse_index());
+    let pseudo_pack_order: Vec<_> = (0..file.num_objects())
+        .map(|position| {
+            let index = file
+                .index_at_pseudo_pack_position(position)
+                .expect("every position is present");
+            file.pack_id_and_pack_offset_at_index(index)
+        })
+        .collect();
+    let mut expected = pseudo_pack_order.clone();
+    expected.sort();
+    assert_eq!(
+        pseudo_pack_order, expected,
+        "objects are ordered by pack, and by their offset within it"
+    );
+    assert!(
+        gix_pack::bitmap::Order::from_multi_index(&file).is_some(),
+        "each object is listed exactly once"
+    );
+    Ok(())
+}
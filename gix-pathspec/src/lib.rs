@@ -103,7 +103,7 @@ pub struct Pattern {
     /// That way, even though pathspecs are applied from the top, we can emulate having changed directory into
     /// a specific sub-directory in a case-sensitive file-system, even if the rest of the pathspec can be set to
     /// match case-insensitively.
-    /// Is set by [Pattern::normalize()].
+    /// Is set by [Pattern::normalize()], or by the `prefix:<len>` magic keyword.
     prefix_len: usize,
 }
 
@@ -28,6 +28,10 @@ pub enum Error {
     MultipleAttributeSpecifications,
     #[error("'literal' and 'glob' keywords cannot be used together in the same pathspec")]
     IncompatibleSearchModes,
+    #[error("Invalid parameter for 'prefix' magic: {value:?}")]
+    InvalidPrefixValue { value: BString },
+    #[error("The prefix length {prefix_len} exceeds the length of path {path:?}")]
+    PrefixOutOfBounds { prefix_len: usize, path: BString },
 }
 
 impl Pattern {
@@ -60,12 +64,16 @@ impl Pattern {
         };
 
         let mut cursor = 0;
+        let mut prefix_len = None;
         if input.first() == Some(&b':') {
             cursor += 1;
-            p.signature |= parse_short_keywords(input, &mut cursor)?;
+            // Like in `git`, the long form is only recognized if it directly follows the colon,
+            // and short keywords can't be mixed with it.
             if let Some(b'(') = input.get(cursor) {
                 cursor += 1;
-                parse_long_keywords(input, &mut p, &mut cursor)?;
+                prefix_len = parse_long_keywords(input, &mut p, &mut cursor)?;
+            } else {
+                p.signature |= parse_short_keywords(input, &mut cursor)?;
             }
         }
 
@@ -73,6 +81,20 @@ impl Pattern {
             p.search_mode = search_mode;
         }
         let mut path = &input[cursor..];
+        if let Some(prefix_len) = prefix_len {
+            if prefix_len > path.len() {
+                return Err(Error::PrefixOutOfBounds {
+                    prefix_len,
+                    path: path.into(),
+                });
+            }
+            // Our prefix is a directory without trailing slash.
+            p.prefix_len = if path[..prefix_len].last() == Some(&b'/') {
+                prefix_len - 1
+            } else {
+                prefix_len
+            };
+        }
         if path.last() == Some(&b'/') {
             p.signature |= MagicSignature::MUST_BE_DIR;
             path = &path[..path.len() - 1];
@@ -94,6 +116,7 @@ impl Pattern {
 }
 
 fn parse_short_keywords(input: &[u8], cursor: &mut usize) -> Result<MagicSignature, Error> {
+    // These are reserved for magic signatures, but `git` doesn't implement them either.
     let unimplemented_chars = b"\"#%&'-',;<=>@_`~";
 
     let mut signature = MagicSignature::empty();
@@ -118,20 +141,25 @@ fn parse_short_keywords(input: &[u8], cursor: &mut usize) -> Result<MagicSignatu
     Ok(signature)
 }
 
-fn parse_long_keywords(input: &[u8], p: &mut Pattern, cursor: &mut usize) -> Result<(), Error> {
-    let end = input.find(")").ok_or(Error::MissingClosingParenthesis)?;
+/// Parse the keywords of the long form, returning the value of the `prefix` keyword if it was present.
+fn parse_long_keywords(input: &[u8], p: &mut Pattern, cursor: &mut usize) -> Result<Option<usize>, Error> {
+    let end = find_non_escaped_char(&input[*cursor..], b')')
+        .map(|pos| *cursor + pos)
+        .ok_or(Error::MissingClosingParenthesis)?;
 
     let input = &input[*cursor..end];
     *cursor = end + 1;
 
+    let mut prefix_len = None;
     if input.is_empty() {
-        return Ok(());
+        return Ok(prefix_len);
     }
 
     split_on_non_escaped_char(input, b',', |keyword| {
         let attr_prefix = b"attr:";
+        let prefix_prefix = b"prefix:";
         match keyword {
-            b"attr" => {}
+            b"" | b"attr" => {}
             b"top" => p.signature |= MagicSignature::TOP,
             b"icase" => p.signature |= MagicSignature::ICASE,
             b"exclude" => p.signature |= MagicSignature::EXCLUDE,
@@ -150,6 +178,9 @@ fn parse_long_keywords(input: &[u8], p: &mut Pattern, cursor: &mut usize) -> Res
                     return Err(Error::MultipleAttributeSpecifications);
                 }
             }
+            _ if keyword.starts_with(prefix_prefix) => {
+                prefix_len = parse_prefix_len(&keyword[prefix_prefix.len()..])?;
+            }
             _ => {
                 return Err(Error::InvalidKeyword {
                     keyword: BString::from(keyword),
@@ -157,7 +188,39 @@ fn parse_long_keywords(input: &[u8], p: &mut Pattern, cursor: &mut usize) -> Res
             }
         };
         Ok(())
-    })
+    })?;
+    Ok(prefix_len)
+}
+
+/// Parse `value` like `strtol()` would, with negative values meaning that no prefix is set.
+fn parse_prefix_len(value: &[u8]) -> Result<Option<usize>, Error> {
+    let invalid = || Error::InvalidPrefixValue { value: value.into() };
+    if value.is_empty() {
+        return Ok(Some(0));
+    }
+    let digits = value.trim_start_with(|c| c.is_ascii_whitespace());
+    let (is_negative, digits) = match digits.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        Some((b'+', rest)) => (false, rest),
+        _ => (false, digits),
+    };
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return Err(invalid());
+    }
+    let len: usize = digits.to_str().ok().and_then(|d| d.parse().ok()).ok_or_else(invalid)?;
+    Ok((!is_negative || len == 0).then_some(len))
+}
+
+fn find_non_escaped_char(input: &[u8], needle: u8) -> Option<usize> {
+    let mut bytes = input.iter().enumerate();
+    while let Some((pos, b)) = bytes.next() {
+        if *b == b'\\' {
+            bytes.next();
+        } else if *b == needle {
+            return Some(pos);
+        }
+    }
+    None
 }
 
 fn split_on_non_escaped_char(
@@ -165,15 +228,10 @@ fn split_on_non_escaped_char(
     split_char: u8,
     mut f: impl FnMut(&[u8]) -> Result<(), Error>,
 ) -> Result<(), Error> {
-    let mut i = 0;
     let mut last = 0;
-    for window in input.windows(2) {
-        i += 1;
-        if window[0] != b'\\' && window[1] == split_char {
-            let keyword = &input[last..i];
-            f(keyword)?;
-            last = i + 1;
-        }
+    while let Some(pos) = find_non_escaped_char(&input[last..], split_char) {
+        f(&input[last..][..pos])?;
+        last += pos + 1;
     }
     let last_keyword = &input[last..];
     f(last_keyword)
@@ -34,7 +34,8 @@ impl Pattern {
     /// Normalize the pattern's path by assuring it's relative to the root of the working tree, and contains
     /// no relative path components. Further, it assures that `/` are used as path separator.
     ///
-    /// If `self.path` is a relative path, it will be put in front of the pattern path if `self.signature` isn't indicating `TOP` already.
+    /// If `self.path` is a relative path, it will be put in front of the pattern path if `self.signature` isn't indicating `TOP` already,
+    /// and if the pattern doesn't already have a prefix set via `prefix:<len>` magic.
     /// If `self.path` is an absolute path, we will use `root` to make it worktree relative if possible.
     ///
     /// `prefix` can be empty, we will still normalize this pathspec to resolve relative path components, and
@@ -60,6 +61,7 @@ impl Pattern {
         let mut path = gix_path::from_bstr(self.path.as_bstr());
         let mut num_prefix_components = 0;
         let mut was_absolute = false;
+        let has_prefix_magic = self.prefix_len != 0;
         if gix_path::is_absolute(path.as_ref()) {
             was_absolute = true;
             let rela_path = match path.strip_prefix(root) {
@@ -72,7 +74,7 @@ impl Pattern {
                 }
             };
             path = rela_path.to_owned().into();
-        } else if !prefix.as_os_str().is_empty() && !self.signature.contains(MagicSignature::TOP) {
+        } else if !prefix.as_os_str().is_empty() && !self.signature.contains(MagicSignature::TOP) && !has_prefix_magic {
             debug_assert_eq!(
                 prefix
                     .components()
@@ -115,7 +117,9 @@ impl Pattern {
         } else {
             let cleaned = PathBuf::from_iter(path.components().filter(|c| !matches!(c, Component::CurDir)));
             let mut out = gix_path::to_unix_separators_on_windows(gix_path::into_bstr(cleaned)).into_owned();
-            self.prefix_len = {
+            self.prefix_len = if has_prefix_magic && !was_absolute {
+                self.prefix_len.min(out.len())
+            } else {
                 if self.signature.contains(MagicSignature::MUST_BE_DIR) {
                     out.push(b'/');
                 }
@@ -171,6 +175,9 @@ impl Pattern {
                 SearchMode::Literal => buf.push_str("literal,"),
                 SearchMode::PathAwareGlob => buf.push_str("glob,"),
             }
+            if self.prefix_len != 0 {
+                buf.push_str(format!("prefix:{},", self.prefix_len));
+            }
             if self.attributes.is_empty() {
                 if buf.last() == Some(&b',') {
                     buf.pop();
@@ -87,14 +87,21 @@ impl Search {
             }
 
             if let Some(attrs) = mapping.value.attrs_match.as_mut() {
-                if !attributes(relative_path, Case::Sensitive, is_dir, attrs) {
-                    // we have attrs, but it didn't match any
-                    return None;
-                }
-                for (actual, expected) in attrs.iter_selected().zip(mapping.value.pattern.attributes.iter()) {
-                    if actual.assignment != expected.as_ref() {
-                        return None;
+                if attributes(relative_path, Case::Sensitive, is_dir, attrs) {
+                    for (actual, expected) in attrs.iter_selected().zip(mapping.value.pattern.attributes.iter()) {
+                        if actual.assignment != expected.as_ref() {
+                            return None;
+                        }
                     }
+                } else if mapping
+                    .value
+                    .pattern
+                    .attributes
+                    .iter()
+                    .any(|expected| expected.state != gix_attributes::State::Unspecified)
+                {
+                    // None of the attributes is specified, which only matches if that's what the pathspec demands.
+                    return None;
                 }
             }
 
@@ -0,0 +1,103 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init;
+
+function baseline() {
+  local specs=""
+  for arg in "$@"; do
+    if [[ $arg == *"+"* ]]; then
+      echo "BUG: Argument '$arg' contains a space - we use that for separating pathspecs right now" >&2
+      exit 1
+    fi
+    specs="${specs}+${arg}"
+  done
+
+  {
+      echo "$specs"
+      git ls-files "$@"
+      echo -n ';'
+  } >> baseline.git
+}
+
+cat <<ATTRS >.gitattributes
+*.txt text
+a.txt c=v
+dir/** c=other
+value c=v -text
+value-other c=other
+unset -c
+ATTRS
+
+mkdir -p dir/sub Dir
+for p in a.txt b.txt dir/a.txt dir/sub/b.txt Dir/A.TXT value value-other unset '(top)a.txt'; do
+  : >"$p"
+done
+git add . && git commit -m init
+# this is to avoid issues on windows, which might not be able to manifest these files.
+for p in '*.txt' 'c*.txt' 'd?.txt' '[ab].txt' 'dir/*'; do
+  git -c core.protectNTFS=false update-index --add --cacheinfo 100644 "$(git rev-parse HEAD:a.txt)" "$p"
+done
+
+git ls-files > paths
+
+# literal and glob
+baseline ':(literal)*.txt'
+baseline ':(literal)c*.txt'
+baseline ':(literal)d?.txt'
+baseline ':(literal)[ab].txt'
+baseline ':(literal,icase)C*.TXT'
+baseline ':(literal)dir/*'
+baseline ':(literal)dir'
+baseline ':(literal)di*'
+baseline ':(exclude,literal)*.txt'
+baseline ':(exclude,literal)*.txt' '*.txt'
+baseline ':(glob)*.txt'
+baseline ':(glob)**/*.txt'
+baseline ':(glob)dir/*'
+baseline '*.txt'
+baseline '[ab].txt'
+baseline 'd?.txt'
+baseline 'dir/*'
+
+# icase
+baseline ':(icase)dir/A.txt'
+baseline ':(icase)DIR'
+baseline ':(icase)DIR/'
+baseline ':(icase,literal)DIR/*'
+baseline ':(icase,glob)DIR/*.TXT'
+
+# attributes with and without values
+baseline ':(attr:c=v)'
+baseline ':(attr:c=other)'
+baseline ':(attr:c=v)*.txt'
+baseline ':(attr:c)'
+baseline ':(attr:-c)'
+baseline ':(attr:!c)'
+baseline ':(attr:text)'
+baseline ':(attr:-text)'
+baseline ':(attr:!text)'
+baseline ':(attr:text c=v)'
+baseline ':(attr:c=v -text)'
+baseline ':(attr:text c=other)dir'
+# a second pathspec avoids a common prefix, which makes git miss 'c=other' from 'dir/**' in the presence of wildcards
+baseline ':(attr:text c=other,glob)dir/*' ':(attr:c=v)value*'
+baseline ':(attr:c=other)dir/*' 'value*'
+baseline ':(icase,attr:c=v)A.TXT'
+baseline ':(exclude,attr:c=other)' '*.txt'
+baseline ':(attr:c=other,exclude)*.txt' ':(attr:text)'
+
+# short and long forms
+baseline ':/(top)a.txt'
+baseline ':!(top)a.txt' '*.txt'
+baseline ':/!*.txt'
+baseline ':!/:*.txt'
+baseline ':(top,,literal)*.txt'
+baseline ':(,icase,)a.TXT'
+
+# prefix
+baseline ':(prefix:4)dir/a.txt'
+baseline ':(prefix:4)dir/*'
+baseline ':(prefix:4,icase)dir/A.TXT'
+baseline ':(prefix:4,icase)DIR/a.txt'
+baseline ':(prefix:0,icase)DIR/a.txt'
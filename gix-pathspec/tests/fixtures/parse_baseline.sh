@@ -87,6 +87,35 @@ baseline ':(attr:v=one\,)'
 baseline ':(attr:v=one\,two\,three)'
 baseline ':(attr:a=\d b= c=\d)'
 
+# short_signatures_do_not_mix_with_long_ones
+baseline ':/(top)some/path'
+baseline ':!(icase)some/path'
+baseline ':/!(attr:a)'
+baseline ':/(top'
+
+# empty_keywords_are_skipped
+baseline ':(top,,icase)some/path'
+baseline ':(,top)some/path'
+baseline ':(top,)some/path'
+baseline ':(,)some/path'
+
+# prefix_keyword
+baseline ':(prefix:0)some/path'
+baseline ':(prefix:)some/path'
+baseline ':(prefix:-1)some/path'
+baseline ':(prefix:4)some/path'
+baseline ':(prefix:5)some/path'
+baseline ':(prefix:+5)some/path'
+baseline ':(prefix: 5)some/path'
+baseline ':(prefix:05)some/path'
+baseline ':(prefix:9)some/path'
+baseline ':(prefix:5)some/'
+baseline ':(prefix:4,prefix:5)some/path'
+baseline ':(top,prefix:5,icase)some/path'
+
+# escaped_closing_parenthesis
+baseline ':(literal)some\)path'
+
 # failing
 
 #empty_input
@@ -144,6 +173,23 @@ baseline ':(attr:one,attr:two)some/path'
 
 # missing_parentheses
 baseline ':(top'
+baseline ':(top\)'
+baseline ':(attr:a=b\))'
+
+# escaped_closing_parenthesis_in_keywords
+baseline ':(top\))some/path'
+baseline ':(top\,icase)some/path'
+
+# invalid_prefix_values
+baseline ':(prefix)some/path'
+baseline ':(prefix:x)some/path'
+baseline ':(prefix:-)some/path'
+baseline ':(prefix:5 )some/path'
+baseline ':(prefix:0x5)some/path'
+
+# prefix_out_of_bounds
+baseline ':(prefix:10)some/path'
+baseline ':(prefix:1)'
 
 # glob_and_literal_keywords_present
 baseline ':(glob,literal)some/path'
@@ -85,6 +85,25 @@ fn relative_top_patterns_ignore_the_prefix() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn prefix_magic_patterns_ignore_the_prefix_and_keep_their_own() -> crate::Result {
+    let spec = normalized_spec(":(prefix:4)a/b/c", "prefix-ignored", "")?;
+    assert_eq!(spec.path(), "a/b/c");
+    assert_eq!(spec.prefix_directory(), "a/b");
+    assert_eq!(spec.to_bstring(), ":(prefix:3)a/b/c", "the prefix survives a roundtrip");
+
+    let mut spec = normalized_spec("c", "a/b", "")?;
+    assert_eq!(spec.to_bstring(), ":(prefix:3)a/b/c");
+    spec.normalize(Path::new("a/b"), Path::new(""))?;
+    assert_eq!(
+        spec.path(),
+        "a/b/c",
+        "normalizing again doesn't prepend the prefix twice"
+    );
+    assert_eq!(spec.prefix_directory(), "a/b");
+    Ok(())
+}
+
 #[test]
 fn absolute_top_patterns_ignore_the_prefix_but_are_made_relative() -> crate::Result {
     let spec = normalized_spec(":(top)/a/b", "prefix-ignored", "/a")?;
@@ -92,11 +92,7 @@ fn invalid_attribute_values() {
 
 #[test]
 fn escape_character_at_end_of_attribute_value() {
-    let inputs = vec![
-        r":(attr:v=invalid\)some/path",
-        r":(attr:v=invalid\ )some/path",
-        r":(attr:v=invalid\ valid)some/path",
-    ];
+    let inputs = vec![r":(attr:v=invalid\ )some/path", r":(attr:v=invalid\ valid)some/path"];
 
     for input in inputs {
         assert!(!check_against_baseline(input), "This pathspec is valid in git: {input}");
@@ -140,6 +136,69 @@ fn missing_parentheses() {
     assert!(matches!(output.unwrap_err(), Error::MissingClosingParenthesis { .. }));
 }
 
+#[test]
+fn escaped_closing_parenthesis_in_keywords() {
+    let inputs = vec![r":(top\))some/path", r":(top\,icase)some/path"];
+
+    for input in inputs {
+        assert!(!check_against_baseline(input), "This pathspec is valid in git: {input}");
+
+        let output = gix_pathspec::parse(input.as_bytes(), Default::default());
+        assert!(output.is_err(), "This pathspec did not produce an error {input}");
+        assert!(matches!(output.unwrap_err(), Error::InvalidKeyword { .. }));
+    }
+
+    for input in [r":(top\)", r":(attr:v=invalid\)some/path"] {
+        assert!(!check_against_baseline(input), "This pathspec is valid in git: {input}");
+
+        let output = gix_pathspec::parse(input.as_bytes(), Default::default());
+        assert!(
+            matches!(output.unwrap_err(), Error::MissingClosingParenthesis),
+            "the only closing parenthesis is escaped in {input}"
+        );
+    }
+
+    let input = r":(attr:a=b\))";
+    assert!(!check_against_baseline(input), "This pathspec is valid in git: {input}");
+    let output = gix_pathspec::parse(input.as_bytes(), Default::default());
+    assert!(
+        matches!(output.unwrap_err(), Error::InvalidAttributeValue { character: ')' }),
+        "escaped parentheses are part of the attribute value"
+    );
+}
+
+#[test]
+fn invalid_prefix_values() {
+    let inputs = vec![
+        ":(prefix)some/path",
+        ":(prefix:x)some/path",
+        ":(prefix:-)some/path",
+        ":(prefix:5 )some/path",
+        ":(prefix:0x5)some/path",
+    ];
+
+    for input in inputs {
+        assert!(!check_against_baseline(input), "This pathspec is valid in git: {input}");
+
+        let output = gix_pathspec::parse(input.as_bytes(), Default::default());
+        assert!(output.is_err(), "This pathspec did not produce an error {input}");
+        assert!(matches!(
+            output.unwrap_err(),
+            Error::InvalidPrefixValue { .. } | Error::InvalidKeyword { .. }
+        ));
+    }
+}
+
+#[test]
+fn prefix_out_of_bounds() {
+    for input in [":(prefix:10)some/path", ":(prefix:1)"] {
+        assert!(!check_against_baseline(input), "This pathspec is valid in git: {input}");
+
+        let output = gix_pathspec::parse(input.as_bytes(), Default::default());
+        assert!(matches!(output.unwrap_err(), Error::PrefixOutOfBounds { .. }));
+    }
+}
+
 #[test]
 fn glob_and_literal_keywords_present() {
     let input = ":(glob,literal)some/path";
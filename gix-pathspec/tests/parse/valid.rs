@@ -11,7 +11,10 @@ fn repeated_matcher_keywords() {
         (":(top,top)", pat_with_sig(MagicSignature::TOP)),
         (":(icase,icase)", pat_with_sig(MagicSignature::ICASE)),
         (":(attr,attr)", pat_with_attrs(vec![])),
-        (":!^(exclude,exclude)", pat_with_sig(MagicSignature::EXCLUDE)),
+        (
+            ":!^(exclude,exclude)",
+            pat_with_path_and_sig("(exclude,exclude)", MagicSignature::EXCLUDE),
+        ),
     ];
 
     check_valid_inputs(input);
@@ -193,6 +196,87 @@ fn short_signatures() {
     check_valid_inputs(inputs)
 }
 
+#[test]
+fn short_signatures_do_not_mix_with_long_ones() {
+    let inputs = vec![
+        (
+            ":/(top)some/path",
+            pat_with_path_and_sig("(top)some/path", MagicSignature::TOP),
+        ),
+        (
+            ":!(icase)some/path",
+            pat_with_path_and_sig("(icase)some/path", MagicSignature::EXCLUDE),
+        ),
+        (
+            ":/!(attr:a)",
+            pat_with_path_and_sig("(attr:a)", MagicSignature::TOP | MagicSignature::EXCLUDE),
+        ),
+        (":/(top", pat_with_path_and_sig("(top", MagicSignature::TOP)),
+    ];
+
+    check_valid_inputs(inputs)
+}
+
+#[test]
+fn empty_keywords_are_skipped() {
+    let inputs = vec![
+        (
+            ":(top,,icase)some/path",
+            pat_with_path_and_sig("some/path", MagicSignature::TOP | MagicSignature::ICASE),
+        ),
+        (
+            ":(,top)some/path",
+            pat_with_path_and_sig("some/path", MagicSignature::TOP),
+        ),
+        (
+            ":(top,)some/path",
+            pat_with_path_and_sig("some/path", MagicSignature::TOP),
+        ),
+        (":(,)some/path", pat_with_path("some/path")),
+    ];
+
+    check_valid_inputs(inputs)
+}
+
+#[test]
+fn prefix_keyword() {
+    let inputs = vec![
+        (":(prefix:0)some/path", ""),
+        (":(prefix:)some/path", ""),
+        (":(prefix:-1)some/path", ""),
+        (":(prefix:4)some/path", "some"),
+        (":(prefix:5)some/path", "some"),
+        (":(prefix:+5)some/path", "some"),
+        (":(prefix: 5)some/path", "some"),
+        (":(prefix:05)some/path", "some"),
+        (":(prefix:9)some/path", "some/path"),
+        (":(prefix:5)some/", "some"),
+        (":(prefix:4,prefix:5)some/path", "some"),
+        (":(top,prefix:5,icase)some/path", "some"),
+    ];
+
+    for (input, expected_prefix) in inputs {
+        assert!(
+            check_against_baseline(input),
+            "This pathspec is invalid in git: {input}"
+        );
+        let pattern = gix_pathspec::parse(input.as_bytes(), Default::default()).expect("valid");
+        assert_eq!(pattern.prefix_directory(), expected_prefix, "{input}");
+        assert_eq!(
+            pattern.path(),
+            input.rsplit_once(')').expect("magic").1.trim_end_matches('/')
+        );
+    }
+}
+
+#[test]
+fn escaped_closing_parenthesis() {
+    check_valid_inputs([(
+        r":(literal)some\)path",
+        pat(r"some\)path", MagicSignature::empty(), SearchMode::Literal, vec![]),
+    )]);
+}
+
 #[test]
 fn trailing_slash_is_turned_into_magic_signature_and_removed() {
     check_valid_inputs([
@@ -220,7 +304,12 @@ fn signatures_and_searchmodes() {
         ),
         (
             ":!(literal)some/*path",
-            pat("some/*path", MagicSignature::EXCLUDE, SearchMode::Literal, vec![]),
+            pat(
+                "(literal)some/*path",
+                MagicSignature::EXCLUDE,
+                SearchMode::ShellGlob,
+                vec![],
+            ),
         ),
         (
             ":(top,literal,icase,attr,exclude)some/path",
@@ -583,6 +583,11 @@ fn files() -> crate::Result {
     baseline::run("file", false, baseline::files)
 }
 
+#[test]
+fn magic_signatures() -> crate::Result {
+    baseline::run("magic", false, baseline::magic)
+}
+
 fn pathspecs(input: &[&str]) -> Vec<gix_pathspec::Pattern> {
     input
         .iter()
@@ -723,6 +728,13 @@ mod baseline {
         let expected = files::parse_expected(&std::fs::read(root.join("baseline.git"))?);
         Ok((root, items, expected))
     }
+
+    pub fn magic() -> crate::Result<(PathBuf, Vec<String>, Vec<Expected>)> {
+        let root = gix_testtools::scripted_fixture_read_only("match_baseline_magic.sh")?;
+        let items = parse_paths(root.join("paths"))?;
+        let expected = files::parse_expected(&std::fs::read(root.join("baseline.git"))?);
+        Ok((root, items, expected))
+    }
 }
 
 fn no_attrs(_: &BStr, _: gix_glob::pattern::Case, _: bool, _: &mut gix_attributes::search::Outcome) -> bool {
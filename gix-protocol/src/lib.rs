@@ -1,4 +1,5 @@
-//! An abstraction over [fetching][fetch()] a pack from the server.
+//! An abstraction over [fetching][fetch()] a pack from the server, along with the building blocks to [push][push] to it.
+//! With the `server` feature, it also provides the building blocks to implement the [server side][server] of the protocol.
 //!
 //! This implementation hides the transport layer, statefulness and the protocol version to the [fetch delegate][fetch::Delegate],
 //! the actual client implementation.
@@ -41,6 +42,16 @@ mod fetch_fn;
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 pub use fetch_fn::{fetch, FetchConnection};
 
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "blocking-client")]
+pub mod push;
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "server")]
+pub mod server;
+
 mod remote_progress;
 pub use remote_progress::RemoteProgress;
 
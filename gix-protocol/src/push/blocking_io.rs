@@ -0,0 +1,33 @@
+use std::io::Write;
+
+use gix_transport::client;
+
+use crate::push::Arguments;
+
+impl Arguments {
+    /// Send all commands and push-options to the server, and return a writer to stream the pack through, along with
+    /// the reader to obtain the status report from once the pack was written.
+    ///
+    /// If [`needs_pack()`](Self::needs_pack()) is `false`, no pack must be written.
+    /// Note that the writer must be dropped before reading the response as the transport may only deliver it afterwards.
+    #[allow(clippy::type_complexity)]
+    pub fn send<'a, T: client::Transport + 'a>(
+        &self,
+        transport: &'a mut T,
+    ) -> Result<(Box<dyn Write + 'a>, Box<dyn client::ExtendedBufRead<'a> + Unpin + 'a>), client::Error> {
+        let mut writer = transport.request(
+            client::WriteMode::OneLfTerminatedLinePerWriteCall,
+            client::MessageKind::Flush,
+            self.trace,
+        )?;
+        for line in self.lines() {
+            match line {
+                Some(line) => writer.write_all(&line)?,
+                None => writer.write_message(client::MessageKind::Flush)?,
+            }
+        }
+        let (mut writer, reader) = writer.into_parts();
+        writer.flush()?;
+        Ok((writer, reader))
+    }
+}
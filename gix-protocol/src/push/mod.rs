@@ -0,0 +1,226 @@
+use bstr::{BString, ByteSlice, ByteVec};
+use gix_transport::client::Capabilities;
+
+/// A single reference update to be requested from the remote as part of a push.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Command {
+    /// The id the reference currently has on the remote, or the null id if it is supposed to be created.
+    pub old_id: gix_hash::ObjectId,
+    /// The id the reference should have after the update, or the null id if it is supposed to be deleted.
+    pub new_id: gix_hash::ObjectId,
+    /// The full name of the reference on the remote, like `refs/heads/main`.
+    pub ref_name: BString,
+}
+
+impl Command {
+    /// Return `true` if this command deletes the reference on the remote.
+    pub fn is_delete(&self) -> bool {
+        self.new_id.is_null()
+    }
+
+    /// Return `true` if this command creates the reference on the remote.
+    pub fn is_create(&self) -> bool {
+        self.old_id.is_null()
+    }
+
+    /// Serialize this command in the form the remote expects it, without trailing newline.
+    pub fn to_bstring(&self) -> BString {
+        let mut out = BString::from(format!("{} {} ", self.old_id, self.new_id));
+        out.push_str(&self.ref_name);
+        out
+    }
+}
+
+/// The arguments of a push, that is all reference updates along with the capabilities to use when sending them.
+#[derive(Debug, Clone)]
+pub struct Arguments {
+    commands: Vec<Command>,
+    features: Vec<crate::command::Feature>,
+    push_options: Vec<BString>,
+
+    report_status: Option<ReportStatus>,
+    supports_atomic: bool,
+    supports_push_options: bool,
+    supports_delete_refs: bool,
+    supports_sideband: bool,
+    supports_ofs_delta: bool,
+
+    trace: bool,
+}
+
+/// The kind of status report the remote will send after receiving our commands and the pack.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReportStatus {
+    /// The `report-status` capability, with a single line per reference.
+    V1,
+    /// The `report-status-v2` capability which allows for additional options per updated reference.
+    V2,
+}
+
+impl Arguments {
+    /// Create a new instance to help setting up the commands to send to the server as part of a `push` operation,
+    /// for which `server_capabilities` are the capabilities as advertised by the remote during the handshake.
+    ///
+    /// The most capable status report, side-band and `ofs-delta` will be used if they are supported by the server.
+    /// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+    pub fn new(server_capabilities: &Capabilities, trace: bool) -> Self {
+        let has = |name: &str| server_capabilities.contains(name);
+        let report_status = if has("report-status-v2") {
+            Some(ReportStatus::V2)
+        } else if has("report-status") {
+            Some(ReportStatus::V1)
+        } else {
+            None
+        };
+        let supports_sideband = has("side-band-64k");
+        let supports_ofs_delta = has("ofs-delta");
+
+        let mut features = Vec::new();
+        match report_status {
+            Some(ReportStatus::V2) => features.push(("report-status-v2", None)),
+            Some(ReportStatus::V1) => features.push(("report-status", None)),
+            None => {}
+        }
+        if supports_sideband {
+            features.push(("side-band-64k", None));
+        }
+        if supports_ofs_delta {
+            features.push(("ofs-delta", None));
+        }
+        if let Some(value) = server_capabilities
+            .capability("object-format")
+            .and_then(|c| c.value().map(|v| v.to_str_lossy().into_owned()))
+        {
+            features.push(("object-format", Some(value.into())));
+        }
+
+        Arguments {
+            commands: Vec::new(),
+            features,
+            push_options: Vec::new(),
+            report_status,
+            supports_atomic: has("atomic"),
+            supports_push_options: has("push-options"),
+            supports_delete_refs: has("delete-refs"),
+            supports_sideband,
+            supports_ofs_delta,
+            trace,
+        }
+    }
+
+    /// Return true if there is no command to send, i.e. there is nothing to push.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+    /// Return `true` if at least one command requires a pack to be sent, which is the case unless all commands are deletions.
+    pub fn needs_pack(&self) -> bool {
+        self.commands.iter().any(|c| !c.is_delete())
+    }
+    /// Return all commands added so far.
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+    /// Return the kind of status report the server is going to send, if any.
+    pub fn report_status(&self) -> Option<ReportStatus> {
+        self.report_status
+    }
+    /// Return true if the server supports atomic pushes, where either all references are updated or none.
+    pub fn can_use_atomic(&self) -> bool {
+        self.supports_atomic
+    }
+    /// Return true if the server supports receiving push-options.
+    pub fn can_use_push_options(&self) -> bool {
+        self.supports_push_options
+    }
+    /// Return true if the server allows references to be deleted.
+    pub fn can_delete_refs(&self) -> bool {
+        self.supports_delete_refs
+    }
+    /// Return true if the server will multiplex its responses, which requires a progress handler to be set on
+    /// the response reader.
+    pub fn uses_sideband(&self) -> bool {
+        self.supports_sideband
+    }
+    /// Return true if the pack to send may contain deltas that refer to their base by offset.
+    pub fn can_use_ofs_delta(&self) -> bool {
+        self.supports_ofs_delta
+    }
+
+    /// Add `command` to the list of reference updates to perform on the remote.
+    pub fn command(&mut self, command: Command) {
+        debug_assert!(
+            !command.is_delete() || self.supports_delete_refs,
+            "'delete-refs' feature required to delete references"
+        );
+        self.commands.push(command);
+    }
+
+    /// Ask the server to apply all reference updates in a single transaction, or none at all.
+    ///
+    /// Needs to only be called once.
+    pub fn use_atomic(&mut self) {
+        debug_assert!(self.supports_atomic, "'atomic' feature required");
+        if self.supports_atomic && !self.features.iter().any(|(n, _)| *n == "atomic") {
+            self.features.push(("atomic", None));
+        }
+    }
+
+    /// Add `option` to the list of push options to transmit to the server, which passes them on to its hooks.
+    pub fn push_option(&mut self, option: impl Into<BString>) {
+        debug_assert!(self.supports_push_options, "'push-options' feature required");
+        if self.supports_push_options {
+            if self.push_options.is_empty() {
+                self.features.push(("push-options", None));
+            }
+            self.push_options.push(option.into());
+        }
+    }
+
+    /// Add the given `feature`, unconditionally.
+    ///
+    /// Note that sending an unknown or unsupported feature may cause the remote to terminate
+    /// the connection. Use this method if you know what you are doing *and* there is no specialized
+    /// method for this, e.g. [`Self::use_atomic()`].
+    pub fn add_feature(&mut self, feature: crate::command::Feature) {
+        self.features.push(feature);
+    }
+
+    /// Produce the lines to send, starting with all commands with the capabilities attached to the first one, followed
+    /// by `None` to indicate a flush packet and the push options if there are any.
+    fn lines(&self) -> Vec<Option<BString>> {
+        let mut out = Vec::with_capacity(self.commands.len() + self.push_options.len() + 2);
+        for (idx, command) in self.commands.iter().enumerate() {
+            let mut line = command.to_bstring();
+            if idx == 0 {
+                line.push_byte(0);
+                for (name, value) in &self.features {
+                    line.push_byte(b' ');
+                    line.push_str(name);
+                    if let Some(value) = value {
+                        line.push_byte(b'=');
+                        line.push_str(value.as_ref());
+                    }
+                }
+            }
+            out.push(Some(line));
+        }
+        out.push(None);
+        if !self.push_options.is_empty() {
+            out.extend(self.push_options.iter().cloned().map(Some));
+            out.push(None);
+        }
+        out
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod response;
+pub use response::Response;
+
+mod handshake;
+pub use handshake::receive_pack as handshake;
+
+mod blocking_io;
@@ -0,0 +1,243 @@
+use bstr::{BStr, BString, ByteSlice};
+use gix_transport::client;
+
+/// The error returned in the [response module][crate::push::response].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Failed to read from line reader")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    DecodePacketline(#[from] gix_transport::packetline::decode::Error),
+    #[error(transparent)]
+    Transport(#[from] client::Error),
+    #[error("The first line of the status report must be the 'unpack' status, got {line:?}")]
+    MissingUnpackStatus { line: BString },
+    #[error("The status report ended before the 'unpack' status was received")]
+    EmptyReport,
+    #[error("Encountered an unknown line prefix in {line:?}")]
+    UnknownLineType { line: BString },
+    #[error("Could not parse line {line:?} of the status report")]
+    MalformedLine { line: BString },
+    #[error("An 'option' line was received before any 'ok' line it could belong to: {line:?}")]
+    OptionWithoutRef { line: BString },
+    #[error(transparent)]
+    DecodeId(#[from] gix_hash::decode::Error),
+}
+
+impl gix_transport::IsSpuriousError for Error {
+    fn is_spurious(&self) -> bool {
+        match self {
+            Error::Io(err) => err.is_spurious(),
+            Error::Transport(err) => err.is_spurious(),
+            _ => false,
+        }
+    }
+}
+
+/// The result of unpacking the pack we sent on the server side.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnpackStatus {
+    /// The pack was received and stored successfully, or there was no pack to unpack.
+    Ok,
+    /// The pack could not be unpacked, which is the reason all reference updates failed.
+    Failed {
+        /// The message sent by the server.
+        message: BString,
+    },
+}
+
+/// Additional information provided by the server about an accepted reference update with `report-status-v2`.
+///
+/// It's typically sent if the server rewrote the update, for instance in a `proc-receive` hook.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RefOptions {
+    /// The name of the reference that was actually updated, if it differs from the requested one.
+    pub ref_name: Option<BString>,
+    /// The id the reference had before it was updated, if it differs from the one we sent.
+    pub old_id: Option<gix_hash::ObjectId>,
+    /// The id the reference has now, if it differs from the one we sent.
+    pub new_id: Option<gix_hash::ObjectId>,
+    /// If `true`, the update was not a fast-forward.
+    pub forced_update: bool,
+}
+
+/// The status of a single reference update as reported by the server.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RefStatus {
+    /// The reference was updated as requested.
+    Ok {
+        /// The full name of the reference, as sent by us.
+        ref_name: BString,
+        /// Additional information about the update, only available with `report-status-v2`.
+        options: Option<RefOptions>,
+    },
+    /// The server refused to update the reference.
+    Rejected {
+        /// The full name of the reference, as sent by us.
+        ref_name: BString,
+        /// The reason for the rejection, like `non-fast-forward` or `deny deleting current branch`.
+        reason: BString,
+    },
+}
+
+impl RefStatus {
+    /// Return the name of the reference this status is about.
+    pub fn ref_name(&self) -> &BStr {
+        match self {
+            RefStatus::Ok { ref_name, .. } | RefStatus::Rejected { ref_name, .. } => ref_name.as_ref(),
+        }
+    }
+
+    /// Return `true` if the update was rejected.
+    pub fn is_rejected(&self) -> bool {
+        matches!(self, RefStatus::Rejected { .. })
+    }
+}
+
+/// A representation of the status report sent by the server after receiving a push.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Response {
+    unpack: UnpackStatus,
+    refs: Vec<RefStatus>,
+}
+
+impl Response {
+    /// Return the status of unpacking the pack on the server side.
+    pub fn unpack_status(&self) -> &UnpackStatus {
+        &self.unpack
+    }
+
+    /// Return the status of each reference update, in the order the server reported them.
+    pub fn ref_updates(&self) -> &[RefStatus] {
+        &self.refs
+    }
+
+    /// Return the status of the reference update for `ref_name`, if the server reported it.
+    pub fn ref_update(&self, ref_name: &BStr) -> Option<&RefStatus> {
+        self.refs.iter().find(|r| r.ref_name() == ref_name)
+    }
+
+    /// Return `true` if the pack was unpacked and all reference updates were accepted.
+    pub fn is_success(&self) -> bool {
+        self.unpack == UnpackStatus::Ok && !self.refs.iter().any(RefStatus::is_rejected)
+    }
+
+    /// Parse a full status report from `lines`, each without the packetline header and possibly with a trailing newline.
+    ///
+    /// This works for both `report-status` and `report-status-v2`, as the latter is a superset of the former.
+    pub fn from_lines<'a>(lines: impl IntoIterator<Item = &'a [u8]>) -> Result<Response, Error> {
+        let mut lines = lines
+            .into_iter()
+            .map(|line| line.as_bstr().trim_end_with(|c| c == '\n'));
+        let first = lines.next().ok_or(Error::EmptyReport)?;
+        let unpack = match first.strip_prefix(b"unpack ") {
+            Some(b"ok") => UnpackStatus::Ok,
+            Some(message) => UnpackStatus::Failed {
+                message: message.into(),
+            },
+            None => return Err(Error::MissingUnpackStatus { line: first.into() }),
+        };
+
+        let mut refs = Vec::new();
+        for line in lines {
+            parse_ref_line(line.as_bstr(), &mut refs)?;
+        }
+        Ok(Response { unpack, refs })
+    }
+}
+
+fn parse_ref_line(line: &BStr, refs: &mut Vec<RefStatus>) -> Result<(), Error> {
+    let malformed = || Error::MalformedLine { line: line.into() };
+    if let Some(ref_name) = line.strip_prefix(b"ok ") {
+        refs.push(RefStatus::Ok {
+            ref_name: ref_name.into(),
+            options: None,
+        });
+    } else if let Some(rest) = line.strip_prefix(b"ng ") {
+        let (ref_name, reason) = match rest.find_byte(b' ') {
+            Some(pos) => (&rest[..pos], &rest[pos + 1..]),
+            None => (rest, &b""[..]),
+        };
+        if ref_name.is_empty() {
+            return Err(malformed());
+        }
+        refs.push(RefStatus::Rejected {
+            ref_name: ref_name.into(),
+            reason: reason.into(),
+        });
+    } else if let Some(option) = line.strip_prefix(b"option ") {
+        let options = match refs.last_mut() {
+            Some(RefStatus::Ok { options, .. }) => options.get_or_insert_with(Default::default),
+            _ => return Err(Error::OptionWithoutRef { line: line.into() }),
+        };
+        let (key, value) = match option.find_byte(b' ') {
+            Some(pos) => (&option[..pos], Some(&option[pos + 1..])),
+            None => (option, None),
+        };
+        match (key, value) {
+            (b"refname", Some(name)) => options.ref_name = Some(name.into()),
+            (b"old-oid", Some(hex)) => options.old_id = Some(gix_hash::ObjectId::from_hex(hex)?),
+            (b"new-oid", Some(hex)) => options.new_id = Some(gix_hash::ObjectId::from_hex(hex)?),
+            (b"forced-update", None) => options.forced_update = true,
+            _ => return Err(malformed()),
+        }
+    } else {
+        return Err(Error::UnknownLineType { line: line.into() });
+    }
+    Ok(())
+}
+
+mod blocking_io {
+    use gix_transport::{client, packetline::PacketLineRef};
+
+    use crate::push::{response::Error, Response};
+
+    impl Response {
+        /// Read the status report from `reader` which is expected to be positioned right after the pack was sent.
+        ///
+        /// If `uses_sideband` is `true`, the report is expected to be multiplexed in the data channel, which means that
+        /// a progress handler must have been [set][client::ExtendedBufRead::set_progress_handler()] on `reader` to receive
+        /// messages sent by the server and its hooks.
+        pub fn from_line_reader<'a>(
+            reader: &mut (dyn client::ExtendedBufRead<'a> + Unpin + 'a),
+            uses_sideband: bool,
+            trace: bool,
+        ) -> Result<Response, Error> {
+            let mut lines = Vec::new();
+            if uses_sideband {
+                let mut demuxed =
+                    gix_transport::packetline::StreamingPeekableIter::new(&mut *reader, &[PacketLineRef::Flush], trace);
+                collect_lines(&mut demuxed, &mut lines)?;
+                drop(demuxed);
+                // Consume everything up to the final flush of the outer stream, even though there shouldn't be more.
+                std::io::copy(reader, &mut std::io::sink())?;
+            } else {
+                while let Some(line) = reader.readline() {
+                    let line = line??;
+                    if let Some(line) = line.as_slice() {
+                        lines.push(line.to_owned());
+                    }
+                }
+            }
+            Response::from_lines(lines.iter().map(Vec::as_slice))
+        }
+    }
+
+    fn collect_lines<T: std::io::Read>(
+        iter: &mut gix_transport::packetline::StreamingPeekableIter<T>,
+        out: &mut Vec<Vec<u8>>,
+    ) -> Result<(), Error> {
+        while let Some(line) = iter.read_line() {
+            let line = line??;
+            if let Some(line) = line.as_slice() {
+                out.push(line.to_owned());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use std::io;
+
+use bstr::{BString, ByteVec};
+use gix_packetline_blocking::encode;
+
+use crate::server::{Error, Ref};
+
+/// The arguments of the `ls-refs` command of protocol V2.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Arguments {
+    /// If `true`, show the target of symbolic references.
+    pub symrefs: bool,
+    /// If `true`, show the peeled object of annotated tags.
+    pub peel: bool,
+    /// If `true`, show `HEAD` even if it is unborn.
+    pub unborn: bool,
+    /// Only show references that start with one of these prefixes, or all references if empty.
+    pub prefixes: Vec<BString>,
+}
+
+impl Arguments {
+    /// Parse the `arguments` of an `ls-refs` command.
+    pub fn from_arguments(arguments: &[BString]) -> Result<Self, Error> {
+        let mut out = Arguments::default();
+        for argument in arguments {
+            match argument.as_slice() {
+                b"symrefs" => out.symrefs = true,
+                b"peel" => out.peel = true,
+                b"unborn" => out.unborn = true,
+                _ => match argument.strip_prefix(b"ref-prefix ") {
+                    Some(prefix) => out.prefixes.push(prefix.into()),
+                    None => {
+                        return Err(Error::UnknownArgument {
+                            argument: argument.clone(),
+                        })
+                    }
+                },
+            }
+        }
+        Ok(out)
+    }
+
+    /// Return `true` if `name` should be listed according to our prefixes.
+    pub fn matches(&self, name: &[u8]) -> bool {
+        self.prefixes.is_empty() || self.prefixes.iter().any(|prefix| name.starts_with(prefix))
+    }
+}
+
+/// Write all `refs` that match `arguments` to `out` as response to the `ls-refs` command, followed by a flush packet.
+pub fn write_response(mut out: impl io::Write, refs: &[Ref], arguments: &Arguments) -> io::Result<()> {
+    for r in refs.iter().filter(|r| arguments.matches(&r.name)) {
+        let mut line = match r.target {
+            Some(target) => BString::from(format!("{target} ")),
+            None if arguments.unborn => BString::from("unborn "),
+            None => continue,
+        };
+        line.push_str(&r.name);
+        if let Some(symref_target) = r.symref_target.as_ref().filter(|_| arguments.symrefs) {
+            line.push_str(" symref-target:");
+            line.push_str(symref_target);
+        }
+        if let Some(peeled) = r.peeled.filter(|_| arguments.peel) {
+            line.push_str(format!(" peeled:{peeled}"));
+        }
+        encode::text_to_write(&line, &mut out)?;
+    }
+    encode::flush_to_write(&mut out)?;
+    Ok(())
+}
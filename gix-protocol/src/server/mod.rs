@@ -0,0 +1,226 @@
+use std::io;
+
+use bstr::{BString, ByteSlice, ByteVec};
+use gix_packetline_blocking::{encode, PacketLineRef, StreamingPeekableIter};
+
+/// A re-export of the packetline implementation used by the server, to allow creating compatible line readers.
+pub use gix_packetline_blocking as packetline;
+
+/// A reference as advertised by the server to its clients.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ref {
+    /// The full name of the reference, like `HEAD` or `refs/heads/main`.
+    pub name: BString,
+    /// The object the reference points to, or `None` if it's unborn, which is only possible for `HEAD`.
+    pub target: Option<gix_hash::ObjectId>,
+    /// The object the reference ultimately points to after peeling annotated tags, if it's different from `target`.
+    pub peeled: Option<gix_hash::ObjectId>,
+    /// The full name of the reference this one is pointing to, if it is symbolic.
+    pub symref_target: Option<BString>,
+}
+
+mod error {
+    use bstr::BString;
+
+    /// The error returned when reading requests of clients.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Failed to read from the client")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        DecodePacketline(#[from] gix_packetline_blocking::decode::Error),
+        #[error("The client sent a line that couldn't be parsed: {line:?}")]
+        MalformedLine { line: BString },
+        #[error("The client sent an argument we don't understand: {argument:?}")]
+        UnknownArgument { argument: BString },
+        #[error("The client sent a request without a command")]
+        MissingCommand,
+        #[error("The client ended the connection unexpectedly")]
+        UnexpectedEof,
+    }
+}
+pub use error::Error;
+
+/// Write the reference advertisement of protocol V0 and V1 for all `refs` to `out`, with `capabilities` attached to the first line,
+/// followed by a flush packet.
+///
+/// Unborn references are skipped, and if there is no reference to advertise, the capabilities are transmitted with
+/// a placeholder line using the null id of `object_hash`.
+/// Note that the `version 1` line has to be written by the caller if the client asked for it.
+pub fn write_v1_advertisement(
+    mut out: impl io::Write,
+    refs: &[Ref],
+    capabilities: &[crate::command::Feature],
+    object_hash: gix_hash::Kind,
+) -> io::Result<()> {
+    let mut capabilities_line = BString::default();
+    for (idx, (name, value)) in capabilities.iter().enumerate() {
+        if idx != 0 {
+            capabilities_line.push_byte(b' ');
+        }
+        capabilities_line.push_str(name);
+        if let Some(value) = value {
+            capabilities_line.push_byte(b'=');
+            capabilities_line.push_str(value.as_bytes());
+        }
+    }
+
+    let mut is_first = true;
+    for (name, target, peeled) in refs
+        .iter()
+        .filter_map(|r| r.target.map(|target| (r.name.as_bstr(), target, r.peeled)))
+    {
+        let mut line = BString::from(format!("{target} "));
+        line.push_str(name);
+        if is_first {
+            line.push_byte(0);
+            line.push_str(&capabilities_line);
+            is_first = false;
+        }
+        encode::text_to_write(&line, &mut out)?;
+        if let Some(peeled) = peeled {
+            let mut line = BString::from(format!("{peeled} "));
+            line.push_str(name);
+            line.push_str("^{}");
+            encode::text_to_write(&line, &mut out)?;
+        }
+    }
+    if is_first {
+        let mut line = BString::from(format!("{} capabilities^{{}}\0", object_hash.null()));
+        line.push_str(&capabilities_line);
+        encode::text_to_write(&line, &mut out)?;
+    }
+    encode::flush_to_write(&mut out)?;
+    Ok(())
+}
+
+/// Write the capability advertisement of protocol V2 to `out`, which includes the version line, all `capabilities` with their
+/// optional value, and a final flush packet.
+pub fn write_v2_capabilities(mut out: impl io::Write, capabilities: &[crate::command::Feature]) -> io::Result<()> {
+    encode::text_to_write(b"version 2", &mut out)?;
+    for (name, value) in capabilities {
+        let mut line = BString::from(*name);
+        if let Some(value) = value {
+            line.push_byte(b'=');
+            line.push_str(value.as_bytes());
+        }
+        encode::text_to_write(&line, &mut out)?;
+    }
+    encode::flush_to_write(&mut out)?;
+    Ok(())
+}
+
+/// A writer to multiplex data and progress messages as side-band packet lines, as used when sending a pack.
+pub struct SidebandWriter<W> {
+    inner: W,
+    max_data_len: usize,
+}
+
+impl<W: io::Write> SidebandWriter<W> {
+    /// Create a new instance that writes into `inner` using the large packet lines of the `side-band-64k` capability.
+    pub fn new(inner: W) -> Self {
+        Self::with_max_line_len(inner, 65520)
+    }
+
+    /// Create a new instance that writes into `inner` with lines of at most `max_line_len` bytes, including the 4 bytes of
+    /// the length prefix and the band.
+    ///
+    /// The `side-band` capability uses `1000`, and `side-band-64k` uses `65520`.
+    pub fn with_max_line_len(inner: W, max_line_len: usize) -> Self {
+        SidebandWriter {
+            inner,
+            max_data_len: max_line_len.saturating_sub(5).max(1),
+        }
+    }
+
+    /// Send `message` on the progress channel, which is displayed by the client.
+    pub fn progress(&mut self, message: &[u8]) -> io::Result<()> {
+        self.write_band(gix_packetline_blocking::Channel::Progress, message)
+    }
+
+    /// Send `message` on the error channel, which causes the client to abort.
+    pub fn error(&mut self, message: &[u8]) -> io::Result<()> {
+        self.write_band(gix_packetline_blocking::Channel::Error, message)
+    }
+
+    /// Return the writer we were created with.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn write_band(&mut self, band: gix_packetline_blocking::Channel, data: &[u8]) -> io::Result<()> {
+        for chunk in data.chunks(self.max_data_len) {
+            encode::band_to_write(band, chunk, &mut self.inner)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: io::Write> io::Write for SidebandWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let len = buf.len().min(self.max_data_len);
+        encode::band_to_write(gix_packetline_blocking::Channel::Data, &buf[..len], &mut self.inner)?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Lines as sent by the client, up to the packet line that terminated them.
+struct Section {
+    lines: Vec<BString>,
+    stopped_at: Option<PacketLineRef<'static>>,
+}
+
+/// Read all lines until a flush or delimiter packet, or return `None` if the client closed the connection before sending anything.
+fn read_section<R: io::Read>(lines: &mut StreamingPeekableIter<R>) -> Result<Option<Section>, Error> {
+    lines.reset_with(&[PacketLineRef::Flush, PacketLineRef::Delimiter]);
+    let mut out = Vec::new();
+    while let Some(line) = lines.read_line() {
+        let line = match line {
+            Ok(line) => line?,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                return if out.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(Error::UnexpectedEof)
+                };
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if let Some(data) = line.as_bstr() {
+            out.push(data.trim_end_with(|c| c == '\n').into());
+        }
+    }
+    Ok(Some(Section {
+        lines: out,
+        stopped_at: lines.stopped_at(),
+    }))
+}
+
+fn parse_id(line: &BString, value: &[u8]) -> Result<gix_hash::ObjectId, Error> {
+    gix_hash::ObjectId::from_hex(value).map_err(|_| Error::MalformedLine { line: line.clone() })
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod v2;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod ls_refs;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod upload_pack;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod receive_pack;
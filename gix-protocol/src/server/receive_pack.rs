@@ -0,0 +1,162 @@
+use std::io;
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_hash::ObjectId;
+use gix_packetline_blocking::{encode, StreamingPeekableIter};
+
+use crate::server::{parse_id, read_section, Error};
+
+/// A single reference update requested by the client.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Command {
+    /// The id the client expects the reference to have, or the null id if it should be created.
+    pub old_id: ObjectId,
+    /// The id the reference should have after the update, or the null id if it should be deleted.
+    pub new_id: ObjectId,
+    /// The full name of the reference to update, like `refs/heads/main`.
+    pub ref_name: BString,
+}
+
+impl Command {
+    /// Return `true` if this command deletes the reference.
+    pub fn is_delete(&self) -> bool {
+        self.new_id.is_null()
+    }
+
+    /// Return `true` if this command creates the reference.
+    pub fn is_create(&self) -> bool {
+        self.old_id.is_null()
+    }
+}
+
+/// The kind of status report the client asked for.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReportStatus {
+    /// The `report-status` capability.
+    V1,
+    /// The `report-status-v2` capability.
+    V2,
+}
+
+/// The request of a client that wants to push, consisting of all reference updates along with the capabilities it chose.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Request {
+    /// The reference updates to perform, in the order they were sent.
+    pub commands: Vec<Command>,
+    /// The commits at the boundary of a shallow client.
+    pub shallow: Vec<ObjectId>,
+    /// The kind of status report to send once the updates were performed, if any.
+    pub report_status: Option<ReportStatus>,
+    /// If `true`, either all reference updates succeed, or none of them is performed.
+    pub atomic: bool,
+    /// If `true`, the client sends push options right after the commands, to be read with [`read_push_options()`].
+    pub push_options: bool,
+    /// If `true`, the status report and progress are sent using the `side-band-64k` capability.
+    pub sideband: bool,
+    /// If `true`, no progress should be sent.
+    pub quiet: bool,
+    /// The name of the client program, if it was sent.
+    pub agent: Option<BString>,
+}
+
+impl Request {
+    /// Read all commands of a client from `lines`, with the capabilities attached to the first one, along with the
+    /// `shallow` lines sent before them by shallow clients.
+    ///
+    /// Return `None` if the client doesn't want to update anything, which ends the interaction.
+    pub fn read_from<R: io::Read>(lines: &mut StreamingPeekableIter<R>) -> Result<Option<Self>, Error> {
+        let Some(section) = read_section(lines)? else {
+            return Ok(None);
+        };
+        let mut out = Request::default();
+        for line in &section.lines {
+            if let Some(id) = line.strip_prefix(b"shallow ") {
+                out.shallow.push(parse_id(line, id)?);
+                continue;
+            }
+            let mut command = line.as_bstr();
+            if out.commands.is_empty() {
+                if let Some(pos) = line.find_byte(0) {
+                    for capability in line[pos + 1..].split_str(b" ").filter(|c| !c.is_empty()) {
+                        out.parse_capability(capability.as_bstr());
+                    }
+                    command = line[..pos].as_bstr();
+                }
+            }
+            let mut tokens = command.splitn_str(3, b" ");
+            let (Some(old_id), Some(new_id), Some(ref_name)) = (tokens.next(), tokens.next(), tokens.next()) else {
+                return Err(Error::MalformedLine { line: line.clone() });
+            };
+            out.commands.push(Command {
+                old_id: parse_id(line, old_id)?,
+                new_id: parse_id(line, new_id)?,
+                ref_name: ref_name.into(),
+            });
+        }
+        Ok((!out.commands.is_empty()).then_some(out))
+    }
+
+    /// Return `true` if a pack follows the commands, which is the case unless all commands are deletions.
+    pub fn needs_pack(&self) -> bool {
+        self.commands.iter().any(|c| !c.is_delete())
+    }
+
+    fn parse_capability(&mut self, capability: &BStr) {
+        match capability.as_bytes() {
+            b"report-status" => self.report_status = self.report_status.or(Some(ReportStatus::V1)),
+            b"report-status-v2" => self.report_status = Some(ReportStatus::V2),
+            b"atomic" => self.atomic = true,
+            b"push-options" => self.push_options = true,
+            b"side-band-64k" => self.sideband = true,
+            b"quiet" => self.quiet = true,
+            _ => {
+                if let Some(agent) = capability.strip_prefix(b"agent=") {
+                    self.agent = Some(agent.into());
+                }
+            }
+        }
+    }
+}
+
+/// Read the push options the client sends after its commands if the `push-options` capability was used.
+pub fn read_push_options<R: io::Read>(lines: &mut StreamingPeekableIter<R>) -> Result<Vec<BString>, Error> {
+    Ok(read_section(lines)?.ok_or(Error::UnexpectedEof)?.lines)
+}
+
+/// The outcome of a single reference update, to be sent to the client as part of the status report.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RefStatus {
+    /// The full name of the reference as sent by the client.
+    pub ref_name: BString,
+    /// The reason for rejecting the update, or `None` if the reference was updated.
+    pub rejection: Option<BString>,
+}
+
+/// Write the status report to `out`, which starts with the result of unpacking, either `Ok(())` or the error message
+/// of the failure, followed by the `status` of each reference update and a flush packet.
+///
+/// If the client uses a side-band, `out` should be a [`SidebandWriter`](crate::server::SidebandWriter) and a flush
+/// packet should be sent afterwards.
+pub fn write_report_status(mut out: impl io::Write, unpack: Result<(), &BStr>, status: &[RefStatus]) -> io::Result<()> {
+    let mut line = BString::from("unpack ");
+    match unpack {
+        Ok(()) => line.push_str("ok"),
+        Err(message) => line.push_str(message),
+    }
+    encode::text_to_write(&line, &mut out)?;
+    for RefStatus { ref_name, rejection } in status {
+        let mut line = BString::from(if rejection.is_some() { "ng " } else { "ok " });
+        line.push_str(ref_name);
+        if let Some(reason) = rejection {
+            line.push_byte(b' ');
+            line.push_str(reason);
+        }
+        encode::text_to_write(&line, &mut out)?;
+    }
+    encode::flush_to_write(&mut out)?;
+    Ok(())
+}
@@ -0,0 +1,327 @@
+use std::io;
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_packetline_blocking::{encode, PacketLineRef, StreamingPeekableIter};
+
+use crate::server::{parse_id, read_section, Error};
+
+/// The kind of acknowledgements a client using protocol V0 or V1 expects during negotiation.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MultiAck {
+    /// Only the first common object is acknowledged.
+    #[default]
+    None,
+    /// The `multi_ack` capability, which acknowledges all common objects with `continue`.
+    Basic,
+    /// The `multi_ack_detailed` capability, which distinguishes between `common` and `ready` acknowledgements.
+    Detailed,
+}
+
+/// The kind of side-band a client using protocol V0 or V1 expects the pack to be sent with.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sideband {
+    /// The `side-band` capability with lines of up to 1000 bytes.
+    Small,
+    /// The `side-band-64k` capability with lines of up to 65520 bytes.
+    Large,
+}
+
+impl Sideband {
+    /// Return the maximum length of a line, including the length prefix.
+    pub fn max_line_len(&self) -> usize {
+        match self {
+            Sideband::Small => 1000,
+            Sideband::Large => 65520,
+        }
+    }
+}
+
+/// All arguments of a `fetch` request, as sent by the client using any protocol version.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Arguments {
+    /// The objects the client wants.
+    pub wants: Vec<ObjectId>,
+    /// The objects the client has, which only contains the objects of the first round of negotiation with protocol V2.
+    pub haves: Vec<ObjectId>,
+    /// The commits at the boundary of a shallow client.
+    pub shallow: Vec<ObjectId>,
+    /// The amount of commits to send along the history of each want, if set.
+    pub depth: Option<u32>,
+    /// If `true`, `depth` is relative to the current shallow boundary of the client.
+    pub deepen_relative: bool,
+    /// Only send commits that are more recent than the given time.
+    pub deepen_since: Option<gix_date::SecondsSinceUnixEpoch>,
+    /// Don't send commits that are reachable from any of the given references.
+    pub deepen_not: Vec<BString>,
+    /// If `true`, the client is done with negotiation and wants the pack right away.
+    pub done: bool,
+    /// If `true`, the pack may contain deltas against objects the client has.
+    pub thin_pack: bool,
+    /// If `true`, no progress should be sent.
+    pub no_progress: bool,
+    /// If `true`, send annotated tags that point to objects that are sent as well.
+    pub include_tag: bool,
+    /// If `true`, the pack may contain deltas that refer to their base by offset.
+    pub ofs_delta: bool,
+    /// If `true`, the server shouldn't send a pack until the client sent `done`, which is only used by protocol V2.
+    pub wait_for_done: bool,
+    /// The kind of acknowledgements to send, only used by protocol V0 and V1.
+    pub multi_ack: MultiAck,
+    /// If `true`, the client won't send `done` after the server indicated it is ready, only used by protocol V0 and V1.
+    pub no_done: bool,
+    /// The side-band to send the pack with, which is always set with protocol V2.
+    pub sideband: Option<Sideband>,
+    /// The name of the client program, if it was sent.
+    pub agent: Option<BString>,
+}
+
+impl Arguments {
+    /// Return `true` if the client wants to change the depth of its history, which requires a shallow update to be sent.
+    pub fn deepens(&self) -> bool {
+        self.depth.is_some() || self.deepen_since.is_some() || !self.deepen_not.is_empty()
+    }
+
+    /// Parse the `arguments` of a `fetch` command as sent with protocol V2.
+    pub fn from_v2_arguments(arguments: &[BString]) -> Result<Self, Error> {
+        let mut out = Arguments {
+            sideband: Some(Sideband::Large),
+            multi_ack: MultiAck::Detailed,
+            ..Default::default()
+        };
+        for argument in arguments {
+            if !out.parse_shared_argument(argument.as_bstr())? {
+                match argument.as_slice() {
+                    b"done" => out.done = true,
+                    b"wait-for-done" => out.wait_for_done = true,
+                    _ => match argument.strip_prefix(b"have ") {
+                        Some(id) => out.haves.push(parse_id(argument, id)?),
+                        None => {
+                            return Err(Error::UnknownArgument {
+                                argument: argument.clone(),
+                            })
+                        }
+                    },
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Read the initial request of a client using protocol V0 or V1 from `lines`, which consists of all wants with
+    /// the capabilities attached to the first one, and the shallow and deepen instructions.
+    ///
+    /// Return `None` if the client doesn't want anything, which ends the interaction.
+    pub fn read_v1_request<R: io::Read>(lines: &mut StreamingPeekableIter<R>) -> Result<Option<Self>, Error> {
+        let Some(section) = read_section(lines)? else {
+            return Ok(None);
+        };
+        let mut out = Arguments::default();
+        for (idx, line) in section.lines.iter().enumerate() {
+            let mut line = line.as_bstr();
+            if idx == 0 {
+                let Some(rest) = line.strip_prefix(b"want ") else {
+                    return Err(Error::MalformedLine { line: line.into() });
+                };
+                let id_len = rest.find_byte(b' ').unwrap_or(rest.len());
+                for capability in rest[id_len..].split_str(b" ").filter(|c| !c.is_empty()) {
+                    out.parse_v1_capability(capability.as_bstr());
+                }
+                line = line[..5 + id_len].as_bstr();
+            }
+            if !out.parse_shared_argument(line)? {
+                return Err(Error::UnknownArgument { argument: line.into() });
+            }
+        }
+        Ok((!out.wants.is_empty()).then_some(out))
+    }
+
+    fn parse_v1_capability(&mut self, capability: &BStr) {
+        match capability.as_bytes() {
+            b"multi_ack" => self.multi_ack = self.multi_ack.max(MultiAck::Basic),
+            b"multi_ack_detailed" => self.multi_ack = MultiAck::Detailed,
+            b"no-done" => self.no_done = true,
+            b"side-band" => self.sideband = self.sideband.or(Some(Sideband::Small)),
+            b"side-band-64k" => self.sideband = Some(Sideband::Large),
+            b"thin-pack" => self.thin_pack = true,
+            b"no-progress" => self.no_progress = true,
+            b"include-tag" => self.include_tag = true,
+            b"ofs-delta" => self.ofs_delta = true,
+            b"deepen-relative" => self.deepen_relative = true,
+            _ => {
+                if let Some(agent) = capability.strip_prefix(b"agent=") {
+                    self.agent = Some(agent.into());
+                }
+            }
+        }
+    }
+
+    /// Parse an argument that is valid in all protocol versions, and return `true` if it was known.
+    fn parse_shared_argument(&mut self, argument: &BStr) -> Result<bool, Error> {
+        let line: BString = argument.into();
+        match argument.as_bytes() {
+            b"thin-pack" => self.thin_pack = true,
+            b"no-progress" => self.no_progress = true,
+            b"include-tag" => self.include_tag = true,
+            b"ofs-delta" => self.ofs_delta = true,
+            b"deepen-relative" => self.deepen_relative = true,
+            _ => {
+                let Some((name, value)) = argument.split_once_str(b" ") else {
+                    return Ok(false);
+                };
+                match name {
+                    b"want" => self.wants.push(parse_id(&line, value)?),
+                    b"shallow" => self.shallow.push(parse_id(&line, value)?),
+                    b"deepen" => {
+                        self.depth = Some(
+                            value
+                                .to_str()
+                                .ok()
+                                .and_then(|v| v.parse().ok())
+                                .ok_or(Error::MalformedLine { line })?,
+                        )
+                    }
+                    b"deepen-since" => {
+                        self.deepen_since = Some(
+                            value
+                                .to_str()
+                                .ok()
+                                .and_then(|v| v.parse().ok())
+                                .ok_or(Error::MalformedLine { line })?,
+                        )
+                    }
+                    b"deepen-not" => self.deepen_not.push(value.into()),
+                    _ => return Ok(false),
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// A single round of negotiation as sent by clients using protocol V0 or V1.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Haves {
+    /// The objects the client has.
+    pub ids: Vec<ObjectId>,
+    /// If `true`, the client sent `done` and waits for the pack, otherwise it sent a flush packet and waits for our acknowledgements.
+    pub done: bool,
+}
+
+/// Read the next round of negotiation sent by a client using protocol V0 or V1 from `lines`.
+///
+/// Return `None` if the client closed the connection, which is expected for stateless clients once they sent all their haves.
+pub fn read_v1_haves<R: io::Read>(lines: &mut StreamingPeekableIter<R>) -> Result<Option<Haves>, Error> {
+    lines.reset_with(&[PacketLineRef::Flush]);
+    let mut out = Haves::default();
+    while let Some(line) = lines.read_line() {
+        let line = match line {
+            Ok(line) => line?,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof && out.ids.is_empty() => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let Some(line) = line.as_bstr() else { continue };
+        let line: BString = line.trim_end_with(|c| c == '\n').into();
+        if line == "done" {
+            out.done = true;
+            return Ok(Some(out));
+        }
+        match line.strip_prefix(b"have ") {
+            Some(id) => out.ids.push(parse_id(&line, id)?),
+            None => return Err(Error::UnknownArgument { argument: line }),
+        }
+    }
+    Ok(Some(out))
+}
+
+/// An acknowledgement to send to clients using protocol V0 or V1 during negotiation.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Acknowledgement {
+    /// The object is common, which is sent without `multi_ack` or as final acknowledgement.
+    Ack(ObjectId),
+    /// The object is common, and negotiation should continue, used with `multi_ack`.
+    Continue(ObjectId),
+    /// The object is common, used with `multi_ack_detailed`.
+    Common(ObjectId),
+    /// We are ready to send a pack, used with `multi_ack_detailed`.
+    Ready(ObjectId),
+    /// There is no common object (yet).
+    Nak,
+}
+
+impl Acknowledgement {
+    /// Write this instance as packet line to `out`.
+    pub fn write_to(&self, out: impl io::Write) -> io::Result<()> {
+        let line = match self {
+            Acknowledgement::Ack(id) => format!("ACK {id}"),
+            Acknowledgement::Continue(id) => format!("ACK {id} continue"),
+            Acknowledgement::Common(id) => format!("ACK {id} common"),
+            Acknowledgement::Ready(id) => format!("ACK {id} ready"),
+            Acknowledgement::Nak => "NAK".into(),
+        };
+        encode::text_to_write(line.as_bytes(), out).map(|_| ())
+    }
+}
+
+/// Write the `shallow` and `unshallow` lines for clients using protocol V0 or V1 to `out`, followed by a flush packet.
+pub fn write_v1_shallow_update(
+    mut out: impl io::Write,
+    shallow: &[ObjectId],
+    unshallow: &[ObjectId],
+) -> io::Result<()> {
+    write_shallow_lines(&mut out, shallow, unshallow)?;
+    encode::flush_to_write(&mut out)?;
+    Ok(())
+}
+
+/// Write the `acknowledgments` section of the response to a `fetch` command of protocol V2 to `out`, with `common` being
+/// all objects of the client that we have.
+///
+/// If `ready` is `true`, the section ends with a delimiter as the pack will follow, otherwise the response ends with
+/// a flush packet and the client is expected to continue negotiation.
+pub fn write_acknowledgments(mut out: impl io::Write, common: &[ObjectId], ready: bool) -> io::Result<()> {
+    encode::text_to_write(b"acknowledgments", &mut out)?;
+    if common.is_empty() {
+        encode::text_to_write(b"NAK", &mut out)?;
+    }
+    for id in common {
+        encode::text_to_write(format!("ACK {id}").as_bytes(), &mut out)?;
+    }
+    if ready {
+        encode::text_to_write(b"ready", &mut out)?;
+        encode::delim_to_write(&mut out)?;
+    } else {
+        encode::flush_to_write(&mut out)?;
+    }
+    Ok(())
+}
+
+/// Write the `shallow-info` section of the response to a `fetch` command of protocol V2 to `out`, followed by a delimiter.
+pub fn write_shallow_info(mut out: impl io::Write, shallow: &[ObjectId], unshallow: &[ObjectId]) -> io::Result<()> {
+    encode::text_to_write(b"shallow-info", &mut out)?;
+    write_shallow_lines(&mut out, shallow, unshallow)?;
+    encode::delim_to_write(&mut out)?;
+    Ok(())
+}
+
+/// Write the header of the `packfile` section of the response to a `fetch` command of protocol V2 to `out`.
+///
+/// It must be followed by the pack, sent through a [`SidebandWriter`](crate::server::SidebandWriter), and a flush packet.
+pub fn write_packfile_header(out: impl io::Write) -> io::Result<()> {
+    encode::text_to_write(b"packfile", out).map(|_| ())
+}
+
+fn write_shallow_lines(mut out: impl io::Write, shallow: &[ObjectId], unshallow: &[ObjectId]) -> io::Result<()> {
+    for id in shallow {
+        encode::text_to_write(format!("shallow {id}").as_bytes(), &mut out)?;
+    }
+    for id in unshallow {
+        encode::text_to_write(format!("unshallow {id}").as_bytes(), &mut out)?;
+    }
+    Ok(())
+}
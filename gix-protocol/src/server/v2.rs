@@ -0,0 +1,55 @@
+use std::io;
+
+use bstr::{BString, ByteSlice};
+use gix_packetline_blocking::{PacketLineRef, StreamingPeekableIter};
+
+use crate::server::{read_section, Error};
+
+/// A command as sent by a client using protocol V2.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Command {
+    /// The name of the command, like `ls-refs` or `fetch`.
+    pub name: BString,
+    /// The capabilities the client sent along with the command, like `agent=git/2.44.0` or `object-format=sha1`.
+    pub capabilities: Vec<BString>,
+    /// The arguments of the command, one per line, without trailing newline.
+    pub arguments: Vec<BString>,
+}
+
+impl Command {
+    /// Return the value of the capability with `name`, if it was sent by the client.
+    pub fn capability(&self, name: &str) -> Option<&bstr::BStr> {
+        self.capabilities.iter().find_map(|c| {
+            c.strip_prefix(name.as_bytes())
+                .and_then(|rest| rest.strip_prefix(b"="))
+                .map(ByteSlice::as_bstr)
+        })
+    }
+}
+
+/// Read the next command from `lines`, or return `None` if the client indicated the end of the session by sending
+/// a flush packet or by closing the connection.
+pub fn read_command<R: io::Read>(lines: &mut StreamingPeekableIter<R>) -> Result<Option<Command>, Error> {
+    let Some(section) = read_section(lines)? else {
+        return Ok(None);
+    };
+    if section.lines.is_empty() && section.stopped_at == Some(PacketLineRef::Flush) {
+        return Ok(None);
+    }
+    let mut keys = section.lines.into_iter();
+    let name = keys
+        .next()
+        .and_then(|line| line.strip_prefix(b"command=").map(Into::into))
+        .ok_or(Error::MissingCommand)?;
+    let capabilities = keys.collect();
+    let arguments = match section.stopped_at {
+        Some(PacketLineRef::Delimiter) => read_section(lines)?.ok_or(Error::UnexpectedEof)?.lines,
+        _ => Vec::new(),
+    };
+    Ok(Some(Command {
+        name,
+        capabilities,
+        arguments,
+    }))
+}
@@ -0,0 +1,291 @@
+fn id(hex: &str) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from_hex(hex.as_bytes()).expect("expect valid hex id")
+}
+
+fn pkt(data: &[u8]) -> Vec<u8> {
+    let mut out = format!("{:04x}", data.len() + 4).into_bytes();
+    out.extend_from_slice(data);
+    out
+}
+
+mod arguments {
+    use bstr::ByteSlice;
+    use gix_protocol::push::{self, Command, ReportStatus};
+    use gix_transport::{client::Capabilities, Protocol};
+
+    use crate::push::id;
+
+    fn capabilities(caps: &str) -> Capabilities {
+        Capabilities::from_bytes(
+            format!("0000000000000000000000000000000000000000 capabilities^{{}}\0{caps}").as_bytes(),
+        )
+        .expect("valid capabilities")
+        .0
+    }
+
+    fn transport(out: &mut Vec<u8>) -> gix_transport::client::git::Connection<&'static [u8], &mut Vec<u8>> {
+        gix_transport::client::git::Connection::new(
+            &[],
+            out,
+            Protocol::V1,
+            b"does/not/matter".as_bstr().to_owned(),
+            None::<(&str, _)>,
+            gix_transport::client::git::ConnectMode::Process,
+            false,
+        )
+    }
+
+    fn update() -> Command {
+        Command {
+            old_id: id("ff333369de1221f9bfbbe03a3a13e9a09bc1ffff"),
+            new_id: id("ee333369de1221f9bfbbe03a3a13e9a09bc1eeee"),
+            ref_name: "refs/heads/main".into(),
+        }
+    }
+
+    fn delete() -> Command {
+        Command {
+            old_id: id("dd333369de1221f9bfbbe03a3a13e9a09bc1dddd"),
+            new_id: gix_hash::Kind::Sha1.null(),
+            ref_name: "refs/heads/gone".into(),
+        }
+    }
+
+    #[test]
+    fn capabilities_are_selected_by_what_the_server_supports() {
+        let args = push::Arguments::new(
+            &capabilities("report-status report-status-v2 delete-refs side-band-64k quiet atomic ofs-delta"),
+            false,
+        );
+        assert_eq!(args.report_status(), Some(ReportStatus::V2));
+        assert!(args.can_use_atomic());
+        assert!(!args.can_use_push_options());
+        assert!(args.can_delete_refs());
+        assert!(args.uses_sideband());
+        assert!(args.can_use_ofs_delta());
+        assert!(args.is_empty());
+
+        let args = push::Arguments::new(&capabilities("report-status"), false);
+        assert_eq!(args.report_status(), Some(ReportStatus::V1));
+        assert!(!args.uses_sideband());
+        assert!(!args.can_delete_refs());
+    }
+
+    #[test]
+    fn commands_with_capabilities_on_first_line() -> crate::Result {
+        let mut out = Vec::new();
+        let mut args = push::Arguments::new(&capabilities("report-status delete-refs side-band-64k atomic"), false);
+        args.command(update());
+        args.command(delete());
+        args.use_atomic();
+        assert!(args.needs_pack());
+        {
+            let mut t = transport(&mut out);
+            let (_writer, _reader) = args.send(&mut t)?;
+        }
+        assert_eq!(
+            out.as_bstr(),
+            b"008aff333369de1221f9bfbbe03a3a13e9a09bc1ffff ee333369de1221f9bfbbe03a3a13e9a09bc1eeee refs/heads/main\0 report-status side-band-64k atomic
+0066dd333369de1221f9bfbbe03a3a13e9a09bc1dddd 0000000000000000000000000000000000000000 refs/heads/gone
+0000"
+                .as_bstr()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deletions_only_need_no_pack() {
+        let mut args = push::Arguments::new(&capabilities("report-status delete-refs"), false);
+        args.command(delete());
+        assert!(!args.needs_pack());
+    }
+
+    #[test]
+    fn push_options_follow_the_commands() -> crate::Result {
+        let mut out = Vec::new();
+        let mut args = push::Arguments::new(&capabilities("report-status push-options"), false);
+        args.command(update());
+        args.push_option("ci.skip");
+        args.push_option("topic=x");
+        {
+            let mut t = transport(&mut out);
+            args.send(&mut t)?;
+        }
+        assert_eq!(
+            out.as_bstr(),
+            b"0082ff333369de1221f9bfbbe03a3a13e9a09bc1ffff ee333369de1221f9bfbbe03a3a13e9a09bc1eeee refs/heads/main\0 report-status push-options
+0000000cci.skip
+000ctopic=x
+0000"
+                .as_bstr()
+        );
+        Ok(())
+    }
+}
+
+mod response {
+    use gix_protocol::push::{
+        response::{Error, RefOptions, RefStatus, UnpackStatus},
+        Response,
+    };
+
+    use crate::push::{id, pkt};
+
+    #[test]
+    fn report_status_v1() -> crate::Result {
+        let r = Response::from_lines([
+            &b"unpack ok\n"[..],
+            b"ok refs/heads/main\n",
+            b"ng refs/heads/other non-fast-forward\n",
+            b"ng refs/heads/hooked pre-receive hook declined\n",
+        ])?;
+        assert_eq!(r.unpack_status(), &UnpackStatus::Ok);
+        assert!(!r.is_success());
+        assert_eq!(
+            r.ref_updates(),
+            &[
+                RefStatus::Ok {
+                    ref_name: "refs/heads/main".into(),
+                    options: None
+                },
+                RefStatus::Rejected {
+                    ref_name: "refs/heads/other".into(),
+                    reason: "non-fast-forward".into()
+                },
+                RefStatus::Rejected {
+                    ref_name: "refs/heads/hooked".into(),
+                    reason: "pre-receive hook declined".into()
+                }
+            ]
+        );
+        assert!(r.ref_update("refs/heads/other".into()).expect("present").is_rejected());
+        Ok(())
+    }
+
+    #[test]
+    fn report_status_v2_with_options() -> crate::Result {
+        let r = Response::from_lines([
+            &b"unpack ok"[..],
+            b"ok refs/for/main",
+            b"option refname refs/changes/1",
+            b"option old-oid ff333369de1221f9bfbbe03a3a13e9a09bc1ffff",
+            b"option new-oid ee333369de1221f9bfbbe03a3a13e9a09bc1eeee",
+            b"option forced-update",
+            b"ok refs/heads/main",
+        ])?;
+        assert!(r.is_success());
+        assert_eq!(
+            r.ref_updates(),
+            &[
+                RefStatus::Ok {
+                    ref_name: "refs/for/main".into(),
+                    options: Some(RefOptions {
+                        ref_name: Some("refs/changes/1".into()),
+                        old_id: Some(id("ff333369de1221f9bfbbe03a3a13e9a09bc1ffff")),
+                        new_id: Some(id("ee333369de1221f9bfbbe03a3a13e9a09bc1eeee")),
+                        forced_update: true,
+                    })
+                },
+                RefStatus::Ok {
+                    ref_name: "refs/heads/main".into(),
+                    options: None
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn failed_unpack() -> crate::Result {
+        let r = Response::from_lines([
+            &b"unpack index-pack abnormal exit"[..],
+            b"ng refs/heads/main unpacker error",
+        ])?;
+        assert_eq!(
+            r.unpack_status(),
+            &UnpackStatus::Failed {
+                message: "index-pack abnormal exit".into()
+            }
+        );
+        assert!(!r.is_success());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_reports() {
+        assert!(matches!(
+            Response::from_lines(std::iter::empty::<&[u8]>()),
+            Err(Error::EmptyReport)
+        ));
+        assert!(matches!(
+            Response::from_lines([&b"ok refs/heads/main"[..]]),
+            Err(Error::MissingUnpackStatus { .. })
+        ));
+        assert!(matches!(
+            Response::from_lines([&b"unpack ok"[..], b"option forced-update"]),
+            Err(Error::OptionWithoutRef { .. })
+        ));
+        assert!(matches!(
+            Response::from_lines([&b"unpack ok"[..], b"what refs/heads/main"]),
+            Err(Error::UnknownLineType { .. })
+        ));
+        assert!(matches!(
+            Response::from_lines([&b"unpack ok"[..], b"ok refs/heads/main", b"option old-oid"]),
+            Err(Error::MalformedLine { .. })
+        ));
+    }
+
+    #[test]
+    fn from_line_reader_without_sideband() -> crate::Result {
+        let mut buf = pkt(b"unpack ok\n");
+        buf.extend(pkt(b"ok refs/heads/main\n"));
+        buf.extend_from_slice(b"0000");
+        let mut provider = gix_packetline::StreamingPeekableIter::new(
+            std::io::Cursor::new(buf),
+            &[gix_packetline::PacketLineRef::Flush],
+            false,
+        );
+        let mut reader = provider.as_read_without_sidebands();
+        let r = Response::from_line_reader(&mut reader, false, false)?;
+        assert!(r.is_success());
+        assert_eq!(r.ref_updates().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn from_line_reader_with_sideband() -> crate::Result {
+        let mut report = pkt(b"unpack ok\n");
+        report.extend(pkt(b"ng refs/heads/main non-fast-forward\n"));
+        report.extend_from_slice(b"0000");
+
+        let mut buf = pkt(b"\x02remote: hook says hi\n");
+        buf.extend(pkt(&[&b"\x01"[..], &report].concat()));
+        buf.extend_from_slice(b"0000");
+
+        let mut messages = Vec::new();
+        let mut provider = gix_packetline::StreamingPeekableIter::new(
+            std::io::Cursor::new(buf),
+            &[gix_packetline::PacketLineRef::Flush],
+            false,
+        );
+        {
+            let mut reader = provider.as_read_with_sidebands(Box::new(|is_err: bool, data: &[u8]| {
+                assert!(!is_err);
+                messages.push(data.to_owned());
+                gix_packetline::read::ProgressAction::Continue
+            })
+                as gix_transport::client::HandleProgress<'_>);
+            let r = Response::from_line_reader(&mut reader, true, false)?;
+            assert!(!r.is_success());
+            assert_eq!(
+                r.ref_updates(),
+                &[RefStatus::Rejected {
+                    ref_name: "refs/heads/main".into(),
+                    reason: "non-fast-forward".into()
+                }]
+            );
+        }
+        assert_eq!(messages, vec![b"remote: hook says hi".to_vec()]);
+        Ok(())
+    }
+}
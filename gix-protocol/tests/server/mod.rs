@@ -0,0 +1,189 @@
+use bstr::ByteSlice;
+use gix_protocol::server::{packetline, Ref};
+
+pub fn oid(hex: &str) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from_hex(hex.as_bytes()).expect("valid hex")
+}
+
+fn lines(input: &[u8]) -> packetline::StreamingPeekableIter<&[u8]> {
+    packetline::StreamingPeekableIter::new(input, &[packetline::PacketLineRef::Flush], false)
+}
+
+fn refs() -> Vec<Ref> {
+    vec![
+        Ref {
+            name: "HEAD".into(),
+            target: Some(oid("1111111111111111111111111111111111111111")),
+            peeled: None,
+            symref_target: Some("refs/heads/main".into()),
+        },
+        Ref {
+            name: "refs/heads/main".into(),
+            target: Some(oid("1111111111111111111111111111111111111111")),
+            peeled: None,
+            symref_target: None,
+        },
+        Ref {
+            name: "refs/tags/v1".into(),
+            target: Some(oid("2222222222222222222222222222222222222222")),
+            peeled: Some(oid("1111111111111111111111111111111111111111")),
+            symref_target: None,
+        },
+    ]
+}
+
+mod advertisement {
+    use bstr::ByteSlice;
+    use gix_protocol::server::{write_v1_advertisement, write_v2_capabilities};
+
+    #[test]
+    fn v1_with_refs_and_peeled_tags() -> crate::Result {
+        let mut out = Vec::new();
+        write_v1_advertisement(
+            &mut out,
+            &super::refs(),
+            &[("multi_ack", None), ("symref", Some("HEAD:refs/heads/main".into()))],
+            gix_hash::Kind::Sha1,
+        )?;
+        assert_eq!(
+            out.as_bstr(),
+            "00581111111111111111111111111111111111111111 HEAD\0multi_ack symref=HEAD:refs/heads/main\n\
+             003d1111111111111111111111111111111111111111 refs/heads/main\n\
+             003a2222222222222222222222222222222222222222 refs/tags/v1\n\
+             003d1111111111111111111111111111111111111111 refs/tags/v1^{}\n\
+             0000"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn v1_without_refs() -> crate::Result {
+        let mut out = Vec::new();
+        write_v1_advertisement(&mut out, &[], &[("report-status", None)], gix_hash::Kind::Sha1)?;
+        assert_eq!(
+            out.as_bstr(),
+            "004b0000000000000000000000000000000000000000 capabilities^{}\0report-status\n0000"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn v2_capabilities() -> crate::Result {
+        let mut out = Vec::new();
+        write_v2_capabilities(
+            &mut out,
+            &[("ls-refs", Some("unborn".into())), ("fetch", Some("shallow".into()))],
+        )?;
+        assert_eq!(
+            out.as_bstr(),
+            "000eversion 2\n0013ls-refs=unborn\n0012fetch=shallow\n0000"
+        );
+        Ok(())
+    }
+}
+
+mod v2 {
+    use gix_protocol::server::v2::read_command;
+
+    #[test]
+    fn command_with_arguments() -> crate::Result {
+        let input = b"0014command=ls-refs\n0015agent=git/2.44.0\n00010009peel\n0014ref-prefix HEAD\n00000000";
+        let mut lines = super::lines(input);
+        let command = read_command(&mut lines)?.expect("a command");
+        assert_eq!(command.name, "ls-refs");
+        assert_eq!(command.capabilities, ["agent=git/2.44.0"]);
+        assert_eq!(command.capability("agent").expect("present"), "git/2.44.0");
+        assert_eq!(command.arguments, ["peel", "ref-prefix HEAD"]);
+        assert_eq!(read_command(&mut lines)?, None, "a flush ends the session");
+        Ok(())
+    }
+
+    #[test]
+    fn command_without_arguments_and_eof() -> crate::Result {
+        let mut lines = super::lines(b"0012command=fetch\n0000");
+        let command = read_command(&mut lines)?.expect("a command");
+        assert_eq!(command.name, "fetch");
+        assert!(command.arguments.is_empty());
+        assert_eq!(read_command(&mut lines)?, None, "EOF ends the session as well");
+        Ok(())
+    }
+
+    #[test]
+    fn missing_command_is_an_error() {
+        let mut lines = super::lines(b"000cagent=x\n0000");
+        assert!(matches!(
+            read_command(&mut lines),
+            Err(gix_protocol::server::Error::MissingCommand)
+        ));
+    }
+}
+
+mod ls_refs {
+    use bstr::ByteSlice;
+    use gix_protocol::server::ls_refs::{write_response, Arguments};
+
+    #[test]
+    fn arguments_and_response() -> crate::Result {
+        let args = Arguments::from_arguments(&["symrefs".into(), "peel".into(), "ref-prefix refs/tags/".into()])?;
+        assert!(args.symrefs && args.peel && !args.unborn);
+        let mut out = Vec::new();
+        write_response(&mut out, &super::refs(), &args)?;
+        assert_eq!(
+            out.as_bstr(),
+            "006a2222222222222222222222222222222222222222 refs/tags/v1 peeled:1111111111111111111111111111111111111111\n0000"
+        );
+
+        let args = Arguments::from_arguments(&["symrefs".into(), "ref-prefix HEAD".into()])?;
+        let mut out = Vec::new();
+        write_response(&mut out, &super::refs(), &args)?;
+        assert_eq!(
+            out.as_bstr(),
+            "00501111111111111111111111111111111111111111 HEAD symref-target:refs/heads/main\n0000"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unborn_head_is_only_shown_if_requested() -> crate::Result {
+        let refs = [gix_protocol::server::Ref {
+            name: "HEAD".into(),
+            target: None,
+            peeled: None,
+            symref_target: Some("refs/heads/main".into()),
+        }];
+        let mut out = Vec::new();
+        write_response(&mut out, &refs, &Arguments::default())?;
+        assert_eq!(out.as_bstr(), "0000");
+
+        let mut out = Vec::new();
+        write_response(
+            &mut out,
+            &refs,
+            &Arguments::from_arguments(&["unborn".into(), "symrefs".into()])?,
+        )?;
+        assert_eq!(out.as_bstr(), "002eunborn HEAD symref-target:refs/heads/main\n0000");
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_arguments_are_rejected() {
+        assert!(Arguments::from_arguments(&["foo".into()]).is_err());
+    }
+}
+
+mod receive_pack;
+mod upload_pack;
+
+#[test]
+fn sideband_writer_splits_data_into_lines() -> crate::Result {
+    use std::io::Write;
+    let mut out = gix_protocol::server::SidebandWriter::with_max_line_len(Vec::new(), 8);
+    out.write_all(b"abcdefg")?;
+    out.progress(b"hi\n")?;
+    out.error(b"no")?;
+    assert_eq!(
+        out.into_inner().as_bstr(),
+        "0008\x01abc0008\x01def0006\x01g0008\x02hi\n0007\x03no"
+    );
+    Ok(())
+}
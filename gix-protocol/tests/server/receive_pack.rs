@@ -0,0 +1,84 @@
+use bstr::ByteSlice;
+use gix_protocol::server::receive_pack::{read_push_options, write_report_status, RefStatus, ReportStatus, Request};
+
+use super::oid;
+
+const ONE: &str = "1111111111111111111111111111111111111111";
+const TWO: &str = "2222222222222222222222222222222222222222";
+const NULL: &str = "0000000000000000000000000000000000000000";
+
+#[test]
+fn request_with_capabilities_shallow_and_push_options() -> crate::Result {
+    let input = format!(
+        "0035shallow {TWO}\n\
+         00a7{NULL} {ONE} refs/heads/main\0report-status side-band-64k atomic push-options agent=git/2.44.0\n\
+         0065{ONE} {NULL} refs/heads/old\n0000\
+         000da-option\n0000"
+    );
+    let mut lines = super::lines(input.as_bytes());
+    let request = Request::read_from(&mut lines)?.expect("commands are present");
+    assert_eq!(request.shallow, [oid(TWO)]);
+    assert_eq!(request.commands.len(), 2);
+    assert!(request.commands[0].is_create() && !request.commands[0].is_delete());
+    assert_eq!(request.commands[0].ref_name, "refs/heads/main");
+    assert_eq!(request.commands[0].new_id, oid(ONE));
+    assert!(request.commands[1].is_delete());
+    assert_eq!(request.commands[1].ref_name, "refs/heads/old");
+    assert!(request.needs_pack());
+    assert_eq!(request.report_status, Some(ReportStatus::V1));
+    assert!(request.atomic && request.push_options && request.sideband && !request.quiet);
+    assert_eq!(request.agent.as_ref().expect("sent"), "git/2.44.0");
+
+    assert_eq!(read_push_options(&mut lines)?, ["a-option"]);
+    Ok(())
+}
+
+#[test]
+fn request_with_deletions_only_needs_no_pack() -> crate::Result {
+    let input = format!("0065{ONE} {NULL} refs/heads/old\n0000");
+    let request = Request::read_from(&mut super::lines(input.as_bytes()))?.expect("commands are present");
+    assert!(!request.needs_pack());
+    assert_eq!(request.report_status, None, "capabilities are optional");
+    Ok(())
+}
+
+#[test]
+fn request_without_commands() -> crate::Result {
+    assert_eq!(Request::read_from(&mut super::lines(b"0000"))?, None);
+    assert_eq!(Request::read_from(&mut super::lines(b""))?, None, "EOF is fine as well");
+    Ok(())
+}
+
+#[test]
+fn malformed_command_is_an_error() {
+    let input = format!("0032{ONE} {NULL}\n0000");
+    assert!(Request::read_from(&mut super::lines(input.as_bytes())).is_err());
+}
+
+#[test]
+fn report_status() -> crate::Result {
+    let mut out = Vec::new();
+    write_report_status(
+        &mut out,
+        Ok(()),
+        &[
+            RefStatus {
+                ref_name: "refs/heads/main".into(),
+                rejection: None,
+            },
+            RefStatus {
+                ref_name: "refs/heads/old".into(),
+                rejection: Some("non-fast-forward".into()),
+            },
+        ],
+    )?;
+    assert_eq!(
+        out.as_bstr(),
+        "000eunpack ok\n0017ok refs/heads/main\n0027ng refs/heads/old non-fast-forward\n0000"
+    );
+
+    out.clear();
+    write_report_status(&mut out, Err("index-pack failed".into()), &[])?;
+    assert_eq!(out.as_bstr(), "001dunpack index-pack failed\n0000");
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use bstr::ByteSlice;
+use gix_protocol::server::upload_pack::{
+    read_v1_haves, write_acknowledgments, write_shallow_info, Acknowledgement, Arguments, MultiAck, Sideband,
+};
+
+use super::oid;
+
+const ONE: &str = "1111111111111111111111111111111111111111";
+const TWO: &str = "2222222222222222222222222222222222222222";
+
+#[test]
+fn v1_request_with_capabilities_and_deepen() -> crate::Result {
+    let input = format!(
+        "0078want {ONE} multi_ack_detailed side-band-64k thin-pack ofs-delta agent=git/2.44.0\n\
+         0032want {TWO}\n0035shallow {TWO}\n000ddeepen 3\n0000"
+    );
+    let mut lines = super::lines(input.as_bytes());
+    let args = Arguments::read_v1_request(&mut lines)?.expect("wants are present");
+    assert_eq!(args.wants, [oid(ONE), oid(TWO)]);
+    assert_eq!(args.shallow, [oid(TWO)]);
+    assert_eq!(args.depth, Some(3));
+    assert!(args.deepens());
+    assert_eq!(args.multi_ack, MultiAck::Detailed);
+    assert_eq!(args.sideband, Some(Sideband::Large));
+    assert!(args.thin_pack && args.ofs_delta && !args.include_tag && !args.no_done);
+    assert_eq!(args.agent.as_ref().expect("sent"), "git/2.44.0");
+    Ok(())
+}
+
+#[test]
+fn v1_request_without_wants() -> crate::Result {
+    let mut lines = super::lines(b"0000");
+    assert_eq!(Arguments::read_v1_request(&mut lines)?, None);
+    Ok(())
+}
+
+#[test]
+fn v1_haves_in_rounds() -> crate::Result {
+    let input = format!("0032have {ONE}\n00000032have {TWO}\n0009done\n");
+    let mut lines = super::lines(input.as_bytes());
+    let round = read_v1_haves(&mut lines)?.expect("a round");
+    assert_eq!(round.ids, [oid(ONE)]);
+    assert!(!round.done);
+    let round = read_v1_haves(&mut lines)?.expect("a round");
+    assert_eq!(round.ids, [oid(TWO)]);
+    assert!(round.done);
+    assert_eq!(read_v1_haves(&mut lines)?, None, "the stateless client hung up");
+    Ok(())
+}
+
+#[test]
+fn v1_acknowledgements() -> crate::Result {
+    let mut out = Vec::new();
+    Acknowledgement::Common(oid(ONE)).write_to(&mut out)?;
+    Acknowledgement::Ready(oid(ONE)).write_to(&mut out)?;
+    Acknowledgement::Nak.write_to(&mut out)?;
+    assert_eq!(
+        out.as_bstr(),
+        format!("0038ACK {ONE} common\n0037ACK {ONE} ready\n0008NAK\n")
+    );
+    Ok(())
+}
+
+#[test]
+fn v2_arguments() -> crate::Result {
+    let args = Arguments::from_v2_arguments(&[
+        format!("want {ONE}").into(),
+        format!("have {TWO}").into(),
+        "deepen-since 1234".into(),
+        "deepen-not refs/heads/main".into(),
+        "include-tag".into(),
+        "no-progress".into(),
+        "done".into(),
+    ])?;
+    assert_eq!(args.wants, [oid(ONE)]);
+    assert_eq!(args.haves, [oid(TWO)]);
+    assert_eq!(args.deepen_since, Some(1234));
+    assert_eq!(args.deepen_not, ["refs/heads/main"]);
+    assert!(args.include_tag && args.no_progress && args.done);
+    assert_eq!(
+        args.sideband,
+        Some(Sideband::Large),
+        "V2 always uses the large side-band"
+    );
+
+    assert!(Arguments::from_v2_arguments(&["filter blob:none".into()]).is_err());
+    assert!(Arguments::from_v2_arguments(&["want not-hex".into()]).is_err());
+    Ok(())
+}
+
+#[test]
+fn v2_sections() -> crate::Result {
+    let mut out = Vec::new();
+    write_acknowledgments(&mut out, &[oid(ONE)], true)?;
+    assert_eq!(
+        out.as_bstr(),
+        format!("0014acknowledgments\n0031ACK {ONE}\n000aready\n0001")
+    );
+
+    let mut out = Vec::new();
+    write_acknowledgments(&mut out, &[], false)?;
+    assert_eq!(out.as_bstr(), "0014acknowledgments\n0008NAK\n0000");
+
+    let mut out = Vec::new();
+    write_shallow_info(&mut out, &[oid(ONE)], &[oid(TWO)])?;
+    assert_eq!(
+        out.as_bstr(),
+        format!("0011shallow-info\n0035shallow {ONE}\n0037unshallow {TWO}\n0001")
+    );
+    Ok(())
+}
@@ -0,0 +1,89 @@
+//! Read and write the `author-script` file, which contains the author of the commit to create as shell variables.
+use std::io::Write;
+
+use bstr::{BString, ByteSlice};
+
+/// The error returned by [`decode()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Expected a line like KEY='value', got {line:?}")]
+    InvalidLine { line: BString },
+    #[error("Value {value:?} is not properly quoted")]
+    InvalidQuoting { value: BString },
+    #[error("The variable {name} is missing")]
+    MissingVariable { name: &'static str },
+    #[error("Could not parse the author date")]
+    InvalidDate(#[from] gix_date::parse::Error),
+}
+
+const NAME: &str = "GIT_AUTHOR_NAME";
+const EMAIL: &str = "GIT_AUTHOR_EMAIL";
+const DATE: &str = "GIT_AUTHOR_DATE";
+
+/// Parse the author from `data`, which sets the variables `GIT_AUTHOR_NAME`, `GIT_AUTHOR_EMAIL` and `GIT_AUTHOR_DATE`.
+pub fn decode(data: &[u8]) -> Result<gix_actor::Signature, Error> {
+    let (mut name, mut email, mut date) = (None, None, None);
+    for line in data.lines().filter(|line| !line.trim().is_empty()) {
+        let (key, value) = line
+            .split_once_str(b"=")
+            .ok_or_else(|| Error::InvalidLine { line: line.into() })?;
+        let value = dequote(value).ok_or_else(|| Error::InvalidQuoting { value: value.into() })?;
+        match key {
+            b"GIT_AUTHOR_NAME" => name = Some(value),
+            b"GIT_AUTHOR_EMAIL" => email = Some(value),
+            b"GIT_AUTHOR_DATE" => date = Some(value),
+            _ => return Err(Error::InvalidLine { line: line.into() }),
+        }
+    }
+    let date = date.ok_or(Error::MissingVariable { name: DATE })?;
+    let date = date.to_str_lossy();
+    Ok(gix_actor::Signature {
+        name: name.ok_or(Error::MissingVariable { name: NAME })?,
+        email: email.ok_or(Error::MissingVariable { name: EMAIL })?,
+        time: gix_date::parse(date.strip_prefix('@').unwrap_or(&date), None)?,
+    })
+}
+
+/// Write `author` to `out` in a format that can be read by [`decode()`] and by a shell.
+pub fn encode(author: gix_actor::SignatureRef<'_>, out: &mut dyn Write) -> std::io::Result<()> {
+    for (name, value) in [(NAME, author.name.as_bytes()), (EMAIL, author.email.as_bytes())] {
+        write!(out, "{name}=")?;
+        write_quoted(out, value)?;
+        out.write_all(b"\n")?;
+    }
+    write!(out, "{DATE}=")?;
+    write_quoted(out, format!("@{}", author.time.to_bstring()).as_bytes())?;
+    out.write_all(b"\n")
+}
+
+/// Quote `value` in single quotes like `git` does, with each single quote in `value` written as `'\''`.
+fn write_quoted(out: &mut dyn Write, value: &[u8]) -> std::io::Result<()> {
+    out.write_all(b"'")?;
+    for (idx, part) in value.split_str(b"'").enumerate() {
+        if idx != 0 {
+            out.write_all(br"'\''")?;
+        }
+        out.write_all(part)?;
+    }
+    out.write_all(b"'")
+}
+
+/// The inverse of [`write_quoted()`], which also accepts escaped characters between quoted sections.
+fn dequote(mut value: &[u8]) -> Option<BString> {
+    let mut out = BString::default();
+    loop {
+        value = value.strip_prefix(b"'")?;
+        let end = value.find_byte(b'\'')?;
+        out.extend_from_slice(&value[..end]);
+        value = &value[end + 1..];
+        match value {
+            [] => return Some(out),
+            [b'\\', escaped, rest @ ..] => {
+                out.push(*escaped);
+                value = rest;
+            }
+            _ => return None,
+        }
+    }
+}
@@ -1,2 +1,74 @@
-#![deny(rust_2018_idioms)]
+//! Read and write the state of rebases in progress, which is kept in `.git/rebase-merge`.
+//!
+//! The format is the one `git` uses, so rebases started by `git` can be continued with `gitoxide`, and vice versa.
+//! The instructions to perform are managed with [`gix_sequencer`].
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+use bstr::BString;
+use gix_hash::ObjectId;
+pub use gix_sequencer::Instruction;
+
+/// The name of the directory in the `.git` directory that contains the [state](State) of a rebase in progress.
+pub const DIR_NAME: &str = "rebase-merge";
+
+/// The state of a rebase in progress, as it's stored in the [`DIR_NAME`] directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    /// The full name of the branch that is rebased, like `refs/heads/main`, or `None` if `HEAD` was detached.
+    pub head_name: Option<BString>,
+    /// The commit that the rebased commits are applied to.
+    pub onto: ObjectId,
+    /// The commit `HEAD` pointed to when the rebase started.
+    pub orig_head: ObjectId,
+    /// If `true`, the user was given the chance to edit the instructions, like with `git rebase --interactive`.
+    pub interactive: bool,
+    /// The instructions that are still to be performed, in order.
+    pub todo: Vec<Instruction>,
+    /// The instructions that were performed, in order, with the last one being the one that is currently performed.
+    pub done: Vec<Instruction>,
+    /// The references to update once the rebase is complete.
+    pub update_refs: Vec<UpdateRef>,
+    /// The chain of fixups and squashes that were applied to the current commit so far.
+    pub current_fixups: Vec<Instruction>,
+    /// Information about the commit to create or amend when continuing, which is present if the rebase was stopped.
+    pub stopped: Option<Stopped>,
+}
+
+/// A reference to update once a rebase is complete, as it's stored in the `update-refs` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateRef {
+    /// The full name of the reference, like `refs/heads/feature`.
+    pub name: BString,
+    /// The commit the reference pointed to when the rebase started.
+    pub before: ObjectId,
+    /// The commit the reference should point to, or `None` if its `update-ref` instruction wasn't performed yet.
+    pub after: Option<ObjectId>,
+}
+
+/// Information about a stopped rebase, which is needed to continue it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stopped {
+    /// The commit that was picked when the rebase stopped, if there was one, from the `stopped-sha` file.
+    pub commit: Option<ObjectId>,
+    /// The commit that was created and that should be amended with staged changes when continuing, from the `amend` file.
+    ///
+    /// This is set if the rebase stopped after an `edit` instruction.
+    pub amend: Option<ObjectId>,
+    /// The message of the commit to create when continuing, from the `message` file.
+    pub message: Option<BString>,
+    /// The author of the commit to create when continuing, from the `author-script` file.
+    pub author: Option<gix_actor::Signature>,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod author_script;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod read;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write;
@@ -0,0 +1,131 @@
+use std::path::{Path, PathBuf};
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::{author_script, State, Stopped, UpdateRef};
+
+/// The error returned by [`State::from_dir()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read '{}'", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+    #[error("The file '{}' is required, but missing", path.display())]
+    Missing { path: PathBuf },
+    #[error("The file '{}' does not contain a valid object id", path.display())]
+    InvalidObjectId {
+        source: gix_hash::decode::Error,
+        path: PathBuf,
+    },
+    #[error("Could not parse the instructions in '{}'", path.display())]
+    Instructions {
+        source: gix_sequencer::decode::Error,
+        path: PathBuf,
+    },
+    #[error("Could not parse the author in '{}'", path.display())]
+    AuthorScript {
+        source: author_script::Error,
+        path: PathBuf,
+    },
+    #[error("The file '{}' must contain the name, old and new object id of each reference on three lines", path.display())]
+    InvalidUpdateRefs { path: PathBuf },
+}
+
+impl State {
+    /// Read the state of the rebase in progress from `dir`, which is `.git/rebase-merge`, or return `None`
+    /// if `dir` doesn't exist as no rebase is in progress.
+    pub fn from_dir(dir: &Path) -> Result<Option<Self>, Error> {
+        if !dir.is_dir() {
+            return Ok(None);
+        }
+        let head_name = read_required(dir, "head-name")?;
+        let head_name = head_name.trim();
+        let update_refs = match read(dir, "update-refs")? {
+            Some(data) => parse_update_refs(&data).ok_or_else(|| Error::InvalidUpdateRefs {
+                path: dir.join("update-refs"),
+            })?,
+            None => Vec::new(),
+        };
+        let stopped = Stopped {
+            commit: read(dir, "stopped-sha")?.and_then(|hex| ObjectId::from_hex(hex.trim()).ok()),
+            amend: read(dir, "amend")?
+                .map(|hex| parse_id(dir, "amend", &hex))
+                .transpose()?,
+            message: read(dir, "message")?.map(Into::into),
+            author: read(dir, "author-script")?
+                .map(|data| {
+                    author_script::decode(&data).map_err(|source| Error::AuthorScript {
+                        source,
+                        path: dir.join("author-script"),
+                    })
+                })
+                .transpose()?,
+        };
+        let has_stopped = stopped.commit.is_some()
+            || stopped.amend.is_some()
+            || stopped.message.is_some()
+            || stopped.author.is_some();
+        Ok(Some(State {
+            head_name: (head_name != b"detached HEAD").then(|| BString::from(head_name)),
+            onto: parse_id(dir, "onto", &read_required(dir, "onto")?)?,
+            orig_head: parse_id(dir, "orig-head", &read_required(dir, "orig-head")?)?,
+            interactive: dir.join("interactive").is_file(),
+            todo: read_instructions(dir, "git-rebase-todo")?,
+            done: read_instructions(dir, "done")?,
+            update_refs,
+            current_fixups: read_instructions(dir, "current-fixups")?,
+            stopped: has_stopped.then_some(stopped),
+        }))
+    }
+}
+
+fn read(dir: &Path, name: &str) -> Result<Option<Vec<u8>>, Error> {
+    let path = dir.join(name);
+    match std::fs::read(&path) {
+        Ok(data) => Ok(Some(data)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(Error::Io { source, path }),
+    }
+}
+
+fn read_required(dir: &Path, name: &str) -> Result<Vec<u8>, Error> {
+    read(dir, name)?.ok_or_else(|| Error::Missing { path: dir.join(name) })
+}
+
+fn read_instructions(dir: &Path, name: &str) -> Result<Vec<gix_sequencer::Instruction>, Error> {
+    read(dir, name)?
+        .map(|data| {
+            gix_sequencer::decode(&data).map_err(|source| Error::Instructions {
+                source,
+                path: dir.join(name),
+            })
+        })
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+fn parse_id(dir: &Path, name: &str, hex: &[u8]) -> Result<ObjectId, Error> {
+    ObjectId::from_hex(hex.trim()).map_err(|source| Error::InvalidObjectId {
+        source,
+        path: dir.join(name),
+    })
+}
+
+fn parse_update_refs(data: &[u8]) -> Option<Vec<UpdateRef>> {
+    let lines: Vec<_> = data.lines().collect();
+    if lines.len() % 3 != 0 {
+        return None;
+    }
+    lines
+        .chunks(3)
+        .map(|lines| {
+            let after = ObjectId::from_hex(lines[2]).ok()?;
+            Some(UpdateRef {
+                name: lines[0].into(),
+                before: ObjectId::from_hex(lines[1]).ok()?,
+                after: (!after.is_null()).then_some(after),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,102 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{author_script, State};
+
+/// The error returned by [`State::write_to_dir()`].
+#[derive(Debug, thiserror::Error)]
+#[error("Could not write '{}'", path.display())]
+pub struct Error {
+    source: std::io::Error,
+    path: PathBuf,
+}
+
+impl State {
+    /// Write this state into `dir`, which is `.git/rebase-merge`, creating it if needed, and remove files for
+    /// information that isn't present.
+    pub fn write_to_dir(&self, dir: &Path) -> Result<(), Error> {
+        std::fs::create_dir_all(dir).map_err(|source| Error {
+            source,
+            path: dir.to_owned(),
+        })?;
+        let hash_kind = self.onto.kind();
+        let write = |name: &str, data: Option<&[u8]>| {
+            let path = dir.join(name);
+            let res = match data {
+                Some(data) => std::fs::write(&path, data),
+                None => match std::fs::remove_file(&path) {
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                    res => res,
+                },
+            };
+            res.map_err(|source| Error { source, path })
+        };
+        let instructions = |instructions: &[gix_sequencer::Instruction]| {
+            let mut buf = Vec::new();
+            gix_sequencer::encode(instructions, &mut buf).expect("writing to a vec never fails");
+            buf
+        };
+
+        let head_name = match &self.head_name {
+            Some(name) => format!("{name}\n"),
+            None => "detached HEAD\n".into(),
+        };
+        write("head-name", Some(head_name.as_bytes()))?;
+        write("onto", Some(format!("{}\n", self.onto).as_bytes()))?;
+        write("orig-head", Some(format!("{}\n", self.orig_head).as_bytes()))?;
+        write("interactive", self.interactive.then_some(&[]))?;
+        write("git-rebase-todo", Some(&instructions(&self.todo)))?;
+        write("done", Some(&instructions(&self.done)))?;
+        write("msgnum", Some(format!("{}\n", self.done.len()).as_bytes()))?;
+        write(
+            "end",
+            Some(format!("{}\n", self.done.len() + self.todo.len()).as_bytes()),
+        )?;
+        write(
+            "current-fixups",
+            (!self.current_fixups.is_empty())
+                .then(|| instructions(&self.current_fixups))
+                .as_deref(),
+        )?;
+
+        let update_refs = (!self.update_refs.is_empty()).then(|| {
+            let mut buf = Vec::new();
+            for update in &self.update_refs {
+                let after = update.after.unwrap_or_else(|| hash_kind.null());
+                writeln!(buf, "{}\n{}\n{}", update.name, update.before, after).expect("writing to a vec never fails");
+            }
+            buf
+        });
+        write("update-refs", update_refs.as_deref())?;
+
+        let stopped = self.stopped.as_ref();
+        write(
+            "stopped-sha",
+            stopped
+                .and_then(|stopped| stopped.commit)
+                .map(|id| format!("{id}\n"))
+                .as_deref()
+                .map(str::as_bytes),
+        )?;
+        write(
+            "amend",
+            stopped
+                .and_then(|stopped| stopped.amend)
+                .map(|id| format!("{id}\n"))
+                .as_deref()
+                .map(str::as_bytes),
+        )?;
+        write(
+            "message",
+            stopped.and_then(|stopped| stopped.message.as_ref()).map(AsRef::as_ref),
+        )?;
+        let author = stopped.and_then(|stopped| stopped.author.as_ref()).map(|author| {
+            let mut buf = Vec::new();
+            author_script::encode(author.to_ref(), &mut buf).expect("writing to a vec never fails");
+            buf
+        });
+        write("author-script", author.as_deref())
+    }
+}
@@ -0,0 +1,38 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit() {
+  local name=${1:?first argument is the file name}
+  local content=${2:?second argument is the content}
+  echo "$content" > "$name"
+  git add "$name"
+  git commit -q -m "$content"
+}
+
+# An interactive rebase that stopped after an 'edit' instruction, with a reference to update.
+git init -q edit
+(cd edit
+  git checkout -q -b main
+  commit a 1
+  commit b 2
+  git branch side
+  commit c 3
+  commit a "fixup! 1"
+
+  GIT_SEQUENCE_EDITOR="sed -i -e 's/^pick \(.* 2\)$/edit \1/'" git rebase -q -i --autosquash --update-refs --root >/dev/null 2>&1
+)
+
+# A rebase that stopped on conflicts.
+git init -q conflict
+(cd conflict
+  git checkout -q -b main
+  commit a base
+  git checkout -q -b topic
+  GIT_AUTHOR_NAME="Jane O'Neil" commit a "ours, with 'quotes'"
+  commit b unrelated
+  git checkout -q main
+  commit a theirs
+  git checkout -q topic
+
+  git rebase -q main >/dev/null 2>&1 || :
+)
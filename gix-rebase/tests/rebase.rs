@@ -0,0 +1,127 @@
+use std::path::{Path, PathBuf};
+
+use gix_rebase::{author_script, Instruction, State};
+
+pub use gix_testtools::Result;
+
+fn fixture_path() -> PathBuf {
+    gix_testtools::scripted_fixture_read_only("make_rebase_states.sh").expect("script succeeds")
+}
+
+fn state_dir(name: &str) -> PathBuf {
+    fixture_path().join(name).join(".git").join(gix_rebase::DIR_NAME)
+}
+
+fn read(dir: &Path) -> State {
+    State::from_dir(dir).expect("valid state").expect("rebase in progress")
+}
+
+#[test]
+fn read_state_stopped_after_edit() {
+    let state = read(&state_dir("edit"));
+    assert_eq!(state.head_name.as_ref().expect("on a branch"), "refs/heads/main");
+    assert!(state.interactive);
+    assert_eq!(
+        state.done.iter().map(Instruction::name).collect::<Vec<_>>(),
+        ["pick", "fixup", "edit"]
+    );
+    assert_eq!(state.todo.len(), 2);
+    assert_eq!(
+        state.todo[0],
+        Instruction::UpdateRef {
+            name: "refs/heads/side".into()
+        }
+    );
+    assert_eq!(state.update_refs.len(), 1);
+    assert_eq!(state.update_refs[0].name, "refs/heads/side");
+    assert_eq!(
+        state.update_refs[0].after, None,
+        "the update-ref instruction wasn't performed yet"
+    );
+
+    let stopped = state.stopped.expect("stopped");
+    assert!(stopped.amend.is_some(), "the edited commit is amended on continue");
+    assert!(stopped.commit.is_some());
+    assert_eq!(stopped.message.expect("present"), "2\n\n");
+    assert!(stopped.author.is_some());
+}
+
+#[test]
+fn read_state_stopped_on_conflict() {
+    let state = read(&state_dir("conflict"));
+    assert_eq!(
+        state.done.last().and_then(Instruction::summary).expect("picked"),
+        "ours, with 'quotes'"
+    );
+    assert_eq!(state.todo.len(), 1, "it stopped on the first commit");
+    assert!(state.update_refs.is_empty());
+
+    let stopped = state.stopped.expect("stopped");
+    assert_eq!(stopped.amend, None);
+    assert_eq!(stopped.message.expect("present"), "ours, with 'quotes'\n\n");
+    assert_eq!(stopped.author.expect("present").name, "Jane O'Neil");
+}
+
+#[test]
+fn no_state_without_directory() -> Result {
+    assert_eq!(State::from_dir(&fixture_path().join("does-not-exist"))?, None);
+    Ok(())
+}
+
+#[test]
+fn write_round_trip_is_compatible_with_git() -> Result {
+    for name in ["edit", "conflict"] {
+        let dir = state_dir(name);
+        let state = read(&dir);
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        state.write_to_dir(tmp.path())?;
+        assert_eq!(read(tmp.path()), state, "{name}: what's written can be read back");
+
+        for file in [
+            "head-name",
+            "onto",
+            "orig-head",
+            "interactive",
+            "done",
+            "msgnum",
+            "update-refs",
+            "amend",
+            "stopped-sha",
+            "message",
+            "author-script",
+        ] {
+            let expected = std::fs::read(dir.join(file)).ok();
+            let actual = std::fs::read(tmp.path().join(file)).ok();
+            assert_eq!(actual, expected, "{name}: {file} is written like git does");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn author_script_round_trip() -> Result {
+    let author = gix_actor::Signature {
+        name: "Jane 'Jay' O'Neil".into(),
+        email: "jane@example.com".into(),
+        time: gix_date::Time::new(1234567890, -3600),
+    };
+    let mut buf = Vec::new();
+    author_script::encode(author.to_ref(), &mut buf)?;
+    assert_eq!(
+        std::str::from_utf8(&buf)?,
+        "GIT_AUTHOR_NAME='Jane '\\''Jay'\\'' O'\\''Neil'\nGIT_AUTHOR_EMAIL='jane@example.com'\nGIT_AUTHOR_DATE='@1234567890 -0100'\n"
+    );
+    assert_eq!(author_script::decode(&buf)?, author);
+
+    assert!(matches!(
+        author_script::decode(b"GIT_AUTHOR_NAME='a'\nGIT_AUTHOR_EMAIL='b'\n"),
+        Err(author_script::Error::MissingVariable {
+            name: "GIT_AUTHOR_DATE"
+        })
+    ));
+    assert!(matches!(
+        author_script::decode(b"GIT_AUTHOR_NAME='a"),
+        Err(author_script::Error::InvalidQuoting { .. })
+    ));
+    Ok(())
+}
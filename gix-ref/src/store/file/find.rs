@@ -147,6 +147,9 @@ impl file::Store {
         let full_name = precomposed_partial_name
             .unwrap_or(partial_name)
             .construct_full_name_ref(inbetween, path_buf);
+        if let Some((stack, name)) = self.reftable_for(full_name) {
+            return Ok(self.find_in_reftable(&stack, name.as_ref(), full_name)?);
+        }
         let content_buf = self.ref_contents(full_name).map_err(|err| Error::ReadFileContents {
             source: err,
             path: self.reference_path(full_name),
@@ -359,7 +362,7 @@ pub mod existing {
 mod error {
     use std::{convert::Infallible, io, path::PathBuf};
 
-    use crate::{file, store_impl::packed};
+    use crate::{file, store_impl::packed, store_impl::reftable};
 
     /// The error returned by [file::Store::find()].
     #[derive(Debug, thiserror::Error)]
@@ -378,6 +381,8 @@ mod error {
         PackedRef(#[from] packed::find::Error),
         #[error("Could not open the packed refs buffer when trying to find references.")]
         PackedOpen(#[from] packed::buffer::open::Error),
+        #[error("A reftable lookup failed")]
+        Reftable(#[from] reftable::stack::Error),
     }
 
     impl From<Infallible> for Error {
@@ -91,7 +91,7 @@ pub struct Platform<'a, 's> {
 
 impl<'a, 's> Platform<'a, 's> {
     /// Return a forward iterator over all log-lines, most recent to oldest.
-    pub fn rev(&mut self) -> std::io::Result<Option<log::iter::Reverse<'_, std::fs::File>>> {
+    pub fn rev(&mut self) -> std::io::Result<Option<log::iter::Reverse<'_, log::iter::Source>>> {
         self.buf.clear();
         self.buf.resize(512, 0);
         self.store
@@ -106,6 +106,33 @@ impl<'a, 's> Platform<'a, 's> {
     }
 }
 
+/// The source of the log lines read by a [`Reverse`] iterator.
+#[derive(Debug)]
+pub enum Source {
+    /// A reflog file.
+    File(std::fs::File),
+    /// Log lines in memory, as produced from the log records of a reftable.
+    Memory(std::io::Cursor<Vec<u8>>),
+}
+
+impl std::io::Read for Source {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Source::File(file) => file.read(buf),
+            Source::Memory(cursor) => cursor.read(buf),
+        }
+    }
+}
+
+impl std::io::Seek for Source {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        match self {
+            Source::File(file) => file.seek(pos),
+            Source::Memory(cursor) => cursor.seek(pos),
+        }
+    }
+}
+
 /// An iterator yielding parsed lines in a file in reverse, most recent to oldest.
 pub struct Reverse<'a, F> {
     buf: &'a mut [u8],
@@ -52,6 +52,7 @@ mod init {
                 write_reflog,
                 namespace: None,
                 packed: gix_fs::SharedFileSnapshotMut::new().into(),
+                reftable: None,
                 object_hash,
                 precompose_unicode,
             }
@@ -76,6 +77,7 @@ mod init {
                 write_reflog,
                 namespace: None,
                 packed: gix_fs::SharedFileSnapshotMut::new().into(),
+                reftable: None,
                 object_hash,
                 precompose_unicode,
             }
@@ -16,7 +16,11 @@ impl file::Store {
         Name: TryInto<&'a FullNameRef, Error = E>,
         crate::name::Error: From<E>,
     {
-        Ok(self.reflog_path(name.try_into()?).is_file())
+        let name = name.try_into()?;
+        if let Some(log) = self.reftable_reflog(name) {
+            return Ok(matches!(log, Ok(Some(_))));
+        }
+        Ok(self.reflog_path(name).is_file())
     }
 
     /// Return a reflog reverse iterator for the given fully qualified `name`, reading chunks from the back into the fixed buffer `buf`.
@@ -27,18 +31,24 @@ impl file::Store {
         &self,
         name: Name,
         buf: &'b mut [u8],
-    ) -> Result<Option<log::iter::Reverse<'b, std::fs::File>>, Error>
+    ) -> Result<Option<log::iter::Reverse<'b, log::iter::Source>>, Error>
     where
         Name: TryInto<&'a FullNameRef, Error = E>,
         crate::name::Error: From<E>,
     {
         let name: &FullNameRef = name.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+        if let Some(log) = self.reftable_reflog(name) {
+            return log?
+                .map(|lines| log::iter::reverse(log::iter::Source::Memory(std::io::Cursor::new(lines)), buf))
+                .transpose()
+                .map_err(Into::into);
+        }
         let path = self.reflog_path(name);
         if path.is_dir() {
             return Ok(None);
         }
         match std::fs::File::open(&path) {
-            Ok(file) => Ok(Some(log::iter::reverse(file, buf)?)),
+            Ok(file) => Ok(Some(log::iter::reverse(log::iter::Source::File(file), buf)?)),
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
             Err(err) => Err(err.into()),
         }
@@ -58,6 +68,12 @@ impl file::Store {
         crate::name::Error: From<E>,
     {
         let name: &FullNameRef = name.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+        if let Some(log) = self.reftable_reflog(name) {
+            return Ok(log?.map(|lines| {
+                *buf = lines;
+                log::iter::forward(buf)
+            }));
+        }
         let path = self.reflog_path(name);
         match std::fs::File::open(&path) {
             Ok(mut file) => {
@@ -173,7 +189,7 @@ pub mod create_or_update {
             }
         }
 
-        fn should_autocreate_reflog(&self, full_name: &Path) -> bool {
+        pub(in crate::store_impl::file) fn should_autocreate_reflog(&self, full_name: &Path) -> bool {
             full_name.starts_with("refs/heads/")
                 || full_name.starts_with("refs/remotes/")
                 || full_name.starts_with("refs/notes/")
@@ -231,6 +247,83 @@ pub mod create_or_update {
     use crate::FullNameRef;
 }
 
+///
+#[allow(clippy::empty_docs)]
+pub mod retain {
+    use std::io::Write;
+
+    use gix_object::bstr::ByteSlice;
+
+    use crate::{
+        store_impl::{file, file::log},
+        FullNameRef,
+    };
+
+    impl file::Store {
+        /// Rewrite the reflog of the reference `name` so that it only contains the entries for which `keep` returns `true`,
+        /// and return the amount of entries that were removed, or `None` if there is no reflog.
+        ///
+        /// Entries that can't be parsed are kept as they are. `lock_mode` determines how to wait for the lock on the reflog.
+        /// Note that reflogs in reftables can't be rewritten yet.
+        pub fn reflog_retain<'a, Name, E>(
+            &self,
+            name: Name,
+            lock_mode: gix_lock::acquire::Fail,
+            keep: &mut dyn FnMut(&log::LineRef<'_>) -> bool,
+        ) -> Result<Option<usize>, Error>
+        where
+            Name: TryInto<&'a FullNameRef, Error = E>,
+            crate::name::Error: From<E>,
+        {
+            let name: &FullNameRef = name.try_into().map_err(|err| Error::RefnameValidation(err.into()))?;
+            if self.reftable_for(name).is_some() {
+                return Err(Error::Reftable);
+            }
+            let path = self.reflog_path(name);
+            let data = match std::fs::read(&path) {
+                Ok(data) => data,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound || path.is_dir() => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+
+            let mut removed = 0;
+            let mut kept = Vec::with_capacity(data.len());
+            for line in data.lines_with_terminator() {
+                let content = line.strip_suffix(b"\n").unwrap_or(line);
+                match log::LineRef::from_bytes(content) {
+                    Ok(entry) if !keep(&entry) => removed += 1,
+                    _ => kept.extend_from_slice(line),
+                }
+            }
+            if removed == 0 {
+                return Ok(Some(0));
+            }
+
+            let mut lock = gix_lock::File::acquire_to_update_resource(&path, lock_mode, None)?;
+            lock.write_all(&kept)?;
+            lock.commit().map_err(|err| err.error)?;
+            Ok(Some(removed))
+        }
+    }
+
+    mod error {
+        /// The error returned by [`crate::file::Store::reflog_retain()`].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error("The reflog name or path is not a valid ref name")]
+            RefnameValidation(#[from] crate::name::Error),
+            #[error("Reflogs stored in reftables can't be rewritten")]
+            Reftable,
+            #[error("Could not obtain the lock to rewrite the reflog")]
+            LockAcquire(#[from] gix_lock::acquire::Error),
+            #[error("The reflog could not be read or written")]
+            Io(#[from] std::io::Error),
+        }
+    }
+    pub use error::Error;
+}
+
 mod error {
     /// The error returned by [`crate::file::Store::reflog_iter()`].
     #[derive(Debug, thiserror::Error)]
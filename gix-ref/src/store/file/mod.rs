@@ -38,6 +38,8 @@ pub struct Store {
     /// It's updated only in one spot, which is prior to reading it based on file stamps.
     /// Doing it like this has the benefit of being able to hand snapshots out to people without blocking others from updating it.
     packed: packed::modifiable::MutableSharedBuffer,
+    /// The reftable stacks to use instead of loose references and `packed-refs`, if enabled with [`Store::use_reftable()`].
+    reftable: Option<reftable::Stacks>,
 }
 
 mod access {
@@ -85,6 +87,8 @@ mod access {
 pub struct Transaction<'s, 'p> {
     store: &'s Store,
     packed_transaction: Option<crate::store_impl::packed::Transaction>,
+    /// The transactions of all reftable stacks that are affected by the edits, each of which holds the lock on its stack.
+    reftable_transactions: Vec<crate::store_impl::reftable::Transaction>,
     updates: Option<Vec<transaction::Edit>>,
     packed_refs: transaction::PackedRefs<'p>,
 }
@@ -127,5 +131,7 @@ pub mod transaction;
 #[allow(clippy::empty_docs)]
 pub mod packed;
 
+mod reftable;
+
 mod raw_ext;
 pub use raw_ext::ReferenceExt;
@@ -24,6 +24,8 @@ pub struct LooseThenPacked<'p, 's> {
     iter_git_dir: Peekable<SortedLoosePaths>,
     #[allow(dead_code)]
     iter_common_dir: Option<Peekable<SortedLoosePaths>>,
+    /// The references of the reftable, which are the only ones if it is used.
+    iter_reftable: Option<file::reftable::RefIter<'p>>,
     buf: Vec<u8>,
 }
 
@@ -38,6 +40,7 @@ enum IterKind {
 pub struct Platform<'s> {
     store: &'s file::Store,
     packed: Option<file::packed::SharedBufferSnapshot>,
+    reftable: Option<file::reftable::Snapshots>,
 }
 
 impl<'p, 's> LooseThenPacked<'p, 's> {
@@ -116,6 +119,11 @@ impl<'p, 's> Iterator for LooseThenPacked<'p, 's> {
     type Item = Result<Reference, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(iter) = self.iter_reftable.as_mut() {
+            return iter
+                .next()
+                .map(|res| res.map(|r| self.strip_namespace(r)).map_err(Error::Reftable));
+        }
         fn advance_to_non_private(iter: &mut Peekable<SortedLoosePaths>) {
             while let Some(Ok((_path, name))) = iter.peek() {
                 if name.category().map_or(false, |cat| cat.is_worktree_private()) {
@@ -192,15 +200,28 @@ impl<'s> Platform<'s> {
     ///
     /// Errors are returned similarly to what would happen when loose and packed refs where iterated by themselves.
     pub fn all(&self) -> std::io::Result<LooseThenPacked<'_, '_>> {
-        self.store.iter_packed(self.packed.as_ref().map(|b| &***b))
+        let mut iter = self.store.iter_packed(self.packed.as_ref().map(|b| &***b))?;
+        iter.iter_reftable = self
+            .reftable
+            .as_ref()
+            .map(|reftable| reftable.iter(self.store.namespace.as_ref(), None))
+            .transpose()?;
+        Ok(iter)
     }
 
     /// As [`iter(…)`][file::Store::iter()], but filters by `prefix`, i.e. "refs/heads".
     ///
     /// Please note that "refs/heads" or "refs\\heads" is equivalent to "refs/heads/"
     pub fn prefixed(&self, prefix: &Path) -> std::io::Result<LooseThenPacked<'_, '_>> {
-        self.store
-            .iter_prefixed_packed(prefix, self.packed.as_ref().map(|b| &***b))
+        let mut iter = self
+            .store
+            .iter_prefixed_packed(prefix, self.packed.as_ref().map(|b| &***b))?;
+        iter.iter_reftable = self
+            .reftable
+            .as_ref()
+            .map(|reftable| reftable.iter(self.store.namespace.as_ref(), Some(prefix)))
+            .transpose()?;
+        Ok(iter)
     }
 }
 
@@ -211,10 +232,15 @@ impl file::Store {
     ///
     /// Note that since packed-refs are storing refs as precomposed unicode if [`Self::precompose_unicode`] is true, for consistency
     /// we also return loose references as precomposed unicode.
+    ///
+    /// If [the reftable is used](file::Store::use_reftable()), only the references in the reftable are returned.
     pub fn iter(&self) -> Result<Platform<'_>, packed::buffer::open::Error> {
         Ok(Platform {
             store: self,
             packed: self.assure_packed_refs_uptodate()?,
+            reftable: self
+                .reftable_snapshots()
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?,
         })
     }
 }
@@ -410,6 +436,20 @@ impl file::Store {
         common_dir_info: Option<IterInfo<'_>>,
         packed: Option<&'p packed::Buffer>,
     ) -> std::io::Result<LooseThenPacked<'p, 's>> {
+        if self.reftable.is_some() {
+            // There are no loose references or `packed-refs` if the reftable is used, but files that look like them
+            // for compatibility with older versions of git.
+            return Ok(LooseThenPacked {
+                git_dir: self.git_dir(),
+                common_dir: self.common_dir(),
+                iter_packed: None,
+                iter_git_dir: SortedLoosePaths::empty(self.git_dir.clone()).peekable(),
+                iter_common_dir: None,
+                iter_reftable: None,
+                buf: Vec::new(),
+                namespace: self.namespace.as_ref(),
+            });
+        }
         Ok(LooseThenPacked {
             git_dir: self.git_dir(),
             common_dir: self.common_dir(),
@@ -426,6 +466,7 @@ impl file::Store {
             },
             iter_git_dir: git_dir_info.into_iter(),
             iter_common_dir: common_dir_info.map(IterInfo::into_iter),
+            iter_reftable: None,
             buf: Vec::new(),
             namespace: self.namespace.as_ref(),
         })
@@ -437,7 +478,7 @@ mod error {
 
     use gix_object::bstr::BString;
 
-    use crate::store_impl::file;
+    use crate::store_impl::{file, reftable};
 
     /// The error returned by the [`LooseThenPacked`][super::LooseThenPacked] iterator.
     #[derive(Debug, thiserror::Error)]
@@ -454,6 +495,8 @@ mod error {
         },
         #[error("Invalid reference in line {line_number}: {invalid_line:?}")]
         PackedReference { invalid_line: BString, line_number: usize },
+        #[error("The reftable could not be read")]
+        Reftable(#[source] reftable::stack::Error),
     }
 }
 pub use error::Error;
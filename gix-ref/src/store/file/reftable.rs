@@ -0,0 +1,276 @@
+use std::{borrow::Cow, cmp::Ordering, iter::Peekable, path::Path};
+
+use gix_hash::ObjectId;
+use gix_object::bstr::{BStr, BString, ByteSlice};
+
+use crate::{
+    file,
+    file::{log, path_to_name, WriteReflog},
+    store_impl::reftable,
+    FullName, FullNameRef, Namespace, Reference, Target,
+};
+
+/// The reftable stacks used by a [`file::Store`] in place of loose references and `packed-refs`.
+#[derive(Debug, Clone)]
+pub(crate) struct Stacks {
+    /// The stack in the `git_dir`, holding the private references of a linked worktree, or all references otherwise.
+    git_dir: reftable::Store,
+    /// The stack in the common directory, holding all shared references, if the store is for a linked worktree.
+    common_dir: Option<reftable::Store>,
+}
+
+/// Reftable
+impl file::Store {
+    /// Keep references in the reftable stack of the repository instead of storing them in loose files and `packed-refs`,
+    /// which is what git does if `extensions.refStorage` is `reftable`.
+    ///
+    /// `FETCH_HEAD` and `MERGE_HEAD` remain loose files, and linked worktrees keep their private references in a stack of their own.
+    pub fn use_reftable(&mut self) {
+        self.reftable = Some(Stacks {
+            git_dir: reftable::Store::at(self.git_dir.join("reftable"), self.object_hash),
+            common_dir: self
+                .common_dir
+                .as_ref()
+                .map(|dir| reftable::Store::at(dir.join("reftable"), self.object_hash)),
+        });
+    }
+
+    /// Return the reftable stack holding all shared references if [the reftable is used](file::Store::use_reftable()).
+    pub fn reftable(&self) -> Option<&reftable::Store> {
+        self.reftable
+            .as_ref()
+            .map(|stacks| stacks.common_dir.as_ref().unwrap_or(&stacks.git_dir))
+    }
+
+    /// Return the reftable stack that stores the reference `name` along with the name it has in that stack,
+    /// or `None` if the reftable isn't used or if the reference is always stored in a loose file.
+    pub(crate) fn reftable_for(&self, name: &FullNameRef) -> Option<(Cow<'_, reftable::Store>, FullName)> {
+        let stacks = self.reftable.as_ref()?;
+        if matches!(name.as_bstr().as_bytes(), b"FETCH_HEAD" | b"MERGE_HEAD") {
+            return None;
+        }
+        let is_reflog = true;
+        let (base, short_name) = self.to_base_dir_and_relative_name(name, is_reflog);
+        let stack = if base == self.git_dir {
+            Cow::Borrowed(&stacks.git_dir)
+        } else if Some(base.as_ref()) == self.common_dir.as_deref() {
+            Cow::Borrowed(stacks.common_dir.as_ref().expect("present if there is a common dir"))
+        } else {
+            Cow::Owned(reftable::Store::at(base.join("reftable"), self.object_hash))
+        };
+        let name = match &self.namespace {
+            Some(namespace) => namespace.to_owned().into_namespaced_name(short_name),
+            None => short_name.to_owned(),
+        };
+        Some((stack, name))
+    }
+
+    /// Find the reference stored as `name` in `stack` and return it as `full_name`.
+    pub(crate) fn find_in_reftable(
+        &self,
+        stack: &reftable::Store,
+        name: &FullNameRef,
+        full_name: &FullNameRef,
+    ) -> Result<Option<Reference>, reftable::stack::Error> {
+        let Some(snapshot) = stack.stack()? else {
+            return Ok(None);
+        };
+        Ok(snapshot.find_ref(name)?.and_then(|record| {
+            let mut reference = reference_from_record(record)?;
+            reference.name = full_name.to_owned();
+            if let Some(namespace) = &self.namespace {
+                reference.strip_namespace(namespace);
+            }
+            Some(reference)
+        }))
+    }
+
+    /// Return all log entries of `name` as they would be stored in a loose reflog file, or `None` if the reftable isn't used
+    /// for `name`. The inner `None` indicates that there is no log.
+    pub(crate) fn reftable_reflog(&self, name: &FullNameRef) -> Option<std::io::Result<Option<Vec<u8>>>> {
+        let (stack, name) = self.reftable_for(name)?;
+        let read = || -> Result<Option<Vec<u8>>, reftable::stack::Error> {
+            let Some(snapshot) = stack.stack()? else {
+                return Ok(None);
+            };
+            let lines = snapshot
+                .logs_of(name.as_ref())?
+                .filter_map(|record| match record {
+                    Ok(reftable::LogRecord {
+                        value: reftable::LogValue::Update(line),
+                        ..
+                    }) => Some(Ok(line)),
+                    Ok(_) => None,
+                    Err(err) => Some(Err(err)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if lines.is_empty() {
+                return Ok(None);
+            }
+            let mut out = Vec::new();
+            for line in lines.iter().rev() {
+                line.write_to(&mut out)
+                    .expect("messages in reftables never contain newlines");
+            }
+            Ok(Some(out))
+        };
+        Some(read().map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err)))
+    }
+
+    /// Like [`reflog_create_or_append()`](file::Store::reflog_create_or_append()), but return the log record to write into
+    /// a reftable as `name` instead, if one should be written.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn reftable_log_record(
+        &self,
+        name: &FullName,
+        update_index: u64,
+        previous_oid: Option<ObjectId>,
+        new: &ObjectId,
+        committer: Option<gix_actor::SignatureRef<'_>>,
+        message: &BStr,
+        force_create_reflog: bool,
+        log_exists: bool,
+    ) -> Result<Option<reftable::LogRecord>, log::create_or_update::Error> {
+        let write = match self.write_reflog {
+            WriteReflog::Always => true,
+            WriteReflog::Normal => {
+                force_create_reflog || log_exists || self.should_autocreate_reflog(&gix_path::from_bstr(name.as_bstr()))
+            }
+            WriteReflog::Disable => false,
+        };
+        if !write {
+            return Ok(None);
+        }
+        let committer = committer.ok_or(log::create_or_update::Error::MissingCommitter)?;
+        // Like git, fold multi-line messages into a single line as the reftable can't store them otherwise.
+        let message: BString = message
+            .lines()
+            .map(ByteSlice::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(&b' ')
+            .into();
+        Ok(Some(reftable::LogRecord {
+            name: name.clone(),
+            update_index,
+            value: reftable::LogValue::Update(crate::log::Line {
+                previous_oid: previous_oid.unwrap_or_else(|| new.kind().null()),
+                new_oid: *new,
+                signature: committer.to_owned(),
+                message,
+            }),
+        }))
+    }
+}
+
+/// Convert `record` into a reference of the same name, or return `None` if it is a deletion.
+pub(crate) fn reference_from_record(record: reftable::RefRecord) -> Option<Reference> {
+    let (target, peeled) = match record.value {
+        reftable::RefValue::Deletion => return None,
+        reftable::RefValue::Object(id) => (Target::Peeled(id), None),
+        reftable::RefValue::ObjectAndPeeled { target, peeled } => (Target::Peeled(target), Some(peeled)),
+        reftable::RefValue::Symbolic(name) => (Target::Symbolic(name), None),
+    };
+    Some(Reference {
+        name: record.name,
+        target,
+        peeled,
+    })
+}
+
+/// Snapshots of all reftable stacks that hold references below `refs/`, taken when iteration starts.
+pub(crate) struct Snapshots {
+    git_dir: Option<reftable::SharedStackSnapshot>,
+    common_dir: Option<reftable::SharedStackSnapshot>,
+}
+
+impl file::Store {
+    pub(crate) fn reftable_snapshots(&self) -> Result<Option<Snapshots>, reftable::stack::Error> {
+        let Some(stacks) = self.reftable.as_ref() else {
+            return Ok(None);
+        };
+        Ok(Some(Snapshots {
+            git_dir: stacks.git_dir.stack()?,
+            common_dir: stacks
+                .common_dir
+                .as_ref()
+                .map(reftable::Store::stack)
+                .transpose()?
+                .flatten(),
+        }))
+    }
+}
+
+impl Snapshots {
+    /// Return an iterator over all references below `refs/` whose names start with `prefix`, which is a path like `refs/heads`.
+    pub(crate) fn iter(&self, namespace: Option<&Namespace>, prefix: Option<&Path>) -> std::io::Result<RefIter<'_>> {
+        let prefix: BString = match (namespace, prefix) {
+            (None, None) => "refs/".into(),
+            (None, Some(prefix)) => path_to_name(prefix).into_owned(),
+            (Some(namespace), None) => namespace.as_bstr().to_owned(),
+            (Some(namespace), Some(prefix)) => {
+                path_to_name(namespace.to_owned().into_namespaced_prefix(prefix)).into_owned()
+            }
+        };
+        fn iter<'a>(
+            snapshot: &'a Option<reftable::SharedStackSnapshot>,
+            prefix: &BStr,
+        ) -> std::io::Result<Option<Peekable<reftable::stack::RefIter<'a>>>> {
+            snapshot
+                .as_ref()
+                .map(|stack| stack.refs_prefixed(prefix).map(Iterator::peekable))
+                .transpose()
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+        }
+        Ok(RefIter {
+            git_dir: iter(&self.git_dir, prefix.as_ref())?,
+            common_dir: iter(&self.common_dir, prefix.as_ref())?,
+        })
+    }
+}
+
+/// An iterator over the references below `refs/` of the reftable stacks of a worktree, which merges the private references
+/// of a linked worktree with the shared ones.
+pub(crate) struct RefIter<'p> {
+    git_dir: Option<Peekable<reftable::stack::RefIter<'p>>>,
+    common_dir: Option<Peekable<reftable::stack::RefIter<'p>>>,
+}
+
+impl<'p> Iterator for RefIter<'p> {
+    type Item = Result<Reference, reftable::stack::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        fn is_private(record: &reftable::RefRecord) -> bool {
+            matches!(record.name.category(), Some(cat) if cat.is_worktree_private())
+        }
+        loop {
+            let record = match (self.git_dir.as_mut(), self.common_dir.as_mut()) {
+                (Some(git_dir), Some(common_dir)) => {
+                    // Private references of other worktrees don't show up in linked worktrees.
+                    while common_dir.next_if(|r| matches!(r, Ok(r) if is_private(r))).is_some() {}
+                    match (git_dir.peek(), common_dir.peek()) {
+                        (Some(Ok(a)), Some(Ok(b))) => match a.name.cmp(&b.name) {
+                            Ordering::Less => git_dir.next(),
+                            Ordering::Equal => {
+                                common_dir.next();
+                                git_dir.next()
+                            }
+                            Ordering::Greater => common_dir.next(),
+                        },
+                        (Some(Err(_)), _) | (Some(_), None) => git_dir.next(),
+                        (None, _) | (_, Some(Err(_))) => common_dir.next(),
+                    }
+                }
+                (Some(iter), None) | (None, Some(iter)) => iter.next(),
+                (None, None) => None,
+            }?;
+            match record {
+                Ok(record) => match reference_from_record(record) {
+                    Some(reference) => return Some(Ok(reference)),
+                    None => continue,
+                },
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
@@ -1,6 +1,15 @@
+use gix_hash::ObjectId;
+
 use crate::{
-    store_impl::file::{transaction::PackedRefs, Transaction},
-    transaction::{Change, LogChange, RefEdit, RefLog},
+    store_impl::{
+        file,
+        file::{
+            transaction::{Edit, PackedRefs},
+            Transaction,
+        },
+        reftable,
+    },
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
     Target,
 };
 
@@ -36,8 +45,12 @@ impl<'s, 'p> Transaction<'s, 'p> {
             PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
         );
 
+        if !self.reftable_transactions.is_empty() {
+            Self::commit_reftable_changes(self.store, self.reftable_transactions, &updates, committer)?;
+        }
+
         // Perform updates first so live commits remain referenced
-        for change in &mut updates {
+        for change in updates.iter_mut().filter(|change| change.reftable.is_none()) {
             assert!(!change.update.deref, "Deref mode is turned into splits and turned off");
             match &change.update.change {
                 // reflog first, then reference
@@ -48,41 +61,17 @@ impl<'s, 'p> Transaction<'s, 'p> {
                         RefLog::AndReference => (true, true),
                     };
                     if update_reflog {
-                        let log_update = match new {
-                            Target::Symbolic(_) => {
-                                // Special HACK: no reflog for symref changes as there is no OID involved which the reflog needs.
-                                // Unless, the ref is new and we can obtain a peeled id
-                                // identified by the expectation of what could be there, as is the case when cloning.
-                                match expected {
-                                    PreviousValue::ExistingMustMatch(Target::Peeled(oid)) => {
-                                        Some((Some(gix_hash::ObjectId::null(oid.kind())), oid))
-                                    }
-                                    _ => None,
-                                }
-                            }
-                            Target::Peeled(new_oid) => {
-                                let previous = match expected {
-                                    // Here, this means that the ref already existed, and that it will receive (even transitively)
-                                    // the given value
-                                    PreviousValue::MustExistAndMatch(Target::Peeled(oid)) => Some(oid.to_owned()),
-                                    _ => None,
-                                }
-                                .or(change.leaf_referent_previous_oid);
-                                Some((previous, new_oid))
-                            }
-                        };
-                        if let Some((previous, new_oid)) = log_update {
-                            let do_update = previous.as_ref().map_or(true, |previous| previous != new_oid);
-                            if do_update {
-                                self.store.reflog_create_or_append(
-                                    change.update.name.as_ref(),
-                                    previous,
-                                    new_oid,
-                                    committer,
-                                    log.message.as_ref(),
-                                    log.force_create_reflog,
-                                )?;
-                            }
+                        if let Some((previous, new_oid)) =
+                            reflog_update(new, expected, change.leaf_referent_previous_oid)
+                        {
+                            self.store.reflog_create_or_append(
+                                change.update.name.as_ref(),
+                                previous,
+                                new_oid,
+                                committer,
+                                log.message.as_ref(),
+                                log.force_create_reflog,
+                            )?;
                         }
                     }
                     // Don't do anything else while keeping the lock after potentially updating the reflog.
@@ -117,7 +106,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
             }
         }
 
-        for change in &mut updates {
+        for change in updates.iter_mut().filter(|change| change.reftable.is_none()) {
             let (reflog_root, relative_name) = self.store.reflog_base_and_relative_path(change.update.name.as_ref());
             match &change.update.change {
                 Change::Update { .. } => {}
@@ -150,7 +139,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
             self.store.force_refresh_packed_buffer().ok();
         }
 
-        for change in &mut updates {
+        for change in updates.iter_mut().filter(|change| change.reftable.is_none()) {
             let take_lock_and_delete = match &change.update.change {
                 Change::Update {
                     log: LogChange { mode, .. },
@@ -175,11 +164,114 @@ impl<'s, 'p> Transaction<'s, 'p> {
         }
         Ok(updates.into_iter().map(|edit| edit.update).collect())
     }
+
+    /// Write all `updates` of references stored in a reftable into a new table of their stack, using the respective transaction
+    /// in `transactions`.
+    fn commit_reftable_changes(
+        store: &file::Store,
+        transactions: Vec<reftable::Transaction>,
+        updates: &[Edit],
+        committer: Option<gix_actor::SignatureRef<'_>>,
+    ) -> Result<(), Error> {
+        let mut records: Vec<(Vec<reftable::RefRecord>, Vec<reftable::LogRecord>)> =
+            transactions.iter().map(|_| Default::default()).collect();
+        for change in updates {
+            let Some(idx) = change.reftable else { continue };
+            let (transaction, (refs, logs)) = (&transactions[idx], &mut records[idx]);
+            let (_stack, name) = store
+                .reftable_for(change.update.name.as_ref())
+                .expect("reference was found to be in a reftable when preparing");
+            let update_index = transaction.next_update_index();
+            let existing_logs = || -> Result<Vec<reftable::LogRecord>, Error> {
+                Ok(transaction
+                    .stack()
+                    .logs_of(name.as_ref())
+                    .and_then(Iterator::collect)
+                    .map_err(reftable::transaction::Error::from)?)
+            };
+            match &change.update.change {
+                Change::Update { log, new, expected } => {
+                    if log.mode == RefLog::AndReference {
+                        refs.push(reftable::RefRecord {
+                            name: name.clone(),
+                            update_index,
+                            value: match new {
+                                Target::Peeled(id) => reftable::RefValue::Object(*id),
+                                Target::Symbolic(target) => reftable::RefValue::Symbolic(target.clone()),
+                            },
+                        });
+                    }
+                    if let Some((previous, new_oid)) = reflog_update(new, expected, change.leaf_referent_previous_oid) {
+                        let log_exists = !existing_logs()?.is_empty();
+                        logs.extend(store.reftable_log_record(
+                            &name,
+                            update_index,
+                            previous,
+                            new_oid,
+                            committer,
+                            log.message.as_ref(),
+                            log.force_create_reflog,
+                            log_exists,
+                        )?);
+                    }
+                }
+                Change::Delete { log: mode, .. } => {
+                    logs.extend(existing_logs()?.into_iter().map(|record| reftable::LogRecord {
+                        value: reftable::LogValue::Deletion,
+                        ..record
+                    }));
+                    if *mode == RefLog::AndReference {
+                        refs.push(reftable::RefRecord {
+                            name,
+                            update_index,
+                            value: reftable::RefValue::Deletion,
+                        });
+                    }
+                }
+            }
+        }
+        for (transaction, (refs, logs)) in transactions.into_iter().zip(records) {
+            transaction.commit(refs, logs)?;
+        }
+        Ok(())
+    }
+}
+
+/// Return the previous and the new object id to record in the reflog when applying `new` to a reference with the `expected`
+/// previous value, or `None` if no log entry should be written.
+fn reflog_update<'a>(
+    new: &'a Target,
+    expected: &'a PreviousValue,
+    leaf_referent_previous_oid: Option<ObjectId>,
+) -> Option<(Option<ObjectId>, &'a ObjectId)> {
+    let (previous, new_oid) = match new {
+        Target::Symbolic(_) => {
+            // Special HACK: no reflog for symref changes as there is no OID involved which the reflog needs.
+            // Unless, the ref is new and we can obtain a peeled id
+            // identified by the expectation of what could be there, as is the case when cloning.
+            match expected {
+                PreviousValue::ExistingMustMatch(Target::Peeled(oid)) => (Some(ObjectId::null(oid.kind())), oid),
+                _ => return None,
+            }
+        }
+        Target::Peeled(new_oid) => {
+            let previous = match expected {
+                // Here, this means that the ref already existed, and that it will receive (even transitively)
+                // the given value
+                PreviousValue::MustExistAndMatch(Target::Peeled(oid)) => Some(oid.to_owned()),
+                _ => None,
+            }
+            .or(leaf_referent_previous_oid);
+            (previous, new_oid)
+        }
+    };
+    let do_update = previous.as_ref() != Some(new_oid);
+    do_update.then_some((previous, new_oid))
 }
 mod error {
     use gix_object::bstr::BString;
 
-    use crate::store_impl::{file, packed};
+    use crate::store_impl::{file, packed, reftable};
 
     /// The error returned by various [`Transaction`][super::Transaction] methods.
     #[derive(Debug, thiserror::Error)]
@@ -197,8 +289,8 @@ mod error {
         DeleteReflog { full_name: BString, source: std::io::Error },
         #[error("The reflog could not be created or updated")]
         CreateOrUpdateRefLog(#[from] file::log::create_or_update::Error),
+        #[error("The changes to the reftable could not be committed")]
+        Reftable(#[from] reftable::transaction::Error),
     }
 }
 pub use error::Error;
-
-use crate::transaction::PreviousValue;
@@ -39,6 +39,8 @@ pub(in crate::store_impl::file) struct Edit {
     /// For symbolic refs, this is the previous OID to put into the reflog instead of our own previous value. It's the
     /// peeled value of the leaf referent.
     leaf_referent_previous_oid: Option<ObjectId>,
+    /// The index of the reftable transaction this edit is applied in, if the reference is stored in a reftable.
+    reftable: Option<usize>,
 }
 
 impl Edit {
@@ -71,6 +73,7 @@ impl file::Store {
         Transaction {
             store: self,
             packed_transaction: None,
+            reftable_transactions: Vec::new(),
             updates: None,
             packed_refs: PackedRefs::default(),
         }
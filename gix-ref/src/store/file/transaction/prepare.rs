@@ -8,6 +8,7 @@ use crate::{
             transaction::{Edit, PackedRefs},
             Transaction,
         },
+        reftable,
     },
     transaction::{Change, LogChange, PreviousValue, RefEdit, RefEditsExt, RefLog},
     FullName, FullNameRef, Reference, Target,
@@ -70,39 +71,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     .into()
                 };
 
-                let existing_ref = existing_ref?;
-                match (&expected, &existing_ref) {
-                    (PreviousValue::MustNotExist, _) => {
-                        panic!("BUG: MustNotExist constraint makes no sense if references are to be deleted")
-                    }
-                    (PreviousValue::ExistingMustMatch(_) | PreviousValue::Any, None)
-                    | (PreviousValue::MustExist | PreviousValue::Any, Some(_)) => {}
-                    (PreviousValue::MustExist | PreviousValue::MustExistAndMatch(_), None) => {
-                        return Err(Error::DeleteReferenceMustExist {
-                            full_name: change.name(),
-                        })
-                    }
-                    (
-                        PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous),
-                        Some(existing),
-                    ) => {
-                        let actual = existing.target.clone();
-                        if *previous != actual {
-                            let expected = previous.clone();
-                            return Err(Error::ReferenceOutOfDate {
-                                full_name: change.name(),
-                                expected,
-                                actual,
-                            });
-                        }
-                    }
-                }
-
-                // Keep the previous value for the caller and ourselves. Maybe they want to keep a log of sorts.
-                if let Some(existing) = existing_ref {
-                    *expected = PreviousValue::MustExistAndMatch(existing.target);
-                }
-
+                verify_deletion(&change.update.name, expected, existing_ref?)?;
                 lock
             }
             Change::Update { expected, new, .. } => {
@@ -120,65 +89,8 @@ impl<'s, 'p> Transaction<'s, 'p> {
                 };
                 let mut lock = (!has_global_lock).then(obtain_lock).transpose()?;
 
-                let existing_ref = existing_ref?;
-                match (&expected, &existing_ref) {
-                    (PreviousValue::Any, _)
-                    | (PreviousValue::MustExist, Some(_))
-                    | (PreviousValue::MustNotExist | PreviousValue::ExistingMustMatch(_), None) => {}
-                    (PreviousValue::MustExist, None) => {
-                        let expected = Target::Peeled(store.object_hash.null());
-                        let full_name = change.name();
-                        return Err(Error::MustExist { full_name, expected });
-                    }
-                    (PreviousValue::MustNotExist, Some(existing)) => {
-                        if existing.target != *new {
-                            let new = new.clone();
-                            return Err(Error::MustNotExist {
-                                full_name: change.name(),
-                                actual: existing.target.clone(),
-                                new,
-                            });
-                        }
-                    }
-                    (
-                        PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous),
-                        Some(existing),
-                    ) => {
-                        if *previous != existing.target {
-                            let actual = existing.target.clone();
-                            let expected = previous.to_owned();
-                            let full_name = change.name();
-                            return Err(Error::ReferenceOutOfDate {
-                                full_name,
-                                actual,
-                                expected,
-                            });
-                        }
-                    }
-
-                    (PreviousValue::MustExistAndMatch(previous), None) => {
-                        let expected = previous.to_owned();
-                        let full_name = change.name();
-                        return Err(Error::MustExist { full_name, expected });
-                    }
-                };
-
-                fn new_would_change_existing(new: &Target, existing: &Target) -> (bool, bool) {
-                    match (new, existing) {
-                        (Target::Peeled(new), Target::Peeled(old)) => (old != new, false),
-                        (Target::Symbolic(new), Target::Symbolic(old)) => (old != new, true),
-                        (Target::Peeled(_), _) => (true, false),
-                        (Target::Symbolic(_), _) => (true, true),
-                    }
-                }
-
-                let (is_effective, is_symbolic) = if let Some(existing) = existing_ref {
-                    let (effective, is_symbolic) = new_would_change_existing(new, &existing.target);
-                    *expected = PreviousValue::MustExistAndMatch(existing.target);
-                    (effective, is_symbolic)
-                } else {
-                    (true, matches!(new, Target::Symbolic(_)))
-                };
+                let (is_effective, is_symbolic) =
+                    verify_update(store.object_hash, &change.update.name, expected, new, existing_ref?)?;
 
                 if (is_effective && !direct_to_packed_refs) || is_symbolic {
                     let mut lock = lock.take().map_or_else(obtain_lock, Ok)?;
@@ -196,6 +108,38 @@ impl<'s, 'p> Transaction<'s, 'p> {
         change.lock = lock;
         Ok(())
     }
+
+    /// Verify `change` against the reftable stack holding its reference, whose transaction is created in `transactions`
+    /// if needed to lock the stack.
+    fn apply_change_in_reftable(
+        store: &file::Store,
+        transactions: &mut Vec<reftable::Transaction>,
+        lock_fail_mode: gix_lock::acquire::Fail,
+        change: &mut Edit,
+    ) -> Result<(), Error> {
+        let (stack, name) = store
+            .reftable_for(change.update.name.as_ref())
+            .expect("caller checked that the reference is in a reftable");
+        let idx = match transactions.iter().position(|t| t.store().dir() == stack.dir()) {
+            Some(idx) => idx,
+            None => {
+                transactions.push(stack.transaction(lock_fail_mode)?);
+                transactions.len() - 1
+            }
+        };
+        let existing_ref = transactions[idx]
+            .stack()
+            .find_ref(name.as_ref())?
+            .and_then(file::reftable::reference_from_record);
+        match &mut change.update.change {
+            Change::Delete { expected, .. } => verify_deletion(&change.update.name, expected, existing_ref)?,
+            Change::Update { expected, new, .. } => {
+                verify_update(store.object_hash, &change.update.name, expected, new, existing_ref)?;
+            }
+        }
+        change.reftable = Some(idx);
+        Ok(())
+    }
 }
 
 impl<'s, 'p> Transaction<'s, 'p> {
@@ -231,6 +175,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
                 lock: None,
                 parent_index: None,
                 leaf_referent_previous_oid: None,
+                reftable: None,
             })
             .collect();
         updates
@@ -247,6 +192,7 @@ impl<'s, 'p> Transaction<'s, 'p> {
                     lock: None,
                     parent_index: Some(idx),
                     leaf_referent_previous_oid: None,
+                    reftable: None,
                 },
             )
             .map_err(Error::PreprocessingFailed)?;
@@ -256,9 +202,11 @@ impl<'s, 'p> Transaction<'s, 'p> {
             | PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_) => Some(0_usize),
             PackedRefs::DeletionsOnly => None,
         };
-        if maybe_updates_for_packed_refs.is_some()
-            || self.store.packed_refs_path().is_file()
-            || self.store.packed_refs_lock_path().is_file()
+        let uses_reftable = self.store.reftable.is_some();
+        if !uses_reftable
+            && (maybe_updates_for_packed_refs.is_some()
+                || self.store.packed_refs_path().is_file()
+                || self.store.packed_refs_lock_path().is_file())
         {
             let mut edits_for_packed_transaction = Vec::<RefEdit>::new();
             let mut needs_packed_refs_lookups = false;
@@ -354,17 +302,27 @@ impl<'s, 'p> Transaction<'s, 'p> {
 
         for cid in 0..updates.len() {
             let change = &mut updates[cid];
-            if let Err(err) = Self::lock_ref_and_apply_change(
-                self.store,
-                ref_files_lock_fail_mode,
-                self.packed_transaction.as_ref().and_then(packed::Transaction::buffer),
-                change,
-                self.packed_transaction.is_some(),
-                matches!(
-                    self.packed_refs,
-                    PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
-                ),
-            ) {
+            let res = if uses_reftable && self.store.reftable_for(change.update.name.as_ref()).is_some() {
+                Self::apply_change_in_reftable(
+                    self.store,
+                    &mut self.reftable_transactions,
+                    ref_files_lock_fail_mode,
+                    change,
+                )
+            } else {
+                Self::lock_ref_and_apply_change(
+                    self.store,
+                    ref_files_lock_fail_mode,
+                    self.packed_transaction.as_ref().and_then(packed::Transaction::buffer),
+                    change,
+                    self.packed_transaction.is_some(),
+                    matches!(
+                        self.packed_refs,
+                        PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
+                    ),
+                )
+            };
+            if let Err(err) = res {
                 let err = match err {
                     Error::LockAcquire {
                         source,
@@ -422,6 +380,110 @@ impl<'s, 'p> Transaction<'s, 'p> {
     }
 }
 
+/// Check the expectation of the deletion of `name` against the `existing_ref`, and remember the previous value in `expected`.
+fn verify_deletion(
+    name: &FullName,
+    expected: &mut PreviousValue,
+    existing_ref: Option<Reference>,
+) -> Result<(), Error> {
+    match (&expected, &existing_ref) {
+        (PreviousValue::MustNotExist, _) => {
+            panic!("BUG: MustNotExist constraint makes no sense if references are to be deleted")
+        }
+        (PreviousValue::ExistingMustMatch(_) | PreviousValue::Any, None)
+        | (PreviousValue::MustExist | PreviousValue::Any, Some(_)) => {}
+        (PreviousValue::MustExist | PreviousValue::MustExistAndMatch(_), None) => {
+            return Err(Error::DeleteReferenceMustExist {
+                full_name: name.0.clone(),
+            })
+        }
+        (PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous), Some(existing)) => {
+            let actual = existing.target.clone();
+            if *previous != actual {
+                let expected = previous.clone();
+                return Err(Error::ReferenceOutOfDate {
+                    full_name: name.0.clone(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+
+    // Keep the previous value for the caller and ourselves. Maybe they want to keep a log of sorts.
+    if let Some(existing) = existing_ref {
+        *expected = PreviousValue::MustExistAndMatch(existing.target);
+    }
+    Ok(())
+}
+
+/// Check the expectation of the update of `name` to `new` against the `existing_ref`, and remember the previous value in `expected`.
+///
+/// Return `(is_effective, is_symbolic)` to indicate if the update changes the reference, and if either its old or new value is symbolic.
+fn verify_update(
+    object_hash: gix_hash::Kind,
+    name: &FullName,
+    expected: &mut PreviousValue,
+    new: &Target,
+    existing_ref: Option<Reference>,
+) -> Result<(bool, bool), Error> {
+    match (&expected, &existing_ref) {
+        (PreviousValue::Any, _)
+        | (PreviousValue::MustExist, Some(_))
+        | (PreviousValue::MustNotExist | PreviousValue::ExistingMustMatch(_), None) => {}
+        (PreviousValue::MustExist, None) => {
+            let expected = Target::Peeled(object_hash.null());
+            let full_name = name.0.clone();
+            return Err(Error::MustExist { full_name, expected });
+        }
+        (PreviousValue::MustNotExist, Some(existing)) => {
+            if existing.target != *new {
+                let new = new.clone();
+                return Err(Error::MustNotExist {
+                    full_name: name.0.clone(),
+                    actual: existing.target.clone(),
+                    new,
+                });
+            }
+        }
+        (PreviousValue::MustExistAndMatch(previous) | PreviousValue::ExistingMustMatch(previous), Some(existing)) => {
+            if *previous != existing.target {
+                let actual = existing.target.clone();
+                let expected = previous.to_owned();
+                let full_name = name.0.clone();
+                return Err(Error::ReferenceOutOfDate {
+                    full_name,
+                    actual,
+                    expected,
+                });
+            }
+        }
+
+        (PreviousValue::MustExistAndMatch(previous), None) => {
+            let expected = previous.to_owned();
+            let full_name = name.0.clone();
+            return Err(Error::MustExist { full_name, expected });
+        }
+    };
+
+    fn new_would_change_existing(new: &Target, existing: &Target) -> (bool, bool) {
+        match (new, existing) {
+            (Target::Peeled(new), Target::Peeled(old)) => (old != new, false),
+            (Target::Symbolic(new), Target::Symbolic(old)) => (old != new, true),
+            (Target::Peeled(_), _) => (true, false),
+            (Target::Symbolic(_), _) => (true, true),
+        }
+    }
+
+    Ok(if let Some(existing) = existing_ref {
+        let (effective, is_symbolic) = new_would_change_existing(new, &existing.target);
+        *expected = PreviousValue::MustExistAndMatch(existing.target);
+        (effective, is_symbolic)
+    } else {
+        (true, matches!(new, Target::Symbolic(_)))
+    })
+}
+
 fn possibly_adjust_name_for_prefixes(name: &FullNameRef) -> Option<FullName> {
     match name.category_and_short_name() {
         Some((c, sn)) => {
@@ -445,7 +507,7 @@ mod error {
     use gix_object::bstr::BString;
 
     use crate::{
-        store_impl::{file, packed},
+        store_impl::{file, packed, reftable},
         Target,
     };
 
@@ -488,6 +550,10 @@ mod error {
         },
         #[error("Could not read reference")]
         ReferenceDecode(#[from] file::loose::reference::decode::Error),
+        #[error("The reftable could not be locked")]
+        ReftableTransaction(#[from] reftable::transaction::Error),
+        #[error("The reftable could not be read")]
+        ReftableFind(#[from] reftable::stack::Error),
     }
 }
 
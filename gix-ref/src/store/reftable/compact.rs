@@ -0,0 +1,93 @@
+use std::{io::Write, ops::Range};
+
+use crate::store_impl::{
+    reftable,
+    reftable::{decode, stack, transaction, write, Store, Table},
+};
+
+/// The factor by which each table should be larger than the one that follows it.
+const FACTOR: u64 = 2;
+
+/// The size of `table` as used to decide about compaction, which doesn't count the header that every table has.
+pub(crate) fn size_for_compaction(table: &Table) -> u64 {
+    table
+        .size_in_bytes()
+        .saturating_sub(decode::header_len(table.version()) as u64 - 1)
+}
+
+/// Return the range of tables with the given `sizes`, oldest first, that should be merged so that each table is at least
+/// twice as large as the one that follows it, or `None` if the stack is in shape already.
+///
+/// This is the same geometric sequence that git maintains.
+pub(crate) fn auto_compaction_segment(sizes: &[u64]) -> Option<Range<usize>> {
+    let mut end = None;
+    let mut bytes = 0;
+    let mut idx = sizes.len().checked_sub(1)?;
+    while idx > 0 {
+        if sizes[idx - 1] < sizes[idx] * FACTOR {
+            end = Some(idx + 1);
+            bytes = sizes[idx];
+            break;
+        }
+        idx -= 1;
+    }
+    let end = end?;
+    let mut start = idx;
+    while idx > 0 {
+        let current = bytes;
+        bytes += sizes[idx - 1];
+        if sizes[idx - 1] < current * FACTOR {
+            start = idx - 1;
+        }
+        idx -= 1;
+    }
+    Some(start..end)
+}
+
+/// Merge all records of `tables` into a new table and return its data. Deletions are only kept if `keep_deletions` is `true`,
+/// as they are needed to shadow records in older tables.
+pub(crate) fn merge(
+    tables: &[Table],
+    keep_deletions: bool,
+    options: write::Options,
+    object_hash: gix_hash::Kind,
+) -> Result<Vec<u8>, transaction::Error> {
+    let min_update_index = tables.first().map_or(0, Table::min_update_index);
+    let max_update_index = tables.last().map_or(0, Table::max_update_index);
+    let mut writer = write::Writer::new(Vec::new(), options, object_hash, min_update_index, max_update_index);
+    for record in stack::merged_refs(tables, keep_deletions) {
+        writer.add_ref(&record?)?;
+    }
+    for record in stack::merged_logs(tables, keep_deletions) {
+        writer.add_log(&record?)?;
+    }
+    Ok(writer.finish()?)
+}
+
+/// Compaction
+impl Store {
+    /// Merge all tables of the stack into one, dropping all deleted references and log entries, to make lookups as fast as possible.
+    pub fn compact(&self, lock_mode: gix_lock::acquire::Fail) -> Result<(), transaction::Error> {
+        let mut transaction = self.transaction(lock_mode)?;
+        let tables = transaction.stack.tables();
+        if tables.len() < 2 {
+            return Ok(());
+        }
+        let data = merge(tables, false, self.write_options, self.object_hash)?;
+        let name = transaction::write_table(
+            &self.dir,
+            transaction.stack.tables[0].min_update_index(),
+            transaction.stack.max_update_index(),
+            &data,
+        )?;
+        transaction.lock.write_all(format!("{name}\n").as_bytes())?;
+        let reftable::Transaction { lock, stack, .. } = transaction;
+        lock.commit()?;
+        for name in stack.names() {
+            std::fs::remove_file(self.dir.join(name)).ok();
+        }
+        drop(stack);
+        self.force_refresh()?;
+        Ok(())
+    }
+}
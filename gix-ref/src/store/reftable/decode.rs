@@ -0,0 +1,345 @@
+use std::{borrow::Cow, ops::Range};
+
+use crate::store_impl::reftable::table::Error;
+
+pub(crate) const MAGIC: &[u8; 4] = b"REFT";
+pub(crate) const HASH_ID_SHA1: u32 = u32::from_be_bytes(*b"sha1");
+pub(crate) const HASH_ID_SHA256: u32 = u32::from_be_bytes(*b"s256");
+
+pub(crate) const BLOCK_TYPE_REF: u8 = b'r';
+pub(crate) const BLOCK_TYPE_LOG: u8 = b'g';
+pub(crate) const BLOCK_TYPE_OBJ: u8 = b'o';
+pub(crate) const BLOCK_TYPE_INDEX: u8 = b'i';
+
+/// The size of the 4 byte block header made of the block type and a 24 bit block length.
+pub(crate) const BLOCK_HEADER_LEN: usize = 4;
+
+pub(crate) fn header_len(version: u8) -> usize {
+    match version {
+        1 => 24,
+        _ => 28,
+    }
+}
+
+pub(crate) fn footer_len(version: u8) -> usize {
+    header_len(version) + 5 * 8 + 4
+}
+
+fn corrupt(message: &'static str) -> Error {
+    Error::Corrupt { message }
+}
+
+/// Decode a variable length integer as used by git for offsets in packs, returning it along with the amount of bytes consumed.
+pub(crate) fn varint(data: &[u8]) -> Result<(u64, usize), Error> {
+    let mut bytes = data.iter();
+    let mut byte = *bytes.next().ok_or_else(|| corrupt("truncated varint"))?;
+    let mut value = u64::from(byte & 0x7f);
+    let mut consumed = 1;
+    while byte & 0x80 != 0 {
+        byte = *bytes.next().ok_or_else(|| corrupt("truncated varint"))?;
+        value = value
+            .checked_add(1)
+            .and_then(|v| v.checked_mul(128))
+            .ok_or_else(|| corrupt("varint overflow"))?
+            | u64::from(byte & 0x7f);
+        consumed += 1;
+    }
+    Ok((value, consumed))
+}
+
+pub(crate) fn be24(data: &[u8]) -> u32 {
+    u32::from_be_bytes([0, data[0], data[1], data[2]])
+}
+
+pub(crate) fn be64(data: &[u8]) -> u64 {
+    u64::from_be_bytes(data[..8].try_into().expect("8 bytes"))
+}
+
+/// A block of any type, with log blocks already decompressed.
+pub(crate) struct Block<'a> {
+    /// The data of the block from its start, which for the first block includes the file header.
+    data: Cow<'a, [u8]>,
+    kind: u8,
+    /// The offset to the first record.
+    records_start: usize,
+    /// The offset past the last record, where the restart offsets start.
+    records_end: usize,
+    restart_count: usize,
+    /// The amount of bytes the block occupies in the file, including padding, which is the distance to the next block.
+    size_in_file: usize,
+}
+
+impl<'a> Block<'a> {
+    /// Decode the block at `offset` in `data`, which ends where the table footer starts, or return `None` if there is no block
+    /// at `offset`. `header_len` is the length of the file header which precedes the first block, and `block_size` is the size
+    /// to which blocks are padded.
+    pub(crate) fn at(
+        data: &'a [u8],
+        offset: usize,
+        header_len: usize,
+        block_size: usize,
+    ) -> Result<Option<Self>, Error> {
+        if offset >= data.len() {
+            return Ok(None);
+        }
+        let header_off = if offset == 0 { header_len } else { 0 };
+        let block = &data[offset..];
+        if block.len() < header_off + BLOCK_HEADER_LEN {
+            return Err(corrupt("truncated block header"));
+        }
+        let kind = block[header_off];
+        let block_len = be24(&block[header_off + 1..]) as usize;
+        let records_start = header_off + BLOCK_HEADER_LEN;
+        if block_len < records_start + 2 {
+            return Err(corrupt("block is too small"));
+        }
+        let (data, size_in_file) = match kind {
+            BLOCK_TYPE_LOG => {
+                let mut out = vec![0; block_len];
+                out[..records_start].copy_from_slice(&block[..records_start]);
+                let (status, consumed_in, consumed_out) =
+                    gix_features::zlib::Inflate::default().once(&block[records_start..], &mut out[records_start..])?;
+                if status != gix_features::zlib::Status::StreamEnd || consumed_out != block_len - records_start {
+                    return Err(corrupt("log block did not decompress to its declared size"));
+                }
+                (Cow::Owned(out), records_start + consumed_in)
+            }
+            BLOCK_TYPE_REF | BLOCK_TYPE_OBJ | BLOCK_TYPE_INDEX => {
+                if block_len > block.len() {
+                    return Err(corrupt("block extends past the end of the table"));
+                }
+                // Blocks are either followed by padding up to the block size, or by the next block right away.
+                let size_in_file = match block.get(block_len) {
+                    Some(0) => block_len.max(block_size).min(block.len()),
+                    _ => block_len,
+                };
+                (Cow::Borrowed(&block[..block_len]), size_in_file)
+            }
+            _ => return Err(corrupt("unknown block type")),
+        };
+        let restart_count = u16::from_be_bytes([data[block_len - 2], data[block_len - 1]]) as usize;
+        let records_end = (block_len - 2)
+            .checked_sub(restart_count * 3)
+            .filter(|end| *end >= records_start && restart_count > 0)
+            .ok_or_else(|| corrupt("invalid restart count"))?;
+        Ok(Some(Block {
+            data,
+            kind,
+            records_start,
+            records_end,
+            restart_count,
+            size_in_file,
+        }))
+    }
+
+    pub(crate) fn kind(&self) -> u8 {
+        self.kind
+    }
+
+    pub(crate) fn size_in_file(&self) -> usize {
+        self.size_in_file
+    }
+
+    fn restart(&self, idx: usize) -> usize {
+        be24(&self.data[self.records_end + idx * 3..]) as usize
+    }
+}
+
+/// A decoded record header, along with the location of the value that follows the key.
+struct RecordHeader {
+    value_type: u8,
+    value: Range<usize>,
+}
+
+/// An iterator over the records of a single block, which keeps the key of the current record.
+pub(crate) struct BlockIter<'a> {
+    block: Block<'a>,
+    pos: usize,
+    key: Vec<u8>,
+    scratch: Vec<u8>,
+    hash_len: usize,
+}
+
+impl<'a> BlockIter<'a> {
+    pub(crate) fn new(block: Block<'a>, hash_len: usize) -> Self {
+        BlockIter {
+            pos: block.records_start,
+            block,
+            key: Vec::new(),
+            scratch: Vec::new(),
+            hash_len,
+        }
+    }
+
+    /// The key of the record last returned by [`next_record()`](Self::next_record()).
+    pub(crate) fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// The data of the block, into which the value ranges returned by [`next_record()`](Self::next_record()) point.
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.block.data
+    }
+
+    /// Decode the next record and return its value type along with the location of its value, or `None` if the block is depleted.
+    pub(crate) fn next_record(&mut self) -> Result<Option<(u8, Range<usize>)>, Error> {
+        if self.pos >= self.block.records_end {
+            return Ok(None);
+        }
+        let mut key = std::mem::take(&mut self.scratch);
+        let header = self.decode_at(self.pos, &mut key)?;
+        self.scratch = std::mem::replace(&mut self.key, key);
+        self.pos = header.value.end;
+        Ok(Some((header.value_type, header.value)))
+    }
+
+    /// Position the iterator so that the next call to [`next_record()`](Self::next_record()) returns the first record whose key
+    /// is equal to or greater than `want`.
+    pub(crate) fn seek(&mut self, want: &[u8]) -> Result<(), Error> {
+        // Find the last restart point with a key smaller than `want`, keys at restart points are never prefix-compressed.
+        let (mut lo, mut hi) = (0, self.block.restart_count);
+        let mut key = std::mem::take(&mut self.scratch);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            key.clear();
+            let offset = self.block.restart(mid);
+            if offset < self.block.records_start || offset >= self.block.records_end {
+                return Err(corrupt("restart offset out of bounds"));
+            }
+            self.decode_at_with_key(offset, &[], &mut key)?;
+            if key.as_slice() < want {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        self.pos = match lo {
+            0 => self.block.records_start,
+            restart => self.block.restart(restart - 1),
+        };
+        self.key.clear();
+        while self.pos < self.block.records_end {
+            key.clear();
+            let header = self.decode_at(self.pos, &mut key)?;
+            if key.as_slice() >= want {
+                break;
+            }
+            std::mem::swap(&mut self.key, &mut key);
+            self.pos = header.value.end;
+        }
+        self.scratch = key;
+        Ok(())
+    }
+
+    fn decode_at(&self, pos: usize, out_key: &mut Vec<u8>) -> Result<RecordHeader, Error> {
+        self.decode_at_with_key(pos, &self.key, out_key)
+    }
+
+    fn decode_at_with_key(
+        &self,
+        mut pos: usize,
+        prev_key: &[u8],
+        out_key: &mut Vec<u8>,
+    ) -> Result<RecordHeader, Error> {
+        let data = &self.block.data[..self.block.records_end];
+        let (prefix_len, consumed) = varint(&data[pos..])?;
+        pos += consumed;
+        let (suffix_len_and_type, consumed) = varint(data.get(pos..).unwrap_or_default())?;
+        pos += consumed;
+        let prefix_len = prefix_len as usize;
+        let suffix_len = (suffix_len_and_type >> 3) as usize;
+        let value_type = (suffix_len_and_type & 0x7) as u8;
+        let suffix = data
+            .get(pos..pos + suffix_len)
+            .ok_or_else(|| corrupt("key suffix out of bounds"))?;
+        let prefix = prev_key
+            .get(..prefix_len)
+            .ok_or_else(|| corrupt("key prefix longer than previous key"))?;
+        out_key.clear();
+        out_key.extend_from_slice(prefix);
+        out_key.extend_from_slice(suffix);
+        pos += suffix_len;
+        let value_len = value_len(self.block.kind, value_type, &data[pos..], self.hash_len)?;
+        if pos + value_len > data.len() {
+            return Err(corrupt("record value out of bounds"));
+        }
+        Ok(RecordHeader {
+            value_type,
+            value: pos..pos + value_len,
+        })
+    }
+}
+
+/// Determine the length of the value of a record of `value_type` in a block of `block_type`, with `data` starting at the value.
+fn value_len(block_type: u8, value_type: u8, data: &[u8], hash_len: usize) -> Result<usize, Error> {
+    fn skip_varint(data: &[u8], pos: &mut usize) -> Result<u64, Error> {
+        let (value, consumed) = varint(data.get(*pos..).unwrap_or_default())?;
+        *pos += consumed;
+        Ok(value)
+    }
+    fn skip_string(data: &[u8], pos: &mut usize) -> Result<(), Error> {
+        let len = skip_varint(data, pos)?;
+        *pos += len as usize;
+        Ok(())
+    }
+    let mut pos = 0;
+    match block_type {
+        BLOCK_TYPE_REF => {
+            skip_varint(data, &mut pos)?;
+            match value_type {
+                0 => {}
+                1 => pos += hash_len,
+                2 => pos += 2 * hash_len,
+                3 => skip_string(data, &mut pos)?,
+                _ => return Err(corrupt("unknown ref value type")),
+            }
+        }
+        BLOCK_TYPE_LOG => match value_type {
+            0 => {}
+            1 => {
+                pos += 2 * hash_len;
+                skip_string(data, &mut pos)?;
+                skip_string(data, &mut pos)?;
+                skip_varint(data, &mut pos)?;
+                pos += 2;
+                skip_string(data, &mut pos)?;
+            }
+            _ => return Err(corrupt("unknown log value type")),
+        },
+        BLOCK_TYPE_OBJ => {
+            let count = match value_type {
+                0 => skip_varint(data, &mut pos)?,
+                count => u64::from(count),
+            };
+            for _ in 0..count {
+                skip_varint(data, &mut pos)?;
+            }
+        }
+        BLOCK_TYPE_INDEX => {
+            skip_varint(data, &mut pos)?;
+        }
+        _ => unreachable!("block types are validated when reading the block"),
+    }
+    Ok(pos)
+}
+
+/// Decode the positions of ref blocks stored in the value of an `obj` record of `value_type`.
+pub(crate) fn obj_positions(value_type: u8, mut data: &[u8]) -> Result<Vec<u64>, Error> {
+    let count = match value_type {
+        0 => {
+            let (count, consumed) = varint(data)?;
+            data = &data[consumed..];
+            count
+        }
+        count => u64::from(count),
+    };
+    let mut positions = Vec::with_capacity(count as usize);
+    let mut last = 0;
+    for idx in 0..count {
+        let (value, consumed) = varint(data)?;
+        data = &data[consumed..];
+        last = if idx == 0 { value } else { last + value };
+        positions.push(last);
+    }
+    Ok(positions)
+}
@@ -0,0 +1,166 @@
+//! An implementation of the [reftable format](https://git-scm.com/docs/reftable), which stores references and their logs
+//! in a stack of immutable, sorted and block-aligned binary tables.
+//!
+//! Git enables it with `extensions.refStorage = reftable`, in which case all references but a few pseudo-refs like `FETCH_HEAD`
+//! are stored in the `reftable/` directory. The `tables.list` file in that directory lists the tables that make up the stack,
+//! oldest first, and newer tables shadow the records of older ones.
+//!
+//! * **[`Table`]**
+//!   * a single table which can be searched for references, their logs and the references pointing to an object.
+//! * **[`Stack`]**
+//!   * all tables as listed in `tables.list`, which presents their records merged into one view.
+//! * **[`Store`]**
+//!   * the stack as it is on disk, along with the means to add new tables to it using a [`Transaction`].
+//!
+//! Typically, the reftable is used through a [`file::Store`][crate::file::Store] which was configured to use it
+//! with [`file::Store::use_reftable()`][crate::file::Store::use_reftable()].
+use std::path::PathBuf;
+
+use gix_features::threading::OwnShared;
+use gix_hash::ObjectId;
+use memmap2::Mmap;
+
+use crate::FullName;
+
+mod decode;
+
+/// A record of a reference in a reftable.
+#[derive(Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Clone)]
+pub struct RefRecord {
+    /// The full name of the reference.
+    pub name: FullName,
+    /// The update index at which the reference was written, with higher indices being more recent.
+    pub update_index: u64,
+    /// The value of the reference, or the indication that it was deleted.
+    pub value: RefValue,
+}
+
+/// The value of a [`RefRecord`].
+#[derive(Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Clone)]
+pub enum RefValue {
+    /// The reference was deleted, hiding all records of the same name in older tables.
+    Deletion,
+    /// The reference points to an object.
+    Object(ObjectId),
+    /// The reference points to an object, typically an annotated tag, which peels to `peeled`.
+    ObjectAndPeeled {
+        /// The object the reference points to.
+        target: ObjectId,
+        /// The object that `target` ultimately peels to.
+        peeled: ObjectId,
+    },
+    /// The reference points to another reference.
+    Symbolic(FullName),
+}
+
+/// A record of a reference log entry in a reftable.
+#[derive(Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Clone)]
+pub struct LogRecord {
+    /// The full name of the reference the log entry belongs to.
+    pub name: FullName,
+    /// The update index at which the log entry was written, which orders all entries of the same reference.
+    pub update_index: u64,
+    /// The log entry itself, or the indication that it was deleted.
+    pub value: LogValue,
+}
+
+/// The value of a [`LogRecord`].
+#[derive(Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Clone)]
+pub enum LogValue {
+    /// The log entry was deleted, hiding the entry with the same update index in older tables.
+    Deletion,
+    /// The log entry, whose message is never terminated by a newline.
+    Update(crate::log::Line),
+}
+
+#[derive(Debug)]
+enum Backing {
+    InMemory(Vec<u8>),
+    Mapped(Mmap),
+}
+
+/// The location of a section of blocks within a [`Table`].
+#[derive(Debug, Default, Clone, Copy)]
+struct Section {
+    /// The offset of the first block of the section, if the section is present.
+    position: Option<u64>,
+    /// The offset of the top-level index of the section, if the section is indexed.
+    index_position: Option<u64>,
+}
+
+/// A single reftable, which is immutable once written.
+#[derive(Debug)]
+pub struct Table {
+    data: Backing,
+    /// The path from which the table was read, if it was read from disk.
+    path: Option<PathBuf>,
+    version: u8,
+    block_size: u32,
+    min_update_index: u64,
+    max_update_index: u64,
+    object_hash: gix_hash::Kind,
+    /// The offset at which the footer starts, which is also the end of all blocks.
+    footer_offset: usize,
+    refs: Section,
+    objs: Section,
+    logs: Section,
+    /// The amount of bytes of object id prefixes in the `obj` section.
+    obj_id_len: usize,
+}
+
+/// All tables of a reftable stack, oldest first, as listed in `tables.list` at the time it was read.
+#[derive(Debug, Default)]
+pub struct Stack {
+    tables: Vec<Table>,
+    /// The names of the tables as listed in `tables.list`.
+    names: Vec<String>,
+}
+
+/// The reftable stack as it is stored in a directory on disk.
+///
+/// It caches the most recent version of the stack and reloads it only if `tables.list` changed.
+#[derive(Debug, Clone)]
+pub struct Store {
+    /// The directory containing `tables.list` and all tables.
+    dir: PathBuf,
+    /// The kind of hash used in tables we write.
+    object_hash: gix_hash::Kind,
+    /// The options to use when writing new tables.
+    pub write_options: write::Options,
+    /// If `true`, the default, tables are merged after each transaction so that each table is at least twice as large
+    /// as the one that follows it, which keeps the stack short.
+    pub auto_compaction: bool,
+    /// The stack as it was last read.
+    stack: OwnShared<gix_fs::SharedFileSnapshotMut<Stack>>,
+}
+
+/// A transaction to add a table to a [`Store`], holding the lock on `tables.list` and the stack as it was when the lock was obtained.
+///
+/// It is rolled back when dropped.
+pub struct Transaction {
+    store: Store,
+    lock: gix_lock::File,
+    stack: Stack,
+}
+
+/// An up-to-date snapshot of a reftable [`Stack`].
+pub type SharedStackSnapshot = gix_fs::SharedFileSnapshot<Stack>;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod table;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod write;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod stack;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod transaction;
+
+mod compact;
+mod store;
@@ -0,0 +1,326 @@
+use std::{cmp::Ordering, path::Path};
+
+use gix_hash::oid;
+use gix_object::bstr::BStr;
+
+pub use error::Error;
+
+use crate::{
+    store_impl::reftable::{table, LogRecord, LogValue, RefRecord, RefValue, Stack, Table},
+    FullNameRef,
+};
+
+/// The name of the file listing all tables of the stack.
+pub const TABLES_LIST: &str = "tables.list";
+
+/// How often to re-read `tables.list` if a table it lists vanished while opening it, which happens if the stack
+/// is compacted concurrently.
+const MAX_OPEN_ATTEMPTS: usize = 10;
+
+/// Initialization
+impl Stack {
+    /// Read `tables.list` in `dir` and open all tables it lists, or return `None` if `tables.list` doesn't exist.
+    pub fn at(dir: impl AsRef<Path>) -> Result<Option<Self>, Error> {
+        let dir = dir.as_ref();
+        let list_path = dir.join(TABLES_LIST);
+        let mut attempt = 0;
+        'retry: loop {
+            attempt += 1;
+            let list = match std::fs::read(&list_path) {
+                Ok(list) => list,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => {
+                    return Err(Error::ReadList {
+                        source: err,
+                        path: list_path,
+                    })
+                }
+            };
+            let names = parse_list(&list).map_err(|_| Error::InvalidList {
+                path: list_path.clone(),
+            })?;
+            let mut tables = Vec::with_capacity(names.len());
+            for name in &names {
+                match Table::open(dir.join(name)) {
+                    Ok(table) => tables.push(table),
+                    Err(table::Error::Io { source, .. })
+                        if source.kind() == std::io::ErrorKind::NotFound && attempt < MAX_OPEN_ATTEMPTS =>
+                    {
+                        continue 'retry;
+                    }
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            return Ok(Some(Stack { tables, names }));
+        }
+    }
+
+    /// Create a stack from `tables`, oldest first, which aren't associated with any `tables.list`.
+    pub fn from_tables(tables: Vec<Table>) -> Self {
+        let names = tables
+            .iter()
+            .map(|table| {
+                table
+                    .path()
+                    .and_then(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            })
+            .collect();
+        Stack { tables, names }
+    }
+}
+
+fn parse_list(list: &[u8]) -> Result<Vec<String>, std::str::Utf8Error> {
+    std::str::from_utf8(list).map(|list| {
+        list.lines()
+            .filter(|line| !line.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()
+    })
+}
+
+/// Access
+impl Stack {
+    /// Return all tables of the stack, oldest first.
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    /// Return the file names of all tables as listed in `tables.list`, oldest first.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Return the highest update index of all tables, or `0` if the stack is empty.
+    pub fn max_update_index(&self) -> u64 {
+        self.tables.last().map_or(0, Table::max_update_index)
+    }
+
+    /// Return `true` if the stack has no tables.
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+}
+
+/// Lookup
+impl Stack {
+    /// Return the most recent record of the reference named `name`, or `None` if there is none or if it was deleted.
+    pub fn find_ref(&self, name: &FullNameRef) -> Result<Option<RefRecord>, Error> {
+        for table in self.tables.iter().rev() {
+            if let Some(record) = table.find_ref(name)? {
+                return Ok((record.value != RefValue::Deletion).then_some(record));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Return an iterator over the most recent records of all references that weren't deleted, sorted by name.
+    pub fn refs(&self) -> RefIter<'_> {
+        merged_refs(&self.tables, false)
+    }
+
+    /// Return an iterator over the most recent records of all references whose name starts with `prefix`
+    /// and which weren't deleted, sorted by name.
+    pub fn refs_prefixed(&self, prefix: &BStr) -> Result<RefIter<'_>, Error> {
+        Ok(RefIter(Merge::new(
+            self.tables
+                .iter()
+                .map(|table| table.refs_prefixed(prefix))
+                .collect::<Result<_, _>>()?,
+            false,
+        )))
+    }
+
+    /// Return an iterator over the most recent records of all references, including deletions, sorted by name.
+    pub fn refs_with_deletions(&self) -> RefIter<'_> {
+        merged_refs(&self.tables, true)
+    }
+
+    /// Return an iterator over all log entries which weren't deleted, sorted by reference name and then by update index,
+    /// most recent first.
+    pub fn logs(&self) -> LogIter<'_> {
+        merged_logs(&self.tables, false)
+    }
+
+    /// Return an iterator over all log entries of the reference named `name` which weren't deleted, most recent first.
+    pub fn logs_of(&self, name: &FullNameRef) -> Result<LogIter<'_>, Error> {
+        Ok(LogIter(Merge::new(
+            self.tables
+                .iter()
+                .map(|table| table.logs_of(name))
+                .collect::<Result<_, _>>()?,
+            false,
+        )))
+    }
+
+    /// Return an iterator over all log entries, including deletions, sorted by reference name and then by update index,
+    /// most recent first.
+    pub fn logs_with_deletions(&self) -> LogIter<'_> {
+        merged_logs(&self.tables, true)
+    }
+
+    /// Return the most recent records of all references which point to `id` or which peel to it.
+    pub fn refs_pointing_to(&self, id: &oid) -> Result<Vec<RefRecord>, Error> {
+        let mut out = Vec::new();
+        for table in &self.tables {
+            for record in table.refs_pointing_to(id)? {
+                // Newer tables may have changed or deleted the reference.
+                if self.find_ref(record.name.as_ref())?.as_ref() == Some(&record) && !out.contains(&record) {
+                    out.push(record);
+                }
+            }
+        }
+        out.sort();
+        Ok(out)
+    }
+}
+
+/// Merge the reference records of all `tables`, oldest first, and skip deletions unless `include_deletions` is set.
+pub(crate) fn merged_refs(tables: &[Table], include_deletions: bool) -> RefIter<'_> {
+    RefIter(Merge::new(tables.iter().map(Table::refs).collect(), include_deletions))
+}
+
+/// Merge the log records of all `tables`, oldest first, and skip deletions unless `include_deletions` is set.
+pub(crate) fn merged_logs(tables: &[Table], include_deletions: bool) -> LogIter<'_> {
+    LogIter(Merge::new(tables.iter().map(Table::logs).collect(), include_deletions))
+}
+
+/// An iterator over the merged reference records of all tables in a [`Stack`].
+pub struct RefIter<'a>(Merge<table::RefIter<'a>, RefRecord>);
+
+impl<'a> Iterator for RefIter<'a> {
+    type Item = Result<RefRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+/// An iterator over the merged log records of all tables in a [`Stack`].
+pub struct LogIter<'a>(Merge<table::LogIter<'a>, LogRecord>);
+
+impl<'a> Iterator for LogIter<'a> {
+    type Item = Result<LogRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+trait Record {
+    fn cmp_key(&self, other: &Self) -> Ordering;
+    fn is_deletion(&self) -> bool;
+}
+
+impl Record for RefRecord {
+    fn cmp_key(&self, other: &Self) -> Ordering {
+        self.name.cmp(&other.name)
+    }
+
+    fn is_deletion(&self) -> bool {
+        self.value == RefValue::Deletion
+    }
+}
+
+impl Record for LogRecord {
+    fn cmp_key(&self, other: &Self) -> Ordering {
+        self.name
+            .cmp(&other.name)
+            .then_with(|| other.update_index.cmp(&self.update_index))
+    }
+
+    fn is_deletion(&self) -> bool {
+        self.value == LogValue::Deletion
+    }
+}
+
+/// Merge the sorted records of multiple tables, oldest first, so that records of newer tables shadow those of older ones
+/// with the same key.
+struct Merge<I, R> {
+    iters: Vec<I>,
+    /// The next record of each iterator, or `None` if it is depleted or still has to be refilled.
+    heads: Vec<Option<R>>,
+    /// The indices of the iterators whose next record has to be obtained.
+    refill: Vec<usize>,
+    include_deletions: bool,
+}
+
+impl<I, R> Merge<I, R> {
+    fn new(iters: Vec<I>, include_deletions: bool) -> Self {
+        Merge {
+            heads: iters.iter().map(|_| None).collect(),
+            refill: (0..iters.len()).collect(),
+            iters,
+            include_deletions,
+        }
+    }
+}
+
+impl<I, R> Iterator for Merge<I, R>
+where
+    I: Iterator<Item = Result<R, table::Error>>,
+    R: Record,
+{
+    type Item = Result<R, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            for idx in std::mem::take(&mut self.refill) {
+                match self.iters[idx].next() {
+                    Some(Ok(record)) => self.heads[idx] = Some(record),
+                    Some(Err(err)) => {
+                        self.heads.iter_mut().for_each(|head| *head = None);
+                        return Some(Err(err.into()));
+                    }
+                    None => self.heads[idx] = None,
+                }
+            }
+
+            // Ties go to the newest table.
+            let mut best: Option<usize> = None;
+            for idx in (0..self.heads.len()).rev() {
+                if let Some(record) = &self.heads[idx] {
+                    let is_better = match best {
+                        None => true,
+                        Some(best) => record.cmp_key(self.heads[best].as_ref().expect("set")) == Ordering::Less,
+                    };
+                    if is_better {
+                        best = Some(idx);
+                    }
+                }
+            }
+            let best = best?;
+            let record = self.heads[best].take().expect("set");
+            self.refill.push(best);
+            for (idx, head) in self.heads.iter_mut().enumerate() {
+                if matches!(head, Some(r) if r.cmp_key(&record) == Ordering::Equal) {
+                    *head = None;
+                    self.refill.push(idx);
+                }
+            }
+            if record.is_deletion() && !self.include_deletions {
+                continue;
+            }
+            return Some(Ok(record));
+        }
+    }
+}
+
+mod error {
+    use std::path::PathBuf;
+
+    use crate::store_impl::reftable::table;
+
+    /// The error returned when reading a [`Stack`][crate::reftable::Stack] or its records.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read the list of tables at {path:?}")]
+        ReadList { source: std::io::Error, path: PathBuf },
+        #[error("The list of tables at {path:?} isn't valid UTF-8")]
+        InvalidList { path: PathBuf },
+        #[error(transparent)]
+        Table(#[from] table::Error),
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+use crate::store_impl::reftable::{stack, write, SharedStackSnapshot, Stack, Store};
+
+/// Initialization
+impl Store {
+    /// Create a new instance for the stack in `dir`, which typically is the `reftable` directory of a repository, and which
+    /// writes tables using `object_hash`.
+    ///
+    /// Neither `dir` nor `tables.list` have to exist, they are created with the first transaction.
+    pub fn at(dir: impl Into<PathBuf>, object_hash: gix_hash::Kind) -> Self {
+        Store {
+            dir: dir.into(),
+            object_hash,
+            write_options: write::Options::default(),
+            auto_compaction: true,
+            stack: Default::default(),
+        }
+    }
+}
+
+/// Access
+impl Store {
+    /// The directory holding `tables.list` and all tables.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The kind of hash used in the tables we write.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// The path to the `tables.list` file.
+    pub fn tables_list_path(&self) -> PathBuf {
+        self.dir.join(stack::TABLES_LIST)
+    }
+
+    /// Return an up-to-date snapshot of the stack, or `None` if `tables.list` doesn't exist.
+    ///
+    /// The stack is only re-read if `tables.list` changed since it was last read.
+    pub fn stack(&self) -> Result<Option<SharedStackSnapshot>, stack::Error> {
+        self.stack.recent_snapshot(
+            || self.tables_list_path().metadata().and_then(|m| m.modified()).ok(),
+            || Stack::at(&self.dir),
+        )
+    }
+
+    /// Re-read the stack even if `tables.list` seems unchanged, which can happen if it was changed multiple times
+    /// within the resolution of the file modification time.
+    pub fn force_refresh(&self) -> Result<(), stack::Error> {
+        self.stack.force_refresh(|| {
+            let modified = match self.tables_list_path().metadata().and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                Err(_) => return Ok(None),
+            };
+            Stack::at(&self.dir).map(|stack| Some(modified).zip(stack))
+        })
+    }
+}
@@ -0,0 +1,612 @@
+use std::{ops::Range, path::PathBuf};
+
+use gix_hash::{oid, ObjectId};
+use gix_object::bstr::{BStr, BString};
+
+pub use error::Error;
+
+use crate::{
+    log,
+    store_impl::reftable::{
+        decode::{self, Block, BlockIter},
+        Backing, LogRecord, LogValue, RefRecord, RefValue, Section, Table,
+    },
+    FullName, FullNameRef,
+};
+
+/// Tables larger than this are memory mapped instead of being read into memory.
+const MMAP_THRESHOLD: u64 = 32 * 1024;
+
+impl AsRef<[u8]> for Backing {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Backing::InMemory(data) => data,
+            Backing::Mapped(map) => map,
+        }
+    }
+}
+
+/// Initialization
+impl Table {
+    /// Open the table at `path`, which is memory mapped if it isn't small.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let read = || -> std::io::Result<Backing> {
+            let file = std::fs::File::open(&path)?;
+            Ok(if file.metadata()?.len() <= MMAP_THRESHOLD {
+                let mut buf = Vec::new();
+                std::io::Read::read_to_end(&mut &file, &mut buf)?;
+                Backing::InMemory(buf)
+            } else {
+                // SAFETY: tables are never changed once they are written, they are only ever deleted.
+                #[allow(unsafe_code)]
+                unsafe {
+                    Backing::Mapped(memmap2::MmapOptions::new().map_copy_read_only(&file)?)
+                }
+            })
+        };
+        let data = read().map_err(|err| Error::Io {
+            source: err,
+            path: path.clone(),
+        })?;
+        Self::from_backing(data, Some(path))
+    }
+
+    /// Decode a table from `data`, as it would be stored on disk.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, Error> {
+        Self::from_backing(Backing::InMemory(data), None)
+    }
+
+    fn from_backing(data: Backing, path: Option<PathBuf>) -> Result<Self, Error> {
+        let bytes = data.as_ref();
+        if bytes.len() < 24 || &bytes[..4] != decode::MAGIC {
+            return Err(Error::Magic);
+        }
+        let version = bytes[4];
+        let object_hash = match version {
+            1 => gix_hash::Kind::Sha1,
+            2 => {
+                let hash_id = u32::from_be_bytes(bytes.get(24..28).ok_or(Error::Magic)?.try_into().expect("4 bytes"));
+                match hash_id {
+                    decode::HASH_ID_SHA1 => gix_hash::Kind::Sha1,
+                    decode::HASH_ID_SHA256 => gix_hash::Kind::Sha256,
+                    _ => return Err(Error::UnknownHashId { hash_id }),
+                }
+            }
+            _ => return Err(Error::UnsupportedVersion { version }),
+        };
+        let header_len = decode::header_len(version);
+        let footer_len = decode::footer_len(version);
+        if bytes.len() < header_len + footer_len {
+            return Err(Error::Corrupt {
+                message: "table is too small to hold header and footer",
+            });
+        }
+        let footer_offset = bytes.len() - footer_len;
+        let footer = &bytes[footer_offset..];
+        if footer[..header_len] != bytes[..header_len] {
+            return Err(Error::Corrupt {
+                message: "footer does not repeat the header",
+            });
+        }
+        let (footer, crc) = footer.split_at(footer_len - 4);
+        if gix_features::hash::crc32(footer) != u32::from_be_bytes(crc.try_into().expect("4 bytes")) {
+            return Err(Error::Checksum);
+        }
+        let offsets = &footer[header_len..];
+        let position = |idx: usize| Some(decode::be64(&offsets[idx * 8..])).filter(|pos| *pos != 0);
+        let obj_position_and_id_len = decode::be64(&offsets[8..]);
+        let first_block_type = (footer_offset > header_len).then(|| bytes[header_len]);
+
+        Ok(Table {
+            version,
+            block_size: decode::be24(&bytes[5..]),
+            min_update_index: decode::be64(&bytes[8..]),
+            max_update_index: decode::be64(&bytes[16..]),
+            object_hash,
+            footer_offset,
+            refs: Section {
+                position: (first_block_type == Some(decode::BLOCK_TYPE_REF)).then_some(0),
+                index_position: position(0),
+            },
+            objs: Section {
+                position: Some(obj_position_and_id_len >> 5).filter(|pos| *pos != 0),
+                index_position: position(2),
+            },
+            logs: Section {
+                position: if first_block_type == Some(decode::BLOCK_TYPE_LOG) {
+                    Some(0)
+                } else {
+                    position(3)
+                },
+                index_position: position(4),
+            },
+            obj_id_len: (obj_position_and_id_len & 0x1f) as usize,
+            data,
+            path,
+        })
+    }
+}
+
+/// Access
+impl Table {
+    /// The path from which the table was read, or `None` if it was decoded from memory.
+    pub fn path(&self) -> Option<&std::path::Path> {
+        self.path.as_deref()
+    }
+
+    /// The version of the reftable format, `1` for tables using SHA1 and `2` for tables which also record their hash kind.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The size to which blocks are padded, or `0` if blocks aren't aligned.
+    pub fn block_size(&self) -> u32 {
+        self.block_size
+    }
+
+    /// The smallest update index of all records in the table.
+    pub fn min_update_index(&self) -> u64 {
+        self.min_update_index
+    }
+
+    /// The largest update index of all records in the table.
+    pub fn max_update_index(&self) -> u64 {
+        self.max_update_index
+    }
+
+    /// The kind of hash used for all object ids in the table.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// The amount of bytes the table occupies on disk.
+    pub fn size_in_bytes(&self) -> u64 {
+        self.data.as_ref().len() as u64
+    }
+}
+
+/// Lookup
+impl Table {
+    /// Return the record of the reference named `name`, which may also indicate that the reference was deleted,
+    /// or `None` if there is no such record in this table.
+    pub fn find_ref(&self, name: &FullNameRef) -> Result<Option<RefRecord>, Error> {
+        let mut iter = self.refs_prefixed(name.as_bstr())?;
+        match iter.next().transpose()? {
+            Some(record) if record.name.as_ref() == name => Ok(Some(record)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Return an iterator over all reference records in the table, including deletions, sorted by name.
+    pub fn refs(&self) -> RefIter<'_> {
+        RefIter {
+            inner: SectionIter::at_start(self, self.refs, decode::BLOCK_TYPE_REF),
+            prefix: None,
+        }
+    }
+
+    /// Return an iterator over all reference records whose name starts with `prefix`, including deletions, sorted by name.
+    pub fn refs_prefixed(&self, prefix: &BStr) -> Result<RefIter<'_>, Error> {
+        Ok(RefIter {
+            inner: SectionIter::seek(self, self.refs, decode::BLOCK_TYPE_REF, prefix)?,
+            prefix: Some(prefix.to_owned()),
+        })
+    }
+
+    /// Return an iterator over all log records in the table, including deletions, sorted by name and then by update index,
+    /// most recent first.
+    pub fn logs(&self) -> LogIter<'_> {
+        LogIter {
+            inner: SectionIter::at_start(self, self.logs, decode::BLOCK_TYPE_LOG),
+            prefix: None,
+        }
+    }
+
+    /// Return an iterator over all log records of the reference named `name`, including deletions, most recent first.
+    pub fn logs_of(&self, name: &FullNameRef) -> Result<LogIter<'_>, Error> {
+        let mut prefix: BString = name.as_bstr().into();
+        prefix.push(0);
+        Ok(LogIter {
+            inner: SectionIter::seek(self, self.logs, decode::BLOCK_TYPE_LOG, prefix.as_ref())?,
+            prefix: Some(prefix),
+        })
+    }
+
+    /// Return all references which point to `id` or which peel to it, using the object index of the table if it has one.
+    ///
+    /// Deleted references are never returned.
+    pub fn refs_pointing_to(&self, id: &oid) -> Result<Vec<RefRecord>, Error> {
+        let points_to_id = |record: &RefRecord| match &record.value {
+            RefValue::Object(target) => target.as_ref() == id,
+            RefValue::ObjectAndPeeled { target, peeled } => target.as_ref() == id || peeled.as_ref() == id,
+            RefValue::Deletion | RefValue::Symbolic(_) => false,
+        };
+        let mut out = Vec::new();
+        let prefix = id.as_bytes().get(..self.obj_id_len).filter(|p| !p.is_empty());
+        match prefix.filter(|_| self.objs.position.is_some()) {
+            Some(prefix) => {
+                let mut objs = SectionIter::seek(self, self.objs, decode::BLOCK_TYPE_OBJ, prefix)?;
+                let positions = match objs.next_record()? {
+                    Some((value_type, value)) if objs.key() == prefix => {
+                        decode::obj_positions(value_type, &objs.data()[value])?
+                    }
+                    _ => return Ok(out),
+                };
+                if positions.is_empty() {
+                    // The object is referenced from too many blocks to list them, so all of them have to be checked.
+                    return self
+                        .refs()
+                        .filter(|r| match r {
+                            Ok(r) => points_to_id(r),
+                            Err(_) => true,
+                        })
+                        .collect();
+                }
+                for position in positions {
+                    let block = self
+                        .block_at(position as usize)?
+                        .filter(|block| block.kind() == decode::BLOCK_TYPE_REF)
+                        .ok_or(Error::Corrupt {
+                            message: "object index points to a block that isn't a ref block",
+                        })?;
+                    let mut records = BlockIter::new(block, self.object_hash.len_in_bytes());
+                    while let Some((value_type, value)) = records.next_record()? {
+                        let record = self.decode_ref(records.key(), value_type, &records.data()[value])?;
+                        if points_to_id(&record) {
+                            out.push(record);
+                        }
+                    }
+                }
+                Ok(out)
+            }
+            None => self
+                .refs()
+                .filter(|r| match r {
+                    Ok(r) => points_to_id(r),
+                    Err(_) => true,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Take a length-prefixed string from the beginning of `value`, which was validated to contain it.
+fn take_string(value: &mut &[u8]) -> Result<BString, Error> {
+    let (len, consumed) = decode::varint(value)?;
+    let (string, rest) = value[consumed..].split_at(len as usize);
+    *value = rest;
+    Ok(string.into())
+}
+
+/// Decoding
+impl Table {
+    pub(crate) fn block_at(&self, offset: usize) -> Result<Option<Block<'_>>, Error> {
+        Block::at(
+            &self.data.as_ref()[..self.footer_offset],
+            offset,
+            decode::header_len(self.version),
+            self.block_size as usize,
+        )
+    }
+
+    fn hash_len(&self) -> usize {
+        self.object_hash.len_in_bytes()
+    }
+
+    fn decode_ref(&self, key: &[u8], value_type: u8, value: &[u8]) -> Result<RefRecord, Error> {
+        let (update_index_delta, consumed) = decode::varint(value)?;
+        let value = &value[consumed..];
+        let hash_len = self.hash_len();
+        Ok(RefRecord {
+            name: FullName::try_from(BString::from(key))?,
+            update_index: self.min_update_index + update_index_delta,
+            value: match value_type {
+                0 => RefValue::Deletion,
+                1 => RefValue::Object(ObjectId::from_bytes_or_panic(value)),
+                2 => RefValue::ObjectAndPeeled {
+                    target: ObjectId::from_bytes_or_panic(&value[..hash_len]),
+                    peeled: ObjectId::from_bytes_or_panic(&value[hash_len..]),
+                },
+                3 => {
+                    let (len, consumed) = decode::varint(value)?;
+                    RefValue::Symbolic(FullName::try_from(BString::from(&value[consumed..][..len as usize]))?)
+                }
+                _ => unreachable!("value types are validated when decoding the record"),
+            },
+        })
+    }
+
+    fn decode_log(&self, key: &[u8], value_type: u8, mut value: &[u8]) -> Result<LogRecord, Error> {
+        if key.len() < 9 || key[key.len() - 9] != 0 {
+            return Err(Error::Corrupt {
+                message: "log record key is malformed",
+            });
+        }
+        let (name, reverse_update_index) = key.split_at(key.len() - 9);
+        let update_index = u64::MAX - decode::be64(&reverse_update_index[1..]);
+        let value = match value_type {
+            0 => LogValue::Deletion,
+            1 => {
+                let hash_len = self.hash_len();
+                let previous_oid = ObjectId::from_bytes_or_panic(&value[..hash_len]);
+                let new_oid = ObjectId::from_bytes_or_panic(&value[hash_len..][..hash_len]);
+                value = &value[2 * hash_len..];
+                let name = take_string(&mut value)?;
+                let email = take_string(&mut value)?;
+                let (seconds, consumed) = decode::varint(value)?;
+                let tz_minutes = i16::from_be_bytes([value[consumed], value[consumed + 1]]);
+                value = &value[consumed + 2..];
+                let mut message = take_string(&mut value)?;
+                if message.last() == Some(&b'\n') {
+                    message.pop();
+                }
+                LogValue::Update(log::Line {
+                    previous_oid,
+                    new_oid,
+                    signature: gix_actor::Signature {
+                        name,
+                        email,
+                        time: gix_date::Time::new(
+                            seconds as gix_date::SecondsSinceUnixEpoch,
+                            i32::from(tz_minutes) * 60,
+                        ),
+                    },
+                    message,
+                })
+            }
+            _ => unreachable!("value types are validated when decoding the record"),
+        };
+        Ok(LogRecord {
+            name: FullName::try_from(BString::from(name))?,
+            update_index,
+            value,
+        })
+    }
+}
+
+/// An iterator over the records of one type stored in a section of a table, block by block.
+pub(crate) struct SectionIter<'a> {
+    table: &'a Table,
+    kind: u8,
+    current: Option<BlockIter<'a>>,
+    /// The offset of the block to read once `current` is depleted, or `None` if the section is depleted.
+    next_offset: Option<usize>,
+}
+
+impl<'a> SectionIter<'a> {
+    fn at_start(table: &'a Table, section: Section, kind: u8) -> Self {
+        SectionIter {
+            table,
+            kind,
+            current: None,
+            next_offset: section.position.map(|pos| pos as usize),
+        }
+    }
+
+    /// Create an iterator which starts at the first record whose key is equal to or greater than `want`.
+    fn seek(table: &'a Table, section: Section, kind: u8, want: &[u8]) -> Result<Self, Error> {
+        let empty = SectionIter {
+            table,
+            kind,
+            current: None,
+            next_offset: None,
+        };
+        let Some(start) = section.position else {
+            return Ok(empty);
+        };
+        let offset = match section.index_position {
+            Some(index) => match table.find_block_with_index(index as usize, kind, want)? {
+                Some(offset) => offset,
+                None => return Ok(empty),
+            },
+            None => table.find_block_linearly(start as usize, kind, want)?,
+        };
+        let block = table
+            .block_at(offset)?
+            .filter(|block| block.kind() == kind)
+            .ok_or(Error::Corrupt {
+                message: "expected block of a different type",
+            })?;
+        let next_offset = offset + block.size_in_file();
+        let mut current = BlockIter::new(block, table.hash_len());
+        current.seek(want)?;
+        Ok(SectionIter {
+            table,
+            kind,
+            current: Some(current),
+            next_offset: Some(next_offset),
+        })
+    }
+
+    /// Return the value type and the location of the value of the next record, or `None` if the section is depleted.
+    pub(crate) fn next_record(&mut self) -> Result<Option<(u8, Range<usize>)>, Error> {
+        loop {
+            if let Some(current) = self.current.as_mut() {
+                if let Some(record) = current.next_record()? {
+                    return Ok(Some(record));
+                }
+            }
+            let Some(offset) = self.next_offset else {
+                return Ok(None);
+            };
+            match self.table.block_at(offset)? {
+                Some(block) if block.kind() == self.kind => {
+                    self.next_offset = Some(offset + block.size_in_file());
+                    self.current = Some(BlockIter::new(block, self.table.hash_len()));
+                }
+                _ => {
+                    self.next_offset = None;
+                    self.current = None;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn key(&self) -> &[u8] {
+        self.current.as_ref().map_or(&[], BlockIter::key)
+    }
+
+    pub(crate) fn data(&self) -> &[u8] {
+        self.current.as_ref().map_or(&[], BlockIter::data)
+    }
+}
+
+impl Table {
+    /// Find the block of `kind` which contains the first key equal to or greater than `want` by walking the index starting
+    /// at `index`, or return `None` if all keys are smaller.
+    fn find_block_with_index(&self, index: usize, kind: u8, want: &[u8]) -> Result<Option<usize>, Error> {
+        let corrupt = |message| Error::Corrupt { message };
+        // The top-level index may span multiple blocks, all of which have to be searched in order.
+        let mut offset = index;
+        let mut position = loop {
+            let block = match self.block_at(offset)? {
+                Some(block) if block.kind() == decode::BLOCK_TYPE_INDEX => block,
+                _ => return Ok(None),
+            };
+            let next_offset = offset + block.size_in_file();
+            let mut records = BlockIter::new(block, self.hash_len());
+            records.seek(want)?;
+            if let Some((_, value)) = records.next_record()? {
+                break decode::varint(&records.data()[value])?.0 as usize;
+            }
+            offset = next_offset;
+        };
+        loop {
+            // Indexed blocks are always written before their index.
+            if position >= offset {
+                return Err(corrupt("index points past itself"));
+            }
+            let block = self
+                .block_at(position)?
+                .ok_or_else(|| corrupt("index points to a block past the end of the table"))?;
+            if block.kind() == kind {
+                return Ok(Some(position));
+            }
+            if block.kind() != decode::BLOCK_TYPE_INDEX {
+                return Err(corrupt("index points to a block of the wrong type"));
+            }
+            let mut records = BlockIter::new(block, self.hash_len());
+            records.seek(want)?;
+            offset = position;
+            position = match records.next_record()? {
+                Some((_, value)) => decode::varint(&records.data()[value])?.0 as usize,
+                None => return Err(corrupt("index block does not cover the key of its parent")),
+            };
+        }
+    }
+
+    /// Find the last block of `kind`, starting at `start`, whose first key is equal to or smaller than `want`.
+    fn find_block_linearly(&self, start: usize, kind: u8, want: &[u8]) -> Result<usize, Error> {
+        let mut offset = start;
+        loop {
+            let block = self
+                .block_at(offset)?
+                .filter(|block| block.kind() == kind)
+                .ok_or(Error::Corrupt {
+                    message: "section does not start with a block of its type",
+                })?;
+            let next_offset = offset + block.size_in_file();
+            match self.block_at(next_offset)? {
+                Some(next) if next.kind() == kind => {
+                    let mut records = BlockIter::new(next, self.hash_len());
+                    if records.next_record()?.is_some() && records.key() <= want {
+                        offset = next_offset;
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            return Ok(offset);
+        }
+    }
+}
+
+/// An iterator over reference records of a [`Table`].
+pub struct RefIter<'a> {
+    inner: SectionIter<'a>,
+    prefix: Option<BString>,
+}
+
+impl<'a> Iterator for RefIter<'a> {
+    type Item = Result<RefRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value_type, value) = match self.inner.next_record() {
+            Ok(Some(record)) => record,
+            Ok(None) => return None,
+            Err(err) => {
+                self.inner.next_offset = None;
+                self.inner.current = None;
+                return Some(Err(err));
+            }
+        };
+        let key = self.inner.key();
+        if let Some(prefix) = &self.prefix {
+            if !key.starts_with(prefix) {
+                self.inner.next_offset = None;
+                self.inner.current = None;
+                return None;
+            }
+        }
+        Some(self.inner.table.decode_ref(key, value_type, &self.inner.data()[value]))
+    }
+}
+
+/// An iterator over log records of a [`Table`].
+pub struct LogIter<'a> {
+    inner: SectionIter<'a>,
+    prefix: Option<BString>,
+}
+
+impl<'a> Iterator for LogIter<'a> {
+    type Item = Result<LogRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value_type, value) = match self.inner.next_record() {
+            Ok(Some(record)) => record,
+            Ok(None) => return None,
+            Err(err) => {
+                self.inner.next_offset = None;
+                self.inner.current = None;
+                return Some(Err(err));
+            }
+        };
+        let key = self.inner.key();
+        if let Some(prefix) = &self.prefix {
+            if !key.starts_with(prefix) {
+                self.inner.next_offset = None;
+                self.inner.current = None;
+                return None;
+            }
+        }
+        Some(self.inner.table.decode_log(key, value_type, &self.inner.data()[value]))
+    }
+}
+
+mod error {
+    use std::path::PathBuf;
+
+    /// The error returned when reading a [`Table`][crate::reftable::Table] or its records.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read the reftable at {path:?}")]
+        Io { source: std::io::Error, path: PathBuf },
+        #[error("The data does not start with the reftable signature")]
+        Magic,
+        #[error("Reftable version {version} is not supported")]
+        UnsupportedVersion { version: u8 },
+        #[error("The hash id {hash_id:#010x} is unknown")]
+        UnknownHashId { hash_id: u32 },
+        #[error("The checksum of the reftable footer did not match")]
+        Checksum,
+        #[error("The reftable is corrupt: {message}")]
+        Corrupt { message: &'static str },
+        #[error("A log block could not be decompressed")]
+        Inflate(#[from] gix_features::zlib::inflate::Error),
+        #[error("A reference name in the reftable is invalid")]
+        RefName(#[from] crate::name::Error),
+    }
+}
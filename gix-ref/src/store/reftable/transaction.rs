@@ -0,0 +1,161 @@
+use std::{
+    hash::{BuildHasher, Hasher},
+    io::Write,
+    path::Path,
+};
+
+pub use error::Error;
+
+use crate::store_impl::reftable::{compact, write::Writer, LogRecord, RefRecord, Stack, Store, Table, Transaction};
+
+/// Transactions
+impl Store {
+    /// Lock `tables.list` and read the stack as it is now, to be able to add a table to it with [`Transaction::commit()`].
+    ///
+    /// The directory of the stack is created if it doesn't exist yet.
+    pub fn transaction(&self, lock_mode: gix_lock::acquire::Fail) -> Result<Transaction, Error> {
+        let lock = gix_lock::File::acquire_to_update_resource(
+            self.tables_list_path(),
+            lock_mode,
+            Some(self.dir.parent().unwrap_or(&self.dir).to_owned()),
+        )?;
+        let stack = Stack::at(&self.dir)?.unwrap_or_default();
+        Ok(Transaction {
+            store: self.clone(),
+            lock,
+            stack,
+        })
+    }
+}
+
+impl Transaction {
+    /// The store the table will be added to.
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// The stack as it was when the lock on `tables.list` was obtained, which can't change until the transaction is done.
+    pub fn stack(&self) -> &Stack {
+        &self.stack
+    }
+
+    /// The update index to use for all records of the table to be added.
+    pub fn next_update_index(&self) -> u64 {
+        self.stack.max_update_index() + 1
+    }
+
+    /// Write `refs` and `logs`, in any order, into a new table on top of the stack, compact the stack if needed, and release the lock.
+    ///
+    /// The update indices of all reference records must be at least [`next_update_index()`](Self::next_update_index()),
+    /// while log records may also delete entries of older tables by their update index.
+    /// If there are no records, the stack remains unchanged.
+    pub fn commit(mut self, mut refs: Vec<RefRecord>, mut logs: Vec<LogRecord>) -> Result<(), Error> {
+        if refs.is_empty() && logs.is_empty() {
+            return Ok(());
+        }
+        refs.sort_by(|a, b| a.name.cmp(&b.name));
+        logs.sort_by(|a, b| a.name.cmp(&b.name).then(b.update_index.cmp(&a.update_index)));
+
+        let min_update_index = self.next_update_index();
+        let max_update_index = refs
+            .iter()
+            .map(|r| r.update_index)
+            .chain(logs.iter().map(|l| l.update_index))
+            .max()
+            .unwrap_or(min_update_index)
+            .max(min_update_index);
+        let mut writer = Writer::new(
+            Vec::new(),
+            self.store.write_options,
+            self.store.object_hash,
+            min_update_index,
+            max_update_index,
+        );
+        for record in &refs {
+            writer.add_ref(record)?;
+        }
+        for record in &logs {
+            writer.add_log(record)?;
+        }
+        let data = writer.finish()?;
+        let name = write_table(&self.store.dir, min_update_index, max_update_index, &data)?;
+
+        let mut names = std::mem::take(&mut self.stack.names);
+        let mut tables = std::mem::take(&mut self.stack.tables);
+        names.push(name);
+        tables.push(Table::from_bytes(data)?);
+
+        let mut obsolete = Vec::new();
+        if self.store.auto_compaction {
+            let sizes: Vec<_> = tables.iter().map(compact::size_for_compaction).collect();
+            if let Some(segment) = compact::auto_compaction_segment(&sizes) {
+                let (min, max) = (
+                    tables[segment.start].min_update_index(),
+                    tables[segment.end - 1].max_update_index(),
+                );
+                let data = compact::merge(
+                    &tables[segment.clone()],
+                    segment.start != 0,
+                    self.store.write_options,
+                    self.store.object_hash,
+                )?;
+                let name = write_table(&self.store.dir, min, max, &data)?;
+                obsolete = names.splice(segment, Some(name)).collect();
+            }
+        }
+
+        let mut list = names.join("\n");
+        list.push('\n');
+        self.lock.write_all(list.as_bytes())?;
+        self.lock.commit()?;
+
+        for name in obsolete {
+            std::fs::remove_file(self.store.dir.join(name)).ok();
+        }
+        self.store.force_refresh()?;
+        Ok(())
+    }
+}
+
+/// Write `data` as table with the given update indices into `dir`, and return its file name.
+pub(crate) fn write_table(
+    dir: &Path,
+    min_update_index: u64,
+    max_update_index: u64,
+    data: &[u8],
+) -> Result<String, Error> {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u64(max_update_index);
+    let name = format!(
+        "0x{min_update_index:012x}-0x{max_update_index:012x}-{:08x}.ref",
+        hasher.finish() as u32
+    );
+    let mut lock =
+        gix_lock::File::acquire_to_update_resource(dir.join(&name), gix_lock::acquire::Fail::Immediately, None)?;
+    lock.write_all(data)?;
+    lock.commit()?;
+    Ok(name)
+}
+
+mod error {
+    use crate::store_impl::reftable::{stack, table, write};
+
+    /// The error returned by [`Store::transaction()`][crate::reftable::Store::transaction()] and
+    /// [`Transaction::commit()`][crate::reftable::Transaction::commit()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not lock the list of tables or write a new table")]
+        LockAcquire(#[from] gix_lock::acquire::Error),
+        #[error("Could not commit the list of tables or a new table")]
+        LockCommit(#[from] gix_lock::commit::Error<gix_lock::File>),
+        #[error(transparent)]
+        Stack(#[from] stack::Error),
+        #[error("A table that was just written could not be read")]
+        Table(#[from] table::Error),
+        #[error(transparent)]
+        Write(#[from] write::Error),
+        #[error("Could not write a new table or the list of tables")]
+        Io(#[from] std::io::Error),
+    }
+}
@@ -0,0 +1,536 @@
+use std::{collections::BTreeMap, io::Write};
+
+use gix_hash::ObjectId;
+
+pub use error::Error;
+
+use crate::store_impl::reftable::{decode, LogRecord, LogValue, RefRecord, RefValue};
+
+/// The options for writing reftables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The size of blocks in bytes, to which all but log blocks are padded.
+    ///
+    /// Defaults to 4096.
+    pub block_size: u32,
+    /// The amount of records after which a key is stored in full to allow binary searches within blocks.
+    ///
+    /// Defaults to 16.
+    pub restart_interval: u16,
+    /// If `true`, tables with many references also get an index to quickly find all references pointing to an object.
+    ///
+    /// Defaults to `true`.
+    pub write_object_index: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            block_size: 4096,
+            restart_interval: 16,
+            write_object_index: true,
+        }
+    }
+}
+
+/// Sections with more blocks than this get an index.
+const INDEX_THRESHOLD: usize = 3;
+/// Object ids are abbreviated to at least this many bytes in the object index.
+const MIN_OBJ_ID_LEN: usize = 2;
+
+/// A block that is being filled with records.
+struct BlockWriter {
+    kind: u8,
+    buf: Vec<u8>,
+    /// The offset of the block header within `buf`, which is non-zero only for the first block as it also holds the file header.
+    header_off: usize,
+    restarts: Vec<u32>,
+    entries: usize,
+    last_key: Vec<u8>,
+}
+
+impl BlockWriter {
+    fn new(kind: u8, file_header: Option<&[u8]>) -> Self {
+        let mut buf = Vec::new();
+        if let Some(header) = file_header {
+            buf.extend_from_slice(header);
+        }
+        let header_off = buf.len();
+        buf.extend_from_slice(&[kind, 0, 0, 0]);
+        BlockWriter {
+            kind,
+            buf,
+            header_off,
+            restarts: Vec::new(),
+            entries: 0,
+            last_key: Vec::new(),
+        }
+    }
+
+    /// Add the record with `key`, `value_type` and `value` if it fits into a block of `block_size`, and return `true` if it was added.
+    ///
+    /// Records are always added to empty log blocks, which then grow beyond the block size.
+    fn add(&mut self, key: &[u8], value_type: u8, value: &[u8], options: &Options) -> bool {
+        let is_restart = self.entries % options.restart_interval.max(1) as usize == 0;
+        let prefix_len = if is_restart {
+            0
+        } else {
+            self.last_key.iter().zip(key).take_while(|(a, b)| a == b).count()
+        };
+        let suffix = &key[prefix_len..];
+        let mut record = Vec::with_capacity(suffix.len() + value.len() + 8);
+        put_varint(&mut record, prefix_len as u64);
+        put_varint(&mut record, ((suffix.len() as u64) << 3) | u64::from(value_type));
+        record.extend_from_slice(suffix);
+        record.extend_from_slice(value);
+
+        let restart_count = self.restarts.len() + usize::from(is_restart);
+        let needed = self.buf.len() + record.len() + 3 * restart_count + 2;
+        if needed > options.block_size as usize && !(self.entries == 0 && self.kind == decode::BLOCK_TYPE_LOG) {
+            return false;
+        }
+        if is_restart {
+            self.restarts.push(self.buf.len() as u32);
+        }
+        self.buf.extend_from_slice(&record);
+        self.entries += 1;
+        self.last_key.clear();
+        self.last_key.extend_from_slice(key);
+        true
+    }
+
+    /// Finish the block and return its bytes as they should be written, without padding.
+    fn finish(mut self) -> Result<Vec<u8>, Error> {
+        for restart in &self.restarts {
+            self.buf.extend_from_slice(&restart.to_be_bytes()[1..]);
+        }
+        self.buf.extend_from_slice(&(self.restarts.len() as u16).to_be_bytes());
+        let block_len = self.buf.len();
+        if block_len > 0xff_ffff {
+            return Err(Error::BlockTooLarge);
+        }
+        self.buf[self.header_off + 1..][..3].copy_from_slice(&(block_len as u32).to_be_bytes()[1..]);
+        if self.kind != decode::BLOCK_TYPE_LOG {
+            return Ok(self.buf);
+        }
+        let records_start = self.header_off + decode::BLOCK_HEADER_LEN;
+        let mut out = self.buf[..records_start].to_vec();
+        let mut deflate = gix_features::zlib::stream::deflate::Write::new(&mut out);
+        deflate.write_all(&self.buf[records_start..])?;
+        deflate.flush()?;
+        Ok(out)
+    }
+}
+
+/// Write a reftable, with reference records added first and log records second, both in the order of their keys.
+///
+/// The update indices of all reference records must be within the bounds given when creating the writer.
+pub struct Writer<W> {
+    out: W,
+    options: Options,
+    object_hash: gix_hash::Kind,
+    header: Vec<u8>,
+    min_update_index: u64,
+    max_update_index: u64,
+    /// The amount of bytes written so far, excluding pending padding.
+    offset: u64,
+    /// The amount of zero bytes to write before the next block, to pad the previous one to the block size.
+    pending_padding: usize,
+    block: Option<BlockWriter>,
+    /// The offset at which `block` will be written.
+    block_offset: u64,
+    /// The last key and offset of each block of the current section.
+    index: Vec<(Vec<u8>, u64)>,
+    /// The offsets of all ref blocks that contain references to an object.
+    objects: BTreeMap<ObjectId, Vec<u64>>,
+    last_ref_key: Option<Vec<u8>>,
+    last_log_key: Option<Vec<u8>>,
+    footer: Footer,
+}
+
+#[derive(Default)]
+struct Footer {
+    ref_index_position: u64,
+    obj_position: u64,
+    obj_id_len: usize,
+    obj_index_position: u64,
+    log_position: u64,
+    log_index_position: u64,
+}
+
+/// Lifecycle
+impl<W: Write> Writer<W> {
+    /// Create a new instance to write a table with records using `object_hash` to `out`, whose update indices are within
+    /// `min_update_index` and `max_update_index`, inclusive.
+    pub fn new(
+        out: W,
+        options: Options,
+        object_hash: gix_hash::Kind,
+        min_update_index: u64,
+        max_update_index: u64,
+    ) -> Self {
+        let version: u8 = match object_hash {
+            gix_hash::Kind::Sha1 => 1,
+            _ => 2,
+        };
+        let mut header = Vec::with_capacity(decode::header_len(version));
+        header.extend_from_slice(decode::MAGIC);
+        header.push(version);
+        header.extend_from_slice(&options.block_size.to_be_bytes()[1..]);
+        header.extend_from_slice(&min_update_index.to_be_bytes());
+        header.extend_from_slice(&max_update_index.to_be_bytes());
+        if version == 2 {
+            let hash_id = match object_hash {
+                gix_hash::Kind::Sha1 => decode::HASH_ID_SHA1,
+                gix_hash::Kind::Sha256 => decode::HASH_ID_SHA256,
+            };
+            header.extend_from_slice(&hash_id.to_be_bytes());
+        }
+        Writer {
+            out,
+            options,
+            object_hash,
+            header,
+            min_update_index,
+            max_update_index,
+            offset: 0,
+            pending_padding: 0,
+            block: None,
+            block_offset: 0,
+            index: Vec::new(),
+            objects: BTreeMap::new(),
+            last_ref_key: None,
+            last_log_key: None,
+            footer: Footer::default(),
+        }
+    }
+
+    /// Write all remaining data, along with the footer, and return the output.
+    pub fn finish(mut self) -> Result<W, Error> {
+        if self.last_log_key.is_some() {
+            self.finish_log_section()?;
+        } else if self.last_ref_key.is_some() {
+            self.finish_ref_section()?;
+        }
+        if self.offset == 0 {
+            let header = std::mem::take(&mut self.header);
+            self.write(&header)?;
+            self.header = header;
+        }
+        let mut footer = self.header.clone();
+        footer.extend_from_slice(&self.footer.ref_index_position.to_be_bytes());
+        footer.extend_from_slice(&((self.footer.obj_position << 5) | self.footer.obj_id_len as u64).to_be_bytes());
+        footer.extend_from_slice(&self.footer.obj_index_position.to_be_bytes());
+        footer.extend_from_slice(&self.footer.log_position.to_be_bytes());
+        footer.extend_from_slice(&self.footer.log_index_position.to_be_bytes());
+        footer.extend_from_slice(&gix_features::hash::crc32(&footer).to_be_bytes());
+        // The last block is never padded.
+        self.pending_padding = 0;
+        self.write(&footer)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Adding records
+impl<W: Write> Writer<W> {
+    /// Add `record`, whose name must sort after the name of the previously added reference record.
+    pub fn add_ref(&mut self, record: &RefRecord) -> Result<(), Error> {
+        if self.last_log_key.is_some() {
+            return Err(Error::RefAfterLog);
+        }
+        let key: &[u8] = record.name.as_bstr().as_ref();
+        if matches!(self.last_ref_key.as_deref(), Some(last) if last >= key) {
+            return Err(Error::Unsorted { key: key.into() });
+        }
+        let update_index_delta = self.update_index_delta(record.update_index)?;
+
+        let mut value = Vec::new();
+        put_varint(&mut value, update_index_delta);
+        let value_type = match &record.value {
+            RefValue::Deletion => 0,
+            RefValue::Object(id) => {
+                self.check_hash(id)?;
+                value.extend_from_slice(id.as_bytes());
+                1
+            }
+            RefValue::ObjectAndPeeled { target, peeled } => {
+                self.check_hash(target)?;
+                self.check_hash(peeled)?;
+                value.extend_from_slice(target.as_bytes());
+                value.extend_from_slice(peeled.as_bytes());
+                2
+            }
+            RefValue::Symbolic(target) => {
+                put_varint(&mut value, target.as_bstr().len() as u64);
+                value.extend_from_slice(target.as_bstr());
+                3
+            }
+        };
+        self.add(decode::BLOCK_TYPE_REF, key, value_type, &value)?;
+        let block_offset = self.block_offset;
+        let mut track = |id: &ObjectId| {
+            let offsets = self.objects.entry(*id).or_default();
+            if offsets.last() != Some(&block_offset) {
+                offsets.push(block_offset);
+            }
+        };
+        match &record.value {
+            RefValue::Object(id) => track(id),
+            RefValue::ObjectAndPeeled { target, peeled } => {
+                track(target);
+                track(peeled);
+            }
+            RefValue::Deletion | RefValue::Symbolic(_) => {}
+        }
+        self.last_ref_key = Some(key.to_owned());
+        Ok(())
+    }
+
+    /// Add `record`, which must sort after the previously added log record, i.e. by name and then by descending update index.
+    ///
+    /// Unlike reference records, its update index may be outside of the bounds of the table, as log records
+    /// keep the update index of the change they record, or of the entry in an older table they delete.
+    pub fn add_log(&mut self, record: &LogRecord) -> Result<(), Error> {
+        if self.last_log_key.is_none() && self.last_ref_key.is_some() {
+            self.finish_ref_section()?;
+        }
+        let mut key: Vec<u8> = record.name.as_bstr().to_vec();
+        key.push(0);
+        key.extend_from_slice(&(u64::MAX - record.update_index).to_be_bytes());
+        if matches!(self.last_log_key.as_deref(), Some(last) if last >= key.as_slice()) {
+            return Err(Error::Unsorted { key: key.into() });
+        }
+
+        let mut value = Vec::new();
+        let value_type = match &record.value {
+            LogValue::Deletion => 0,
+            LogValue::Update(line) => {
+                self.check_hash(&line.previous_oid)?;
+                self.check_hash(&line.new_oid)?;
+                value.extend_from_slice(line.previous_oid.as_bytes());
+                value.extend_from_slice(line.new_oid.as_bytes());
+                put_string(&mut value, &line.signature.name);
+                put_string(&mut value, &line.signature.email);
+                put_varint(&mut value, line.signature.time.seconds as u64);
+                let tz_minutes = line.signature.time.offset / 60;
+                value.extend_from_slice(&(tz_minutes as i16).to_be_bytes());
+                let message = line.message.strip_suffix(b"\n").unwrap_or(&line.message);
+                if message.contains(&b'\n') {
+                    return Err(Error::MultiLineMessage { key: key.into() });
+                }
+                put_varint(&mut value, message.len() as u64 + 1);
+                value.extend_from_slice(message);
+                value.push(b'\n');
+                1
+            }
+        };
+        self.add(decode::BLOCK_TYPE_LOG, &key, value_type, &value)?;
+        self.last_log_key = Some(key);
+        Ok(())
+    }
+}
+
+/// Utilities
+impl<W: Write> Writer<W> {
+    fn update_index_delta(&self, update_index: u64) -> Result<u64, Error> {
+        if update_index < self.min_update_index || update_index > self.max_update_index {
+            return Err(Error::UpdateIndexOutOfBounds {
+                update_index,
+                min: self.min_update_index,
+                max: self.max_update_index,
+            });
+        }
+        Ok(update_index - self.min_update_index)
+    }
+
+    fn check_hash(&self, id: &ObjectId) -> Result<(), Error> {
+        if id.kind() != self.object_hash {
+            return Err(Error::HashKind {
+                expected: self.object_hash,
+                actual: id.kind(),
+            });
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        if self.pending_padding > 0 {
+            self.out.write_all(&vec![0; self.pending_padding])?;
+            self.offset += self.pending_padding as u64;
+            self.pending_padding = 0;
+        }
+        self.out.write_all(data)?;
+        self.offset += data.len() as u64;
+        Ok(())
+    }
+
+    /// Add a record to the current block of `kind`, starting a new block as needed.
+    fn add(&mut self, kind: u8, key: &[u8], value_type: u8, value: &[u8]) -> Result<(), Error> {
+        if let Some(block) = self.block.as_mut() {
+            if block.add(key, value_type, value, &self.options) {
+                return Ok(());
+            }
+            self.flush_block()?;
+        }
+        let mut block = self.new_block(kind);
+        if !block.add(key, value_type, value, &self.options) {
+            return Err(Error::RecordTooLarge { key: key.into() });
+        }
+        self.block = Some(block);
+        Ok(())
+    }
+
+    fn new_block(&mut self, kind: u8) -> BlockWriter {
+        let is_first = self.offset == 0 && self.pending_padding == 0;
+        self.block_offset = self.offset + self.pending_padding as u64;
+        BlockWriter::new(kind, is_first.then_some(self.header.as_slice()))
+    }
+
+    /// Write the current block, if there is one, and remember its last key for the index.
+    fn flush_block(&mut self) -> Result<(), Error> {
+        let Some(block) = self.block.take() else {
+            return Ok(());
+        };
+        let kind = block.kind;
+        self.index.push((block.last_key.clone(), self.block_offset));
+        let data = block.finish()?;
+        self.write(&data)?;
+        if kind != decode::BLOCK_TYPE_LOG {
+            self.pending_padding = (self.options.block_size as usize).saturating_sub(data.len());
+        }
+        Ok(())
+    }
+
+    /// Flush the last block of a section and write its index if it has enough blocks, returning the position of the index.
+    fn finish_section(&mut self) -> Result<u64, Error> {
+        self.flush_block()?;
+        let mut index = std::mem::take(&mut self.index);
+        let mut index_position = 0;
+        while index.len() > INDEX_THRESHOLD {
+            index_position = self.offset + self.pending_padding as u64;
+            for (key, offset) in std::mem::take(&mut index) {
+                let mut value = Vec::new();
+                put_varint(&mut value, offset);
+                self.add(decode::BLOCK_TYPE_INDEX, &key, 0, &value)?;
+            }
+            self.flush_block()?;
+            index = std::mem::take(&mut self.index);
+        }
+        Ok(index_position)
+    }
+
+    fn finish_ref_section(&mut self) -> Result<(), Error> {
+        self.footer.ref_index_position = self.finish_section()?;
+        if self.footer.ref_index_position != 0 && self.options.write_object_index && !self.objects.is_empty() {
+            self.write_object_index()?;
+        }
+        self.objects.clear();
+        self.footer.log_position = self.offset + self.pending_padding as u64;
+        Ok(())
+    }
+
+    fn finish_log_section(&mut self) -> Result<(), Error> {
+        self.footer.log_index_position = self.finish_section()?;
+        Ok(())
+    }
+
+    fn write_object_index(&mut self) -> Result<(), Error> {
+        let hash_len = self.object_hash.len_in_bytes();
+        let mut max_common_prefix = 0;
+        let mut ids = self.objects.keys();
+        if let Some(mut last) = ids.next() {
+            for id in ids {
+                let common = last
+                    .as_bytes()
+                    .iter()
+                    .zip(id.as_bytes())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                max_common_prefix = max_common_prefix.max(common);
+                last = id;
+            }
+        }
+        let obj_id_len = (max_common_prefix + 1).clamp(MIN_OBJ_ID_LEN, hash_len);
+        self.footer.obj_id_len = obj_id_len;
+        self.footer.obj_position = self.offset + self.pending_padding as u64;
+
+        for (id, offsets) in std::mem::take(&mut self.objects) {
+            let key = &id.as_bytes()[..obj_id_len];
+            let mut value = Vec::new();
+            let value_type = if (1..8).contains(&offsets.len()) {
+                offsets.len() as u8
+            } else {
+                put_varint(&mut value, offsets.len() as u64);
+                0
+            };
+            let mut last = 0;
+            for (idx, offset) in offsets.iter().enumerate() {
+                put_varint(&mut value, if idx == 0 { *offset } else { offset - last });
+                last = *offset;
+            }
+            match self.add(decode::BLOCK_TYPE_OBJ, key, value_type, &value) {
+                Err(Error::RecordTooLarge { .. }) => {
+                    // Too many blocks refer to this object, readers have to scan all of them.
+                    let mut value = Vec::new();
+                    put_varint(&mut value, 0);
+                    self.add(decode::BLOCK_TYPE_OBJ, key, 0, &value)?;
+                }
+                res => res?,
+            }
+        }
+        self.footer.obj_index_position = self.finish_section()?;
+        Ok(())
+    }
+}
+
+/// Encode `value` as variable length integer like git does for offsets in packs.
+pub(crate) fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    let mut buf = [0u8; 10];
+    let mut pos = buf.len() - 1;
+    buf[pos] = (value & 0x7f) as u8;
+    loop {
+        value >>= 7;
+        if value == 0 {
+            break;
+        }
+        value -= 1;
+        pos -= 1;
+        buf[pos] = 0x80 | (value & 0x7f) as u8;
+    }
+    out.extend_from_slice(&buf[pos..]);
+}
+
+fn put_string(out: &mut Vec<u8>, value: &[u8]) {
+    put_varint(out, value.len() as u64);
+    out.extend_from_slice(value);
+}
+
+mod error {
+    use gix_object::bstr::BString;
+
+    /// The error returned by [`Writer`][super::Writer] methods.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not write the reftable")]
+        Io(#[from] std::io::Error),
+        #[error("Records must be added in order, but {key:?} doesn't sort after the previous record")]
+        Unsorted { key: BString },
+        #[error("Reference records must be added before log records")]
+        RefAfterLog,
+        #[error("Update index {update_index} is not within {min} and {max}")]
+        UpdateIndexOutOfBounds { update_index: u64, min: u64, max: u64 },
+        #[error("Object ids must be of kind {expected}, got {actual}")]
+        HashKind {
+            expected: gix_hash::Kind,
+            actual: gix_hash::Kind,
+        },
+        #[error("The record with key {key:?} does not fit into a block")]
+        RecordTooLarge { key: BString },
+        #[error("Log messages must not contain newlines, but the one with key {key:?} does")]
+        MultiLineMessage { key: BString },
+        #[error("A block exceeds the maximum size of 16MB")]
+        BlockTooLarge,
+    }
+}
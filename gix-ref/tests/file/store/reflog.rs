@@ -66,3 +66,69 @@ mod iter_rev {
         Ok(())
     }
 }
+
+mod retain {
+    use gix_lock::acquire::Fail;
+
+    #[test]
+    fn non_existing_returns_none() -> crate::Result {
+        let tmp = gix_testtools::scripted_fixture_writable_standalone("make_repo_for_reflog.sh")?;
+        let store = crate::file::Store::at(
+            tmp.path().join(".git"),
+            gix_ref::store::WriteReflog::Disable,
+            gix_hash::Kind::Sha1,
+            false,
+        );
+        assert_eq!(
+            store.reflog_retain("FAILURE_NONEXISTING", Fail::Immediately, &mut |_| false)?,
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn removes_all_entries_that_are_not_kept() -> crate::Result {
+        let tmp = gix_testtools::scripted_fixture_writable_standalone("make_repo_for_reflog.sh")?;
+        let store = crate::file::Store::at(
+            tmp.path().join(".git"),
+            gix_ref::store::WriteReflog::Disable,
+            gix_hash::Kind::Sha1,
+            false,
+        );
+        let mut buf = Vec::new();
+        let last = store
+            .reflog_iter("HEAD", &mut buf)?
+            .expect("exists")
+            .filter_map(Result::ok)
+            .last()
+            .expect("non-empty")
+            .to_owned();
+
+        assert_eq!(
+            store.reflog_retain("HEAD", Fail::Immediately, &mut |_| true)?,
+            Some(0),
+            "nothing to do if all entries are kept"
+        );
+        assert_eq!(
+            store.reflog_retain("HEAD", Fail::Immediately, &mut |line| line.new_oid() == last.new_oid)?,
+            Some(4)
+        );
+        let remaining: Vec<_> = store
+            .reflog_iter("HEAD", &mut buf)?
+            .expect("still exists")
+            .map(|line| line.map(|line| line.to_owned()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(remaining, vec![last], "the kept entry is unchanged");
+
+        assert_eq!(
+            store.reflog_retain("refs/heads/main", Fail::Immediately, &mut |_| false)?,
+            Some(5)
+        );
+        assert_eq!(
+            store.reflog_iter("refs/heads/main", &mut buf)?.expect("exists").count(),
+            0,
+            "the log is empty, but still present"
+        );
+        Ok(())
+    }
+}
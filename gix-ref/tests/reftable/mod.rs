@@ -0,0 +1,403 @@
+use gix_hash::ObjectId;
+use gix_ref::{
+    reftable::{write, LogRecord, LogValue, RefRecord, RefValue, Table},
+    FullName,
+};
+
+fn id(n: u8) -> ObjectId {
+    ObjectId::from([n; 20])
+}
+
+fn name(name: &str) -> FullName {
+    name.try_into().expect("valid name")
+}
+
+fn object_ref(full_name: &str, update_index: u64, target: ObjectId) -> RefRecord {
+    RefRecord {
+        name: name(full_name),
+        update_index,
+        value: RefValue::Object(target),
+    }
+}
+
+fn deletion(full_name: &str, update_index: u64) -> RefRecord {
+    RefRecord {
+        name: name(full_name),
+        update_index,
+        value: RefValue::Deletion,
+    }
+}
+
+fn log(full_name: &str, update_index: u64, previous: ObjectId, new: ObjectId, message: &str) -> LogRecord {
+    LogRecord {
+        name: name(full_name),
+        update_index,
+        value: LogValue::Update(gix_ref::log::Line {
+            previous_oid: previous,
+            new_oid: new,
+            signature: crate::file::transaction::prepare_and_commit::committer(),
+            message: message.into(),
+        }),
+    }
+}
+
+fn write_table(
+    options: write::Options,
+    object_hash: gix_hash::Kind,
+    (min, max): (u64, u64),
+    refs: &[RefRecord],
+    logs: &[LogRecord],
+) -> crate::Result<Table> {
+    let mut writer = write::Writer::new(Vec::new(), options, object_hash, min, max);
+    for record in refs {
+        writer.add_ref(record)?;
+    }
+    for record in logs {
+        writer.add_log(record)?;
+    }
+    Ok(Table::from_bytes(writer.finish()?)?)
+}
+
+mod table {
+    use gix_ref::reftable::{write, RefRecord, RefValue};
+
+    use super::{deletion, id, log, name, object_ref, write_table};
+
+    #[test]
+    fn round_trip_of_all_kinds_of_records() -> crate::Result {
+        let refs = vec![
+            RefRecord {
+                name: name("HEAD"),
+                update_index: 1,
+                value: RefValue::Symbolic(name("refs/heads/main")),
+            },
+            deletion("refs/heads/gone", 1),
+            object_ref("refs/heads/main", 1, id(1)),
+            RefRecord {
+                name: name("refs/tags/v1"),
+                update_index: 1,
+                value: RefValue::ObjectAndPeeled {
+                    target: id(2),
+                    peeled: id(1),
+                },
+            },
+        ];
+        let logs = vec![
+            log("refs/heads/main", 2, id(1), id(3), "second"),
+            log("refs/heads/main", 1, gix_hash::Kind::Sha1.null(), id(1), "first"),
+        ];
+        let table = write_table(write::Options::default(), gix_hash::Kind::Sha1, (1, 2), &refs, &logs)?;
+        assert_eq!(table.version(), 1);
+        assert_eq!((table.min_update_index(), table.max_update_index()), (1, 2));
+
+        assert_eq!(table.refs().collect::<Result<Vec<_>, _>>()?, refs);
+        assert_eq!(table.logs().collect::<Result<Vec<_>, _>>()?, logs);
+        assert_eq!(table.find_ref(name("refs/heads/main").as_ref())?, Some(refs[2].clone()));
+        assert_eq!(table.find_ref(name("refs/heads/missing").as_ref())?, None);
+        assert_eq!(
+            table.find_ref(name("refs/heads/gone").as_ref())?,
+            Some(refs[1].clone()),
+            "deletions are records as well"
+        );
+        assert_eq!(
+            table.logs_of(name("refs/heads/main").as_ref())?.count(),
+            2,
+            "logs are returned newest first"
+        );
+        assert_eq!(table.refs_pointing_to(&id(1))?, vec![refs[2].clone(), refs[3].clone()]);
+        Ok(())
+    }
+
+    #[test]
+    fn many_references_in_small_blocks_are_indexed() -> crate::Result {
+        let refs: Vec<_> = (0..2000u32)
+            .map(|n| object_ref(&format!("refs/heads/branch-{n:05}"), 1, id((n % 7) as u8)))
+            .collect();
+        let options = write::Options {
+            block_size: 256,
+            restart_interval: 4,
+            write_object_index: true,
+        };
+        let table = write_table(options, gix_hash::Kind::Sha1, (1, 1), &refs, &[])?;
+        assert_eq!(table.block_size(), 256);
+        assert_eq!(table.refs().collect::<Result<Vec<_>, _>>()?, refs);
+        for record in refs.iter().step_by(97) {
+            assert_eq!(table.find_ref(record.name.as_ref())?.as_ref(), Some(record));
+        }
+        assert_eq!(
+            table
+                .refs_prefixed("refs/heads/branch-019".into())?
+                .collect::<Result<Vec<_>, _>>()?,
+            refs[1900..2000]
+        );
+        assert_eq!(
+            table.refs_pointing_to(&id(3))?,
+            refs.iter()
+                .filter(|r| r.value == RefValue::Object(id(3)))
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sha256_tables_use_version_2() -> crate::Result {
+        let target = gix_hash::ObjectId::from_bytes_or_panic(&[5; 32]);
+        let refs = vec![RefRecord {
+            name: name("refs/heads/main"),
+            update_index: 1,
+            value: RefValue::Object(target),
+        }];
+        let table = write_table(write::Options::default(), gix_hash::Kind::Sha256, (1, 1), &refs, &[])?;
+        assert_eq!(table.version(), 2);
+        assert_eq!(table.object_hash(), gix_hash::Kind::Sha256);
+        assert_eq!(table.find_ref(name("refs/heads/main").as_ref())?, Some(refs[0].clone()));
+        Ok(())
+    }
+
+    #[test]
+    fn ref_update_indices_must_be_within_bounds() {
+        let mut writer = write::Writer::new(Vec::new(), write::Options::default(), gix_hash::Kind::Sha1, 2, 3);
+        assert!(writer.add_ref(&object_ref("refs/heads/main", 1, id(1))).is_err());
+    }
+}
+
+mod stack {
+    use gix_ref::reftable::{write, Stack};
+
+    use super::{deletion, id, log, name, object_ref, write_table};
+
+    #[test]
+    fn newer_tables_shadow_older_ones() -> crate::Result {
+        let options = write::Options::default();
+        let kind = gix_hash::Kind::Sha1;
+        let stack = Stack::from_tables(vec![
+            write_table(
+                options,
+                kind,
+                (1, 1),
+                &[
+                    object_ref("refs/heads/a", 1, id(1)),
+                    object_ref("refs/heads/b", 1, id(1)),
+                ],
+                &[log("refs/heads/a", 1, kind.null(), id(1), "create")],
+            )?,
+            write_table(
+                options,
+                kind,
+                (2, 2),
+                &[deletion("refs/heads/a", 2), object_ref("refs/heads/b", 2, id(2))],
+                &[log("refs/heads/b", 2, id(1), id(2), "update")],
+            )?,
+        ]);
+        assert_eq!(stack.max_update_index(), 2);
+        assert_eq!(
+            stack.find_ref(name("refs/heads/a").as_ref())?,
+            None,
+            "deleted in newer table"
+        );
+        assert_eq!(
+            stack.find_ref(name("refs/heads/b").as_ref())?,
+            Some(object_ref("refs/heads/b", 2, id(2)))
+        );
+        assert_eq!(
+            stack.refs().collect::<Result<Vec<_>, _>>()?,
+            vec![object_ref("refs/heads/b", 2, id(2))]
+        );
+        assert_eq!(stack.refs_with_deletions().count(), 2);
+        assert_eq!(stack.logs().count(), 2, "logs of all tables are visible");
+        assert!(stack.refs_pointing_to(&id(1))?.is_empty(), "only current values count");
+        Ok(())
+    }
+}
+
+mod store {
+    use gix_ref::{
+        file,
+        reftable::{RefValue, Store},
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        Target,
+    };
+
+    use super::{id, log, name, object_ref};
+    use crate::file::transaction::prepare_and_commit::{committer, empty_store};
+
+    fn reftable_store() -> crate::Result<(gix_testtools::tempfile::TempDir, file::Store)> {
+        let (dir, mut store) = empty_store()?;
+        store.use_reftable();
+        Ok((dir, store))
+    }
+
+    fn update(full_name: &str, new: Target, message: &str) -> RefEdit {
+        RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: true,
+                    message: message.into(),
+                },
+                expected: PreviousValue::Any,
+                new,
+            },
+            name: name(full_name),
+            deref: false,
+        }
+    }
+
+    fn delete(full_name: &str) -> RefEdit {
+        RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::MustExist,
+                log: RefLog::AndReference,
+            },
+            name: name(full_name),
+            deref: false,
+        }
+    }
+
+    fn commit(store: &file::Store, edits: Vec<RefEdit>) -> crate::Result<Vec<RefEdit>> {
+        Ok(store
+            .transaction()
+            .prepare(
+                edits,
+                gix_lock::acquire::Fail::Immediately,
+                gix_lock::acquire::Fail::Immediately,
+            )?
+            .commit(committer().to_ref())?)
+    }
+
+    #[test]
+    fn transactions_write_tables_which_are_used_for_lookups_iteration_and_reflogs() -> crate::Result {
+        let (dir, store) = reftable_store()?;
+        commit(
+            &store,
+            vec![
+                update("HEAD", Target::Symbolic(name("refs/heads/main")), "init"),
+                update("refs/heads/main", Target::Peeled(id(1)), "commit: first"),
+            ],
+        )?;
+        assert!(dir.path().join("reftable").join("tables.list").is_file());
+        assert!(!dir.path().join("refs").exists(), "no loose references are written");
+
+        assert_eq!(store.find("HEAD")?.target, Target::Symbolic(name("refs/heads/main")));
+        assert_eq!(store.find("main")?.target, Target::Peeled(id(1)));
+
+        commit(
+            &store,
+            vec![
+                update("refs/heads/main", Target::Peeled(id(2)), "commit: second\n\nbody"),
+                update("refs/heads/other", Target::Peeled(id(2)), "branch"),
+            ],
+        )?;
+        let err = store
+            .transaction()
+            .prepare(
+                Some(RefEdit {
+                    change: Change::Update {
+                        log: LogChange::default(),
+                        expected: PreviousValue::MustExistAndMatch(Target::Peeled(id(1))),
+                        new: Target::Peeled(id(3)),
+                    },
+                    name: name("refs/heads/main"),
+                    deref: false,
+                }),
+                gix_lock::acquire::Fail::Immediately,
+                gix_lock::acquire::Fail::Immediately,
+            )
+            .expect_err("the reference changed");
+        assert!(matches!(
+            err,
+            gix_ref::file::transaction::prepare::Error::ReferenceOutOfDate { .. }
+        ));
+
+        let refs = store.iter()?.all()?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            refs.iter().map(|r| r.name.as_bstr().to_string()).collect::<Vec<_>>(),
+            ["refs/heads/main", "refs/heads/other"]
+        );
+
+        let mut buf = Vec::new();
+        let lines = store
+            .reflog_iter("refs/heads/main", &mut buf)?
+            .expect("log exists")
+            .map(|line| line.map(gix_ref::log::Line::from))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            lines,
+            vec![
+                log_line(gix_hash::Kind::Sha1.null(), id(1), "commit: first"),
+                log_line(id(1), id(2), "commit: second body")
+            ],
+            "multi-line messages are folded into one line"
+        );
+        let mut buf = [0; 256];
+        let newest = store
+            .reflog_iter_rev("refs/heads/main", &mut buf)?
+            .expect("log exists")
+            .next()
+            .expect("one line")?;
+        assert_eq!(newest.new_oid, id(2));
+
+        commit(&store, vec![delete("refs/heads/other")])?;
+        assert!(store.try_find("other")?.is_none());
+        assert!(!store.reflog_exists("refs/heads/other")?, "the log is deleted as well");
+        assert_eq!(store.iter()?.all()?.count(), 1);
+
+        let reftable = store.reftable().expect("in use");
+        let stack = reftable.stack()?.expect("tables exist");
+        assert_eq!(
+            stack.names().len(),
+            1,
+            "tables are compacted automatically as each one would be larger than the one before"
+        );
+        Ok(())
+    }
+
+    fn log_line(previous: gix_hash::ObjectId, new: gix_hash::ObjectId, message: &str) -> gix_ref::log::Line {
+        match log("refs/heads/main", 0, previous, new, message).value {
+            gix_ref::reftable::LogValue::Update(line) => line,
+            gix_ref::reftable::LogValue::Deletion => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn manual_compaction_merges_all_tables_and_drops_deletions() -> crate::Result {
+        let dir = gix_testtools::tempfile::tempdir()?;
+        let mut store = Store::at(dir.path().join("reftable"), gix_hash::Kind::Sha1);
+        store.auto_compaction = false;
+        for (idx, branch) in ["a", "b", "c"].into_iter().enumerate() {
+            let t = store.transaction(gix_lock::acquire::Fail::Immediately)?;
+            let update_index = t.next_update_index();
+            assert_eq!(update_index, idx as u64 + 1);
+            t.commit(
+                vec![object_ref(&format!("refs/heads/{branch}"), update_index, id(1))],
+                Vec::new(),
+            )?;
+        }
+        let t = store.transaction(gix_lock::acquire::Fail::Immediately)?;
+        let update_index = t.next_update_index();
+        t.commit(vec![super::deletion("refs/heads/b", update_index)], Vec::new())?;
+        assert_eq!(store.stack()?.expect("present").names().len(), 4);
+
+        store.compact(gix_lock::acquire::Fail::Immediately)?;
+        let stack = store.stack()?.expect("present");
+        assert_eq!(stack.names().len(), 1);
+        assert_eq!(
+            std::fs::read_dir(store.dir())?.count(),
+            2,
+            "only the list and a single table remain"
+        );
+        assert_eq!(
+            stack.refs_with_deletions().collect::<Result<Vec<_>, _>>()?,
+            vec![
+                object_ref("refs/heads/a", 1, id(1)),
+                object_ref("refs/heads/c", 3, id(1))
+            ]
+        );
+        assert!(matches!(
+            stack.find_ref(name("refs/heads/c").as_ref())?.map(|r| r.value),
+            Some(RefValue::Object(_))
+        ));
+        Ok(())
+    }
+}
@@ -26,6 +26,13 @@ pub enum Push<'a> {
         /// The reference or pattern to delete on the remote.
         ref_or_pattern: &'a BStr,
     },
+    /// Exclude a single ref or the refs matching a pattern from being pushed.
+    ///
+    /// Note that it's matched against the destination on the remote, not against the local source.
+    Exclude {
+        /// A full ref name on the remote, or a pattern with a single `*`. It cannot be a spelled out object hash.
+        src: &'a BStr,
+    },
     /// Push a single ref or refspec to a known destination ref.
     Matching {
         /// The source ref or refspec to push. If pattern, it contains a single `*`.
@@ -28,11 +28,12 @@ impl<'a> MatchGroup<'a> {
 
 /// Matching
 impl<'a> MatchGroup<'a> {
-    /// Match all `items` against all *fetch* specs present in this group, returning deduplicated mappings from source to destination.
-    /// *Note that this method is correct only for specs*, even though it also *works for push-specs*.
+    /// Match all `items` against all specs present in this group, returning deduplicated mappings from source to destination.
+    /// For fetch specs, `items` are the references on the remote, whereas for push specs these are the local references.
     ///
     /// Note that negative matches are not part of the return value, so they are not observable but will be used to remove mappings.
-    // TODO: figure out how to deal with push-specs, probably when push is being implemented.
+    /// Like in `git`, negative fetch specs are matched against the source of each mapping, while negative push specs are matched
+    /// against the destination on the remote, see [`is_excluded()`](Self::is_excluded()).
     pub fn match_remotes<'item>(self, mut items: impl Iterator<Item = Item<'item>> + Clone) -> Outcome<'a, 'item> {
         let mut out = Vec::new();
         let mut seen = BTreeSet::default();
@@ -84,30 +85,35 @@ impl<'a> MatchGroup<'a> {
 
         if let Some(hash_kind) = has_negation.then(|| items.next().map(|i| i.target.kind())).flatten() {
             let null_id = hash_kind.null();
-            for matcher in matchers
-                .into_iter()
-                .zip(self.specs.iter())
-                .filter_map(|(m, spec)| m.and_then(|m| (spec.mode == Mode::Negative).then_some(m)))
-            {
-                out.retain(|m| match m.lhs {
-                    SourceRef::ObjectId(_) => true,
-                    SourceRef::FullName(name) => {
-                        !matcher
-                            .matches_lhs(Item {
-                                full_ref_name: name,
-                                target: &null_id,
-                                object: None,
-                            })
-                            .0
-                    }
-                });
-            }
+            out.retain(|m| {
+                let remote_name = match (self.specs[m.spec_index].op, m.lhs, m.rhs.as_deref()) {
+                    (Operation::Fetch, SourceRef::FullName(name), _) => name,
+                    (Operation::Push, _, Some(dst)) => dst,
+                    (Operation::Push, SourceRef::FullName(name), None) => name,
+                    (_, SourceRef::ObjectId(_), _) => return true,
+                };
+                !self.is_excluded(Item {
+                    full_ref_name: remote_name,
+                    target: &null_id,
+                    object: None,
+                })
+            });
         }
         Outcome {
             group: self,
             mappings: out,
         }
     }
+
+    /// Return `true` if `item` is matched by any of the negative specs in this group, and should thus be excluded.
+    ///
+    /// `item` is expected to refer to a reference on the remote, which is the source when fetching, or the destination when pushing.
+    pub fn is_excluded(&self, item: Item<'_>) -> bool {
+        self.specs
+            .iter()
+            .filter(|spec| spec.mode == Mode::Negative)
+            .any(|spec| Matcher::from(*spec).matches_lhs(item).0)
+    }
 }
 
 fn calculate_hash<T: std::hash::Hash>(t: &T) -> u64 {
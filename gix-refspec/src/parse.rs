@@ -8,19 +8,15 @@ pub enum Error {
     NegativeWithDestination,
     #[error("Negative specs must not be empty")]
     NegativeEmpty,
-    #[error("Negative specs are only supported when fetching")]
-    NegativeUnsupported,
-    #[error("Negative specs must be object hashes")]
+    #[error("Negative specs must not be object hashes")]
     NegativeObjectHash,
     #[error("Negative specs must be full ref names, starting with \"refs/\"")]
     NegativePartialName,
-    #[error("Negative glob patterns are not allowed")]
-    NegativeGlobPattern,
     #[error("Fetch destinations must be ref-names, like 'HEAD:refs/heads/branch'")]
     InvalidFetchDestination,
     #[error("Cannot push into an empty destination")]
     PushToEmpty,
-    #[error("glob patterns may only involved a single '*' character, found {pattern:?}")]
+    #[error("Glob patterns may only involve a single '*' character, found {pattern:?}")]
     PatternUnsupported { pattern: bstr::BString },
     #[error("Both sides of the specification need a pattern, like 'a/*:b/*'")]
     PatternUnbalanced,
@@ -62,9 +58,6 @@ pub(crate) mod function {
         let mode = match spec.first() {
             Some(&b'^') => {
                 spec = &spec[1..];
-                if operation == Operation::Push {
-                    return Err(Error::NegativeUnsupported);
-                }
                 Mode::Negative
             }
             Some(&b'+') => {
@@ -123,6 +116,8 @@ pub(crate) mod function {
         }
         let (src, src_had_pattern) = validated(src, operation == Operation::Push && dst.is_some())?;
         let (dst, dst_had_pattern) = validated(dst, false)?;
+        // Like `git`, a push without destination uses the source as destination, which may also be a pattern.
+        let dst_had_pattern = dst_had_pattern || (operation == Operation::Push && dst.is_none() && src_had_pattern);
         if mode != Mode::Negative && src_had_pattern != dst_had_pattern {
             return Err(Error::PatternUnbalanced);
         }
@@ -130,9 +125,7 @@ pub(crate) mod function {
         if mode == Mode::Negative {
             match src {
                 Some(spec) => {
-                    if src_had_pattern {
-                        return Err(Error::NegativeGlobPattern);
-                    } else if looks_like_object_hash(spec) {
+                    if looks_like_object_hash(spec) {
                         return Err(Error::NegativeObjectHash);
                     } else if !spec.starts_with(b"refs/") && spec != "HEAD" {
                         return Err(Error::NegativePartialName);
@@ -107,8 +107,11 @@ impl<'a> RefSpecRef<'a> {
     }
 
     /// Always returns the remote side, whose actual side in the refspec depends on how it was parsed.
+    ///
+    /// Note that negative push specs only have a remote side.
     pub fn remote(&self) -> Option<&BStr> {
         match self.op {
+            Operation::Push if self.mode == Mode::Negative => self.src,
             Operation::Push => self.dst,
             Operation::Fetch => self.src,
         }
@@ -117,6 +120,7 @@ impl<'a> RefSpecRef<'a> {
     /// Always returns the local side, whose actual side in the refspec depends on how it was parsed.
     pub fn local(&self) -> Option<&BStr> {
         match self.op {
+            Operation::Push if self.mode == Mode::Negative => None,
             Operation::Push => self.src,
             Operation::Fetch => self.dst,
         }
@@ -208,6 +212,7 @@ impl<'a> RefSpecRef<'a> {
                     dst,
                     allow_non_fast_forward: matches!(self.mode, Mode::Force),
                 }),
+                (Mode::Negative, Some(src), None) => Instruction::Push(Push::Exclude { src }),
                 (mode, src, dest) => {
                     unreachable!(
                         "BUG: push instructions with {:?} {:?} {:?} are not possible",
@@ -7,7 +7,8 @@ pub(crate) enum Mode {
     Normal,
     /// Even though according to normal rules a non-fastforward would be denied, override this and reset a ref forcefully in the destination.
     Force,
-    /// Instead of considering matching refs included, we consider them excluded. This applies only to the source side of a refspec.
+    /// Instead of considering matching refs included, we consider them excluded. This applies only to refs on the remote,
+    /// which is the source side of a fetch refspec, and the destination of the mappings of a push refspec.
     Negative,
 }
 
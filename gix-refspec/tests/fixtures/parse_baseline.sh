@@ -71,6 +71,13 @@ baseline fetch "refs/heads/${bad}"
 baseline fetch 'refs/*/*'
 baseline fetch 'refs/heads/*'
 baseline fetch '^refs/*/*'
+baseline push '^refs/*/*'
+baseline push '^refs/heads/wip/*:refs/heads/wip/*'
+baseline push '^refs/heads/main:'
+baseline push 'refs/heads/*/*'
+baseline push 'refs/heads/*:refs/remotes/*/*'
+baseline push ':refs/heads/*'
+baseline fetch 'refs/heads/*:refs/remotes/*/*'
 
 # valid
 baseline push '+:'
@@ -97,12 +104,21 @@ baseline fetch 'HEAD'
 baseline push '@'
 baseline fetch '@'
 
-baseline push '^@' fail
+baseline push '^@'
 baseline fetch '^@'
 baseline fetch '^refs/heads/main'
 baseline fetch '^refs/heads/*'
 baseline fetch '^heads/main'
 baseline fetch '^heads/*'
+baseline fetch '^refs/heads/wip/*'
+baseline push '^refs/heads/main'
+baseline push '^refs/heads/*'
+baseline push '^refs/heads/wip/*'
+baseline push '^heads/main'
+baseline push '^HEAD'
+baseline push 'refs/heads/*'
+baseline push '+refs/heads/*'
+baseline push 'refs/heads/*/for-linus'
 
 baseline push '+@'
 baseline fetch '+@'
@@ -184,3 +184,76 @@ mod multiple {
         )
     }
 }
+
+mod push {
+    use gix_refspec::{match_group::SourceRef, parse::Operation, MatchGroup};
+
+    use crate::matching::baseline;
+
+    /// Return `source:destination` for each mapping, with expectations checked by hand with `git push --dry-run`.
+    fn mappings<'a>(specs: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let group = MatchGroup::from_push_specs(
+            specs
+                .into_iter()
+                .map(|spec| gix_refspec::parse(spec.into(), Operation::Push).expect("valid spec")),
+        );
+        group
+            .match_remotes(baseline::input())
+            .mappings
+            .into_iter()
+            .map(|m| {
+                let SourceRef::FullName(lhs) = m.lhs else {
+                    unreachable!("no object ids are used")
+                };
+                format!("{lhs}:{}", m.rhs.as_deref().unwrap_or(lhs))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn negations_match_the_destination() {
+        assert_eq!(
+            mappings(["refs/heads/*:refs/heads/*", "^refs/heads/f*"]),
+            [
+                "refs/heads/main:refs/heads/main",
+                "refs/heads/sub/f4:refs/heads/sub/f4",
+                "refs/heads/sub/subdir/f5:refs/heads/sub/subdir/f5",
+                "refs/heads/suub/f6:refs/heads/suub/f6"
+            ]
+        );
+        assert_eq!(
+            mappings(["refs/heads/*", "^refs/heads/s*", "^refs/heads/main"]),
+            [
+                "refs/heads/f1:refs/heads/f1",
+                "refs/heads/f2:refs/heads/f2",
+                "refs/heads/f3:refs/heads/f3"
+            ],
+            "patterns without destination push to the same name, and negations may be patterns"
+        );
+        assert_eq!(
+            mappings(["refs/heads/f*:refs/remotes/origin/f*", "^refs/remotes/origin/f1"]),
+            [
+                "refs/heads/f2:refs/remotes/origin/f2",
+                "refs/heads/f3:refs/remotes/origin/f3"
+            ]
+        );
+        assert_eq!(
+            mappings(["refs/heads/f*:refs/remotes/origin/f*", "^refs/heads/f1"]),
+            [
+                "refs/heads/f1:refs/remotes/origin/f1",
+                "refs/heads/f2:refs/remotes/origin/f2",
+                "refs/heads/f3:refs/remotes/origin/f3"
+            ],
+            "the local source isn't considered"
+        );
+    }
+
+    #[test]
+    fn negations_apply_to_single_refs_as_well() {
+        assert!(mappings(["refs/heads/f1", "^refs/heads/f*"]).is_empty());
+        assert_eq!(
+            mappings(["refs/heads/f1:refs/heads/other", "^refs/heads/f*"]),
+            ["refs/heads/f1:refs/heads/other"]
+        );
+    }
+}
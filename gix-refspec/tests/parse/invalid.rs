@@ -33,16 +33,20 @@ fn complex_patterns_with_more_than_one_asterisk() {
             ));
         }
     }
-    assert!(matches!(
-        try_parse("^*/*", Operation::Fetch).unwrap_err(),
-        Error::PatternUnsupported { .. }
-    ));
+    for op in [Operation::Fetch, Operation::Push] {
+        for spec in ["^*/*", "^refs/heads/*/*", "refs/heads/*:refs/remotes/*/*"] {
+            assert!(matches!(
+                try_parse(spec, op).unwrap_err(),
+                Error::PatternUnsupported { .. }
+            ));
+        }
+    }
 }
 
 #[test]
 fn both_sides_need_pattern_if_one_uses_it() {
     for op in [Operation::Fetch, Operation::Push] {
-        for spec in ["refs/*/a", ":a/*", "+:a/*", "a*:b/c", "a:b/*"] {
+        for spec in [":a/*", "+:a/*", "a*:b/c", "a:b/*"] {
             assert!(
                 matches!(try_parse(spec, op).unwrap_err(), Error::PatternUnbalanced),
                 "{}",
@@ -50,6 +54,13 @@ fn both_sides_need_pattern_if_one_uses_it() {
             );
         }
     }
+    assert!(
+        matches!(
+            try_parse("refs/*/a", Operation::Fetch).unwrap_err(),
+            Error::PatternUnbalanced
+        ),
+        "fetching needs a destination for patterns, while pushing uses the source as destination"
+    );
 }
 
 #[test]
@@ -38,13 +38,7 @@ fn baseline() {
                 }
                 _ => {
                     match (res.as_ref().err(), err_code == 0) {
-                        (
-                            Some(
-                                gix_refspec::parse::Error::NegativePartialName
-                                | gix_refspec::parse::Error::NegativeGlobPattern,
-                            ),
-                            true,
-                        ) => {} // we prefer failing fast, git let's it pass
+                        (Some(gix_refspec::parse::Error::NegativePartialName), true) => {} // we prefer failing fast, git let's it pass
                         _ => {
                             eprintln!("{err_code} {res:?} {} {:?}", kind.as_bstr(), spec.as_bstr());
                             mismatch += 1;
@@ -7,15 +7,65 @@ use gix_refspec::{
 use crate::parse::{assert_parse, b, try_parse};
 
 #[test]
-fn negative_unsupported() {
-    for spec in ["^a:b", "^a:", "^:", "^:b", "^"] {
+fn negative_with_destination() {
+    for spec in ["^a:b", "^a:", "^:", "^:b"] {
         assert!(matches!(
             try_parse(spec, Operation::Push).unwrap_err(),
-            Error::NegativeUnsupported
+            Error::NegativeWithDestination
         ));
     }
 }
 
+#[test]
+fn negative_must_not_be_empty() {
+    assert!(matches!(
+        try_parse("^", Operation::Push).unwrap_err(),
+        Error::NegativeEmpty
+    ));
+}
+
+#[test]
+fn exclude() {
+    assert!(matches!(
+        try_parse("^a", Operation::Push).unwrap_err(),
+        Error::NegativePartialName
+    ));
+    assert!(matches!(
+        try_parse("^e69de29bb2d1d6434b8b29ae775ad8c2e48c5391", Operation::Push).unwrap_err(),
+        Error::NegativeObjectHash
+    ));
+    let spec = assert_parse(
+        "^refs/heads/wip/*",
+        Instruction::Push(Push::Exclude {
+            src: b("refs/heads/wip/*"),
+        }),
+    );
+    assert_eq!(
+        spec.remote(),
+        Some(b("refs/heads/wip/*")),
+        "it excludes refs on the remote"
+    );
+    assert_eq!(spec.local(), None);
+    assert_parse(
+        "^refs/heads/main",
+        Instruction::Push(Push::Exclude {
+            src: b("refs/heads/main"),
+        }),
+    );
+}
+
+#[test]
+fn patterns_without_destination_push_to_the_same_name() {
+    assert_parse(
+        "refs/heads/*",
+        Instruction::Push(Push::Matching {
+            src: b("refs/heads/*"),
+            dst: b("refs/heads/*"),
+            allow_non_fast_forward: false,
+        }),
+    );
+}
+
 #[test]
 fn revspecs_with_ref_name_destination() {
     assert_parse(
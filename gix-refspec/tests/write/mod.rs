@@ -30,6 +30,17 @@ mod push {
         );
     }
 
+    #[test]
+    fn exclude() {
+        assert_eq!(
+            Instruction::Push(instruction::Push::Exclude {
+                src: "refs/heads/wip/*".into(),
+            })
+            .to_bstring(),
+            "^refs/heads/wip/*"
+        );
+    }
+
     #[test]
     fn matching() {
         assert_eq!(
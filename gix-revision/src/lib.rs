@@ -1,4 +1,5 @@
-//! Interact with git revisions by parsing them from rev-specs and describing them in terms of reference names.
+//! Interact with git revisions by parsing them from rev-specs, describing them in terms of reference names
+//! and finding their merge-bases.
 //!
 //! ## Feature Flags
 #![cfg_attr(
@@ -15,6 +16,13 @@ pub mod describe;
 #[cfg(feature = "describe")]
 pub use describe::function::describe;
 
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "merge_base")]
+pub mod merge_base;
+#[cfg(feature = "merge_base")]
+pub use merge_base::function::merge_base;
+
 ///
 #[allow(clippy::empty_docs)]
 pub mod spec;
@@ -0,0 +1,266 @@
+use std::cmp::Ordering;
+
+use gix_hash::ObjectId;
+use gix_revwalk::graph;
+
+use super::{Error, Flags};
+use crate::{graph::Generation, Graph, PriorityQueue};
+
+/// Given a commit at `first` id, traverse the commit `graph` and return all possible merge-base between it and `others`,
+/// sorted from best to worst, or `None` if there is no merge-base as `first` and `others` don't share history.
+/// If `others` is empty, `Some(first)` is returned.
+///
+/// Note that this function doesn't do any work if `first` is contained in `others`, which is when `first` will be returned
+/// as only merge-base right away. This is even the case if some commits of `others` are disjoint.
+///
+/// This is the equivalent of `git merge-base --all first others…`, where `others` are treated as if they were merged into
+/// one commit, and only the first of the returned merge-bases would be printed without `--all`.
+///
+/// ### Performance
+///
+/// Generation numbers of a commit-graph that was provided to `graph` are used to stop the traversal early.
+pub fn merge_base(
+    first: ObjectId,
+    others: &[ObjectId],
+    graph: &mut Graph<'_, graph::Commit<Flags>>,
+) -> Result<Option<Vec<ObjectId>>, Error> {
+    let _span = gix_trace::coarse!("gix_revision::merge_base()", ?first, ?others);
+    if others.is_empty() || others.contains(&first) {
+        return Ok(Some(vec![first]));
+    }
+
+    graph.clear();
+    let bases = paint_down_to_common(first, others, graph, None)?;
+    graph.clear();
+
+    let bases = remove_redundant(&bases, graph)?;
+    Ok((!bases.is_empty()).then_some(bases))
+}
+
+/// Find all merge-bases of all `commits` in the commit `graph`, which are commits that are reachable from each of them
+/// but not from another such commit, sorted from best to worst, or `None` if they don't share history.
+///
+/// This is the equivalent of `git merge-base --octopus --all commits…`.
+pub fn octopus(
+    commits: &[ObjectId],
+    graph: &mut Graph<'_, graph::Commit<Flags>>,
+) -> Result<Option<Vec<ObjectId>>, Error> {
+    let _span = gix_trace::coarse!("gix_revision::merge_base::octopus()", ?commits);
+    let Some((first, rest)) = commits.split_first() else {
+        return Ok(None);
+    };
+    let mut bases = vec![*first];
+    for commit in rest {
+        let mut next_bases = Vec::new();
+        for base in &bases {
+            for id in merge_base(*base, &[*commit], graph)?.into_iter().flatten() {
+                if !next_bases.contains(&id) {
+                    next_bases.push(id);
+                }
+            }
+        }
+        if next_bases.is_empty() {
+            return Ok(None);
+        }
+        bases = next_bases;
+    }
+    Ok(Some(remove_redundant(&bases, graph)?))
+}
+
+/// Return all `commits` that can't be reached from any other commit in `commits`, in the order they were provided
+/// and without duplicates.
+///
+/// This is the equivalent of `git merge-base --independent commits…`.
+pub fn independent(commits: &[ObjectId], graph: &mut Graph<'_, graph::Commit<Flags>>) -> Result<Vec<ObjectId>, Error> {
+    let _span = gix_trace::coarse!("gix_revision::merge_base::independent()", ?commits);
+    remove_redundant(commits, graph)
+}
+
+/// Return `true` if `ancestor` can be reached from `descendant` in the commit `graph`, which is also the case if both are the same.
+///
+/// This is the equivalent of `git merge-base --is-ancestor ancestor descendant`.
+///
+/// ### Performance
+///
+/// Generation numbers of a commit-graph that was provided to `graph` are used to stop the traversal once all commits
+/// that could possibly lead to `ancestor` were seen.
+pub fn is_ancestor(
+    ancestor: ObjectId,
+    descendant: ObjectId,
+    graph: &mut Graph<'_, graph::Commit<Flags>>,
+) -> Result<bool, Error> {
+    let _span = gix_trace::coarse!("gix_revision::merge_base::is_ancestor()", %ancestor, %descendant);
+    if ancestor == descendant {
+        return Ok(true);
+    }
+
+    graph.clear();
+    let ancestor_generation = lookup(ancestor, graph)?.generation;
+    if let Some((ancestor_generation, descendant_generation)) =
+        ancestor_generation.zip(lookup(descendant, graph)?.generation)
+    {
+        if descendant_generation <= ancestor_generation {
+            graph.clear();
+            return Ok(false);
+        }
+    }
+
+    paint_down_to_common(ancestor, &[descendant], graph, ancestor_generation)?;
+    let is_ancestor = graph[&ancestor].data.contains(Flags::COMMIT2);
+    graph.clear();
+    Ok(is_ancestor)
+}
+
+/// Remove all commits from `commits` that are reachable from another commit in `commits`, keeping their order.
+fn remove_redundant(commits: &[ObjectId], graph: &mut Graph<'_, graph::Commit<Flags>>) -> Result<Vec<ObjectId>, Error> {
+    let mut commits = commits.to_vec();
+    {
+        let mut seen = gix_hashtable::HashSet::default();
+        commits.retain(|id| seen.insert(*id));
+    }
+    if commits.len() < 2 {
+        return Ok(commits);
+    }
+
+    graph.clear();
+    let mut min_generation = GENERATION_NUMBER_INFINITY;
+    for id in &commits {
+        min_generation = min_generation.min(lookup(*id, graph)?.generation.unwrap_or(GENERATION_NUMBER_INFINITY));
+    }
+
+    let mut redundant = vec![false; commits.len()];
+    for idx in 0..commits.len() {
+        if redundant[idx] {
+            continue;
+        }
+        let (others, others_idx): (Vec<_>, Vec<_>) = commits
+            .iter()
+            .enumerate()
+            .filter(|(other_idx, _)| *other_idx != idx && !redundant[*other_idx])
+            .map(|(other_idx, id)| (*id, other_idx))
+            .unzip();
+        graph.clear();
+        paint_down_to_common(commits[idx], &others, graph, Some(min_generation))?;
+        if graph[&commits[idx]].data.contains(Flags::COMMIT2) {
+            redundant[idx] = true;
+        }
+        for (other, other_idx) in others.iter().zip(others_idx) {
+            if graph[other].data.contains(Flags::COMMIT1) {
+                redundant[other_idx] = true;
+            }
+        }
+    }
+    graph.clear();
+
+    Ok(commits
+        .into_iter()
+        .zip(redundant)
+        .filter_map(|(id, is_redundant)| (!is_redundant).then_some(id))
+        .collect())
+}
+
+/// Walk the graph from `first` and `others` at the same time and mark all commits with the side they were reached from,
+/// until only commits that are reachable from both sides are left. Return all commits reachable from both sides that
+/// weren't reached from another such commit, ordered by commit-time, youngest first.
+///
+/// If `min_generation` is set, the traversal stops at commits with a lower generation.
+fn paint_down_to_common(
+    first: ObjectId,
+    others: &[ObjectId],
+    graph: &mut Graph<'_, graph::Commit<Flags>>,
+    min_generation: Option<Generation>,
+) -> Result<Vec<ObjectId>, Error> {
+    let mut queue = PriorityQueue::<GenThenTime, ObjectId>::new();
+    insert_start(first, Flags::COMMIT1, graph, &mut queue)?;
+    for other in others {
+        insert_start(*other, Flags::COMMIT2, graph, &mut queue)?;
+    }
+
+    let mut out = Vec::new();
+    while queue.iter_unordered().any(|id| !graph[id].data.contains(Flags::STALE)) {
+        let (info, commit_id) = queue.pop().expect("we have non-stale");
+        if min_generation.is_some_and(|min_generation| info.generation < min_generation) {
+            break;
+        }
+        let commit = graph.get_mut(&commit_id).expect("everything queued is in graph");
+        let mut flags_without_result = commit.data & (Flags::COMMIT1 | Flags::COMMIT2 | Flags::STALE);
+        if flags_without_result == (Flags::COMMIT1 | Flags::COMMIT2) {
+            if !commit.data.contains(Flags::RESULT) {
+                commit.data |= Flags::RESULT;
+                out.push(commit_id);
+            }
+            flags_without_result |= Flags::STALE;
+        }
+
+        for parent_id in commit.parents.clone() {
+            let Some(parent) = graph.try_lookup_or_insert_commit(parent_id, |_| {})? else {
+                // skip missing objects, this is due to shallow clones for instance.
+                continue;
+            };
+            if parent.data.contains(flags_without_result) {
+                continue;
+            }
+            parent.data |= flags_without_result;
+            queue.insert(GenThenTime::from(&*parent), parent_id);
+        }
+    }
+
+    out.retain(|id| !graph[id].data.contains(Flags::STALE));
+    out.sort_by(|a, b| graph[b].commit_time.cmp(&graph[a].commit_time));
+    Ok(out)
+}
+
+fn insert_start(
+    id: ObjectId,
+    flags: Flags,
+    graph: &mut Graph<'_, graph::Commit<Flags>>,
+    queue: &mut PriorityQueue<GenThenTime, ObjectId>,
+) -> Result<(), Error> {
+    let commit = graph
+        .try_lookup_or_insert_commit(id, |data| *data |= flags)?
+        .ok_or(Error::MissingCommit { id })?;
+    queue.insert(GenThenTime::from(&*commit), id);
+    Ok(())
+}
+
+fn lookup<'a>(
+    id: ObjectId,
+    graph: &'a mut Graph<'_, graph::Commit<Flags>>,
+) -> Result<&'a mut graph::Commit<Flags>, Error> {
+    graph
+        .try_lookup_or_insert_commit(id, |_| {})?
+        .ok_or(Error::MissingCommit { id })
+}
+
+/// The generation assumed for commits that aren't part of a commit-graph, which places them before all commits that are.
+const GENERATION_NUMBER_INFINITY: Generation = Generation::MAX;
+
+/// Orders commits by generation first, and by commit-time if generations are equal, which happens if no commit-graph is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GenThenTime {
+    generation: Generation,
+    time: gix_date::SecondsSinceUnixEpoch,
+}
+
+impl From<&graph::Commit<Flags>> for GenThenTime {
+    fn from(commit: &graph::Commit<Flags>) -> Self {
+        GenThenTime {
+            generation: commit.generation.unwrap_or(GENERATION_NUMBER_INFINITY),
+            time: commit.commit_time,
+        }
+    }
+}
+
+impl PartialOrd<Self> for GenThenTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GenThenTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.generation
+            .cmp(&other.generation)
+            .then_with(|| self.time.cmp(&other.time))
+    }
+}
@@ -0,0 +1,28 @@
+bitflags::bitflags! {
+    /// The flags used in the graph for finding [merge bases](crate::merge_base()).
+    #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+    pub struct Flags: u8 {
+        /// The commit belongs to the graph reachable by the first commit.
+        const COMMIT1 = 1 << 0;
+        /// The commit belongs to the graph reachable by any of the other commits.
+        const COMMIT2 = 1 << 1;
+
+        /// Marks the commit as done, it's reachable by both COMMIT1 and COMMIT2.
+        const STALE = 1 << 2;
+        /// The commit was already put into the result list.
+        const RESULT = 1 << 3;
+    }
+}
+
+/// The error returned by the [`merge_base()`][function::merge_base()] function and its siblings.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Commit {id} to find merge-bases for could not be found")]
+    MissingCommit { id: gix_hash::ObjectId },
+    #[error("A commit could not be looked up or decoded during traversal")]
+    Lookup(#[from] crate::graph::try_lookup_or_insert_default::Error),
+}
+
+pub(crate) mod function;
+pub use function::{independent, is_ancestor, octopus};
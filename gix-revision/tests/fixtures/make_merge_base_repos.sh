@@ -0,0 +1,117 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Commit with a distinct, monotonically increasing timestamp to make the order of merge-bases predictable.
+time=0
+function tick () {
+  time=$((time + 60))
+  export GIT_AUTHOR_DATE="@$((946684800 + time)) +0000"
+  export GIT_COMMITTER_DATE="$GIT_AUTHOR_DATE"
+}
+
+function commit () {
+  tick
+  git commit -q --allow-empty -m "$1"
+  git tag "$1"
+}
+
+function merge () {
+  local name=${1:?first argument is the name of the merge commit}
+  shift
+  tick
+  git merge -q --no-ff --allow-unrelated-histories -m "$name" "$@"
+  git tag "$name"
+}
+
+function baseline () {
+  local mode=${1:?first argument is the mode}
+  shift
+  local args=()
+  for name in "$@"; do
+    args+=("$(git rev-parse "$name")")
+  done
+  echo "$mode ${args[*]}" >> baseline.git
+  case "$mode" in
+    all) git merge-base --all "${args[@]}" >> baseline.git || : ;;
+    octopus) git merge-base --all --octopus "${args[@]}" >> baseline.git || : ;;
+    independent) git merge-base --independent "${args[@]}" >> baseline.git ;;
+    is-ancestor)
+      if git merge-base --is-ancestor "${args[@]}"; then
+        echo true >> baseline.git
+      else
+        echo false >> baseline.git
+      fi
+      ;;
+  esac
+  echo >> baseline.git
+}
+
+git init -q
+git config merge.ff false
+git checkout -q -b main
+
+#  R---A1--A2--X1---A3      (main)
+#   \   \      /    /
+#    \   B1---B2---B3       (branch)
+#     \         \
+#      C1--------C2         (other)
+#
+#  U1                       (unrelated)
+commit R
+commit A1
+git checkout -q -b branch
+commit B1
+git checkout -q -b other R
+commit C1
+git checkout -q main
+commit A2
+git checkout -q branch
+commit B2
+git checkout -q main
+merge X1 branch
+git checkout -q other
+merge C2 B2
+git checkout -q branch
+commit B3
+git checkout -q main
+merge A3 B3
+
+# criss-cross merges have two merge-bases
+git checkout -q -b cross-a A2
+commit D1
+git checkout -q -b cross-b B2
+commit E1
+git checkout -q cross-a
+merge D2 E1
+git checkout -q cross-b
+merge E2 D1
+
+git checkout -q --orphan unrelated
+git rm -rfq . 2>/dev/null || :
+commit U1
+git checkout -q main
+
+baseline all A3 C2
+baseline all A2 B2
+baseline all B1 B3
+baseline all D2 E2
+baseline all A2 B1 C1
+baseline all C2 B3 A2
+baseline all A3 U1
+baseline all R R
+baseline octopus A3 C2 E2
+baseline octopus D2 E2 A2
+baseline octopus A2 B2 C1
+baseline octopus A3 C2 U1
+baseline independent A3 C2 B1 R
+baseline independent A1 B1 A2 R B1
+baseline independent D1 E1 D2 E2
+baseline independent U1 R
+baseline is-ancestor R A3
+baseline is-ancestor A3 R
+baseline is-ancestor B1 C2
+baseline is-ancestor C1 A3
+baseline is-ancestor D1 E2
+baseline is-ancestor U1 A3
+
+git commit-graph write --no-progress --reachable
@@ -0,0 +1,117 @@
+use gix_hash::ObjectId;
+use gix_revision::merge_base;
+
+use crate::hex_to_id;
+
+#[test]
+fn validate() -> crate::Result {
+    let root = fixture_path();
+    let store = odb_at(&root);
+    let baseline = std::fs::read_to_string(root.join("baseline.git"))?;
+    let cases = parse_baseline(&baseline);
+    assert_eq!(cases.len(), 22, "all cases are parsed");
+
+    for use_commitgraph in [false, true] {
+        let cache = use_commitgraph
+            .then(|| gix_commitgraph::Graph::from_info_dir(&store.store_ref().path().join("info")).unwrap());
+        let mut graph = gix_revision::Graph::new(&store, cache);
+        for Case { mode, input, expected } in &cases {
+            let actual = match *mode {
+                "all" => gix_revision::merge_base(input[0], &input[1..], &mut graph)?
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>(),
+                "octopus" => merge_base::octopus(input, &mut graph)?
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|id| id.to_string())
+                    .collect(),
+                "independent" => merge_base::independent(input, &mut graph)?
+                    .into_iter()
+                    .map(|id| id.to_string())
+                    .collect(),
+                "is-ancestor" => vec![merge_base::is_ancestor(input[0], input[1], &mut graph)?.to_string()],
+                unknown => unreachable!("unknown mode: {unknown}"),
+            };
+            assert_eq!(
+                &actual, expected,
+                "{mode} {input:?} must match the baseline (commitgraph: {use_commitgraph})"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn missing_commits_are_an_error() -> crate::Result {
+    let root = fixture_path();
+    let store = odb_at(&root);
+    let baseline = std::fs::read_to_string(root.join("baseline.git"))?;
+    let existing = parse_baseline(&baseline)[0].input[0];
+    let mut graph = gix_revision::Graph::new(&store, None);
+    let missing = hex_to_id("0000000000000000000000000000000000000001");
+    for res in [
+        gix_revision::merge_base(missing, &[existing], &mut graph).map(|_| ()),
+        gix_revision::merge_base(existing, &[missing], &mut graph).map(|_| ()),
+        merge_base::is_ancestor(missing, existing, &mut graph).map(|_| ()),
+        merge_base::independent(&[existing, missing], &mut graph).map(|_| ()),
+    ] {
+        assert!(
+            matches!(res, Err(merge_base::Error::MissingCommit { id }) if id == missing),
+            "commits to start from must exist"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn trivial_cases_do_not_traverse() -> crate::Result {
+    let store = odb_at(&fixture_path());
+    let mut graph = gix_revision::Graph::new(&store, None);
+    let missing = hex_to_id("0000000000000000000000000000000000000001");
+    assert_eq!(
+        gix_revision::merge_base(missing, &[], &mut graph)?,
+        Some(vec![missing]),
+        "without others, the first commit is the merge-base"
+    );
+    assert_eq!(
+        gix_revision::merge_base(missing, &[missing], &mut graph)?,
+        Some(vec![missing]),
+        "a commit is its own merge-base"
+    );
+    assert!(merge_base::is_ancestor(missing, missing, &mut graph)?);
+    assert_eq!(merge_base::octopus(&[], &mut graph)?, None);
+    assert_eq!(merge_base::independent(&[missing], &mut graph)?, vec![missing]);
+    Ok(())
+}
+
+fn odb_at(root: &std::path::Path) -> gix_odb::Handle {
+    gix_odb::at(root.join(".git/objects")).unwrap()
+}
+
+fn fixture_path() -> std::path::PathBuf {
+    gix_testtools::scripted_fixture_read_only("make_merge_base_repos.sh").unwrap()
+}
+
+struct Case<'a> {
+    mode: &'a str,
+    input: Vec<ObjectId>,
+    expected: Vec<String>,
+}
+
+fn parse_baseline(baseline: &str) -> Vec<Case<'_>> {
+    baseline
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+        .map(|block| {
+            let mut lines = block.lines();
+            let mut header = lines.next().expect("header").split(' ');
+            Case {
+                mode: header.next().expect("mode"),
+                input: header.map(hex_to_id).collect(),
+                expected: lines.map(ToOwned::to_owned).collect(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use bstr::{BStr, ByteSlice};
+
+use crate::{FixupMessage, Instruction};
+
+/// Move each [`Pick`](Instruction::Pick) of a commit whose summary starts with `fixup! `, `squash! ` or `amend! ` right
+/// after the instruction using the commit it refers to, and turn it into a fixup, squash or `fixup -C` respectively,
+/// just like `git rebase --autosquash` does.
+///
+/// The remainder of the summary refers to a commit with the same summary, a commit whose name starts with it,
+/// or a commit whose summary starts with it, in that order. Only commits used by earlier instructions are considered,
+/// and instructions referring to unknown commits are left untouched.
+pub fn autosquash(instructions: &mut Vec<Instruction>) {
+    let mut first_by_summary = HashMap::<&BStr, usize>::new();
+    let mut root_of = vec![None; instructions.len()];
+    let mut last_of_chain = vec![None; instructions.len()];
+    let mut next = vec![None; instructions.len()];
+    let mut kinds = Vec::new();
+
+    for (idx, instruction) in instructions.iter().enumerate() {
+        let Some(summary) = instruction.summary().filter(|_| instruction.commit().is_some()) else {
+            continue;
+        };
+        if let Instruction::Pick { .. } = instruction {
+            if let Some((kind, target)) = strip_prefixes(summary) {
+                let root = first_by_summary
+                    .get(target)
+                    .copied()
+                    .or_else(|| {
+                        let is_hex = target.len() >= 4 && target.iter().all(u8::is_ascii_hexdigit);
+                        is_hex
+                            .then(|| {
+                                instructions[..idx].iter().position(
+                                    |other| matches!(other.commit(), Some(commit) if commit.starts_with(target)),
+                                )
+                            })
+                            .flatten()
+                    })
+                    .or_else(|| {
+                        instructions[..idx].iter().position(|other| {
+                            other.commit().is_some()
+                                && matches!(other.summary(), Some(summary) if summary.starts_with(target))
+                        })
+                    })
+                    .map(|target| root_of[target].unwrap_or(target));
+                if let Some(root) = root {
+                    root_of[idx] = Some(root);
+                    match last_of_chain[root] {
+                        Some(last) => next[last] = Some(idx),
+                        None => next[root] = Some(idx),
+                    }
+                    last_of_chain[root] = Some(idx);
+                    kinds.push((idx, kind));
+                }
+            }
+        }
+        first_by_summary.entry(summary).or_insert(idx);
+    }
+    if kinds.is_empty() {
+        return;
+    }
+
+    let mut taken: Vec<_> = std::mem::take(instructions).into_iter().map(Some).collect();
+    for (idx, kind) in kinds {
+        if let Some(Instruction::Pick { commit, summary }) = taken[idx].take() {
+            taken[idx] = Some(match kind {
+                Kind::Squash => Instruction::Squash { commit, summary },
+                Kind::Fixup => Instruction::Fixup {
+                    commit,
+                    summary,
+                    message: FixupMessage::Discard,
+                },
+                Kind::Amend => Instruction::Fixup {
+                    commit,
+                    summary,
+                    message: FixupMessage::Replace,
+                },
+            });
+        }
+    }
+    for (idx, root) in root_of.iter().enumerate() {
+        if root.is_some() {
+            continue;
+        }
+        let mut cursor = Some(idx);
+        while let Some(idx) = cursor {
+            instructions.extend(taken[idx].take());
+            cursor = next[idx];
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Kind {
+    Fixup,
+    Squash,
+    Amend,
+}
+
+/// Return the kind of fixup indicated by the first prefix of `summary`, and the summary without all of these prefixes.
+fn strip_prefixes(summary: &BStr) -> Option<(Kind, &BStr)> {
+    let prefixes = [
+        ("fixup! ", Kind::Fixup),
+        ("squash! ", Kind::Squash),
+        ("amend! ", Kind::Amend),
+    ];
+    let mut kind = None;
+    let mut summary = summary.as_bytes();
+    'outer: loop {
+        for (prefix, prefix_kind) in prefixes {
+            if let Some(rest) = summary.strip_prefix(prefix.as_bytes()) {
+                kind.get_or_insert(prefix_kind);
+                summary = rest.trim_start_with(|c| c.is_ascii_whitespace());
+                continue 'outer;
+            }
+        }
+        break;
+    }
+    kind.map(|kind| (kind, summary.as_bstr()))
+}
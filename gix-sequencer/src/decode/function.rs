@@ -0,0 +1,156 @@
+use bstr::{BStr, ByteSlice};
+
+use crate::{decode::Error, FixupMessage, Instruction};
+
+/// Parse all instructions from `data` in the format of `git-rebase-todo`, with one instruction per line.
+///
+/// Commands may be abbreviated to their first letter, and empty lines as well as lines starting with `#` are ignored.
+/// Summaries may optionally be prefixed with `# `, as newer versions of `git` write them.
+pub fn decode(data: &[u8]) -> Result<Vec<Instruction>, Error> {
+    let mut out = Vec::new();
+    for (line_number, line) in data.lines().enumerate().map(|(idx, line)| (idx + 1, line)) {
+        let line = line.trim_with(|c| c.is_ascii_whitespace()).as_bstr();
+        if line.is_empty() || line[0] == b'#' {
+            continue;
+        }
+        let (command, args) = split_word(line);
+        let name = match command.as_bytes() {
+            b"p" | b"pick" => "pick",
+            b"r" | b"reword" => "reword",
+            b"e" | b"edit" => "edit",
+            b"s" | b"squash" => "squash",
+            b"f" | b"fixup" => "fixup",
+            b"x" | b"exec" => "exec",
+            b"b" | b"break" => "break",
+            b"d" | b"drop" => "drop",
+            b"l" | b"label" => "label",
+            b"t" | b"reset" => "reset",
+            b"m" | b"merge" => "merge",
+            b"u" | b"update-ref" => "update-ref",
+            b"noop" => "noop",
+            _ => {
+                return Err(Error::UnknownCommand {
+                    line_number,
+                    line: line.into(),
+                })
+            }
+        };
+        let missing_argument = || Error::MissingArgument {
+            line_number,
+            command: name,
+        };
+        if matches!(name, "break" | "noop") {
+            if !args.is_empty() {
+                return Err(Error::UnexpectedArgument {
+                    line_number,
+                    command: name,
+                });
+            }
+        } else if args.is_empty() {
+            return Err(missing_argument());
+        }
+
+        let instruction = match name {
+            "pick" | "reword" | "edit" | "squash" | "drop" => {
+                let (commit, summary) = commit_and_summary(args);
+                let (commit, summary) = (commit.into(), summary.into());
+                match name {
+                    "pick" => Instruction::Pick { commit, summary },
+                    "reword" => Instruction::Reword { commit, summary },
+                    "edit" => Instruction::Edit { commit, summary },
+                    "squash" => Instruction::Squash { commit, summary },
+                    _ => Instruction::Drop { commit, summary },
+                }
+            }
+            "fixup" => {
+                let (flag, rest) = split_word(args);
+                let (message, args) = match flag.as_bytes() {
+                    b"-C" => (FixupMessage::Replace, rest),
+                    b"-c" => (FixupMessage::ReplaceAndEdit, rest),
+                    _ => (FixupMessage::Discard, args),
+                };
+                if args.is_empty() {
+                    return Err(missing_argument());
+                }
+                let (commit, summary) = commit_and_summary(args);
+                Instruction::Fixup {
+                    commit: commit.into(),
+                    summary: summary.into(),
+                    message,
+                }
+            }
+            "exec" => Instruction::Exec { command: args.into() },
+            "break" => Instruction::Break,
+            "noop" => Instruction::Noop,
+            "label" => Instruction::Label {
+                name: split_word(args).0.into(),
+            },
+            "reset" => Instruction::Reset {
+                target: args
+                    .find(" #")
+                    .map_or(args, |pos| {
+                        args[..pos].trim_end_with(|c| c.is_ascii_whitespace()).as_bstr()
+                    })
+                    .into(),
+            },
+            "update-ref" => Instruction::UpdateRef {
+                name: split_word(args).0.into(),
+            },
+            "merge" => {
+                let (flag, rest) = split_word(args);
+                let (commit, edit_message, mut args) = match flag.as_bytes() {
+                    b"-C" | b"-c" => {
+                        let (commit, rest) = split_word(rest);
+                        if commit.is_empty() {
+                            return Err(missing_argument());
+                        }
+                        (Some(commit.into()), flag == "-c", rest)
+                    }
+                    _ => (None, false, args),
+                };
+                let mut labels = Vec::new();
+                let mut summary = BStr::new("");
+                while !args.is_empty() {
+                    if args[0] == b'#' {
+                        summary = args[1..].trim_start_with(|c| c.is_ascii_whitespace()).as_bstr();
+                        break;
+                    }
+                    let (label, rest) = split_word(args);
+                    labels.push(label.into());
+                    args = rest;
+                }
+                if labels.is_empty() {
+                    return Err(missing_argument());
+                }
+                Instruction::Merge {
+                    commit,
+                    edit_message,
+                    labels,
+                    summary: summary.into(),
+                }
+            }
+            _ => unreachable!("all commands are handled"),
+        };
+        out.push(instruction);
+    }
+    Ok(out)
+}
+
+/// Split the first word off `input`, and return it along with the remainder without leading whitespace.
+fn split_word(input: &BStr) -> (&BStr, &BStr) {
+    match input.find_byteset(b" \t") {
+        Some(pos) => (
+            input[..pos].as_bstr(),
+            input[pos..].trim_start_with(|c| c.is_ascii_whitespace()).as_bstr(),
+        ),
+        None => (input, BStr::new("")),
+    }
+}
+
+fn commit_and_summary(args: &BStr) -> (&BStr, &BStr) {
+    let (commit, summary) = split_word(args);
+    let summary = summary.strip_prefix(b"#").map_or(summary, |summary| {
+        summary.trim_start_with(|c| c.is_ascii_whitespace()).as_bstr()
+    });
+    (commit, summary)
+}
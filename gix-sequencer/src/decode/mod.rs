@@ -0,0 +1,15 @@
+use bstr::BString;
+
+/// The error returned by [`decode()`](crate::decode()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Line {line_number} does not start with a known command: {line:?}")]
+    UnknownCommand { line_number: usize, line: BString },
+    #[error("Line {line_number}: '{command}' needs an argument")]
+    MissingArgument { line_number: usize, command: &'static str },
+    #[error("Line {line_number}: '{command}' does not accept arguments")]
+    UnexpectedArgument { line_number: usize, command: &'static str },
+}
+
+pub(crate) mod function;
@@ -0,0 +1,68 @@
+use std::io::Write;
+
+use crate::{FixupMessage, Instruction};
+
+/// Write all `instructions` to `out` in the format of `git-rebase-todo`, with one instruction per line.
+///
+/// Commands are always written in full, and [`decode()`](crate::decode()) can read them back.
+pub fn encode(instructions: &[Instruction], out: &mut dyn Write) -> std::io::Result<()> {
+    for instruction in instructions {
+        out.write_all(instruction.name().as_bytes())?;
+        match instruction {
+            Instruction::Pick { commit, summary }
+            | Instruction::Reword { commit, summary }
+            | Instruction::Edit { commit, summary }
+            | Instruction::Squash { commit, summary }
+            | Instruction::Drop { commit, summary } => write_commit_and_summary(out, commit, summary)?,
+            Instruction::Fixup {
+                commit,
+                summary,
+                message,
+            } => {
+                match message {
+                    FixupMessage::Discard => {}
+                    FixupMessage::Replace => out.write_all(b" -C")?,
+                    FixupMessage::ReplaceAndEdit => out.write_all(b" -c")?,
+                }
+                write_commit_and_summary(out, commit, summary)?
+            }
+            Instruction::Exec { command } => write_argument(out, command)?,
+            Instruction::Label { name } | Instruction::UpdateRef { name } => write_argument(out, name)?,
+            Instruction::Reset { target } => write_argument(out, target)?,
+            Instruction::Merge {
+                commit,
+                edit_message,
+                labels,
+                summary,
+            } => {
+                if let Some(commit) = commit {
+                    out.write_all(if *edit_message { b" -c" } else { b" -C" })?;
+                    write_argument(out, commit)?;
+                }
+                for label in labels {
+                    write_argument(out, label)?;
+                }
+                if !summary.is_empty() {
+                    out.write_all(b" #")?;
+                    write_argument(out, summary)?;
+                }
+            }
+            Instruction::Break | Instruction::Noop => {}
+        }
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn write_argument(out: &mut dyn Write, argument: &[u8]) -> std::io::Result<()> {
+    out.write_all(b" ")?;
+    out.write_all(argument)
+}
+
+fn write_commit_and_summary(out: &mut dyn Write, commit: &[u8], summary: &[u8]) -> std::io::Result<()> {
+    write_argument(out, commit)?;
+    if !summary.is_empty() {
+        write_argument(out, summary)?;
+    }
+    Ok(())
+}
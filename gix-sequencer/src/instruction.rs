@@ -0,0 +1,71 @@
+use bstr::BStr;
+
+use crate::Instruction;
+
+/// Access
+impl Instruction {
+    /// Return the name of the command of this instruction as it's written in lists, like `pick`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Instruction::Pick { .. } => "pick",
+            Instruction::Reword { .. } => "reword",
+            Instruction::Edit { .. } => "edit",
+            Instruction::Squash { .. } => "squash",
+            Instruction::Fixup { .. } => "fixup",
+            Instruction::Exec { .. } => "exec",
+            Instruction::Break => "break",
+            Instruction::Drop { .. } => "drop",
+            Instruction::Label { .. } => "label",
+            Instruction::Reset { .. } => "reset",
+            Instruction::Merge { .. } => "merge",
+            Instruction::UpdateRef { .. } => "update-ref",
+            Instruction::Noop => "noop",
+        }
+    }
+
+    /// Return the name of the commit this instruction applies, if there is one.
+    ///
+    /// Note that the commit of a [`Merge`](Instruction::Merge) is only used for its message, and isn't returned.
+    pub fn commit(&self) -> Option<&BStr> {
+        match self {
+            Instruction::Pick { commit, .. }
+            | Instruction::Reword { commit, .. }
+            | Instruction::Edit { commit, .. }
+            | Instruction::Squash { commit, .. }
+            | Instruction::Fixup { commit, .. }
+            | Instruction::Drop { commit, .. } => Some(commit.as_ref()),
+            Instruction::Exec { .. }
+            | Instruction::Break
+            | Instruction::Label { .. }
+            | Instruction::Reset { .. }
+            | Instruction::Merge { .. }
+            | Instruction::UpdateRef { .. }
+            | Instruction::Noop => None,
+        }
+    }
+
+    /// Return the summary of the commit this instruction applies, if there is one.
+    pub fn summary(&self) -> Option<&BStr> {
+        match self {
+            Instruction::Pick { summary, .. }
+            | Instruction::Reword { summary, .. }
+            | Instruction::Edit { summary, .. }
+            | Instruction::Squash { summary, .. }
+            | Instruction::Fixup { summary, .. }
+            | Instruction::Drop { summary, .. }
+            | Instruction::Merge { summary, .. } => Some(summary.as_ref()),
+            Instruction::Exec { .. }
+            | Instruction::Break
+            | Instruction::Label { .. }
+            | Instruction::Reset { .. }
+            | Instruction::UpdateRef { .. }
+            | Instruction::Noop => None,
+        }
+    }
+
+    /// Return `true` if this instruction melds a commit into the previous one, i.e. if it's a
+    /// [`Squash`](Instruction::Squash) or [`Fixup`](Instruction::Fixup).
+    pub fn is_fixup(&self) -> bool {
+        matches!(self, Instruction::Squash { .. } | Instruction::Fixup { .. })
+    }
+}
@@ -1,2 +1,124 @@
-#![deny(rust_2018_idioms)]
+//! Parse, write and rearrange lists of instructions that drive operations which may need human help to complete,
+//! like `git rebase --interactive`.
+//!
+//! The format is the one `git` uses in `.git/rebase-merge/git-rebase-todo` and `.git/rebase-merge/done`, with one
+//! [instruction](Instruction) per line, so both can work on the same lists.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+use bstr::BString;
+
+/// A single instruction in a list of instructions, along with all of its arguments.
+///
+/// Commits are referred to by the name used in the list, which usually is a full or abbreviated hexadecimal object id,
+/// and it's up to the caller to resolve them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// Apply the changes of `commit` and commit them with its message.
+    Pick {
+        /// The name of the commit to pick.
+        commit: BString,
+        /// The summary of the commit, which is only informative.
+        summary: BString,
+    },
+    /// Like [`Pick`](Instruction::Pick), but edit the commit message.
+    Reword {
+        /// The name of the commit to pick.
+        commit: BString,
+        /// The summary of the commit, which is only informative.
+        summary: BString,
+    },
+    /// Like [`Pick`](Instruction::Pick), but stop afterwards to allow amending the commit.
+    Edit {
+        /// The name of the commit to pick.
+        commit: BString,
+        /// The summary of the commit, which is only informative.
+        summary: BString,
+    },
+    /// Meld the changes of `commit` into the previous commit, and combine both commit messages.
+    Squash {
+        /// The name of the commit to squash.
+        commit: BString,
+        /// The summary of the commit, which is only informative.
+        summary: BString,
+    },
+    /// Meld the changes of `commit` into the previous commit, and choose the commit message according to `message`.
+    Fixup {
+        /// The name of the commit to meld into the previous one.
+        commit: BString,
+        /// The summary of the commit, which is only informative.
+        summary: BString,
+        /// Which commit message to keep.
+        message: FixupMessage,
+    },
+    /// Run `command` using the shell, and stop if it fails.
+    Exec {
+        /// The command line to run.
+        command: BString,
+    },
+    /// Stop to let the user do something before continuing.
+    Break,
+    /// Don't use `commit`, which is the same as removing the line.
+    Drop {
+        /// The name of the commit to drop.
+        commit: BString,
+        /// The summary of the commit, which is only informative.
+        summary: BString,
+    },
+    /// Label the current commit with `name` so it can be referred to later.
+    Label {
+        /// The name of the label.
+        name: BString,
+    },
+    /// Reset the current commit to `target`, which is a label or the name of a commit.
+    Reset {
+        /// The label or commit to reset to.
+        target: BString,
+    },
+    /// Create a merge commit of the current commit and all commits labeled with `labels`.
+    Merge {
+        /// The name of the original merge commit whose message to use, or `None` to use `summary` as message.
+        commit: Option<BString>,
+        /// If `true`, the message of `commit` should be edited. Always `false` if there is no `commit`.
+        edit_message: bool,
+        /// The labels of the commits to merge into the current one.
+        labels: Vec<BString>,
+        /// The one-line description of the merge, which is the message if there is no `commit`.
+        summary: BString,
+    },
+    /// Update the reference `name` to point to the current commit once the operation is complete.
+    UpdateRef {
+        /// The full name of the reference to update, like `refs/heads/main`.
+        name: BString,
+    },
+    /// Do nothing, which is useful to indicate that the list is intentionally empty.
+    Noop,
+}
+
+/// Determine which commit message to keep when performing an [`Instruction::Fixup`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FixupMessage {
+    /// Keep the message of the previous commit, which is what `fixup` does.
+    #[default]
+    Discard,
+    /// Use the message of the fixup commit instead, which is what `fixup -C` does.
+    Replace,
+    /// Like [`Replace`](FixupMessage::Replace), but edit the message, which is what `fixup -c` does.
+    ReplaceAndEdit,
+}
+
+mod instruction;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod decode;
+pub use decode::function::decode;
+
+mod encode;
+pub use encode::encode;
+
+mod autosquash;
+pub use autosquash::autosquash;
+
+mod update_refs;
+pub use update_refs::insert_update_refs;
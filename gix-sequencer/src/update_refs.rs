@@ -0,0 +1,21 @@
+use bstr::{BStr, BString};
+
+use crate::Instruction;
+
+/// Insert an [`UpdateRef`](Instruction::UpdateRef) instruction for each reference that `refs_at(commit)` returns
+/// right after the instruction using `commit`, to update them to the rewritten commit, just like
+/// `git rebase --update-refs` does.
+///
+/// Dropped commits are skipped.
+pub fn insert_update_refs(instructions: &mut Vec<Instruction>, mut refs_at: impl FnMut(&BStr) -> Vec<BString>) {
+    let mut out = Vec::with_capacity(instructions.len());
+    for instruction in instructions.drain(..) {
+        let names = match &instruction {
+            Instruction::Drop { .. } => Vec::new(),
+            instruction => instruction.commit().map(&mut refs_at).unwrap_or_default(),
+        };
+        out.push(instruction);
+        out.extend(names.into_iter().map(|name| Instruction::UpdateRef { name }));
+    }
+    *instructions = out;
+}
@@ -0,0 +1,232 @@
+use gix_sequencer::{autosquash, encode, insert_update_refs, FixupMessage, Instruction};
+
+fn pick(commit: &str, summary: &str) -> Instruction {
+    Instruction::Pick {
+        commit: commit.into(),
+        summary: summary.into(),
+    }
+}
+
+fn fixup(commit: &str, summary: &str, message: FixupMessage) -> Instruction {
+    Instruction::Fixup {
+        commit: commit.into(),
+        summary: summary.into(),
+        message,
+    }
+}
+
+fn encoded(instructions: &[Instruction]) -> String {
+    let mut buf = Vec::new();
+    encode(instructions, &mut buf).expect("writing to a vec works");
+    String::from_utf8(buf).expect("valid UTF-8")
+}
+
+mod decode {
+    use gix_sequencer::{decode, decode::Error, FixupMessage, Instruction};
+
+    use crate::{encoded, fixup, pick};
+
+    #[test]
+    fn all_commands_in_full_and_abbreviated_form() -> gix_testtools::Result {
+        let todo = "pick b1c636ca304bbae7eac5475fa172d1322ca155ef c1
+fixup 60d822fc891d4e969b4b7073eebd88d4e63c4461 fixup! c1
+update-ref refs/heads/side
+
+# a comment
+p 1b26c51 # c3
+r 1b26c52 c4 with spaces
+e 1b26c53
+s 1b26c54 squash! c4
+f -C 1b26c55 amend! c4
+fixup -c 1b26c56
+x make test  --all
+b
+d 1b26c57 dropped
+l onto
+t onto
+reset 1b26c58 # Subject of the commit
+reset [new root]
+m -C 1b26c59 topic # Merge branch 'topic'
+merge -c 1b26c60 one two
+merge topic
+u refs/heads/main
+  noop
+";
+        let instructions = decode(todo.as_bytes())?;
+        let expected = vec![
+            pick("b1c636ca304bbae7eac5475fa172d1322ca155ef", "c1"),
+            fixup(
+                "60d822fc891d4e969b4b7073eebd88d4e63c4461",
+                "fixup! c1",
+                FixupMessage::Discard,
+            ),
+            Instruction::UpdateRef {
+                name: "refs/heads/side".into(),
+            },
+            pick("1b26c51", "c3"),
+            Instruction::Reword {
+                commit: "1b26c52".into(),
+                summary: "c4 with spaces".into(),
+            },
+            Instruction::Edit {
+                commit: "1b26c53".into(),
+                summary: "".into(),
+            },
+            Instruction::Squash {
+                commit: "1b26c54".into(),
+                summary: "squash! c4".into(),
+            },
+            fixup("1b26c55", "amend! c4", FixupMessage::Replace),
+            fixup("1b26c56", "", FixupMessage::ReplaceAndEdit),
+            Instruction::Exec {
+                command: "make test  --all".into(),
+            },
+            Instruction::Break,
+            Instruction::Drop {
+                commit: "1b26c57".into(),
+                summary: "dropped".into(),
+            },
+            Instruction::Label { name: "onto".into() },
+            Instruction::Reset { target: "onto".into() },
+            Instruction::Reset {
+                target: "1b26c58".into(),
+            },
+            Instruction::Reset {
+                target: "[new root]".into(),
+            },
+            Instruction::Merge {
+                commit: Some("1b26c59".into()),
+                edit_message: false,
+                labels: vec!["topic".into()],
+                summary: "Merge branch 'topic'".into(),
+            },
+            Instruction::Merge {
+                commit: Some("1b26c60".into()),
+                edit_message: true,
+                labels: vec!["one".into(), "two".into()],
+                summary: "".into(),
+            },
+            Instruction::Merge {
+                commit: None,
+                edit_message: false,
+                labels: vec!["topic".into()],
+                summary: "".into(),
+            },
+            Instruction::UpdateRef {
+                name: "refs/heads/main".into(),
+            },
+            Instruction::Noop,
+        ];
+        assert_eq!(instructions, expected);
+        assert_eq!(
+            decode(encoded(&instructions).as_bytes())?,
+            instructions,
+            "what's written can be read back"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn encode_writes_full_commands() {
+        assert_eq!(
+            encoded(&[
+                pick("abcd", "c1"),
+                fixup("abce", "", FixupMessage::Replace),
+                Instruction::Merge {
+                    commit: Some("abcf".into()),
+                    edit_message: true,
+                    labels: vec!["topic".into()],
+                    summary: "Merge 'topic'".into(),
+                },
+                Instruction::Break,
+            ]),
+            "pick abcd c1\nfixup -C abce\nmerge -c abcf topic # Merge 'topic'\nbreak\n"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            decode(b"pick a\nfrobnicate abc").unwrap_err(),
+            Error::UnknownCommand { line_number: 2, .. }
+        ));
+        assert!(matches!(
+            decode(b"pick").unwrap_err(),
+            Error::MissingArgument {
+                line_number: 1,
+                command: "pick"
+            }
+        ));
+        assert!(matches!(
+            decode(b"fixup -C").unwrap_err(),
+            Error::MissingArgument { command: "fixup", .. }
+        ));
+        assert!(matches!(
+            decode(b"merge -C abc # no label").unwrap_err(),
+            Error::MissingArgument { command: "merge", .. }
+        ));
+        assert!(matches!(
+            decode(b"break now").unwrap_err(),
+            Error::UnexpectedArgument { command: "break", .. }
+        ));
+    }
+}
+
+#[test]
+fn autosquash_moves_fixups_after_their_targets() {
+    let mut instructions = vec![
+        pick("aaa1", "first"),
+        pick("aaa2", "second commit"),
+        Instruction::Exec { command: "true".into() },
+        pick("aaa3", "fixup! first"),
+        pick("aaa4", "squash! aaa2"),
+        pick("aaa5", "amend! second"),
+        pick("aaa6", "fixup! fixup! first"),
+        pick("aaa7", "fixup! unknown"),
+    ];
+    autosquash(&mut instructions);
+    assert_eq!(
+        instructions,
+        vec![
+            pick("aaa1", "first"),
+            fixup("aaa3", "fixup! first", FixupMessage::Discard),
+            fixup("aaa6", "fixup! fixup! first", FixupMessage::Discard),
+            pick("aaa2", "second commit"),
+            Instruction::Squash {
+                commit: "aaa4".into(),
+                summary: "squash! aaa2".into()
+            },
+            fixup("aaa5", "amend! second", FixupMessage::Replace),
+            Instruction::Exec { command: "true".into() },
+            pick("aaa7", "fixup! unknown"),
+        ]
+    );
+
+    let mut unchanged = vec![pick("aaa1", "fixup! first"), pick("aaa2", "first")];
+    autosquash(&mut unchanged);
+    assert_eq!(
+        unchanged,
+        vec![pick("aaa1", "fixup! first"), pick("aaa2", "first")],
+        "only earlier commits can be fixed up"
+    );
+}
+
+#[test]
+fn update_refs_are_inserted_after_their_commits() {
+    let mut instructions = vec![
+        pick("a1", "first"),
+        Instruction::Drop {
+            commit: "a2".into(),
+            summary: "dropped".into(),
+        },
+        pick("a3", "third"),
+    ];
+    insert_update_refs(&mut instructions, |commit| match commit.to_string().as_str() {
+        "a1" => vec!["refs/heads/one".into(), "refs/heads/two".into()],
+        _ => vec!["refs/heads/never".into()],
+    });
+    assert_eq!(
+        encoded(&instructions),
+        "pick a1 first\nupdate-ref refs/heads/one\nupdate-ref refs/heads/two\ndrop a2 dropped\npick a3 third\nupdate-ref refs/heads/never\n"
+    );
+}
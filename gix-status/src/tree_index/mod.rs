@@ -0,0 +1,295 @@
+//! Changes between a tree and the index, along with optional rename tracking.
+mod types;
+pub use types::{Change, Context, Error, Options, Outcome, Summary, VisitEntry};
+
+mod recorder;
+pub use recorder::Recorder;
+
+pub(super) mod function {
+    use crate::tree_index::{Change, Context, Error, Options, Outcome, VisitEntry};
+    use bstr::{BStr, BString, ByteSlice, ByteVec};
+    use gix_diff::rewrites::CopySource;
+    use gix_object::tree::EntryMode;
+    use gix_object::FindExt;
+    use std::sync::atomic::Ordering;
+
+    /// Compare the tree with `tree_id` to the entries of `index` and pass each [change](Change) to `delegate`,
+    /// similar to what `git diff --cached` would show.
+    ///
+    /// * `tree_id`
+    ///     - The tree to compare the index to, typically the tree of the `HEAD` commit.
+    /// * `index`
+    ///     - The index whose entries are compared to the ones in the tree.
+    /// * `delegate`
+    ///     - A [`VisitEntry`] implementation that sees the results of this operation.
+    /// * `objects`
+    ///     - A way to obtain trees, and blobs for similarity checks during rewrite tracking.
+    /// * `ctx`
+    ///     - Additional information that will be accessed during the comparison.
+    /// * `options`
+    ///     - A way to configure the operation.
+    ///
+    /// Conflicting index entries and those added with `--intent-to-add` aren't compared to the tree, as the index-worktree
+    /// comparison is the one to inform about these. Directory entries of sparse indices are skipped along with all tree
+    /// entries below them.
+    ///
+    /// Without rewrite tracking, changes are observed in the order of the index. Otherwise, they are sorted by their
+    /// [location](Change::location()).
+    pub fn tree_index<Find>(
+        tree_id: &gix_hash::oid,
+        index: &gix_index::State,
+        delegate: &mut impl VisitEntry,
+        objects: &Find,
+        ctx: Context<'_>,
+        options: Options,
+    ) -> Result<Outcome, Error>
+    where
+        Find: gix_object::FindObjectOrHeader,
+    {
+        let Context {
+            mut pathspec,
+            mut resource_cache,
+            should_interrupt,
+        } = ctx;
+        let mut out = Outcome::default();
+        let mut tree_entries = Vec::new();
+        let mut trees = vec![(BString::default(), tree_id.to_owned())];
+        let mut buf = Vec::new();
+        while let Some((prefix, id)) = trees.pop() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            // The empty tree isn't necessarily stored, yet it's what's used to compare the index of unborn branches with.
+            if id.is_empty_tree() {
+                continue;
+            }
+            for entry in objects.find_tree_iter(&id, &mut buf)? {
+                let entry = entry?;
+                let mut path = prefix.clone();
+                if !path.is_empty() {
+                    path.push_byte(b'/');
+                }
+                path.push_str(entry.filename);
+                if entry.mode.is_tree() {
+                    if pathspec.can_match_relative_path(path.as_bstr(), Some(true)) {
+                        trees.push((path, entry.oid.to_owned()));
+                    } else {
+                        out.entries_skipped_by_pathspec += 1;
+                    }
+                    continue;
+                }
+                tree_entries.push((path, entry.mode, entry.oid.to_owned()));
+            }
+        }
+        let mut is_excluded = |path: &BStr, is_dir: bool, out: &mut Outcome| {
+            let attr_stack = &mut resource_cache.attr_stack;
+            let pathspec_match =
+                pathspec.pattern_matching_relative_path(path, Some(is_dir), &mut |relative_path, case, is_dir, out| {
+                    match attr_stack.set_case(case).at_entry(relative_path, Some(is_dir), objects) {
+                        Ok(platform) => platform.matching_attributes(out),
+                        Err(_) => false,
+                    }
+                });
+            let excluded = match pathspec_match {
+                None => true,
+                Some(m) => m.is_excluded(),
+            };
+            if excluded {
+                out.entries_skipped_by_pathspec += 1;
+            }
+            excluded
+        };
+
+        tree_entries.retain(|(path, mode, _)| !is_excluded(path.as_bstr(), mode.is_commit(), &mut out));
+        tree_entries.sort_by(|a, b| a.0.cmp(&b.0));
+        out.tree_entries = tree_entries.len();
+
+        let mut tracker = options.rewrites.map(gix_diff::rewrites::Tracker::<Change>::new);
+        let needs_unchanged_entries = matches!(
+            options.rewrites.and_then(|rewrites| rewrites.copies),
+            Some(copies) if copies.source == CopySource::FromSetOfModifiedFilesAndAllSources
+        );
+        let mut unchanged = Vec::new();
+        let mut changes = Vec::new();
+        let mut emit = |change: Change| match tracker.as_mut() {
+            Some(tracker) => {
+                let location = change.location().to_owned();
+                if let Some(change) = tracker.try_push_change(change, location.as_bstr()) {
+                    changes.push(change);
+                }
+            }
+            None => delegate.visit_entry(change),
+        };
+
+        let mut tree_entries = tree_entries.into_iter().peekable();
+        for (entry_index, entry) in index.entries().iter().enumerate() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            let path = entry.path(index);
+            while let Some((location, entry_mode, id)) =
+                tree_entries.next_if(|(location, _, _)| location.as_bstr() < path)
+            {
+                emit(Change::Deletion {
+                    location,
+                    entry_mode,
+                    id,
+                });
+            }
+
+            if entry.mode.is_sparse() {
+                out.entries_skipped_by_entry_flags += 1;
+                let dir = path.strip_suffix(b"/").unwrap_or(path);
+                while tree_entries
+                    .next_if(|(location, _, _)| {
+                        location.starts_with(dir) && location.get(dir.len()).copied() == Some(b'/')
+                    })
+                    .is_some()
+                {
+                    out.entries_skipped_by_entry_flags += 1;
+                }
+                continue;
+            }
+            if entry.stage() != 0 || entry.flags.contains(gix_index::entry::Flags::INTENT_TO_ADD) {
+                out.entries_skipped_by_entry_flags += 1;
+                if tree_entries
+                    .next_if(|(location, _, _)| location.as_bstr() == path)
+                    .is_some()
+                {
+                    out.entries_skipped_by_entry_flags += 1;
+                }
+                continue;
+            }
+            let Some(entry_mode) = entry.mode.to_tree_entry_mode() else {
+                continue;
+            };
+            if is_excluded(path, entry.mode.is_submodule(), &mut out) {
+                continue;
+            }
+            out.index_entries += 1;
+
+            match tree_entries.next_if(|(location, _, _)| location.as_bstr() == path) {
+                Some((location, previous_entry_mode, previous_id)) => {
+                    if previous_id != entry.id || previous_entry_mode.kind() != entry_mode.kind() {
+                        emit(Change::Modification {
+                            location,
+                            index: entry_index,
+                            previous_entry_mode,
+                            previous_id,
+                            entry_mode,
+                            id: entry.id,
+                        });
+                    } else if needs_unchanged_entries {
+                        unchanged.push(Change::Modification {
+                            location,
+                            index: entry_index,
+                            previous_entry_mode,
+                            previous_id,
+                            entry_mode,
+                            id: entry.id,
+                        });
+                    }
+                }
+                None => emit(Change::Addition {
+                    location: path.to_owned(),
+                    index: entry_index,
+                    entry_mode,
+                    id: entry.id,
+                }),
+            }
+        }
+        for (location, entry_mode, id) in tree_entries {
+            emit(Change::Deletion {
+                location,
+                entry_mode,
+                id,
+            });
+        }
+
+        let Some(mut tracker) = tracker else {
+            return Ok(out);
+        };
+        let outcome = tracker
+            .emit(
+                |dest, src| {
+                    let change = match src {
+                        None => dest.change,
+                        Some(src) => {
+                            let Change::Addition {
+                                location,
+                                index,
+                                entry_mode,
+                                id,
+                            } = dest.change
+                            else {
+                                unreachable!("BUG: only additions can be destinations of rewrites");
+                            };
+                            Change::Rewrite {
+                                source_location: src.location.to_owned(),
+                                source_entry_mode: src.entry_mode,
+                                source_id: src.id,
+                                location,
+                                index,
+                                entry_mode,
+                                id,
+                                diff: src.diff,
+                                copy: src.kind == gix_diff::rewrites::tracker::visit::SourceKind::Copy,
+                            }
+                        }
+                    };
+                    changes.push(change);
+                    gix_diff::tree::visit::Action::Continue
+                },
+                &mut resource_cache,
+                objects,
+                |push| {
+                    for change in unchanged.drain(..) {
+                        let location = change.location().to_owned();
+                        push(change, location.as_bstr());
+                    }
+                    Ok::<_, std::io::Error>(())
+                },
+            )
+            .map_err(Box::new)?;
+        out.rewrites = Some(outcome);
+
+        changes.sort_by(|a, b| a.location().cmp(b.location()));
+        for change in changes {
+            delegate.visit_entry(change);
+        }
+        Ok(out)
+    }
+
+    impl gix_diff::rewrites::tracker::Change for Change {
+        fn id(&self) -> &gix_hash::oid {
+            match self {
+                Change::Addition { id, .. }
+                | Change::Deletion { id, .. }
+                | Change::Modification { id, .. }
+                | Change::Rewrite { id, .. } => id,
+            }
+        }
+
+        fn kind(&self) -> gix_diff::rewrites::tracker::ChangeKind {
+            use gix_diff::rewrites::tracker::ChangeKind;
+            match self {
+                Change::Addition { .. } => ChangeKind::Addition,
+                Change::Deletion { .. } => ChangeKind::Deletion,
+                Change::Modification { .. } | Change::Rewrite { .. } => ChangeKind::Modification,
+            }
+        }
+
+        fn entry_mode(&self) -> EntryMode {
+            match self {
+                Change::Addition { entry_mode, .. }
+                | Change::Deletion { entry_mode, .. }
+                | Change::Modification { entry_mode, .. }
+                | Change::Rewrite { entry_mode, .. } => *entry_mode,
+            }
+        }
+
+        fn id_and_entry_mode(&self) -> (&gix_hash::oid, EntryMode) {
+            (self.id(), self.entry_mode())
+        }
+    }
+}
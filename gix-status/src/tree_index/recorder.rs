@@ -0,0 +1,14 @@
+use crate::tree_index::{Change, VisitEntry};
+
+/// Convenience implementation of [`VisitEntry`] that collects all changes into a `Vec`.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    /// The collected changes.
+    pub records: Vec<Change>,
+}
+
+impl VisitEntry for Recorder {
+    fn visit_entry(&mut self, change: Change) {
+        self.records.push(change)
+    }
+}
@@ -0,0 +1,201 @@
+use bstr::{BStr, BString, ByteSlice};
+use gix_object::tree::EntryMode;
+use std::sync::atomic::AtomicBool;
+
+/// The error returned by [tree_index()`](crate::tree_index()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not find tree to compare with the index")]
+    FindTree(#[from] gix_object::find::existing_iter::Error),
+    #[error("Could not decode a tree entry")]
+    DecodeTree(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    RewriteTracker(#[from] Box<gix_diff::rewrites::tracker::emit::Error>),
+    #[error("The operation was interrupted")]
+    Interrupted,
+}
+
+/// Options for use in [tree_index()](crate::tree_index()).
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct Options {
+    /// The configuration for the rewrite tracking between the tree and the index.
+    ///
+    /// If `None`, no tracking will occur and all changes are passed to the delegate as they are encountered, in the order of the index.
+    /// If `Some(_)`, all changes are collected and will be sorted by their location before being passed to the delegate.
+    pub rewrites: Option<gix_diff::Rewrites>,
+}
+
+/// The context for [tree_index()`](crate::tree_index()).
+pub struct Context<'a> {
+    /// The pathspec to limit the amount of paths that are checked. Can be empty to allow all paths.
+    pub pathspec: gix_pathspec::Search,
+    /// A platform to produce diffable buffers for use with rewrite tracking, with an `attr_stack` that is also used to
+    /// match pathspecs with attributes.
+    pub resource_cache: gix_diff::blob::Platform,
+    /// A flag to query to learn if cancellation is requested.
+    pub should_interrupt: &'a AtomicBool,
+}
+
+/// Provide additional information collected during the runtime of [`tree_index()`](crate::tree_index()).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outcome {
+    /// The amount of non-tree entries in the tree that were compared to the index.
+    pub tree_entries: usize,
+    /// The amount of index entries that were compared to the tree.
+    pub index_entries: usize,
+    /// The amount of tree and index entries that were skipped as they didn't match the pathspec.
+    pub entries_skipped_by_pathspec: usize,
+    /// The amount of index entries that were skipped as they are conflicting or were added with `--intent-to-add`,
+    /// along with the tree entries at the same location.
+    pub entries_skipped_by_entry_flags: usize,
+    /// The result of the rewrite operation, if [rewrites were configured](Options::rewrites).
+    pub rewrites: Option<gix_diff::rewrites::Outcome>,
+}
+
+/// A change between a tree and the index, i.e. a change that would be committed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// An entry exists in the index but not in the tree.
+    Addition {
+        /// The repository-relative path of the entry.
+        location: BString,
+        /// The index of the entry for lookup in [`gix_index::State::entries()`].
+        index: usize,
+        /// The mode of the entry in the index.
+        entry_mode: EntryMode,
+        /// The object id of the entry in the index.
+        id: gix_hash::ObjectId,
+    },
+    /// An entry exists in the tree but not in the index.
+    Deletion {
+        /// The repository-relative path of the entry.
+        location: BString,
+        /// The mode of the entry in the tree.
+        entry_mode: EntryMode,
+        /// The object id of the entry in the tree.
+        id: gix_hash::ObjectId,
+    },
+    /// An entry exists in both the tree and the index, but with a different mode or object id.
+    Modification {
+        /// The repository-relative path of the entry.
+        location: BString,
+        /// The index of the entry for lookup in [`gix_index::State::entries()`].
+        index: usize,
+        /// The mode of the entry in the tree.
+        previous_entry_mode: EntryMode,
+        /// The object id of the entry in the tree.
+        previous_id: gix_hash::ObjectId,
+        /// The mode of the entry in the index.
+        entry_mode: EntryMode,
+        /// The object id of the entry in the index.
+        id: gix_hash::ObjectId,
+    },
+    /// The rewrite tracking discovered a match between a deleted or modified entry in the tree and an added entry in the index.
+    Rewrite {
+        /// The repository-relative path of the source of the rewrite.
+        source_location: BString,
+        /// The mode of the source of the rewrite.
+        source_entry_mode: EntryMode,
+        /// The object id of the source of the rewrite.
+        source_id: gix_hash::ObjectId,
+        /// The repository-relative path of the destination of the rewrite, as found in the index.
+        location: BString,
+        /// The index of the destination for lookup in [`gix_index::State::entries()`].
+        index: usize,
+        /// The mode of the destination in the index.
+        entry_mode: EntryMode,
+        /// The object id of the destination in the index.
+        id: gix_hash::ObjectId,
+        /// It's `None` if `source_id` is equal to `id`, as identity made an actual diff computation unnecessary.
+        /// Otherwise, and if enabled, it's `Some(stats)` to indicate how similar both entries were.
+        diff: Option<gix_diff::blob::DiffLineStats>,
+        /// If true, this rewrite is created by copy, and `source_location` still exists in the index.
+        /// Otherwise, it's a rename, and `source_location` was removed from the index.
+        copy: bool,
+    },
+}
+
+/// An easy to grasp summary of the changes of the index compared to the tree.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub enum Summary {
+    /// An entry exists in the index but not in the tree.
+    Added,
+    /// An entry exists in the tree but not in the index.
+    Removed,
+    /// The content or the executable bit of an entry changed.
+    Modified,
+    /// The type of the entry changed, like when a file was replaced by a symlink.
+    TypeChange,
+    /// An entry of the tree was moved to a new location in the index, possibly with modifications.
+    Renamed,
+    /// An entry of the tree was copied to a new location in the index, possibly with modifications.
+    Copied,
+}
+
+/// Access
+impl Change {
+    /// Return a summary of the change as digest of its kind.
+    pub fn summary(&self) -> Summary {
+        match self {
+            Change::Addition { .. } => Summary::Added,
+            Change::Deletion { .. } => Summary::Removed,
+            Change::Modification {
+                previous_entry_mode,
+                entry_mode,
+                ..
+            } => {
+                use gix_object::tree::EntryKind::*;
+                match (previous_entry_mode.kind(), entry_mode.kind()) {
+                    (Blob | BlobExecutable, Blob | BlobExecutable) | (Link, Link) | (Commit, Commit) | (Tree, Tree) => {
+                        Summary::Modified
+                    }
+                    _ => Summary::TypeChange,
+                }
+            }
+            Change::Rewrite { copy, .. } => {
+                if *copy {
+                    Summary::Copied
+                } else {
+                    Summary::Renamed
+                }
+            }
+        }
+    }
+
+    /// The repository-relative path at which the source of a rewrite is located.
+    ///
+    /// If this isn't a rewrite, the path is the location of the entry itself.
+    pub fn source_location(&self) -> &BStr {
+        match self {
+            Change::Rewrite { source_location, .. } => source_location.as_bstr(),
+            _ => self.location(),
+        }
+    }
+
+    /// The repository-relative path of the entry, which is the destination of a rewrite.
+    pub fn location(&self) -> &BStr {
+        match self {
+            Change::Addition { location, .. }
+            | Change::Deletion { location, .. }
+            | Change::Modification { location, .. }
+            | Change::Rewrite { location, .. } => location.as_bstr(),
+        }
+    }
+
+    /// The index of the entry in [`gix_index::State::entries()`], or `None` if it was deleted from the index.
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            Change::Addition { index, .. } | Change::Modification { index, .. } | Change::Rewrite { index, .. } => {
+                Some(*index)
+            }
+            Change::Deletion { .. } => None,
+        }
+    }
+}
+
+/// Observe the changes between a tree and the index.
+pub trait VisitEntry {
+    /// Observe a single `change`.
+    fn visit_entry(&mut self, change: Change);
+}
@@ -0,0 +1,38 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+echo "content" > modified
+echo "content" > mode
+echo "unchanged content" > unchanged
+echo "to be deleted" > deleted
+echo "becomes a link" > typechange
+seq 10 > renamed-source
+seq 20 > renamed-and-modified-source
+mkdir dir
+echo "nested" > dir/nested
+echo "conflict base" > conflict
+
+git add .
+git commit -q -m "initial"
+
+echo "change" >> modified
+chmod +x mode
+git rm -q --cached deleted
+rm typechange && ln -s somewhere typechange
+git mv renamed-source dir/renamed
+git mv renamed-and-modified-source renamed-and-modified
+echo 21 >> renamed-and-modified
+cp unchanged dir/copy
+echo "new" > added
+echo "intent" > intent-to-add
+git add mode modified typechange renamed-and-modified dir/copy added
+git add --intent-to-add intent-to-add
+
+# a conflict is not considered a change between tree and index
+blob=$(echo "ours" | git hash-object -w --stdin)
+git update-index --index-info <<INFO
+0 0000000000000000000000000000000000000000	conflict
+100644 $blob 2	conflict
+100644 $blob 3	conflict
+INFO
@@ -0,0 +1,174 @@
+use crate::status::fixture_path;
+use bstr::ByteSlice;
+use gix_diff::rewrites::{Copies, CopySource};
+use gix_status::tree_index::{Change, Context, Options, Outcome, Recorder, Summary};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn changes_without_rewrites() {
+    let (changes, out) = run(&[], None);
+    assert_eq!(
+        summarize(&changes),
+        [
+            (Summary::Added, "added", "added"),
+            (Summary::Removed, "deleted", "deleted"),
+            (Summary::Added, "dir/copy", "dir/copy"),
+            (Summary::Added, "dir/renamed", "dir/renamed"),
+            (Summary::Modified, "mode", "mode"),
+            (Summary::Modified, "modified", "modified"),
+            (Summary::Added, "renamed-and-modified", "renamed-and-modified"),
+            (
+                Summary::Removed,
+                "renamed-and-modified-source",
+                "renamed-and-modified-source"
+            ),
+            (Summary::Removed, "renamed-source", "renamed-source"),
+            (Summary::TypeChange, "typechange", "typechange"),
+        ],
+        "changes are in index order, and conflicts as well as intent-to-add entries are ignored"
+    );
+    assert_eq!(
+        out,
+        Outcome {
+            tree_entries: 9,
+            index_entries: 9,
+            entries_skipped_by_pathspec: 0,
+            entries_skipped_by_entry_flags: 4,
+            rewrites: None,
+        }
+    );
+}
+
+#[test]
+fn changes_with_renames() {
+    let (changes, out) = run(&[], Some(Default::default()));
+    assert_eq!(
+        summarize(&changes),
+        [
+            (Summary::Added, "added", "added"),
+            (Summary::Removed, "deleted", "deleted"),
+            (Summary::Added, "dir/copy", "dir/copy"),
+            (Summary::Renamed, "renamed-source", "dir/renamed"),
+            (Summary::Modified, "mode", "mode"),
+            (Summary::Modified, "modified", "modified"),
+            (Summary::Renamed, "renamed-and-modified-source", "renamed-and-modified"),
+            (Summary::TypeChange, "typechange", "typechange"),
+        ],
+        "rewrites are sorted by their destination"
+    );
+    assert!(out.rewrites.is_some(), "rewrites were enabled");
+}
+
+#[test]
+fn changes_with_copies_from_all_sources() {
+    let (changes, _out) = run(
+        &[],
+        Some(gix_diff::Rewrites {
+            copies: Some(Copies {
+                source: CopySource::FromSetOfModifiedFilesAndAllSources,
+                percentage: None,
+            }),
+            ..Default::default()
+        }),
+    );
+    assert_eq!(
+        summarize(&changes),
+        [
+            (Summary::Added, "added", "added"),
+            (Summary::Removed, "deleted", "deleted"),
+            (Summary::Copied, "unchanged", "dir/copy"),
+            (Summary::Renamed, "renamed-source", "dir/renamed"),
+            (Summary::Modified, "mode", "mode"),
+            (Summary::Modified, "modified", "modified"),
+            (Summary::Renamed, "renamed-and-modified-source", "renamed-and-modified"),
+            (Summary::TypeChange, "typechange", "typechange"),
+        ],
+        "unchanged files can be the source of copies as well"
+    );
+}
+
+#[test]
+fn pathspecs_limit_both_sides() {
+    let (changes, out) = run(&["dir", "renamed-source"], Some(Default::default()));
+    assert_eq!(
+        summarize(&changes),
+        [
+            (Summary::Added, "dir/copy", "dir/copy"),
+            (Summary::Renamed, "renamed-source", "dir/renamed"),
+        ]
+    );
+    assert_eq!(out.tree_entries, 2);
+}
+
+fn run(pathspecs: &[&str], rewrites: Option<gix_diff::Rewrites>) -> (Vec<Change>, Outcome) {
+    let worktree = fixture_path("status_tree_index.sh");
+    let git_dir = worktree.join(".git");
+    let index = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, false, Default::default()).unwrap();
+    let objects = gix_odb::at(git_dir.join("objects")).unwrap();
+    let head_tree = {
+        let head = std::fs::read_to_string(git_dir.join("refs/heads/main"))
+            .or_else(|_| std::fs::read_to_string(git_dir.join("refs/heads/master")))
+            .expect("the fixture commits to the default branch");
+        let commit_id = gix_hash::ObjectId::from_hex(head.trim().as_bytes()).unwrap();
+        let mut buf = Vec::new();
+        use gix_object::FindExt;
+        let tree = objects.find_commit(&commit_id, &mut buf).unwrap().tree();
+        tree
+    };
+    let search = gix_pathspec::Search::from_specs(
+        crate::status::index_as_worktree::to_pathspecs(pathspecs),
+        None,
+        std::path::Path::new(""),
+    )
+    .expect("valid specs can be normalized");
+    let stack = gix_worktree::Stack::from_state_and_ignore_case(
+        worktree.clone(),
+        false,
+        gix_worktree::stack::State::AttributesStack(Default::default()),
+        &index,
+        index.path_backing(),
+    );
+    let resource_cache = gix_diff::blob::Platform::new(
+        Default::default(),
+        gix_diff::blob::Pipeline::new(
+            Default::default(),
+            gix_filter::Pipeline::new(Default::default(), Default::default()),
+            vec![],
+            gix_diff::blob::pipeline::Options {
+                large_file_threshold_bytes: 0,
+                fs: gix_fs::Capabilities::probe(&git_dir),
+            },
+        ),
+        gix_diff::blob::pipeline::Mode::ToGit,
+        stack,
+    );
+
+    let mut recorder = Recorder::default();
+    let out = gix_status::tree_index(
+        &head_tree,
+        &index,
+        &mut recorder,
+        &objects,
+        Context {
+            pathspec: search,
+            resource_cache,
+            should_interrupt: &Default::default(),
+        },
+        Options { rewrites },
+    )
+    .unwrap();
+    (recorder.records, out)
+}
+
+fn summarize(changes: &[Change]) -> Vec<(Summary, &str, &str)> {
+    changes
+        .iter()
+        .map(|change| {
+            (
+                change.summary(),
+                change.source_location().to_str().unwrap(),
+                change.location().to_str().unwrap(),
+            )
+        })
+        .collect()
+}
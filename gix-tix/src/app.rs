@@ -0,0 +1,165 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use gix::bstr::BStr;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::Line,
+    Frame,
+};
+
+use crate::{
+    view::{Log, Motion, Refs, Status, View},
+    App, Control, Error,
+};
+
+/// Lifecycle
+impl<'repo> App<'repo> {
+    /// Create a new instance showing the log of `rev` in `repo`, or the log of `HEAD` if `rev` is `None`.
+    pub fn new(repo: &'repo gix::Repository, rev: Option<&BStr>) -> Result<Self, Error> {
+        let (tip, title) = match rev {
+            Some(rev) => (repo.rev_parse_single(rev)?.detach(), rev.to_string()),
+            None => (repo.head_id()?.detach(), "HEAD".into()),
+        };
+        let tip = repo.find_object(tip)?.peel_to_kind(gix::object::Kind::Commit)?.id;
+        Ok(App {
+            repo,
+            views: vec![View::Log(Log::new(repo, tip, title)?)],
+            height: 0,
+            message: None,
+        })
+    }
+}
+
+/// Access
+impl<'repo> App<'repo> {
+    /// The view that is currently displayed.
+    pub fn view(&self) -> &View<'repo> {
+        self.views.last().expect("there is always at least one view")
+    }
+
+    /// All views, with the currently displayed one last.
+    pub fn views(&self) -> &[View<'repo>] {
+        &self.views
+    }
+
+    /// The message that is displayed instead of the status bar, typically an error of the last action.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+}
+
+/// Interaction
+impl<'repo> App<'repo> {
+    /// React to `key`, and return whether the application should keep running.
+    ///
+    /// Errors are not returned, but displayed in place of the status bar until the next key press.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Control {
+        if key.kind == KeyEventKind::Release {
+            return Control::Continue;
+        }
+        self.message = None;
+        match self.handle_key_inner(key) {
+            Ok(control) => control,
+            Err(err) => {
+                self.message = Some(err.to_string());
+                Control::Continue
+            }
+        }
+    }
+
+    fn handle_key_inner(&mut self, key: KeyEvent) -> Result<Control, Error> {
+        let motion = match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(Control::Quit),
+            KeyCode::Char('j') | KeyCode::Down => Motion::Down,
+            KeyCode::Char('k') | KeyCode::Up => Motion::Up,
+            KeyCode::PageDown | KeyCode::Char(' ') => Motion::PageDown,
+            KeyCode::PageUp | KeyCode::Char('-') => Motion::PageUp,
+            KeyCode::Char('g') | KeyCode::Home => Motion::First,
+            KeyCode::Char('G') | KeyCode::End => Motion::Last,
+            KeyCode::Char('Q') => return Ok(Control::Quit),
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.views.pop();
+                return Ok(if self.views.is_empty() {
+                    Control::Quit
+                } else {
+                    Control::Continue
+                });
+            }
+            KeyCode::Enter => {
+                if let Some(view) = self.view().open(self.repo)? {
+                    self.views.push(view);
+                }
+                return Ok(Control::Continue);
+            }
+            KeyCode::Char('m') => {
+                self.views.truncate(1);
+                return Ok(Control::Continue);
+            }
+            KeyCode::Char('r') => {
+                let view = View::Refs(Refs::new(self.repo)?);
+                self.push_toplevel(view);
+                return Ok(Control::Continue);
+            }
+            KeyCode::Char('s') => {
+                let view = View::Status(Status::new(self.repo)?);
+                self.push_toplevel(view);
+                return Ok(Control::Continue);
+            }
+            KeyCode::Char('R') => {
+                let repo = self.repo;
+                self.view_mut().reload(repo)?;
+                return Ok(Control::Continue);
+            }
+            _ => return Ok(Control::Continue),
+        };
+        let height = self.height;
+        self.view_mut().apply(motion, height)?;
+        Ok(Control::Continue)
+    }
+
+    /// Draw the current view along with the status bar into `frame`.
+    pub fn draw(&mut self, frame: &mut Frame<'_>) {
+        let area = frame.size();
+        if area.height == 0 {
+            return;
+        }
+        let view_area = Rect::new(area.x, area.y, area.width, area.height - 1);
+        let status_area = Rect::new(area.x, area.y + area.height - 1, area.width, 1);
+        self.height = view_area.height as usize;
+
+        if let Err(err) = self.view_mut().render(view_area, frame.buffer_mut()) {
+            self.message = Some(err.to_string());
+        }
+        let (text, style) = match &self.message {
+            Some(message) => (message.clone(), Style::default().fg(Color::Red)),
+            None => (self.status_line(), Style::default().fg(Color::Black).bg(Color::Gray)),
+        };
+        let buf = frame.buffer_mut();
+        buf.set_style(status_area, style);
+        buf.set_line(status_area.x, status_area.y, &Line::raw(text), status_area.width);
+    }
+
+    /// Describe the current view and the position of the cursor within it.
+    fn status_line(&self) -> String {
+        let view = self.view();
+        let len = view.len();
+        let line = if len == 0 { 0 } else { view.cursor().selected + 1 };
+        let more = if view.is_complete() { "" } else { "+" };
+        let percent = (line * 100).checked_div(len).unwrap_or_default();
+        format!(
+            "[{}] {} - line {line} of {len}{more}  {percent}%",
+            view.name(),
+            view.title()
+        )
+    }
+
+    fn view_mut(&mut self) -> &mut View<'repo> {
+        self.views.last_mut().expect("there is always at least one view")
+    }
+
+    /// Show `view` right on top of the log, replacing all other views.
+    fn push_toplevel(&mut self, view: View<'repo>) {
+        self.views.truncate(1);
+        self.views.push(view);
+    }
+}
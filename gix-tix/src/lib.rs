@@ -1,2 +1,102 @@
-#![deny(rust_2018_idioms)]
+//! A history browser for the terminal like `tig`, but minimal, fast and efficient.
+//!
+//! The [`App`] holds a stack of views and reacts to key presses. It can be drawn into any [`ratatui`] terminal,
+//! which makes it possible to test it against a [`TestBackend`](ratatui::backend::TestBackend), while [`run()`] uses
+//! it in the actual terminal.
+//!
+//! ### Views
+//!
+//! * **log** - the commits reachable from a revision, newest first, along with the references pointing to them.
+//!   Commits are only traversed as far as they are displayed.
+//! * **commit** - the details of a commit, along with the files it changed compared to its first parent.
+//! * **blob** - the contents of a file.
+//! * **refs** - all references, each of which can be opened in a log.
+//! * **status** - the changes in the worktree compared to the index.
+//!
+//! ### Keys
+//!
+//! * `j`, `k`, arrow keys, `PageUp`, `PageDown`, `Space`, `g`, `G`, `Home` and `End` move the cursor.
+//! * `Enter` opens the item under the cursor.
+//! * `q` and `Esc` close the current view, and quit when closing the last one. `Q` quits right away.
+//! * `m`, `r` and `s` open the log, refs and status view respectively.
+//! * `R` reloads the current view.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+#![allow(clippy::result_large_err)]
+
+pub use crossterm;
+pub use ratatui;
+
+mod app;
+mod run;
+pub use run::run;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod view;
+
+/// The application state, which is a stack of views of which only the top-most one is visible.
+///
+/// The bottom-most view is always the log of the revision the application was started with.
+pub struct App<'repo> {
+    repo: &'repo gix::Repository,
+    views: Vec<view::View<'repo>>,
+    /// The amount of lines available to the current view when it was last drawn.
+    height: usize,
+    /// The last error, displayed in place of the status bar until the next key press.
+    message: Option<String>,
+}
+
+/// What to do after a key press was handled by [`App::handle_key()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Control {
+    /// Keep running and redraw.
+    Continue,
+    /// Quit the application.
+    Quit,
+}
+
+/// The error returned by [`App::new()`] and [`run()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not interact with the terminal")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    RevParse(#[from] gix::revision::spec::parse::single::Error),
+    #[error(transparent)]
+    HeadId(#[from] gix::reference::head_id::Error),
+    #[error(transparent)]
+    Walk(#[from] gix::revision::walk::Error),
+    #[error(transparent)]
+    WalkCommits(#[from] gix::traverse::commit::ancestors::Error),
+    #[error(transparent)]
+    FindObject(#[from] gix::object::find::existing::Error),
+    #[error(transparent)]
+    NotACommit(#[from] gix::object::try_into::Error),
+    #[error(transparent)]
+    PeelToCommit(#[from] gix::object::peel::to_kind::Error),
+    #[error(transparent)]
+    DecodeObject(#[from] gix::objs::decode::Error),
+    #[error(transparent)]
+    DiffTree(#[from] gix::diff::tree::changes::Error),
+    #[error(transparent)]
+    IterReferences(#[from] gix::reference::iter::Error),
+    #[error(transparent)]
+    IterReferencesInit(#[from] gix::reference::iter::init::Error),
+    #[error(transparent)]
+    PeelReference(#[from] gix::reference::peel::Error),
+    #[error(transparent)]
+    Status(#[from] gix::status::Error),
+    #[error(transparent)]
+    StatusIter(#[from] gix::status::index_worktree::iter::Error),
+    #[error(transparent)]
+    StatusItem(#[from] gix::status::index_worktree::Error),
+    #[error("Could not read '{}' from the worktree", path.display())]
+    ReadWorktreeFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Cannot show the status of a bare repository")]
+    BareRepository,
+}
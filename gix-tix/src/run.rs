@@ -0,0 +1,43 @@
+use std::io::Stdout;
+
+use crossterm::{
+    event::{self, Event},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use gix::bstr::BStr;
+use ratatui::{backend::CrosstermBackend, Terminal};
+
+use crate::{App, Control, Error};
+
+/// Browse the history of `rev` in `repo`, or of `HEAD` if `rev` is `None`, in the terminal until the user quits.
+///
+/// The terminal is put into raw mode and switched to the alternate screen, both of which are undone before returning.
+pub fn run(repo: &gix::Repository, rev: Option<&BStr>) -> Result<(), Error> {
+    let mut app = App::new(repo, rev)?;
+
+    enable_raw_mode()?;
+    let mut stdout = std::io::stdout();
+    if let Err(err) = execute!(stdout, EnterAlternateScreen) {
+        disable_raw_mode().ok();
+        return Err(err.into());
+    }
+    let res = Terminal::new(CrosstermBackend::new(stdout)).and_then(|mut terminal| event_loop(&mut terminal, &mut app));
+
+    disable_raw_mode().ok();
+    execute!(std::io::stdout(), LeaveAlternateScreen).ok();
+    res.map_err(Into::into)
+}
+
+fn event_loop(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App<'_>) -> std::io::Result<()> {
+    terminal.hide_cursor()?;
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if app.handle_key(key) == Control::Quit {
+                break;
+            }
+        }
+    }
+    terminal.show_cursor()
+}
@@ -0,0 +1,35 @@
+use gix::bstr::ByteSlice;
+
+use crate::view::Cursor;
+
+/// The contents of a file, line by line.
+pub struct Blob {
+    pub(crate) title: String,
+    pub(crate) lines: Vec<String>,
+    pub(crate) cursor: Cursor,
+}
+
+impl Blob {
+    /// Create a view of `data`, described by `title`.
+    ///
+    /// Binary data isn't displayed, which is assumed if there is a null-byte among its first 8000 bytes, just like `git` does.
+    pub fn new(title: String, data: &[u8]) -> Self {
+        let lines = if data[..data.len().min(8000)].contains(&0) {
+            vec![format!("Binary file, {} bytes", data.len())]
+        } else {
+            data.lines()
+                .map(|line| line.to_str_lossy().replace('\t', "        "))
+                .collect()
+        };
+        Blob {
+            title,
+            lines,
+            cursor: Cursor::default(),
+        }
+    }
+
+    /// The lines to display.
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+}
@@ -0,0 +1,143 @@
+use gix::{
+    bstr::{BString, ByteSlice},
+    diff::tree::recorder::Change,
+    ObjectId,
+};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::{
+    view::{Blob, Cursor, View},
+    Error,
+};
+
+/// The details of a commit, along with the files it changed compared to its first parent.
+pub struct Commit {
+    pub(crate) id: ObjectId,
+    pub(crate) lines: Vec<CommitLine>,
+    pub(crate) cursor: Cursor,
+}
+
+/// A line in the [`Commit`] view.
+pub struct CommitLine {
+    pub(crate) text: Line<'static>,
+    /// The path and id of the blob to show for lines that represent a changed file.
+    pub(crate) blob: Option<(BString, ObjectId)>,
+}
+
+impl Commit {
+    /// Create a view of the commit with `id`, with the cursor on the first changed file.
+    pub fn new(repo: &gix::Repository, id: ObjectId) -> Result<Self, Error> {
+        let commit = repo.find_object(id)?.try_into_commit()?;
+        let mut lines = Vec::new();
+        let label = |name: &str| Span::styled(format!("{name:<12}"), Style::default().fg(Color::Yellow));
+        {
+            let decoded = commit.decode()?;
+            lines.push(Line::from(vec![
+                label("commit"),
+                Span::styled(id.to_string(), Style::default().fg(Color::Yellow)),
+            ]));
+            for parent in decoded.parents() {
+                lines.push(Line::from(vec![label("parent"), Span::raw(parent.to_string())]));
+            }
+            for (name, signature) in [("Author:", decoded.author), ("Commit:", decoded.committer)] {
+                lines.push(Line::from(vec![
+                    label(name),
+                    Span::raw(format!("{} <{}>", signature.name, signature.email)),
+                ]));
+                lines.push(Line::from(vec![
+                    label(&format!("{}Date:", name.trim_end_matches(':'))),
+                    Span::raw(signature.time.format(gix::date::time::format::ISO8601)),
+                ]));
+            }
+            lines.push(Line::default());
+            for line in decoded.message.lines() {
+                lines.push(Line::raw(format!("    {}", line.to_str_lossy())));
+            }
+            lines.push(Line::default());
+        }
+        let mut lines: Vec<_> = lines.into_iter().map(|text| CommitLine { text, blob: None }).collect();
+        let first_change = lines.len();
+
+        for change in changes(repo, &commit)? {
+            let (status, color, path, id) = match change {
+                Change::Addition { path, oid, .. } => ("A", Color::Green, path, oid),
+                Change::Deletion { path, oid, .. } => ("D", Color::Red, path, oid),
+                Change::Modification { path, oid, .. } => ("M", Color::Blue, path, oid),
+            };
+            lines.push(CommitLine {
+                text: Line::from(vec![
+                    Span::styled(
+                        format!(" {status} "),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(path.to_str_lossy().into_owned()),
+                ]),
+                blob: Some((path, id)),
+            });
+        }
+        let cursor = Cursor {
+            selected: if first_change < lines.len() { first_change } else { 0 },
+            offset: 0,
+        };
+        Ok(Commit { id, lines, cursor })
+    }
+
+    pub(crate) fn open<'repo>(&self, repo: &'repo gix::Repository) -> Result<Option<View<'repo>>, Error> {
+        let Some((path, id)) = self.lines.get(self.cursor.selected).and_then(|line| line.blob.as_ref()) else {
+            return Ok(None);
+        };
+        let blob = repo.find_object(*id)?;
+        Ok(Some(View::Blob(Blob::new(
+            format!("{path} ({})", id.to_hex_with_len(7)),
+            &blob.data,
+        ))))
+    }
+}
+
+/// Return all changes to files between the first parent of `commit` and `commit` itself, or all files if there is no parent,
+/// sorted by path.
+fn changes(repo: &gix::Repository, commit: &gix::Commit<'_>) -> Result<Vec<Change>, Error> {
+    let tree = repo.find_object(commit.tree_id()?)?;
+    let parent_tree = match commit.parent_ids().next() {
+        Some(parent) => {
+            let parent = repo.find_object(parent)?.try_into_commit()?;
+            Some(repo.find_object(parent.tree_id()?)?)
+        }
+        None => None,
+    };
+    let mut recorder = gix::diff::tree::Recorder::default();
+    gix::diff::tree::Changes::from(
+        parent_tree
+            .as_ref()
+            .map(|tree| gix::objs::TreeRefIter::from_bytes(&tree.data, repo.object_hash())),
+    )
+    .needed_to_obtain(
+        gix::objs::TreeRefIter::from_bytes(&tree.data, repo.object_hash()),
+        gix::diff::tree::State::default(),
+        &repo.objects,
+        &mut recorder,
+    )?;
+    let mut changes: Vec<_> = recorder
+        .records
+        .into_iter()
+        .filter(|change| {
+            let mode = match change {
+                Change::Addition { entry_mode, .. }
+                | Change::Deletion { entry_mode, .. }
+                | Change::Modification { entry_mode, .. } => entry_mode,
+            };
+            !mode.is_tree()
+        })
+        .collect();
+    changes.sort_by(|a, b| path(a).cmp(path(b)));
+    Ok(changes)
+}
+
+fn path(change: &Change) -> &BString {
+    match change {
+        Change::Addition { path, .. } | Change::Deletion { path, .. } | Change::Modification { path, .. } => path,
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use gix::{bstr::ByteSlice, refs::Category, ObjectId};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::{
+    view::{fixed_width, short_time, Commit, Cursor, View},
+    Error,
+};
+
+/// The commits reachable from a commit, newest first.
+pub struct Log<'repo> {
+    pub(crate) tip: ObjectId,
+    pub(crate) title: String,
+    /// The traversal that is still to be continued, or `None` if all commits were seen.
+    pub(crate) walk: Option<gix::revision::Walk<'repo>>,
+    pub(crate) entries: Vec<Entry>,
+    pub(crate) cursor: Cursor,
+    decorations: HashMap<ObjectId, Vec<Decoration>>,
+}
+
+/// A single commit in the [`Log`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The id of the commit.
+    pub id: ObjectId,
+    /// The time at which the commit was authored.
+    pub time: String,
+    /// The name of the author.
+    pub author: String,
+    /// The first line of the commit message.
+    pub summary: String,
+    /// The references pointing to the commit.
+    pub decorations: Vec<Decoration>,
+}
+
+/// A reference that points to a commit in the [`Log`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoration {
+    /// The shortened name of the reference.
+    pub name: String,
+    /// The kind of reference.
+    pub kind: DecorationKind,
+}
+
+/// The kind of reference a [`Decoration`] refers to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DecorationKind {
+    /// `HEAD` if it is detached.
+    Head,
+    /// A branch in `refs/heads`.
+    LocalBranch,
+    /// A branch in `refs/remotes`.
+    RemoteBranch,
+    /// A tag in `refs/tags`.
+    Tag,
+}
+
+impl<'repo> Log<'repo> {
+    /// Create a log of all commits reachable from `tip`, displayed with `title`.
+    ///
+    /// No commit is traversed until the log is displayed.
+    pub fn new(repo: &'repo gix::Repository, tip: ObjectId, title: String) -> Result<Self, Error> {
+        let walk = repo
+            .rev_walk([tip])
+            .sorting(gix::traverse::commit::Sorting::ByCommitTimeNewestFirst)
+            .all()?;
+        Ok(Log {
+            tip,
+            title,
+            walk: Some(walk),
+            entries: Vec::new(),
+            cursor: Cursor::default(),
+            decorations: decorations(repo)?,
+        })
+    }
+
+    /// The commits that were traversed so far.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Traverse commits until there are at least `count` of them, or until all commits were seen.
+    pub fn load(&mut self, count: usize) -> Result<(), Error> {
+        while self.entries.len() < count {
+            let Some(walk) = self.walk.as_mut() else {
+                break;
+            };
+            let Some(info) = walk.next() else {
+                self.walk = None;
+                break;
+            };
+            let info = info?;
+            let commit = info.object()?;
+            let commit = commit.decode()?;
+            self.entries.push(Entry {
+                id: info.id,
+                time: short_time(commit.author.time),
+                author: commit.author.name.to_str_lossy().into_owned(),
+                summary: commit.message_summary().to_str_lossy().into_owned(),
+                decorations: self.decorations.get(&info.id).cloned().unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn open(&self, repo: &'repo gix::Repository) -> Result<Option<View<'repo>>, Error> {
+        self.entries
+            .get(self.cursor.selected)
+            .map(|entry| Commit::new(repo, entry.id).map(View::Commit))
+            .transpose()
+    }
+}
+
+impl Entry {
+    pub(crate) fn to_line(&self) -> Line<'_> {
+        let mut spans = vec![
+            Span::styled(format!("{} ", self.time), Style::default().fg(Color::Blue)),
+            Span::styled(
+                format!("{} ", fixed_width(&self.author, 16)),
+                Style::default().fg(Color::Green),
+            ),
+        ];
+        for decoration in &self.decorations {
+            let (open, close, color) = match decoration.kind {
+                DecorationKind::Head => ("[", "]", Color::Cyan),
+                DecorationKind::LocalBranch => ("[", "]", Color::Cyan),
+                DecorationKind::RemoteBranch => ("{", "}", Color::Red),
+                DecorationKind::Tag => ("<", ">", Color::Yellow),
+            };
+            spans.push(Span::styled(
+                format!("{open}{}{close}", decoration.name),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::raw(self.summary.as_str()));
+        Line::from(spans)
+    }
+}
+
+/// Collect the names of all branches and tags by the commit they point to.
+fn decorations(repo: &gix::Repository) -> Result<HashMap<ObjectId, Vec<Decoration>>, Error> {
+    let mut out = HashMap::<ObjectId, Vec<Decoration>>::new();
+    if let Ok(head) = repo.head() {
+        if head.is_detached() {
+            if let Some(id) = head.id() {
+                out.entry(id.detach()).or_default().push(Decoration {
+                    name: "HEAD".into(),
+                    kind: DecorationKind::Head,
+                });
+            }
+        }
+    }
+    for reference in repo.references()?.all()? {
+        let Ok(mut reference) = reference else {
+            continue;
+        };
+        let Some((category, short_name)) = reference.name().category_and_short_name() else {
+            continue;
+        };
+        let kind = match category {
+            Category::LocalBranch => DecorationKind::LocalBranch,
+            Category::RemoteBranch => DecorationKind::RemoteBranch,
+            Category::Tag => DecorationKind::Tag,
+            _ => continue,
+        };
+        let name = short_name.to_str_lossy().into_owned();
+        if kind == DecorationKind::RemoteBranch && name.ends_with("/HEAD") {
+            continue;
+        }
+        if let Ok(id) = reference.peel_to_id_in_place() {
+            out.entry(id.detach()).or_default().push(Decoration { name, kind });
+        }
+    }
+    for decorations in out.values_mut() {
+        decorations.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.name.cmp(&b.name)));
+    }
+    Ok(out)
+}
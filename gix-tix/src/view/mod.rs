@@ -0,0 +1,267 @@
+use std::ops::Range;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+};
+
+use crate::Error;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod blob;
+pub use blob::Blob;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod commit;
+pub use commit::Commit;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod log;
+pub use log::Log;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod refs;
+pub use refs::Refs;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod status;
+pub use status::Status;
+
+/// One of the views the [`App`](crate::App) can show.
+pub enum View<'repo> {
+    /// The commits reachable from a revision.
+    Log(Log<'repo>),
+    /// The details and changes of a single commit.
+    Commit(Commit),
+    /// The contents of a file.
+    Blob(Blob),
+    /// All references.
+    Refs(Refs),
+    /// The changes in the worktree.
+    Status(Status),
+}
+
+/// A way to move the cursor of a view.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Motion {
+    /// One line up.
+    Up,
+    /// One line down.
+    Down,
+    /// One screen up.
+    PageUp,
+    /// One screen down.
+    PageDown,
+    /// To the first line.
+    First,
+    /// To the last line.
+    Last,
+}
+
+/// The position of the cursor in a view, and the first line that is visible.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    /// The index of the line the cursor is on.
+    pub selected: usize,
+    /// The index of the first visible line.
+    pub offset: usize,
+}
+
+impl Cursor {
+    /// Move the cursor by `motion` within `len` lines, of which `height` lines are visible at a time, and keep it visible.
+    pub fn apply(&mut self, motion: Motion, len: usize, height: usize) {
+        let page = height.max(1);
+        self.selected = match motion {
+            Motion::Up => self.selected.saturating_sub(1),
+            Motion::Down => self.selected + 1,
+            Motion::PageUp => self.selected.saturating_sub(page),
+            Motion::PageDown => self.selected + page,
+            Motion::First => 0,
+            Motion::Last => usize::MAX,
+        }
+        .min(len.saturating_sub(1));
+        self.visible(len, height);
+    }
+
+    /// Adjust the offset so the cursor is visible in `height` lines and return the range of visible lines among `len` lines.
+    pub fn visible(&mut self, len: usize, height: usize) -> Range<usize> {
+        self.selected = self.selected.min(len.saturating_sub(1));
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if height > 0 && self.selected >= self.offset + height {
+            self.offset = self.selected + 1 - height;
+        }
+        self.offset = self.offset.min(len.saturating_sub(height));
+        self.offset..len.min(self.offset + height)
+    }
+}
+
+impl<'repo> View<'repo> {
+    /// The name of the view, as shown in the status bar.
+    pub fn name(&self) -> &'static str {
+        match self {
+            View::Log(_) => "log",
+            View::Commit(_) => "commit",
+            View::Blob(_) => "blob",
+            View::Refs(_) => "refs",
+            View::Status(_) => "status",
+        }
+    }
+
+    /// A description of what is shown, for display in the status bar.
+    pub fn title(&self) -> String {
+        match self {
+            View::Log(v) => v.title.clone(),
+            View::Commit(v) => v.id.to_string(),
+            View::Blob(v) => v.title.clone(),
+            View::Refs(_) => "references".into(),
+            View::Status(v) => v.title(),
+        }
+    }
+
+    /// The cursor of the view.
+    pub fn cursor(&self) -> &Cursor {
+        match self {
+            View::Log(v) => &v.cursor,
+            View::Commit(v) => &v.cursor,
+            View::Blob(v) => &v.cursor,
+            View::Refs(v) => &v.cursor,
+            View::Status(v) => &v.cursor,
+        }
+    }
+
+    /// Move the cursor of the view by `motion`, assuming `height` lines are visible.
+    pub fn apply(&mut self, motion: Motion, height: usize) -> Result<(), Error> {
+        if let View::Log(v) = self {
+            let wanted = match motion {
+                Motion::Last => usize::MAX,
+                _ => v.cursor.selected + height.max(1) + 1,
+            };
+            v.load(wanted)?;
+        }
+        let len = self.len();
+        self.cursor_mut().apply(motion, len, height);
+        Ok(())
+    }
+
+    /// Open the item under the cursor in a new view, or return `None` if there is nothing to open.
+    pub fn open(&self, repo: &'repo gix::Repository) -> Result<Option<View<'repo>>, Error> {
+        match self {
+            View::Log(v) => v.open(repo),
+            View::Commit(v) => v.open(repo),
+            View::Blob(_) => Ok(None),
+            View::Refs(v) => v.open(repo),
+            View::Status(v) => v.open(repo),
+        }
+    }
+
+    /// Recreate the view from the repository, keeping the cursor where possible.
+    pub fn reload(&mut self, repo: &'repo gix::Repository) -> Result<(), Error> {
+        let cursor = *self.cursor();
+        match self {
+            View::Log(v) => *v = Log::new(repo, v.tip, v.title.clone())?,
+            View::Commit(v) => *v = Commit::new(repo, v.id)?,
+            View::Blob(_) => {}
+            View::Refs(v) => *v = Refs::new(repo)?,
+            View::Status(v) => *v = Status::new(repo)?,
+        }
+        if let View::Log(v) = self {
+            v.load(cursor.selected + 1)?;
+        }
+        *self.cursor_mut() = cursor;
+        Ok(())
+    }
+
+    /// The amount of lines the view currently has.
+    pub fn len(&self) -> usize {
+        match self {
+            View::Log(v) => v.entries.len(),
+            View::Commit(v) => v.lines.len(),
+            View::Blob(v) => v.lines.len(),
+            View::Refs(v) => v.entries.len(),
+            View::Status(v) => v.entries.len(),
+        }
+    }
+
+    /// Return `true` if the view has no lines.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return `true` if all lines of the view are known, which is only `false` for logs that weren't traversed completely yet.
+    pub fn is_complete(&self) -> bool {
+        match self {
+            View::Log(v) => v.walk.is_none(),
+            _ => true,
+        }
+    }
+
+    /// Draw the view into `area` of `buf`, highlighting the line under the cursor.
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) -> Result<(), Error> {
+        let height = area.height as usize;
+        if let View::Log(v) = self {
+            v.load(v.cursor.offset.max(v.cursor.selected) + height + 1)?;
+        }
+        let len = self.len();
+        let visible = self.cursor_mut().visible(len, height);
+        let selected = self.cursor().selected;
+        for (row, idx) in visible.enumerate() {
+            let y = area.y + row as u16;
+            buf.set_line(area.x, y, &self.line(idx), area.width);
+            if idx == selected {
+                buf.set_style(
+                    Rect::new(area.x, y, area.width, 1),
+                    Style::default().add_modifier(Modifier::REVERSED),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn cursor_mut(&mut self) -> &mut Cursor {
+        match self {
+            View::Log(v) => &mut v.cursor,
+            View::Commit(v) => &mut v.cursor,
+            View::Blob(v) => &mut v.cursor,
+            View::Refs(v) => &mut v.cursor,
+            View::Status(v) => &mut v.cursor,
+        }
+    }
+
+    fn line(&self, idx: usize) -> Line<'_> {
+        match self {
+            View::Log(v) => v.entries[idx].to_line(),
+            View::Commit(v) => v.lines[idx].text.clone(),
+            View::Blob(v) => Line::raw(v.lines[idx].as_str()),
+            View::Refs(v) => v.entries[idx].to_line(),
+            View::Status(v) => v.entries[idx].to_line(),
+        }
+    }
+}
+
+/// Shorten `id` to the amount of hex characters usually displayed.
+fn short_id(id: &gix::oid) -> String {
+    id.to_hex_with_len(7).to_string()
+}
+
+/// Format `time` in a compact way, with minute precision.
+fn short_time(time: gix::date::Time) -> String {
+    let formatted = time.format(gix::date::time::format::ISO8601);
+    // `YYYY-MM-DD HH:MM:SS +ZZZZ` becomes `YYYY-MM-DD HH:MM`.
+    formatted.get(..16).map(ToOwned::to_owned).unwrap_or(formatted)
+}
+
+/// Return at most `width` characters of `text`, padded with spaces if it's shorter.
+fn fixed_width(text: &str, width: usize) -> String {
+    let mut out: String = text.chars().take(width).collect();
+    let len = out.chars().count();
+    out.extend(std::iter::repeat(' ').take(width - len));
+    out
+}
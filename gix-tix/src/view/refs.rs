@@ -0,0 +1,91 @@
+use gix::{bstr::ByteSlice, refs::Category, ObjectId};
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+use crate::{
+    view::{fixed_width, short_id, Cursor, Log, View},
+    Error,
+};
+
+/// All references of the repository, branches first, followed by remote branches, tags and everything else.
+pub struct Refs {
+    pub(crate) entries: Vec<Entry>,
+    pub(crate) cursor: Cursor,
+}
+
+/// A reference in the [`Refs`] view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The full name of the reference.
+    pub name: String,
+    /// The object the reference points to after peeling, or `None` if it couldn't be peeled.
+    pub id: Option<ObjectId>,
+    /// The summary of the commit the reference points to, or an empty string if it's not a commit.
+    pub summary: String,
+}
+
+impl Refs {
+    /// Create a view of all references in `repo`.
+    pub fn new(repo: &gix::Repository) -> Result<Self, Error> {
+        let mut entries = Vec::new();
+        for reference in repo.references()?.all()? {
+            let Ok(mut reference) = reference else {
+                continue;
+            };
+            let order = match reference.name().category() {
+                Some(Category::LocalBranch) => 0,
+                Some(Category::RemoteBranch) => 1,
+                Some(Category::Tag) => 2,
+                _ => 3,
+            };
+            let name = reference.name().as_bstr().to_str_lossy().into_owned();
+            let id = reference.peel_to_id_in_place().ok().map(gix::Id::detach);
+            let summary = id
+                .and_then(|id| repo.find_object(id).ok()?.try_into_commit().ok())
+                .and_then(|commit| Some(commit.message().ok()?.summary().to_str_lossy().into_owned()))
+                .unwrap_or_default();
+            entries.push((order, Entry { name, id, summary }));
+        }
+        entries.sort_by(|(a_order, a), (b_order, b)| a_order.cmp(b_order).then_with(|| a.name.cmp(&b.name)));
+        Ok(Refs {
+            entries: entries.into_iter().map(|(_, entry)| entry).collect(),
+            cursor: Cursor::default(),
+        })
+    }
+
+    /// All references.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub(crate) fn open<'repo>(&self, repo: &'repo gix::Repository) -> Result<Option<View<'repo>>, Error> {
+        let Some(Entry { name, id: Some(id), .. }) = self.entries.get(self.cursor.selected) else {
+            return Ok(None);
+        };
+        if !repo.find_object(*id)?.kind.is_commit() {
+            return Ok(None);
+        }
+        Ok(Some(View::Log(Log::new(repo, *id, name.clone())?)))
+    }
+}
+
+impl Entry {
+    pub(crate) fn to_line(&self) -> Line<'_> {
+        Line::from(vec![
+            Span::styled(
+                format!("{} ", fixed_width(&self.name, 32)),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::styled(
+                format!(
+                    "{} ",
+                    self.id.as_ref().map_or_else(|| "-------".into(), |id| short_id(id))
+                ),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::raw(self.summary.as_str()),
+        ])
+    }
+}
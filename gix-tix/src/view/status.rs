@@ -0,0 +1,123 @@
+use gix::{
+    bstr::{BString, ByteSlice},
+    status::index_worktree::iter::{Item, Summary},
+};
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+use crate::{
+    view::{Blob, Cursor, View},
+    Error,
+};
+
+/// The changes in the worktree compared to the index, including untracked files.
+pub struct Status {
+    pub(crate) workdir: std::path::PathBuf,
+    pub(crate) entries: Vec<Entry>,
+    pub(crate) cursor: Cursor,
+}
+
+/// A changed file in the [`Status`] view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// What happened to the file.
+    pub summary: Summary,
+    /// The path of the file in the worktree, relative to its root.
+    pub rela_path: BString,
+    /// The path the file was renamed or copied from, if the change is a rewrite.
+    pub source_rela_path: Option<BString>,
+}
+
+impl Status {
+    /// Create a view of all changes in the worktree of `repo`, which must not be bare.
+    pub fn new(repo: &gix::Repository) -> Result<Self, Error> {
+        let workdir = repo.work_dir().ok_or(Error::BareRepository)?.to_owned();
+        let mut entries = Vec::new();
+        for item in repo
+            .status(gix::progress::Discard)?
+            .into_index_worktree_iter(Vec::new())?
+        {
+            let item = item?;
+            let Some(summary) = item.summary() else {
+                continue;
+            };
+            let (rela_path, source_rela_path) = match item {
+                Item::Modification { rela_path, .. } => (rela_path, None),
+                Item::DirectoryContents { entry, .. } => (entry.rela_path, None),
+                Item::Rewrite {
+                    source, dirwalk_entry, ..
+                } => (dirwalk_entry.rela_path, Some(source.rela_path().to_owned())),
+            };
+            entries.push(Entry {
+                summary,
+                rela_path,
+                source_rela_path,
+            });
+        }
+        entries.sort_by(|a, b| a.rela_path.cmp(&b.rela_path));
+        Ok(Status {
+            workdir,
+            entries,
+            cursor: Cursor::default(),
+        })
+    }
+
+    /// All changed files, sorted by path.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub(crate) fn title(&self) -> String {
+        match self.entries.len() {
+            0 => "nothing to commit, working tree clean".into(),
+            1 => "1 changed file".into(),
+            n => format!("{n} changed files"),
+        }
+    }
+
+    pub(crate) fn open<'repo>(&self, _repo: &'repo gix::Repository) -> Result<Option<View<'repo>>, Error> {
+        let Some(entry) = self.entries.get(self.cursor.selected) else {
+            return Ok(None);
+        };
+        if entry.summary == Summary::Removed {
+            return Ok(None);
+        }
+        let path = self.workdir.join(gix::path::from_bstr(entry.rela_path.as_bstr()));
+        if path.is_dir() {
+            return Ok(None);
+        }
+        let data = std::fs::read(&path).map_err(|source| Error::ReadWorktreeFile {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(Some(View::Blob(Blob::new(
+            format!("{} (worktree)", entry.rela_path),
+            &data,
+        ))))
+    }
+}
+
+impl Entry {
+    pub(crate) fn to_line(&self) -> Line<'_> {
+        let (status, color) = match self.summary {
+            Summary::Added => ("?", Color::Magenta),
+            Summary::Removed => ("D", Color::Red),
+            Summary::Modified => ("M", Color::Blue),
+            Summary::TypeChange => ("T", Color::Blue),
+            Summary::Renamed => ("R", Color::Yellow),
+            Summary::Copied => ("C", Color::Yellow),
+            Summary::IntentToAdd => ("A", Color::Green),
+            Summary::Conflict => ("U", Color::Red),
+        };
+        let path = match &self.source_rela_path {
+            Some(source) => format!("{source} -> {}", self.rela_path),
+            None => self.rela_path.to_str_lossy().into_owned(),
+        };
+        Line::from(vec![
+            Span::styled(format!(" {status} "), Style::default().fg(color)),
+            Span::raw(path),
+        ])
+    }
+}
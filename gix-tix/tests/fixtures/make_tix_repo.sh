@@ -0,0 +1,40 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit_at() {
+  local day=$1; shift
+  GIT_AUTHOR_DATE="2000-01-$day 00:00:00 +0000" GIT_COMMITTER_DATE="2000-01-$day 00:00:00 +0000" git commit -q "$@"
+}
+
+git init -q
+git checkout -q -b main
+
+printf 'first line\n\tindented\n' > file
+printf 'a\0b' > binary
+mkdir dir
+echo content > dir/sub
+git add .
+commit_at 01 -m "initial" -m "with a body"
+
+echo "second line" >> file
+git rm -q binary
+commit_at 02 -am "change file and remove binary"
+git tag -a -m "annotated" v1
+
+git checkout -q -b feature
+echo new > new
+git add new
+commit_at 03 -m "add new file on feature"
+
+git checkout -q main
+for day in $(seq 10 28); do
+  echo "$day" > dir/sub
+  commit_at "$day" -am "commit $day"
+done
+
+echo "changed in worktree" >> file
+rm dir/sub
+echo untracked > untracked
+
+git clone -q --bare . bare.git
+echo bare.git >> .git/info/exclude
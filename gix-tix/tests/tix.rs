@@ -0,0 +1,227 @@
+use gix_tix::{
+    crossterm::event::{KeyCode, KeyEvent},
+    ratatui::{backend::TestBackend, Terminal},
+    view::View,
+    App, Control,
+};
+
+pub use gix_testtools::Result;
+
+fn repo() -> Result<gix::Repository> {
+    let dir = gix_testtools::scripted_fixture_read_only("make_tix_repo.sh")?;
+    Ok(gix::open_opts(dir, gix::open::Options::isolated())?)
+}
+
+/// Draw `app` into a terminal of the given size and return each line of the screen, without trailing whitespace.
+fn screen(app: &mut App<'_>, width: u16, height: u16) -> Result<Vec<String>> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|frame| app.draw(frame))?;
+    let buf = terminal.backend().buffer();
+    Ok((0..buf.area.height)
+        .map(|y| {
+            (0..buf.area.width)
+                .map(|x| buf.get(x, y).symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_owned()
+        })
+        .collect())
+}
+
+fn press(app: &mut App<'_>, keys: &str) -> Control {
+    let mut control = Control::Continue;
+    for key in keys.chars() {
+        let code = match key {
+            '\n' => KeyCode::Enter,
+            other => KeyCode::Char(other),
+        };
+        control = app.handle_key(KeyEvent::from(code));
+        assert_eq!(app.message(), None, "key {key:?} is handled without error");
+    }
+    control
+}
+
+#[test]
+fn log_is_loaded_lazily_and_shows_decorations() -> Result {
+    let repo = repo()?;
+    let mut app = App::new(&repo, None)?;
+    let lines = screen(&mut app, 80, 10)?;
+    assert_eq!(lines.len(), 10);
+    assert_eq!(lines[0], "2000-01-28 00:00 author           [main] commit 28");
+    assert_eq!(lines[1], "2000-01-27 00:00 author           commit 27");
+    assert_eq!(lines[9], "[log] HEAD - line 1 of 10+  10%");
+    assert!(
+        !app.view().is_complete(),
+        "only what's needed for display was traversed"
+    );
+
+    press(&mut app, "G");
+    let lines = screen(&mut app, 80, 10)?;
+    assert_eq!(
+        lines[7],
+        "2000-01-02 00:00 author           <v1> change file and remove binary"
+    );
+    assert_eq!(lines[8], "2000-01-01 00:00 author           initial");
+    assert_eq!(lines[9], "[log] HEAD - line 21 of 21  100%");
+    assert!(app.view().is_complete());
+
+    press(&mut app, "gjj");
+    let lines = screen(&mut app, 80, 10)?;
+    assert_eq!(lines[0], "2000-01-28 00:00 author           [main] commit 28");
+    assert_eq!(lines[9], "[log] HEAD - line 3 of 21  14%");
+    Ok(())
+}
+
+#[test]
+fn commit_and_blob_views() -> Result {
+    let repo = repo()?;
+    let mut app = App::new(&repo, Some("v1".into()))?;
+    screen(&mut app, 80, 16)?;
+    press(&mut app, "\n");
+    assert!(matches!(app.view(), View::Commit(_)));
+    let lines = screen(&mut app, 80, 16)?;
+    let id = repo.rev_parse_single("v1^{commit}")?;
+    let parent = id.object()?.into_commit().parent_ids().next().expect("one parent");
+    assert_eq!(
+        &lines[..12],
+        [
+            format!("commit      {id}"),
+            format!("parent      {parent}"),
+            "Author:     author <author@example.com>".into(),
+            "AuthorDate: 2000-01-02 00:00:00 +0000".into(),
+            "Commit:     committer <committer@example.com>".into(),
+            "CommitDate: 2000-01-02 00:00:00 +0000".into(),
+            "".into(),
+            "    change file and remove binary".into(),
+            "".into(),
+            " D binary".into(),
+            " M file".into(),
+            "".into(),
+        ]
+    );
+    assert_eq!(
+        lines[15],
+        format!("[commit] {id} - line 10 of 11  90%"),
+        "the cursor starts on the first change"
+    );
+
+    press(&mut app, "\n");
+    let lines = screen(&mut app, 80, 4)?;
+    assert_eq!(lines[0], "Binary file, 3 bytes", "binary files aren't displayed");
+
+    press(&mut app, "qj\n");
+    let lines = screen(&mut app, 80, 4)?;
+    assert_eq!(lines[..3], ["first line", "        indented", "second line"]);
+    assert!(lines[3].starts_with("[blob] file ("), "{}", lines[3]);
+
+    press(&mut app, "qq");
+    assert!(matches!(app.view(), View::Log(_)), "closing views returns to the log");
+    assert_eq!(press(&mut app, "q"), Control::Quit, "closing the last view quits");
+    Ok(())
+}
+
+#[test]
+fn root_commit_shows_all_files_as_added() -> Result {
+    let repo = repo()?;
+    let mut app = App::new(&repo, Some("main".into()))?;
+    press(&mut app, "G\n");
+    let lines = screen(&mut app, 80, 16)?;
+    assert_eq!(
+        lines[6..13],
+        [
+            "    initial",
+            "",
+            "    with a body",
+            "",
+            " A binary",
+            " A dir/sub",
+            " A file"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn refs_view_opens_logs() -> Result {
+    let repo = repo()?;
+    let mut app = App::new(&repo, None)?;
+    press(&mut app, "r");
+    let lines = screen(&mut app, 80, 5)?;
+    let short = |rev: &str| -> Result<String> { Ok(repo.rev_parse_single(rev)?.shorten()?.to_string()) };
+    assert_eq!(
+        lines[0],
+        format!(
+            "refs/heads/feature               {} add new file on feature",
+            short("feature")?
+        )
+    );
+    assert_eq!(
+        lines[1],
+        format!("refs/heads/main                  {} commit 28", short("main")?)
+    );
+    assert_eq!(
+        lines[2],
+        format!(
+            "refs/tags/v1                     {} change file and remove binary",
+            short("v1^{commit}")?
+        ),
+        "annotated tags are peeled"
+    );
+    assert_eq!(lines[4], "[refs] references - line 1 of 3  33%");
+
+    press(&mut app, "\n");
+    let lines = screen(&mut app, 80, 5)?;
+    assert_eq!(
+        lines[0],
+        "2000-01-03 00:00 author           [feature] add new file on feature"
+    );
+    assert_eq!(
+        lines[1],
+        "2000-01-02 00:00 author           <v1> change file and remove binary"
+    );
+    assert_eq!(lines[4], "[log] refs/heads/feature - line 1 of 3  33%");
+
+    press(&mut app, "m");
+    assert_eq!(app.views().len(), 1, "the main view is always the bottom-most one");
+    Ok(())
+}
+
+#[test]
+fn status_view_shows_worktree_changes() -> Result {
+    let repo = repo()?;
+    let mut app = App::new(&repo, None)?;
+    press(&mut app, "s");
+    let lines = screen(&mut app, 80, 5)?;
+    assert_eq!(lines[..3], [" D dir/sub", " M file", " ? untracked"]);
+    assert_eq!(lines[4], "[status] 3 changed files - line 1 of 3  33%");
+
+    press(&mut app, "\n");
+    assert!(matches!(app.view(), View::Status(_)), "deleted files can't be opened");
+    press(&mut app, "j\n");
+    let lines = screen(&mut app, 80, 5)?;
+    assert_eq!(lines[..3], ["first line", "        indented", "second line"]);
+    assert_eq!(lines[3], "changed in worktree");
+    assert_eq!(lines[4], "[blob] file (worktree) - line 1 of 4  25%");
+    Ok(())
+}
+
+#[test]
+fn errors_are_shown_in_the_status_bar() -> Result {
+    let repo = repo()?;
+    assert!(App::new(&repo, Some("does-not-exist".into())).is_err());
+
+    let bare = gix::open_opts(
+        repo.work_dir().expect("non-bare").join("bare.git"),
+        gix::open::Options::isolated(),
+    )?;
+    let mut app = App::new(&bare, None)?;
+    assert_eq!(app.handle_key(KeyEvent::from(KeyCode::Char('s'))), Control::Continue);
+    assert_eq!(app.message(), Some("Cannot show the status of a bare repository"));
+    let lines = screen(&mut app, 60, 3)?;
+    assert_eq!(lines[2], "Cannot show the status of a bare repository");
+
+    press(&mut app, "j");
+    let lines = screen(&mut app, 60, 3)?;
+    assert!(lines[2].starts_with("[log] HEAD - line 2 of"), "{}", lines[2]);
+    Ok(())
+}
@@ -0,0 +1,305 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::HashSet,
+    error::Error,
+    io::{BufReader, Read, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use bstr::{BStr, ByteSlice, ByteVec};
+use gix_packetline::{PacketLineRef, StreamingPeekableIter};
+
+use crate::{
+    client::{self, capabilities, Capabilities, MessageKind, RequestWriter, SetServiceResponse, WriteMode},
+    Service,
+};
+
+/// The largest amount of pack data to send in a single side-band line, as the channel takes one byte.
+const MAX_PACK_DATA_PER_LINE: usize = 65515;
+
+/// A transport which serves the references and the pack of a bundle file like a `git upload-pack` process would,
+/// so bundles can be fetched from and cloned without a network connection.
+///
+/// It speaks protocol V1 and acknowledges the prerequisites and references of the bundle as common objects,
+/// but sends the complete pack of the bundle no matter what was requested.
+/// Note that it's up to the caller to assure all [prerequisites](gix_bundle::Header::prerequisites) are present
+/// in the receiving repository.
+///
+/// It can only be instantiated using [`connect()`].
+pub struct Connection {
+    url: gix_url::Url,
+    bundle: gix_bundle::File,
+    request: Arc<Mutex<Vec<u8>>>,
+    line_provider: Option<StreamingPeekableIter<Responder>>,
+    trace: bool,
+}
+
+impl Connection {
+    /// Return the bundle this transport serves.
+    pub fn bundle(&self) -> &gix_bundle::File {
+        &self.bundle
+    }
+}
+
+impl client::TransportWithoutIO for Connection {
+    fn request(
+        &mut self,
+        write_mode: WriteMode,
+        on_into_read: MessageKind,
+        trace: bool,
+    ) -> Result<RequestWriter<'_>, client::Error> {
+        Ok(RequestWriter::new_from_bufread(
+            RecordRequest(self.request.clone()),
+            Box::new(
+                self.line_provider
+                    .as_mut()
+                    .expect("handshake() to have been called first")
+                    .as_read_without_sidebands(),
+            ),
+            write_mode,
+            on_into_read,
+            trace,
+        ))
+    }
+
+    fn to_url(&self) -> Cow<'_, BStr> {
+        Cow::Owned(self.url.to_bstring())
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        true
+    }
+
+    fn configure(&mut self, _config: &dyn Any) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        Ok(())
+    }
+}
+
+impl client::Transport for Connection {
+    fn handshake<'a>(
+        &mut self,
+        service: Service,
+        _extra_parameters: &'a [(&'a str, Option<&'a str>)],
+    ) -> Result<SetServiceResponse<'_>, client::Error> {
+        if service != Service::UploadPack {
+            return Err(client::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!(
+                    "Bundles can only be fetched from, but {} was requested",
+                    service.as_str()
+                ),
+            )));
+        }
+        let mut responder = Responder {
+            pack_source: self.bundle.clone(),
+            request: self.request.clone(),
+            known: HashSet::new(),
+            last_common: None,
+            out: Vec::new(),
+            out_pos: 0,
+            pack: None,
+        };
+        responder.advertise_refs()?;
+        let line_provider = self.line_provider.insert(StreamingPeekableIter::new(
+            responder,
+            &[PacketLineRef::Flush],
+            self.trace,
+        ));
+        let capabilities::recv::Outcome {
+            capabilities,
+            refs,
+            protocol: actual_protocol,
+        } = Capabilities::from_lines_with_version_detection(line_provider)?;
+        Ok(SetServiceResponse {
+            actual_protocol,
+            capabilities,
+            refs,
+        })
+    }
+}
+
+/// A writer which keeps what the client sends so the [`Responder`] can answer it.
+struct RecordRequest(Arc<Mutex<Vec<u8>>>);
+
+impl Write for RecordRequest {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().expect("not poisoned").extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The server side which produces packet lines in response to the recorded requests.
+struct Responder {
+    pack_source: gix_bundle::File,
+    request: Arc<Mutex<Vec<u8>>>,
+    /// The hexadecimal ids of all objects the bundle knows, which are acknowledged if the client has them.
+    known: HashSet<Vec<u8>>,
+    last_common: Option<Vec<u8>>,
+    out: Vec<u8>,
+    out_pos: usize,
+    pack: Option<BufReader<std::fs::File>>,
+}
+
+impl Responder {
+    fn advertise_refs(&mut self) -> std::io::Result<()> {
+        let header = &self.pack_source.header;
+        let head = header.refs.iter().find(|r| r.name == "HEAD");
+        let mut capabilities = format!(
+            "multi_ack_detailed side-band-64k thin-pack ofs-delta include-tag object-format={}",
+            match header.object_hash {
+                gix_hash::Kind::Sha1 => "sha1",
+                gix_hash::Kind::Sha256 => "sha256",
+            }
+        );
+        if let Some(branch) = head.and_then(|head| {
+            header
+                .refs
+                .iter()
+                .find(|r| r.id == head.id && r.name.starts_with(b"refs/heads/"))
+        }) {
+            capabilities.push_str(" symref=HEAD:");
+            capabilities.push_str(&branch.name.to_str_lossy());
+        }
+
+        let refs: Vec<_> = head
+            .into_iter()
+            .chain(header.refs.iter().filter(|r| r.name != "HEAD"))
+            .map(|r| (r.id.to_string(), r.name.as_bstr()))
+            .collect();
+        let null = header.object_hash.null().to_string();
+        let mut lines = refs.iter().map(|(hex, name)| (hex.as_str(), *name));
+        let (first_hex, first_name) = lines.next().unwrap_or((null.as_str(), "capabilities^{}".into()));
+        let mut line = format!("{first_hex} ").into_bytes();
+        line.extend_from_slice(first_name);
+        line.push(0);
+        line.extend_from_slice(capabilities.as_bytes());
+        gix_packetline::encode::text_to_write(&line, &mut self.out)?;
+        for (hex, name) in lines {
+            let mut line = format!("{hex} ").into_bytes();
+            line.extend_from_slice(name);
+            gix_packetline::encode::text_to_write(&line, &mut self.out)?;
+        }
+        gix_packetline::encode::flush_to_write(&mut self.out)?;
+
+        self.known = header
+            .refs
+            .iter()
+            .map(|r| r.id)
+            .chain(header.prerequisites.iter().map(|p| p.id))
+            .map(|id| id.to_string().into_bytes())
+            .collect();
+        Ok(())
+    }
+
+    /// Answer the negotiation `request` of the client like `git upload-pack` would with `multi_ack_detailed`, and
+    /// start sending the pack once the client is done.
+    fn respond(&mut self, request: &[u8]) -> std::io::Result<()> {
+        let mut is_negotiation = false;
+        let mut is_done = false;
+        let mut lines = gix_packetline::StreamingPeekableIter::new(request, &[], false);
+        while let Some(line) = lines.read_line() {
+            let line = match line {
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                line => line?.map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?,
+            };
+            let Some(line) = line.as_bstr() else {
+                continue;
+            };
+            let line = line.trim_end();
+            if let Some(hex) = line.strip_prefix(b"have ") {
+                is_negotiation = true;
+                if self.known.contains(hex) {
+                    let mut ack = b"ACK ".to_vec();
+                    ack.extend_from_slice(hex);
+                    ack.extend_from_slice(b" common");
+                    gix_packetline::encode::text_to_write(&ack, &mut self.out)?;
+                    self.last_common = Some(hex.to_owned());
+                }
+            } else if line.starts_with(b"want ") {
+                is_negotiation = true;
+            } else if line == b"done" {
+                is_done = true;
+            }
+        }
+
+        if is_done {
+            match &self.last_common {
+                Some(hex) => {
+                    let mut ack = b"ACK ".to_vec();
+                    ack.extend_from_slice(hex);
+                    gix_packetline::encode::text_to_write(&ack, &mut self.out)?
+                }
+                None => gix_packetline::encode::text_to_write(b"NAK", &mut self.out)?,
+            };
+            self.pack = Some(self.pack_source.pack_data()?);
+        } else if is_negotiation {
+            gix_packetline::encode::text_to_write(b"NAK", &mut self.out)?;
+        }
+        Ok(())
+    }
+}
+
+impl Read for Responder {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            if self.out_pos < self.out.len() {
+                let n = (self.out.len() - self.out_pos).min(buf.len());
+                buf[..n].copy_from_slice(&self.out[self.out_pos..][..n]);
+                self.out_pos += n;
+                return Ok(n);
+            }
+            self.out.clear();
+            self.out_pos = 0;
+
+            if let Some(pack) = self.pack.as_mut() {
+                let mut chunk = vec![0; MAX_PACK_DATA_PER_LINE];
+                let n = pack.read(&mut chunk)?;
+                if n == 0 {
+                    self.pack = None;
+                    gix_packetline::encode::flush_to_write(&mut self.out)?;
+                } else {
+                    gix_packetline::encode::band_to_write(gix_packetline::Channel::Data, &chunk[..n], &mut self.out)?;
+                }
+                continue;
+            }
+
+            let request = std::mem::take(&mut *self.request.lock().expect("not poisoned"));
+            if request.is_empty() {
+                return Ok(0);
+            }
+            self.respond(&request)?;
+        }
+    }
+}
+
+/// Connect to the bundle at `path`, which is opened right away to read its header.
+/// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+///
+/// Use [`gix_bundle::is_bundle()`] to learn if a local path should be connected to with this transport instead of
+/// with [`file::connect()`](crate::client::file::connect()).
+pub fn connect(path: impl Into<PathBuf>, trace: bool) -> Result<Connection, gix_bundle::file::Error> {
+    let bundle = gix_bundle::File::at(path)?;
+    let url = gix_url::Url::from_parts(
+        gix_url::Scheme::File,
+        None,
+        None,
+        None,
+        None,
+        Vec::from_path_lossy(&bundle.path).into_owned().into(),
+        true,
+    )
+    .expect("valid url");
+    Ok(Connection {
+        url,
+        bundle,
+        request: Default::default(),
+        line_provider: None,
+        trace,
+    })
+}
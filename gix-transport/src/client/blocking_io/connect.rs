@@ -27,10 +27,17 @@ pub(crate) mod function {
                         scheme: url.scheme,
                     });
                 }
-                Box::new(
-                    crate::client::blocking_io::file::connect(url.path, options.version, options.trace)
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
-                )
+                let path = gix_url::expand_path(None, url.path.as_ref()).ok();
+                match path.filter(|path| gix_bundle::is_bundle(path)) {
+                    Some(path) => Box::new(
+                        crate::client::blocking_io::bundle::connect(path, options.trace)
+                            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
+                    ),
+                    None => Box::new(
+                        crate::client::blocking_io::file::connect(url.path, options.version, options.trace)
+                            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
+                    ),
+                }
             }
             gix_url::Scheme::Ssh => Box::new({
                 crate::client::blocking_io::ssh::connect(url, options.version, options.ssh, options.trace)
@@ -1,3 +1,7 @@
+///
+#[allow(clippy::empty_docs)]
+pub mod bundle;
+
 ///
 #[allow(clippy::empty_docs)]
 pub mod connect;
@@ -1,6 +1,20 @@
 #![deny(rust_2018_idioms)]
 #![forbid(unsafe_code)]
 
-fn main() {
-    unimplemented!();
+use gix::bstr::ByteVec;
+
+/// Browse the history of the repository in the current directory, starting at the revision given as only argument, or `HEAD`.
+fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut args = std::env::args_os().skip(1);
+    let rev = args
+        .next()
+        .map(Vec::from_os_string)
+        .transpose()
+        .map_err(|_| "The revision must be valid UTF-8")?;
+    if args.next().is_some() {
+        return Err("Usage: gixi [<revision>]".into());
+    }
+    let repo = gix::discover(".")?;
+    gix_tix::run(&repo, rev.as_ref().map(|rev| rev.as_slice().into()))?;
+    Ok(())
 }
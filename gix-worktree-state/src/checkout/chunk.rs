@@ -85,6 +85,8 @@ pub struct Options {
     pub overwrite_existing: bool,
     pub keep_going: bool,
     pub filter_process_delay: gix_filter::driver::apply::Delay,
+    /// If `true`, only entries flagged with [`UPDATE`](gix_index::entry::Flags::UPDATE) are checked out.
+    pub only_entries_to_update: bool,
 }
 
 impl From<&checkout::Options> for Options {
@@ -95,6 +97,7 @@ impl From<&checkout::Options> for Options {
             overwrite_existing: opts.overwrite_existing,
             keep_going: opts.keep_going,
             filter_process_delay: opts.filter_process_delay,
+            only_entries_to_update: false,
         }
     }
 }
@@ -116,6 +119,9 @@ where
     let mut files_in_chunk = 0;
 
     for (entry, entry_path) in entries_with_paths {
+        if ctx.options.only_entries_to_update && !entry.flags.contains(gix_index::entry::Flags::UPDATE) {
+            continue;
+        }
         // TODO: write test for that
         if entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE) {
             files.fetch_add(1, Ordering::Relaxed);
@@ -29,13 +29,25 @@ where
     Find: gix_object::Find + Send + Clone,
 {
     let paths = index.take_path_backing();
-    let res = checkout_inner(index, &paths, dir, objects, files, bytes, should_interrupt, options);
+    let res = checkout_inner(
+        index,
+        &paths,
+        dir,
+        objects,
+        files,
+        bytes,
+        should_interrupt,
+        options,
+        false,
+    );
     index.return_path_backing(paths);
     res
 }
 
+/// Like [`checkout()`], but if `only_entries_to_update` is `true`, only entries flagged with
+/// [`UPDATE`](gix_index::entry::Flags::UPDATE) will be written.
 #[allow(clippy::too_many_arguments)]
-fn checkout_inner<Find>(
+pub(crate) fn checkout_inner<Find>(
     index: &mut gix_index::State,
     paths: &gix_index::PathStorage,
     dir: impl Into<std::path::PathBuf>,
@@ -44,10 +56,14 @@ fn checkout_inner<Find>(
     bytes: &dyn gix_features::progress::Count,
     should_interrupt: &AtomicBool,
     mut options: crate::checkout::Options,
+    only_entries_to_update: bool,
 ) -> Result<crate::checkout::Outcome, crate::checkout::Error>
 where
     Find: gix_object::Find + Send + Clone,
 {
+    if let Some(patterns) = options.sparse_checkout.take() {
+        mark_excluded_entries(index, paths, &patterns);
+    }
     let num_files = files.counter();
     let num_bytes = bytes.counter();
     let dir = dir.into();
@@ -60,7 +76,10 @@ where
 
     let mut ctx = chunk::Context {
         buf: Vec::new(),
-        options: (&options).into(),
+        options: chunk::Options {
+            only_entries_to_update,
+            ..(&options).into()
+        },
         path_cache: Stack::from_state_and_ignore_case(
             dir,
             options.fs.ignore_case,
@@ -148,3 +167,20 @@ where
         delayed_paths_unprocessed,
     })
 }
+
+/// Flag all entries that aren't included by `patterns` so they are skipped during checkout. Flags that are already set are kept.
+fn mark_excluded_entries(
+    index: &mut gix_index::State,
+    paths: &gix_index::PathStorage,
+    patterns: &gix_worktree::sparse::Patterns,
+) {
+    use gix_index::entry::Flags;
+    for (entry, path) in index.entries_mut_with_paths_in(paths) {
+        if entry.mode.is_sparse() || entry.stage() != 0 {
+            continue;
+        }
+        if !patterns.is_included(path, entry.mode.is_submodule()) {
+            entry.flags.insert(Flags::SKIP_WORKTREE | Flags::EXTENDED);
+        }
+    }
+}
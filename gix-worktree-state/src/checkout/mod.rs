@@ -66,6 +66,10 @@ pub struct Options {
     pub filters: gix_filter::Pipeline,
     /// Control how long-running processes may use the 'delay' capability.
     pub filter_process_delay: gix_filter::driver::apply::Delay,
+    /// If set, entries that aren't included by these patterns are marked with the
+    /// [`SKIP_WORKTREE`](gix_index::entry::Flags::SKIP_WORKTREE) flag and are not written to the worktree,
+    /// similar to what happens if `core.sparseCheckout` is enabled.
+    pub sparse_checkout: Option<gix_worktree::sparse::Patterns>,
 }
 
 /// The error returned by the [checkout()][crate::checkout()] function.
@@ -5,3 +5,12 @@
 #[allow(clippy::empty_docs)]
 pub mod checkout;
 pub use checkout::function::checkout;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod switch;
+pub use switch::function::switch;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod sparse;
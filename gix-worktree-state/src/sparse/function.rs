@@ -0,0 +1,103 @@
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_index::entry::Flags;
+
+use crate::{
+    sparse::{Error, Outcome},
+    switch::function::{remove_file, Worktree},
+};
+
+/// Change the worktree at `dir` along with its `index` so that only the entries included by `patterns` are present,
+/// similar to what `git sparse-checkout reapply` would do. Resolve objects with `objects`, count each checked out file
+/// with `files` and the amount of written bytes with `bytes`. If `should_interrupt` is `true`, the operation will abort.
+///
+/// A sparse `index` is expanded first, and the resulting index won't be sparse. Entries that aren't included anymore are
+/// marked as `skip-worktree` and their files are removed, unless they have local modifications, in which case they
+/// are kept as they are. Entries that are included now are checked out according to `options`.
+///
+/// ### Handling the return value
+///
+/// The worktree may have been changed partially if an error is returned, while the `index` already reflects the new state.
+/// Note that [`Error::Interrupted`] may be returned after files were removed.
+#[allow(clippy::too_many_arguments)]
+pub fn update<Find>(
+    index: &mut gix_index::State,
+    patterns: &gix_worktree::sparse::Patterns,
+    dir: impl Into<PathBuf>,
+    objects: Find,
+    files: &dyn gix_features::progress::Count,
+    bytes: &dyn gix_features::progress::Count,
+    should_interrupt: &AtomicBool,
+    mut options: crate::checkout::Options,
+) -> Result<Outcome, Error>
+where
+    Find: gix_object::Find + Send + Clone,
+{
+    let dir = dir.into();
+    if index.is_sparse() {
+        index
+            .expand_sparse_directories(objects.clone())
+            .map_err(Error::ExpandSparseIndex)?;
+    }
+    let changes = gix_worktree::sparse::apply(patterns, index);
+
+    let mut files_removed = 0;
+    let mut modified_paths_kept = Vec::new();
+    {
+        let mut worktree = Worktree::new(&dir, index, &objects, &options);
+        for idx in changes.skipped.iter().copied() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            let entry = &index.entries()[idx];
+            let path = entry.path(index);
+            if worktree.differs(entry, path, true)? {
+                modified_paths_kept.push(path.to_owned());
+            } else if remove_file(&dir, path)? {
+                files_removed += 1;
+            }
+        }
+    }
+    for path in &modified_paths_kept {
+        let entry = index.entry_mut_by_path_and_stage(path.as_ref(), 0).expect("present");
+        entry.flags.remove(Flags::SKIP_WORKTREE);
+        if !entry.flags.contains(Flags::INTENT_TO_ADD) {
+            entry.flags.remove(Flags::EXTENDED);
+        }
+    }
+
+    for idx in changes.included.iter().copied() {
+        index.entries_mut()[idx].flags.insert(Flags::UPDATE);
+    }
+    options.destination_is_initially_empty = false;
+    options.sparse_checkout = None;
+    let paths = index.take_path_backing();
+    let checkout = crate::checkout::function::checkout_inner(
+        index,
+        &paths,
+        dir,
+        objects,
+        files,
+        bytes,
+        should_interrupt,
+        options,
+        true,
+    );
+    index.return_path_backing(paths);
+    for idx in changes.included {
+        index.entries_mut()[idx].flags.remove(Flags::UPDATE);
+    }
+    let checkout = checkout.map_err(Box::new)?;
+    if should_interrupt.load(Ordering::Relaxed) {
+        return Err(Error::Interrupted);
+    }
+
+    Ok(Outcome {
+        checkout,
+        files_removed,
+        modified_paths_kept,
+    })
+}
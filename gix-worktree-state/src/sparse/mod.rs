@@ -0,0 +1,30 @@
+use bstr::BString;
+
+/// The outcome of [updating](crate::sparse::update()) the worktree to match new sparse checkout patterns.
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// The outcome of checking out all entries that are included now, but weren't before.
+    pub checkout: crate::checkout::Outcome,
+    /// The amount of files that were removed from the worktree as they are not included anymore.
+    pub files_removed: usize,
+    /// Paths that aren't included anymore, but whose files were kept as they have local modifications.
+    /// Their entries are not marked as `skip-worktree`.
+    pub modified_paths_kept: Vec<BString>,
+}
+
+/// The error returned by the [update()](crate::sparse::update()) function.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not expand the sparse directories of the index")]
+    ExpandSparseIndex(#[source] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    Worktree(#[from] crate::switch::Error),
+    #[error(transparent)]
+    Checkout(#[from] Box<crate::checkout::Error>),
+    #[error("The operation was interrupted")]
+    Interrupted,
+}
+
+pub(crate) mod function;
+pub use function::update;
@@ -0,0 +1,542 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    io::Read,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_filter::pipeline::convert::ToGitOutcome;
+use gix_index::{
+    entry::{Flags, Mode, Stat},
+    Entry,
+};
+use gix_object::FindExt;
+use gix_worktree::{stack, Stack};
+
+use crate::switch::{Error, Outcome};
+
+/// Change the worktree at `dir` along with its `index` from `current_tree` to `target_tree`, similar to what `git switch`
+/// or `git checkout <commit>` would do. Resolve objects with `objects`, count each checked out file with `files` and the amount
+/// of written bytes with `bytes`. If `should_interrupt` is `true`, the operation will abort.
+///
+/// Only paths whose entries differ between `current_tree` and `target_tree` are touched. Files that aren't part of `target_tree`
+/// are removed along with directories that are empty then, and all added or changed entries are checked out. All other index
+/// entries are kept, which includes their staged changes and the changes made to their files in the worktree.
+///
+/// Paths to be touched must not have staged changes, their files must match their index entries, and no untracked
+/// files may be in the way of new entries. Otherwise [`Error::WouldOverwrite`] is returned before anything is changed.
+/// The same is true for unmerged entries, which cause [`Error::Unmerged`] to be returned. If `options.overwrite_existing`
+/// is `true`, all of these checks are skipped and local changes are discarded instead, similar to `git checkout --force`.
+///
+/// Entries flagged as `skip-worktree` keep the flag and are neither written nor removed. If `options.sparse_checkout`
+/// is set, it decides which of the added or changed entries are flagged as `skip-worktree` instead, and files of entries
+/// that are skipped now are removed. A sparse `index` is expanded first, and its sparse directories are collapsed again
+/// afterwards if they still only contain `skip-worktree` entries whose trees are present in `objects`.
+///
+/// On success, the entries of `index` are replaced with those of `target_tree` and all entries which were kept, and it can
+/// be written to disk. Its extensions are kept, with the cached trees and untracked files of all changed paths being invalidated.
+///
+/// ### Handling the return value
+///
+/// The worktree may have been changed partially if [`Error::Interrupted`] or [`Error::Checkout`] are returned,
+/// in which case `index` is left untouched.
+#[allow(clippy::too_many_arguments)]
+pub fn switch<Find>(
+    index: &mut gix_index::State,
+    current_tree: &gix_hash::oid,
+    target_tree: &gix_hash::oid,
+    dir: impl Into<PathBuf>,
+    objects: Find,
+    files: &dyn gix_features::progress::Count,
+    bytes: &dyn gix_features::progress::Count,
+    should_interrupt: &AtomicBool,
+    mut options: crate::checkout::Options,
+) -> Result<Outcome, Error>
+where
+    Find: gix_object::Find + Send + Clone,
+{
+    let dir = dir.into();
+    let force = options.overwrite_existing;
+    let index_from_tree = |id: &gix_hash::oid| {
+        if id.to_owned().is_empty_tree() {
+            return Ok(gix_index::State::new(id.kind()));
+        }
+        gix_index::State::from_tree(id, objects.clone()).map_err(|source| Error::IndexFromTree {
+            id: id.to_owned(),
+            source,
+        })
+    };
+    let sparse_dirs: HashSet<BString> = index
+        .entries()
+        .iter()
+        .filter(|entry| entry.mode.is_sparse())
+        .map(|entry| {
+            let path = entry.path(index);
+            path.strip_suffix(b"/").unwrap_or(path).into()
+        })
+        .collect();
+    if index.is_sparse() {
+        index
+            .expand_sparse_directories(objects.clone())
+            .map_err(Error::ExpandSparseIndex)?;
+    }
+    let sparse_checkout = options.sparse_checkout.take();
+    let current = index_from_tree(current_tree)?;
+    let target = index_from_tree(target_tree)?;
+
+    let (mut result, to_remove, changed_paths, entries_kept) = {
+        let previous: &gix_index::State = index;
+        let mut slots = BTreeMap::<&BStr, Slot<'_>>::new();
+        for entry in current.entries() {
+            slots.entry(entry.path(&current)).or_default().head = Some(entry);
+        }
+        for entry in previous.entries() {
+            let slot = slots.entry(entry.path(previous)).or_default();
+            if entry.stage() == 0 {
+                slot.index = Some(entry);
+            } else {
+                slot.unmerged = true;
+            }
+        }
+        for entry in target.entries() {
+            slots.entry(entry.path(&target)).or_default().target = Some(entry);
+        }
+
+        let mut worktree = Worktree::new(&dir, previous, &objects, &options);
+        let mut result = gix_index::State::new(previous.object_hash());
+        let mut to_remove = BTreeSet::<BString>::new();
+        let mut new_paths = Vec::new();
+        let mut unmerged = Vec::new();
+        let mut would_overwrite = Vec::new();
+        let mut entries_kept = 0;
+        for (path, slot) in slots {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            if slot.unmerged && !force {
+                unmerged.push(path.to_owned());
+                continue;
+            }
+            let index_entry = slot.index.filter(|_| !slot.unmerged);
+            let skip_worktree = matches!(index_entry, Some(entry) if entry.flags.contains(Flags::SKIP_WORKTREE));
+            let skip_target = match (&sparse_checkout, slot.target) {
+                (Some(patterns), Some(target)) => !patterns.is_included(path, target.mode.is_submodule()),
+                _ => skip_worktree,
+            };
+
+            if force {
+                match (index_entry, slot.target) {
+                    (Some(entry), Some(target))
+                        if is_same(entry, target) && (skip_worktree || !worktree.differs(entry, path, true)?) =>
+                    {
+                        keep(&mut result, entry, path);
+                        entries_kept += 1;
+                    }
+                    (_, Some(target)) => {
+                        if skip_target && !skip_worktree {
+                            to_remove.insert(path.to_owned());
+                        }
+                        update(&mut result, target, path, skip_target);
+                    }
+                    // The entry was added to the index, but isn't part of either tree.
+                    (Some(entry), None) if slot.head.is_none() => {
+                        keep(&mut result, entry, path);
+                        entries_kept += 1;
+                    }
+                    (Some(_), None) => {
+                        if !skip_worktree {
+                            to_remove.insert(path.to_owned());
+                        }
+                    }
+                    (None, None) => {}
+                }
+                continue;
+            }
+
+            if is_same_or_absent(slot.head, slot.target) || is_same_or_absent(index_entry, slot.target) {
+                if let Some(entry) = index_entry {
+                    keep(&mut result, entry, path);
+                    entries_kept += 1;
+                }
+                continue;
+            }
+            let has_staged_changes = !is_same_or_absent(index_entry, slot.head);
+            if has_staged_changes {
+                would_overwrite.push(path.to_owned());
+                continue;
+            }
+            if let Some(entry) = index_entry {
+                if !skip_worktree && worktree.differs(entry, path, true)? {
+                    would_overwrite.push(path.to_owned());
+                    continue;
+                }
+            }
+            match slot.target {
+                Some(target) => {
+                    if skip_target {
+                        if !skip_worktree && index_entry.is_some() {
+                            to_remove.insert(path.to_owned());
+                        }
+                    } else if index_entry.is_none() || skip_worktree {
+                        new_paths.push((path, target));
+                    }
+                    update(&mut result, target, path, skip_target);
+                }
+                None => {
+                    if !skip_worktree {
+                        to_remove.insert(path.to_owned());
+                    }
+                }
+            }
+        }
+
+        let mut checked_leading_dirs = HashSet::new();
+        for (path, target) in new_paths {
+            if worktree.is_obstructed(path, target, &to_remove, &mut checked_leading_dirs)? {
+                would_overwrite.push(path.to_owned());
+            }
+        }
+        if !unmerged.is_empty() {
+            return Err(Error::Unmerged { paths: unmerged });
+        }
+        if !would_overwrite.is_empty() {
+            would_overwrite.sort();
+            return Err(Error::WouldOverwrite { paths: would_overwrite });
+        }
+        let changed_paths = changed_paths(previous, &result);
+        (result, to_remove, changed_paths, entries_kept)
+    };
+
+    let mut files_removed = 0;
+    for rela_path in &to_remove {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        if remove_file(&dir, rela_path.as_bstr())? {
+            files_removed += 1;
+        }
+    }
+
+    options.destination_is_initially_empty = false;
+    options.overwrite_existing = true;
+    let paths = result.take_path_backing();
+    let checkout = crate::checkout::function::checkout_inner(
+        &mut result,
+        &paths,
+        dir,
+        objects.clone(),
+        files,
+        bytes,
+        should_interrupt,
+        options,
+        true,
+    );
+    result.return_path_backing(paths);
+    let checkout = checkout.map_err(Box::new)?;
+    if should_interrupt.load(Ordering::Relaxed) {
+        return Err(Error::Interrupted);
+    }
+
+    for entry in result.entries_mut() {
+        entry.flags.remove(Flags::UPDATE);
+    }
+    index.set_timestamp(result.timestamp());
+    let (entries, path_backing) = result.into_entries();
+    index.replace_entries(entries, path_backing);
+    for path in &changed_paths {
+        index.invalidate_path(path.as_bstr());
+    }
+    if !sparse_dirs.is_empty() {
+        let object_hash = index.object_hash();
+        let mut buf = Vec::new();
+        // Directories are only collapsed if all of their trees exist, otherwise the index stays expanded.
+        index
+            .collapse_sparse_directories(
+                |dir| sparse_dirs.contains(dir),
+                |tree| existing_tree_id(tree, object_hash, &objects, &mut buf),
+            )
+            .ok();
+    }
+    Ok(Outcome {
+        checkout,
+        files_removed,
+        entries_kept,
+    })
+}
+
+/// Return the paths of all entries that were added, removed or changed in `current` compared to `previous`.
+fn changed_paths(previous: &gix_index::State, current: &gix_index::State) -> Vec<BString> {
+    let mut previous_entries: BTreeMap<&BStr, Option<&Entry>> = BTreeMap::new();
+    for entry in previous.entries() {
+        let is_unmerged = entry.stage() != 0;
+        previous_entries
+            .entry(entry.path(previous))
+            .and_modify(|previous| *previous = None)
+            .or_insert((!is_unmerged).then_some(entry));
+    }
+    let mut changed = Vec::new();
+    for entry in current.entries() {
+        let path = entry.path(current);
+        match previous_entries.remove(path) {
+            Some(Some(previous)) if is_same(previous, entry) => {}
+            _ => changed.push(path.to_owned()),
+        }
+    }
+    changed.extend(previous_entries.into_keys().map(ToOwned::to_owned));
+    changed
+}
+
+/// Return the id of `tree` with hashes of `object_hash` if it's present in `objects`, or `Err(())` otherwise.
+fn existing_tree_id(
+    tree: &gix_object::Tree,
+    object_hash: gix_hash::Kind,
+    objects: &impl gix_object::Find,
+    buf: &mut Vec<u8>,
+) -> Result<gix_hash::ObjectId, ()> {
+    buf.clear();
+    gix_object::WriteTo::write_to(tree, &mut *buf).map_err(|_| ())?;
+    let id = gix_object::compute_hash(object_hash, gix_object::Kind::Tree, buf);
+    match objects.try_find(&id, buf) {
+        Ok(Some(_)) => Ok(id),
+        _ => Err(()),
+    }
+}
+
+/// The entries at the same path in the current tree, the index and the target tree.
+#[derive(Default)]
+struct Slot<'a> {
+    head: Option<&'a Entry>,
+    index: Option<&'a Entry>,
+    unmerged: bool,
+    target: Option<&'a Entry>,
+}
+
+fn is_same(a: &Entry, b: &Entry) -> bool {
+    a.id == b.id && a.mode == b.mode
+}
+
+fn is_same_or_absent(a: Option<&Entry>, b: Option<&Entry>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => is_same(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn keep(result: &mut gix_index::State, entry: &Entry, path: &BStr) {
+    result.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, path);
+}
+
+fn update(result: &mut gix_index::State, entry: &Entry, path: &BStr, skip_worktree: bool) {
+    let flags = entry.flags
+        | if skip_worktree {
+            Flags::SKIP_WORKTREE | Flags::EXTENDED
+        } else {
+            Flags::UPDATE
+        };
+    result.dangerously_push_entry(Stat::default(), entry.id, flags, entry.mode, path);
+}
+
+/// Remove the file at `rela_path` in `dir`, along with all of its parent directories that are empty then.
+/// Return `true` if a file was removed.
+pub(crate) fn remove_file(dir: &Path, rela_path: &BStr) -> Result<bool, Error> {
+    let path = dir.join(gix_path::from_bstr(rela_path));
+    let res = match std::fs::symlink_metadata(&path) {
+        Ok(meta) if meta.is_dir() => {
+            // Submodules are only removed if they are empty, like git does.
+            std::fs::remove_dir(&path).ok();
+            return Ok(false);
+        }
+        Ok(meta) if meta.file_type().is_symlink() => gix_fs::symlink::remove(&path),
+        Ok(_) => std::fs::remove_file(&path),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => Err(err),
+    };
+    res.map_err(|source| Error::Remove {
+        path: path.clone(),
+        source,
+    })?;
+
+    let mut parent = path.parent();
+    while let Some(dir_to_remove) = parent.filter(|parent| *parent != dir) {
+        if std::fs::remove_dir(dir_to_remove).is_err() {
+            break;
+        }
+        parent = dir_to_remove.parent();
+    }
+    Ok(true)
+}
+
+/// Return `true` if the directory at `path` only contains files that are listed in `rela_paths`.
+fn contains_only(path: &Path, rela_dir: &BStr, rela_paths: &BTreeSet<BString>) -> Result<bool, Error> {
+    let check_worktree = |source| Error::CheckWorktree {
+        path: path.to_owned(),
+        source,
+    };
+    for entry in std::fs::read_dir(path).map_err(check_worktree)? {
+        let entry = entry.map_err(check_worktree)?;
+        let mut rela_path = rela_dir.to_owned();
+        rela_path.push_byte(b'/');
+        rela_path.push_str(gix_path::into_bstr(Path::new(&entry.file_name())).as_ref());
+        let is_dir = entry.file_type().map_err(check_worktree)?.is_dir();
+        let is_removed = if is_dir {
+            contains_only(&entry.path(), rela_path.as_bstr(), rela_paths)?
+        } else {
+            rela_paths.contains(rela_path.as_bstr())
+        };
+        if !is_removed {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Compare index entries with their files in the worktree.
+pub(crate) struct Worktree<'a, Find> {
+    dir: &'a Path,
+    stack: Stack,
+    filters: gix_filter::Pipeline,
+    objects: &'a Find,
+    options: &'a crate::checkout::Options,
+    timestamp: filetime::FileTime,
+    buf: Vec<u8>,
+}
+
+impl<'a, Find> Worktree<'a, Find>
+where
+    Find: gix_object::Find,
+{
+    pub(crate) fn new(
+        dir: &'a Path,
+        index: &gix_index::State,
+        objects: &'a Find,
+        options: &'a crate::checkout::Options,
+    ) -> Self {
+        Worktree {
+            dir,
+            stack: Stack::from_state_and_ignore_case(
+                dir.to_owned(),
+                options.fs.ignore_case,
+                stack::State::AttributesStack(options.attributes.clone()),
+                index,
+                index.path_backing(),
+            ),
+            filters: options.filters.clone(),
+            objects,
+            options,
+            timestamp: index.timestamp(),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Return `true` if what's on disk at `rela_path` doesn't match `entry`, which is assumed if it's not a file anymore.
+    /// Files that don't exist are considered unchanged, as nothing would be lost by overwriting them.
+    ///
+    /// If `use_stat` is `true`, the stat information of `entry` is used to avoid reading files.
+    pub(crate) fn differs(&mut self, entry: &Entry, rela_path: &BStr, use_stat: bool) -> Result<bool, Error> {
+        let path = self.dir.join(gix_path::from_bstr(rela_path));
+        let metadata = match gix_index::fs::Metadata::from_path_no_follow(&path) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(source) => return Err(Error::CheckWorktree { path, source }),
+        };
+        if entry.mode.is_submodule() {
+            return Ok(!metadata.is_dir());
+        }
+        if metadata.is_dir() {
+            return Ok(true);
+        }
+        if use_stat
+            && !entry.stat.is_racy(self.timestamp, self.options.stat_options)
+            && matches!(Stat::from_fs(&metadata), Ok(stat) if entry.stat.matches(&stat, self.options.stat_options))
+        {
+            return Ok(false);
+        }
+
+        let fs = self.options.fs;
+        let is_symlink = entry.mode == Mode::SYMLINK;
+        if fs.symlink && metadata.is_symlink() != is_symlink {
+            return Ok(true);
+        }
+        if !is_symlink && fs.executable_bit && metadata.is_executable() != (entry.mode == Mode::FILE_EXECUTABLE) {
+            return Ok(true);
+        }
+
+        let check_worktree = |source| Error::CheckWorktree {
+            path: path.clone(),
+            source,
+        };
+        self.buf.clear();
+        if is_symlink {
+            if metadata.is_symlink() {
+                let target = std::fs::read_link(&path).map_err(check_worktree)?;
+                self.buf.extend_from_slice(&gix_path::into_bstr(target));
+            } else {
+                self.buf = std::fs::read(&path).map_err(check_worktree)?;
+            }
+        } else {
+            let file = std::fs::File::open(&path).map_err(check_worktree)?;
+            let platform = self
+                .stack
+                .at_entry(rela_path, Some(false), self.objects)
+                .map_err(check_worktree)?;
+            let objects = self.objects;
+            let out = self
+                .filters
+                .convert_to_git(
+                    file,
+                    gix_path::from_bstr(rela_path).as_ref(),
+                    &mut |_path, attrs| {
+                        platform.matching_attributes(attrs);
+                    },
+                    &mut |buf| Ok(objects.find_blob(&entry.id, buf).map(|_| Some(()))?),
+                )
+                .map_err(Box::new)?;
+            match out {
+                ToGitOutcome::Unchanged(mut file) => {
+                    file.read_to_end(&mut self.buf).map_err(check_worktree)?;
+                }
+                ToGitOutcome::Process(mut stream) => {
+                    stream.read_to_end(&mut self.buf).map_err(check_worktree)?;
+                }
+                ToGitOutcome::Buffer(buf) => self.buf.extend_from_slice(buf),
+            }
+        }
+        let id = gix_object::compute_hash(entry.id.kind(), gix_object::Kind::Blob, &self.buf);
+        Ok(id != entry.id)
+    }
+
+    /// Return `true` if an untracked file or directory is in the way of checking out `entry` at `rela_path`,
+    /// ignoring everything listed in `to_remove`. `checked_leading_dirs` is used to check each leading directory only once.
+    fn is_obstructed(
+        &mut self,
+        rela_path: &BStr,
+        entry: &Entry,
+        to_remove: &BTreeSet<BString>,
+        checked_leading_dirs: &mut HashSet<BString>,
+    ) -> Result<bool, Error> {
+        for pos in rela_path.find_iter("/") {
+            let leading_dir = rela_path[..pos].as_bstr();
+            if !checked_leading_dirs.insert(leading_dir.to_owned()) {
+                continue;
+            }
+            let path = self.dir.join(gix_path::from_bstr(leading_dir));
+            match std::fs::symlink_metadata(&path) {
+                Ok(meta) if meta.is_dir() => {}
+                Ok(_) if to_remove.contains(leading_dir) => return Ok(false),
+                Ok(_) => return Ok(true),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+                Err(source) => return Err(Error::CheckWorktree { path, source }),
+            }
+        }
+
+        let path = self.dir.join(gix_path::from_bstr(rela_path));
+        match std::fs::symlink_metadata(&path) {
+            Ok(meta) if meta.is_dir() => Ok(!entry.mode.is_submodule() && !contains_only(&path, rela_path, to_remove)?),
+            Ok(_) => self.differs(entry, rela_path, false),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(source) => Err(Error::CheckWorktree { path, source }),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use bstr::BString;
+
+/// The outcome of [switching](crate::switch()) the worktree from one tree to another.
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// The outcome of checking out all entries that were added or changed.
+    pub checkout: crate::checkout::Outcome,
+    /// The amount of files that were removed from the worktree as they are not part of the target tree anymore.
+    pub files_removed: usize,
+    /// The amount of entries that were taken over from the previous index, along with their worktree files.
+    pub entries_kept: usize,
+}
+
+/// The error returned by the [switch()](crate::switch()) function.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not create index from tree at {id}")]
+    IndexFromTree {
+        id: gix_hash::ObjectId,
+        source: gix_traverse::tree::breadthfirst::Error,
+    },
+    #[error("Could not expand the sparse directories of the index")]
+    ExpandSparseIndex(#[source] gix_traverse::tree::breadthfirst::Error),
+    #[error("The following paths are unmerged and need to be resolved first: {}", join(.paths))]
+    Unmerged { paths: Vec<BString> },
+    #[error("Local changes to the following paths would be overwritten: {}", join(.paths))]
+    WouldOverwrite { paths: Vec<BString> },
+    #[error("Could not check '{}' for local modifications", path.display())]
+    CheckWorktree { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    Filter(#[from] Box<gix_filter::pipeline::convert::to_git::Error>),
+    #[error("Could not remove '{}' from the worktree", path.display())]
+    Remove { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    Checkout(#[from] Box<crate::checkout::Error>),
+    #[error("The operation was interrupted")]
+    Interrupted,
+}
+
+fn join(paths: &[BString]) -> String {
+    paths.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+pub(crate) mod function;
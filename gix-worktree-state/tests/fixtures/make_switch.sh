@@ -0,0 +1,35 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b current
+
+echo unchanged > unchanged
+echo current > modified
+echo removed > removed
+echo executable > executable
+mkdir dir-to-file && echo nested > dir-to-file/file
+echo file > file-to-dir
+mkdir -p removed-dir/sub && echo nested > removed-dir/sub/file
+ln -s unchanged link
+
+git add -A
+git commit -q -m "current"
+
+git checkout -q -b target
+echo target > modified
+git rm -q removed
+chmod +x executable
+git rm -q -r dir-to-file
+echo file > dir-to-file
+git rm -q file-to-dir
+mkdir file-to-dir && echo nested > file-to-dir/file
+git rm -q -r removed-dir
+mkdir -p added-dir/sub && echo added > added-dir/sub/file
+echo added > added
+rm link && ln -s modified link
+
+git add -A
+git commit -q -m "target"
+
+git checkout -q current
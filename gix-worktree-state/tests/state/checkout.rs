@@ -57,6 +57,31 @@ fn submodules_are_instantiated_as_directories() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn entries_excluded_by_sparse_checkout_patterns_are_skipped() -> crate::Result {
+    let mut opts = opts_from_probe();
+    opts.sparse_checkout = Some(gix_worktree::sparse::Patterns::Cone(Default::default()));
+    let (_source_tree, destination, index, outcome) = checkout_index_in_tmp_dir(opts, "make_mixed_without_submodules")?;
+
+    assert_eq!(
+        stripped_prefix(destination.path(), &dir_structure(&destination)),
+        paths(["empty", "executable"]),
+        "only files in the root are included by an empty cone, with hidden files not being listed"
+    );
+    assert_eq!(outcome.files_updated, 5, "skipped entries are counted as well");
+    for entry in index.entries() {
+        let is_root_file = !entry.path(&index).contains(&b'/');
+        assert_eq!(
+            entry
+                .flags
+                .contains(gix_index::entry::Flags::SKIP_WORKTREE | gix_index::entry::Flags::EXTENDED),
+            !is_root_file,
+            "skipped entries are flagged so the index can be written as is"
+        );
+    }
+    Ok(())
+}
+
 fn assure_is_empty(dir: impl AsRef<Path>) -> std::io::Result<()> {
     assert_eq!(std::fs::read_dir(dir)?.count(), 0);
     Ok(())
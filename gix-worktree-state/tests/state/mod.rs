@@ -1,4 +1,6 @@
 mod checkout;
+mod sparse;
+mod switch;
 
 use std::path::{Path, PathBuf};
 
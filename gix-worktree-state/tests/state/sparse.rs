@@ -0,0 +1,86 @@
+use std::sync::atomic::AtomicBool;
+
+use gix_features::progress;
+use gix_index::entry::Flags;
+use gix_object::bstr::BStr;
+use gix_worktree::sparse::{Cone, Patterns};
+
+#[test]
+fn excluded_files_are_removed_unless_modified_and_included_ones_are_checked_out() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_writable_with_args_standalone(
+        "make_switch.sh",
+        None::<String>,
+        gix_testtools::Creation::ExecuteScript,
+    )
+    .expect("script works");
+    let git_dir = dir.path().join(".git");
+    let mut index: gix_index::State =
+        gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, false, Default::default())?.into();
+    let odb = gix_odb::at(git_dir.join("objects"))?.into_inner().into_arc()?;
+    let opts = gix_worktree_state::checkout::Options {
+        fs: gix_fs::Capabilities::probe(&git_dir),
+        ..Default::default()
+    };
+    let update = |index: &mut gix_index::State, patterns: Patterns| {
+        gix_worktree_state::sparse::update(
+            index,
+            &patterns,
+            dir.path(),
+            odb.clone(),
+            &progress::Discard,
+            &progress::Discard,
+            &AtomicBool::default(),
+            opts.clone(),
+        )
+    };
+    let is_skipped = |index: &gix_index::State, path: &str| {
+        index
+            .entry_by_path(BStr::new(path))
+            .expect("present")
+            .flags
+            .contains(Flags::SKIP_WORKTREE)
+    };
+
+    std::fs::write(dir.path().join("removed-dir/sub/file"), "local change\n")?;
+    let outcome = update(&mut index, Patterns::Cone(Cone::default()))?;
+    assert_eq!(outcome.files_removed, 1, "dir-to-file/file");
+    assert_eq!(outcome.modified_paths_kept, ["removed-dir/sub/file"]);
+    assert_eq!(outcome.checkout.files_updated, 0);
+    assert!(
+        !dir.path().join("dir-to-file").exists(),
+        "empty directories are removed"
+    );
+    assert!(is_skipped(&index, "dir-to-file/file"));
+    assert!(
+        !is_skipped(&index, "removed-dir/sub/file"),
+        "modified files stay in the worktree, and thus aren't skipped"
+    );
+    assert!(
+        !is_skipped(&index, "unchanged"),
+        "files in the root are always included"
+    );
+
+    let outcome = update(&mut index, Patterns::Cone(Cone::default()))?;
+    assert_eq!(
+        outcome.modified_paths_kept,
+        ["removed-dir/sub/file"],
+        "it's still modified"
+    );
+
+    std::fs::write(dir.path().join("removed-dir/sub/file"), "nested\n")?;
+    let outcome = update(&mut index, Patterns::Cone(Cone::from_directories(["dir-to-file"])))?;
+    assert_eq!(
+        outcome.files_removed, 1,
+        "removed-dir/sub/file is excluded and unmodified now"
+    );
+    assert!(outcome.modified_paths_kept.is_empty());
+    assert_eq!(outcome.checkout.files_updated, 1, "dir-to-file/file");
+    assert_eq!(std::fs::read(dir.path().join("dir-to-file/file"))?, b"nested\n");
+    assert!(!is_skipped(&index, "dir-to-file/file"));
+    assert!(is_skipped(&index, "removed-dir/sub/file"));
+    assert!(
+        index.entries().iter().all(|e| !e.flags.contains(Flags::UPDATE)),
+        "no temporary flags are left"
+    );
+    Ok(())
+}
@@ -0,0 +1,291 @@
+use std::{path::Path, sync::atomic::AtomicBool};
+
+use gix_features::progress;
+use gix_object::{bstr::ByteSlice, FindExt};
+use gix_testtools::tempfile::TempDir;
+use gix_worktree_state::switch::Error;
+
+#[test]
+fn only_changed_entries_are_written_and_removed_ones_are_deleted() -> crate::Result {
+    let mut fixture = Fixture::new()?;
+    let outcome = fixture.switch(fixture.opts())?;
+
+    assert_eq!(
+        outcome.files_removed, 4,
+        "removed, dir-to-file/file, file-to-dir and removed-dir/sub/file"
+    );
+    assert_eq!(
+        outcome.checkout.files_updated, 6,
+        "modified, executable, dir-to-file, file-to-dir/file, added and added-dir/sub/file, as symlinks aren't counted"
+    );
+    assert_eq!(outcome.entries_kept, 1, "only 'unchanged' stays the same");
+
+    let dir = fixture.dir.path();
+    assert_eq!(std::fs::read(dir.join("modified"))?, b"target\n");
+    assert_eq!(std::fs::read(dir.join("dir-to-file"))?, b"file\n");
+    assert_eq!(std::fs::read(dir.join("file-to-dir/file"))?, b"nested\n");
+    assert_eq!(std::fs::read(dir.join("added-dir/sub/file"))?, b"added\n");
+    assert!(!dir.join("removed").exists());
+    assert!(
+        !dir.join("removed-dir").exists(),
+        "empty directories are removed as well"
+    );
+    if fixture.fs.symlink {
+        assert_eq!(std::fs::read_link(dir.join("link"))?, Path::new("modified"));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_ne!(
+            std::fs::metadata(dir.join("executable"))?.permissions().mode() & 0o100,
+            0,
+            "the executable bit is set"
+        );
+    }
+
+    let expected = fixture.tree_index("target")?;
+    assert_eq!(
+        entries(&fixture.index),
+        entries(&expected),
+        "the index now matches the target tree"
+    );
+    assert!(
+        fixture.index.entries().iter().all(|e| e.stat.mtime.secs != 0),
+        "all entries have their stat information set"
+    );
+    Ok(())
+}
+
+#[test]
+fn extensions_are_kept_with_changed_paths_being_invalidated() -> crate::Result {
+    let mut fixture = Fixture::new()?;
+    let tree = fixture.index.tree().expect("git writes the tree extension").clone();
+    assert!(tree.num_entries.is_some(), "the tree cache is valid initially");
+    assert!(
+        tree.children
+            .iter()
+            .any(|child| child.name.as_slice() == b"dir-to-file"),
+        "the directory is present in the tree cache"
+    );
+
+    fixture.switch(fixture.opts())?;
+    let tree = fixture.index.tree().expect("the tree extension is kept");
+    assert_eq!(tree.num_entries, None, "the root tree contains changed entries");
+    assert!(
+        !tree
+            .children
+            .iter()
+            .any(|child| child.name.as_slice() == b"dir-to-file"),
+        "trees that were replaced by a file are removed"
+    );
+    assert!(
+        tree.children.iter().all(|child| child.num_entries.is_none()),
+        "all other trees contain changed entries, too"
+    );
+    Ok(())
+}
+
+#[test]
+fn local_modifications_of_changed_entries_prevent_the_switch_unless_forced() -> crate::Result {
+    let mut fixture = Fixture::new()?;
+    std::fs::write(fixture.dir.path().join("modified"), "local change\n")?;
+    std::fs::write(fixture.dir.path().join("removed"), "local change\n")?;
+
+    let err = fixture.switch(fixture.opts()).unwrap_err();
+    assert!(
+        matches!(&err, Error::WouldOverwrite { paths } if paths == &["modified", "removed"]),
+        "{err:?}"
+    );
+    assert!(
+        fixture.dir.path().join("dir-to-file").is_dir(),
+        "nothing was changed in the worktree"
+    );
+    assert_eq!(entries(&fixture.index), entries(&fixture.tree_index("current")?));
+
+    let mut opts = fixture.opts();
+    opts.overwrite_existing = true;
+    fixture.switch(opts)?;
+    assert_eq!(std::fs::read(fixture.dir.path().join("modified"))?, b"target\n");
+    assert!(!fixture.dir.path().join("removed").exists());
+    Ok(())
+}
+
+#[test]
+fn local_modifications_of_unchanged_entries_are_kept() -> crate::Result {
+    let mut fixture = Fixture::new()?;
+    std::fs::write(fixture.dir.path().join("unchanged"), "local change\n")?;
+
+    fixture.switch(fixture.opts())?;
+    assert_eq!(std::fs::read(fixture.dir.path().join("unchanged"))?, b"local change\n");
+    Ok(())
+}
+
+#[test]
+fn untracked_files_in_the_way_prevent_the_switch_unless_they_match() -> crate::Result {
+    let mut fixture = Fixture::new()?;
+    std::fs::write(fixture.dir.path().join("added"), "untracked\n")?;
+    std::fs::create_dir(fixture.dir.path().join("file-to-dir.tmp"))?;
+    std::fs::write(fixture.dir.path().join("dir-to-file/untracked"), "untracked\n")?;
+
+    let err = fixture.switch(fixture.opts()).unwrap_err();
+    assert!(
+        matches!(&err, Error::WouldOverwrite { paths } if paths == &["added", "dir-to-file"]),
+        "{err:?}"
+    );
+
+    std::fs::write(fixture.dir.path().join("added"), "added\n")?;
+    std::fs::remove_file(fixture.dir.path().join("dir-to-file/untracked"))?;
+    fixture.switch(fixture.opts())?;
+    assert!(
+        fixture.dir.path().join("file-to-dir.tmp").is_dir(),
+        "untracked files not in the way are kept"
+    );
+    Ok(())
+}
+
+#[test]
+fn staged_changes_of_changed_entries_prevent_the_switch() -> crate::Result {
+    let mut fixture = Fixture::new()?;
+    let unchanged_id = fixture.index.entry_by_path("unchanged".into()).expect("present").id;
+    let (entries, paths) = fixture.index.entries_mut_and_pathbacking();
+    let entry = entries
+        .iter_mut()
+        .find(|e| e.path_in(paths) == "modified")
+        .expect("present");
+    entry.id = unchanged_id;
+
+    let err = fixture.switch(fixture.opts()).unwrap_err();
+    assert!(
+        matches!(&err, Error::WouldOverwrite { paths } if paths == &["modified"]),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn unmerged_entries_prevent_the_switch() -> crate::Result {
+    let mut fixture = Fixture::new()?;
+    let (entries, paths) = fixture.index.entries_mut_and_pathbacking();
+    let entry = entries
+        .iter_mut()
+        .find(|e| e.path_in(paths) == "unchanged")
+        .expect("present");
+    entry.flags.insert(gix_index::entry::Flags::from_bits_retain(2 << 12));
+
+    let err = fixture.switch(fixture.opts()).unwrap_err();
+    assert!(
+        matches!(&err, Error::Unmerged { paths } if paths == &["unchanged"]),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn entries_excluded_by_sparse_checkout_patterns_are_skipped() -> crate::Result {
+    let mut fixture = Fixture::new()?;
+    let mut opts = fixture.opts();
+    opts.sparse_checkout = Some(gix_worktree::sparse::Patterns::Cone(
+        gix_worktree::sparse::Cone::from_directories(["added-dir/sub"]),
+    ));
+    let outcome = fixture.switch(opts)?;
+
+    assert_eq!(
+        outcome.checkout.files_updated, 5,
+        "modified, executable, dir-to-file, added and added-dir/sub/file"
+    );
+    let dir = fixture.dir.path();
+    assert!(
+        !dir.join("file-to-dir").exists(),
+        "the file was removed, but the new directory is excluded"
+    );
+    assert_eq!(std::fs::read(dir.join("added-dir/sub/file"))?, b"added\n");
+
+    let skipped: Vec<_> = fixture
+        .index
+        .entries()
+        .iter()
+        .filter(|e| {
+            e.flags
+                .contains(gix_index::entry::Flags::SKIP_WORKTREE | gix_index::entry::Flags::EXTENDED)
+        })
+        .map(|e| e.path(&fixture.index).to_owned())
+        .collect();
+    assert_eq!(skipped, ["file-to-dir/file"]);
+    Ok(())
+}
+
+struct Fixture {
+    dir: TempDir,
+    index: gix_index::State,
+    odb: gix_odb::store::Handle<std::sync::Arc<gix_odb::Store>>,
+    fs: gix_fs::Capabilities,
+}
+
+impl Fixture {
+    fn new() -> crate::Result<Self> {
+        let dir = gix_testtools::scripted_fixture_writable_with_args_standalone(
+            "make_switch.sh",
+            None::<String>,
+            gix_testtools::Creation::ExecuteScript,
+        )
+        .expect("script works");
+        let git_dir = dir.path().join(".git");
+        let index = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, false, Default::default())?;
+        let odb = gix_odb::at(git_dir.join("objects"))?.into_inner().into_arc()?;
+        let fs = gix_fs::Capabilities::probe(&git_dir);
+        Ok(Fixture {
+            dir,
+            index: index.into(),
+            odb,
+            fs,
+        })
+    }
+
+    fn tree(&self, branch: &str) -> crate::Result<gix_hash::ObjectId> {
+        let commit = std::fs::read_to_string(self.dir.path().join(".git/refs/heads").join(branch))?;
+        let commit = gix_hash::ObjectId::from_hex(commit.trim().as_bytes())?;
+        let mut buf = Vec::new();
+        let tree = self.odb.find_commit(&commit, &mut buf)?.tree();
+        Ok(tree)
+    }
+
+    fn tree_index(&self, branch: &str) -> crate::Result<gix_index::State> {
+        Ok(gix_index::State::from_tree(&self.tree(branch)?, &self.odb)?)
+    }
+
+    fn opts(&self) -> gix_worktree_state::checkout::Options {
+        gix_worktree_state::checkout::Options {
+            fs: self.fs,
+            ..Default::default()
+        }
+    }
+
+    fn switch(
+        &mut self,
+        opts: gix_worktree_state::checkout::Options,
+    ) -> Result<gix_worktree_state::switch::Outcome, Error> {
+        let (current, target) = (
+            self.tree("current").expect("valid"),
+            self.tree("target").expect("valid"),
+        );
+        gix_worktree_state::switch(
+            &mut self.index,
+            &current,
+            &target,
+            self.dir.path(),
+            self.odb.clone(),
+            &progress::Discard,
+            &progress::Discard,
+            &AtomicBool::default(),
+            opts,
+        )
+    }
+}
+
+fn entries(index: &gix_index::State) -> Vec<(String, gix_index::entry::Mode, gix_hash::ObjectId)> {
+    index
+        .entries()
+        .iter()
+        .map(|e| (e.path(index).to_str_lossy().into_owned(), e.mode, e.id))
+        .collect()
+}
@@ -0,0 +1,304 @@
+//! Match paths against the patterns of a sparse checkout, as found in `.git/info/sparse-checkout`, to learn which
+//! entries of the index should be present in the worktree.
+use std::collections::BTreeSet;
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+
+/// The patterns of a sparse checkout, which decide which entries of the index are present in the worktree.
+///
+/// Entries that aren't included are marked with the [`SKIP_WORKTREE`](gix_index::entry::Flags::SKIP_WORKTREE) flag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Patterns {
+    /// Only directories are specified, which is fast to match and allows the index to be sparse as well.
+    ///
+    /// This is used if `core.sparseCheckoutCone` is enabled and all patterns have the shape that `git sparse-checkout set`
+    /// produces in cone mode.
+    Cone(Cone),
+    /// Patterns like the ones in `.gitignore` files, where paths that match a pattern are included, unless the pattern is negated.
+    NonCone(gix_ignore::Search),
+}
+
+/// Directories to include in a sparse checkout, along with all files in the root of the worktree.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cone {
+    /// Directories whose entire content is included, without leading or trailing slash.
+    pub recursive: BTreeSet<BString>,
+    /// Directories whose files are included, but not their sub-directories, without leading or trailing slash.
+    ///
+    /// These are typically the parent directories of the ones in [`recursive`](Self::recursive).
+    pub parents: BTreeSet<BString>,
+}
+
+/// Initialization
+impl Patterns {
+    /// Parse the content of a `.git/info/sparse-checkout` file in `bytes`, interpreting it in cone mode if `cone` is `true`.
+    ///
+    /// Just like `git`, non-cone mode is used if `bytes` contains patterns that don't work in cone mode.
+    pub fn from_bytes(bytes: &[u8], cone: bool) -> Self {
+        match cone.then(|| Cone::from_bytes(bytes)).flatten() {
+            Some(cone) => Patterns::Cone(cone),
+            None => {
+                let mut search = gix_ignore::Search::default();
+                search.add_patterns_buffer(bytes, "info/sparse-checkout", None);
+                Patterns::NonCone(search)
+            }
+        }
+    }
+
+    /// Read the patterns from the file at `path`, typically `.git/info/sparse-checkout`, and interpret them in cone mode
+    /// if `cone` is `true`. A file that doesn't exist is treated like an empty one, which only includes files in the root
+    /// of the worktree in cone mode, and nothing in non-cone mode.
+    pub fn from_path(path: &std::path::Path, cone: bool) -> std::io::Result<Self> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self::from_bytes(&bytes, cone))
+    }
+}
+
+/// Access
+impl Patterns {
+    /// Return `true` if the entry at `rela_path` should be present in the worktree, with `is_dir` being `true` if it's a directory.
+    ///
+    /// Note that directories are included if at least some of their content may be included.
+    pub fn is_included(&self, rela_path: &BStr, is_dir: bool) -> bool {
+        match self {
+            Patterns::Cone(cone) => cone.is_included(rela_path, is_dir),
+            Patterns::NonCone(search) => {
+                // Like `git`, let the first match of the path itself or of one of its parent directories decide.
+                let mut path = rela_path;
+                let mut is_dir = is_dir;
+                loop {
+                    if let Some(m) =
+                        search.pattern_matching_relative_path(path, Some(is_dir), gix_glob::pattern::Case::Sensitive)
+                    {
+                        break !m.pattern.is_negative();
+                    }
+                    match path.rfind_byte(b'/') {
+                        Some(pos) => {
+                            path = path[..pos].as_bstr();
+                            is_dir = true;
+                        }
+                        None => break false,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return `true` if nothing within the directory at `rela_dir` can be included, which is only known in cone mode.
+    /// Such directories can be stored as single sparse directory entries in the index.
+    pub fn is_excluded_dir(&self, rela_dir: &BStr) -> bool {
+        match self {
+            Patterns::Cone(cone) => !cone.is_included(rela_dir, true),
+            Patterns::NonCone(_) => false,
+        }
+    }
+
+    /// Return `true` if this is a cone.
+    pub fn is_cone(&self) -> bool {
+        matches!(self, Patterns::Cone(_))
+    }
+}
+
+/// Initialization
+impl Cone {
+    /// Create a new instance that includes all `directories` recursively, along with the files in each of their parent directories.
+    ///
+    /// Leading and trailing slashes are ignored, and empty directories are skipped.
+    pub fn from_directories(directories: impl IntoIterator<Item = impl AsRef<BStr>>) -> Self {
+        let mut out = Cone::default();
+        out.extend(directories);
+        out
+    }
+
+    /// Parse `bytes` as written by `git sparse-checkout` in cone mode, or return `None` if there is a pattern that
+    /// isn't supported in cone mode.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut out = Cone::default();
+        for line in bytes.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(b"#") {
+                continue;
+            }
+            if line == b"/*" || line == b"!/*/" {
+                continue;
+            }
+            match line.strip_prefix(b"!") {
+                Some(negated) => {
+                    let dir = unescape(negated.strip_prefix(b"/")?.strip_suffix(b"/*/")?)?;
+                    // Negations are only valid to turn a previously seen recursive directory into a parent directory.
+                    if !out.recursive.remove(&dir) {
+                        return None;
+                    }
+                    out.parents.insert(dir);
+                }
+                None => {
+                    let dir = unescape(line.strip_prefix(b"/")?.strip_suffix(b"/")?)?;
+                    if dir.is_empty() {
+                        return None;
+                    }
+                    out.recursive.insert(dir);
+                }
+            }
+        }
+        Some(out)
+    }
+}
+
+/// Mutation
+impl Cone {
+    /// Add all `directories` recursively, along with the files in each of their parent directories.
+    pub fn extend(&mut self, directories: impl IntoIterator<Item = impl AsRef<BStr>>) {
+        for dir in directories {
+            let dir = dir.as_ref().trim_with(|c| c == '/');
+            if dir.is_empty() {
+                continue;
+            }
+            let mut parent = dir;
+            while let Some(pos) = parent.rfind_byte(b'/') {
+                parent = &parent[..pos];
+                self.parents.insert(parent.as_bstr().to_owned());
+            }
+            self.recursive.insert(dir.as_bstr().to_owned());
+        }
+    }
+}
+
+/// Access
+impl Cone {
+    /// Return `true` if the entry at `rela_path` should be present in the worktree, with `is_dir` being `true` if it's a directory.
+    ///
+    /// Files in the root are always included, as are directories that lead to included directories.
+    pub fn is_included(&self, rela_path: &BStr, is_dir: bool) -> bool {
+        if is_dir {
+            return self.has_recursive_ancestor_or_self(rela_path)
+                || self.parents.contains(rela_path)
+                || self
+                    .parents
+                    .iter()
+                    .chain(&self.recursive)
+                    .any(|dir| is_dir_in(dir.as_bstr(), rela_path));
+        }
+        match rela_path.rfind_byte(b'/') {
+            None => true,
+            Some(pos) => {
+                let dir = rela_path[..pos].as_bstr();
+                self.parents.contains(dir) || self.has_recursive_ancestor_or_self(dir)
+            }
+        }
+    }
+
+    /// Return the directories that are included recursively and aren't contained in another recursively included
+    /// directory, which is what `git sparse-checkout list` shows in cone mode.
+    pub fn directories(&self) -> impl Iterator<Item = &BStr> + '_ {
+        self.recursive
+            .iter()
+            .map(|dir| dir.as_bstr())
+            .filter(|dir| !self.has_recursive_ancestor(dir))
+    }
+
+    /// Serialize this instance like `git sparse-checkout` would in cone mode.
+    ///
+    /// Parent directories that are included recursively anyway are omitted.
+    pub fn to_bstring(&self) -> BString {
+        let mut out = BString::from("/*\n!/*/\n");
+        for dir in self
+            .parents
+            .iter()
+            .filter(|dir| !self.has_recursive_ancestor_or_self(dir.as_bstr()))
+        {
+            let dir = escape(dir.as_bstr());
+            out.push_str(format!("/{dir}/\n!/{dir}/*/\n"));
+        }
+        for dir in self.directories() {
+            out.push_str(format!("/{}/\n", escape(dir)));
+        }
+        out
+    }
+
+    fn has_recursive_ancestor_or_self(&self, dir: &BStr) -> bool {
+        self.recursive.contains(dir) || self.has_recursive_ancestor(dir)
+    }
+
+    fn has_recursive_ancestor(&self, dir: &BStr) -> bool {
+        let mut parent = dir;
+        while let Some(pos) = parent.rfind_byte(b'/') {
+            parent = parent[..pos].as_bstr();
+            if self.recursive.contains(parent) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Return `true` if `path` is located within `dir`.
+fn is_dir_in(path: &BStr, dir: &BStr) -> bool {
+    path.len() > dir.len() && path.starts_with(dir) && path[dir.len()] == b'/'
+}
+
+fn escape(dir: &BStr) -> BString {
+    let mut out = BString::default();
+    for b in dir.iter().copied() {
+        if matches!(b, b'*' | b'?' | b'[' | b'\\') {
+            out.push_byte(b'\\');
+        }
+        out.push_byte(b);
+    }
+    out
+}
+
+/// Remove escapes, or return `None` if there are unescaped glob characters which can't be used in cone mode.
+fn unescape(dir: &[u8]) -> Option<BString> {
+    let mut out = BString::default();
+    let mut bytes = dir.iter().copied();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'\\' => out.push_byte(bytes.next()?),
+            b'*' | b'?' | b'[' => return None,
+            b => out.push_byte(b),
+        }
+    }
+    Some(out)
+}
+
+/// Update the [`SKIP_WORKTREE`](gix_index::entry::Flags::SKIP_WORKTREE) flag of all entries in `index` according to `patterns`,
+/// and return the indices of the entries that changed.
+///
+/// Unmerged entries are never skipped, and sparse directory entries are left untouched, so the index should be
+/// [expanded](gix_index::State::expand_sparse_directories()) first if `patterns` may include some of them.
+pub fn apply(patterns: &Patterns, index: &mut gix_index::State) -> Changes {
+    use gix_index::entry::Flags;
+
+    let mut out = Changes::default();
+    for (idx, (entry, path)) in index.entries_mut_with_paths().enumerate() {
+        if entry.mode.is_sparse() {
+            continue;
+        }
+        let include = entry.stage() != 0 || patterns.is_included(path, entry.mode.is_submodule());
+        let is_skipped = entry.flags.contains(Flags::SKIP_WORKTREE);
+        if include && is_skipped {
+            entry.flags.remove(Flags::SKIP_WORKTREE);
+            if !entry.flags.contains(Flags::INTENT_TO_ADD) {
+                entry.flags.remove(Flags::EXTENDED);
+            }
+            out.included.push(idx);
+        } else if !include && !is_skipped {
+            entry.flags.insert(Flags::SKIP_WORKTREE | Flags::EXTENDED);
+            out.skipped.push(idx);
+        }
+    }
+    out
+}
+
+/// The entries whose [`SKIP_WORKTREE`](gix_index::entry::Flags::SKIP_WORKTREE) flag was changed by [`apply()`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Changes {
+    /// The indices of entries that are skipped now, but weren't before.
+    pub skipped: Vec<usize>,
+    /// The indices of entries that aren't skipped anymore.
+    pub included: Vec<usize>,
+}
@@ -0,0 +1,26 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+for path in root.txt root.md a/file a/b/file a/b/c/file a/b/c/x.md a/d/file c/file c/sub/file d/file d/e/file.txt; do
+  mkdir -p "$(dirname "$path")"
+  echo "$path" >"$path"
+done
+git add .
+git commit -q -m "init"
+
+function baseline() {
+  local name=${1:?first argument is the name of the baseline}
+  shift
+  git sparse-checkout set "$@"
+  cp .git/info/sparse-checkout "sparse-checkout-$name"
+  git ls-files -t >"baseline-$name"
+}
+
+baseline cone-empty
+baseline cone a/b c
+baseline cone-nested a/b/c d/e
+baseline cone-overlapping a a/b c/d/e c
+baseline non-cone --no-cone '/*.txt' '!/root.txt' 'a/b/' '*.md'
+baseline non-cone-negated-dir --no-cone '/*' '!/a/' '/a/b/'
+git sparse-checkout disable
@@ -0,0 +1,119 @@
+use bstr::{BString, ByteSlice};
+use gix_worktree::sparse::{Cone, Patterns};
+
+fn baseline(name: &str) -> crate::Result<(std::path::PathBuf, Vec<(BString, bool)>)> {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_sparse_baseline.sh")?;
+    let expected = std::fs::read(dir.join(format!("baseline-{name}")))?
+        .lines()
+        .map(|line| {
+            let (status, path) = line.split_at(2);
+            (path.into(), status[0] == b'S')
+        })
+        .collect();
+    Ok((dir.join(format!("sparse-checkout-{name}")), expected))
+}
+
+#[test]
+fn apply_matches_git() -> crate::Result {
+    for (name, cone) in [
+        ("cone-empty", true),
+        ("cone", true),
+        ("cone-nested", true),
+        ("cone-overlapping", true),
+        ("non-cone", false),
+        ("non-cone-negated-dir", false),
+    ] {
+        let (patterns_path, expected) = baseline(name)?;
+        let patterns = Patterns::from_path(&patterns_path, cone)?;
+        assert_eq!(
+            patterns.is_cone(),
+            cone,
+            "{name}: the patterns are parsed in the mode they were written in"
+        );
+
+        let dir = gix_testtools::scripted_fixture_read_only_standalone("make_sparse_baseline.sh")?;
+        let mut index: gix_index::State =
+            gix_index::File::at(dir.join(".git/index"), gix_hash::Kind::Sha1, false, Default::default())?.into();
+        let changes = gix_worktree::sparse::apply(&patterns, &mut index);
+        assert!(changes.included.is_empty(), "nothing was skipped before");
+
+        let actual: Vec<_> = index
+            .entries()
+            .iter()
+            .map(|e| {
+                (
+                    e.path(&index).to_owned(),
+                    e.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE),
+                )
+            })
+            .collect();
+        assert_eq!(actual, expected, "{name}");
+        assert_eq!(
+            changes.skipped.len(),
+            expected.iter().filter(|(_, skipped)| *skipped).count(),
+            "{name}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn cone_roundtrips_with_what_git_writes() -> crate::Result {
+    for (name, dirs) in [
+        ("cone-empty", &[][..]),
+        ("cone", &["a/b", "c"][..]),
+        ("cone-nested", &["a/b/c", "d/e"][..]),
+    ] {
+        let (patterns_path, _) = baseline(name)?;
+        let expected = std::fs::read(patterns_path)?;
+        let cone = Cone::from_bytes(&expected).expect("valid cone patterns");
+        assert_eq!(cone.to_bstring(), expected.as_bstr(), "{name}");
+        assert_eq!(Cone::from_directories(dirs.iter().copied()), cone, "{name}");
+        assert_eq!(cone.directories().collect::<Vec<_>>(), dirs, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn cone_with_overlapping_directories_roundtrips_with_what_git_writes() -> crate::Result {
+    let (patterns_path, _) = baseline("cone-overlapping")?;
+    let expected = std::fs::read(patterns_path)?;
+    let cone = Cone::from_directories(["a", "a/b", "c/d/e", "c"]);
+    assert_eq!(
+        cone.to_bstring(),
+        expected.as_bstr(),
+        "parents that are included recursively aren't written"
+    );
+    assert_eq!(cone.directories().collect::<Vec<_>>(), ["a", "c"]);
+
+    let parsed = Cone::from_bytes(&expected).expect("valid cone patterns");
+    assert_eq!(parsed.to_bstring(), expected.as_bstr());
+    assert_eq!(parsed.directories().collect::<Vec<_>>(), ["a", "c"]);
+    Ok(())
+}
+
+#[test]
+fn cone_patterns_with_globs_fall_back_to_non_cone_mode() {
+    let patterns = Patterns::from_bytes(b"/*\n!/*/\n/a*/\n", true);
+    assert!(!patterns.is_cone());
+    assert!(patterns.is_included("ab/file".into(), false));
+
+    let patterns = Patterns::from_bytes(b"/*\n!/*/\n/a\\*/\n", true);
+    assert!(patterns.is_cone(), "escaped glob characters are fine");
+    assert!(patterns.is_included("a*/file".into(), false));
+    assert!(!patterns.is_included("ab/file".into(), false));
+}
+
+#[test]
+fn directories_of_a_cone_are_excluded_only_if_nothing_within_is_included() {
+    let patterns = Patterns::Cone(Cone::from_directories(["a/b"]));
+    assert!(!patterns.is_excluded_dir("a".into()), "it contains a/b");
+    assert!(!patterns.is_excluded_dir("a/b/c".into()));
+    assert!(patterns.is_excluded_dir("a/c".into()));
+    assert!(patterns.is_excluded_dir("b".into()));
+    assert!(
+        patterns.is_included("a/file".into(), false),
+        "files in parents are included"
+    );
+    assert!(!patterns.is_included("a/c/file".into(), false));
+}
@@ -0,0 +1,80 @@
+//! Create and verify bundle files, which contain a pack along with references, similar to `git bundle`.
+//!
+//! [`Repository::create_bundle()`](crate::Repository::create_bundle()) writes a bundle of all objects selected by
+//! rev-specs, and [`Repository::verify_bundle()`](crate::Repository::verify_bundle()) checks if all prerequisites of a
+//! bundle are present so it can be fetched from.
+//!
+//! Fetching from bundles, and cloning them, is possible by using their path as remote URL.
+pub use gix_bundle::{File, Header, Prerequisite, Ref, Version};
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "bundle")]
+pub mod create {
+    /// Options for use in [`Repository::create_bundle()`](crate::Repository::create_bundle()).
+    #[derive(Debug, Default, Clone)]
+    pub struct Options {
+        /// The version of the bundle to write, or `None` to use the lowest version able to store objects of the repository.
+        pub version: Option<gix_bundle::Version>,
+        /// The amount of threads to use at most when compressing objects, or `None` to use all logical cores.
+        pub thread_limit: Option<usize>,
+    }
+
+    /// The outcome of [`Repository::create_bundle()`](crate::Repository::create_bundle()).
+    #[derive(Debug, Clone)]
+    pub struct Outcome {
+        /// The header that was written before the pack.
+        pub header: gix_bundle::Header,
+        /// The amount of objects in the pack of the bundle.
+        pub num_objects: usize,
+    }
+
+    /// The error returned by [`Repository::create_bundle()`](crate::Repository::create_bundle()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        RevParse(#[from] crate::revision::spec::parse::Error),
+        #[error("Refusing to create an empty bundle as none of the rev-specs is a reference")]
+        NoReferences,
+        #[error(transparent)]
+        MergeBase(#[from] gix_revision::merge_base::Error),
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::existing::Error),
+        #[error(transparent)]
+        Walk(#[from] crate::revision::walk::Error),
+        #[error(transparent)]
+        Traverse(#[from] gix_traverse::commit::ancestors::Error),
+        #[error(transparent)]
+        ToCommit(#[from] crate::object::conversion::Error),
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        WriteHeader(#[from] gix_bundle::encode::Error),
+        #[error(transparent)]
+        CountObjects(#[from] gix_pack::data::output::count::objects::Error),
+        #[error(transparent)]
+        WritePack(#[from] gix_pack::data::output::bytes::Error<gix_pack::data::output::entry::iter_from_counts::Error>),
+        #[error("The operation was interrupted")]
+        Interrupted,
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod verify {
+    /// The error returned by [`Repository::verify_bundle()`](crate::Repository::verify_bundle()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The bundle contains {bundle} objects, but the repository uses {repository}")]
+        ObjectHash {
+            bundle: gix_hash::Kind,
+            repository: gix_hash::Kind,
+        },
+        #[error("The repository lacks these prerequisite commits of the bundle: {}", ids.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+        MissingPrerequisites { ids: Vec<gix_hash::ObjectId> },
+    }
+}
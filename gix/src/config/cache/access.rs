@@ -80,6 +80,9 @@ impl Cache {
             if let Some(textconv) = section.value(config::tree::Diff::DRIVER_TEXTCONV.name) {
                 driver.binary_to_text_command = textconv.into_owned().into();
             }
+            if let Some(xfuncname) = section.value(config::tree::Diff::DRIVER_XFUNCNAME.name) {
+                driver.xfuncname = xfuncname.into_owned().into();
+            }
             if let Some(algorithm) = section.value("algorithm") {
                 driver.algorithm = config::tree::Diff::DRIVER_ALGORITHM
                     .try_into_algorithm(algorithm)
@@ -101,6 +104,79 @@ impl Cache {
         Ok(out)
     }
 
+    #[cfg(feature = "merge")]
+    pub(crate) fn merge_drivers(&self) -> Vec<gix_merge::blob::Driver> {
+        let mut out = Vec::<gix_merge::blob::Driver>::new();
+        for section in self
+            .resolved
+            .sections_by_name("merge")
+            .into_iter()
+            .flatten()
+            .filter(|s| (self.filter_config_section)(s.meta()))
+        {
+            let Some(name) = section.header().subsection_name().filter(|n| !n.is_empty()) else {
+                continue;
+            };
+
+            let driver = match out.iter_mut().find(|d| d.name == name) {
+                Some(existing) => existing,
+                None => {
+                    out.push(gix_merge::blob::Driver {
+                        name: name.into(),
+                        display_name: name.into(),
+                        command: Default::default(),
+                        recursive: None,
+                    });
+                    out.last_mut().expect("just pushed")
+                }
+            };
+
+            if let Some(display_name) = section.value(config::tree::Merge::DRIVER_NAME.name) {
+                driver.display_name = display_name.into_owned();
+            }
+            if let Some(command) = section.value(config::tree::Merge::DRIVER_COMMAND.name) {
+                driver.command = command.into_owned();
+            }
+            if let Some(recursive) = section.value(config::tree::Merge::DRIVER_RECURSIVE.name) {
+                driver.recursive = Some(recursive.into_owned());
+            }
+        }
+        out
+    }
+
+    #[cfg(feature = "merge")]
+    pub(crate) fn merge_pipeline_options(
+        &self,
+    ) -> Result<gix_merge::blob::platform::Options, config::merge::pipeline_options::Error> {
+        use crate::config::cache::util::ApplyLeniencyDefault;
+        use gix_merge::blob::builtin_driver::text;
+
+        let style = self
+            .resolved
+            .string("merge", None, config::tree::Merge::CONFLICT_STYLE.name)
+            .map(|value| {
+                config::tree::Merge::CONFLICT_STYLE
+                    .try_into_conflict_style(value)
+                    .with_lenient_default(self.lenient_config)
+            })
+            .transpose()?
+            .unwrap_or_default();
+        Ok(gix_merge::blob::platform::Options {
+            text: text::Options {
+                diff_algorithm: self.diff_algorithm()?,
+                conflict: text::Conflict::Keep {
+                    style,
+                    marker_size: text::Conflict::DEFAULT_MARKER_SIZE,
+                },
+            },
+            resolve_binary_with: None,
+            default_driver: self
+                .resolved
+                .string("merge", None, config::tree::Merge::DEFAULT.name)
+                .map(std::borrow::Cow::into_owned),
+        })
+    }
+
     #[cfg(feature = "blob-diff")]
     pub(crate) fn diff_pipeline_options(
         &self,
@@ -211,6 +287,32 @@ impl Cache {
             .into()
     }
 
+    /// The paths of all files listing revisions to ignore when blaming, in the order they were configured in `blame.ignoreRevsFile`.
+    /// Empty values are returned as empty paths.
+    #[cfg(feature = "blame")]
+    pub(crate) fn blame_ignore_revs_files(&self) -> Result<Vec<PathBuf>, gix_config::path::interpolate::Error> {
+        let install_dir = crate::path::install_dir().ok();
+        let home = self.home_dir();
+        let ctx = config::cache::interpolate_context(install_dir.as_deref(), home.as_deref());
+        self.resolved
+            .strings_filter(
+                "blame",
+                None,
+                config::tree::Blame::IGNORE_REVS_FILE.name,
+                &mut self.filter_config_section.clone(),
+            )
+            .unwrap_or_default()
+            .into_iter()
+            .map(|value| {
+                if value.is_empty() {
+                    Ok(PathBuf::new())
+                } else {
+                    gix_config::Path::from(value).interpolate(ctx).map(Cow::into_owned)
+                }
+            })
+            .collect()
+    }
+
     /// A helper to obtain a file from trusted configuration at `section_name`, `subsection_name`, and `key`, which is interpolated
     /// if present.
     pub(crate) fn trusted_file_path(
@@ -262,6 +364,21 @@ impl Cache {
         })
     }
 
+    /// Return `Some(cone)` if `core.sparseCheckout` is enabled, with `cone` being the value of `core.sparseCheckoutCone`.
+    #[cfg(feature = "excludes")]
+    pub(crate) fn sparse_checkout(&self) -> Result<Option<bool>, boolean::Error> {
+        if !boolean(self, "core.sparseCheckout", &Core::SPARSE_CHECKOUT, false)? {
+            return Ok(None);
+        }
+        boolean(self, "core.sparseCheckoutCone", &Core::SPARSE_CHECKOUT_CONE, false).map(Some)
+    }
+
+    /// Return `true` if `index.sparse` is enabled, and sparse directory entries should be written in cone mode.
+    #[cfg(feature = "worktree-mutation")]
+    pub(crate) fn sparse_index(&self) -> Result<bool, boolean::Error> {
+        boolean(self, "index.sparse", &config::tree::Index::SPARSE, false)
+    }
+
     /// Collect everything needed to checkout files into a worktree.
     /// Note that some of the options being returned will be defaulted so safe settings, the caller might have to override them
     /// depending on the use-case.
@@ -310,6 +427,7 @@ impl Cache {
             destination_is_initially_empty: false,
             overwrite_existing: false,
             keep_going: false,
+            sparse_checkout: repo.sparse_checkout_patterns()?,
             stat_options: self.stat_options().map_err(|err| match err {
                 config::stat_options::Error::ConfigCheckStat(err) => {
                     config::checkout_options::Error::ConfigCheckStat(err)
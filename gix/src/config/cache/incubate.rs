@@ -13,6 +13,8 @@ pub(crate) struct StageOne {
     pub is_bare: bool,
     pub lossy: Option<bool>,
     pub object_hash: gix_hash::Kind,
+    /// If `true`, references are stored in a reftable.
+    pub reftable: bool,
     pub reflog: Option<gix_ref::store::WriteReflog>,
     pub precompose_unicode: bool,
 }
@@ -53,6 +55,15 @@ impl StageOne {
             })
             .transpose()?
             .unwrap_or(gix_hash::Kind::Sha1);
+        let reftable = (repo_format_version == 1)
+            .then(|| {
+                config
+                    .string("extensions", None, "refStorage")
+                    .map(|storage| Extensions::REF_STORAGE.try_into_is_reftable(storage))
+            })
+            .flatten()
+            .transpose()?
+            .unwrap_or_default();
 
         let extension_worktree = util::config_bool(
             &config,
@@ -87,6 +98,7 @@ impl StageOne {
             is_bare,
             lossy,
             object_hash,
+            reftable,
             reflog,
             precompose_unicode,
         })
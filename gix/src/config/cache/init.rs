@@ -26,6 +26,7 @@ impl Cache {
             lossy,
             is_bare,
             object_hash,
+            reftable: _,
             reflog: _,
             precompose_unicode: _,
         }: StageOne,
@@ -596,6 +597,11 @@ fn apply_environment_overrides(
                 let key = &Core::USE_REPLACE_REFS;
                 (env(key), key.name, objects)
             },
+            #[cfg(feature = "notes")]
+            {
+                let key = &Core::NOTES_REF;
+                (env(key), key.name, git_prefix)
+            },
         ] {
             if let Some(value) = var_as_bstring(var, permission) {
                 section.push_with_comment(
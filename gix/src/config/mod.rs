@@ -160,6 +160,25 @@ pub mod diff {
     }
 }
 
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "merge")]
+pub mod merge {
+    ///
+    #[allow(clippy::empty_docs)]
+    pub mod pipeline_options {
+        /// The error produced when obtaining options needed to fill in [gix_merge::blob::platform::Options].
+        #[derive(Debug, thiserror::Error)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            #[error(transparent)]
+            DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+            #[error(transparent)]
+            ConflictStyle(#[from] crate::config::key::GenericErrorWithValue),
+        }
+    }
+}
+
 ///
 #[allow(clippy::empty_docs)]
 pub mod stat_options {
@@ -193,6 +212,8 @@ pub mod checkout_options {
         FilterPipelineOptions(#[from] crate::filter::pipeline::options::Error),
         #[error(transparent)]
         CommandContext(#[from] crate::config::command_context::Error),
+        #[error(transparent)]
+        SparseCheckout(#[from] crate::sparse::patterns::Error),
     }
 }
 
@@ -18,6 +18,9 @@ pub(crate) mod root {
     impl Tree {
         /// The `author` section.
         pub const AUTHOR: sections::Author = sections::Author;
+        /// The `blame` section.
+        #[cfg(feature = "blame")]
+        pub const BLAME: sections::Blame = sections::Blame;
         /// The `branch` section.
         pub const BRANCH: sections::Branch = sections::Branch;
         /// The `checkout` section.
@@ -37,6 +40,9 @@ pub(crate) mod root {
         pub const EXTENSIONS: sections::Extensions = sections::Extensions;
         /// The `fetch` section.
         pub const FETCH: sections::Fetch = sections::Fetch;
+        /// The `gc` section.
+        #[cfg(feature = "gc")]
+        pub const GC: sections::Gc = sections::Gc;
         /// The `gitoxide` section.
         pub const GITOXIDE: sections::Gitoxide = sections::Gitoxide;
         /// The `http` section.
@@ -47,14 +53,26 @@ pub(crate) mod root {
         pub const INIT: sections::Init = sections::Init;
         /// The `mailmap` section.
         pub const MAILMAP: sections::Mailmap = sections::Mailmap;
+        /// The `merge` section.
+        #[cfg(feature = "merge")]
+        pub const MERGE: sections::Merge = sections::Merge;
+        /// The `notes` section.
+        #[cfg(feature = "notes")]
+        pub const NOTES: sections::Notes = sections::Notes;
         /// The `pack` section.
         pub const PACK: sections::Pack = sections::Pack;
         /// The `protocol` section.
         pub const PROTOCOL: sections::Protocol = sections::Protocol;
         /// The `push` section.
         pub const PUSH: sections::Push = sections::Push;
+        /// The `rebase` section.
+        #[cfg(feature = "rebase")]
+        pub const REBASE: sections::Rebase = sections::Rebase;
         /// The `remote` section.
         pub const REMOTE: sections::Remote = sections::Remote;
+        /// The `repack` section.
+        #[cfg(feature = "gc")]
+        pub const REPACK: sections::Repack = sections::Repack;
         /// The `safe` section.
         pub const SAFE: sections::Safe = sections::Safe;
         /// The `ssh` section.
@@ -71,6 +89,8 @@ pub(crate) mod root {
         pub fn sections(&self) -> &[&dyn Section] {
             &[
                 &Self::AUTHOR,
+                #[cfg(feature = "blame")]
+                &Self::BLAME,
                 &Self::BRANCH,
                 &Self::CHECKOUT,
                 &Self::CLONE,
@@ -81,15 +101,25 @@ pub(crate) mod root {
                 &Self::DIFF,
                 &Self::EXTENSIONS,
                 &Self::FETCH,
+                #[cfg(feature = "gc")]
+                &Self::GC,
                 &Self::GITOXIDE,
                 &Self::HTTP,
                 &Self::INDEX,
                 &Self::INIT,
                 &Self::MAILMAP,
+                #[cfg(feature = "merge")]
+                &Self::MERGE,
+                #[cfg(feature = "notes")]
+                &Self::NOTES,
                 &Self::PACK,
                 &Self::PROTOCOL,
                 &Self::PUSH,
+                #[cfg(feature = "rebase")]
+                &Self::REBASE,
                 &Self::REMOTE,
+                #[cfg(feature = "gc")]
+                &Self::REPACK,
                 &Self::SAFE,
                 &Self::SSH,
                 #[cfg(feature = "status")]
@@ -102,6 +132,8 @@ pub(crate) mod root {
 }
 
 mod sections;
+#[cfg(feature = "blame")]
+pub use sections::Blame;
 pub use sections::{
     branch, checkout, core, credential, extensions, fetch, gitoxide, http, index, protocol, push, remote, ssh, Author,
     Branch, Checkout, Clone, Committer, Core, Credential, Extensions, Fetch, Gitoxide, Http, Index, Init, Mailmap,
@@ -109,6 +141,14 @@ pub use sections::{
 };
 #[cfg(feature = "blob-diff")]
 pub use sections::{diff, Diff};
+#[cfg(feature = "gc")]
+pub use sections::{gc, Gc, Repack};
+#[cfg(feature = "merge")]
+pub use sections::{merge, Merge};
+#[cfg(feature = "notes")]
+pub use sections::{notes, Notes};
+#[cfg(feature = "rebase")]
+pub use sections::{rebase, Rebase};
 #[cfg(feature = "status")]
 pub use sections::{status, Status};
 
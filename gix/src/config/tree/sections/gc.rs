@@ -0,0 +1,144 @@
+use crate::{
+    config,
+    config::tree::{keys, Gc, Key, Section},
+};
+
+impl Gc {
+    /// The `gc.auto` key.
+    pub const AUTO: keys::UnsignedInteger = keys::UnsignedInteger::new_unsigned_integer("auto", &config::Tree::GC);
+    /// The `gc.autoPackLimit` key.
+    pub const AUTO_PACK_LIMIT: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("autoPackLimit", &config::Tree::GC);
+    /// The `gc.cruftPacks` key.
+    pub const CRUFT_PACKS: keys::Boolean = keys::Boolean::new_boolean("cruftPacks", &config::Tree::GC);
+    /// The `gc.packRefs` key.
+    pub const PACK_REFS: PackRefs = PackRefs::new_with_validate("packRefs", &config::Tree::GC, validate::PackRefs);
+    /// The `gc.pruneExpire` key.
+    pub const PRUNE_EXPIRE: Expiry = Expiry::new_with_validate("pruneExpire", &config::Tree::GC, validate::Expiry);
+    /// The `gc.reflogExpire` key.
+    pub const REFLOG_EXPIRE: Expiry = Expiry::new_with_validate("reflogExpire", &config::Tree::GC, validate::Expiry)
+        .with_deviation("Per-reference patterns like `gc.<pattern>.reflogExpire` aren't supported");
+    /// The `gc.reflogExpireUnreachable` key.
+    pub const REFLOG_EXPIRE_UNREACHABLE: Expiry =
+        Expiry::new_with_validate("reflogExpireUnreachable", &config::Tree::GC, validate::Expiry)
+            .with_deviation("Per-reference patterns like `gc.<pattern>.reflogExpireUnreachable` aren't supported");
+    /// The `gc.writeCommitGraph` key.
+    pub const WRITE_COMMIT_GRAPH: keys::Boolean = keys::Boolean::new_boolean("writeCommitGraph", &config::Tree::GC);
+}
+
+impl Section for Gc {
+    fn name(&self) -> &str {
+        "gc"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[
+            &Self::AUTO,
+            &Self::AUTO_PACK_LIMIT,
+            &Self::CRUFT_PACKS,
+            &Self::PACK_REFS,
+            &Self::PRUNE_EXPIRE,
+            &Self::REFLOG_EXPIRE,
+            &Self::REFLOG_EXPIRE_UNREACHABLE,
+            &Self::WRITE_COMMIT_GRAPH,
+        ]
+    }
+}
+
+/// The `gc.pruneExpire` key and other keys that describe a point in time before which something expires.
+pub type Expiry = keys::Any<validate::Expiry>;
+
+/// The `gc.packRefs` key.
+pub type PackRefs = keys::Any<validate::PackRefs>;
+
+mod expiry {
+    use std::{borrow::Cow, time::SystemTime};
+
+    use crate::{
+        bstr::{BStr, ByteSlice},
+        config,
+        config::tree::gc::Expiry,
+    };
+
+    impl Expiry {
+        /// Interpret `value` as point in time relative to `now`, before which items expire, or `None` if they never expire.
+        ///
+        /// Besides dates, values like `2.weeks.ago` or `90 days` are supported, as well as `now` and `all` to expire
+        /// everything, and `never` or `false` to expire nothing.
+        pub fn try_into_expiry(
+            &'static self,
+            value: Cow<'_, BStr>,
+            now: SystemTime,
+        ) -> Result<Option<SystemTime>, config::key::GenericErrorWithValue<gix_date::parse::Error>> {
+            let input = value.to_str_lossy();
+            let input = input.trim().to_ascii_lowercase();
+            Ok(match input.as_str() {
+                "never" | "false" => None,
+                "now" | "all" => Some(now),
+                _ => {
+                    let time = gix_date::parse(&input, Some(now)).or_else(|_| {
+                        let mut relative = input.replace(['.', '_'], " ");
+                        if !relative.ends_with("ago") {
+                            relative.push_str(" ago");
+                        }
+                        gix_date::parse(&relative, Some(now))
+                    });
+                    let time = time.map_err(|err| {
+                        config::key::GenericErrorWithValue::from_value(self, value.clone().into_owned())
+                            .with_source(err)
+                    })?;
+                    Some(if time.seconds < 0 {
+                        SystemTime::UNIX_EPOCH - std::time::Duration::from_secs(time.seconds.unsigned_abs())
+                    } else {
+                        SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(time.seconds as u64)
+                    })
+                }
+            })
+        }
+    }
+}
+
+mod pack_refs {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::gc::PackRefs};
+
+    impl PackRefs {
+        /// Interpret `value` as boolean or as `notbare`, which enables packing references only if the repository isn't bare
+        /// as indicated by `is_bare`.
+        pub fn try_into_pack_refs(
+            &'static self,
+            value: Cow<'_, BStr>,
+            is_bare: bool,
+        ) -> Result<bool, config::key::GenericErrorWithValue> {
+            if value.as_ref() == "notbare" {
+                return Ok(!is_bare);
+            }
+            gix_config::Boolean::try_from(value.as_ref())
+                .map(Into::into)
+                .map_err(|err| {
+                    config::key::GenericErrorWithValue::from_value(self, value.into_owned()).with_source(err)
+                })
+        }
+    }
+}
+
+mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct Expiry;
+    impl keys::Validate for Expiry {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Gc::PRUNE_EXPIRE.try_into_expiry(value.into(), std::time::SystemTime::now())?;
+            Ok(())
+        }
+    }
+
+    pub struct PackRefs;
+    impl keys::Validate for PackRefs {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Gc::PACK_REFS.try_into_pack_refs(value.into(), false)?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::config;
+use crate::config::tree::sections::Merge;
+use crate::config::tree::{keys, Key, Section, SubSectionRequirement};
+
+impl Merge {
+    /// The `merge.conflictStyle` key.
+    pub const CONFLICT_STYLE: ConflictStyle =
+        ConflictStyle::new_with_validate("conflictStyle", &config::Tree::MERGE, validate::ConflictStyle);
+    /// The `merge.default` key.
+    pub const DEFAULT: keys::String = keys::String::new_string("default", &config::Tree::MERGE);
+    /// The `merge.renames` key.
+    pub const RENAMES: super::diff::Renames = super::diff::Renames::new_renames("renames", &config::Tree::MERGE);
+    /// The `merge.renameLimit` key.
+    pub const RENAME_LIMIT: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("renameLimit", &config::Tree::MERGE);
+
+    /// The `merge.<driver>.name` key.
+    pub const DRIVER_NAME: keys::String = keys::String::new_string("name", &config::Tree::MERGE)
+        .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")));
+    /// The `merge.<driver>.driver` key.
+    pub const DRIVER_COMMAND: keys::Program = keys::Program::new_program("driver", &config::Tree::MERGE)
+        .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")));
+    /// The `merge.<driver>.recursive` key.
+    pub const DRIVER_RECURSIVE: keys::String = keys::String::new_string("recursive", &config::Tree::MERGE)
+        .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")));
+}
+
+/// The `merge.conflictStyle` key.
+pub type ConflictStyle = keys::Any<validate::ConflictStyle>;
+
+mod conflict_style {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::sections::merge::ConflictStyle};
+    use gix_merge::blob::builtin_driver::text;
+
+    impl ConflictStyle {
+        /// Derive the conflict style identified by `value`.
+        pub fn try_into_conflict_style(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<text::ConflictStyle, config::key::GenericErrorWithValue> {
+            use crate::bstr::ByteSlice;
+            Ok(match value.as_ref().as_bytes() {
+                b"merge" => text::ConflictStyle::Merge,
+                b"diff3" => text::ConflictStyle::Diff3,
+                b"zdiff3" => text::ConflictStyle::ZealousDiff3,
+                _ => return Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned())),
+            })
+        }
+    }
+}
+
+impl Section for Merge {
+    fn name(&self) -> &str {
+        "merge"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[
+            &Self::CONFLICT_STYLE,
+            &Self::DEFAULT,
+            &Self::RENAMES,
+            &Self::RENAME_LIMIT,
+            &Self::DRIVER_NAME,
+            &Self::DRIVER_COMMAND,
+            &Self::DRIVER_RECURSIVE,
+        ]
+    }
+}
+
+mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct ConflictStyle;
+    impl keys::Validate for ConflictStyle {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Merge::CONFLICT_STYLE.try_into_conflict_style(value.into())?;
+            Ok(())
+        }
+    }
+}
@@ -5,6 +5,13 @@
 pub struct Author;
 mod author;
 
+/// The `blame` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "blame")]
+pub struct Blame;
+#[cfg(feature = "blame")]
+mod blame;
+
 /// The `branch` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Branch;
@@ -52,6 +59,13 @@ pub mod extensions;
 pub struct Fetch;
 pub mod fetch;
 
+/// The `gc` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "gc")]
+pub struct Gc;
+#[cfg(feature = "gc")]
+pub mod gc;
+
 /// The `gitoxide` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Gitoxide;
@@ -76,6 +90,20 @@ mod init;
 pub struct Mailmap;
 mod mailmap;
 
+/// The `merge` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "merge")]
+pub struct Merge;
+#[cfg(feature = "merge")]
+pub mod merge;
+
+/// The `notes` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "notes")]
+pub struct Notes;
+#[cfg(feature = "notes")]
+pub mod notes;
+
 /// The `pack` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Pack;
@@ -91,11 +119,25 @@ pub mod protocol;
 pub struct Push;
 pub mod push;
 
+/// The `rebase` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "rebase")]
+pub struct Rebase;
+#[cfg(feature = "rebase")]
+pub mod rebase;
+
 /// The `remote` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Remote;
 pub mod remote;
 
+/// The `repack` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "gc")]
+pub struct Repack;
+#[cfg(feature = "gc")]
+mod repack;
+
 /// The `safe` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Safe;
@@ -0,0 +1,55 @@
+use crate::config::tree::{keys, Key, Notes, Section, SubSectionRequirement};
+
+impl Notes {
+    /// The `notes.displayRef` key.
+    pub const DISPLAY_REF: keys::String = keys::String::new_string("displayRef", &crate::config::Tree::NOTES);
+    /// The `notes.mergeStrategy` key.
+    pub const MERGE_STRATEGY: MergeStrategy =
+        MergeStrategy::new_with_validate("mergeStrategy", &crate::config::Tree::NOTES, validate::MergeStrategy);
+    /// The `notes.<name>.mergeStrategy` key.
+    pub const REF_MERGE_STRATEGY: MergeStrategy =
+        MergeStrategy::new_with_validate("mergeStrategy", &crate::config::Tree::NOTES, validate::MergeStrategy)
+            .with_subsection_requirement(Some(SubSectionRequirement::Parameter("name")));
+}
+
+impl Section for Notes {
+    fn name(&self) -> &str {
+        "notes"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::DISPLAY_REF, &Self::MERGE_STRATEGY, &Self::REF_MERGE_STRATEGY]
+    }
+}
+
+/// The `notes.mergeStrategy` key.
+pub type MergeStrategy = keys::Any<validate::MergeStrategy>;
+
+mod merge_strategy {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::sections::notes::MergeStrategy};
+
+    impl MergeStrategy {
+        /// Derive the strategy for merging notes identified by `value`.
+        pub fn try_into_strategy(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<gix_note::merge::Strategy, config::key::GenericErrorWithValue> {
+            gix_note::merge::Strategy::from_name(value.as_ref())
+                .ok_or_else(|| config::key::GenericErrorWithValue::from_value(self, value.into_owned()))
+        }
+    }
+}
+
+mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct MergeStrategy;
+    impl keys::Validate for MergeStrategy {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Notes::MERGE_STRATEGY.try_into_strategy(value.into())?;
+            Ok(())
+        }
+    }
+}
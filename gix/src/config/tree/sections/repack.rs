@@ -0,0 +1,16 @@
+use crate::config::tree::{keys, Key, Repack, Section};
+
+impl Repack {
+    /// The `repack.writeBitmaps` key.
+    pub const WRITE_BITMAPS: keys::Boolean = keys::Boolean::new_boolean("writeBitmaps", &crate::config::Tree::REPACK);
+}
+
+impl Section for Repack {
+    fn name(&self) -> &str {
+        "repack"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::WRITE_BITMAPS]
+    }
+}
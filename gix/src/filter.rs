@@ -107,6 +107,7 @@ impl<'repo> Pipeline<'repo> {
         let drivers = extract_drivers(repo)?;
         Ok(gix_filter::pipeline::Options {
             drivers,
+            builtin_drivers: Vec::new(),
             eol_config: gix_filter::eol::Configuration { auto_crlf, eol },
             encodings_with_roundtrip_check: encodings,
             crlf_roundtrip_check: safe_crlf,
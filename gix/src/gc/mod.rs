@@ -0,0 +1,136 @@
+//! Maintain the object database and the references of a repository similar to `git gc`, to keep it fast and small.
+//!
+//! [`Repository::gc()`](crate::Repository::gc()) performs all maintenance tasks with [options](Options) that are typically
+//! obtained from the configuration with [`Repository::gc_options()`](crate::Repository::gc_options()).
+//! [`Repository::gc_auto()`](crate::Repository::gc_auto()) does so only if the thresholds of `gc.auto` and `gc.autoPackLimit`
+//! are exceeded, which makes it suitable to be called after each fetch.
+//!
+//! Each task is also available on its own, see [`Repository::pack_refs()`](crate::Repository::pack_refs()),
+//! [`Repository::expire_reflogs()`](crate::Repository::expire_reflogs()), [`Repository::repack()`](crate::Repository::repack())
+//! and [`Repository::prune_loose_objects()`](crate::Repository::prune_loose_objects()).
+use std::time::SystemTime;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod pack_refs;
+///
+#[allow(clippy::empty_docs)]
+pub mod prune;
+///
+#[allow(clippy::empty_docs)]
+pub mod reachable;
+///
+#[allow(clippy::empty_docs)]
+pub mod reflog;
+///
+#[allow(clippy::empty_docs)]
+pub mod repack;
+
+pub(crate) mod packs;
+
+/// The object database used to count and pack objects, which keeps all packs loaded and ignores replacements.
+pub(crate) type Db = gix_odb::Cache<gix_odb::store::Handle<std::sync::Arc<gix_odb::Store>>>;
+
+pub(crate) fn object_database(repo: &crate::Repository) -> std::io::Result<Db> {
+    let mut db = repo.objects.clone().into_arc()?;
+    db.prevent_pack_unload();
+    db.ignore_replacements = true;
+    Ok(db)
+}
+
+/// Options to configure what [`Repository::gc()`](crate::Repository::gc()) does.
+///
+/// Use [`Repository::gc_options()`](crate::Repository::gc_options()) to obtain them as configured.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// If `true`, move all loose references into the `packed-refs` file, or compact all reftables into one.
+    pub pack_refs: bool,
+    /// Determine which reflog entries to remove.
+    pub reflog: reflog::Options,
+    /// Determine how to consolidate packs and loose objects.
+    pub repack: repack::Options,
+    /// Delete unreachable loose objects that were last modified at or before this time, or keep them if `None`.
+    pub prune_expire: Option<SystemTime>,
+    /// If `true`, write a commit-graph with all reachable commits on top of the existing one.
+    pub write_commit_graph: bool,
+}
+
+/// The reason for [`Repository::gc_auto()`](crate::Repository::gc_auto()) to perform maintenance, as returned by
+/// [`Repository::needs_gc()`](crate::Repository::needs_gc()).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Trigger {
+    /// There are more packs than allowed by `gc.autoPackLimit`, which is why all of them are repacked into one.
+    TooManyPacks {
+        /// The amount of packs that aren't kept.
+        num_packs: usize,
+        /// The configured limit.
+        limit: u64,
+    },
+    /// There are likely more loose objects than allowed by `gc.auto`, which is why they are packed.
+    TooManyLooseObjects {
+        /// The estimated amount of loose objects, based on the amount found in a single fan-out directory.
+        estimated_objects: u64,
+        /// The configured limit.
+        limit: u64,
+    },
+}
+
+/// The outcome of [`Repository::gc()`](crate::Repository::gc()).
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The outcome of packing references, if enabled.
+    pub pack_refs: Option<pack_refs::Outcome>,
+    /// The outcome of expiring reflog entries.
+    pub reflog: reflog::Outcome,
+    /// The outcome of repacking objects.
+    pub repack: repack::Outcome,
+    /// The outcome of pruning unreachable loose objects, if enabled.
+    pub prune: Option<prune::Outcome>,
+    /// The outcome of writing the commit-graph, if it was written.
+    pub commit_graph: Option<gix_commitgraph::write::Outcome>,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod options {
+    /// The error returned by [`Repository::gc_options()`](crate::Repository::gc_options()) and
+    /// [`Repository::needs_gc()`](crate::Repository::needs_gc()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Boolean(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        UnsignedInteger(#[from] crate::config::unsigned_integer::Error),
+        #[error(transparent)]
+        Value(#[from] crate::config::key::GenericErrorWithValue),
+        #[error(transparent)]
+        Expiry(#[from] crate::config::key::GenericErrorWithValue<gix_date::parse::Error>),
+    }
+}
+
+mod error {
+    /// The error returned by [`Repository::gc()`](crate::Repository::gc()) and
+    /// [`Repository::gc_auto()`](crate::Repository::gc_auto()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Options(#[from] super::options::Error),
+        #[error("Could not count the objects in the object database")]
+        CountObjects(#[from] std::io::Error),
+        #[error(transparent)]
+        PackRefs(#[from] super::pack_refs::Error),
+        #[error(transparent)]
+        Reflog(#[from] super::reflog::Error),
+        #[error(transparent)]
+        Reachable(#[from] super::reachable::Error),
+        #[error(transparent)]
+        Repack(#[from] super::repack::Error),
+        #[error(transparent)]
+        Prune(#[from] super::prune::Error),
+        #[error(transparent)]
+        CommitGraph(#[from] crate::repository::write_commit_graph::Error),
+    }
+}
+pub use error::Error;
@@ -0,0 +1,90 @@
+use gix_ref::{
+    file::transaction::PackedRefs,
+    transaction::{Change, LogChange, PreviousValue, RefEdit},
+};
+
+use crate::Repository;
+
+/// The outcome of [`Repository::pack_refs()`](crate::Repository::pack_refs()).
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of loose references that were moved into the `packed-refs` file.
+    pub num_packed_refs: usize,
+    /// If `true`, the references are stored in reftables which were compacted into one.
+    pub compacted_reftables: bool,
+}
+
+mod error {
+    /// The error returned by [`Repository::pack_refs()`](crate::Repository::pack_refs()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not traverse loose references")]
+        Traverse(#[from] std::io::Error),
+        #[error(transparent)]
+        LooseReference(#[from] gix_ref::file::iter::loose_then_packed::Error),
+        #[error(transparent)]
+        Edit(#[from] crate::reference::edit::Error),
+        #[error(transparent)]
+        CompactReftables(#[from] gix_ref::reftable::transaction::Error),
+    }
+}
+pub use error::Error;
+
+/// Move all loose references of `repo` that aren't private to a worktree and that point to an object into the
+/// `packed-refs` file, like `git pack-refs --all --prune`.
+pub(crate) fn run(repo: &Repository) -> Result<Outcome, Error> {
+    let (file_lock_fail, packed_refs_lock_fail) = repo
+        .config
+        .lock_timeout()
+        .map_err(crate::reference::edit::Error::from)?;
+    if let Some(reftable) = repo.refs.reftable() {
+        reftable.compact(file_lock_fail)?;
+        return Ok(Outcome {
+            num_packed_refs: 0,
+            compacted_reftables: true,
+        });
+    }
+
+    let mut edits = Vec::new();
+    for reference in repo.refs.loose_iter()? {
+        let reference = reference?;
+        if reference.kind() != gix_ref::Kind::Peeled
+            || !reference.name.as_bstr().starts_with(b"refs/")
+            || matches!(reference.name.category(), Some(category) if category.is_worktree_private())
+        {
+            continue;
+        }
+        edits.push(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::MustExistAndMatch(reference.target.clone()),
+                new: reference.target,
+            },
+            name: reference.name,
+            deref: false,
+        });
+    }
+    if edits.is_empty() {
+        return Ok(Outcome::default());
+    }
+
+    let num_packed_refs = edits.len();
+    repo.refs
+        .transaction()
+        .packed_refs(PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(
+            Box::new(&repo.objects),
+        ))
+        .prepare(edits, file_lock_fail, packed_refs_lock_fail)
+        .map_err(crate::reference::edit::Error::from)?
+        .commit(
+            repo.committer()
+                .transpose()
+                .map_err(crate::reference::edit::Error::ParseCommitterTime)?,
+        )
+        .map_err(crate::reference::edit::Error::from)?;
+    Ok(Outcome {
+        num_packed_refs,
+        compacted_reftables: false,
+    })
+}
@@ -0,0 +1,171 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use gix_hash::oid;
+
+/// A pack in the `objects/pack` directory of a repository.
+pub(crate) struct Pack {
+    /// The path to the `.pack` file.
+    pub data_path: PathBuf,
+    pub index: gix_pack::index::File,
+    /// If `true`, the pack must not be deleted and its objects must not be repacked, as it has a `.keep` or `.promisor` file.
+    pub keep: bool,
+    /// The time at which the pack was last modified.
+    pub mtime: SystemTime,
+    /// The modification time of each object, if this is a cruft pack.
+    pub mtimes: Option<gix_pack::index::mtimes::File>,
+}
+
+impl Pack {
+    pub fn contains(&self, id: &oid) -> bool {
+        self.index.lookup(id).is_some()
+    }
+
+    /// The time at which object `id` was last modified, assuming it is contained in this pack.
+    pub fn mtime_of(&self, id: &oid) -> SystemTime {
+        self.mtimes
+            .as_ref()
+            .zip(self.index.lookup(id))
+            .and_then(|(mtimes, index)| mtimes.mtime_at(index))
+            .map_or(self.mtime, |secs| {
+                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs.into())
+            })
+    }
+}
+
+/// The extensions of all files that belong to a pack, with the index coming first as it makes the pack visible.
+const EXTENSIONS: &[&str] = &["idx", "pack", "rev", "bitmap", "mtimes", "keep", "promisor"];
+
+/// Return all packs in `pack_dir` that have an index.
+pub(crate) fn at(pack_dir: &Path, object_hash: gix_hash::Kind) -> std::io::Result<Vec<Pack>> {
+    let mut packs = Vec::new();
+    let entries = match std::fs::read_dir(pack_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(packs),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let index_path = entry?.path();
+        if !matches!(index_path.extension(), Some(ext) if ext == "idx") {
+            continue;
+        }
+        let data_path = index_path.with_extension("pack");
+        let Ok(metadata) = std::fs::metadata(&data_path) else {
+            continue;
+        };
+        let index = gix_pack::index::File::at(&index_path, object_hash)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        let mtimes = gix_pack::index::mtimes::File::at(index_path.with_extension("mtimes"), object_hash).ok();
+        packs.push(Pack {
+            keep: index_path.with_extension("keep").is_file() || index_path.with_extension("promisor").is_file(),
+            mtime: metadata.modified()?,
+            mtimes,
+            index,
+            data_path,
+        });
+    }
+    packs.sort_by(|a, b| a.data_path.cmp(&b.data_path));
+    Ok(packs)
+}
+
+/// Return the amount of packs in `pack_dir` that have an index and that are neither kept nor cruft packs, without opening them.
+pub(crate) fn count_unkept(pack_dir: &Path) -> std::io::Result<usize> {
+    let entries = match std::fs::read_dir(pack_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err),
+    };
+    let mut count = 0;
+    for entry in entries {
+        let index_path = entry?.path();
+        if matches!(index_path.extension(), Some(ext) if ext == "idx")
+            && index_path.with_extension("pack").is_file()
+            && !index_path.with_extension("keep").is_file()
+            && !index_path.with_extension("promisor").is_file()
+            && !index_path.with_extension("mtimes").is_file()
+        {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Remove all files belonging to the pack at `data_path`.
+pub(crate) fn remove(data_path: &Path) -> std::io::Result<()> {
+    for extension in EXTENSIONS {
+        match std::fs::remove_file(data_path.with_extension(extension)) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Delete all loose objects in `objects_dir` that are also contained in one of the `packs`, and return their amount.
+pub(crate) fn prune_packed(
+    objects_dir: &Path,
+    object_hash: gix_hash::Kind,
+    packs: &[Pack],
+) -> Result<usize, gix_odb::loose::iter::Error> {
+    let loose = gix_odb::loose::Store::at(objects_dir, object_hash);
+    let mut removed = 0;
+    for id in loose.iter() {
+        let id = id?;
+        if packs.iter().any(|pack| pack.contains(&id)) {
+            let path = loose.object_path(&id);
+            if std::fs::remove_file(&path).is_ok() {
+                removed += 1;
+                if let Some(parent) = path.parent() {
+                    std::fs::remove_dir(parent).ok();
+                }
+            }
+        }
+    }
+    Ok(removed)
+}
+
+/// Rewrite the multi-pack index in `pack_dir` so it refers to all `packs` if it exists, along with
+/// removing the files that belong to the previous one.
+pub(crate) fn update_multi_pack_index(
+    pack_dir: &Path,
+    packs: &[Pack],
+    object_hash: gix_hash::Kind,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &std::sync::atomic::AtomicBool,
+) -> Result<(), super::repack::Error> {
+    let path = pack_dir.join("multi-pack-index");
+    if !path.is_file() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(pack_dir)? {
+        let entry_path = entry?.path();
+        let is_midx_companion = matches!(
+            entry_path.file_name().and_then(|name| name.to_str()),
+            Some(name) if name.starts_with("multi-pack-index-") && (name.ends_with(".bitmap") || name.ends_with(".rev"))
+        );
+        if is_midx_companion {
+            std::fs::remove_file(entry_path)?;
+        }
+    }
+    if packs.is_empty() {
+        std::fs::remove_file(path)?;
+        return Ok(());
+    }
+
+    let mut lock = gix_lock::File::acquire_to_update_resource(&path, gix_lock::acquire::Fail::Immediately, None)?;
+    gix_pack::multi_index::File::write_from_index_paths(
+        packs.iter().map(|pack| pack.index.path().to_owned()).collect(),
+        &mut lock,
+        progress,
+        should_interrupt,
+        gix_pack::multi_index::write::Options {
+            object_hash,
+            reverse_index: false,
+        },
+    )?;
+    lock.commit().map_err(|err| err.error)?;
+    Ok(())
+}
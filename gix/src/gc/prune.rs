@@ -0,0 +1,92 @@
+use std::time::SystemTime;
+
+use gix_pack::data::output::Count;
+
+use crate::Repository;
+
+/// The outcome of [`Repository::prune_loose_objects()`](crate::Repository::prune_loose_objects()).
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of unreachable loose objects that were deleted.
+    pub num_removed_objects: usize,
+    /// The amount of unreachable loose objects that were kept as they are too recent.
+    pub num_kept_objects: usize,
+}
+
+mod error {
+    /// The error returned by [`Repository::prune_loose_objects()`](crate::Repository::prune_loose_objects()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        OpenObjectDatabase(#[from] std::io::Error),
+        #[error(transparent)]
+        LooseObjects(#[from] gix_odb::loose::iter::Error),
+        #[error(transparent)]
+        Reachable(#[from] crate::gc::reachable::Error),
+        #[error("Could not delete loose object at '{}'", path.display())]
+        Remove {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+    }
+}
+pub use error::Error;
+
+/// Delete all loose objects in `repo` that aren't in `reachable` and that were last modified at or before `expire`,
+/// reading objects from `db`.
+///
+/// Like `git`, unreachable objects that were modified after `expire`, loose or packed, keep all objects they refer to.
+pub(crate) fn run(
+    repo: &Repository,
+    db: &super::Db,
+    reachable: &[Count],
+    expire: SystemTime,
+) -> Result<Outcome, Error> {
+    let reachable: gix_hashtable::HashSet<_> = reachable.iter().map(|count| count.id).collect();
+    let objects_dir = repo.objects.store_ref().path();
+    let loose = gix_odb::loose::Store::at(objects_dir, repo.object_hash());
+    let mut candidates = Vec::new();
+    let mut recent = Vec::new();
+    for id in loose.iter() {
+        let id = id?;
+        if reachable.contains(&id) {
+            continue;
+        }
+        let path = loose.object_path(&id);
+        let Ok(mtime) = std::fs::metadata(&path).and_then(|metadata| metadata.modified()) else {
+            continue;
+        };
+        if mtime > expire {
+            recent.push(id);
+        }
+        candidates.push((id, path, mtime));
+    }
+    for pack in super::packs::at(&objects_dir.join("pack"), repo.object_hash())? {
+        recent.extend(
+            pack.index
+                .iter()
+                .map(|entry| entry.oid)
+                .filter(|id| !reachable.contains(id) && pack.mtime_of(id) > expire),
+        );
+    }
+    let keep = super::reachable::from_recent(db, recent)?;
+
+    let mut out = Outcome::default();
+    for (id, path, mtime) in candidates {
+        if mtime > expire || keep.contains(&id) {
+            out.num_kept_objects += 1;
+            continue;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(Error::Remove { path, source: err }),
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::remove_dir(parent).ok();
+        }
+        out.num_removed_objects += 1;
+    }
+    Ok(out)
+}
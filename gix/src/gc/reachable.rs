@@ -0,0 +1,234 @@
+use std::sync::atomic::AtomicBool;
+
+use gix_features::progress::Progress;
+use gix_hash::ObjectId;
+use gix_pack::data::output::{count, Count};
+
+use crate::{bstr::ByteSlice, Repository};
+
+mod error {
+    use gix_hash::ObjectId;
+
+    /// The error returned when determining all reachable objects.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        OpenObjectDatabase(#[from] std::io::Error),
+        #[error(transparent)]
+        References(#[from] crate::reference::iter::Error),
+        #[error(transparent)]
+        ReferencesInit(#[from] crate::reference::iter::init::Error),
+        #[error("A reference could not be read")]
+        Reference(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        Head(#[from] crate::reference::find::existing::Error),
+        #[error(transparent)]
+        Reflog(#[from] gix_ref::file::log::Error),
+        #[error(transparent)]
+        OpenWorktree(#[from] crate::open::Error),
+        #[error(transparent)]
+        Index(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        DecodeTag(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        Walk(#[from] crate::revision::walk::Error),
+        #[error(transparent)]
+        Count(#[from] gix_pack::data::output::count::objects::Error),
+        #[error(transparent)]
+        FindRecent(#[from] gix_object::find::Error),
+        #[error("Could not decode object {id} which is reachable from a recent object")]
+        DecodeRecent {
+            id: ObjectId,
+            source: gix_object::decode::Error,
+        },
+    }
+}
+pub use error::Error;
+
+/// The starting points from which all reachable objects are found.
+#[derive(Default)]
+struct Tips {
+    commits: Vec<ObjectId>,
+    others: Vec<ObjectId>,
+}
+
+impl Tips {
+    /// Add `id`, along with all tags it points to.
+    fn add(&mut self, repo: &Repository, mut id: ObjectId) -> Result<(), Error> {
+        loop {
+            let object = repo.find_object(id)?;
+            match object.kind {
+                gix_object::Kind::Commit => {
+                    self.commits.push(id);
+                    return Ok(());
+                }
+                gix_object::Kind::Tag => {
+                    self.others.push(id);
+                    id = object.into_tag().target_id()?.detach();
+                }
+                gix_object::Kind::Tree | gix_object::Kind::Blob => {
+                    self.others.push(id);
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Add `id` if it exists, which is assumed to be the case for all objects that are referred to by references.
+    fn add_if_present(&mut self, repo: &Repository, id: ObjectId) -> Result<(), Error> {
+        if !id.is_null() && repo.has_object(id) {
+            self.add(repo, id)?;
+        }
+        Ok(())
+    }
+
+    /// Add all objects referred to by `HEAD`, the reflogs and the index of the worktree of `repo`.
+    fn add_worktree(&mut self, repo: &Repository, buf: &mut Vec<u8>) -> Result<(), Error> {
+        if let Some(id) = repo.head()?.id() {
+            self.add(repo, id.detach())?;
+        }
+        self.add_reflog(repo, "HEAD".try_into().expect("valid"), buf)?;
+        if repo.index_path().is_file() {
+            let index = repo.open_index()?;
+            for entry in index.entries() {
+                if entry.mode != gix_index::entry::Mode::COMMIT {
+                    self.add_if_present(repo, entry.id)?;
+                }
+            }
+            let mut trees: Vec<_> = index.tree().into_iter().collect();
+            while let Some(tree) = trees.pop() {
+                if tree.num_entries.is_some() {
+                    self.add_if_present(repo, tree.id)?;
+                }
+                trees.extend(tree.children.iter());
+            }
+        }
+        Ok(())
+    }
+
+    fn add_reflog(&mut self, repo: &Repository, name: &gix_ref::FullNameRef, buf: &mut Vec<u8>) -> Result<(), Error> {
+        let Some(log) = repo.refs.reflog_iter(name, buf)? else {
+            return Ok(());
+        };
+        let mut ids = Vec::new();
+        for line in log.filter_map(Result::ok) {
+            ids.push(line.previous_oid());
+            ids.push(line.new_oid());
+        }
+        for id in ids {
+            self.add_if_present(repo, id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Return the commits that all references and `HEAD` point to, after peeling tags.
+pub(crate) fn commit_tips(repo: &Repository) -> Result<Vec<ObjectId>, Error> {
+    let mut tips = Vec::new();
+    for reference in repo.references()?.all()? {
+        let reference = reference.map_err(Error::Reference)?;
+        if let Some(id) = reference.target().try_id() {
+            if let Ok(commit) = repo.find_object(id)?.peel_to_kind(gix_object::Kind::Commit) {
+                tips.push(commit.id);
+            }
+        }
+    }
+    if let Some(id) = repo.head()?.id() {
+        tips.push(id.detach());
+    }
+    Ok(tips)
+}
+
+/// Count all objects reachable from references, their reflogs, `HEAD` and the index of all worktrees,
+/// so they can be written into a pack.
+pub(crate) fn objects(
+    repo: &Repository,
+    db: &super::Db,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<Vec<Count>, Error> {
+    let mut tips = Tips::default();
+    let mut buf = Vec::new();
+    for reference in repo.references()?.all()? {
+        let reference = reference.map_err(Error::Reference)?;
+        if let Some(id) = reference.target().try_id() {
+            tips.add(repo, id.to_owned())?;
+        }
+        if reference.name().as_bstr().starts_with_str("refs/") {
+            tips.add_reflog(repo, reference.name(), &mut buf)?;
+        }
+    }
+    tips.add_worktree(repo, &mut buf)?;
+    for proxy in repo.worktrees()? {
+        let worktree_repo = proxy.into_repo_with_possibly_inaccessible_worktree()?;
+        tips.add_worktree(&worktree_repo, &mut buf)?;
+    }
+
+    let mut commits = Vec::new();
+    if !tips.commits.is_empty() {
+        for info in repo.rev_walk(tips.commits).all()? {
+            commits.push(info.map_err(|err| Error::Walk(err.into()))?.id);
+        }
+    }
+    let mut counting = progress.add_child("counting".into());
+    counting.init(None, gix_features::progress::count("objects"));
+    let (counts, _outcome) = count::objects_unthreaded(
+        db,
+        &mut commits
+            .into_iter()
+            .chain(tips.others)
+            .map(Ok::<_, Box<dyn std::error::Error + Send + Sync + 'static>>),
+        &counting,
+        should_interrupt,
+        count::objects::ObjectExpansion::TreeContents,
+    )?;
+    Ok(counts)
+}
+
+/// Return all objects reachable from `recent`, unreachable objects that are too new to be removed, including `recent`
+/// themselves. Like `git`, these are kept along with the objects they refer to even if those are old, while missing
+/// objects are ignored.
+pub(crate) fn from_recent(
+    db: &super::Db,
+    recent: impl IntoIterator<Item = ObjectId>,
+) -> Result<gix_hashtable::HashSet<ObjectId>, Error> {
+    let mut seen = gix_hashtable::HashSet::default();
+    let mut ids: Vec<_> = recent.into_iter().collect();
+    let mut buf = Vec::new();
+    while let Some(id) = ids.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        let Some(object) = gix_object::Find::try_find(db, &id, &mut buf)? else {
+            continue;
+        };
+        let decode_err = |source| Error::DecodeRecent { id, source };
+        match object.kind {
+            gix_object::Kind::Commit => {
+                let mut commit = gix_object::CommitRefIter::from_bytes(object.data);
+                ids.push(commit.tree_id().map_err(decode_err)?);
+                ids.extend(commit.parent_ids());
+            }
+            gix_object::Kind::Tag => {
+                ids.push(
+                    gix_object::TagRefIter::from_bytes(object.data)
+                        .target_id()
+                        .map_err(decode_err)?,
+                );
+            }
+            gix_object::Kind::Tree => {
+                for entry in gix_object::TreeRefIter::from_bytes(object.data, object.object_hash) {
+                    let entry = entry.map_err(decode_err)?;
+                    if !entry.mode.is_commit() {
+                        ids.push(entry.oid.to_owned());
+                    }
+                }
+            }
+            gix_object::Kind::Blob => {}
+        }
+    }
+    Ok(seen)
+}
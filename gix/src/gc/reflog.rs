@@ -0,0 +1,144 @@
+use std::time::SystemTime;
+
+use gix_hash::ObjectId;
+
+use crate::Repository;
+
+/// Options for use in [`Repository::expire_reflogs()`](crate::Repository::expire_reflogs()).
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Remove all entries that are older than this time, or keep them if `None`.
+    ///
+    /// Configured with `gc.reflogExpire`.
+    pub expire: Option<SystemTime>,
+    /// Remove entries that are older than this time and that refer to commits that aren't reachable from the tip
+    /// of the reference anymore, or keep them if `None`.
+    ///
+    /// Configured with `gc.reflogExpireUnreachable`.
+    pub expire_unreachable: Option<SystemTime>,
+}
+
+/// The outcome of [`Repository::expire_reflogs()`](crate::Repository::expire_reflogs()).
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of reflogs that were looked at.
+    pub num_reflogs: usize,
+    /// The amount of reflog entries that were removed in total.
+    pub num_removed_entries: usize,
+}
+
+mod error {
+    /// The error returned by [`Repository::expire_reflogs()`](crate::Repository::expire_reflogs()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        References(#[from] crate::reference::iter::Error),
+        #[error(transparent)]
+        ReferencesInit(#[from] crate::reference::iter::init::Error),
+        #[error("A reference could not be read")]
+        Reference(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        ReadReflog(#[from] gix_ref::file::log::Error),
+        #[error(transparent)]
+        RewriteReflog(#[from] gix_ref::file::log::retain::Error),
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        Walk(#[from] crate::revision::walk::Error),
+    }
+}
+pub use error::Error;
+
+/// Remove the entries of all reflogs in `repo` according to `options`.
+pub(crate) fn run(repo: &Repository, options: Options) -> Result<Outcome, Error> {
+    let mut out = Outcome::default();
+    if repo.refs.reftable().is_some() || (options.expire.is_none() && options.expire_unreachable.is_none()) {
+        return Ok(out);
+    }
+    let seconds = |time: SystemTime| {
+        time.duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs() as gix_date::SecondsSinceUnixEpoch)
+    };
+    let expire = options.expire.map(seconds);
+    let expire_unreachable = options.expire_unreachable.map(seconds);
+
+    let mut names_and_tips = Vec::new();
+    for reference in repo.references()?.all()? {
+        let mut reference = reference.map_err(Error::Reference)?;
+        let tip = match reference.peel_to_id_in_place() {
+            Ok(id) => Some(id.detach()),
+            Err(_) => None,
+        };
+        names_and_tips.push((reference.name().to_owned(), tip));
+    }
+    let all_tips: Vec<_> = names_and_tips.iter().filter_map(|(_, tip)| *tip).collect();
+    names_and_tips.push(("HEAD".try_into().expect("valid"), None));
+
+    let mut buf = Vec::new();
+    for (name, tip) in names_and_tips {
+        let needs_reachability = {
+            let Some(log) = repo.refs.reflog_iter(name.as_ref(), &mut buf)? else {
+                continue;
+            };
+            let mut needs_reachability = false;
+            for line in log.filter_map(Result::ok) {
+                let time = line.signature.time.seconds;
+                if matches!(expire_unreachable, Some(expire_unreachable) if time < expire_unreachable)
+                    && !matches!(expire, Some(expire) if time < expire)
+                {
+                    needs_reachability = true;
+                    break;
+                }
+            }
+            needs_reachability
+        };
+        let reachable = if needs_reachability {
+            let tips = if name.as_bstr() == "HEAD" {
+                all_tips.clone()
+            } else {
+                tip.into_iter().collect()
+            };
+            reachable_commits(repo, tips)?
+        } else {
+            Default::default()
+        };
+
+        let removed = repo
+            .refs
+            .reflog_retain(name.as_ref(), gix_lock::acquire::Fail::Immediately, &mut |line| {
+                let time = line.signature.time.seconds;
+                if matches!(expire, Some(expire) if time < expire) {
+                    return false;
+                }
+                if matches!(expire_unreachable, Some(expire_unreachable) if time < expire_unreachable) {
+                    let is_reachable = |id: ObjectId| id.is_null() || reachable.contains(&id);
+                    return is_reachable(line.previous_oid()) && is_reachable(line.new_oid());
+                }
+                true
+            })?;
+        if let Some(removed) = removed {
+            out.num_reflogs += 1;
+            out.num_removed_entries += removed;
+        }
+    }
+    Ok(out)
+}
+
+/// Return all commits reachable from `tips`, ignoring those that aren't commits.
+fn reachable_commits(repo: &Repository, tips: Vec<ObjectId>) -> Result<gix_hashtable::HashSet, Error> {
+    let mut commits = Vec::new();
+    for tip in tips {
+        if let Ok(commit) = repo.find_object(tip)?.peel_to_kind(gix_object::Kind::Commit) {
+            commits.push(commit.id);
+        }
+    }
+    let mut reachable = gix_hashtable::HashSet::default();
+    if commits.is_empty() {
+        return Ok(reachable);
+    }
+    for info in repo.rev_walk(commits).all()? {
+        reachable.insert(info.map_err(|err| Error::Walk(err.into()))?.id);
+    }
+    Ok(reachable)
+}
@@ -0,0 +1,492 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::SystemTime,
+};
+
+use gix_features::{
+    parallel::InOrderIter,
+    progress::{Count as _, Progress},
+};
+use gix_hash::ObjectId;
+use gix_object::FindExt;
+use gix_odb::Write as _;
+use gix_pack::data::output::{count, entry, Count};
+
+use super::packs::{self, Pack};
+use crate::Repository;
+
+/// Options for use in [`Repository::repack()`](crate::Repository::repack()).
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Determine which objects to put into the new pack, and which packs to delete afterwards.
+    pub mode: Mode,
+    /// If `true`, write a bitmap for the new pack if it contains all objects of the repository.
+    ///
+    /// This is only possible in [`Mode::AllIntoOne`] if there are no kept packs and the repository isn't shallow.
+    pub write_bitmap: bool,
+    /// If `true`, write a reverse index next to each new pack index.
+    pub write_reverse_index: bool,
+    /// The amount of threads to use at most, or `None` to use all logical cores.
+    pub thread_limit: Option<usize>,
+    /// The amount of objects to consider as delta base for each object.
+    pub delta_window: usize,
+    /// The maximum length of delta chains.
+    pub delta_depth: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        let defaults = entry::iter_from_counts::Options::default();
+        Options {
+            mode: Mode::Incremental,
+            write_bitmap: false,
+            write_reverse_index: true,
+            thread_limit: None,
+            delta_window: defaults.delta_window,
+            delta_depth: defaults.delta_depth,
+        }
+    }
+}
+
+/// Determine which objects to repack.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Put all reachable loose objects into a new pack, like `git repack -d`.
+    Incremental,
+    /// Put all reachable objects into a single new pack and delete all previous packs, like `git repack -a -d`.
+    ///
+    /// Packs that are kept with a `.keep` or `.promisor` file are left untouched, and their objects aren't repacked.
+    AllIntoOne {
+        /// Determine what happens to unreachable objects in the packs that are deleted.
+        unreachable: Unreachable,
+    },
+    /// Roll up the smallest packs along with all loose objects into a new pack so that each remaining pack contains
+    /// at least `factor` times as many objects as the next smaller one, like `git repack --geometric=<factor> -d`.
+    ///
+    /// Kept packs and cruft packs aren't considered.
+    Geometric {
+        /// The factor by which the amount of objects in each pack should grow.
+        factor: u32,
+    },
+}
+
+impl Mode {
+    /// Return `true` if this mode needs to know all reachable objects.
+    pub fn needs_reachable_objects(&self) -> bool {
+        !matches!(self, Mode::Geometric { .. })
+    }
+}
+
+/// Determine what happens to unreachable objects when [repacking everything](Mode::AllIntoOne).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Unreachable {
+    /// Delete unreachable objects in the packs that are deleted, like `git repack -a -d`.
+    Drop,
+    /// Write unreachable objects in the packs that are deleted as loose objects, with the modification time of their pack,
+    /// like `git repack -A -d`.
+    Loosen {
+        /// Drop objects that were last modified at or before this time instead of loosening them, or loosen all if `None`.
+        ///
+        /// Objects that are referred to by more recent unreachable objects are loosened as well.
+        expire: Option<SystemTime>,
+    },
+    /// Write all unreachable objects, packed or loose, into a cruft pack that keeps their modification time,
+    /// like `git repack --cruft -d`.
+    Cruft {
+        /// Drop objects that were last modified at or before this time instead of adding them to the cruft pack,
+        /// or keep all if `None`.
+        ///
+        /// Objects that are referred to by more recent unreachable objects are kept as well.
+        expire: Option<SystemTime>,
+    },
+}
+
+/// The outcome of [`Repository::repack()`](crate::Repository::repack()).
+#[derive(Default, Debug, Clone)]
+pub struct Outcome {
+    /// The path to the new pack, or `None` if there was nothing to pack.
+    pub pack: Option<PathBuf>,
+    /// The amount of objects in the new pack.
+    pub num_objects: usize,
+    /// The path to the bitmap of the new pack, if one was written.
+    pub bitmap: Option<PathBuf>,
+    /// The path to the cruft pack with all unreachable objects, if one was written.
+    pub cruft_pack: Option<PathBuf>,
+    /// The amount of objects in the cruft pack.
+    pub num_cruft_objects: usize,
+    /// The amount of unreachable objects that were written as loose objects.
+    pub num_loosened_objects: usize,
+    /// The paths to all packs that were deleted.
+    pub removed_packs: Vec<PathBuf>,
+    /// The amount of loose objects that were deleted as they are now contained in a pack.
+    pub num_removed_loose_objects: usize,
+}
+
+mod error {
+    /// The error returned by [`Repository::repack()`](crate::Repository::repack()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        LooseObjects(#[from] gix_odb::loose::iter::Error),
+        #[error(transparent)]
+        Reachable(#[from] crate::gc::reachable::Error),
+        #[error(transparent)]
+        CountObjects(#[from] gix_pack::data::output::count::objects::Error),
+        #[error(transparent)]
+        WritePackData(
+            #[from] gix_pack::data::output::bytes::Error<gix_pack::data::output::entry::iter_from_counts::Error>,
+        ),
+        #[error(transparent)]
+        WritePack(#[from] gix_pack::bundle::write::Error),
+        #[error(transparent)]
+        OpenIndex(#[from] gix_pack::index::init::Error),
+        #[error(transparent)]
+        WriteBitmap(#[from] gix_pack::bitmap::write::Error),
+        #[error(transparent)]
+        WriteMultiIndex(#[from] gix_pack::multi_index::write::Error),
+        #[error(transparent)]
+        LockAcquire(#[from] gix_lock::acquire::Error),
+        #[error(transparent)]
+        FindObject(#[from] gix_object::find::existing::Error),
+        #[error("Could not write unreachable object {id} as loose object")]
+        WriteLooseObject {
+            id: gix_hash::ObjectId,
+            source: gix_odb::write::Error,
+        },
+        #[error("The operation was interrupted")]
+        Interrupted,
+    }
+}
+pub use error::Error;
+
+/// Repack the objects of `repo` according to `options`, reading objects from `db`.
+///
+/// `reachable` are all reachable objects, which are computed if `None` and needed by the mode.
+pub(crate) fn run(
+    repo: &Repository,
+    db: &super::Db,
+    reachable: Option<&[Count]>,
+    options: &Options,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<Outcome, Error> {
+    let object_hash = repo.object_hash();
+    let objects_dir = repo.objects.store_ref().path().to_owned();
+    let pack_dir = objects_dir.join("pack");
+    let loose = gix_odb::loose::Store::at(&objects_dir, object_hash);
+    let existing = packs::at(&pack_dir, object_hash)?;
+
+    let computed;
+    let reachable = match reachable {
+        Some(reachable) => reachable,
+        None if options.mode.needs_reachable_objects() => {
+            computed = super::reachable::objects(repo, db, progress, should_interrupt)?;
+            &computed
+        }
+        None => &[],
+    };
+    let in_pack = |id: &gix_hash::oid, keep: bool| existing.iter().any(|pack| pack.keep == keep && pack.contains(id));
+
+    let (counts, packs_to_remove): (Vec<Count>, Vec<&Pack>) = match options.mode {
+        Mode::Incremental => (
+            reachable
+                .iter()
+                .filter(|count| loose.contains(&count.id) && !in_pack(&count.id, false) && !in_pack(&count.id, true))
+                .cloned()
+                .collect(),
+            Vec::new(),
+        ),
+        Mode::AllIntoOne { .. } => (
+            reachable
+                .iter()
+                .filter(|count| !in_pack(&count.id, true) && (in_pack(&count.id, false) || loose.contains(&count.id)))
+                .cloned()
+                .collect(),
+            existing.iter().filter(|pack| !pack.keep).collect(),
+        ),
+        Mode::Geometric { factor } => {
+            let mut candidates: Vec<_> = existing
+                .iter()
+                .filter(|pack| !pack.keep && pack.mtimes.is_none())
+                .collect();
+            candidates.sort_by_key(|pack| pack.index.num_objects());
+            let num_rolled_up = geometric_split(
+                &candidates
+                    .iter()
+                    .map(|pack| u64::from(pack.index.num_objects()))
+                    .collect::<Vec<_>>(),
+                factor.into(),
+            );
+            candidates.truncate(num_rolled_up);
+
+            let mut seen = gix_hashtable::HashSet::default();
+            let mut ids = Vec::new();
+            for pack in &candidates {
+                ids.extend(pack.index.iter().map(|entry| entry.oid).filter(|id| seen.insert(*id)));
+            }
+            let num_packed_ids = ids.len();
+            for id in loose.iter() {
+                let id = id?;
+                if !existing.iter().any(|pack| pack.contains(&id)) && seen.insert(id) {
+                    ids.push(id);
+                }
+            }
+            if candidates.len() < 2 && ids.len() == num_packed_ids {
+                (Vec::new(), Vec::new())
+            } else {
+                (count_as_is(db, ids, progress, should_interrupt)?, candidates)
+            }
+        }
+    };
+
+    let mut out = Outcome::default();
+    let mut new_packs = Vec::new();
+    if let Some(bundle) = write_pack(db, counts, &pack_dir, object_hash, options, progress, should_interrupt)? {
+        out.num_objects = bundle.index.num_objects as usize;
+        let index_path = bundle.index_path.expect("written to directory");
+        let can_write_bitmap = matches!(options.mode, Mode::AllIntoOne { .. })
+            && !existing.iter().any(|pack| pack.keep)
+            && !repo.is_shallow();
+        if options.write_bitmap && can_write_bitmap {
+            let index = gix_pack::index::File::at(&index_path, object_hash)?;
+            let bitmap_path = index_path.with_extension("bitmap");
+            let mut lock =
+                gix_lock::File::acquire_to_update_resource(&bitmap_path, gix_lock::acquire::Fail::Immediately, None)?;
+            let mut bitmap_progress = progress.add_child("writing bitmap".into());
+            gix_pack::bitmap::File::write_to(
+                &index,
+                &gix_pack::bitmap::Order::from_index(&index),
+                db,
+                &mut lock,
+                &mut bitmap_progress,
+                should_interrupt,
+                Default::default(),
+            )?;
+            lock.commit().map_err(|err| err.error)?;
+            out.bitmap = Some(bitmap_path);
+        }
+        let data_path = bundle.data_path.expect("written to directory");
+        new_packs.push(data_path.clone());
+        out.pack = Some(data_path);
+    }
+
+    if let Mode::AllIntoOne { unreachable } = options.mode {
+        let reachable: gix_hashtable::HashSet<_> = reachable.iter().map(|count| count.id).collect();
+        let is_candidate = |id: &gix_hash::oid| !reachable.contains(id) && !in_pack(id, true);
+        let mut seen = gix_hashtable::HashSet::default();
+        let mut unreachable_objects = Vec::new();
+        for pack in &packs_to_remove {
+            for entry in pack.index.iter() {
+                if is_candidate(&entry.oid) && seen.insert(entry.oid) {
+                    unreachable_objects.push((entry.oid, pack.mtime_of(&entry.oid), false));
+                }
+            }
+        }
+        for id in loose.iter() {
+            let id = id?;
+            if is_candidate(&id) && seen.insert(id) {
+                let mtime = std::fs::metadata(loose.object_path(&id))?.modified()?;
+                unreachable_objects.push((id, mtime, true));
+            }
+        }
+        let expire = match unreachable {
+            Unreachable::Drop => None,
+            Unreachable::Loosen { expire } | Unreachable::Cruft { expire } => expire,
+        };
+        let is_expired = |mtime: SystemTime| matches!(expire, Some(expire) if mtime <= expire);
+        let keep = if expire.is_some() {
+            super::reachable::from_recent(
+                db,
+                unreachable_objects
+                    .iter()
+                    .filter(|(_, mtime, _)| !is_expired(*mtime))
+                    .map(|(id, _, _)| *id),
+            )?
+        } else {
+            Default::default()
+        };
+        let is_expired = |id: &gix_hash::oid, mtime: SystemTime| is_expired(mtime) && !keep.contains(id);
+
+        match unreachable {
+            Unreachable::Drop => {}
+            Unreachable::Loosen { .. } => {
+                let mut buf = Vec::new();
+                for (id, mtime, is_loose) in unreachable_objects {
+                    if is_loose || is_expired(&id, mtime) || loose.contains(&id) {
+                        continue;
+                    }
+                    let object = db.find(&id, &mut buf)?;
+                    loose
+                        .write_buf(object.kind, object.data)
+                        .map_err(|err| Error::WriteLooseObject { id, source: err })?;
+                    filetime::set_file_mtime(loose.object_path(&id), filetime::FileTime::from_system_time(mtime))?;
+                    out.num_loosened_objects += 1;
+                }
+            }
+            Unreachable::Cruft { .. } => {
+                let mtimes: gix_hashtable::HashMap<_, _> = unreachable_objects
+                    .into_iter()
+                    .filter(|(id, mtime, _)| !is_expired(id, *mtime))
+                    .map(|(id, mtime, _)| (id, mtime))
+                    .collect();
+                let counts = count_as_is(db, mtimes.keys().copied().collect(), progress, should_interrupt)?;
+                if let Some(bundle) =
+                    write_pack(db, counts, &pack_dir, object_hash, options, progress, should_interrupt)?
+                {
+                    let index_path = bundle.index_path.expect("written to directory");
+                    let index = gix_pack::index::File::at(&index_path, object_hash)?;
+                    let mut lock = gix_lock::File::acquire_to_update_resource(
+                        index_path.with_extension("mtimes"),
+                        gix_lock::acquire::Fail::Immediately,
+                        None,
+                    )?;
+                    gix_pack::index::mtimes::File::write_from_index(
+                        &index,
+                        &mut |id| {
+                            mtimes
+                                .get(id)
+                                .and_then(|mtime| mtime.duration_since(SystemTime::UNIX_EPOCH).ok())
+                                .map_or(0, |duration| duration.as_secs().min(u32::MAX.into()) as u32)
+                        },
+                        &mut lock,
+                    )?;
+                    lock.commit().map_err(|err| err.error)?;
+
+                    out.num_cruft_objects = bundle.index.num_objects as usize;
+                    let data_path = bundle.data_path.expect("written to directory");
+                    new_packs.push(data_path.clone());
+                    out.cruft_pack = Some(data_path);
+                }
+            }
+        }
+    }
+
+    for pack in packs_to_remove {
+        if !new_packs.contains(&pack.data_path) {
+            packs::remove(&pack.data_path)?;
+            out.removed_packs.push(pack.data_path.clone());
+        }
+    }
+    drop(existing);
+
+    let remaining = packs::at(&pack_dir, object_hash)?;
+    out.num_removed_loose_objects = packs::prune_packed(&objects_dir, object_hash, &remaining)?;
+    if !new_packs.is_empty() || !out.removed_packs.is_empty() {
+        packs::update_multi_pack_index(&pack_dir, &remaining, object_hash, progress, should_interrupt)?;
+    }
+    Ok(out)
+}
+
+/// Return the amount of packs, sorted by ascending amount of objects in `num_objects`, that have to be rolled up into one
+/// so that each of the remaining packs contains at least `factor` times as many objects as the next smaller one.
+fn geometric_split(num_objects: &[u64], factor: u64) -> usize {
+    let mut split = (1..num_objects.len())
+        .rev()
+        .find(|&idx| num_objects[idx] < factor.saturating_mul(num_objects[idx - 1]))
+        .map_or(0, |idx| idx + 1);
+    let mut total: u64 = num_objects[..split].iter().sum();
+    while split < num_objects.len() && num_objects[split] < factor.saturating_mul(total) {
+        total += num_objects[split];
+        split += 1;
+    }
+    split
+}
+
+fn count_as_is(
+    db: &super::Db,
+    ids: Vec<ObjectId>,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<Vec<Count>, Error> {
+    let mut counting = progress.add_child("counting".into());
+    counting.init(Some(ids.len()), gix_features::progress::count("objects"));
+    let (counts, _outcome) = count::objects_unthreaded(
+        db,
+        &mut ids
+            .into_iter()
+            .map(Ok::<_, Box<dyn std::error::Error + Send + Sync + 'static>>),
+        &counting,
+        should_interrupt,
+        count::objects::ObjectExpansion::AsIs,
+    )?;
+    Ok(counts)
+}
+
+/// Write a pack with all objects in `counts` into `pack_dir`, or return `None` if there are no objects.
+fn write_pack(
+    db: &super::Db,
+    counts: Vec<Count>,
+    pack_dir: &Path,
+    object_hash: gix_hash::Kind,
+    options: &Options,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<Option<gix_pack::bundle::write::Outcome>, Error> {
+    if counts.is_empty() {
+        return Ok(None);
+    }
+    let num_objects = counts.len();
+    let entries = InOrderIter::from(entry::iter_from_counts(
+        counts,
+        db.clone(),
+        Box::new(progress.add_child("creating entries".into())),
+        entry::iter_from_counts::Options {
+            thread_limit: options.thread_limit,
+            mode: entry::iter_from_counts::Mode::PackCopyAndDeltaCompression,
+            allow_thin_pack: false,
+            chunk_size: 1000,
+            version: Default::default(),
+            delta_window: options.delta_window,
+            delta_depth: options.delta_depth,
+        },
+    ));
+    let mut tempfile = gix_tempfile::new(
+        pack_dir,
+        gix_tempfile::ContainingDirectory::Exists,
+        gix_tempfile::AutoRemove::Tempfile,
+    )?;
+    {
+        let mut write_progress = progress.add_child("writing".into());
+        write_progress.init(None, gix_features::progress::bytes());
+        let mut out = std::io::BufWriter::new(&mut tempfile);
+        let mut pack = gix_pack::data::output::bytes::FromEntriesIter::new(
+            entries,
+            &mut out,
+            num_objects as u32,
+            gix_pack::data::Version::default(),
+            object_hash,
+        );
+        for written in pack.by_ref() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            write_progress.inc_by(written? as usize);
+        }
+        out.flush()?;
+    }
+
+    let pack = tempfile.with_mut(|file| file.reopen())??;
+    let outcome = gix_pack::Bundle::write_to_directory(
+        &mut std::io::BufReader::new(pack),
+        Some(pack_dir),
+        progress,
+        should_interrupt,
+        None::<gix_object::find::Never>,
+        gix_pack::bundle::write::Options {
+            thread_limit: options.thread_limit,
+            iteration_mode: gix_pack::data::input::Mode::Verify,
+            index_version: Default::default(),
+            object_hash,
+            write_reverse_index: options.write_reverse_index,
+        },
+    )?;
+    if let Some(keep_path) = &outcome.keep_path {
+        std::fs::remove_file(keep_path)?;
+    }
+    Ok(Some(outcome))
+}
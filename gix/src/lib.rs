@@ -95,6 +95,8 @@
 pub use gix_actor as actor;
 #[cfg(feature = "attributes")]
 pub use gix_attributes as attrs;
+#[cfg(feature = "blame")]
+pub use gix_blame as blame;
 #[cfg(feature = "command")]
 pub use gix_command as command;
 pub use gix_commitgraph as commitgraph;
@@ -120,8 +122,12 @@ pub use gix_ignore as ignore;
 #[cfg(feature = "index")]
 pub use gix_index as index;
 pub use gix_lock as lock;
+#[cfg(feature = "merge")]
+pub use gix_merge as merge;
 #[cfg(feature = "credentials")]
 pub use gix_negotiate as negotiate;
+#[cfg(feature = "notes")]
+pub use gix_note as note;
 pub use gix_object as objs;
 pub use gix_object::bstr;
 pub use gix_odb as odb;
@@ -133,6 +139,8 @@ pub use gix_ref as refs;
 pub use gix_refspec as refspec;
 pub use gix_revwalk as revwalk;
 pub use gix_sec as sec;
+#[cfg(feature = "rebase")]
+pub use gix_sequencer as sequencer;
 pub use gix_tempfile as tempfile;
 pub use gix_trace as trace;
 pub use gix_traverse as traverse;
@@ -166,6 +174,8 @@ pub(crate) type Config = OwnShared<gix_config::File<'static>>;
 mod types;
 #[cfg(any(feature = "excludes", feature = "attributes"))]
 pub use types::AttributeStack;
+#[cfg(feature = "notes")]
+pub use types::Notes;
 pub use types::{
     Blob, Commit, Head, Id, Object, ObjectDetached, Reference, Remote, Repository, Tag, ThreadSafeRepository, Tree,
     Worktree,
@@ -173,6 +183,8 @@ pub use types::{
 #[cfg(feature = "attributes")]
 pub use types::{Pathspec, PathspecDetached, Submodule};
 
+#[cfg(any(feature = "bundle", feature = "blocking-network-client"))]
+pub mod bundle;
 ///
 #[allow(clippy::empty_docs)]
 pub mod clone;
@@ -181,15 +193,25 @@ pub mod commit;
 #[cfg(feature = "dirwalk")]
 #[allow(clippy::empty_docs)]
 pub mod dirwalk;
+#[cfg(feature = "gc")]
+pub mod gc;
 pub mod head;
 pub mod id;
+#[cfg(feature = "notes")]
+pub mod notes;
 pub mod object;
 #[cfg(feature = "attributes")]
 pub mod pathspec;
+#[cfg(feature = "rebase")]
+pub mod rebase;
 pub mod reference;
 pub mod repository;
+#[cfg(feature = "excludes")]
+pub mod sparse;
 #[cfg(feature = "attributes")]
 pub mod submodule;
+#[cfg(feature = "worktree-mutation")]
+pub mod switch;
 pub mod tag;
 #[cfg(any(feature = "dirwalk", feature = "status"))]
 pub(crate) mod util;
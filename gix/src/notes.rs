@@ -0,0 +1,396 @@
+//! Read, write and merge notes, which are stored in notes references like `refs/notes/commits`.
+use gix_hash::{oid, ObjectId};
+use gix_ref::{transaction::PreviousValue, FullNameRef};
+
+use crate::{bstr::ByteSlice, config::cache::util::ApplyLeniency, Id, Notes};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod open {
+    /// The error returned by [Repository::notes()](crate::Repository::notes()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The notes reference configured in core.notesRef is invalid")]
+        InvalidRefName(#[from] gix_validate::reference::name::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod to_display {
+    /// The error returned by [Repository::notes_to_display()](crate::Repository::notes_to_display()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Notes(#[from] super::open::Error),
+        #[error(transparent)]
+        InvalidRefName(#[from] gix_validate::reference::name::Error),
+        #[error(transparent)]
+        IterReferences(#[from] crate::reference::iter::Error),
+        #[error("Could not read a reference to match it against notes.displayRef")]
+        ReadReference(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        IterReferencesInit(#[from] crate::reference::iter::init::Error),
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod tip {
+    /// The error returned by [Notes::tip()](crate::Notes::tip()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::Error),
+        #[error(transparent)]
+        PeelReference(#[from] crate::reference::peel::Error),
+        #[error(transparent)]
+        FindCommit(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        NotACommit(#[from] crate::object::try_into::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod find {
+    /// The error returned by [Notes::find()](crate::Notes::find()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Tip(#[from] super::tip::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        FindNote(#[from] gix_note::find::Error),
+        #[error(transparent)]
+        FindBlob(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        NotABlob(#[from] crate::object::try_into::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod load {
+    /// The error returned by [Notes::load()](crate::Notes::load()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Tip(#[from] super::tip::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        ReadNotes(#[from] gix_note::from_tree::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod edit {
+    use crate::bstr::BString;
+
+    /// The error returned by [Notes::add()](crate::Notes::add()), [Notes::append()](crate::Notes::append())
+    /// and [Notes::remove()](crate::Notes::remove()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Object {target} already has a note in '{ref_name}', which can only be overwritten on request")]
+        NoteExists {
+            target: gix_hash::ObjectId,
+            ref_name: BString,
+        },
+        #[error(transparent)]
+        Load(#[from] super::load::Error),
+        #[error(transparent)]
+        FindBlob(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        NotABlob(#[from] crate::object::try_into::Error),
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+        #[error(transparent)]
+        Commit(#[from] crate::commit::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod merge {
+    use crate::Id;
+
+    /// The error returned by [Notes::merge()](crate::Notes::merge()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Strategy(#[from] crate::config::key::GenericErrorWithValue),
+        #[error(transparent)]
+        Tip(#[from] super::tip::Error),
+        #[error(transparent)]
+        Load(#[from] super::load::Error),
+        #[error(transparent)]
+        MergeBase(#[from] crate::repository::merge_base::Error),
+        #[error(transparent)]
+        FindCommit(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        NotACommit(#[from] crate::object::try_into::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error(transparent)]
+        ReadNotes(#[from] gix_note::from_tree::Error),
+        #[error(transparent)]
+        Merge(#[from] gix_note::merge::Error),
+        #[error(transparent)]
+        WriteObject(#[from] crate::object::write::Error),
+        #[error(transparent)]
+        Commit(#[from] crate::commit::Error),
+        #[error(transparent)]
+        EditReference(#[from] crate::reference::edit::Error),
+    }
+
+    /// The outcome of [Notes::merge()](crate::Notes::merge()).
+    #[derive(Debug, Clone)]
+    pub enum Outcome<'repo> {
+        /// All of their notes are already contained in our notes, so nothing was done.
+        UpToDate,
+        /// Our notes reference now points to their notes commit, as we had no notes or no notes of our own.
+        FastForward {
+            /// Their notes commit.
+            commit: Id<'repo>,
+        },
+        /// A merge commit with our and their notes commit as parents was created, and our notes reference points to it.
+        Merged {
+            /// The newly created merge commit.
+            commit: Id<'repo>,
+        },
+        /// Notes were changed differently on both sides and the [manual](gix_note::merge::Strategy::Manual) strategy was used,
+        /// so nothing was written.
+        Conflicts(Vec<gix_note::merge::Conflict>),
+    }
+}
+
+/// The default notes reference if `core.notesRef` isn't set.
+pub const DEFAULT_REF: &str = "refs/notes/commits";
+
+impl<'repo> Notes<'repo> {
+    /// Return the name of the notes reference.
+    pub fn ref_name(&self) -> &FullNameRef {
+        self.ref_name.as_ref()
+    }
+
+    /// Return the commit our notes reference points to, or `None` if it doesn't exist yet.
+    pub fn tip(&self) -> Result<Option<crate::Commit<'repo>>, tip::Error> {
+        let Some(mut reference) = self.repo.try_find_reference(self.ref_name.as_ref())? else {
+            return Ok(None);
+        };
+        Ok(Some(reference.peel_to_id_in_place()?.object()?.try_into_commit()?))
+    }
+
+    /// Return the blob with the note for the object with id `target`, or `None` if it isn't annotated.
+    pub fn find(&self, target: impl AsRef<oid>) -> Result<Option<crate::Blob<'repo>>, find::Error> {
+        let Some(commit) = self.tip()? else {
+            return Ok(None);
+        };
+        let Some(note) = gix_note::find(&commit.tree_id()?, target.as_ref(), &self.repo.objects)? else {
+            return Ok(None);
+        };
+        Ok(Some(self.repo.find_object(note)?.try_into_blob()?))
+    }
+
+    /// Load all notes, which is useful to iterate them or to look up many of them. There are no notes if the
+    /// notes reference doesn't exist.
+    pub fn load(&self) -> Result<gix_note::Notes, load::Error> {
+        Ok(match self.tip()? {
+            Some(commit) => gix_note::Notes::from_tree(&commit.tree_id()?, &self.repo.objects)?,
+            None => Default::default(),
+        })
+    }
+
+    /// Set `note` as note of the object with id `target`, and return the id of the new notes commit.
+    ///
+    /// If there already is a note for `target`, it is only replaced if `overwrite` is `true`, or an error is returned.
+    pub fn add(
+        &self,
+        target: impl Into<ObjectId>,
+        note: impl AsRef<[u8]>,
+        overwrite: bool,
+    ) -> Result<Id<'repo>, edit::Error> {
+        let target = target.into();
+        self.edit("Notes added by 'git notes add'", |notes| {
+            if !overwrite && notes.notes.contains_key(&target) {
+                return Err(edit::Error::NoteExists {
+                    target,
+                    ref_name: self.ref_name.as_bstr().to_owned(),
+                });
+            }
+            notes
+                .notes
+                .insert(target, self.repo.write_blob(note.as_ref())?.detach());
+            Ok(true)
+        })
+        .map(|commit| commit.expect("always changed"))
+    }
+
+    /// Append `note` to the note of the object with id `target` with an empty line in between, or add it as new note,
+    /// and return the id of the new notes commit.
+    pub fn append(&self, target: impl Into<ObjectId>, note: impl AsRef<[u8]>) -> Result<Id<'repo>, edit::Error> {
+        let target = target.into();
+        self.edit("Notes added by 'git notes append'", |notes| {
+            let note = match notes.notes.get(&target) {
+                Some(existing) => {
+                    let existing = self.repo.find_object(*existing)?.try_into_blob()?;
+                    gix_note::merge::concatenate(&existing.data, note.as_ref())
+                }
+                None => note.as_ref().to_owned(),
+            };
+            notes.notes.insert(target, self.repo.write_blob(note)?.detach());
+            Ok(true)
+        })
+        .map(|commit| commit.expect("always changed"))
+    }
+
+    /// Remove the note of the object with id `target`, and return the id of the new notes commit, or `None` if there
+    /// was no note to remove.
+    pub fn remove(&self, target: impl AsRef<oid>) -> Result<Option<Id<'repo>>, edit::Error> {
+        let target = target.as_ref();
+        self.edit("Notes removed by 'git notes remove'", |notes| {
+            Ok(notes.notes.remove(target).is_some())
+        })
+    }
+
+    /// Merge the notes of the notes reference `theirs` into our notes, like `git notes merge` does.
+    ///
+    /// Notes changed differently on both sides are resolved with `strategy`, or if `None`, with the strategy
+    /// configured in `notes.<name>.mergeStrategy` or `notes.mergeStrategy`, where `<name>` is the name of our
+    /// notes reference without the `refs/notes/` prefix. If neither is set, the *manual* strategy is used.
+    pub fn merge(
+        &self,
+        theirs: &FullNameRef,
+        strategy: Option<gix_note::merge::Strategy>,
+    ) -> Result<merge::Outcome<'repo>, merge::Error> {
+        let strategy = match strategy {
+            Some(strategy) => strategy,
+            None => self.configured_merge_strategy()?.unwrap_or_default(),
+        };
+        let theirs_name = theirs;
+        let their_notes = Notes {
+            repo: self.repo,
+            ref_name: theirs.to_owned(),
+        };
+        let Some(theirs) = their_notes.tip()? else {
+            return Ok(merge::Outcome::UpToDate);
+        };
+        let Some(ours) = self.tip()? else {
+            self.repo.reference(
+                self.ref_name.clone(),
+                theirs.id,
+                PreviousValue::MustNotExist,
+                "notes: Fast-forward",
+            )?;
+            return Ok(merge::Outcome::FastForward { commit: theirs.id() });
+        };
+        if ours.id == theirs.id {
+            return Ok(merge::Outcome::UpToDate);
+        }
+        let base = match self.repo.merge_base(ours.id, theirs.id) {
+            Ok(base) => Some(base.detach()),
+            Err(crate::repository::merge_base::Error::NotFound { .. }) => None,
+            Err(err) => return Err(err.into()),
+        };
+        if base == Some(theirs.id) {
+            return Ok(merge::Outcome::UpToDate);
+        }
+        if base == Some(ours.id) {
+            self.repo.reference(
+                self.ref_name.clone(),
+                theirs.id,
+                PreviousValue::MustExistAndMatch(gix_ref::Target::Peeled(ours.id)),
+                "notes: Fast-forward",
+            )?;
+            return Ok(merge::Outcome::FastForward { commit: theirs.id() });
+        }
+
+        let base = match base {
+            Some(base) => {
+                let tree = self.repo.find_object(base)?.try_into_commit()?.tree_id()?;
+                gix_note::Notes::from_tree(&tree, &self.repo.objects)?
+            }
+            None => Default::default(),
+        };
+        let outcome = gix_note::merge(
+            &base,
+            &gix_note::Notes::from_tree(&ours.tree_id()?, &self.repo.objects)?,
+            &gix_note::Notes::from_tree(&theirs.tree_id()?, &self.repo.objects)?,
+            strategy,
+            &self.repo.objects,
+            |object| self.repo.write_object(object).map(Id::detach),
+        )?;
+        if !outcome.conflicts.is_empty() {
+            return Ok(merge::Outcome::Conflicts(outcome.conflicts));
+        }
+        let tree = outcome
+            .notes
+            .write_tree(|object| self.repo.write_object(object).map(Id::detach))?;
+        let message = format!(
+            "notes: Merged notes from {} into {}",
+            theirs_name.as_bstr(),
+            self.ref_name.as_bstr()
+        );
+        let commit = self
+            .repo
+            .commit(self.ref_name.as_bstr(), message, tree, [ours.id, theirs.id])?;
+        Ok(merge::Outcome::Merged { commit })
+    }
+
+    fn configured_merge_strategy(
+        &self,
+    ) -> Result<Option<gix_note::merge::Strategy>, crate::config::key::GenericErrorWithValue> {
+        use crate::config::tree::Notes;
+        let config = &self.repo.config.resolved;
+        let name = self.ref_name.as_bstr();
+        let name = name.strip_prefix(b"refs/notes/").unwrap_or(name).as_bstr();
+        config
+            .string("notes", Some(name), Notes::REF_MERGE_STRATEGY.name)
+            .map(|value| Notes::REF_MERGE_STRATEGY.try_into_strategy(value))
+            .or_else(|| {
+                config
+                    .string("notes", None, Notes::MERGE_STRATEGY.name)
+                    .map(|value| Notes::MERGE_STRATEGY.try_into_strategy(value))
+            })
+            .transpose()
+            .with_leniency(self.repo.config.lenient_config)
+    }
+
+    /// Apply `edit` to all notes, and if it returns `true`, write them as new notes commit with `message`.
+    fn edit(
+        &self,
+        message: &str,
+        edit: impl FnOnce(&mut gix_note::Notes) -> Result<bool, edit::Error>,
+    ) -> Result<Option<Id<'repo>>, edit::Error> {
+        let tip = self.tip().map_err(load::Error::from)?;
+        let mut notes = match &tip {
+            Some(commit) => {
+                gix_note::Notes::from_tree(&commit.tree_id().map_err(load::Error::from)?, &self.repo.objects)
+                    .map_err(load::Error::from)?
+            }
+            None => Default::default(),
+        };
+        if !edit(&mut notes)? {
+            return Ok(None);
+        }
+        let tree = notes.write_tree(|object| self.repo.write_object(object).map(Id::detach))?;
+        Ok(Some(self.repo.commit(
+            self.ref_name.as_bstr(),
+            message,
+            tree,
+            tip.map(|commit| commit.id),
+        )?))
+    }
+}
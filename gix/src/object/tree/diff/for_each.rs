@@ -88,8 +88,9 @@ impl<'a, 'old> Platform<'a, 'old> {
             tracked: self.rewrites.map(rewrites::Tracker::new),
             err: None,
         };
-        match gix_diff::tree::Changes::from(TreeRefIter::from_bytes(&self.lhs.data)).needed_to_obtain(
-            TreeRefIter::from_bytes(&other.data),
+        let object_hash = repo.object_hash();
+        match gix_diff::tree::Changes::from(TreeRefIter::from_bytes(&self.lhs.data, object_hash)).needed_to_obtain(
+            TreeRefIter::from_bytes(&other.data, object_hash),
             &mut self.state,
             &repo.objects,
             &mut delegate,
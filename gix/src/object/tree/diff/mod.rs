@@ -89,3 +89,7 @@ impl<'a, 'repo> Platform<'a, 'repo> {
 ///
 #[allow(clippy::empty_docs)]
 pub mod for_each;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod patch;
@@ -0,0 +1,440 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    io::Write,
+};
+
+use gix_diff::blob::{
+    platform::prepare_diff::Operation,
+    unified_diff::{ConsumeHunk, ContextSize, FunctionName, Hunk},
+    ResourceKind, UnifiedDiff,
+};
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+use super::{change::Event, Action, Platform};
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    ext::ObjectIdExt,
+    Repository, Tree,
+};
+
+/// The error returned by [`Platform::write_patch_to()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Diff(#[from] super::for_each::Error),
+    #[error(transparent)]
+    SetResource(#[from] gix_diff::blob::platform::set_resource::Error),
+    #[error(transparent)]
+    PrepareDiff(#[from] gix_diff::blob::platform::prepare_diff::Error),
+    #[error("The value of diff.{name}.xfuncname could not be compiled")]
+    FunctionName {
+        name: BString,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Options for use in [`Platform::write_patch_to()`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The amount of unchanged lines to show around each change, like `git diff -U<n>`.
+    pub context_size: ContextSize,
+    /// If `true`, show changed words instead of changed lines, like `git diff --word-diff=plain`.
+    pub word_diff: bool,
+}
+
+/// Patches
+impl<'a, 'old> Platform<'a, 'old> {
+    /// Write a patch to `out` with all changes needed to turn the tree of this platform into `other`, just like
+    /// `git diff <this-tree> <other>` would print it.
+    ///
+    /// Each file is introduced with a `diff --git` header followed by lines about mode changes, renames and copies,
+    /// and the `index` line with abbreviated object ids, before its hunks or a notice that binary files differ are printed.
+    /// Files are ordered by their path in `other`, and changes of the type of a file, like turning a file into a symbolic
+    /// link, are shown as deletion followed by an addition.
+    ///
+    /// Use `resource_cache` to obtain diffable data, typically created with [`Repository::diff_resource_cache()`] with
+    /// the [`ToGitUnlessBinaryToTextIsPresent`](gix_diff::blob::pipeline::Mode::ToGitUnlessBinaryToTextIsPresent) mode.
+    /// The names of the functions hunks are in are found with `diff.<driver>.xfuncname` of the driver of each file,
+    /// or with the default heuristic of `git`.
+    ///
+    /// Note that this turns on [path tracking](Self::track_path()), and that
+    /// [rewrite tracking](Self::track_rewrites()) is respected to show renames and copies.
+    ///
+    /// ### Deviation
+    ///
+    /// * The similarity of rewrites is based on the amount of changed lines, whereas `git` computes it based on the amount
+    ///   of changed bytes, so the shown percentage may differ unless the rewrite is exact.
+    /// * The built-in function name patterns of `git`, like the ones for `diff=rust` or `diff=cpp`, aren't available.
+    /// * The diff algorithms don't use the indent heuristic of `git`, so the lines shown as changed may differ in
+    ///   ambiguous cases.
+    pub fn write_patch_to(
+        &mut self,
+        other: &Tree<'_>,
+        resource_cache: &mut gix_diff::blob::Platform,
+        out: &mut dyn Write,
+        options: Options,
+    ) -> Result<(), Error> {
+        let repo = self.lhs.repo;
+        self.track_path();
+        let mut changes = Vec::<FileChange>::new();
+        self.for_each_to_obtain_tree_with_cache(other, resource_cache, |change| {
+            let location = change.location.to_owned();
+            let file = |mode: EntryMode, id: crate::Id<'_>| (!mode.is_tree()).then(|| (mode, id.detach()));
+            match change.event {
+                Event::Addition { entry_mode, id } => changes.push(FileChange {
+                    source_location: location.clone(),
+                    location,
+                    old: None,
+                    new: file(entry_mode, id),
+                    rewrite: None,
+                }),
+                Event::Deletion { entry_mode, id } => changes.push(FileChange {
+                    source_location: location.clone(),
+                    location,
+                    old: file(entry_mode, id),
+                    new: None,
+                    rewrite: None,
+                }),
+                Event::Modification {
+                    previous_entry_mode,
+                    previous_id,
+                    entry_mode,
+                    id,
+                } => {
+                    let (old, new) = (file(previous_entry_mode, previous_id), file(entry_mode, id));
+                    if let (Some((old_mode, _)), Some((new_mode, _))) = (old, new) {
+                        if is_type_change(old_mode, new_mode) {
+                            changes.push(FileChange {
+                                source_location: location.clone(),
+                                location: location.clone(),
+                                old,
+                                new: None,
+                                rewrite: None,
+                            });
+                            changes.push(FileChange {
+                                source_location: location.clone(),
+                                location,
+                                old: None,
+                                new,
+                                rewrite: None,
+                            });
+                            return Ok::<_, std::convert::Infallible>(Action::Continue);
+                        }
+                    }
+                    changes.push(FileChange {
+                        source_location: location.clone(),
+                        location,
+                        old,
+                        new,
+                        rewrite: None,
+                    });
+                }
+                Event::Rewrite {
+                    source_location,
+                    source_entry_mode,
+                    source_id,
+                    diff,
+                    entry_mode,
+                    id,
+                    copy,
+                } => changes.push(FileChange {
+                    source_location: source_location.to_owned(),
+                    location,
+                    old: file(source_entry_mode, source_id),
+                    new: file(entry_mode, id),
+                    rewrite: Some(Rewrite {
+                        copy,
+                        similarity: diff.map_or(100, |diff| (diff.similarity * 100.0) as u32),
+                    }),
+                }),
+            }
+            Ok(Action::Continue)
+        })?;
+        changes.retain(|change| change.old.is_some() || change.new.is_some());
+        changes.sort_by(|a, b| a.location.cmp(&b.location));
+
+        let mut writer = PatchWriter {
+            repo,
+            xfuncnames: resource_cache
+                .filter
+                .drivers()
+                .iter()
+                .map(|driver| (driver.name.clone(), driver.xfuncname.clone()))
+                .collect(),
+            function_names: HashMap::new(),
+            default_function_name: FunctionName::default(),
+            options,
+        };
+        resource_cache.options.skip_internal_diff_if_external_is_configured = false;
+        for change in &changes {
+            writer.write_change(change, resource_cache, out)?;
+        }
+        Ok(())
+    }
+}
+
+/// A change to a single file, with trees already filtered out.
+struct FileChange {
+    source_location: BString,
+    location: BString,
+    old: Option<(EntryMode, ObjectId)>,
+    new: Option<(EntryMode, ObjectId)>,
+    rewrite: Option<Rewrite>,
+}
+
+struct Rewrite {
+    copy: bool,
+    /// The similarity in percent.
+    similarity: u32,
+}
+
+struct PatchWriter<'repo> {
+    repo: &'repo Repository,
+    /// The name and `xfuncname` value of each diff driver, by driver index.
+    xfuncnames: Vec<(BString, Option<BString>)>,
+    /// Compiled function name matchers, by driver index.
+    function_names: HashMap<usize, FunctionName>,
+    default_function_name: FunctionName,
+    options: Options,
+}
+
+impl PatchWriter<'_> {
+    fn write_change(
+        &mut self,
+        change: &FileChange,
+        resource_cache: &mut gix_diff::blob::Platform,
+        out: &mut dyn Write,
+    ) -> Result<(), Error> {
+        let options = self.options;
+        let old_name = quote_path("a/", change.source_location.as_ref());
+        let new_name = quote_path("b/", change.location.as_ref());
+        out.write_all(b"diff --git ")?;
+        out.write_all(&old_name)?;
+        out.write_all(b" ")?;
+        out.write_all(&new_name)?;
+        out.write_all(b"\n")?;
+        match (change.old, change.new) {
+            (None, Some((mode, _))) => writeln!(out, "new file mode {:06o}", mode.0)?,
+            (Some((mode, _)), None) => writeln!(out, "deleted file mode {:06o}", mode.0)?,
+            (Some((old_mode, _)), Some((new_mode, _))) if old_mode != new_mode => {
+                writeln!(out, "old mode {:06o}", old_mode.0)?;
+                writeln!(out, "new mode {:06o}", new_mode.0)?;
+            }
+            _ => {}
+        }
+        if let Some(rewrite) = &change.rewrite {
+            let kind = if rewrite.copy { "copy" } else { "rename" };
+            writeln!(out, "similarity index {}%", rewrite.similarity)?;
+            write!(out, "{kind} from ")?;
+            out.write_all(&quote_path("", change.source_location.as_ref()))?;
+            write!(out, "\n{kind} to ")?;
+            out.write_all(&quote_path("", change.location.as_ref()))?;
+            out.write_all(b"\n")?;
+        }
+
+        let (old_id, new_id) = (change.old.map(|(_, id)| id), change.new.map(|(_, id)| id));
+        if old_id == new_id {
+            return Ok(());
+        }
+        // Objects that don't exist, like the commits of submodules, are abbreviated without checking for ambiguity.
+        let abbreviate = |id: Option<ObjectId>| {
+            id.map(|id| match id.attach(self.repo).shorten() {
+                Ok(prefix) => prefix.to_string(),
+                Err(_) => id.to_hex_with_len(self.repo.config.hex_len.unwrap_or(7)).to_string(),
+            })
+        };
+        let (old_hex, new_hex) = (abbreviate(old_id), abbreviate(new_id));
+        let null_hex = || "0".repeat(old_hex.as_ref().or(new_hex.as_ref()).map_or(7, String::len));
+        write!(
+            out,
+            "index {}..{}",
+            old_hex.clone().unwrap_or_else(null_hex),
+            new_hex.clone().unwrap_or_else(null_hex)
+        )?;
+        match (change.old, change.new) {
+            (Some((old_mode, _)), Some((new_mode, _))) if old_mode == new_mode => writeln!(out, " {:06o}", old_mode.0)?,
+            _ => out.write_all(b"\n")?,
+        }
+
+        let old_label = if change.old.is_some() {
+            old_name
+        } else {
+            "/dev/null".into()
+        };
+        let new_label = if change.new.is_some() {
+            new_name
+        } else {
+            "/dev/null".into()
+        };
+        let is_submodule = |side: Option<(EntryMode, ObjectId)>| matches!(side, Some((mode, _)) if mode.is_commit());
+        if is_submodule(change.old) || is_submodule(change.new) {
+            let text = |side: Option<(EntryMode, ObjectId)>| {
+                side.map(|(_, id)| format!("Subproject commit {id}\n"))
+                    .unwrap_or_default()
+            };
+            let (old_text, new_text) = (text(change.old), text(change.new));
+            let input = gix_diff::blob::intern::InternedInput::new(
+                gix_diff::blob::sources::byte_lines_with_terminator(old_text.as_bytes()),
+                gix_diff::blob::sources::byte_lines_with_terminator(new_text.as_bytes()),
+            );
+            let sink = UnifiedDiff::new(
+                &input,
+                HunkWriter::new(out, &old_label, &new_label, options.word_diff),
+                options.context_size,
+            );
+            gix_diff::blob::diff(gix_diff::blob::Algorithm::Histogram, &input, sink)?;
+            return Ok(());
+        }
+
+        let object_hash = self.repo.object_hash();
+        for (side, kind, mode) in [
+            (change.old, ResourceKind::OldOrSource, change.new.map(|(mode, _)| mode)),
+            (
+                change.new,
+                ResourceKind::NewOrDestination,
+                change.old.map(|(mode, _)| mode),
+            ),
+        ] {
+            let (location, (mode, id)) = match (side, kind) {
+                (Some(side), ResourceKind::OldOrSource) => (change.source_location.as_bstr(), side),
+                (Some(side), ResourceKind::NewOrDestination) => (change.location.as_bstr(), side),
+                (None, _) => (
+                    change.location.as_bstr(),
+                    (mode.expect("at least one side is set"), object_hash.null()),
+                ),
+            };
+            resource_cache.set_resource(id, mode.kind(), location, kind, &self.repo.objects)?;
+        }
+
+        let prep = resource_cache.prepare_diff()?;
+        match prep.operation {
+            Operation::InternalDiff { algorithm } => {
+                let function_name = match prep.old.driver_index.or(prep.new.driver_index) {
+                    Some(idx) => self.function_name(idx)?,
+                    None => &self.default_function_name,
+                };
+                let input = prep.interned_input();
+                let sink = UnifiedDiff::new(
+                    &input,
+                    HunkWriter::new(out, &old_label, &new_label, options.word_diff),
+                    options.context_size,
+                )
+                .with_function_name(function_name);
+                gix_diff::blob::diff(algorithm, &input, sink)?;
+            }
+            Operation::ExternalCommand { .. } => {
+                unreachable!("we disabled that")
+            }
+            Operation::SourceOrDestinationIsBinary => {
+                out.write_all(b"Binary files ")?;
+                out.write_all(&old_label)?;
+                out.write_all(b" and ")?;
+                out.write_all(&new_label)?;
+                out.write_all(b" differ\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the function name matcher of the driver at `idx`, compiling it if needed.
+    fn function_name(&mut self, idx: usize) -> Result<&FunctionName, Error> {
+        let Some((name, Some(xfuncname))) = self.xfuncnames.get(idx) else {
+            return Ok(&self.default_function_name);
+        };
+        Ok(match self.function_names.entry(idx) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(
+                    FunctionName::from_xfuncname(xfuncname.as_ref()).map_err(|err| Error::FunctionName {
+                        name: name.clone(),
+                        source: err.into(),
+                    })?,
+                )
+            }
+        })
+    }
+}
+
+/// Write hunks to `out`, preceded by the `---` and `+++` lines before the first one.
+struct HunkWriter<'a> {
+    out: &'a mut dyn Write,
+    old_label: &'a BStr,
+    new_label: &'a BStr,
+    word_diff: bool,
+    wrote_labels: bool,
+}
+
+impl<'a> HunkWriter<'a> {
+    fn new(out: &'a mut dyn Write, old_label: &'a BString, new_label: &'a BString, word_diff: bool) -> Self {
+        HunkWriter {
+            out,
+            old_label: old_label.as_ref(),
+            new_label: new_label.as_ref(),
+            word_diff,
+            wrote_labels: false,
+        }
+    }
+}
+
+impl ConsumeHunk for HunkWriter<'_> {
+    type Out = ();
+
+    fn consume_hunk(&mut self, hunk: &Hunk<'_>) -> std::io::Result<()> {
+        if !self.wrote_labels {
+            for (prefix, label) in [("--- ", self.old_label), ("+++ ", self.new_label)] {
+                self.out.write_all(prefix.as_bytes())?;
+                self.out.write_all(label)?;
+                // Like `git`, help tools to find the end of names with spaces.
+                if label.contains(&b' ') {
+                    self.out.write_all(b"\t")?;
+                }
+                self.out.write_all(b"\n")?;
+            }
+            self.wrote_labels = true;
+        }
+        if self.word_diff {
+            hunk.write_words_to(self.out)
+        } else {
+            hunk.write_to(self.out)
+        }
+    }
+
+    fn finish(self) -> Self::Out {}
+}
+
+/// Return `true` if the kind of object changes between `a` and `b`, which isn't the case if only the executable bit changes.
+fn is_type_change(a: EntryMode, b: EntryMode) -> bool {
+    a.is_blob() != b.is_blob() || a.is_link() != b.is_link() || a.is_commit() != b.is_commit()
+}
+
+/// Return `prefix` followed by `path`, quoted and escaped like `git` does by default if `path` contains control characters,
+/// quotes, backslashes or non-ASCII characters.
+fn quote_path(prefix: &str, path: &BStr) -> BString {
+    let needs_quoting = |b: u8| b < 0x20 || b == b'"' || b == b'\\' || b >= 0x7f;
+    let mut out = BString::from(prefix);
+    if !path.iter().any(|b| needs_quoting(*b)) {
+        out.extend_from_slice(path);
+        return out;
+    }
+    out.insert(0, b'"');
+    for &b in path.iter() {
+        match b {
+            b'\x07' => out.extend_from_slice(b"\\a"),
+            b'\x08' => out.extend_from_slice(b"\\b"),
+            b'\t' => out.extend_from_slice(b"\\t"),
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\x0b' => out.extend_from_slice(b"\\v"),
+            b'\x0c' => out.extend_from_slice(b"\\f"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            b'"' | b'\\' => out.extend_from_slice(&[b'\\', b]),
+            b if needs_quoting(b) => out.extend_from_slice(format!("\\{b:03o}").as_bytes()),
+            b => out.push(b),
+        }
+    }
+    out.push(b'"');
+    out
+}
@@ -216,7 +216,7 @@ impl ThreadSafeRepository {
         let mut refs = {
             let reflog = repo_config.reflog.unwrap_or(gix_ref::store::WriteReflog::Disable);
             let object_hash = repo_config.object_hash;
-            match &common_dir {
+            let mut refs = match &common_dir {
                 Some(common_dir) => crate::RefStore::for_linked_worktree(
                     git_dir.to_owned(),
                     common_dir.into(),
@@ -225,7 +225,11 @@ impl ThreadSafeRepository {
                     repo_config.precompose_unicode,
                 ),
                 None => crate::RefStore::at(git_dir.to_owned(), reflog, object_hash, repo_config.precompose_unicode),
+            };
+            if repo_config.reftable {
+                refs.use_reftable();
             }
+            refs
         };
         let head = refs.find("HEAD").ok();
         let git_install_dir = crate::path::install_dir().ok();
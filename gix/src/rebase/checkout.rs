@@ -0,0 +1,172 @@
+use std::{collections::HashSet, path::Path, sync::atomic::AtomicBool};
+
+use gix_hash::{oid, ObjectId};
+use gix_index::entry::Flags;
+use gix_object::tree::EntryKind;
+
+use crate::{
+    bstr::{BStr, ByteSlice},
+    rebase::Error,
+    Repository,
+};
+
+/// Make the worktree and the index match `tree`, like `git reset --hard` would.
+///
+/// If `conflicts` is set, it's an index of `tree` with conflicting entries in stages 1 to 3, which will be written
+/// instead of an index that is created from `tree`.
+///
+/// Only files whose entry changed compared to the current index are written, and files that aren't part of `tree`
+/// anymore are removed.
+pub(crate) fn checkout(repo: &Repository, tree: &oid, conflicts: Option<gix_index::State>) -> Result<(), Error> {
+    let workdir = repo.work_dir().ok_or(Error::BareRepository)?;
+    let previous = if repo.index_path().is_file() {
+        Some(repo.open_index()?)
+    } else {
+        None
+    };
+    let mut index = repo.index_from_tree(tree)?;
+
+    let mut unchanged = Vec::new();
+    if let Some(previous) = &previous {
+        let paths: HashSet<&BStr> = index.entries().iter().map(|entry| entry.path(&index)).collect();
+        for entry in previous.entries() {
+            let path = entry.path(previous);
+            if !paths.contains(path) {
+                remove_file(workdir, path)?;
+            }
+        }
+
+        let (entries, backing) = index.entries_mut_and_pathbacking();
+        for (idx, entry) in entries.iter_mut().enumerate() {
+            let Some(previous) = previous.entry_by_path_and_stage(entry.path_in(backing), 0) else {
+                continue;
+            };
+            if previous.id == entry.id && previous.mode == entry.mode {
+                entry.stat = previous.stat;
+                if !previous.flags.contains(Flags::SKIP_WORKTREE) {
+                    unchanged.push(idx);
+                }
+                entry.flags.insert(Flags::SKIP_WORKTREE);
+            }
+        }
+    }
+
+    let mut options = repo
+        .config
+        .checkout_options(repo, gix_worktree::stack::state::attributes::Source::IdMapping)?;
+    options.overwrite_existing = true;
+    options.destination_is_initially_empty = false;
+    let outcome = gix_worktree_state::checkout(
+        &mut index,
+        workdir,
+        repo.objects.clone().into_arc()?,
+        &gix_features::progress::Discard,
+        &gix_features::progress::Discard,
+        &AtomicBool::default(),
+        options,
+    )?;
+    if let Some(record) = outcome.errors.into_iter().next() {
+        return Err(Error::CheckoutEntry {
+            path: record.path,
+            source: record.error,
+        });
+    }
+    for idx in unchanged {
+        index.entries_mut()[idx].flags.remove(Flags::SKIP_WORKTREE);
+    }
+
+    match conflicts {
+        Some(mut conflicts) => {
+            for (entry, path) in conflicts.entries_mut_with_paths() {
+                if let Some(checked_out) = index.entry_by_path_and_stage(path, 0) {
+                    if checked_out.id == entry.id {
+                        entry.stat = checked_out.stat;
+                    }
+                }
+            }
+            gix_index::File::from_state(conflicts, repo.index_path()).write(Default::default())?;
+        }
+        None => index.write(Default::default())?,
+    }
+    Ok(())
+}
+
+/// Return `true` if the stage 0 entries of `index` are exactly those of `tree`.
+pub(crate) fn index_matches_tree(repo: &Repository, index: &gix_index::State, tree: &oid) -> Result<bool, Error> {
+    let expected = gix_index::State::from_tree(tree, &repo.objects)?;
+    Ok(index.entries().len() == expected.entries().len()
+        && index.entries().iter().zip(expected.entries()).all(|(actual, wanted)| {
+            actual.stage() == 0
+                && actual.id == wanted.id
+                && actual.mode == wanted.mode
+                && actual.path(index) == wanted.path(&expected)
+        }))
+}
+
+/// Write the entries of `index` as tree and return its id. `index` must not have any conflicts.
+pub(crate) fn write_tree(repo: &Repository, index: &gix_index::State) -> Result<ObjectId, Error> {
+    let entries: Vec<_> = index
+        .entries()
+        .iter()
+        .filter_map(|entry| Some((entry.path(index), entry.mode.to_tree_entry_mode()?, entry.id)))
+        .collect();
+    write_tree_inner(repo, &entries)
+}
+
+fn write_tree_inner(
+    repo: &Repository,
+    entries: &[(&BStr, gix_object::tree::EntryMode, ObjectId)],
+) -> Result<ObjectId, Error> {
+    let mut tree = gix_object::Tree::empty();
+    let mut idx = 0;
+    while idx < entries.len() {
+        let (location, mode, id) = entries[idx];
+        match location.find_byte(b'/') {
+            None => {
+                tree.entries.push(gix_object::tree::Entry {
+                    mode,
+                    filename: location.to_owned(),
+                    oid: id,
+                });
+                idx += 1;
+            }
+            Some(pos) => {
+                let directory = &location[..=pos];
+                let end = entries[idx..]
+                    .iter()
+                    .position(|(location, _, _)| !location.starts_with(directory))
+                    .map_or(entries.len(), |len| idx + len);
+                let children: Vec<_> = entries[idx..end]
+                    .iter()
+                    .map(|(location, mode, id)| (location[pos + 1..].as_bstr(), *mode, *id))
+                    .collect();
+                tree.entries.push(gix_object::tree::Entry {
+                    mode: EntryKind::Tree.into(),
+                    filename: location[..pos].into(),
+                    oid: write_tree_inner(repo, &children)?,
+                });
+                idx = end;
+            }
+        }
+    }
+    tree.entries.sort();
+    Ok(repo.write_object(&tree)?.detach())
+}
+
+/// Remove the file at `path` in `workdir`, along with all of its parent directories that are empty then.
+fn remove_file(workdir: &Path, path: &BStr) -> Result<(), Error> {
+    let path = workdir.join(gix_path::from_bstr(path));
+    match std::fs::remove_file(&path) {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(source) => return Err(Error::UpdateWorktree { path, source }),
+    }
+    let mut dir = path.parent();
+    while let Some(parent) = dir.filter(|dir| *dir != workdir) {
+        if std::fs::remove_dir(parent).is_err() {
+            break;
+        }
+        dir = parent.parent();
+    }
+    Ok(())
+}
@@ -0,0 +1,612 @@
+use std::path::PathBuf;
+
+use gix_hash::ObjectId;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use super::{checkout, Editor, Error, FixupMessage, Instruction, Outcome, State, Stop, Stopped, UpdateRef};
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    Commit, Repository,
+};
+
+/// Performs the instructions of a rebase one by one, and keeps its state on disk so it can be resumed at any time.
+pub(crate) struct Engine<'repo, 'editor> {
+    repo: &'repo Repository,
+    dir: PathBuf,
+    state: State,
+    editor: &'editor mut dyn Editor,
+    committer: gix_actor::Signature,
+    merge_options: gix_merge::tree::Options,
+}
+
+/// Lifecycle
+impl<'repo, 'editor> Engine<'repo, 'editor> {
+    pub(crate) fn new(repo: &'repo Repository, state: State, editor: &'editor mut dyn Editor) -> Result<Self, Error> {
+        Ok(Engine {
+            repo,
+            dir: repo.git_dir().join(gix_rebase::DIR_NAME),
+            state,
+            editor,
+            committer: repo.committer().ok_or(Error::MissingCommitter)??.to_owned(),
+            merge_options: repo.tree_merge_options()?,
+        })
+    }
+
+    /// Detach `HEAD` at `onto` and perform all instructions.
+    pub(crate) fn start(self) -> Result<Outcome, Error> {
+        self.save()?;
+        let onto = self.state.onto;
+        self.reset_to(onto)?;
+        self.set_head(onto, format!("rebase (start): checkout {onto}"))?;
+        self.run()
+    }
+
+    /// Commit the changes that the user staged after the rebase stopped, and perform all remaining instructions.
+    pub(crate) fn resume(mut self) -> Result<Outcome, Error> {
+        let index = self.repo.open_index()?;
+        if index.entries().iter().any(|entry| entry.stage() != 0) {
+            return Err(Error::UnresolvedConflicts);
+        }
+        let tree = checkout::write_tree(self.repo, &index)?;
+        let head = self.repo.head_commit()?;
+        let stopped = self.state.stopped.take();
+        if tree != head.tree_id()? {
+            let stopped = stopped.ok_or(Error::DirtyWorktree)?;
+            let last = self.state.done.last().cloned();
+            let (parents, author, message) = if let Some(amend) = stopped.amend {
+                if amend != head.id {
+                    return Err(Error::AmendHeadChanged);
+                }
+                (
+                    head.parent_ids().map(crate::Id::detach).collect(),
+                    head.author()?.to_owned(),
+                    head.message_raw()?.to_owned(),
+                )
+            } else {
+                let mut message = stopped.message.ok_or(Error::MissingCommitInformation)?;
+                if matches!(&last, Some(instruction) if needs_message_edit(instruction)) {
+                    self.edit_message(&mut message)?;
+                }
+                if self.state.current_fixups.is_empty() {
+                    let mut parents = vec![head.id];
+                    if let Some(Instruction::Merge { labels, .. }) = &last {
+                        parents.push(self.resolve_label(labels[0].as_ref())?);
+                    }
+                    (
+                        parents,
+                        stopped.author.unwrap_or_else(|| self.committer.clone()),
+                        message,
+                    )
+                } else {
+                    (
+                        head.parent_ids().map(crate::Id::detach).collect(),
+                        head.author()?.to_owned(),
+                        message,
+                    )
+                }
+            };
+            let id = self.commit(tree, parents, author, message.clone())?;
+            self.set_head(id, reflog_message("continue", message.as_ref()))?;
+        }
+        self.remove_stop_refs()?;
+        self.run()
+    }
+
+    /// Discard all changes since the rebase stopped, and perform all remaining instructions.
+    pub(crate) fn skip(mut self) -> Result<Outcome, Error> {
+        if self.state.stopped.take().is_some()
+            && matches!(self.state.done.last(), Some(instruction) if instruction.is_fixup())
+        {
+            self.state.current_fixups.pop();
+        }
+        let head = self.repo.head_id()?.detach();
+        self.reset_to(head)?;
+        self.remove_stop_refs()?;
+        self.run()
+    }
+
+    /// Restore `HEAD` and the worktree as they were before the rebase started, and remove all of its state.
+    pub(crate) fn abort(self) -> Result<(), Error> {
+        let orig_head = self.state.orig_head;
+        self.reset_to(orig_head)?;
+        match &self.state.head_name {
+            Some(branch) => {
+                let branch: FullName = branch.as_bstr().try_into()?;
+                let message = format!("rebase (abort): returning to {}", branch.as_bstr());
+                self.set_head_to_branch(branch, message)?;
+            }
+            None => self.set_head(orig_head, format!("rebase (abort): returning to {orig_head}"))?,
+        }
+        self.remove_stop_refs()?;
+        self.cleanup()
+    }
+}
+
+/// Performing instructions
+impl<'repo, 'editor> Engine<'repo, 'editor> {
+    fn run(mut self) -> Result<Outcome, Error> {
+        while !self.state.todo.is_empty() {
+            let instruction = self.state.todo.remove(0);
+            if !instruction.is_fixup() {
+                self.state.current_fixups.clear();
+            }
+            self.state.done.push(instruction.clone());
+            self.save()?;
+            if let Some(stop) = self.perform(&instruction)? {
+                self.save()?;
+                return Ok(Outcome::Stopped(stop));
+            }
+        }
+        self.finish()
+    }
+
+    fn perform(&mut self, instruction: &Instruction) -> Result<Option<Stop>, Error> {
+        match instruction {
+            Instruction::Pick { .. }
+            | Instruction::Reword { .. }
+            | Instruction::Edit { .. }
+            | Instruction::Squash { .. }
+            | Instruction::Fixup { .. } => self.pick(instruction),
+            Instruction::Exec { command } => self.exec(command.as_ref()),
+            Instruction::Break => Ok(Some(Stop::Break)),
+            Instruction::Drop { .. } | Instruction::Noop => Ok(None),
+            Instruction::Label { name } => {
+                let head = self.repo.head_id()?;
+                self.repo.reference(
+                    rewritten_ref_name(name.as_ref()),
+                    head,
+                    PreviousValue::Any,
+                    format!("rebase (label) '{name}'"),
+                )?;
+                Ok(None)
+            }
+            Instruction::Reset { target } => {
+                let id = self.resolve_label(target.as_ref())?;
+                self.reset_to(id)?;
+                self.set_head(id, format!("rebase (reset): '{target}'"))?;
+                Ok(None)
+            }
+            Instruction::Merge {
+                commit,
+                edit_message,
+                labels,
+                summary,
+            } => self.merge(
+                commit.as_ref().map(AsRef::as_ref),
+                *edit_message,
+                labels,
+                summary.as_ref(),
+            ),
+            Instruction::UpdateRef { name } => {
+                let head = self.repo.head_id()?.detach();
+                match self.state.update_refs.iter_mut().find(|update| update.name == *name) {
+                    Some(update) => update.after = Some(head),
+                    None => {
+                        let before = self
+                            .repo
+                            .try_find_reference(name.as_bstr())?
+                            .map(|mut reference| reference.peel_to_id_in_place().map(crate::Id::detach))
+                            .transpose()?
+                            .unwrap_or_else(|| ObjectId::null(self.repo.object_hash()));
+                        self.state.update_refs.push(UpdateRef {
+                            name: name.clone(),
+                            before,
+                            after: Some(head),
+                        });
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Apply the changes of the commit of `instruction`, which picks it or melds it into `HEAD`.
+    fn pick(&mut self, instruction: &Instruction) -> Result<Option<Stop>, Error> {
+        let commit = self.resolve(instruction.commit().expect("only called for instructions with commit"))?;
+        let mut parents = commit.parent_ids().map(crate::Id::detach);
+        let parent = parents.next();
+        if parents.next().is_some() {
+            return Err(Error::PickMerge { id: commit.id });
+        }
+        let amend = instruction.is_fixup();
+        if amend && !self.has_previous_commit() {
+            return Err(Error::NoPreviousCommit {
+                command: instruction.name(),
+            });
+        }
+        let head = self.repo.head_commit()?;
+
+        if parent == Some(head.id) && matches!(instruction, Instruction::Pick { .. } | Instruction::Edit { .. }) {
+            self.reset_to(commit.id)?;
+            self.set_head(commit.id, reflog_message(instruction.name(), commit.message_raw()?))?;
+            return self.stop_for_edit(instruction, &commit, commit.id);
+        }
+
+        let message = self.message(instruction, &commit, &head)?;
+        let author = if amend { head.author()? } else { commit.author()? }.to_owned();
+        let base_tree = match parent {
+            Some(parent) => self.repo.find_object(parent)?.try_into_commit()?.tree_id()?.detach(),
+            None => ObjectId::empty_tree(self.repo.object_hash()),
+        };
+        let head_tree = head.tree_id()?.detach();
+        let commit_tree = commit.tree_id()?.detach();
+        let their_label = format!("{} ({})", commit.id.to_hex_with_len(7), commit.message()?.summary());
+        let ancestor_label = format!("parent of {their_label}");
+        let outcome = self.repo.merge_trees(
+            base_tree,
+            head_tree,
+            commit_tree,
+            gix_merge::blob::Labels {
+                ancestor: Some(ancestor_label.as_str().into()),
+                current: Some("HEAD".into()),
+                other: Some(their_label.as_str().into()),
+            },
+            self.merge_options.clone(),
+        )?;
+        if amend {
+            self.state.current_fixups.push(instruction.clone());
+        }
+        if outcome.has_unresolved_conflicts() {
+            let mut paths: Vec<_> = outcome.conflicts.iter().map(|c| c.location.clone()).collect();
+            paths.dedup();
+            checkout::checkout(self.repo, &outcome.tree, Some(outcome.index))?;
+            self.state.stopped = Some(Stopped {
+                commit: Some(commit.id),
+                amend: None,
+                message: Some(message),
+                author: Some(author),
+            });
+            self.repo
+                .reference("REBASE_HEAD", commit.id, PreviousValue::Any, "rebase")?;
+            return Ok(Some(Stop::Conflict {
+                commit: commit.id,
+                paths,
+            }));
+        }
+        if !amend && outcome.tree == head_tree && commit_tree != base_tree {
+            // All changes are present already, so there is nothing to commit.
+            return Ok(None);
+        }
+
+        let mut message = message;
+        if needs_message_edit(instruction) {
+            self.edit_message(&mut message)?;
+        }
+        let parents = if amend {
+            head.parent_ids().map(crate::Id::detach).collect()
+        } else {
+            vec![head.id]
+        };
+        let id = self.commit(outcome.tree, parents, author, message.clone())?;
+        checkout::checkout(self.repo, &outcome.tree, None)?;
+        self.set_head(id, reflog_message(instruction.name(), message.as_ref()))?;
+        self.stop_for_edit(instruction, &commit, id)
+    }
+
+    /// Stop if `instruction` is an `edit` that applied `commit` as `id`.
+    fn stop_for_edit(
+        &mut self,
+        instruction: &Instruction,
+        commit: &Commit<'_>,
+        id: ObjectId,
+    ) -> Result<Option<Stop>, Error> {
+        if !matches!(instruction, Instruction::Edit { .. }) {
+            return Ok(None);
+        }
+        self.state.stopped = Some(Stopped {
+            commit: Some(commit.id),
+            amend: Some(id),
+            message: Some(commit.message_raw()?.to_owned()),
+            author: Some(commit.author()?.to_owned()),
+        });
+        self.repo
+            .reference("REBASE_HEAD", commit.id, PreviousValue::Any, "rebase")?;
+        Ok(Some(Stop::Edit { commit: commit.id }))
+    }
+
+    /// Return the message to use for the commit created by `instruction`, which applies `commit` onto `head`.
+    fn message(&self, instruction: &Instruction, commit: &Commit<'_>, head: &Commit<'_>) -> Result<BString, Error> {
+        let message = commit.message_raw()?;
+        Ok(match instruction {
+            Instruction::Fixup {
+                message: FixupMessage::Discard,
+                ..
+            } => head.message_raw()?.to_owned(),
+            Instruction::Fixup { .. } => match message.strip_prefix(b"amend! ") {
+                Some(rest) => rest
+                    .find("\n\n")
+                    .map_or(BString::default(), |pos| rest[pos + 2..].into()),
+                None => message.to_owned(),
+            },
+            Instruction::Squash { .. } => {
+                let mut combined: BString = head.message_raw()?.trim_end().into();
+                combined.push_str("\n\n");
+                combined.push_str(message);
+                combined
+            }
+            _ => message.to_owned(),
+        })
+    }
+
+    fn merge(
+        &mut self,
+        commit: Option<&BStr>,
+        edit_message: bool,
+        labels: &[BString],
+        summary: &BStr,
+    ) -> Result<Option<Stop>, Error> {
+        let label = match labels {
+            [label] => label,
+            _ => return Err(Error::OctopusMerge),
+        };
+        let other = self.resolve_label(label.as_ref())?;
+        let head = self.repo.head_commit()?;
+        let original = commit.map(|name| self.resolve(name)).transpose()?;
+        if let Some(original) = &original {
+            if original.parent_ids().map(crate::Id::detach).eq([head.id, other]) {
+                self.reset_to(original.id)?;
+                self.set_head(original.id, reflog_message("merge", summary))?;
+                return Ok(None);
+            }
+        }
+
+        let other_commit = self.repo.find_object(other)?.try_into_commit()?;
+        let base_tree = match self.repo.merge_base(head.id, other) {
+            Ok(base) => self.repo.find_object(base)?.try_into_commit()?.tree_id()?.detach(),
+            Err(crate::repository::merge_base::Error::NotFound { .. }) => ObjectId::empty_tree(self.repo.object_hash()),
+            Err(crate::repository::merge_base::Error::MergeBase(err)) => return Err(err.into()),
+        };
+        let outcome = self.repo.merge_trees(
+            base_tree,
+            head.tree_id()?,
+            other_commit.tree_id()?,
+            gix_merge::blob::Labels {
+                ancestor: Some("merged common ancestors".into()),
+                current: Some("HEAD".into()),
+                other: Some(label.as_bstr()),
+            },
+            self.merge_options.clone(),
+        )?;
+        let (mut message, author) = match &original {
+            Some(original) => (original.message_raw()?.to_owned(), original.author()?.to_owned()),
+            None => {
+                let mut message: BString = summary.into();
+                message.push(b'\n');
+                (message, self.committer.clone())
+            }
+        };
+        if outcome.has_unresolved_conflicts() {
+            let mut paths: Vec<_> = outcome.conflicts.iter().map(|c| c.location.clone()).collect();
+            paths.dedup();
+            checkout::checkout(self.repo, &outcome.tree, Some(outcome.index))?;
+            self.state.stopped = Some(Stopped {
+                commit: Some(original.as_ref().map_or(other, |original| original.id)),
+                amend: None,
+                message: Some(message),
+                author: Some(author),
+            });
+            self.repo.reference("MERGE_HEAD", other, PreviousValue::Any, "rebase")?;
+            return Ok(Some(Stop::Conflict { commit: other, paths }));
+        }
+
+        if edit_message {
+            self.edit_message(&mut message)?;
+        }
+        let id = self.commit(outcome.tree, vec![head.id, other], author, message)?;
+        checkout::checkout(self.repo, &outcome.tree, None)?;
+        self.set_head(id, reflog_message("merge", summary))?;
+        Ok(None)
+    }
+
+    fn exec(&mut self, command: &BStr) -> Result<Option<Stop>, Error> {
+        let workdir = self.repo.work_dir().ok_or(Error::BareRepository)?;
+        let mut cmd: std::process::Command = gix_command::prepare(gix_path::from_bstr(command).into_owned())
+            .with_shell()
+            .stdin(std::process::Stdio::inherit())
+            .stdout(std::process::Stdio::inherit())
+            .into();
+        let status = cmd.current_dir(workdir).status().map_err(|source| Error::Exec {
+            command: command.to_owned(),
+            source,
+        })?;
+        Ok((!status.success()).then(|| Stop::Exec {
+            command: command.to_owned(),
+            status,
+        }))
+    }
+
+    /// Update all references and return to the rebased branch, if there was one.
+    fn finish(self) -> Result<Outcome, Error> {
+        let head = self.repo.head_id()?.detach();
+        for update in &self.state.update_refs {
+            if let Some(after) = update.after {
+                self.repo.reference(
+                    update.name.as_bstr(),
+                    after,
+                    PreviousValue::Any,
+                    format!("rebase (update-refs): {}", update.name),
+                )?;
+            }
+        }
+        if let Some(branch) = &self.state.head_name {
+            let branch: FullName = branch.as_bstr().try_into()?;
+            self.repo.edit_reference(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: format!("rebase (finish): {} onto {}", branch.as_bstr(), self.state.onto).into(),
+                    },
+                    expected: PreviousValue::Any,
+                    new: Target::Peeled(head),
+                },
+                name: branch.clone(),
+                deref: false,
+            })?;
+            let message = format!("rebase (finish): returning to {}", branch.as_bstr());
+            self.set_head_to_branch(branch, message)?;
+        }
+        self.repo
+            .reference("ORIG_HEAD", self.state.orig_head, PreviousValue::Any, "rebase")?;
+        self.cleanup()?;
+        Ok(Outcome::Done { head })
+    }
+}
+
+/// Utilities
+impl<'repo, 'editor> Engine<'repo, 'editor> {
+    fn save(&self) -> Result<(), Error> {
+        Ok(self.state.write_to_dir(&self.dir)?)
+    }
+
+    /// Remove the state of the rebase, along with all labels.
+    fn cleanup(self) -> Result<(), Error> {
+        let labels = self
+            .repo
+            .references()?
+            .prefixed("refs/rewritten/")?
+            .filter_map(Result::ok)
+            .map(|reference| reference.detach().name)
+            .collect::<Vec<_>>();
+        self.repo.edit_references(labels.into_iter().map(|name| RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::Any,
+                log: RefLog::AndReference,
+            },
+            name,
+            deref: false,
+        }))?;
+        std::fs::remove_dir_all(&self.dir).map_err(|source| Error::UpdateWorktree {
+            path: self.dir.clone(),
+            source,
+        })
+    }
+
+    /// Remove the references that are written when stopping.
+    fn remove_stop_refs(&self) -> Result<(), Error> {
+        for name in ["REBASE_HEAD", "MERGE_HEAD"] {
+            if let Some(reference) = self.repo.try_find_reference(name)? {
+                reference.delete()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn has_previous_commit(&self) -> bool {
+        self.state.done.iter().rev().skip(1).any(|instruction| {
+            !matches!(instruction, Instruction::Drop { .. }) && instruction.commit().is_some()
+                || matches!(instruction, Instruction::Merge { .. } | Instruction::Reset { .. })
+        })
+    }
+
+    fn resolve(&self, name: &BStr) -> Result<Commit<'repo>, Error> {
+        self.repo
+            .rev_parse_single(name)
+            .map_err(Into::into)
+            .and_then(|id| id.object().map_err(Into::into))
+            .and_then(|object| {
+                object
+                    .peel_to_kind(gix_object::Kind::Commit)
+                    .map(crate::Object::into_commit)
+                    .map_err(Into::into)
+            })
+            .map_err(
+                |source: Box<dyn std::error::Error + Send + Sync>| Error::ResolveCommit {
+                    name: name.to_owned(),
+                    source,
+                },
+            )
+    }
+
+    /// Resolve `name` as label, or as commit if there is no such label.
+    fn resolve_label(&self, name: &BStr) -> Result<ObjectId, Error> {
+        match self.repo.try_find_reference(rewritten_ref_name(name).as_bstr())? {
+            Some(mut reference) => Ok(reference.peel_to_id_in_place()?.detach()),
+            None => Ok(self.resolve(name)?.id),
+        }
+    }
+
+    fn edit_message(&mut self, message: &mut BString) -> Result<(), Error> {
+        self.editor.edit_message(message).map_err(Error::Editor)
+    }
+
+    fn commit(
+        &self,
+        tree: ObjectId,
+        parents: Vec<ObjectId>,
+        author: gix_actor::Signature,
+        message: BString,
+    ) -> Result<ObjectId, Error> {
+        let commit = gix_object::Commit {
+            tree,
+            parents: parents.into(),
+            author,
+            committer: self.committer.clone(),
+            encoding: None,
+            message,
+            extra_headers: Vec::new(),
+        };
+        Ok(self.repo.write_object(&commit)?.detach())
+    }
+
+    /// Make the worktree and the index match the tree of commit `id`.
+    fn reset_to(&self, id: ObjectId) -> Result<(), Error> {
+        let tree = self.repo.find_object(id)?.try_into_commit()?.tree_id()?;
+        checkout::checkout(self.repo, &tree, None)
+    }
+
+    /// Detach `HEAD` at `id`.
+    fn set_head(&self, id: ObjectId, message: impl Into<BString>) -> Result<(), Error> {
+        self.edit_head(Target::Peeled(id), message.into())
+    }
+
+    fn set_head_to_branch(&self, branch: FullName, message: impl Into<BString>) -> Result<(), Error> {
+        self.edit_head(Target::Symbolic(branch), message.into())
+    }
+
+    fn edit_head(&self, new: Target, message: BString) -> Result<(), Error> {
+        self.repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message,
+                },
+                expected: PreviousValue::Any,
+                new,
+            },
+            name: "HEAD".try_into().expect("valid"),
+            deref: false,
+        })?;
+        Ok(())
+    }
+}
+
+/// Return `true` if the message of the commit that `instruction` creates should be edited.
+fn needs_message_edit(instruction: &Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::Reword { .. }
+            | Instruction::Squash { .. }
+            | Instruction::Fixup {
+                message: FixupMessage::ReplaceAndEdit,
+                ..
+            }
+            | Instruction::Merge { edit_message: true, .. }
+    )
+}
+
+fn reflog_message(command: &str, message: &BStr) -> BString {
+    let mut out = BString::from(format!("rebase ({command}): "));
+    out.push_str(message.lines().next().unwrap_or_default());
+    out
+}
+
+fn rewritten_ref_name(label: &BStr) -> BString {
+    let mut name = BString::from("refs/rewritten/");
+    name.push_str(label);
+    name
+}
@@ -0,0 +1,202 @@
+//! Rebase commits onto another commit, interactively or not, with the state of rebases in progress being kept in
+//! `.git/rebase-merge` just like `git rebase` does, so `git` and `gitoxide` can continue each others rebases.
+//!
+//! Rebases are started with [`Repository::rebase()`](crate::Repository::rebase()), and when they stop, they can be
+//! resumed with [`Repository::rebase_continue()`](crate::Repository::rebase_continue()) or
+//! [`Repository::rebase_skip()`](crate::Repository::rebase_skip()), or undone with
+//! [`Repository::rebase_abort()`](crate::Repository::rebase_abort()).
+use crate::bstr::BString;
+pub use gix_rebase::{State, Stopped, UpdateRef};
+pub use gix_sequencer::{FixupMessage, Instruction};
+
+pub(crate) mod checkout;
+pub(crate) mod engine;
+
+/// Options to configure how [`Repository::rebase()`](crate::Repository::rebase()) works.
+///
+/// Use [`Repository::rebase_options()`](crate::Repository::rebase_options()) to obtain options as configured.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, let the [`Editor`] change the instructions before the rebase starts, like `git rebase --interactive`.
+    pub interactive: bool,
+    /// If `true`, move commits whose summary starts with `fixup!`, `squash!` or `amend!` right after the commit they refer to,
+    /// like `git rebase --autosquash`.
+    pub autosquash: bool,
+    /// If `true`, also update local branches that point to one of the rebased commits once the rebase is complete,
+    /// like `git rebase --update-refs`.
+    pub update_refs: bool,
+}
+
+/// The error an [`Editor`] may return to stop the operation.
+pub type EditorError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// A way for the user to change instructions and commit messages during a rebase, similar to what `git` does with
+/// `sequence.editor` and `core.editor`.
+pub trait Editor {
+    /// Change `instructions` as desired before they are performed. Removing all instructions aborts the rebase.
+    ///
+    /// This is only called if [`Options::interactive`] is set, and does nothing by default.
+    fn edit_instructions(&mut self, instructions: &mut Vec<Instruction>) -> Result<(), EditorError> {
+        _ = instructions;
+        Ok(())
+    }
+
+    /// Change `message` as desired before it's used for a commit, which happens for `reword`, `squash` and `fixup -c`
+    /// instructions, and for `merge -c`.
+    ///
+    /// By default, the message is left as is.
+    fn edit_message(&mut self, message: &mut BString) -> Result<(), EditorError> {
+        _ = message;
+        Ok(())
+    }
+}
+
+/// An [`Editor`] that doesn't change anything.
+#[derive(Default, Debug, Copy, Clone)]
+pub struct NoEdit;
+
+impl Editor for NoEdit {}
+
+/// The outcome of starting or resuming a rebase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// All instructions were performed and the rebase is complete, with `HEAD` pointing to `head`.
+    Done {
+        /// The commit `HEAD` points to now.
+        head: gix_hash::ObjectId,
+    },
+    /// The rebase stopped and is still in progress. It can be resumed with
+    /// [`Repository::rebase_continue()`](crate::Repository::rebase_continue()).
+    Stopped(Stop),
+}
+
+/// The reason a rebase [stopped](Outcome::Stopped).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stop {
+    /// `commit` couldn't be applied without conflicts, which are left in the worktree and the index to be resolved.
+    Conflict {
+        /// The commit that was applied.
+        commit: gix_hash::ObjectId,
+        /// The paths of all conflicting entries, relative to the root of the worktree.
+        paths: Vec<BString>,
+    },
+    /// An `edit` instruction applied `commit`, which can now be amended.
+    Edit {
+        /// The commit that was applied.
+        commit: gix_hash::ObjectId,
+    },
+    /// A `break` instruction was performed.
+    Break,
+    /// The command of an `exec` instruction failed.
+    Exec {
+        /// The command that was run.
+        command: BString,
+        /// The exit status of the command.
+        status: std::process::ExitStatus,
+    },
+}
+
+/// The error returned by [`Repository::rebase()`](crate::Repository::rebase()) and the methods to resume or abort rebases.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("A rebase is already in progress")]
+    InProgress,
+    #[error("There is no rebase in progress")]
+    NotInProgress,
+    #[error("Rebases need a worktree")]
+    BareRepository,
+    #[error("Cannot rebase with uncommitted changes in the index or the worktree")]
+    DirtyWorktree,
+    #[error("Cannot continue while there are unresolved conflicts in the index")]
+    UnresolvedConflicts,
+    #[error("All instructions were removed, there is nothing to do")]
+    NothingToDo,
+    #[error("HEAD was moved to another commit after the rebase stopped for an edit, which would lose changes")]
+    AmendHeadChanged,
+    #[error("Cannot '{command}' without a previous commit")]
+    NoPreviousCommit { command: &'static str },
+    #[error("Commit {id} is a merge, which can only be recreated with the 'merge' instruction")]
+    PickMerge { id: gix_hash::ObjectId },
+    #[error("Merging more than one label at once isn't supported yet")]
+    OctopusMerge,
+    #[error("Could not resolve '{name}' to a commit")]
+    ResolveCommit {
+        name: BString,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    #[error("There is no information about the commit to create when continuing")]
+    MissingCommitInformation,
+    #[error("The editor failed")]
+    Editor(#[source] EditorError),
+    #[error("Could not run '{command}'")]
+    Exec { command: BString, source: std::io::Error },
+    #[error("Could not update the worktree at '{}'", path.display())]
+    UpdateWorktree {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Could not check out '{path}'")]
+    CheckoutEntry {
+        path: BString,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[from] std::io::Error),
+    #[error(transparent)]
+    InvalidRefName(#[from] gix_validate::reference::name::Error),
+    #[error(transparent)]
+    ReadState(#[from] gix_rebase::read::Error),
+    #[error(transparent)]
+    WriteState(#[from] gix_rebase::write::Error),
+    #[error(transparent)]
+    IsDirty(#[from] crate::status::is_dirty::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    IndexFromTree(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    Checkout(#[from] gix_worktree_state::checkout::Error),
+    #[error(transparent)]
+    MergeOptions(#[from] crate::repository::merge::tree_merge_options::Error),
+    #[error(transparent)]
+    MergeTrees(#[from] crate::repository::merge::merge_trees::Error),
+    #[error(transparent)]
+    MergeBase(#[from] gix_revision::merge_base::Error),
+    #[error(transparent)]
+    Walk(#[from] crate::revision::walk::Error),
+    #[error(transparent)]
+    WalkCommits(#[from] gix_traverse::commit::ancestors::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    TryFindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    IterReferences(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    IterReferencesInit(#[from] crate::reference::iter::init::Error),
+    #[error(transparent)]
+    PeelReference(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    HeadId(#[from] crate::reference::head_id::Error),
+    #[error(transparent)]
+    HeadCommit(#[from] crate::reference::head_commit::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    NotACommit(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    Committer(#[from] crate::config::time::Error),
+    #[error("A committer is required to create commits, but none was configured")]
+    MissingCommitter,
+}
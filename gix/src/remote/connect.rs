@@ -111,6 +111,10 @@ impl<'repo> Remote<'repo> {
         fn sanitize(mut url: gix_url::Url) -> Result<gix_url::Url, Error> {
             if url.scheme == gix_url::Scheme::File {
                 let mut dir = gix_path::to_native_path_on_windows(Cow::Borrowed(url.path.as_ref()));
+                #[cfg(feature = "blocking-network-client")]
+                if gix_bundle::is_bundle(dir.as_ref()) {
+                    return Ok(url);
+                }
                 let kind = gix_discover::is_git(dir.as_ref())
                     .or_else(|_| {
                         dir.to_mut().push(gix_discover::DOT_GIT_DIR);
@@ -24,3 +24,20 @@ pub fn pack_index_version(repo: &Repository) -> Result<gix_pack::index::Version,
         .with_leniency(repo.options.lenient_config)?
         .unwrap_or(gix_pack::index::Version::V2))
 }
+
+pub fn pack_write_reverse_index(repo: &Repository) -> Result<bool, Error> {
+    Ok(repo
+        .config
+        .resolved
+        .boolean_filter(
+            "pack",
+            None,
+            Pack::WRITE_REVERSE_INDEX.name,
+            &mut repo.filter_config_section(),
+        )
+        .map(|value| Pack::WRITE_REVERSE_INDEX.enrich_error(value))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)
+        .map_err(Error::PackWriteReverseIndexConfig)?
+        .unwrap_or(false))
+}
@@ -45,6 +45,23 @@ pub enum Error {
     RejectShallowRemote,
     #[error(transparent)]
     NegotiationAlgorithmConfig(#[from] config::key::GenericErrorWithValue),
+    #[error("Could not obtain configuration to learn if a reverse index should be written")]
+    PackWriteReverseIndexConfig(#[source] config::boolean::Error),
+    #[error("Could not obtain configuration to learn if FETCH_HEAD should be written")]
+    WriteFetchHeadConfig(#[source] config::boolean::Error),
+    #[error("FETCH_HEAD could not be locked in preparation for writing it")]
+    LockFetchHead(#[source] gix_lock::acquire::Error),
+    #[error("Failed to write FETCH_HEAD at \"{}\"", path.display())]
+    WriteFetchHead {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[cfg(feature = "blocking-network-client")]
+    #[error(transparent)]
+    OpenBundle(#[from] gix_bundle::file::Error),
+    #[cfg(feature = "blocking-network-client")]
+    #[error("The bundle to fetch from can't be used with this repository")]
+    VerifyBundle(#[from] crate::bundle::verify::Error),
 }
 
 impl gix_protocol::transport::IsSpuriousError for Error {
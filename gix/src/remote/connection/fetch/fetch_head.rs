@@ -0,0 +1,142 @@
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    config::{cache::util::ApplyLeniency, tree::Fetch},
+    remote,
+    remote::fetch::{refs, refs::update::Mode, Error, Mapping, SpecIndex, Tags, WriteFetchHead},
+    Remote, Repository,
+};
+
+/// Write `.git/FETCH_HEAD` with one line for each of the `mappings` the way `git fetch` does, using `updates` to learn
+/// which of the implicitly included tags were actually fetched.
+pub(crate) fn write(
+    repo: &Repository,
+    remote: &Remote<'_>,
+    mode: WriteFetchHead,
+    mappings: &[Mapping],
+    extra_refspecs: &[gix_refspec::RefSpec],
+    updates: &refs::update::Outcome,
+) -> Result<(), Error> {
+    if mode == WriteFetchHead::Never || !is_enabled(repo)? {
+        return Ok(());
+    }
+    let refspecs = remote.refspecs(remote::Direction::Fetch);
+    let url = remote
+        .url(remote::Direction::Fetch)
+        .map(|url| {
+            let mut url = url.clone();
+            url.set_user(None);
+            url.set_password(None);
+            url.to_bstring()
+        })
+        .unwrap_or_default();
+    let implicit_tag_refspec = remote
+        .fetch_tags
+        .to_refspec()
+        .filter(|_| matches!(remote.fetch_tags, Tags::Included));
+    let merge_refs = match mode {
+        WriteFetchHead::Configured => configured_merge_refs(repo, remote),
+        WriteFetchHead::AllForMerge | WriteFetchHead::Never => None,
+    };
+
+    let mut entries = Vec::new();
+    let mut first_ref_is_marked = false;
+    for (update, mapping, spec, _edit) in updates.iter_mapping_updates(mappings, refspecs, extra_refspecs) {
+        if matches!(
+            update.mode,
+            Mode::ImplicitTagNotSentByRemote | Mode::RejectedSourceObjectNotFound { .. }
+        ) {
+            continue;
+        }
+        let Some(id) = mapping.remote.as_id() else {
+            continue;
+        };
+        let is_implicit_tag =
+            matches!((spec, implicit_tag_refspec), (Some(spec), Some(tag_spec)) if spec.to_ref() == tag_spec);
+        if is_implicit_tag && update.mode != Mode::New {
+            continue;
+        }
+        let name: BString = mapping
+            .remote
+            .as_name()
+            .map_or_else(|| id.to_hex().to_string().into(), ToOwned::to_owned);
+        let for_merge = !is_implicit_tag
+            && match mode {
+                WriteFetchHead::AllForMerge => true,
+                WriteFetchHead::Configured => match &merge_refs {
+                    Some(merge_refs) => merge_refs
+                        .iter()
+                        .any(|merge_ref| is_merge_ref(merge_ref.as_ref(), name.as_ref())),
+                    None => {
+                        let is_first_ref = !first_ref_is_marked
+                            && mapping.spec_index == SpecIndex::ExplicitInRemote(0)
+                            && !matches!(refspecs[0].to_ref().source(), Some(source) if source.contains(&b'*'));
+                        first_ref_is_marked |= is_first_ref;
+                        is_first_ref
+                    }
+                },
+                WriteFetchHead::Never => unreachable!("we returned early"),
+            };
+        entries.push(gix_fetchhead::Entry::new(
+            id.to_owned(),
+            for_merge,
+            name.as_ref(),
+            url.as_ref(),
+        ));
+    }
+
+    // Write atomically so readers like `git merge FETCH_HEAD` never see a partially written file.
+    let path = repo.git_dir().join(gix_fetchhead::FILE_NAME);
+    let mut lock = gix_lock::File::acquire_to_update_resource(&path, gix_lock::acquire::Fail::Immediately, None)
+        .map_err(Error::LockFetchHead)?;
+    let write_err = |source| Error::WriteFetchHead {
+        path: path.clone(),
+        source,
+    };
+    gix_fetchhead::encode(&entries, &mut lock).map_err(write_err)?;
+    lock.commit().map_err(|err| write_err(err.error))?;
+    Ok(())
+}
+
+fn is_enabled(repo: &Repository) -> Result<bool, Error> {
+    Ok(repo
+        .config
+        .resolved
+        .boolean_filter_by_key("fetch.writeFetchHead", &mut repo.filter_config_section())
+        .map(|value| Fetch::WRITE_FETCH_HEAD.enrich_error(value))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)
+        .map_err(Error::WriteFetchHeadConfig)?
+        .unwrap_or(true))
+}
+
+/// Return the values of `branch.<name>.merge` of the current branch if it has any, or `None` if there is no current branch
+/// or no such configuration.
+///
+/// Note that the returned list is empty if the current branch is configured to merge from another remote.
+fn configured_merge_refs(repo: &Repository, remote: &Remote<'_>) -> Option<Vec<BString>> {
+    let head_name = repo.head_name().ok()??;
+    let short_name = head_name.as_bstr().strip_prefix(b"refs/heads/")?.as_bstr();
+    let merge_refs: Vec<BString> = repo
+        .config
+        .resolved
+        .strings_filter("branch", Some(short_name), "merge", &mut repo.filter_config_section())?
+        .into_iter()
+        .map(std::borrow::Cow::into_owned)
+        .collect();
+    if merge_refs.is_empty() {
+        return None;
+    }
+    let is_remote_of_branch = matches!(
+        (repo.branch_remote_name(short_name, remote::Direction::Fetch), remote.name()),
+        (Some(branch_remote), Some(name)) if branch_remote.as_bstr() == name.as_bstr()
+    );
+    Some(if is_remote_of_branch { merge_refs } else { Vec::new() })
+}
+
+/// Return `true` if `merge_ref`, a value of `branch.<name>.merge`, refers to the remote reference `name`.
+fn is_merge_ref(merge_ref: &BStr, name: &BStr) -> bool {
+    merge_ref == name
+        || ["refs/", "refs/heads/", "refs/tags/"]
+            .iter()
+            .any(|prefix| name.strip_prefix(prefix.as_bytes()) == Some(merge_ref.as_bytes()))
+}
@@ -194,6 +194,7 @@ where
             dry_run: DryRun::No,
             reflog_message: None,
             write_packed_refs: WritePackedRefs::Never,
+            write_fetch_head: Default::default(),
             shallow: Default::default(),
         })
     }
@@ -210,6 +211,7 @@ where
 }
 
 mod config;
+mod fetch_head;
 mod receive_pack;
 ///
 #[path = "update_refs/mod.rs"]
@@ -225,6 +227,7 @@ where
     dry_run: DryRun,
     reflog_message: Option<RefLogMessage>,
     write_packed_refs: WritePackedRefs,
+    write_fetch_head: remote::fetch::WriteFetchHead,
     shallow: remote::fetch::Shallow,
 }
 
@@ -260,6 +263,13 @@ where
         self
     }
 
+    /// Control how `.git/FETCH_HEAD` is written after receiving the pack, which by default is
+    /// [as configured](remote::fetch::WriteFetchHead::Configured).
+    pub fn with_write_fetch_head(mut self, write_fetch_head: remote::fetch::WriteFetchHead) -> Self {
+        self.write_fetch_head = write_fetch_head;
+        self
+    }
+
     /// Define what to do when the current repository is a shallow clone.
     ///
     /// *Has no effect if the current repository is not as shallow clone.*
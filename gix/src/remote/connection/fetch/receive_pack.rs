@@ -16,7 +16,7 @@ use crate::{
     },
     remote,
     remote::{
-        connection::fetch::config,
+        connection::fetch::{config, fetch_head},
         fetch,
         fetch::{
             negotiate, negotiate::Algorithm, outcome, refs, Error, Outcome, Prepare, ProgressId, RefLogMessage,
@@ -124,6 +124,17 @@ where
                 remote: self.ref_map.object_hash,
             });
         }
+        #[cfg(feature = "blocking-network-client")]
+        if let Some(url) = con
+            .remote
+            .url(crate::remote::Direction::Fetch)
+            .filter(|url| url.scheme == gix_url::Scheme::File)
+        {
+            let path = gix_path::from_bstr(url.path.as_ref());
+            if gix_bundle::is_bundle(&path) {
+                repo.verify_bundle(&crate::bundle::File::at(path)?.header)?;
+            }
+        }
 
         let negotiate_span = gix_trace::detail!(
             "negotiate",
@@ -263,6 +274,7 @@ where
                     index_version: config::pack_index_version(repo)?,
                     iteration_mode: gix_pack::data::input::Mode::Verify,
                     object_hash: con.remote.repo.object_hash(),
+                    write_reverse_index: config::pack_write_reverse_index(repo)?,
                 };
 
                 let write_pack_bundle = if matches!(self.dry_run, fetch::DryRun::No) {
@@ -331,6 +343,16 @@ where
             self.dry_run,
             self.write_packed_refs,
         )?;
+        if matches!(self.dry_run, fetch::DryRun::No) {
+            fetch_head::write(
+                repo,
+                con.remote,
+                self.write_fetch_head,
+                &self.ref_map.mappings,
+                &self.ref_map.extra_refspecs,
+                &update_refs,
+            )?;
+        }
 
         if let Some(bundle) = write_pack_bundle.as_mut() {
             if !update_refs.edits.is_empty() || bundle.index.num_objects == 0 {
@@ -0,0 +1,30 @@
+use super::Error;
+use crate::{
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{keys, Key, Pack},
+    },
+    Repository,
+};
+
+pub fn pack_threads(repo: &Repository) -> Result<Option<usize>, Error> {
+    unsigned_integer(repo, &Pack::THREADS)
+}
+
+/// Return the `pack.window` and `pack.depth` values to use for delta compression, falling back to `defaults`.
+pub fn pack_delta_window_and_depth(repo: &Repository, defaults: (usize, usize)) -> Result<(usize, usize), Error> {
+    Ok((
+        unsigned_integer(repo, &Pack::WINDOW)?.unwrap_or(defaults.0),
+        unsigned_integer(repo, &Pack::DEPTH)?.unwrap_or(defaults.1),
+    ))
+}
+
+fn unsigned_integer(repo: &Repository, key: &'static keys::UnsignedInteger) -> Result<Option<usize>, Error> {
+    Ok(repo
+        .config
+        .resolved
+        .integer_filter("pack", None, key.name(), &mut repo.filter_config_section())
+        .map(|value| key.try_into_usize(value))
+        .transpose()
+        .with_leniency(repo.options.lenient_config)?)
+}
@@ -0,0 +1,46 @@
+use crate::config;
+
+/// The error returned by [`send()`](super::Prepare::send()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The value to configure pack threads should be 0 to auto-configure or the amount of threads to use")]
+    PackThreads(#[from] config::unsigned_integer::Error),
+    #[error("Server lack feature {feature:?}: {description}")]
+    MissingServerFeature {
+        feature: &'static str,
+        description: &'static str,
+    },
+    #[error(transparent)]
+    Client(#[from] gix_protocol::transport::client::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Could not decode the status report of the remote")]
+    Report(#[from] gix_protocol::push::response::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    Ancestors(#[from] gix_traverse::commit::ancestors::Error),
+    #[error(transparent)]
+    CountObjects(#[from] gix_pack::data::output::count::objects::Error),
+    #[error(transparent)]
+    WritePack(#[from] gix_pack::data::output::bytes::Error<gix_pack::data::output::entry::iter_from_counts::Error>),
+    #[error(transparent)]
+    UpdateTrackingRefs(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    InvalidTrackingRefName(#[from] gix_validate::reference::name::Error),
+    #[error(transparent)]
+    FindTrackingRef(#[from] crate::reference::find::Error),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+impl gix_protocol::transport::IsSpuriousError for Error {
+    fn is_spurious(&self) -> bool {
+        match self {
+            Error::Report(err) => err.is_spurious(),
+            Error::Client(err) => err.is_spurious(),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use gix_protocol::transport::client::Transport;
+
+use crate::{
+    bstr::BString,
+    remote::{fetch::DryRun, Connection},
+    Progress,
+};
+
+mod error;
+pub use error::Error;
+
+/// Options for use in [`Connection::prepare_push()`].
+#[derive(Default, Debug, Clone)]
+pub struct Options {
+    /// Parameters in the form of `(name, optional value)` to add to the handshake.
+    ///
+    /// This is useful in case of custom servers.
+    pub handshake_parameters: Vec<(String, Option<String>)>,
+}
+
+/// A single reference update as derived from the push ref-specs, along with the way it is going to be performed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Update {
+    /// The full name of the reference to update on the remote.
+    pub remote_ref: gix_ref::FullName,
+    /// The full name of the local reference the new value originates from, or `None` if the source was an object
+    /// or if the remote reference is supposed to be deleted.
+    pub local_ref: Option<gix_ref::FullName>,
+    /// The value the reference has on the remote, or `None` if it doesn't exist there yet.
+    pub old_id: Option<gix_hash::ObjectId>,
+    /// The value the reference should have on the remote, or `None` if it should be deleted.
+    pub new_id: Option<gix_hash::ObjectId>,
+    /// How the update is going to be performed, or why it won't be sent to the remote at all.
+    pub mode: update::Mode,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod update {
+    /// Describe how a reference on the remote will be updated, as determined locally before sending the push.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Mode {
+        /// The reference doesn't exist on the remote and will be created.
+        New,
+        /// The reference exists on the remote and its commit is an ancestor of the new one.
+        FastForward,
+        /// The reference will be set to the new value without taking its ancestry into consideration.
+        Forced,
+        /// The reference will be deleted on the remote.
+        Delete,
+        /// The reference on the remote already has the desired value, so nothing will be sent for it.
+        UpToDate,
+        /// The new value isn't a descendant of the value on the remote, and the ref-spec doesn't specify force.
+        RejectedNonFastForward,
+        /// The value on the remote isn't known locally, so the update can't be a fast-forward without fetching first.
+        RejectedFetchFirst,
+        /// The reference is a tag that already exists on the remote, which can only be changed if the ref-spec specifies force.
+        RejectedAlreadyExists,
+        /// The previous or new value isn't a commit, which means the update can only be performed if the ref-spec specifies force.
+        RejectedNeedsForce,
+        /// The reference to delete doesn't exist on the remote.
+        RejectedRemoteRefMissing,
+        /// The remote doesn't support deleting references.
+        RejectedDeleteUnsupported,
+    }
+
+    impl Mode {
+        /// Return `true` if the update was rejected locally, before anything was sent to the remote.
+        pub fn is_rejected(&self) -> bool {
+            matches!(
+                self,
+                Mode::RejectedNonFastForward
+                    | Mode::RejectedFetchFirst
+                    | Mode::RejectedAlreadyExists
+                    | Mode::RejectedNeedsForce
+                    | Mode::RejectedRemoteRefMissing
+                    | Mode::RejectedDeleteUnsupported
+            )
+        }
+
+        /// Return `true` if a command to update the remote reference has to be sent.
+        pub fn needs_command(&self) -> bool {
+            matches!(self, Mode::New | Mode::FastForward | Mode::Forced | Mode::Delete)
+        }
+    }
+
+    impl std::fmt::Display for Mode {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Mode::New => "new",
+                Mode::FastForward => "fast-forward",
+                Mode::Forced => "forced-update",
+                Mode::Delete => "deleted",
+                Mode::UpToDate => "up-to-date",
+                Mode::RejectedNonFastForward => "rejected (non-fast-forward)",
+                Mode::RejectedFetchFirst => "rejected (fetch first)",
+                Mode::RejectedAlreadyExists => "rejected (already exists)",
+                Mode::RejectedNeedsForce => "rejected (needs force)",
+                Mode::RejectedRemoteRefMissing => "rejected (remote ref does not exist)",
+                Mode::RejectedDeleteUnsupported => "rejected (remote does not support deleting refs)",
+            }
+            .fmt(f)
+        }
+    }
+}
+
+/// The status of the push operation.
+#[derive(Debug, Clone)]
+pub enum Status {
+    /// Nothing was sent to the remote, as all updates were either up-to-date or rejected locally.
+    NothingSent {
+        /// If `true`, nothing was sent due to dry-run mode being enabled.
+        dry_run: bool,
+    },
+    /// An [atomic](Prepare::with_atomic()) push was aborted before sending anything as at least one update was rejected locally.
+    AtomicRejected,
+    /// Reference updates were sent to the remote, along with a pack if needed, and the remote responded with a status report.
+    Sent {
+        /// The amount of objects sent in the pack, or `None` if no pack was sent as all updates were deletions.
+        num_objects: Option<usize>,
+        /// The status report sent by the remote, indicating which reference updates were performed.
+        report: gix_protocol::push::Response,
+        /// The edits made to our remote-tracking references for all accepted reference updates.
+        tracking_ref_edits: Vec<gix_ref::transaction::RefEdit>,
+    },
+}
+
+/// The outcome of [`Prepare::send()`].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// All updates as determined from the push ref-specs, including the ones that weren't sent.
+    pub updates: Vec<Update>,
+    /// The status of the operation to indicate what happened.
+    pub status: Status,
+}
+
+impl Outcome {
+    /// Return `true` if all updates were either up-to-date or accepted by the remote.
+    pub fn is_success(&self) -> bool {
+        match &self.status {
+            Status::NothingSent { .. } => !self.updates.iter().any(|u| u.mode.is_rejected()),
+            Status::AtomicRejected => false,
+            Status::Sent { report, .. } => report.is_success() && !self.updates.iter().any(|u| u.mode.is_rejected()),
+        }
+    }
+
+    /// Return all updates that were rejected, locally or by the remote, along with the reason for the rejection.
+    pub fn rejections(&self) -> Vec<(&Update, BString)> {
+        self.updates
+            .iter()
+            .filter_map(|update| {
+                if update.mode.is_rejected() {
+                    return Some((update, update.mode.to_string().into()));
+                }
+                match &self.status {
+                    Status::AtomicRejected if update.mode.needs_command() => {
+                        Some((update, "atomic push failed".into()))
+                    }
+                    Status::Sent { report, .. } if update.mode.needs_command() => {
+                        match report.ref_update(update.remote_ref.as_bstr()) {
+                            Some(gix_protocol::push::response::RefStatus::Rejected { reason, .. }) => {
+                                Some((update, reason.clone()))
+                            }
+                            Some(gix_protocol::push::response::RefStatus::Ok { .. }) => None,
+                            None => Some((update, "no status reported by remote".into())),
+                        }
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The progress ids used in during various steps of the push operation.
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The amount of objects counted to be sent in the pack.
+    CountObjects,
+    /// The amount of bytes of the pack written to the remote.
+    WritePack,
+    /// The progress name is defined by the remote and the progress messages it sets, along with their progress values and limits.
+    RemoteProgress,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::CountObjects => *b"PUCO",
+            ProgressId::WritePack => *b"PUWP",
+            ProgressId::RemoteProgress => *b"PURP",
+        }
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod prepare {
+    use crate::bstr::BString;
+
+    /// The error returned by [`prepare_push()`][super::Connection::prepare_push()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        RefMap(#[from] crate::remote::ref_map::Error),
+        #[error("The remote responded with protocol {version:?}, but pushing requires the initial reference advertisement of V0 or V1")]
+        MissingRefAdvertisement { version: gix_protocol::transport::Protocol },
+        #[error("Cannot push without any configured push ref-specs as `push.default` is set to 'nothing'")]
+        MissingRefSpecs,
+        #[error(transparent)]
+        PushDefault(#[from] crate::config::key::GenericErrorWithValue),
+        #[error(transparent)]
+        FindHead(#[from] crate::reference::find::existing::Error),
+        #[error("Cannot push the current branch as HEAD is detached")]
+        DetachedHead,
+        #[error(
+            "The current branch {branch:?} has no upstream branch configured, which is required by `push.default`"
+        )]
+        NoUpstream { branch: BString },
+        #[error("The upstream branch {upstream:?} of the current branch {branch:?} has a different name, which is refused by `push.default=simple`")]
+        UpstreamNameMismatch { branch: BString, upstream: BString },
+        #[error(transparent)]
+        UpstreamName(#[from] crate::repository::branch_remote_ref_name::Error),
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::Error),
+        #[error(transparent)]
+        FollowReference(#[from] gix_ref::file::find::existing::Error),
+        #[error(transparent)]
+        IterReferences(Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error(transparent)]
+        InitReferencesIter(#[from] crate::reference::iter::init::Error),
+        #[error(transparent)]
+        OpenPackedRefs(#[from] crate::reference::iter::Error),
+        #[error("The source {src:?} of a push ref-spec did not match any local reference or object")]
+        SourceNotFound { src: BString },
+        #[error("The destination {dst:?} is not a full reference name and could not be inferred from the source or the remote references")]
+        UnqualifiedDestination { dst: BString },
+        #[error("The remote reference {name:?} would be updated by multiple ref-specs with different values")]
+        ConflictingDestination { name: BString },
+        #[error(transparent)]
+        InvalidRefName(#[from] gix_validate::reference::name::Error),
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        Ancestors(#[from] gix_traverse::commit::ancestors::Error),
+    }
+
+    impl gix_protocol::transport::IsSpuriousError for Error {
+        fn is_spurious(&self) -> bool {
+            match self {
+                Error::RefMap(err) => err.is_spurious(),
+                _ => false,
+            }
+        }
+    }
+}
+
+impl<'remote, 'repo, T> Connection<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    /// Perform a handshake with the remote's `receive-pack` service and match the push ref-specs of our remote against the
+    /// references it advertised to compute all reference updates to perform, according to `options`.
+    /// If the remote doesn't have any push ref-specs, `push.default` is used to determine what to push.
+    ///
+    /// Note that at this point, the `transport` should already be configured using the [`transport_mut()`][Self::transport_mut()]
+    /// method, as it will be consumed here.
+    ///
+    /// From there additional properties of the push can be adjusted, before [sending](Prepare::send()) it.
+    #[allow(clippy::result_large_err)]
+    pub fn prepare_push(
+        mut self,
+        mut progress: impl Progress,
+        options: Options,
+    ) -> Result<Prepare<'remote, 'repo, T>, prepare::Error> {
+        let _span = gix_trace::coarse!("remote::Connection::prepare_push()");
+        let mut handshake = self.handshake(
+            gix_protocol::transport::Service::ReceivePack,
+            crate::remote::Direction::Push,
+            options.handshake_parameters,
+            &mut progress,
+        )?;
+        let remote_refs = handshake.refs.take().ok_or(prepare::Error::MissingRefAdvertisement {
+            version: handshake.server_protocol_version,
+        })?;
+        let object_hash = crate::remote::connection::ref_map::extract_object_format(self.remote.repo, &handshake)
+            .map_err(prepare::Error::from)?;
+        let supports_delete_refs = handshake.capabilities.contains("delete-refs");
+        let updates = updates::compute(self.remote, &remote_refs, supports_delete_refs)?;
+        Ok(Prepare {
+            con: Some(self),
+            handshake,
+            remote_refs,
+            object_hash,
+            updates,
+            dry_run: DryRun::No,
+            atomic: false,
+            push_options: Vec::new(),
+        })
+    }
+}
+
+mod config;
+mod send_pack;
+mod updates;
+
+/// A structure to hold the result of the handshake with the remote and configure the upcoming push operation.
+pub struct Prepare<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    con: Option<Connection<'remote, 'repo, T>>,
+    handshake: gix_protocol::handshake::Outcome,
+    remote_refs: Vec<gix_protocol::handshake::Ref>,
+    object_hash: gix_hash::Kind,
+    updates: Vec<Update>,
+    dry_run: DryRun,
+    atomic: bool,
+    push_options: Vec<BString>,
+}
+
+/// Access
+impl<'remote, 'repo, T> Prepare<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    /// Return the outcome of the handshake with the remote, containing its capabilities.
+    pub fn handshake(&self) -> &gix_protocol::handshake::Outcome {
+        &self.handshake
+    }
+
+    /// Return the references as advertised by the remote.
+    pub fn remote_refs(&self) -> &[gix_protocol::handshake::Ref] {
+        &self.remote_refs
+    }
+
+    /// Return all reference updates as derived from the push ref-specs, including the ones that won't be sent.
+    pub fn updates(&self) -> &[Update] {
+        &self.updates
+    }
+}
+
+/// Builder
+impl<'remote, 'repo, T> Prepare<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    /// If dry run is enabled, no change to the remote or the local repository will be made.
+    ///
+    /// This works by not sending anything to the remote after the updates were computed.
+    pub fn with_dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = if enabled { DryRun::Yes } else { DryRun::No };
+        self
+    }
+
+    /// If enabled, ask the remote to either perform all reference updates, or none at all.
+    ///
+    /// This also means that nothing will be sent if at least one update was rejected locally.
+    pub fn with_atomic(mut self, enabled: bool) -> Self {
+        self.atomic = enabled;
+        self
+    }
+
+    /// Transmit `options` to the remote, which passes them on to its hooks.
+    pub fn with_push_options(mut self, options: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+        self.push_options = options.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+impl<'remote, 'repo, T> Drop for Prepare<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    fn drop(&mut self) {
+        if let Some(mut con) = self.con.take() {
+            gix_protocol::indicate_end_of_interaction(&mut con.transport, con.trace).ok();
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use std::{
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_features::{
+    parallel::InOrderIter,
+    progress::{Count, Progress},
+};
+use gix_protocol::{
+    push,
+    transport::{client::Transport, packetline::read::ProgressAction},
+};
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use super::{config, Error, Outcome, Prepare, ProgressId, Status, Update};
+use crate::{
+    remote::{fetch::DryRun, Direction},
+    Remote, Repository,
+};
+
+impl<'remote, 'repo, T> Prepare<'remote, 'repo, T>
+where
+    T: Transport,
+{
+    /// Send all reference updates that need it to the remote, along with a pack with all objects the remote doesn't have yet,
+    /// and update our remote-tracking references for all updates that were accepted.
+    ///
+    /// Note that updates that were rejected locally won't be sent, and if the push is [atomic](Self::with_atomic()), nothing
+    /// will be sent at all in that case.
+    /// Use `should_interrupt` to stop the operation while the pack is being generated or sent.
+    pub fn send<P>(mut self, mut progress: P, should_interrupt: &AtomicBool) -> Result<Outcome, Error>
+    where
+        P: gix_features::progress::NestedProgress,
+        P::SubProgress: 'static,
+    {
+        let _span = gix_trace::coarse!("push::Prepare::send()");
+        let updates = std::mem::take(&mut self.updates);
+        if self.atomic && updates.iter().any(|u| u.mode.is_rejected()) {
+            return Ok(Outcome {
+                updates,
+                status: Status::AtomicRejected,
+            });
+        }
+        let dry_run = self.dry_run == DryRun::Yes;
+        if dry_run || !updates.iter().any(|u| u.mode.needs_command()) {
+            return Ok(Outcome {
+                updates,
+                status: Status::NothingSent { dry_run },
+            });
+        }
+
+        let (repo, trace) = {
+            let con = self.con.as_ref().expect("send() can only be called once");
+            (con.remote.repo, con.trace)
+        };
+        let mut arguments = push::Arguments::new(&self.handshake.capabilities, trace);
+        if arguments.report_status().is_none() {
+            return Err(Error::MissingServerFeature {
+                feature: "report-status",
+                description: "the status of reference updates can't be obtained without it",
+            });
+        }
+        if self.atomic {
+            if !arguments.can_use_atomic() {
+                return Err(Error::MissingServerFeature {
+                    feature: "atomic",
+                    description: "atomic pushes are not supported by the remote",
+                });
+            }
+            arguments.use_atomic();
+        }
+        if !self.push_options.is_empty() {
+            if !arguments.can_use_push_options() {
+                return Err(Error::MissingServerFeature {
+                    feature: "push-options",
+                    description: "push options can't be transmitted to the remote",
+                });
+            }
+            for option in &self.push_options {
+                arguments.push_option(option.clone());
+            }
+        }
+        arguments.add_feature(repo.config.user_agent_tuple());
+        for update in updates.iter().filter(|u| u.mode.needs_command()) {
+            arguments.command(push::Command {
+                old_id: update.old_id.unwrap_or_else(|| self.object_hash.null()),
+                new_id: update.new_id.unwrap_or_else(|| self.object_hash.null()),
+                ref_name: update.remote_ref.as_bstr().to_owned(),
+            });
+        }
+
+        let counts = if arguments.needs_pack() {
+            let input = objects_to_send(repo, &updates, &self.remote_refs)?;
+            let mut counting = progress.add_child_with_id("counting", ProgressId::CountObjects.into());
+            counting.init(None, gix_features::progress::count("objects"));
+            let db = object_database(repo)?;
+            let (counts, _outcome) = gix_pack::data::output::count::objects_unthreaded(
+                &db,
+                &mut input
+                    .into_iter()
+                    .map(Ok::<_, Box<dyn std::error::Error + Send + Sync + 'static>>),
+                &counting,
+                should_interrupt,
+                gix_pack::data::output::count::objects::ObjectExpansion::TreeAdditionsComparedToAncestor,
+            )?;
+            Some((counts, db))
+        } else {
+            None
+        };
+
+        let mut con = self.con.take().expect("send() can only be called once");
+        let (mut writer, mut reader) = arguments.send(&mut con.transport)?;
+        let num_objects = match counts {
+            Some((counts, db)) => {
+                let num_objects = counts.len();
+                let defaults = gix_pack::data::output::entry::iter_from_counts::Options::default();
+                let (delta_window, delta_depth) =
+                    config::pack_delta_window_and_depth(repo, (defaults.delta_window, defaults.delta_depth))?;
+                let entries = InOrderIter::from(gix_pack::data::output::entry::iter_from_counts(
+                    counts,
+                    db,
+                    Box::new(progress.add_child("creating entries")),
+                    gix_pack::data::output::entry::iter_from_counts::Options {
+                        thread_limit: config::pack_threads(repo)?,
+                        mode: gix_pack::data::output::entry::iter_from_counts::Mode::PackCopyAndDeltaCompression,
+                        allow_thin_pack: false,
+                        chunk_size: 1000,
+                        version: Default::default(),
+                        delta_window,
+                        delta_depth,
+                    },
+                ));
+                let mut write_progress = progress.add_child_with_id("writing", ProgressId::WritePack.into());
+                write_progress.init(None, gix_features::progress::bytes());
+                let mut pack = gix_pack::data::output::bytes::FromEntriesIter::new(
+                    entries,
+                    &mut writer,
+                    num_objects as u32,
+                    gix_pack::data::Version::default(),
+                    self.object_hash,
+                );
+                for written in pack.by_ref() {
+                    if should_interrupt.load(Ordering::Relaxed) {
+                        return Err(Error::Interrupted);
+                    }
+                    write_progress.inc_by(written? as usize);
+                }
+                Some(num_objects)
+            }
+            None => None,
+        };
+        writer.flush()?;
+        drop(writer);
+
+        let uses_sideband = arguments.uses_sideband();
+        if uses_sideband {
+            setup_remote_progress(&mut progress, &mut reader, should_interrupt);
+        }
+        let report = push::Response::from_line_reader(&mut *reader, uses_sideband, trace)?;
+        drop(reader);
+
+        let tracking_ref_edits = update_tracking_refs(con.remote, &updates, &report)?;
+        Ok(Outcome {
+            updates,
+            status: Status::Sent {
+                num_objects,
+                report,
+                tracking_ref_edits,
+            },
+        })
+    }
+}
+
+fn object_database(
+    repo: &Repository,
+) -> std::io::Result<gix_odb::Cache<gix_odb::store::Handle<std::sync::Arc<gix_odb::Store>>>> {
+    let mut db = repo.objects.clone().into_arc()?;
+    db.prevent_pack_unload();
+    db.ignore_replacements = true;
+    Ok(db)
+}
+
+/// Return the objects to use as input for counting, which are all commits reachable from the tips we are about to send,
+/// but not from any of the commits we know the remote has, along with all non-commit tips.
+fn objects_to_send(
+    repo: &Repository,
+    updates: &[Update],
+    remote_refs: &[gix_protocol::handshake::Ref],
+) -> Result<Vec<gix_hash::ObjectId>, Error> {
+    let mut remote_tips = Vec::new();
+    for remote_ref in remote_refs {
+        let (_name, target, peeled) = remote_ref.unpack();
+        let Some(id) = peeled.or(target) else { continue };
+        if repo.has_object(id) && repo.find_object(id)?.kind == gix_object::Kind::Commit {
+            remote_tips.push(id.to_owned());
+        }
+    }
+    let known_to_remote = gix_traverse::commit::Ancestors::new(
+        remote_tips,
+        gix_traverse::commit::ancestors::State::default(),
+        &repo.objects,
+    )
+    .map(|info| info.map(|info| info.id))
+    .collect::<Result<gix_hashtable::HashSet<_>, _>>()?;
+
+    let mut input = Vec::new();
+    let mut tips = Vec::new();
+    for id in updates
+        .iter()
+        .filter(|u| u.mode.needs_command())
+        .filter_map(|u| u.new_id)
+    {
+        let object = repo.find_object(id)?;
+        match object.kind {
+            gix_object::Kind::Commit => tips.push(id),
+            gix_object::Kind::Tag => {
+                input.push(id);
+                if let Ok(Ok(commit)) = object.peel_tags_to_end().map(crate::Object::try_into_commit) {
+                    tips.push(commit.id);
+                }
+            }
+            gix_object::Kind::Tree | gix_object::Kind::Blob => input.push(id),
+        }
+    }
+    tips.retain(|id| !known_to_remote.contains(id));
+    for info in gix_traverse::commit::Ancestors::filtered(
+        tips,
+        gix_traverse::commit::ancestors::State::default(),
+        &repo.objects,
+        |id| !known_to_remote.contains(id),
+    ) {
+        input.push(info?.id);
+    }
+    Ok(input)
+}
+
+/// Set the remote-tracking references of all accepted updates to their new value, or delete them.
+fn update_tracking_refs(
+    remote: &Remote<'_>,
+    updates: &[Update],
+    report: &push::Response,
+) -> Result<Vec<RefEdit>, Error> {
+    let repo = remote.repo;
+    let fetch_specs = remote.refspecs(Direction::Fetch);
+    if fetch_specs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let null = repo.object_hash().null();
+    let mut edits = Vec::new();
+    for update in updates.iter().filter(|u| u.mode.needs_command()) {
+        if !matches!(
+            report.ref_update(update.remote_ref.as_bstr()),
+            Some(push::response::RefStatus::Ok { .. })
+        ) {
+            continue;
+        }
+        let tracking_ref =
+            gix_refspec::MatchGroup::from_fetch_specs(fetch_specs.iter().map(gix_refspec::RefSpec::to_ref))
+                .match_remotes(std::iter::once(gix_refspec::match_group::Item {
+                    full_ref_name: update.remote_ref.as_bstr(),
+                    target: update.new_id.as_ref().unwrap_or(&null),
+                    object: None,
+                }))
+                .mappings
+                .into_iter()
+                .find_map(|m| m.rhs);
+        let Some(tracking_ref) = tracking_ref else { continue };
+        let name: gix_ref::FullName = tracking_ref.into_owned().try_into()?;
+        let change = match update.new_id {
+            Some(id) => Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: "update by push".into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Peeled(id),
+            },
+            None => {
+                if repo.try_find_reference(name.as_ref())?.is_none() {
+                    continue;
+                }
+                Change::Delete {
+                    expected: PreviousValue::Any,
+                    log: RefLog::AndReference,
+                }
+            }
+        };
+        edits.push(RefEdit {
+            change,
+            name,
+            deref: false,
+        });
+    }
+    if edits.is_empty() {
+        return Ok(edits);
+    }
+    Ok(repo.edit_references(edits)?)
+}
+
+fn setup_remote_progress<'a>(
+    progress: &mut dyn crate::DynNestedProgress,
+    reader: &mut Box<dyn gix_protocol::transport::client::ExtendedBufRead<'a> + Unpin + 'a>,
+    should_interrupt: &'a AtomicBool,
+) {
+    use gix_protocol::transport::client::ExtendedBufRead;
+    reader.set_progress_handler(Some(Box::new({
+        let mut remote_progress = progress.add_child_with_id("remote".to_string(), ProgressId::RemoteProgress.into());
+        move |is_err: bool, data: &[u8]| {
+            gix_protocol::RemoteProgress::translate_to_progress(is_err, data, &mut remote_progress);
+            if should_interrupt.load(Ordering::Relaxed) {
+                ProgressAction::Interrupt
+            } else {
+                ProgressAction::Continue
+            }
+        }
+    }) as gix_protocol::transport::client::HandleProgress<'a>));
+}
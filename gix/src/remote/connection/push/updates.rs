@@ -0,0 +1,333 @@
+use gix_hash::ObjectId;
+use gix_protocol::handshake::Ref;
+use gix_refspec::{instruction, Instruction};
+
+use super::{prepare::Error, update::Mode, Update};
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    config::{
+        cache::util::ApplyLeniencyDefault,
+        tree::{Push, Section},
+    },
+    ext::ObjectIdExt,
+    push,
+    remote::Direction,
+    Remote, Repository,
+};
+
+/// A single reference update as obtained from a ref-spec, before it was compared to the state of the remote.
+struct Candidate {
+    remote_ref: gix_ref::FullName,
+    local_ref: Option<gix_ref::FullName>,
+    new_id: Option<ObjectId>,
+    force: bool,
+}
+
+/// Match the push ref-specs of `remote`, or the ones implied by `push.default`, against local references and
+/// the `remote_refs` advertised by the remote to produce all updates to perform.
+/// Updates to remote references matched by negative ref-specs are dropped.
+pub(crate) fn compute(
+    remote: &Remote<'_>,
+    remote_refs: &[Ref],
+    supports_delete_refs: bool,
+) -> Result<Vec<Update>, Error> {
+    let repo = remote.repo;
+    let remote_refs: Vec<_> = remote_refs
+        .iter()
+        .filter_map(|r| {
+            let (name, target, _peeled) = r.unpack();
+            Some((name, target?.to_owned())).filter(|(name, _)| name.starts_with(b"refs/"))
+        })
+        .collect();
+    let specs = if remote.refspecs(Direction::Push).is_empty() {
+        implied_specs(remote)?
+    } else {
+        remote.refspecs(Direction::Push).to_vec()
+    };
+
+    let mut candidates = Vec::new();
+    for spec in &specs {
+        match spec.to_ref().instruction() {
+            Instruction::Push(instruction::Push::AllMatchingBranches { allow_non_fast_forward }) => {
+                for local in repo.references()?.local_branches()? {
+                    let local = local.map_err(Error::IterReferences)?;
+                    if !remote_refs.iter().any(|(name, _)| *name == local.name().as_bstr()) {
+                        continue;
+                    }
+                    let name = local.name().to_owned();
+                    candidates.push(Candidate {
+                        remote_ref: name.clone(),
+                        new_id: local.try_id().map(crate::Id::detach),
+                        local_ref: Some(name),
+                        force: allow_non_fast_forward,
+                    });
+                }
+            }
+            Instruction::Push(instruction::Push::Delete { ref_or_pattern }) => {
+                if ref_or_pattern.contains(&b'*') {
+                    for (name, _) in &remote_refs {
+                        if glob_match(ref_or_pattern, name).is_some() {
+                            candidates.push(Candidate {
+                                remote_ref: (*name).to_owned().try_into()?,
+                                local_ref: None,
+                                new_id: None,
+                                force: false,
+                            });
+                        }
+                    }
+                } else {
+                    candidates.push(Candidate {
+                        remote_ref: remote_ref_name(ref_or_pattern, None, &remote_refs)?,
+                        local_ref: None,
+                        new_id: None,
+                        force: false,
+                    });
+                }
+            }
+            Instruction::Push(instruction::Push::Matching {
+                src,
+                dst,
+                allow_non_fast_forward,
+            }) => {
+                if src.contains(&b'*') {
+                    for local in repo.references()?.all()? {
+                        let local = local.map_err(Error::IterReferences)?;
+                        let Some(matched) = glob_match(src, local.name().as_bstr()) else {
+                            continue;
+                        };
+                        let Some(id) = local.try_id() else { continue };
+                        candidates.push(Candidate {
+                            remote_ref: glob_replace(dst, matched).try_into()?,
+                            local_ref: Some(local.name().to_owned()),
+                            new_id: Some(id.detach()),
+                            force: allow_non_fast_forward,
+                        });
+                    }
+                } else {
+                    let (local_ref, id) = resolve_source(repo, src)?;
+                    let remote_ref = if dst == src {
+                        match &local_ref {
+                            Some(name) => name.clone(),
+                            None => return Err(Error::UnqualifiedDestination { dst: dst.to_owned() }),
+                        }
+                    } else {
+                        remote_ref_name(dst, local_ref.as_ref(), &remote_refs)?
+                    };
+                    candidates.push(Candidate {
+                        remote_ref,
+                        local_ref,
+                        new_id: Some(id),
+                        force: allow_non_fast_forward,
+                    });
+                }
+            }
+            Instruction::Push(instruction::Push::Exclude { .. }) => {}
+            Instruction::Fetch(_) => unreachable!("BUG: push specs only contain push instructions"),
+        }
+    }
+
+    let group = gix_refspec::MatchGroup::from_push_specs(specs.iter().map(gix_refspec::RefSpec::to_ref));
+    let null_id = repo.object_hash().null();
+    candidates.retain(|candidate| {
+        !group.is_excluded(gix_refspec::match_group::Item {
+            full_ref_name: candidate.remote_ref.as_bstr(),
+            target: candidate.new_id.as_ref().unwrap_or(&null_id),
+            object: None,
+        })
+    });
+
+    let mut out = Vec::<Update>::with_capacity(candidates.len());
+    for candidate in candidates {
+        if let Some(existing) = out.iter().find(|u| u.remote_ref == candidate.remote_ref) {
+            if existing.new_id != candidate.new_id {
+                return Err(Error::ConflictingDestination {
+                    name: candidate.remote_ref.as_bstr().to_owned(),
+                });
+            }
+            continue;
+        }
+        let old_id = remote_refs
+            .iter()
+            .find_map(|(name, id)| (*name == candidate.remote_ref.as_bstr()).then_some(*id));
+        let mode = match (old_id, candidate.new_id) {
+            (None, None) => Mode::RejectedRemoteRefMissing,
+            (Some(_), None) if supports_delete_refs => Mode::Delete,
+            (Some(_), None) => Mode::RejectedDeleteUnsupported,
+            (None, Some(_)) => Mode::New,
+            (Some(old), Some(new)) if old == new => Mode::UpToDate,
+            (Some(_), Some(_)) if candidate.force => Mode::Forced,
+            (Some(_), Some(_)) if candidate.remote_ref.as_bstr().starts_with(b"refs/tags/") => {
+                Mode::RejectedAlreadyExists
+            }
+            (Some(old), Some(new)) => fast_forward_mode(repo, old, new)?,
+        };
+        out.push(Update {
+            remote_ref: candidate.remote_ref,
+            local_ref: candidate.local_ref,
+            old_id,
+            new_id: candidate.new_id,
+            mode,
+        });
+    }
+    Ok(out)
+}
+
+/// Produce the ref-specs to use if the remote has none configured, as defined by `push.default`.
+fn implied_specs(remote: &Remote<'_>) -> Result<Vec<gix_refspec::RefSpec>, Error> {
+    let repo = remote.repo;
+    let push_default = repo
+        .config
+        .resolved
+        .string(Push.name(), None, Push::DEFAULT.name)
+        .map_or(Ok(Default::default()), |v| {
+            Push::DEFAULT
+                .try_into_default(v)
+                .with_lenient_default(repo.config.lenient_config)
+        })?;
+    let spec: BString = match push_default {
+        push::Default::Nothing => return Err(Error::MissingRefSpecs),
+        push::Default::Matching => ":".into(),
+        push::Default::Current | push::Default::Simple | push::Default::Upstream => {
+            let head = repo.head_name()?.ok_or(Error::DetachedHead)?;
+            let upstream = repo
+                .branch_remote_ref_name(head.as_ref(), Direction::Fetch)
+                .transpose()?
+                .map(std::borrow::Cow::into_owned);
+            let is_upstream_remote = matches!(
+                repo.branch_remote_name(head.shorten(), Direction::Fetch).zip(remote.name()),
+                Some((a, b)) if a.as_bstr() == b.as_bstr()
+            );
+            let dst = match push_default {
+                push::Default::Upstream => upstream.ok_or_else(|| Error::NoUpstream {
+                    branch: head.shorten().to_owned(),
+                })?,
+                push::Default::Simple => match upstream {
+                    Some(upstream) if is_upstream_remote && upstream != head => {
+                        return Err(Error::UpstreamNameMismatch {
+                            branch: head.shorten().to_owned(),
+                            upstream: upstream.as_bstr().to_owned(),
+                        })
+                    }
+                    _ => head.clone(),
+                },
+                _ => head.clone(),
+            };
+            let mut spec = head.as_bstr().to_owned();
+            spec.push_byte(b':');
+            spec.push_str(dst.as_bstr());
+            spec
+        }
+    };
+    Ok(vec![gix_refspec::parse(
+        spec.as_bstr(),
+        gix_refspec::parse::Operation::Push,
+    )
+    .expect("valid full ref names yield valid ref-specs")
+    .to_owned()])
+}
+
+/// Find the local reference or object that `src` refers to, and return the fully resolved reference name along with
+/// the id it points to.
+fn resolve_source(repo: &Repository, src: &BStr) -> Result<(Option<gix_ref::FullName>, ObjectId), Error> {
+    if let Ok(id) = ObjectId::from_hex(src) {
+        return Ok((None, id));
+    }
+    if let Ok(partial_name) = <&gix_ref::PartialNameRef>::try_from(src) {
+        if let Some(mut reference) = repo.try_find_reference(partial_name)? {
+            while let Some(next) = reference.follow() {
+                reference = next?;
+            }
+            return match reference.try_id() {
+                Some(id) => Ok((Some(reference.name().to_owned()), id.detach())),
+                None => Err(Error::SourceNotFound { src: src.to_owned() }),
+            };
+        }
+    }
+    #[cfg(feature = "revision")]
+    if let Ok(id) = repo.rev_parse_single(src) {
+        return Ok((None, id.detach()));
+    }
+    Err(Error::SourceNotFound { src: src.to_owned() })
+}
+
+/// Turn `dst` into a full reference name, either by matching it against the references on the remote, or by
+/// placing it into the same category as the `local_ref` it is pushed from.
+fn remote_ref_name(
+    dst: &BStr,
+    local_ref: Option<&gix_ref::FullName>,
+    remote_refs: &[(&BStr, ObjectId)],
+) -> Result<gix_ref::FullName, Error> {
+    if dst.starts_with(b"refs/") {
+        return Ok(dst.to_owned().try_into()?);
+    }
+    for prefix in ["refs/", "refs/tags/", "refs/heads/", "refs/remotes/"] {
+        let mut candidate = BString::from(prefix);
+        candidate.push_str(dst);
+        if remote_refs.iter().any(|(name, _)| *name == candidate) {
+            return Ok(candidate.try_into()?);
+        }
+    }
+    let category = local_ref.and_then(|name| {
+        ["refs/heads/", "refs/tags/"]
+            .into_iter()
+            .find(|prefix| name.as_bstr().starts_with(prefix.as_bytes()))
+    });
+    match category {
+        Some(prefix) => {
+            let mut name = BString::from(prefix);
+            name.push_str(dst);
+            Ok(name.try_into()?)
+        }
+        None => Err(Error::UnqualifiedDestination { dst: dst.to_owned() }),
+    }
+}
+
+/// Determine if `new` is a descendant of `old`, which must be commits.
+fn fast_forward_mode(repo: &Repository, old: ObjectId, new: ObjectId) -> Result<Mode, Error> {
+    if !repo.has_object(old) {
+        return Ok(Mode::RejectedFetchFirst);
+    }
+    let (old_commit, new_commit) = match (
+        repo.find_object(old)?.try_into_commit(),
+        repo.find_object(new)?.try_into_commit(),
+    ) {
+        (Ok(old), Ok(new)) => (old, new),
+        _ => return Ok(Mode::RejectedNeedsForce),
+    };
+    let sorting = match old_commit.time() {
+        Ok(time) => gix_traverse::commit::Sorting::ByCommitTimeNewestFirstCutoffOlderThan { seconds: time.seconds },
+        Err(_) => gix_traverse::commit::Sorting::BreadthFirst,
+    };
+    let is_fast_forward = new_commit
+        .id
+        .ancestors(&repo.objects)
+        .sorting(sorting)?
+        .any(|info| matches!(info, Ok(info) if info.id == old));
+    Ok(if is_fast_forward {
+        Mode::FastForward
+    } else {
+        Mode::RejectedNonFastForward
+    })
+}
+
+/// Match `name` against `pattern` with a single `*`, and return the portion of `name` that matched the `*`.
+fn glob_match<'a>(pattern: &BStr, name: &'a BStr) -> Option<&'a BStr> {
+    let pos = pattern.find_byte(b'*')?;
+    let (prefix, suffix) = (&pattern[..pos], &pattern[pos + 1..]);
+    (name.len() >= prefix.len() + suffix.len() && name.starts_with(prefix) && name.ends_with(suffix))
+        .then(|| name[prefix.len()..name.len() - suffix.len()].as_bstr())
+}
+
+/// Substitute the `*` in `pattern` with `matched`.
+fn glob_replace(pattern: &BStr, matched: &BStr) -> BString {
+    let mut out = BString::from(Vec::with_capacity(pattern.len() + matched.len()));
+    match pattern.find_byte(b'*') {
+        Some(pos) => {
+            out.push_str(&pattern[..pos]);
+            out.push_str(matched);
+            out.push_str(&pattern[pos + 1..]);
+        }
+        None => out.push_str(pattern),
+    }
+    out
+}
@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 
 use gix_features::progress::Progress;
-use gix_protocol::transport::client::Transport;
+use gix_protocol::transport::{client::Transport, Service};
 
 use crate::{
     bstr,
@@ -188,35 +188,9 @@ where
         mut progress: impl Progress,
     ) -> Result<HandshakeWithRefs, Error> {
         let _span = gix_trace::coarse!("remote::Connection::fetch_refs()");
-        let mut credentials_storage;
-        let url = self.transport.to_url();
-        let authenticate = match self.authenticate.as_mut() {
-            Some(f) => f,
-            None => {
-                let url = self.remote.url(Direction::Fetch).map_or_else(
-                    || gix_url::parse(url.as_ref()).expect("valid URL to be provided by transport"),
-                    ToOwned::to_owned,
-                );
-                credentials_storage = self.configured_credentials(url)?;
-                &mut credentials_storage
-            }
-        };
-
-        if self.transport_options.is_none() {
-            self.transport_options = self
-                .remote
-                .repo
-                .transport_options(url.as_ref(), self.remote.name().map(crate::remote::Name::as_bstr))
-                .map_err(|err| Error::GatherTransportConfig {
-                    source: err,
-                    url: url.into_owned(),
-                })?;
-        }
-        if let Some(config) = self.transport_options.as_ref() {
-            self.transport.configure(&**config)?;
-        }
-        let mut outcome =
-            gix_protocol::fetch::handshake(&mut self.transport, authenticate, extra_parameters, &mut progress).await?;
+        let mut outcome = self
+            .handshake(Service::UploadPack, Direction::Fetch, extra_parameters, &mut progress)
+            .await?;
         let refs = match outcome.refs.take() {
             Some(refs) => refs,
             None => {
@@ -250,11 +224,52 @@ where
         };
         Ok(HandshakeWithRefs { outcome, refs })
     }
+
+    /// Perform a handshake with `service`, using credentials and transport options configured for `direction` unless
+    /// they were set by the caller.
+    #[allow(clippy::result_large_err)]
+    #[gix_protocol::maybe_async::maybe_async]
+    pub(crate) async fn handshake(
+        &mut self,
+        service: Service,
+        direction: Direction,
+        extra_parameters: Vec<(String, Option<String>)>,
+        progress: &mut impl Progress,
+    ) -> Result<gix_protocol::handshake::Outcome, Error> {
+        let mut credentials_storage;
+        let url = self.transport.to_url();
+        let authenticate = match self.authenticate.as_mut() {
+            Some(f) => f,
+            None => {
+                let url = self.remote.url(direction).map_or_else(
+                    || gix_url::parse(url.as_ref()).expect("valid URL to be provided by transport"),
+                    ToOwned::to_owned,
+                );
+                credentials_storage = self.configured_credentials(url)?;
+                &mut credentials_storage
+            }
+        };
+
+        if self.transport_options.is_none() {
+            self.transport_options = self
+                .remote
+                .repo
+                .transport_options(url.as_ref(), self.remote.name().map(crate::remote::Name::as_bstr))
+                .map_err(|err| Error::GatherTransportConfig {
+                    source: err,
+                    url: url.into_owned(),
+                })?;
+        }
+        if let Some(config) = self.transport_options.as_ref() {
+            self.transport.configure(&**config)?;
+        }
+        Ok(gix_protocol::handshake(&mut self.transport, service, authenticate, extra_parameters, progress).await?)
+    }
 }
 
 /// Assume sha1 if server says nothing, otherwise configure anything beyond sha1 in the local repo configuration
 #[allow(clippy::result_large_err)]
-fn extract_object_format(
+pub(crate) fn extract_object_format(
     _repo: &crate::Repository,
     outcome: &gix_protocol::handshake::Outcome,
 ) -> Result<gix_hash::Kind, Error> {
@@ -266,6 +281,7 @@ fn extract_object_format(
             })?;
             match object_format {
                 "sha1" => gix_hash::Kind::Sha1,
+                "sha256" => gix_hash::Kind::Sha256,
                 unknown => return Err(Error::UnknownObjectFormat { format: unknown.into() }),
             }
         } else {
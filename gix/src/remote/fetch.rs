@@ -37,6 +37,27 @@ pub(crate) enum WritePackedRefs {
     Only,
 }
 
+/// Control if and how `.git/FETCH_HEAD` is written after fetching, which is what `git merge FETCH_HEAD` and `git pull` use
+/// to learn what to merge.
+///
+/// Note that it's never written if `fetch.writeFetchHead` is `false`, or during a dry-run.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
+pub enum WriteFetchHead {
+    /// Don't write `FETCH_HEAD` at all, like `git fetch --no-write-fetch-head` or `git clone`.
+    Never,
+    /// Write `FETCH_HEAD` like `git fetch <remote>` does when using the refspecs configured for the remote.
+    ///
+    /// If the current branch is configured to merge from the remote with `branch.<name>.remote`, the refs configured
+    /// with `branch.<name>.merge` are marked for merge. If the current branch has no such configuration and the first
+    /// refspec of the remote isn't a pattern, the first ref it matched is marked for merge.
+    #[default]
+    Configured,
+    /// Write `FETCH_HEAD` with all refs matched by refspecs marked for merge, which is what `git fetch <remote> <refspec>…`
+    /// does with refspecs passed on the command-line.
+    AllForMerge,
+}
+
 /// Describe how to handle tags when fetching
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Tags {
@@ -48,6 +48,11 @@ pub mod init;
 #[allow(clippy::empty_docs)]
 pub mod fetch;
 
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "blocking-network-client")]
+pub mod push;
+
 ///
 #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
 pub mod connect;
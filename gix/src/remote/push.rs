@@ -0,0 +1 @@
+pub use super::connection::push::{prepare, update, Error, Options, Outcome, Prepare, ProgressId, Status, Update};
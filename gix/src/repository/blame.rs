@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::{BStr, ByteSlice},
+    Repository,
+};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod ignore_revs {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [Repository::blame_ignore_revs()](crate::Repository::blame_ignore_revs()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        InterpolatePath(#[from] gix_config::path::interpolate::Error),
+        #[error("Could not read revisions to ignore from '{}'", path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+        #[error("Invalid object name '{line}' in '{}'", path.display())]
+        InvalidObjectName { path: PathBuf, line: BString },
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod blame_file {
+    /// The error returned by [Repository::blame_file()](crate::Repository::blame_file()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        DiffResourceCache(#[from] crate::repository::diff::resource_cache::Error),
+        #[error(transparent)]
+        Traverse(#[from] gix_traverse::commit::ancestors::Error),
+        #[error(transparent)]
+        Blame(#[from] gix_blame::file::Error),
+    }
+}
+
+/// Blame-utilities
+impl Repository {
+    /// Attribute each line of the file at `file_path` in the tree of the `suspect` commit to the commit that introduced it,
+    /// walking all of its ancestors as needed. `options` can be used to limit the lines to blame, to follow renames,
+    /// or to ignore certain revisions, for instance those returned by [`blame_ignore_revs()`](Self::blame_ignore_revs()).
+    ///
+    /// Blobs are diffed as stored in the object database, with the diff-algorithm and attributes configured
+    /// for [diffs](Self::diff_resource_cache()).
+    pub fn blame_file(
+        &self,
+        file_path: &BStr,
+        suspect: impl Into<ObjectId>,
+        options: gix_blame::file::Options,
+    ) -> Result<gix_blame::file::Outcome, blame_file::Error> {
+        let mut resource_cache = self.diff_resource_cache(gix_diff::blob::pipeline::Mode::ToGit, Default::default())?;
+        let traverse = gix_traverse::commit::Ancestors::new(
+            Some(suspect.into()),
+            gix_traverse::commit::ancestors::State::default(),
+            &self.objects,
+        )
+        .sorting(gix_traverse::commit::Sorting::ByCommitTimeNewestFirst)?
+        .commit_graph(self.commit_graph().ok());
+        Ok(gix_blame::file(
+            &self.objects,
+            traverse,
+            &mut resource_cache,
+            file_path,
+            options,
+        )?)
+    }
+
+    /// Read the ids of all commits to ignore when blaming from the files configured in `blame.ignoreRevsFile`,
+    /// followed by `additional_files`. Relative paths are relative to the working tree, or to the `.git` directory
+    /// in bare repositories, and an empty path clears all previously read ids, just like in `git`.
+    ///
+    /// Each line of such a file contains a full hexadecimal object id, with everything after `#` being a comment.
+    pub fn blame_ignore_revs(
+        &self,
+        additional_files: impl IntoIterator<Item = PathBuf>,
+    ) -> Result<gix_hashtable::HashSet<ObjectId>, ignore_revs::Error> {
+        let mut paths = self.config.blame_ignore_revs_files()?;
+        paths.extend(additional_files);
+        let base = self.work_dir().unwrap_or_else(|| self.git_dir());
+        let mut out = gix_hashtable::HashSet::default();
+        for path in paths {
+            if path.as_os_str().is_empty() {
+                out.clear();
+                continue;
+            }
+            let path = base.join(path);
+            let data = std::fs::read(&path).map_err(|source| ignore_revs::Error::Io {
+                path: path.clone(),
+                source,
+            })?;
+            for line in data.split(|b| *b == b'\n') {
+                let line = line.split(|b| *b == b'#').next().unwrap_or_default().trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let id = ObjectId::from_hex(line).map_err(|_| ignore_revs::Error::InvalidObjectName {
+                    path: path.clone(),
+                    line: line.into(),
+                })?;
+                out.insert(id);
+            }
+        }
+        Ok(out)
+    }
+}
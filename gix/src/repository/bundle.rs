@@ -0,0 +1,245 @@
+use crate::{bundle, Repository};
+
+/// Bundles
+impl Repository {
+    /// Check if `header` of a bundle can be used with this repository, which is the case if its objects are of the same
+    /// kind and if all of its prerequisite commits are present.
+    ///
+    /// Only if this is the case can the bundle be fetched from, as the objects in its pack refer to the prerequisites.
+    pub fn verify_bundle(&self, header: &bundle::Header) -> Result<(), bundle::verify::Error> {
+        if header.object_hash != self.object_hash() {
+            return Err(bundle::verify::Error::ObjectHash {
+                bundle: header.object_hash,
+                repository: self.object_hash(),
+            });
+        }
+        let ids: Vec<_> = header
+            .missing_prerequisites(|id| self.has_object(id))
+            .map(|prerequisite| prerequisite.id)
+            .collect();
+        if ids.is_empty() {
+            Ok(())
+        } else {
+            Err(bundle::verify::Error::MissingPrerequisites { ids })
+        }
+    }
+}
+
+#[cfg(feature = "bundle")]
+mod create {
+    use std::{
+        io::Write,
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use gix_features::{
+        parallel::InOrderIter,
+        progress::{Count as _, Progress},
+    };
+    use gix_hash::ObjectId;
+    use gix_pack::data::output::{count, entry};
+    use gix_ref::Target;
+
+    use crate::{
+        bstr::BStr,
+        bundle::{self, create::Error},
+        Repository,
+    };
+
+    /// Bundles
+    impl Repository {
+        /// Write a bundle to `out` which contains all objects reachable from the commits included by `specs`,
+        /// but not from the commits they exclude, similar to `git bundle create`.
+        ///
+        /// Each rev-spec that resolves to a reference, like `main`, `HEAD` or `v1.0..main`, adds that reference to the
+        /// bundle, and it's an error if there is no such reference.
+        /// Excluded commits that are parents of included ones become the prerequisites of the bundle, which a receiving
+        /// repository needs to have to be able to fetch from it.
+        ///
+        /// Configure the bundle with `options`, and use `progress` and `should_interrupt` to observe and stop the operation.
+        pub fn create_bundle(
+            &self,
+            specs: impl IntoIterator<Item = impl AsRef<BStr>>,
+            out: &mut dyn Write,
+            options: bundle::create::Options,
+            progress: &mut dyn crate::DynNestedProgress,
+            should_interrupt: &AtomicBool,
+        ) -> Result<bundle::create::Outcome, Error> {
+            let mut includes = Vec::new();
+            let mut excludes = Vec::new();
+            let mut refs = Vec::<bundle::Ref>::new();
+            for spec in specs {
+                let parsed = self.rev_parse(spec.as_ref())?;
+                let (first_ref, second_ref) = (parsed.first_reference().cloned(), parsed.second_reference().cloned());
+                let mut add_ref = |r: Option<gix_ref::Reference>, id: ObjectId| -> Result<(), Error> {
+                    if let Some(r) = self.bundle_ref(r, id)? {
+                        if !refs.iter().any(|existing| existing.name == r.name) {
+                            refs.push(r);
+                        }
+                    }
+                    Ok(())
+                };
+                match parsed.detach() {
+                    gix_revision::Spec::Include(id) => {
+                        includes.push(id);
+                        add_ref(first_ref, id)?;
+                    }
+                    gix_revision::Spec::Exclude(id) => excludes.push(id),
+                    gix_revision::Spec::Range { from, to } => {
+                        excludes.push(from);
+                        includes.push(to);
+                        add_ref(second_ref, to)?;
+                    }
+                    gix_revision::Spec::Merge { theirs, ours } => {
+                        includes.extend([theirs, ours]);
+                        excludes.extend(
+                            self.merge_bases_many(theirs, &[ours])?
+                                .into_iter()
+                                .map(crate::Id::detach),
+                        );
+                        add_ref(first_ref, theirs)?;
+                        add_ref(second_ref, ours)?;
+                    }
+                    gix_revision::Spec::IncludeOnlyParents(id) => {
+                        includes.extend(self.find_object(id)?.try_to_commit_ref()?.parents());
+                    }
+                    gix_revision::Spec::ExcludeParents(id) => {
+                        includes.push(id);
+                        excludes.extend(self.find_object(id)?.try_to_commit_ref()?.parents());
+                        add_ref(first_ref, id)?;
+                    }
+                }
+            }
+            if refs.is_empty() {
+                return Err(Error::NoReferences);
+            }
+
+            let mut input = Vec::new();
+            let mut tips = Vec::new();
+            for id in includes {
+                let object = self.find_object(id)?;
+                match object.kind {
+                    gix_object::Kind::Commit => tips.push(id),
+                    gix_object::Kind::Tag => {
+                        input.push(id);
+                        if let Ok(Ok(commit)) = object.peel_tags_to_end().map(crate::Object::try_into_commit) {
+                            tips.push(commit.id);
+                        }
+                    }
+                    gix_object::Kind::Tree | gix_object::Kind::Blob => input.push(id),
+                }
+            }
+            let mut exclude_tips = Vec::new();
+            for id in excludes {
+                if let Ok(commit) = self.find_object(id)?.peel_tags_to_end()?.try_into_commit() {
+                    exclude_tips.push(commit.id);
+                }
+            }
+
+            let excluded = gix_traverse::commit::Ancestors::new(
+                exclude_tips,
+                gix_traverse::commit::ancestors::State::default(),
+                &self.objects,
+            )
+            .map(|info| info.map(|info| info.id))
+            .collect::<Result<gix_hashtable::HashSet<_>, _>>()?;
+            tips.retain(|id| !excluded.contains(id));
+            let mut prerequisites = Vec::<ObjectId>::new();
+            for info in gix_traverse::commit::Ancestors::filtered(
+                tips,
+                gix_traverse::commit::ancestors::State::default(),
+                &self.objects,
+                |id| !excluded.contains(id),
+            ) {
+                let info = info?;
+                for parent_id in info.parent_ids.iter().filter(|id| excluded.contains(*id)) {
+                    if !prerequisites.contains(parent_id) {
+                        prerequisites.push(*parent_id);
+                    }
+                }
+                input.push(info.id);
+            }
+
+            let mut header = bundle::Header::new(self.object_hash());
+            if let Some(version) = options.version {
+                header.version = version;
+            }
+            header.refs = refs;
+            let mut known_to_receiver = gix_hashtable::HashSet::default();
+            for id in prerequisites {
+                let commit = self.find_object(id)?;
+                let commit_ref = commit.try_to_commit_ref()?;
+                known_to_receiver.insert(commit_ref.tree());
+                header.prerequisites.push(bundle::Prerequisite {
+                    id,
+                    comment: commit_ref.message_summary().into_owned(),
+                });
+                known_to_receiver.insert(id);
+            }
+
+            let mut db = self.objects.clone().into_arc()?;
+            db.prevent_pack_unload();
+            let mut counting = progress.add_child("counting".into());
+            counting.init(None, gix_features::progress::count("objects"));
+            let (mut counts, _outcome) = count::objects_unthreaded(
+                &db,
+                &mut input
+                    .into_iter()
+                    .map(Ok::<_, Box<dyn std::error::Error + Send + Sync + 'static>>),
+                &counting,
+                should_interrupt,
+                count::objects::ObjectExpansion::TreeAdditionsComparedToAncestor,
+            )?;
+            // Parents and their trees are added for comparison, but they are prerequisites or reachable from them.
+            counts.retain(|count| !known_to_receiver.contains(&count.id));
+
+            header.write_to(out)?;
+            let num_objects = counts.len();
+            let defaults = entry::iter_from_counts::Options::default();
+            let entries = InOrderIter::from(entry::iter_from_counts(
+                counts,
+                db,
+                Box::new(progress.add_child("creating entries".into())),
+                entry::iter_from_counts::Options {
+                    thread_limit: options.thread_limit,
+                    mode: entry::iter_from_counts::Mode::PackCopyAndDeltaCompression,
+                    allow_thin_pack: false,
+                    chunk_size: 1000,
+                    version: Default::default(),
+                    delta_window: defaults.delta_window,
+                    delta_depth: defaults.delta_depth,
+                },
+            ));
+            let mut write_progress = progress.add_child("writing".into());
+            write_progress.init(None, gix_features::progress::bytes());
+            let mut pack = gix_pack::data::output::bytes::FromEntriesIter::new(
+                entries,
+                &mut *out,
+                num_objects as u32,
+                gix_pack::data::Version::default(),
+                self.object_hash(),
+            );
+            for written in pack.by_ref() {
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+                write_progress.inc_by(written? as usize);
+            }
+            out.flush()?;
+            Ok(bundle::create::Outcome { header, num_objects })
+        }
+
+        /// Return a bundle reference for `r` if it points to `id`, possibly through a symbolic reference like `HEAD`.
+        fn bundle_ref(&self, r: Option<gix_ref::Reference>, id: ObjectId) -> Result<Option<bundle::Ref>, Error> {
+            let Some(r) = r else { return Ok(None) };
+            let target = match &r.target {
+                Target::Peeled(target) => Some(*target),
+                Target::Symbolic(name) => self.find_reference(name.as_ref())?.try_id().map(crate::Id::detach),
+            };
+            Ok((target == Some(id)).then(|| bundle::Ref {
+                name: r.name.as_bstr().to_owned(),
+                id,
+            }))
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use std::{sync::atomic::AtomicBool, time::SystemTime};
+
+use crate::{
+    config::{
+        cache::util::ApplyLeniency,
+        tree::{Gc, Pack, Repack},
+    },
+    gc,
+    gc::{options::Error, repack, Trigger},
+    Repository,
+};
+
+/// The default of `gc.auto`.
+const DEFAULT_AUTO: u64 = 6700;
+/// The default of `gc.autoPackLimit`.
+const DEFAULT_AUTO_PACK_LIMIT: u64 = 50;
+
+/// Maintenance
+impl Repository {
+    /// Return the options for [`gc()`](Self::gc()) as configured with `gc.packRefs`, `gc.reflogExpire`,
+    /// `gc.reflogExpireUnreachable`, `gc.pruneExpire`, `gc.cruftPacks`, `gc.writeCommitGraph`, `repack.writeBitmaps`
+    /// and the `pack.*` settings for delta compression, which repack all objects into one pack.
+    pub fn gc_options(&self) -> Result<gc::Options, Error> {
+        let config = &self.config.resolved;
+        let lenient = self.config.lenient_config;
+        let now = SystemTime::now();
+        let day = std::time::Duration::from_secs(60 * 60 * 24);
+
+        let boolean = |key: &str, config_key: &'static crate::config::tree::keys::Boolean| {
+            config
+                .boolean_by_key(key)
+                .map(|value| config_key.enrich_error(value))
+                .transpose()
+                .with_leniency(lenient)
+        };
+        let expiry = |key: &str, config_key: &'static crate::config::tree::gc::Expiry, default: Option<SystemTime>| {
+            Ok::<_, Error>(
+                config
+                    .string_by_key(key)
+                    .map(|value| config_key.try_into_expiry(value, now))
+                    .transpose()
+                    .with_leniency(lenient)?
+                    .unwrap_or(default),
+            )
+        };
+        let unsigned = |key: &str, config_key: &'static crate::config::tree::keys::UnsignedInteger| {
+            config
+                .integer_by_key(key)
+                .map(|value| config_key.try_into_usize(value))
+                .transpose()
+                .with_leniency(lenient)
+        };
+
+        let pack_refs = config
+            .string_by_key("gc.packRefs")
+            .map(|value| Gc::PACK_REFS.try_into_pack_refs(value, self.is_bare()))
+            .transpose()
+            .with_leniency(lenient)?
+            .unwrap_or(true);
+        let reflog = gc::reflog::Options {
+            expire: expiry("gc.reflogExpire", &Gc::REFLOG_EXPIRE, Some(now - day * 90))?,
+            expire_unreachable: expiry(
+                "gc.reflogExpireUnreachable",
+                &Gc::REFLOG_EXPIRE_UNREACHABLE,
+                Some(now - day * 30),
+            )?,
+        };
+        let prune_expire = expiry("gc.pruneExpire", &Gc::PRUNE_EXPIRE, Some(now - day * 14))?;
+        let unreachable = if boolean("gc.cruftPacks", &Gc::CRUFT_PACKS)?.unwrap_or(true) {
+            repack::Unreachable::Cruft { expire: prune_expire }
+        } else {
+            repack::Unreachable::Loosen { expire: prune_expire }
+        };
+        let defaults = repack::Options::default();
+        Ok(gc::Options {
+            pack_refs,
+            reflog,
+            repack: repack::Options {
+                mode: repack::Mode::AllIntoOne { unreachable },
+                write_bitmap: boolean("repack.writeBitmaps", &Repack::WRITE_BITMAPS)?.unwrap_or(self.is_bare()),
+                write_reverse_index: defaults.write_reverse_index,
+                thread_limit: unsigned("pack.threads", &Pack::THREADS)?,
+                delta_window: unsigned("pack.window", &Pack::WINDOW)?.unwrap_or(defaults.delta_window),
+                delta_depth: unsigned("pack.depth", &Pack::DEPTH)?.unwrap_or(defaults.delta_depth),
+            },
+            prune_expire,
+            write_commit_graph: boolean("gc.writeCommitGraph", &Gc::WRITE_COMMIT_GRAPH)?.unwrap_or(true),
+        })
+    }
+
+    /// Return the reason for maintenance if the amount of packs exceeds `gc.autoPackLimit`, or if the amount of loose objects
+    /// likely exceeds `gc.auto`, or `None` if no maintenance is needed or if `gc.auto` is `0`.
+    ///
+    /// Like `git gc --auto`, the amount of loose objects is estimated by counting them in a single fan-out directory.
+    pub fn needs_gc(&self) -> Result<Option<Trigger>, gc::Error> {
+        let config = &self.config.resolved;
+        let lenient = self.config.lenient_config;
+        let unsigned = |key: &str, config_key: &'static crate::config::tree::keys::UnsignedInteger| {
+            config
+                .integer_by_key(key)
+                .map(|value| config_key.try_into_u64(value))
+                .transpose()
+                .with_leniency(lenient)
+                .map_err(Error::from)
+        };
+        let auto = unsigned("gc.auto", &Gc::AUTO)?.unwrap_or(DEFAULT_AUTO);
+        if auto == 0 {
+            return Ok(None);
+        }
+        let objects_dir = self.objects.store_ref().path();
+
+        let pack_limit = unsigned("gc.autoPackLimit", &Gc::AUTO_PACK_LIMIT)?.unwrap_or(DEFAULT_AUTO_PACK_LIMIT);
+        if pack_limit > 0 {
+            let num_packs = gc::packs::count_unkept(&objects_dir.join("pack"))?;
+            if num_packs as u64 > pack_limit {
+                return Ok(Some(Trigger::TooManyPacks {
+                    num_packs,
+                    limit: pack_limit,
+                }));
+            }
+        }
+
+        let fan_out_dir = objects_dir.join("17");
+        let hex_len = self.object_hash().len_in_hex() - 2;
+        let mut num_objects = 0;
+        match std::fs::read_dir(fan_out_dir) {
+            Ok(entries) => {
+                for entry in entries {
+                    let name = entry?.file_name();
+                    let is_object = matches!(
+                        name.to_str(),
+                        Some(name) if name.len() == hex_len && name.bytes().all(|b| b.is_ascii_hexdigit())
+                    );
+                    if is_object {
+                        num_objects += 1;
+                    }
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        Ok(
+            (num_objects > (auto + 255) / 256).then_some(Trigger::TooManyLooseObjects {
+                estimated_objects: num_objects * 256,
+                limit: auto,
+            }),
+        )
+    }
+
+    /// Perform all maintenance tasks according to `options`, similar to `git gc`, and report progress to `progress`.
+    /// Use `should_interrupt` to stop the operation while objects are being packed.
+    ///
+    /// References are packed first and reflogs are expired, after which all objects are [repacked](Self::repack()), unreachable
+    /// loose objects are [pruned](Self::prune_loose_objects()) and finally the commit-graph is updated.
+    pub fn gc(
+        &self,
+        options: gc::Options,
+        progress: &mut dyn crate::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<gc::Outcome, gc::Error> {
+        let _span = gix_trace::coarse!("gix::Repository::gc()");
+        let pack_refs = options.pack_refs.then(|| gc::pack_refs::run(self)).transpose()?;
+        let reflog = gc::reflog::run(self, options.reflog)?;
+
+        let db = gc::object_database(self)?;
+        let reachable = if options.repack.mode.needs_reachable_objects() || options.prune_expire.is_some() {
+            Some(gc::reachable::objects(self, &db, progress, should_interrupt)?)
+        } else {
+            None
+        };
+        let repack = gc::repack::run(
+            self,
+            &db,
+            reachable.as_deref(),
+            &options.repack,
+            progress,
+            should_interrupt,
+        )?;
+        let prune = options
+            .prune_expire
+            .map(|expire| gc::prune::run(self, &db, reachable.as_deref().unwrap_or_default(), expire))
+            .transpose()?;
+
+        let commit_graph = if options.write_commit_graph && !self.is_shallow() {
+            let tips = gc::reachable::commit_tips(self)?;
+            (!tips.is_empty())
+                .then(|| {
+                    self.write_commit_graph(
+                        tips,
+                        gix_commitgraph::write::Options {
+                            split: Some(Default::default()),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .transpose()?
+        } else {
+            None
+        };
+        Ok(gc::Outcome {
+            pack_refs,
+            reflog,
+            repack,
+            prune,
+            commit_graph,
+        })
+    }
+
+    /// Perform maintenance with the [configured options](Self::gc_options()) only if [it is needed](Self::needs_gc()),
+    /// similar to `git gc --auto`, and return `None` if nothing was done.
+    ///
+    /// If there are only too many loose objects, they are packed incrementally instead of repacking everything.
+    /// This is useful to call after fetching.
+    pub fn gc_auto(
+        &self,
+        progress: &mut dyn crate::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Option<gc::Outcome>, gc::Error> {
+        let Some(trigger) = self.needs_gc()? else {
+            return Ok(None);
+        };
+        let mut options = self.gc_options()?;
+        if let Trigger::TooManyLooseObjects { .. } = trigger {
+            options.repack.mode = repack::Mode::Incremental;
+        }
+        self.gc(options, progress, should_interrupt).map(Some)
+    }
+
+    /// Pack objects according to `options`, similar to `git repack -d`, and report progress to `progress`.
+    /// Use `should_interrupt` to stop the operation while objects are being packed.
+    ///
+    /// Loose objects that are contained in a pack afterwards are deleted, and an existing multi-pack index is updated.
+    pub fn repack(
+        &self,
+        options: &repack::Options,
+        progress: &mut dyn crate::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<repack::Outcome, repack::Error> {
+        let db = gc::object_database(self)?;
+        gc::repack::run(self, &db, None, options, progress, should_interrupt)
+    }
+
+    /// Delete all loose objects that aren't reachable from any reference, reflog or index and that were last modified
+    /// at or before `expire`, similar to `git prune --expire <expire>`.
+    ///
+    /// Objects that are referred to by more recent unreachable objects are kept as well.
+    pub fn prune_loose_objects(
+        &self,
+        expire: SystemTime,
+        progress: &mut dyn crate::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<gc::prune::Outcome, gc::prune::Error> {
+        let db = gc::object_database(self)?;
+        let reachable = gc::reachable::objects(self, &db, progress, should_interrupt)?;
+        gc::prune::run(self, &db, &reachable, expire)
+    }
+
+    /// Remove reflog entries according to `options`, similar to `git reflog expire --all`.
+    ///
+    /// Note that reflogs stored in reftables are left untouched.
+    pub fn expire_reflogs(&self, options: gc::reflog::Options) -> Result<gc::reflog::Outcome, gc::reflog::Error> {
+        gc::reflog::run(self, options)
+    }
+
+    /// Move all loose references into the `packed-refs` file, similar to `git pack-refs --all`, or compact all reftables
+    /// into one if the repository uses them.
+    pub fn pack_refs(&self) -> Result<gc::pack_refs::Outcome, gc::pack_refs::Error> {
+        gc::pack_refs::run(self)
+    }
+}
@@ -31,4 +31,92 @@ impl crate::Repository {
     pub fn commit_graph(&self) -> Result<gix_commitgraph::Graph, gix_commitgraph::init::Error> {
         gix_commitgraph::at(self.objects.store_ref().path().join("info"))
     }
+
+    /// Write a commit-graph with all commits reachable from `tips`, which must point to commits, into the object database
+    /// of this repository, configured by `options`, which always uses the object hash of this repository.
+    ///
+    /// If [`split`][gix_commitgraph::write::Options::split] is set, only commits that aren't yet part of the existing
+    /// commit-graph are traversed and written into a new file of the commit-graph chain. Note that changed-path filters
+    /// are only computed for commits that are newly written.
+    pub fn write_commit_graph(
+        &self,
+        tips: impl IntoIterator<Item = impl Into<gix_hash::ObjectId>>,
+        mut options: gix_commitgraph::write::Options,
+    ) -> Result<gix_commitgraph::write::Outcome, crate::repository::write_commit_graph::Error> {
+        options.object_hash = self.object_hash();
+        let existing = options.split.and_then(|_| self.commit_graph().ok());
+        let mut seen = gix_hashtable::HashSet::default();
+        let mut queue: Vec<gix_hash::ObjectId> = tips.into_iter().map(Into::into).collect();
+        let mut commits = Vec::new();
+        while let Some(id) = queue.pop() {
+            if !seen.insert(id) || matches!(&existing, Some(graph) if graph.lookup(id).is_some()) {
+                continue;
+            }
+            let commit = self.find_object(id)?.try_into_commit()?;
+            let parents: Vec<_> = commit.parent_ids().map(crate::Id::detach).collect();
+            queue.extend(parents.iter().rev().copied());
+            commits.push(gix_commitgraph::write::Commit {
+                id,
+                tree: commit.tree_id()?.detach(),
+                parents,
+                commit_time: commit.committer()?.time.seconds.max(0) as u64,
+                changed_paths: None,
+            });
+        }
+
+        if let Some(settings) = options.changed_paths {
+            let trees: gix_hashtable::HashMap<_, _> = commits.iter().map(|commit| (commit.id, commit.tree)).collect();
+            let mut state = gix_diff::tree::State::default();
+            for commit in &mut commits {
+                let parent_tree = match commit.parents.first() {
+                    Some(parent) => match trees.get(parent) {
+                        Some(tree) => *tree,
+                        None => self.find_object(*parent)?.try_into_commit()?.tree_id()?.detach(),
+                    },
+                    None => gix_hash::ObjectId::empty_tree(self.object_hash()),
+                };
+                commit.changed_paths =
+                    Some(self.changed_paths_filter(parent_tree, commit.tree, &settings, &mut state)?);
+            }
+        }
+
+        Ok(gix_commitgraph::Graph::write_to_info_dir(
+            &self.objects.store_ref().path().join("info"),
+            commits,
+            options,
+        )?)
+    }
+
+    /// Compute the Bloom filter of all paths that differ between the trees `lhs` and `rhs`.
+    fn changed_paths_filter(
+        &self,
+        lhs: gix_hash::ObjectId,
+        rhs: gix_hash::ObjectId,
+        settings: &gix_commitgraph::bloom::Settings,
+        state: &mut gix_diff::tree::State,
+    ) -> Result<gix_commitgraph::bloom::Filter, crate::repository::write_commit_graph::Error> {
+        let lhs = self.find_object(lhs)?;
+        let rhs = self.find_object(rhs)?;
+        let mut recorder = gix_diff::tree::Recorder::default();
+        gix_diff::tree::Changes::from(gix_object::TreeRefIter::from_bytes(&lhs.data, self.object_hash()))
+            .needed_to_obtain(
+                gix_object::TreeRefIter::from_bytes(&rhs.data, self.object_hash()),
+                state,
+                &self.objects,
+                &mut recorder,
+            )?;
+        use gix_diff::tree::recorder::Change;
+        let paths = recorder.records.iter().filter_map(|change| match change {
+            Change::Addition { entry_mode, path, .. } | Change::Deletion { entry_mode, path, .. } => {
+                (!entry_mode.is_tree()).then_some(path.as_ref())
+            }
+            Change::Modification {
+                previous_entry_mode,
+                entry_mode,
+                path,
+                ..
+            } => (!(entry_mode.is_tree() && previous_entry_mode.is_tree())).then_some(path.as_ref()),
+        });
+        Ok(gix_commitgraph::bloom::Filter::from_changed_paths(paths, settings))
+    }
 }
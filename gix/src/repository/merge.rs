@@ -0,0 +1,142 @@
+use crate::{
+    config::{cache::util::ApplyLeniency, tree::Merge},
+    Repository,
+};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod blob_merge_platform {
+    /// The error returned by [Repository::blob_merge_platform()](crate::Repository::blob_merge_platform()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        PipelineOptions(#[from] crate::config::merge::pipeline_options::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod tree_merge_options {
+    /// The error returned by [Repository::tree_merge_options()](crate::Repository::tree_merge_options()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Renames(#[from] crate::config::key::GenericError),
+        #[error(transparent)]
+        RenameLimit(#[from] crate::config::unsigned_integer::Error),
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod merge_trees {
+    /// The error returned by [Repository::merge_trees()](crate::Repository::merge_trees()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        BlobMergePlatform(#[from] super::blob_merge_platform::Error),
+        #[error(transparent)]
+        DiffResourceCache(#[from] crate::repository::diff::resource_cache::Error),
+        #[error(transparent)]
+        Index(#[from] crate::repository::index_or_load_from_head::Error),
+        #[error(transparent)]
+        AttributeStack(#[from] crate::config::attribute_stack::Error),
+        #[error(transparent)]
+        MergeTrees(#[from] gix_merge::tree::Error),
+    }
+}
+
+/// Merge-utilities
+impl Repository {
+    /// Create a platform for merging blobs, configured with all user-defined merge drivers and with `merge.conflictStyle`
+    /// and `merge.default`, just like `git` would use them.
+    pub fn blob_merge_platform(&self) -> Result<gix_merge::blob::Platform, blob_merge_platform::Error> {
+        Ok(gix_merge::blob::Platform::new(
+            self.config.merge_drivers(),
+            self.config.merge_pipeline_options()?,
+        ))
+    }
+
+    /// Return the options for merging trees as configured, with renames being tracked unless `merge.renames` or
+    /// `diff.renames` disable it, and with `merge.renameLimit` or `diff.renameLimit` as limit.
+    pub fn tree_merge_options(&self) -> Result<gix_merge::tree::Options, tree_merge_options::Error> {
+        let config = &self.config.resolved;
+        let lenient = self.config.lenient_config;
+        let renames = config
+            .boolean_by_key("merge.renames")
+            .map(|value| Merge::RENAMES.try_into_renames(value))
+            .or_else(|| {
+                config
+                    .boolean_by_key("diff.renames")
+                    .map(|value| crate::config::tree::Diff::RENAMES.try_into_renames(value))
+            })
+            .transpose()
+            .with_leniency(lenient)?;
+        if renames == Some(crate::diff::rename::Tracking::Disabled) {
+            return Ok(gix_merge::tree::Options { rewrites: None });
+        }
+
+        let default = gix_diff::Rewrites::default();
+        let limit = config
+            .integer_by_key("merge.renameLimit")
+            .map(|value| Merge::RENAME_LIMIT.try_into_usize(value))
+            .or_else(|| {
+                config
+                    .integer_by_key("diff.renameLimit")
+                    .map(|value| crate::config::tree::Diff::RENAME_LIMIT.try_into_usize(value))
+            })
+            .transpose()
+            .with_leniency(lenient)?
+            .unwrap_or(default.limit);
+        Ok(gix_merge::tree::Options {
+            rewrites: Some(gix_diff::Rewrites {
+                copies: None,
+                limit,
+                ..default
+            }),
+        })
+    }
+
+    /// Merge `our_tree` and `their_tree` with `ancestor_tree` as their merge-base, and write all merged objects
+    /// into the object database. `labels` are used in conflict markers, and `options` can be obtained with
+    /// [`tree_merge_options()`](Self::tree_merge_options()).
+    ///
+    /// Blobs are merged with the platform from [`blob_merge_platform()`](Self::blob_merge_platform()), and
+    /// attributes are read from the index of `HEAD`.
+    ///
+    /// Note that conflicts aren't an error, but are recorded in the returned outcome, whose `index` has all
+    /// conflicting entries in stages 1, 2 and 3.
+    pub fn merge_trees(
+        &self,
+        ancestor_tree: impl AsRef<gix_hash::oid>,
+        our_tree: impl AsRef<gix_hash::oid>,
+        their_tree: impl AsRef<gix_hash::oid>,
+        labels: gix_merge::blob::Labels<'_>,
+        options: gix_merge::tree::Options,
+    ) -> Result<gix_merge::tree::Outcome, merge_trees::Error> {
+        let mut blob_merge = self.blob_merge_platform()?;
+        let mut diff_cache = self.diff_resource_cache(gix_diff::blob::pipeline::Mode::ToGit, Default::default())?;
+        let index = self.index_or_load_from_head()?;
+        let mut attributes = self.attributes_only(&index, gix_worktree::stack::state::attributes::Source::IdMapping)?;
+        let outcome = gix_merge::tree(
+            ancestor_tree.as_ref(),
+            our_tree.as_ref(),
+            their_tree.as_ref(),
+            labels,
+            &self.objects,
+            |object| self.write_object(object).map(crate::Id::detach),
+            &mut blob_merge,
+            &mut |path, out| {
+                if let Ok(platform) = attributes.at_entry(path, Some(false)) {
+                    platform.matching_attributes(out);
+                }
+            },
+            &mut diff_cache,
+            options,
+        )?;
+        Ok(outcome)
+    }
+}
@@ -38,6 +38,11 @@ impl crate::Repository {
 
 #[cfg(any(feature = "attributes", feature = "excludes"))]
 pub mod attributes;
+///
+#[cfg(feature = "blame")]
+pub mod blame;
+#[cfg(any(feature = "bundle", feature = "blocking-network-client"))]
+mod bundle;
 mod cache;
 mod config;
 ///
@@ -49,6 +54,8 @@ mod dirwalk;
 ///
 #[cfg(feature = "attributes")]
 pub mod filter;
+#[cfg(feature = "gc")]
+mod gc;
 mod graph;
 pub(crate) mod identity;
 mod impls;
@@ -59,17 +66,28 @@ mod kind;
 mod location;
 #[cfg(feature = "mailmap")]
 mod mailmap;
+///
+#[cfg(feature = "merge")]
+pub mod merge;
+#[cfg(feature = "notes")]
+mod notes;
 mod object;
 #[cfg(feature = "attributes")]
 mod pathspec;
+#[cfg(feature = "rebase")]
+mod rebase;
 mod reference;
 mod remote;
 #[cfg(feature = "revision")]
 mod revision;
 mod shallow;
+#[cfg(feature = "excludes")]
+mod sparse;
 mod state;
 #[cfg(feature = "attributes")]
 mod submodule;
+#[cfg(feature = "worktree-mutation")]
+mod switch;
 mod thread_safe;
 mod worktree;
 
@@ -170,3 +188,39 @@ pub mod worktree_archive {
     /// The error returned by [`Repository::worktree_archive()`][crate::Repository::worktree_archive()].
     pub type Error = gix_archive::Error;
 }
+
+///
+#[cfg(feature = "revision")]
+pub mod merge_base {
+    /// The error returned by [`Repository::merge_base()`][crate::Repository::merge_base()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        MergeBase(#[from] gix_revision::merge_base::Error),
+        #[error("No merge base was found between {one} and {two}")]
+        NotFound {
+            one: gix_hash::ObjectId,
+            two: gix_hash::ObjectId,
+        },
+    }
+}
+
+///
+pub mod write_commit_graph {
+    /// The error returned by [`Repository::write_commit_graph()`][crate::Repository::write_commit_graph()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        NotACommit(#[from] crate::object::try_into::Error),
+        #[error(transparent)]
+        DecodeCommit(#[from] gix_object::decode::Error),
+        #[error("Could not compute the paths changed by a commit")]
+        Diff(#[from] gix_diff::tree::changes::Error),
+        #[error(transparent)]
+        Write(#[from] gix_commitgraph::write::Error),
+    }
+}
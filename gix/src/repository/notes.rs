@@ -0,0 +1,101 @@
+use std::borrow::Cow;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    config::{cache::util::ApplyLeniency, tree::Core},
+    Notes, Repository,
+};
+
+/// Notes-utilities
+impl Repository {
+    /// Return the notes of the notes reference configured in `core.notesRef`, or of `refs/notes/commits` by default,
+    /// which is where notes are read from and written to unless another notes reference is given.
+    pub fn notes(&self) -> Result<Notes<'_>, crate::notes::open::Error> {
+        let ref_name = self
+            .config
+            .resolved
+            .string("core", None, Core::NOTES_REF.name)
+            .map(|name| crate::config::tree::branch::Merge::try_into_fullrefname(name).map(Cow::into_owned))
+            .transpose()
+            .with_leniency(self.config.lenient_config)?
+            .unwrap_or_else(|| {
+                crate::notes::DEFAULT_REF
+                    .try_into()
+                    .expect("statically known to be valid")
+            });
+        Ok(Notes { repo: self, ref_name })
+    }
+
+    /// Return the notes of the notes reference `name`, which is expanded like `git notes --ref` does, so `foo`
+    /// and `notes/foo` both refer to `refs/notes/foo`.
+    pub fn notes_at<'a>(&self, name: impl Into<&'a BStr>) -> Result<Notes<'_>, gix_validate::reference::name::Error> {
+        Ok(Notes {
+            repo: self,
+            ref_name: expand_notes_ref(name.into()).try_into()?,
+        })
+    }
+
+    /// Return the notes which are displayed along with commits, like `git log` does. These are the [default notes](Self::notes())
+    /// followed by all notes matching the patterns configured in `notes.displayRef`, in order and without duplicates.
+    ///
+    /// Patterns may contain glob characters, and are expanded to all matching references, while references that don't
+    /// exist are ignored.
+    pub fn notes_to_display(&self) -> Result<Vec<Notes<'_>>, crate::notes::to_display::Error> {
+        let mut out = vec![self.notes()?];
+        let patterns = self
+            .config
+            .resolved
+            .strings("notes", None, crate::config::tree::Notes::DISPLAY_REF.name)
+            .unwrap_or_default();
+        if patterns.is_empty() {
+            return Ok(out);
+        }
+
+        let mut names = Vec::new();
+        for pattern in patterns {
+            let pattern: BString = if pattern.starts_with(b"refs/") {
+                pattern.into_owned()
+            } else {
+                let mut prefixed = BString::from("refs/");
+                prefixed.push_str(pattern.as_ref());
+                prefixed
+            };
+            if pattern.iter().any(|b| matches!(b, b'*' | b'?' | b'[')) {
+                let mut matches = Vec::new();
+                for reference in self.references()?.prefixed("refs/")? {
+                    let reference = reference.map_err(crate::notes::to_display::Error::ReadReference)?;
+                    let name = reference.name().as_bstr();
+                    if gix_glob::wildmatch(pattern.as_ref(), name, gix_glob::wildmatch::Mode::empty()) {
+                        matches.push(name.to_owned());
+                    }
+                }
+                matches.sort();
+                names.extend(matches);
+            } else if self.try_find_reference(pattern.as_bstr())?.is_some() {
+                names.push(pattern);
+            }
+        }
+        for name in names {
+            let ref_name: gix_ref::FullName = name.try_into()?;
+            if out.iter().all(|notes| notes.ref_name != ref_name) {
+                out.push(Notes { repo: self, ref_name });
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Turn `name` into a full reference name below `refs/notes/`, unless it already is one.
+fn expand_notes_ref(name: &BStr) -> BString {
+    if name.starts_with(b"refs/notes/") {
+        name.to_owned()
+    } else if name.starts_with(b"notes/") {
+        let mut out = BString::from("refs/");
+        out.push_str(name);
+        out
+    } else {
+        let mut out = BString::from("refs/notes/");
+        out.push_str(name);
+        out
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::{HashMap, HashSet};
+
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::BString,
+    config::{cache::util::ApplyLeniency, tree::Rebase},
+    rebase::{engine::Engine, Editor, Error, Instruction, Options, Outcome, State, UpdateRef},
+    Repository,
+};
+
+/// Rebase-utilities
+impl Repository {
+    /// Return the options for [`rebase()`](Self::rebase()) as configured with `rebase.autoSquash` and `rebase.updateRefs`.
+    pub fn rebase_options(&self) -> Result<Options, crate::config::boolean::Error> {
+        let config = &self.config.resolved;
+        let lenient = self.config.lenient_config;
+        let autosquash = config
+            .boolean_by_key("rebase.autoSquash")
+            .map(|value| Rebase::AUTO_SQUASH.enrich_error(value))
+            .transpose()
+            .with_leniency(lenient)?
+            .unwrap_or_default();
+        let update_refs = config
+            .boolean_by_key("rebase.updateRefs")
+            .map(|value| Rebase::UPDATE_REFS.enrich_error(value))
+            .transpose()
+            .with_leniency(lenient)?
+            .unwrap_or_default();
+        Ok(Options {
+            interactive: false,
+            autosquash,
+            update_refs,
+        })
+    }
+
+    /// Return the state of the rebase in progress, or `None` if there is none.
+    ///
+    /// Note that only rebases that keep their state in `.git/rebase-merge` are detected, which is what `git rebase` uses by default.
+    pub fn rebase_state(&self) -> Result<Option<State>, gix_rebase::read::Error> {
+        State::from_dir(&self.git_dir().join(gix_rebase::DIR_NAME))
+    }
+
+    /// Apply all commits reachable from `HEAD` that aren't reachable from `upstream` on top of `onto`, or on top of
+    /// `upstream` if `onto` is `None`, similar to `git rebase [--onto <onto>] <upstream>`.
+    ///
+    /// Merge commits are left out, and `editor` is used to let the user change the instructions if
+    /// [`Options::interactive`] is set, and to change messages where needed.
+    ///
+    /// If the rebase [stops](Outcome::Stopped), it can be resumed with [`rebase_continue()`](Self::rebase_continue())
+    /// or `git rebase --continue`.
+    pub fn rebase(
+        &self,
+        upstream: impl Into<ObjectId>,
+        onto: Option<ObjectId>,
+        options: Options,
+        editor: &mut dyn Editor,
+    ) -> Result<Outcome, Error> {
+        let upstream = upstream.into();
+        let onto = onto.unwrap_or(upstream);
+        if self.work_dir().is_none() {
+            return Err(Error::BareRepository);
+        }
+        if self.rebase_state()?.is_some() || self.git_dir().join("rebase-apply").is_dir() {
+            return Err(Error::InProgress);
+        }
+        let head = self.head_commit()?;
+        let index = self.open_index()?;
+        if self.is_dirty()? || !crate::rebase::checkout::index_matches_tree(self, &index, &head.tree_id()?)? {
+            return Err(Error::DirtyWorktree);
+        }
+        let head_name = self.head_name()?.map(|name| BString::from(name.as_bstr()));
+
+        let mut todo = Vec::new();
+        for id in self.commits_to_rebase(head.id, upstream)? {
+            let commit = self.find_object(id)?.try_into_commit()?;
+            todo.push(Instruction::Pick {
+                commit: id.to_hex().to_string().into(),
+                summary: commit.message()?.summary().into_owned(),
+            });
+        }
+        if options.update_refs {
+            let mut branches = HashMap::<BString, Vec<BString>>::new();
+            for reference in self.references()?.local_branches()?.filter_map(Result::ok) {
+                let mut reference = reference;
+                let name = BString::from(reference.name().as_bstr());
+                if Some(&name) == head_name.as_ref() {
+                    continue;
+                }
+                if let Ok(id) = reference.peel_to_id_in_place() {
+                    branches.entry(id.to_hex().to_string().into()).or_default().push(name);
+                }
+            }
+            gix_sequencer::insert_update_refs(&mut todo, |commit| branches.get(commit).cloned().unwrap_or_default());
+        }
+        if options.autosquash {
+            gix_sequencer::autosquash(&mut todo);
+        }
+        if options.interactive {
+            editor.edit_instructions(&mut todo).map_err(Error::Editor)?;
+            if todo.is_empty() {
+                return Err(Error::NothingToDo);
+            }
+        }
+
+        let mut update_refs = Vec::new();
+        for instruction in &todo {
+            if let Instruction::UpdateRef { name } = instruction {
+                if let Some(mut reference) = self.try_find_reference(name.as_ref() as &crate::bstr::BStr)? {
+                    update_refs.push(UpdateRef {
+                        name: name.clone(),
+                        before: reference.peel_to_id_in_place()?.detach(),
+                        after: None,
+                    });
+                }
+            }
+        }
+        let state = State {
+            head_name,
+            onto,
+            orig_head: head.id,
+            interactive: true,
+            todo,
+            done: Vec::new(),
+            update_refs,
+            current_fixups: Vec::new(),
+            stopped: None,
+        };
+        Engine::new(self, state, editor)?.start()
+    }
+
+    /// Commit the changes that were staged to resolve conflicts or to amend a commit after the rebase in progress stopped,
+    /// and continue it, similar to `git rebase --continue`.
+    pub fn rebase_continue(&self, editor: &mut dyn Editor) -> Result<Outcome, Error> {
+        let state = self.rebase_state()?.ok_or(Error::NotInProgress)?;
+        Engine::new(self, state, editor)?.resume()
+    }
+
+    /// Discard all changes to the worktree and the index, and continue the rebase in progress without the commit it stopped at,
+    /// similar to `git rebase --skip`.
+    pub fn rebase_skip(&self, editor: &mut dyn Editor) -> Result<Outcome, Error> {
+        let state = self.rebase_state()?.ok_or(Error::NotInProgress)?;
+        Engine::new(self, state, editor)?.skip()
+    }
+
+    /// Stop the rebase in progress and return to the commit and branch it started from, discarding all changes to
+    /// the worktree and the index, similar to `git rebase --abort`.
+    pub fn rebase_abort(&self) -> Result<(), Error> {
+        let state = self.rebase_state()?.ok_or(Error::NotInProgress)?;
+        Engine::new(self, state, &mut crate::rebase::NoEdit)?.abort()
+    }
+
+    /// Return all non-merge commits reachable from `head` but not from `upstream`, with parents before their children.
+    fn commits_to_rebase(&self, head: ObjectId, upstream: ObjectId) -> Result<Vec<ObjectId>, Error> {
+        let hidden = self
+            .rev_walk([upstream])
+            .all()?
+            .map(|info| info.map(|info| info.id))
+            .collect::<Result<HashSet<_>, _>>()?;
+        let mut parents_by_id = HashMap::new();
+        let mut order = Vec::new();
+        for info in self.rev_walk([head]).selected(move |id| !hidden.contains(id))? {
+            let info = info?;
+            order.push(info.id);
+            parents_by_id.insert(info.id, info.parent_ids.into_iter().collect::<Vec<_>>());
+        }
+
+        let mut out = Vec::new();
+        let mut seen = HashSet::new();
+        for tip in order.into_iter().rev() {
+            let mut stack = vec![(tip, false)];
+            while let Some((id, parents_done)) = stack.pop() {
+                if parents_done {
+                    if parents_by_id[&id].len() < 2 {
+                        out.push(id);
+                    }
+                    continue;
+                }
+                if !seen.insert(id) {
+                    continue;
+                }
+                stack.push((id, true));
+                stack.extend(
+                    parents_by_id[&id]
+                        .iter()
+                        .filter(|parent| parents_by_id.contains_key(*parent) && !seen.contains(*parent))
+                        .map(|parent| (*parent, false)),
+                );
+            }
+        }
+        Ok(out)
+    }
+}
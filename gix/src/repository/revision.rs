@@ -45,4 +45,45 @@ impl crate::Repository {
     ) -> revision::walk::Platform<'_> {
         revision::walk::Platform::new(tips, self)
     }
+
+    /// Obtain the best merge-base between commit `one` and `two`, or fail if there is none.
+    ///
+    /// Use [`merge_bases_many()`](Self::merge_bases_many()) to obtain all merge-bases, which may be more than one
+    /// in case of criss-cross merges.
+    ///
+    /// # Performance
+    ///
+    /// For repeated calls, a [commit-graph](Self::commit_graph()) should be available to speed up the traversal.
+    #[doc(alias = "git2")]
+    pub fn merge_base(
+        &self,
+        one: impl Into<gix_hash::ObjectId>,
+        two: impl Into<gix_hash::ObjectId>,
+    ) -> Result<Id<'_>, super::merge_base::Error> {
+        let one = one.into();
+        let two = two.into();
+        let mut graph = self.revision_graph();
+        let bases = gix_revision::merge_base(one, &[two], &mut graph)?
+            .ok_or(super::merge_base::Error::NotFound { one, two })?;
+        Ok(Id::from_id(bases[0], self))
+    }
+
+    /// Obtain all merge-bases between commit `one` and `others`, sorted from best to worst, which are all commits that
+    /// are reachable from `one` and all of `others`, without being reachable from one another.
+    /// The list is empty if they don't share any history.
+    ///
+    /// Note that `others` are treated as if they were merged into a single commit, just like `git merge-base --all` does.
+    #[doc(alias = "git2")]
+    pub fn merge_bases_many(
+        &self,
+        one: impl Into<gix_hash::ObjectId>,
+        others: &[gix_hash::ObjectId],
+    ) -> Result<Vec<Id<'_>>, gix_revision::merge_base::Error> {
+        let mut graph = self.revision_graph();
+        Ok(gix_revision::merge_base(one.into(), others, &mut graph)?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|id| Id::from_id(id, self))
+            .collect())
+    }
 }
@@ -0,0 +1,198 @@
+use crate::{sparse, Repository};
+
+/// Access
+impl Repository {
+    /// Return the path to the file with the sparse checkout patterns of this worktree, typically `.git/info/sparse-checkout`.
+    pub fn sparse_checkout_path(&self) -> std::path::PathBuf {
+        self.git_dir().join("info").join("sparse-checkout")
+    }
+
+    /// Return the patterns of the sparse checkout if `core.sparseCheckout` is enabled, or `None` if all entries of the index
+    /// are supposed to be checked out.
+    ///
+    /// The patterns are read from [`sparse_checkout_path()`](Self::sparse_checkout_path()), and interpreted in cone mode
+    /// if `core.sparseCheckoutCone` is enabled as well.
+    pub fn sparse_checkout_patterns(&self) -> Result<Option<sparse::Patterns>, sparse::patterns::Error> {
+        let cone = match self.config.sparse_checkout()? {
+            Some(cone) => cone,
+            None => return Ok(None),
+        };
+        let path = self.sparse_checkout_path();
+        sparse::Patterns::from_path(&path, cone)
+            .map(Some)
+            .map_err(|source| sparse::patterns::Error::Io { path, source })
+    }
+}
+
+#[cfg(feature = "worktree-mutation")]
+mod set {
+    use std::sync::atomic::AtomicBool;
+
+    use gix_lock::acquire::Fail;
+
+    use crate::{
+        bstr::{BStr, BString, ByteVec},
+        config::tree::Core,
+        sparse::{
+            self,
+            set::{Error, Outcome, ProgressId},
+        },
+        Progress, Repository,
+    };
+
+    /// Worktree mutation
+    impl Repository {
+        /// Enable the sparse checkout and only keep the entries included by `patterns` in the worktree, similar to
+        /// `git sparse-checkout set`. Return information about how the worktree changed.
+        ///
+        /// In cone mode, if `cone` is `true`, `patterns` are directories that are included recursively along with the files
+        /// in their parent directories and in the root of the worktree. Otherwise, they are patterns like in `.gitignore` files
+        /// that include what they match, unless they are negated.
+        ///
+        /// The patterns are written to [`sparse_checkout_path()`](Self::sparse_checkout_path()), and `core.sparseCheckout`
+        /// as well as `core.sparseCheckoutCone` are set in the repository-local configuration. Files that aren't included
+        /// anymore are removed from the worktree unless they are modified, and newly included ones are checked out.
+        /// If `index.sparse` is enabled in cone mode, excluded directories are stored as single entries in the index.
+        pub fn sparse_checkout_set(
+            &mut self,
+            patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+            cone: bool,
+            progress: &mut dyn crate::DynNestedProgress,
+            should_interrupt: &AtomicBool,
+        ) -> Result<Outcome, Error> {
+            let content = if cone {
+                sparse::Cone::from_directories(patterns).to_bstring()
+            } else {
+                to_lines(BString::default(), patterns)
+            };
+            self.sparse_checkout_update(content, cone, progress, should_interrupt)
+        }
+
+        /// Add `patterns` to the patterns of the current sparse checkout and update the worktree accordingly, similar to
+        /// `git sparse-checkout add`. They are interpreted like in [`sparse_checkout_set()`](Self::sparse_checkout_set()),
+        /// depending on whether or not the current sparse checkout is in cone mode.
+        ///
+        /// It's an error if the sparse checkout isn't enabled.
+        pub fn sparse_checkout_add(
+            &mut self,
+            patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+            progress: &mut dyn crate::DynNestedProgress,
+            should_interrupt: &AtomicBool,
+        ) -> Result<Outcome, Error> {
+            match self.sparse_checkout_patterns()?.ok_or(Error::NotEnabled)? {
+                sparse::Patterns::Cone(mut cone) => {
+                    cone.extend(patterns);
+                    self.sparse_checkout_update(cone.to_bstring(), true, progress, should_interrupt)
+                }
+                sparse::Patterns::NonCone(_) => {
+                    let path = self.sparse_checkout_path();
+                    let mut content: BString = match std::fs::read(&path) {
+                        Ok(content) => content.into(),
+                        Err(err) if err.kind() == std::io::ErrorKind::NotFound => BString::default(),
+                        Err(source) => return Err(sparse::patterns::Error::Io { path, source }.into()),
+                    };
+                    if !content.is_empty() && !content.ends_with(b"\n") {
+                        content.push_byte(b'\n');
+                    }
+                    let content = to_lines(content, patterns);
+                    self.sparse_checkout_update(content, false, progress, should_interrupt)
+                }
+            }
+        }
+
+        fn sparse_checkout_update(
+            &mut self,
+            content: BString,
+            cone: bool,
+            progress: &mut dyn crate::DynNestedProgress,
+            should_interrupt: &AtomicBool,
+        ) -> Result<Outcome, Error> {
+            let _span = gix_trace::coarse!("gix::Repository::sparse_checkout_update()");
+            let workdir = self.work_dir().ok_or(Error::BareRepository)?.to_owned();
+            let patterns = sparse::Patterns::from_bytes(&content, cone);
+
+            let mut index_lock =
+                gix_lock::File::acquire_to_update_resource(self.index_path(), Fail::Immediately, None)?;
+            let mut index = if self.index_path().is_file() {
+                self.open_index()?
+            } else {
+                gix_index::File::from_state(gix_index::State::new(self.object_hash()), self.index_path())
+            };
+            let config_path = self.common_dir().join("config");
+            let mut config_lock = gix_lock::File::acquire_to_update_resource(&config_path, Fail::Immediately, None)?;
+
+            let checkout_options = self
+                .config
+                .checkout_options(self, gix_worktree::stack::state::attributes::Source::IdMapping)?;
+            let mut files = progress.add_child_with_id("checkout".to_string(), ProgressId::CheckoutFiles.into());
+            let mut bytes = progress.add_child_with_id("writing".to_string(), ProgressId::BytesWritten.into());
+            files.init(None, crate::progress::count("files"));
+            bytes.init(None, crate::progress::bytes());
+
+            let start = std::time::Instant::now();
+            let worktree = gix_worktree_state::sparse::update(
+                &mut index,
+                &patterns,
+                &workdir,
+                self.objects.clone().into_arc().map_err(Error::OpenArcOdb)?,
+                &files,
+                &bytes,
+                should_interrupt,
+                checkout_options,
+            )?;
+            files.show_throughput(start);
+            bytes.show_throughput(start);
+
+            let sparse_directories = if patterns.is_cone() && self.config.sparse_index()? {
+                index.collapse_sparse_directories(
+                    |dir| patterns.is_excluded_dir(dir),
+                    |tree| self.write_object(tree).map(crate::Id::detach),
+                )?
+            } else {
+                0
+            };
+
+            let path = self.sparse_checkout_path();
+            path.parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&path, &content))
+                .map_err(|source| Error::WritePatterns { path, source })?;
+
+            let mut config = gix_config::File::from_path_no_includes(config_path, gix_config::Source::Local)?;
+            let cone_value = if cone { "true" } else { "false" };
+            config
+                .set_raw_value("core", None, "sparseCheckout", "true")
+                .map_err(crate::config::set_value::Error::from)?;
+            config
+                .set_raw_value("core", None, "sparseCheckoutCone", cone_value)
+                .map_err(crate::config::set_value::Error::from)?;
+            config.write_to(&mut config_lock).map_err(Error::Write)?;
+
+            {
+                let mut out = std::io::BufWriter::new(&mut index_lock);
+                index.write_to(&mut out, Default::default()).map_err(Error::Write)?;
+                std::io::Write::flush(&mut out).map_err(Error::Write)?;
+            }
+            index_lock.commit()?;
+            config_lock.commit()?;
+
+            let mut snapshot = self.config_snapshot_mut();
+            snapshot.set_value(&Core::SPARSE_CHECKOUT, "true")?;
+            snapshot.set_value(&Core::SPARSE_CHECKOUT_CONE, cone_value)?;
+            snapshot.commit()?;
+
+            Ok(Outcome {
+                worktree,
+                sparse_directories,
+            })
+        }
+    }
+
+    fn to_lines(mut out: BString, patterns: impl IntoIterator<Item = impl AsRef<BStr>>) -> BString {
+        for pattern in patterns {
+            out.push_str(pattern.as_ref());
+            out.push_byte(b'\n');
+        }
+        out
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::atomic::AtomicBool;
+
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    bstr::BString,
+    switch::{self, Error, Outcome, ProgressId},
+    Progress, Repository,
+};
+
+/// Worktree mutation
+impl Repository {
+    /// Make `HEAD` point to `target` and update the index and the worktree to match the tree of its commit, similar to
+    /// `git switch <branch>` or `git switch --detach <commit>`.
+    ///
+    /// Only files that differ between the current `HEAD` commit and the target commit are touched. Local modifications
+    /// of all other files are kept, while those of changed files, along with untracked files in the way of new ones,
+    /// prevent the operation unless [`Options::force`](switch::Options::force) is set.
+    ///
+    /// The index is locked for the entire duration of the operation, and `HEAD` is locked before the worktree is changed.
+    /// Both are only written once the worktree was updated successfully, with `HEAD` being updated right after the index.
+    /// `HEAD` must not have been changed by someone else in the meantime.
+    ///
+    /// A reflog entry for `HEAD` is written in both cases, with symbolic switches being logged in a separate edit as
+    /// symbolic reference updates aren't logged by themselves.
+    pub fn switch(
+        &self,
+        target: switch::Target,
+        progress: &mut dyn crate::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+        options: switch::Options,
+    ) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::switch()");
+        let workdir = self.work_dir().ok_or(Error::BareRepository)?;
+
+        let mut head = self.head()?;
+        let previous_name = head.referent_name().map(|name| name.shorten().to_owned());
+        let previous_target = match &head.kind {
+            crate::head::Kind::Symbolic(branch) => Target::Symbolic(branch.name.clone()),
+            crate::head::Kind::Unborn(name) => Target::Symbolic(name.clone()),
+            crate::head::Kind::Detached { target, .. } => Target::Peeled(*target),
+        };
+        let previous_head = head.try_peel_to_id_in_place()?.map(crate::Id::detach);
+        let current_tree = match previous_head {
+            Some(id) => self.find_object(id)?.peel_to_tree()?.id,
+            None => self.empty_tree().id,
+        };
+
+        let is_symbolic_switch = matches!(target, switch::Target::Branch(_));
+        let (new_head, commit_id, target_name) = match target {
+            switch::Target::Branch(name) => {
+                let mut branch = self.find_reference(name.as_ref()).map_err(|err| match err {
+                    crate::reference::find::existing::Error::NotFound => Error::BranchNotFound {
+                        name: name.as_bstr().to_owned(),
+                    },
+                    err => err.into(),
+                })?;
+                let commit = branch.peel_to_id_in_place()?;
+                let target_name = name.shorten().to_owned();
+                (Target::Symbolic(name), commit.detach(), target_name)
+            }
+            switch::Target::Detached(id) => {
+                let commit = self.find_object(id)?.peel_to_kind(gix_object::Kind::Commit)?.id;
+                (Target::Peeled(commit), commit, BString::from(commit.to_string()))
+            }
+        };
+        let target_tree = self.find_object(commit_id)?.peel_to_tree()?.id;
+
+        let mut index_lock =
+            gix_lock::File::acquire_to_update_resource(self.index_path(), gix_lock::acquire::Fail::Immediately, None)?;
+        let mut index = if self.index_path().is_file() {
+            self.open_index()?
+        } else {
+            gix_index::File::from_state(gix_index::State::new(self.object_hash()), self.index_path())
+        };
+
+        let (file_lock_fail, packed_refs_lock_fail) = self
+            .config
+            .lock_timeout()
+            .map_err(crate::reference::edit::Error::from)?;
+        let message = format!(
+            "checkout: moving from {} to {}",
+            previous_name.unwrap_or_else(|| previous_head.unwrap_or(commit_id).to_string().into()),
+            target_name
+        );
+        let head_edit = self
+            .refs
+            .transaction()
+            .prepare(
+                Some(RefEdit {
+                    change: Change::Update {
+                        log: LogChange {
+                            mode: RefLog::AndReference,
+                            force_create_reflog: false,
+                            message: message.clone().into(),
+                        },
+                        expected: PreviousValue::MustExistAndMatch(previous_target),
+                        new: new_head,
+                    },
+                    name: "HEAD".try_into().expect("valid"),
+                    deref: false,
+                }),
+                file_lock_fail,
+                packed_refs_lock_fail,
+            )
+            .map_err(crate::reference::edit::Error::from)?;
+
+        let mut checkout_options = self
+            .config
+            .checkout_options(self, gix_worktree::stack::state::attributes::Source::IdMapping)?;
+        checkout_options.overwrite_existing = options.force;
+
+        let mut files = progress.add_child_with_id("checkout".to_string(), ProgressId::CheckoutFiles.into());
+        let mut bytes = progress.add_child_with_id("writing".to_string(), ProgressId::BytesWritten.into());
+        files.init(None, crate::progress::count("files"));
+        bytes.init(None, crate::progress::bytes());
+
+        let start = std::time::Instant::now();
+        let worktree = gix_worktree_state::switch(
+            &mut index,
+            &current_tree,
+            &target_tree,
+            workdir,
+            self.objects.clone().into_arc().map_err(Error::OpenArcOdb)?,
+            &files,
+            &bytes,
+            should_interrupt,
+            checkout_options,
+        )?;
+        files.show_throughput(start);
+        bytes.show_throughput(start);
+
+        {
+            let mut out = std::io::BufWriter::new(&mut index_lock);
+            index
+                .write_to(&mut out, Default::default())
+                .map_err(Error::WriteIndex)?;
+            std::io::Write::flush(&mut out).map_err(Error::WriteIndex)?;
+        }
+        index_lock.commit()?;
+        head_edit
+            .commit(
+                self.committer()
+                    .transpose()
+                    .map_err(crate::reference::edit::Error::from)?,
+            )
+            .map_err(crate::reference::edit::Error::from)?;
+        if is_symbolic_switch {
+            self.edit_reference(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::Only,
+                        force_create_reflog: false,
+                        message: message.into(),
+                    },
+                    expected: PreviousValue::Any,
+                    new: Target::Peeled(commit_id),
+                },
+                name: "HEAD".try_into().expect("valid"),
+                deref: false,
+            })?;
+        }
+
+        Ok(Outcome {
+            previous_head,
+            head: commit_id,
+            worktree,
+        })
+    }
+}
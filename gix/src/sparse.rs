@@ -0,0 +1,96 @@
+//! Sparse checkouts, which only populate the worktree with the entries that are included by the patterns
+//! in `.git/info/sparse-checkout`, similar to `git sparse-checkout`.
+pub use gix_worktree::sparse::{Cone, Patterns};
+
+///
+#[allow(clippy::empty_docs)]
+pub mod patterns {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Repository::sparse_checkout_patterns()`](crate::Repository::sparse_checkout_patterns()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ConfigBoolean(#[from] crate::config::boolean::Error),
+        #[error("Could not read the sparse checkout patterns at '{}'", path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+#[cfg(feature = "worktree-mutation")]
+pub mod set {
+    /// The outcome of [`Repository::sparse_checkout_set()`](crate::Repository::sparse_checkout_set())
+    /// and [`Repository::sparse_checkout_add()`](crate::Repository::sparse_checkout_add()).
+    #[derive(Debug)]
+    pub struct Outcome {
+        /// Information about how the worktree was changed.
+        pub worktree: gix_worktree_state::sparse::Outcome,
+        /// The amount of directories that are stored as single entry in the index now, if `index.sparse` is enabled.
+        pub sparse_directories: usize,
+    }
+
+    /// The progress ids used in [`Repository::sparse_checkout_set()`](crate::Repository::sparse_checkout_set()).
+    ///
+    /// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+    #[derive(Debug, Copy, Clone)]
+    pub enum ProgressId {
+        /// The amount of files checked out thus far.
+        CheckoutFiles,
+        /// The amount of bytes written in total, the aggregate of the size of the content of all files thus far.
+        BytesWritten,
+    }
+
+    impl From<ProgressId> for gix_features::progress::Id {
+        fn from(v: ProgressId) -> Self {
+            match v {
+                ProgressId::CheckoutFiles => *b"SPCF",
+                ProgressId::BytesWritten => *b"SPBW",
+            }
+        }
+    }
+
+    /// The error returned by [`Repository::sparse_checkout_set()`](crate::Repository::sparse_checkout_set())
+    /// and [`Repository::sparse_checkout_add()`](crate::Repository::sparse_checkout_add()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("A worktree is required for a sparse checkout")]
+        BareRepository,
+        #[error("There is no sparse checkout to add patterns to")]
+        NotEnabled,
+        #[error(transparent)]
+        Patterns(#[from] super::patterns::Error),
+        #[error(transparent)]
+        ConfigBoolean(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error("Could not lock the index or the configuration file for writing")]
+        Lock(#[from] gix_lock::acquire::Error),
+        #[error(transparent)]
+        CheckoutOptions(#[from] crate::config::checkout_options::Error),
+        #[error(transparent)]
+        OpenArcOdb(std::io::Error),
+        #[error(transparent)]
+        Update(#[from] gix_worktree_state::sparse::Error),
+        #[error(transparent)]
+        WriteTree(#[from] crate::object::write::Error),
+        #[error("Could not write the sparse checkout patterns at '{}'", path.display())]
+        WritePatterns {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error("Could not read the repository configuration file to enable the sparse checkout")]
+        ReadConfig(#[from] gix_config::file::init::from_paths::Error),
+        #[error(transparent)]
+        SetConfig(#[from] crate::config::set_value::Error),
+        #[error("Could not apply the configuration changes")]
+        ApplyConfig(#[from] crate::config::Error),
+        #[error("Could not write the repository configuration file or the index")]
+        Write(#[source] std::io::Error),
+        #[error("Could not commit the changes to the repository configuration file or the index")]
+        Commit(#[from] gix_lock::commit::Error<gix_lock::File>),
+    }
+}
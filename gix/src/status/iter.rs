@@ -0,0 +1,115 @@
+use crate::bstr::{BStr, BString};
+use crate::status::{index_worktree, Platform};
+
+/// The item produced by the [status iterator](super::Iter).
+#[derive(Clone, PartialEq, Debug)]
+pub enum Item {
+    /// A change between the `HEAD` tree and the index, i.e. a change that is staged for commit.
+    TreeIndex(gix_status::tree_index::Change),
+    /// A change between the index and the worktree, i.e. a change that isn't staged yet, or an untracked file.
+    IndexWorktree(index_worktree::iter::Item),
+}
+
+impl Item {
+    /// The repository-relative path of the item, which for rewrites is the location of the destination.
+    pub fn location(&self) -> &BStr {
+        match self {
+            Item::TreeIndex(change) => change.location(),
+            Item::IndexWorktree(item) => match item {
+                index_worktree::iter::Item::Modification { rela_path, .. } => rela_path.as_ref(),
+                index_worktree::iter::Item::DirectoryContents { entry, .. } => entry.rela_path.as_ref(),
+                index_worktree::iter::Item::Rewrite { dirwalk_entry, .. } => dirwalk_entry.rela_path.as_ref(),
+            },
+        }
+    }
+}
+
+/// The error returned by [Platform::into_iter()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Index(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    HeadTreeId(#[from] crate::reference::head_tree_id::Error),
+    #[error(transparent)]
+    TreeIndex(#[from] crate::status::tree_index::Error),
+    #[error(transparent)]
+    IndexWorktreeIter(#[from] index_worktree::iter::Error),
+}
+
+/// Lifecycle
+impl<'repo, Progress> Platform<'repo, Progress>
+where
+    Progress: gix_features::progress::Progress,
+{
+    /// Turn the platform into an iterator for all changes between the `HEAD` tree and the index, followed by all changes
+    /// between the index and the working tree, similar to what `git status` shows.
+    ///
+    /// * `patterns`
+    ///     - Optional patterns to use to limit the paths to look at. If empty, all paths are considered.
+    ///
+    /// Note that the changes between the `HEAD` tree and the index are computed right away, before the index-worktree
+    /// comparison starts. If `HEAD` is unborn, all index entries are considered added.
+    pub fn into_iter(mut self, patterns: Vec<BString>) -> Result<super::Iter, Error> {
+        let index = match self.index.take() {
+            None => crate::worktree::IndexPersistedOrInMemory::Persisted(self.repo.index_or_empty()?),
+            Some(index) => index,
+        };
+        let tree_id = if self.repo.head()?.is_unborn() {
+            gix_hash::ObjectId::empty_tree(self.repo.object_hash())
+        } else {
+            self.repo.head_tree_id()?.detach()
+        };
+        let mut changes = gix_status::tree_index::Recorder::default();
+        let should_interrupt = self.should_interrupt.clone().unwrap_or_default();
+        let tree_index_outcome = self.repo.tree_index_status(
+            &tree_id,
+            &index,
+            &patterns,
+            &mut changes,
+            self.tree_index_rewrites,
+            &should_interrupt,
+        )?;
+
+        self.index = Some(index);
+        self.should_interrupt = Some(should_interrupt);
+        Ok(super::Iter {
+            tree_index: changes.records.into_iter(),
+            tree_index_outcome,
+            index_worktree: self.into_index_worktree_iter(patterns)?,
+        })
+    }
+}
+
+impl Iterator for super::Iter {
+    type Item = Result<Item, index_worktree::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(change) = self.tree_index.next() {
+            return Some(Ok(Item::TreeIndex(change)));
+        }
+        self.index_worktree.next().map(|res| res.map(Item::IndexWorktree))
+    }
+}
+
+/// Access
+impl super::Iter {
+    /// Return the outcome of the comparison between the `HEAD` tree and the index.
+    pub fn tree_index_outcome(&self) -> &gix_status::tree_index::Outcome {
+        &self.tree_index_outcome
+    }
+
+    /// Return the outcome of the index-worktree comparison, or `None` if the iterator isn't fully consumed.
+    pub fn index_worktree_outcome_mut(&mut self) -> Option<&mut index_worktree::iter::Outcome> {
+        self.index_worktree.outcome_mut()
+    }
+
+    /// Turn the iterator into the outcome of the index-worktree comparison, which is `None` on error or if the iteration
+    /// isn't complete.
+    pub fn into_index_worktree_outcome(self) -> Option<index_worktree::iter::Outcome> {
+        self.index_worktree.into_outcome()
+    }
+}
@@ -13,6 +13,7 @@ where
     index: Option<crate::worktree::IndexPersistedOrInMemory>,
     submodules: Submodule,
     index_worktree_options: index_worktree::Options,
+    tree_index_rewrites: Option<gix_diff::Rewrites>,
     should_interrupt: Option<OwnedOrStaticAtomicBool>,
 }
 
@@ -69,6 +70,8 @@ pub enum Error {
     DirwalkOptions(#[from] config::boolean::Error),
     #[error(transparent)]
     ConfigureUntrackedFiles(#[from] config::key::GenericErrorWithValue),
+    #[error(transparent)]
+    TreeIndexRewrites(#[from] crate::diff::new_rewrites::Error),
 }
 
 /// Status
@@ -86,6 +89,9 @@ impl Repository {
     /// collapsed by default. If that needs to be controlled,
     /// [configure the directory walk explicitly](Platform::dirwalk_options) or more [implicitly](Platform::untracked_files).
     ///
+    /// Renames between the `HEAD` tree and the index are tracked as configured for diffs,
+    /// which can be [changed as well](Platform::tree_index_rewrites).
+    ///
     /// Pass `progress` to receive progress information on file modifications on this repository.
     /// Use [`progress::Discard`](crate::progress::Discard) to discard all progress information.
     ///
@@ -110,6 +116,7 @@ impl Repository {
                 rewrites: None,
                 thread_limit: None,
             },
+            tree_index_rewrites: self.config.diff_renames()?.unwrap_or_default().into(),
         };
 
         let untracked = self
@@ -139,23 +146,18 @@ pub mod is_dirty {
         #[error(transparent)]
         StatusPlatform(#[from] crate::status::Error),
         #[error(transparent)]
-        CreateStatusIterator(#[from] crate::status::index_worktree::iter::Error),
+        CreateStatusIterator(#[from] crate::status::iter::Error),
     }
 
     impl Repository {
         /// Returns `true` if the repository is dirty.
         /// This means it's changed in one of the following ways:
         ///
-        /// * the index was changed in comparison to its working tree
+        /// * the index was changed in comparison to the tree of `HEAD`
         /// * the working tree was changed in comparison to the index
         /// * submodules are taken in consideration, along with their `ignore` and `isActive` configuration
         ///
         /// Note that *untracked files* do *not* affect this flag.
-        ///
-        /// ### Incomplete Implementation Warning
-        ///
-        /// Currently, this does not compute changes between the head and the index.
-        // TODO: use iterator which also tests for head->index changes.
         pub fn is_dirty(&self) -> Result<bool, Error> {
             let is_dirty = self
                 .status(gix_features::progress::Discard)?
@@ -164,7 +166,8 @@ pub mod is_dirty {
                 .index_worktree_options_mut(|opts| {
                     opts.dirwalk_options = None;
                 })
-                .into_index_worktree_iter(Vec::new())?
+                .tree_index_rewrites(None)
+                .into_iter(Vec::new())?
                 .take_while(Result::is_ok)
                 .next()
                 .is_some();
@@ -178,3 +181,20 @@ mod platform;
 ///
 #[allow(clippy::empty_docs)]
 pub mod index_worktree;
+
+///
+#[allow(clippy::empty_docs)]
+pub mod tree_index;
+
+/// An iterator for all changes between the `HEAD` tree and the index, followed by all changes between the index and the worktree.
+///
+/// It's created by [Platform::into_iter()], and its item is [iter::Item].
+pub struct Iter {
+    tree_index: std::vec::IntoIter<gix_status::tree_index::Change>,
+    tree_index_outcome: gix_status::tree_index::Outcome,
+    index_worktree: index_worktree::Iter,
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod iter;
@@ -100,6 +100,14 @@ where
         self
     }
 
+    /// Configure the tree-index rename tracking with `rewrites`, which defaults to what's configured for diffs.
+    ///
+    /// If `None`, renames and copies between the `HEAD` tree and the index will show up as deletions and additions.
+    pub fn tree_index_rewrites(mut self, rewrites: impl Into<Option<gix_diff::Rewrites>>) -> Self {
+        self.tree_index_rewrites = rewrites.into();
+        self
+    }
+
     /// Adjust all options related to the index-worktree status.
     /// This is a catch-all in case there are no more specific methods that could be used instead to change
     /// the respective option.
@@ -0,0 +1,80 @@
+use crate::bstr::BStr;
+use crate::Repository;
+use std::sync::atomic::AtomicBool;
+
+/// The error returned by [Repository::tree_index_status()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    AttributeStack(#[from] crate::config::attribute_stack::Error),
+    #[error(transparent)]
+    Pathspec(#[from] crate::pathspec::init::Error),
+    #[error(transparent)]
+    ResourceCache(#[from] crate::diff::resource_cache::Error),
+    #[error(transparent)]
+    TreeIndex(#[from] gix_status::tree_index::Error),
+}
+
+impl Repository {
+    /// Obtain the changes between the tree with `tree_id` and the `index`, which are the changes that would be committed,
+    /// similar to `git diff --cached <tree>`.
+    ///
+    /// * `tree_id`
+    ///     - The tree to compare the index to, typically the [tree of `HEAD`](Repository::head_tree_id()).
+    ///       The empty tree can be used for unborn branches.
+    /// * `index`
+    ///     - The index to compare to the tree.
+    /// * `patterns`
+    ///     - Optional patterns to use to limit the paths to look at. If empty, all paths are considered.
+    /// * `delegate`
+    ///     - The sink for receiving all changes.
+    /// * `rewrites`
+    ///     - If `Some(_)`, renames and possibly copies will be tracked, which also causes all changes to be sorted by
+    ///       their location. [`crate::diff::new_rewrites()`] can be used to obtain the configured value.
+    /// * `should_interrupt`
+    ///     - A flag to stop the whole operation.
+    ///
+    /// ### Note
+    ///
+    /// This is a lower-level method, prefer the [`status`](Repository::status()) method for greater ease of use.
+    pub fn tree_index_status(
+        &self,
+        tree_id: &gix_hash::oid,
+        index: &gix_index::State,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        delegate: &mut impl gix_status::tree_index::VisitEntry,
+        rewrites: Option<gix_diff::Rewrites>,
+        should_interrupt: &AtomicBool,
+    ) -> Result<gix_status::tree_index::Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::tree_index_status");
+        let attrs = self.attributes_only(index, crate::worktree::stack::state::attributes::Source::IdMapping)?;
+        let pathspec = crate::Pathspec::new(
+            self,
+            false, /* empty patterns match prefix */
+            patterns,
+            true, /* inherit ignore case */
+            || Ok(attrs.clone()),
+        )?;
+        let resource_cache = crate::diff::resource_cache(
+            self,
+            gix_diff::blob::pipeline::Mode::ToGit,
+            attrs.inner,
+            Default::default(),
+        )?;
+
+        let out = gix_status::tree_index(
+            tree_id,
+            index,
+            delegate,
+            &self.objects,
+            gix_status::tree_index::Context {
+                pathspec: pathspec.search,
+                resource_cache,
+                should_interrupt,
+            },
+            gix_status::tree_index::Options { rewrites },
+        )?;
+        Ok(out)
+    }
+}
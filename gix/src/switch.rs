@@ -0,0 +1,88 @@
+//! Switch `HEAD` to another branch or commit and update the index and the worktree accordingly,
+//! similar to `git switch` and `git checkout <revision>`.
+use crate::bstr::BString;
+
+/// What [`Repository::switch()`](crate::Repository::switch()) should make `HEAD` point to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// Make `HEAD` a symbolic reference to the given branch, which must exist, and check out the commit it points to.
+    Branch(gix_ref::FullName),
+    /// Detach `HEAD` at the commit that the given object peels to.
+    Detached(gix_hash::ObjectId),
+}
+
+/// Options for use in [`Repository::switch()`](crate::Repository::switch()).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, discard local modifications of files that differ between the current and the target commit,
+    /// and overwrite untracked files in the way, like `git switch --discard-changes`.
+    /// Otherwise, the operation fails with [`Error::Switch`] before anything is changed if that would be necessary.
+    pub force: bool,
+}
+
+/// The outcome of [`Repository::switch()`](crate::Repository::switch()).
+#[derive(Debug)]
+pub struct Outcome {
+    /// The commit `HEAD` pointed to before, or `None` if it was unborn.
+    pub previous_head: Option<gix_hash::ObjectId>,
+    /// The commit `HEAD` points to now.
+    pub head: gix_hash::ObjectId,
+    /// Information about how the worktree was changed.
+    pub worktree: gix_worktree_state::switch::Outcome,
+}
+
+/// The progress ids used in [`Repository::switch()`](crate::Repository::switch()).
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The amount of files checked out thus far.
+    CheckoutFiles,
+    /// The amount of bytes written in total, the aggregate of the size of the content of all files thus far.
+    BytesWritten,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::CheckoutFiles => *b"SWCF",
+            ProgressId::BytesWritten => *b"SWBW",
+        }
+    }
+}
+
+/// The error returned by [`Repository::switch()`](crate::Repository::switch()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("A worktree is required to switch to another commit")]
+    BareRepository,
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    PeelHead(#[from] crate::head::peel::Error),
+    #[error(transparent)]
+    PeelBranch(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error("Could not peel the target to a commit and its tree")]
+    PeelTarget(#[from] crate::object::peel::to_kind::Error),
+    #[error("The branch '{}' to switch to does not exist", name)]
+    BranchNotFound { name: BString },
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error("Could not lock the index for writing")]
+    LockIndex(#[from] gix_lock::acquire::Error),
+    #[error("Could not write the new index")]
+    WriteIndex(#[source] std::io::Error),
+    #[error("Could not commit the new index")]
+    CommitIndex(#[from] gix_lock::commit::Error<gix_lock::File>),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    OpenArcOdb(std::io::Error),
+    #[error(transparent)]
+    Switch(#[from] gix_worktree_state::switch::Error),
+    #[error(transparent)]
+    EditHead(#[from] crate::reference::edit::Error),
+}
@@ -258,6 +258,14 @@ pub struct Submodule<'repo> {
     pub(crate) name: crate::bstr::BString,
 }
 
+/// A platform to read, write and merge the notes stored in a notes reference, like `refs/notes/commits`.
+#[derive(Clone)]
+#[cfg(feature = "notes")]
+pub struct Notes<'repo> {
+    pub(crate) repo: &'repo Repository,
+    pub(crate) ref_name: gix_ref::FullName,
+}
+
 /// A utility to access `.gitattributes` and `.gitignore` information efficiently.
 #[cfg(any(feature = "attributes", feature = "excludes"))]
 pub struct AttributeStack<'repo> {
@@ -176,6 +176,92 @@ mod status {
     }
 }
 
+#[cfg(feature = "gc")]
+mod gc {
+    use std::time::{Duration, SystemTime};
+
+    use crate::config::tree::bcow;
+    use gix::config::tree::{Gc, Key};
+
+    #[test]
+    fn expiry() -> crate::Result {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100 * 24 * 60 * 60);
+        let days_ago = |days: u64| Some(now - Duration::from_secs(days * 24 * 60 * 60));
+        for (actual, expected) in [
+            ("never", None),
+            ("false", None),
+            ("now", Some(now)),
+            ("all", Some(now)),
+            ("2.weeks.ago", days_ago(14)),
+            ("90 days", days_ago(90)),
+            ("30.days", days_ago(30)),
+            (
+                "1970-01-02",
+                Some(SystemTime::UNIX_EPOCH + Duration::from_secs(24 * 60 * 60)),
+            ),
+        ] {
+            assert_eq!(
+                Gc::PRUNE_EXPIRE.try_into_expiry(bcow(actual), now)?,
+                expected,
+                "{actual}"
+            );
+            assert!(Gc::REFLOG_EXPIRE.validate(actual.into()).is_ok());
+        }
+
+        assert_eq!(
+            Gc::PRUNE_EXPIRE
+                .try_into_expiry(bcow("soon"), now)
+                .unwrap_err()
+                .to_string(),
+            "The key \"gc.pruneExpire=soon\" was invalid"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pack_refs() -> crate::Result {
+        for (actual, is_bare, expected) in [
+            ("true", true, true),
+            ("false", false, false),
+            ("notbare", false, true),
+            ("notbare", true, false),
+        ] {
+            assert_eq!(Gc::PACK_REFS.try_into_pack_refs(bcow(actual), is_bare)?, expected);
+        }
+        assert!(Gc::PACK_REFS.validate("sometimes".into()).is_err());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "merge")]
+mod merge {
+    use crate::config::tree::bcow;
+    use gix::config::tree::{Key, Merge};
+    use gix::merge::blob::builtin_driver::text::ConflictStyle;
+
+    #[test]
+    fn conflict_style() -> crate::Result {
+        for (actual, expected) in [
+            ("merge", ConflictStyle::Merge),
+            ("diff3", ConflictStyle::Diff3),
+            ("zdiff3", ConflictStyle::ZealousDiff3),
+        ] {
+            assert_eq!(Merge::CONFLICT_STYLE.try_into_conflict_style(bcow(actual))?, expected);
+            assert!(Merge::CONFLICT_STYLE.validate(actual.into()).is_ok());
+        }
+
+        assert_eq!(
+            Merge::CONFLICT_STYLE
+                .try_into_conflict_style(bcow("DIFF3"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"merge.conflictStyle=DIFF3\" was invalid",
+            "case-sensitive comparisons"
+        );
+        Ok(())
+    }
+}
+
 mod push {
     use crate::config::tree::bcow;
     use gix::config::tree::Push;
@@ -667,6 +753,10 @@ mod extensions {
             gix_hash::Kind::Sha1,
             "case-insensitive"
         );
+        assert_eq!(
+            Extensions::OBJECT_FORMAT.try_into_object_format(bcow("sha256"))?,
+            gix_hash::Kind::Sha256
+        );
         assert_eq!(
             Extensions::OBJECT_FORMAT
                 .try_into_object_format(bcow("invalid"))
@@ -675,9 +765,26 @@ mod extensions {
             "The key \"extensions.objectFormat=invalid\" was invalid"
         );
         assert!(Extensions::OBJECT_FORMAT.validate("sha1".into()).is_ok());
+        assert!(Extensions::OBJECT_FORMAT.validate("sha256".into()).is_ok());
         assert!(Extensions::OBJECT_FORMAT.validate("invalid".into()).is_err());
         Ok(())
     }
+
+    #[test]
+    fn ref_storage() -> crate::Result {
+        assert!(!Extensions::REF_STORAGE.try_into_is_reftable(bcow("files"))?);
+        assert!(Extensions::REF_STORAGE.try_into_is_reftable(bcow("reftable"))?);
+        assert_eq!(
+            Extensions::REF_STORAGE
+                .try_into_is_reftable(bcow("invalid"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"extensions.refStorage=invalid\" was invalid"
+        );
+        assert!(Extensions::REF_STORAGE.validate("reftable".into()).is_ok());
+        assert!(Extensions::REF_STORAGE.validate("invalid".into()).is_err());
+        Ok(())
+    }
 }
 
 mod checkout {
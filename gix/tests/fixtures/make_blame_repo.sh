@@ -0,0 +1,26 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+printf '1\n2\n3\n' > file
+git add file && git commit -q -m first
+git tag first
+
+git mv file renamed
+printf '1\ntwo\n3\n4\n' > renamed
+git add renamed && git commit -q -m second
+git tag second
+
+printf '1\nTWO\n3\n4\n' > renamed
+git commit -q -am reformat
+git tag reformat
+
+{
+  echo "# revisions to ignore"
+  echo "$(git rev-parse reformat) # reformatting"
+  echo
+} > .git-blame-ignore-revs
+git rev-parse second > .git/more-revs-to-ignore
+git config blame.ignoreRevsFile .git-blame-ignore-revs
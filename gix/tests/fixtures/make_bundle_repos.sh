@@ -0,0 +1,30 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q repo
+(cd repo
+  git checkout -q -b main
+  echo 1 >file1
+  git add file1
+  git commit -q -m c1
+
+  git checkout -q -b other
+  echo o >other
+  git add other
+  git commit -q -m o1
+
+  git checkout -q main
+  echo 2 >file2
+  git add file2
+  git commit -q -m c2
+  git tag -a -m "annotated" v1
+  git branch base
+
+  echo 3 >file3
+  git add file3
+  git commit -q -m c3
+
+  git bundle create -q ../all.bundle --all
+  git bundle create -q ../base.bundle base
+  git bundle create -q ../incremental.bundle v1..main
+)
@@ -0,0 +1,20 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q remote
+(cd remote
+  git checkout -q -b main
+  git commit -q --allow-empty -m "initial"
+  git branch other
+  git tag -a -m "annotated" v1
+)
+
+git clone -q remote clone
+
+(cd remote
+  git commit -q --allow-empty -m "second"
+  git tag v2
+  git checkout -q other
+  git commit -q --allow-empty -m "on other"
+  git checkout -q main
+)
@@ -0,0 +1,31 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+for n in 1 2 3; do
+  echo $n >file$n
+  git add file$n
+  git commit -q -m "c$n"
+done
+git repack -q -d
+
+echo 4 >file4
+git add file4
+git commit -q -m c4
+git tag -a -m "annotated" v1
+git repack -q -d
+
+echo dropped >dropped
+git add dropped
+git commit -q -m "dropped"
+git reset -q --hard HEAD~1
+
+echo 5 >file5
+git add file5
+git commit -q -m c5
+
+echo unreachable-loose | git hash-object -w --stdin >/dev/null
+echo unreachable-packed | git hash-object -w --stdin | git pack-objects -q .git/objects/pack/pack >/dev/null
+git prune-packed
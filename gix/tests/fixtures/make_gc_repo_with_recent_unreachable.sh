@@ -0,0 +1,22 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+echo 1 >file1
+git add file1
+git commit -q -m c1
+
+function make_old() {
+  local id=${1:?first argument is the id of the loose object}
+  touch -t 200001010000 ".git/objects/${id:0:2}/${id:2}"
+}
+
+old_referenced=$(echo old-referenced | git hash-object -w --stdin)
+make_old "$old_referenced"
+old_unreferenced=$(echo old-unreferenced | git hash-object -w --stdin)
+make_old "$old_unreferenced"
+
+tree=$(printf "100644 blob %s\told\n" "$old_referenced" | git mktree)
+git commit-tree -m "recent and unreachable" "$tree" >/dev/null
@@ -0,0 +1,29 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+printf '1\n2\n3\n4\n5\n6\n7\n8\n9\n' > a
+printf '1\n2\n3\n' > conflict
+echo unchanged > b
+git add -A && git commit -q -m base
+git tag base
+
+git checkout -q -b ours
+git mv a renamed
+printf '1\nours\n3\n' > conflict
+git commit -q -am ours
+
+git checkout -q -b theirs base
+printf '1\n2\n3\n4\n5\n6\n7\n8\ntheirs\n' > a
+printf '1\ntheirs\n3\n' > conflict
+git commit -q -am theirs
+
+git checkout -q -b theirs-clean base
+printf '1\n2\n3\n4\n5\n6\n7\n8\ntheirs\n' > a
+git commit -q -am theirs-clean
+
+git checkout -q main
+
+git merge-tree --write-tree ours theirs-clean > .git/clean-merge-tree.expected
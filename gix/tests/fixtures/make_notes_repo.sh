@@ -0,0 +1,20 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+git commit -q --allow-empty -m first
+git tag first
+git commit -q --allow-empty -m second
+git tag second
+
+git notes add -m "first note" first
+git notes --ref other add -m "other note" second
+git notes --ref review/alice add -m "looks good" first
+
+git notes --ref base add -m "base" first
+git update-ref refs/notes/ours refs/notes/base
+git update-ref refs/notes/theirs refs/notes/base
+git notes --ref ours add -f -m "ours" first 2>/dev/null
+git notes --ref theirs add -f -m "theirs" first 2>/dev/null
+git notes --ref theirs add -m "theirs" second
@@ -0,0 +1,103 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config diff.simple.xfuncname '^fn ([a-z_]+)'
+
+cat <<EOF2 >.gitattributes
+*.rs diff=simple
+EOF2
+cat <<EOF2 >code.rs
+fn first() {
+  let a = 1;
+  let b = 2;
+  let c = 3;
+  let d = 4;
+}
+
+  fn indented_is_no_function() {
+  let e = 5;
+  let f = 6;
+  let g = 7;
+  let h = 8;
+  let i = 9;
+}
+EOF2
+cat <<EOF2 >plain.txt
+Heading
+one
+two
+three
+four
+five
+six
+seven
+eight
+nine
+ten
+EOF2
+printf 'no newline' >no-newline
+echo "executable" >mode
+echo "gone" >removed
+seq 1 20 >renamed-source
+echo "binary" >binary
+echo "spaces" >"with space"
+echo "non-ascii" >"ümlaut"
+echo "becomes a link" >typechange
+ln -s target link
+mkdir dir
+echo "nested" >dir/nested
+git add .
+mkdir submodule
+git update-index --add --cacheinfo 160000,1111111111111111111111111111111111111111,submodule
+git commit -q -m "first"
+
+cat <<EOF2 >code.rs
+fn first() {
+  let a = 1;
+  let b = 2;
+  let c = 30;
+  let d = 4;
+}
+
+  fn indented_is_no_function() {
+  let e = 5;
+  let f = 6;
+  let g = 7;
+  let h = 8;
+  let i = 90;
+}
+EOF2
+cat <<EOF2 >plain.txt
+Heading
+one
+two
+three and more
+four
+five
+six
+seven
+eight
+nine
+ten
+eleven
+EOF2
+printf 'no newline\nbut now there is' >no-newline
+chmod +x mode
+git rm -q removed
+git mv renamed-source renamed-destination
+printf 'bin\0ary' >binary
+echo "more spaces" >"with space"
+echo "still non-ascii" >"ümlaut"
+rm typechange && ln -s somewhere typechange
+rm link && ln -s other-target link
+echo "added" >dir/added
+touch empty
+git add .
+git update-index --cacheinfo 160000,2222222222222222222222222222222222222222,submodule
+git commit -q -m "second"
+
+git diff HEAD~1 HEAD >patch.diff
+git diff -U1 HEAD~1 HEAD >context-1.diff
+git diff --word-diff=plain HEAD~1 HEAD >word.diff
+git diff --no-renames HEAD~1 HEAD >no-renames.diff
@@ -0,0 +1,40 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+function write_and_commit() {
+  local file=${1:?first argument is the file to write}
+  echo "$file" > "$file"
+  git add "$file"
+  git commit -q -m "$file"
+}
+
+git init -q --bare server
+(cd server
+  git config receive.advertisePushOptions true
+  git config receive.advertiseAtomic true
+
+  cat > hooks/pre-receive <<'HOOK'
+#!/bin/sh
+echo "${GIT_PUSH_OPTION_COUNT:-0} ${GIT_PUSH_OPTION_0:-}" > push-options
+HOOK
+  cat > hooks/update <<'HOOK'
+#!/bin/sh
+test "$1" != refs/heads/protected
+HOOK
+  chmod +x hooks/pre-receive hooks/update
+)
+
+git init -q local
+(cd local
+  git checkout -q -b main
+  write_and_commit a
+  write_and_commit b
+  git remote add origin "$PWD/../server"
+  git push -q origin main main:to-delete main:diverged
+  git fetch -q origin
+
+  write_and_commit c
+  git checkout -q -b diverged HEAD~2
+  write_and_commit d
+  git checkout -q main
+)
@@ -0,0 +1,35 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+echo a >a
+echo b >b
+git add a b
+git commit -q -m base
+
+git checkout -q -b feature
+echo a1 >>a
+git commit -q -am "change a"
+mkdir dir
+echo new >dir/new
+git add dir
+git commit -q -m "add dir/new"
+git branch feature-middle
+echo a2 >>a
+git commit -q -am "fixup! change a"
+
+git checkout -q -b conflicting main
+echo "b theirs" >b
+git commit -q -am "change b"
+echo d >d
+git add d
+git commit -q -m "add d"
+
+git checkout -q main
+echo "b main" >b
+echo c >c
+git add b c
+git commit -q -m "upstream changes b and adds c"
+
+git checkout -q feature
@@ -0,0 +1,22 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q --object-format=sha256 loose
+(cd loose
+  git checkout -q -b main
+  mkdir dir
+  echo a > a
+  echo b > dir/b
+  git add .
+  git commit -q -m first
+  echo changed > a
+  git commit -q -am second
+  git tag -m "annotated" v1
+)
+
+git clone -q --no-local loose packed
+(cd packed
+  git gc -q
+  git commit-graph write --no-progress --reachable
+  git multi-pack-index write --no-progress
+)
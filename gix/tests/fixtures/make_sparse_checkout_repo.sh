@@ -0,0 +1,10 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+for path in root a/file a/b/file c/file c/d/file; do
+  mkdir -p "$(dirname "$path")"
+  echo "$path" >"$path"
+done
+git add .
+git commit -q -m "init"
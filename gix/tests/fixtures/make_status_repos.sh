@@ -14,3 +14,21 @@ git init -q untracked-only
   touch new/untracked subdir/untracked
 )
 
+
+git init -q staged-changes
+(cd staged-changes
+  echo "content" > modified
+  seq 10 > renamed
+  echo "removed" > removed
+  echo "unchanged" > unchanged
+
+  git add .
+  git commit -q -m init
+
+  echo "change" >> modified
+  git mv renamed renamed-destination
+  git rm -q --cached removed
+  echo "added" > added
+  git add modified added
+  echo "unstaged change" >> unchanged
+)
@@ -54,7 +54,8 @@ mod with_overrides {
             .set("GIT_TERMINAL_PROMPT", "42")
             .set("GIT_SHALLOW_FILE", "shallow-file-env")
             .set("GIT_NAMESPACE", "namespace-env")
-            .set("GIT_EXTERNAL_DIFF", "external-diff-env");
+            .set("GIT_EXTERNAL_DIFF", "external-diff-env")
+            .set("GIT_NOTES_REF", "refs/notes/env");
         let mut opts = gix::open::Options::isolated()
             .cli_overrides([
                 "http.userAgent=agent-from-cli",
@@ -241,6 +242,8 @@ mod with_overrides {
             ("core.useReplaceRefs", "no-replace"),
             #[cfg(feature = "blob-diff")]
             ("diff.external", "external-diff-env"),
+            #[cfg(feature = "notes")]
+            ("core.notesRef", "refs/notes/env"),
             ("gitoxide.objects.replaceRefBase", "refs/replace-mine"),
             ("gitoxide.committer.nameFallback", "committer name"),
             ("gitoxide.committer.emailFallback", "committer email"),
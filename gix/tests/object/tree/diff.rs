@@ -1130,6 +1130,67 @@ rename to gix-sec/tests/sec.rs
         Ok(())
     }
 }
+mod patch {
+    use gix::{
+        diff::blob::{pipeline::Mode, unified_diff::ContextSize},
+        object::tree::diff::patch::Options,
+    };
+    use gix_object::bstr::{BString, ByteSlice};
+
+    use crate::object::tree::diff::tree_named;
+
+    fn patch(repo: &gix::Repository, renames: bool, options: Options) -> crate::Result<BString> {
+        let mut resource_cache =
+            repo.diff_resource_cache(Mode::ToGitUnlessBinaryToTextIsPresent, Default::default())?;
+        let tree = tree_named(repo, "@~1");
+        let mut changes = tree.changes()?;
+        if !renames {
+            changes.track_rewrites(None);
+        }
+        let mut out = Vec::new();
+        changes.write_patch_to(&tree_named(repo, "@"), &mut resource_cache, &mut out, options)?;
+        Ok(out.into())
+    }
+
+    fn expected(repo: &gix::Repository, name: &str) -> crate::Result<BString> {
+        Ok(std::fs::read(repo.work_dir().expect("non-bare").join(name))?.into())
+    }
+
+    #[test]
+    fn matches_git_byte_for_byte() -> crate::Result {
+        let repo = crate::named_repo("make_patch_repo.sh")?;
+        for (name, renames, options) in [
+            ("patch.diff", true, Options::default()),
+            (
+                "context-1.diff",
+                true,
+                Options {
+                    context_size: ContextSize::symmetrical(1),
+                    ..Default::default()
+                },
+            ),
+            (
+                "word.diff",
+                true,
+                Options {
+                    word_diff: true,
+                    ..Default::default()
+                },
+            ),
+            ("no-renames.diff", false, Options::default()),
+        ] {
+            let actual = patch(&repo, renames, options)?;
+            let expected = expected(&repo, name)?;
+            assert_eq!(
+                actual.to_str_lossy(),
+                expected.to_str_lossy(),
+                "{name}: the patch is exactly what git produces"
+            );
+        }
+        Ok(())
+    }
+}
+
 fn store(change: &gix::object::tree::diff::Change<'_, '_, '_>) -> (char, BString) {
     (shorthand(&change.event), change.location.to_owned())
 }
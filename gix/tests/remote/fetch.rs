@@ -10,6 +10,135 @@ mod shallow {
     }
 }
 
+#[cfg(feature = "blocking-network-client")]
+mod fetch_head {
+    use std::{path::Path, sync::atomic::AtomicBool};
+
+    use gix::remote::{fetch::WriteFetchHead, Direction::Fetch};
+
+    fn repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+        let tmp = gix_testtools::scripted_fixture_writable("make_fetch_head_repos.sh")?;
+        let repo = gix::open_opts(tmp.path().join("clone"), crate::restricted())?;
+        Ok((repo, tmp))
+    }
+
+    fn fetch(remote: gix::Remote<'_>, write_fetch_head: WriteFetchHead, dry_run: bool) -> crate::Result {
+        remote
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .with_write_fetch_head(write_fetch_head)
+            .with_dry_run(dry_run)
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        Ok(())
+    }
+
+    fn origin(repo: &gix::Repository) -> crate::Result<gix::Remote<'_>> {
+        Ok(repo.find_remote("origin")?)
+    }
+
+    fn fetch_head(repo: &gix::Repository) -> crate::Result<String> {
+        Ok(std::fs::read_to_string(repo.git_dir().join("FETCH_HEAD"))?)
+    }
+
+    fn git(workdir: &Path, args: &[&str]) -> crate::Result {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(workdir)
+            .status()?;
+        assert!(status.success(), "git {args:?} failed");
+        Ok(())
+    }
+
+    fn remote_id(repo: &gix::Repository, name: &str) -> crate::Result<gix::ObjectId> {
+        Ok(repo.find_reference(name)?.peel_to_id_in_place()?.detach())
+    }
+
+    #[test]
+    fn configured_refspecs_mark_the_merge_branch_and_list_new_tags() -> crate::Result {
+        let (repo, _tmp) = repo_rw()?;
+        fetch(origin(&repo)?, WriteFetchHead::Configured, false)?;
+
+        let url = origin(&repo)?.url(Fetch).expect("set").to_bstring();
+        let main = remote_id(&repo, "refs/remotes/origin/main")?;
+        let other = remote_id(&repo, "refs/remotes/origin/other")?;
+        let actual = fetch_head(&repo)?;
+        assert_eq!(
+            actual,
+            format!(
+                "{main}\t\tbranch 'main' of {url}\n\
+                 {other}\tnot-for-merge\tbranch 'other' of {url}\n\
+                 {main}\tnot-for-merge\ttag 'v2' of {url}\n"
+            ),
+            "only the branch configured with `branch.main.merge` is merged, and new tags are listed as well"
+        );
+        assert_eq!(
+            repo.rev_parse_single("FETCH_HEAD")?,
+            main,
+            "FETCH_HEAD resolves to the first entry"
+        );
+
+        git(repo.work_dir().expect("non-bare"), &["fetch", "-q"])?;
+        assert!(
+            actual.starts_with(&fetch_head(&repo)?),
+            "git writes the same, but doesn't list the tag it has already seen"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn refspecs_like_on_the_command_line_are_all_for_merge() -> crate::Result {
+        let (repo, _tmp) = repo_rw()?;
+        let mut remote = origin(&repo)?.with_fetch_tags(gix::remote::fetch::Tags::None);
+        remote.replace_refspecs(["other", "main"], Fetch)?;
+        fetch(remote, WriteFetchHead::AllForMerge, false)?;
+        let actual = fetch_head(&repo)?;
+
+        git(
+            repo.work_dir().expect("non-bare"),
+            &["fetch", "-q", "origin", "other", "main"],
+        )?;
+        assert_eq!(actual, fetch_head(&repo)?, "the order of refspecs is retained");
+        Ok(())
+    }
+
+    #[test]
+    fn detached_head_marks_nothing_for_merge() -> crate::Result {
+        let (repo, _tmp) = repo_rw()?;
+        git(repo.work_dir().expect("non-bare"), &["checkout", "-q", "--detach"])?;
+        fetch(origin(&repo)?, WriteFetchHead::Configured, false)?;
+        let actual = fetch_head(&repo)?;
+        assert_eq!(actual.lines().count(), 3);
+        assert!(actual.lines().all(|line| line.contains("\tnot-for-merge\t")));
+
+        git(repo.work_dir().expect("non-bare"), &["fetch", "-q"])?;
+        assert!(actual.starts_with(&fetch_head(&repo)?));
+        Ok(())
+    }
+
+    #[test]
+    fn it_is_not_written_during_dry_runs_or_if_disabled() -> crate::Result {
+        let (repo, _tmp) = repo_rw()?;
+        fetch(origin(&repo)?, WriteFetchHead::Configured, true)?;
+        assert!(
+            !repo.git_dir().join("FETCH_HEAD").exists(),
+            "dry-runs don't change anything"
+        );
+
+        fetch(origin(&repo)?, WriteFetchHead::Never, false)?;
+        assert!(!repo.git_dir().join("FETCH_HEAD").exists());
+
+        let (mut repo, _tmp) = repo_rw()?;
+        repo.config_snapshot_mut()
+            .set_value(&gix::config::tree::Fetch::WRITE_FETCH_HEAD, "false")?;
+        fetch(origin(&repo)?, WriteFetchHead::AllForMerge, false)?;
+        assert!(
+            !repo.git_dir().join("FETCH_HEAD").exists(),
+            "`fetch.writeFetchHead` overrides everything"
+        );
+        Ok(())
+    }
+}
+
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client-async-std"))]
 mod blocking_and_async_io {
     use std::sync::atomic::AtomicBool;
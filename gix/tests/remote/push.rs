@@ -0,0 +1,241 @@
+#[cfg(feature = "blocking-network-client")]
+mod blocking_io {
+    use std::sync::atomic::AtomicBool;
+
+    use gix::remote::{
+        push::{update::Mode, Status},
+        Direction::Push,
+    };
+    use gix_protocol::push::response::RefStatus;
+
+    type Prepare<'remote, 'repo> =
+        gix::remote::push::Prepare<'remote, 'repo, Box<dyn gix::protocol::transport::client::Transport + Send>>;
+
+    fn repo_rw() -> crate::Result<(gix::Repository, gix::Repository, gix_testtools::tempfile::TempDir)> {
+        let dir = gix_testtools::scripted_fixture_writable_with_args(
+            "make_push_repos.sh",
+            None::<String>,
+            gix_testtools::Creation::ExecuteScript,
+        )?;
+        let local = gix::open_opts(dir.path().join("local"), crate::restricted())?;
+        let server = gix::open_opts(dir.path().join("server"), crate::restricted())?;
+        Ok((local, server, dir))
+    }
+
+    fn push<'a>(
+        repo: &gix::Repository,
+        specs: impl IntoIterator<Item = &'a str>,
+        configure: impl for<'r, 'p> FnOnce(Prepare<'r, 'p>) -> Prepare<'r, 'p>,
+    ) -> crate::Result<gix::remote::push::Outcome> {
+        let mut remote = repo.find_remote("origin")?;
+        remote.replace_refspecs(specs, Push)?;
+        let prepare = remote
+            .connect(Push)?
+            .prepare_push(gix::progress::Discard, Default::default())?;
+        Ok(configure(prepare).send(gix::progress::Discard, &AtomicBool::default())?)
+    }
+
+    fn id_of(repo: &gix::Repository, name: &str) -> Option<gix::ObjectId> {
+        repo.try_find_reference(name)
+            .expect("valid name")
+            .map(|r| r.id().detach())
+    }
+
+    #[test]
+    fn fast_forward_and_new_branch() -> crate::Result {
+        let (repo, server, _tmp) = repo_rw()?;
+        let main = id_of(&repo, "refs/heads/main").expect("present");
+        let outcome = push(&repo, ["refs/heads/main:refs/heads/main", "main:new"], |p| p)?;
+
+        assert!(outcome.is_success());
+        assert_eq!(
+            outcome.updates.iter().map(|u| u.mode).collect::<Vec<_>>(),
+            [Mode::FastForward, Mode::New]
+        );
+        assert_eq!(outcome.updates[1].remote_ref.as_bstr(), "refs/heads/new");
+        match outcome.status {
+            Status::Sent {
+                num_objects,
+                report,
+                tracking_ref_edits,
+            } => {
+                assert_eq!(
+                    num_objects,
+                    Some(5),
+                    "one commit, its tree and the new blob, along with the parent commit and tree it was compared to"
+                );
+                assert_eq!(report.ref_updates().len(), 2);
+                assert_eq!(tracking_ref_edits.len(), 2, "both remote-tracking refs are updated");
+            }
+            _ => unreachable!("the push was sent"),
+        }
+
+        assert_eq!(id_of(&server, "refs/heads/main"), Some(main));
+        assert_eq!(id_of(&server, "refs/heads/new"), Some(main));
+        assert!(server.has_object(main), "the pack was received");
+        assert_eq!(id_of(&repo, "refs/remotes/origin/main"), Some(main));
+        assert_eq!(id_of(&repo, "refs/remotes/origin/new"), Some(main));
+        Ok(())
+    }
+
+    #[test]
+    fn non_fast_forward_is_rejected_locally_unless_forced() -> crate::Result {
+        let (repo, server, _tmp) = repo_rw()?;
+        let server_diverged = id_of(&server, "refs/heads/diverged");
+        let outcome = push(&repo, ["diverged"], |p| p)?;
+        assert!(!outcome.is_success());
+        assert_eq!(outcome.updates[0].mode, Mode::RejectedNonFastForward);
+        assert!(matches!(outcome.status, Status::NothingSent { dry_run: false }));
+        assert_eq!(outcome.rejections()[0].1, "rejected (non-fast-forward)");
+        assert_eq!(
+            id_of(&server, "refs/heads/diverged"),
+            server_diverged,
+            "nothing changed"
+        );
+
+        let outcome = push(&repo, ["+diverged"], |p| p)?;
+        assert!(outcome.is_success());
+        assert_eq!(outcome.updates[0].mode, Mode::Forced);
+        assert_eq!(
+            id_of(&server, "refs/heads/diverged"),
+            id_of(&repo, "refs/heads/diverged")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn delete() -> crate::Result {
+        let (repo, server, _tmp) = repo_rw()?;
+        assert!(id_of(&repo, "refs/remotes/origin/to-delete").is_some());
+        let outcome = push(&repo, [":to-delete"], |p| p)?;
+        assert!(outcome.is_success());
+        assert_eq!(outcome.updates[0].mode, Mode::Delete);
+        assert!(
+            matches!(outcome.status, Status::Sent { num_objects: None, .. }),
+            "no pack is needed for deletions"
+        );
+        assert_eq!(id_of(&server, "refs/heads/to-delete"), None);
+        assert_eq!(
+            id_of(&repo, "refs/remotes/origin/to-delete"),
+            None,
+            "the remote-tracking ref is removed as well"
+        );
+
+        let outcome = push(&repo, [":refs/heads/to-delete"], |p| p)?;
+        assert_eq!(outcome.updates[0].mode, Mode::RejectedRemoteRefMissing);
+        Ok(())
+    }
+
+    #[test]
+    fn rejection_by_remote_hook() -> crate::Result {
+        let (repo, server, _tmp) = repo_rw()?;
+        let outcome = push(&repo, ["main:protected", "main:accepted"], |p| p)?;
+        assert!(!outcome.is_success());
+        let Status::Sent { report, .. } = &outcome.status else {
+            unreachable!("the push was sent")
+        };
+        assert_eq!(
+            report.ref_update("refs/heads/protected".into()),
+            Some(&RefStatus::Rejected {
+                ref_name: "refs/heads/protected".into(),
+                reason: "hook declined".into()
+            })
+        );
+        let rejections = outcome.rejections();
+        assert_eq!(rejections.len(), 1);
+        assert_eq!(rejections[0].0.remote_ref.as_bstr(), "refs/heads/protected");
+        assert_eq!(rejections[0].1, "hook declined");
+
+        assert_eq!(id_of(&server, "refs/heads/protected"), None);
+        assert!(
+            id_of(&server, "refs/heads/accepted").is_some(),
+            "non-atomic pushes apply what they can"
+        );
+        assert_eq!(id_of(&repo, "refs/remotes/origin/protected"), None);
+        assert!(id_of(&repo, "refs/remotes/origin/accepted").is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn atomic() -> crate::Result {
+        let (repo, server, _tmp) = repo_rw()?;
+        let outcome = push(&repo, ["main", "diverged"], |p| p.with_atomic(true))?;
+        assert!(matches!(outcome.status, Status::AtomicRejected));
+        assert_eq!(
+            outcome.rejections().len(),
+            2,
+            "the local rejection and the update it prevented"
+        );
+        assert_ne!(id_of(&server, "refs/heads/main"), id_of(&repo, "refs/heads/main"));
+
+        let outcome = push(&repo, ["main:protected", "main:accepted"], |p| p.with_atomic(true))?;
+        assert!(!outcome.is_success());
+        assert_eq!(
+            id_of(&server, "refs/heads/accepted"),
+            None,
+            "the remote doesn't apply any update if one fails"
+        );
+        assert_eq!(outcome.rejections().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn dry_run() -> crate::Result {
+        let (repo, server, _tmp) = repo_rw()?;
+        let before = id_of(&server, "refs/heads/main");
+        let outcome = push(&repo, ["main"], |p| p.with_dry_run(true))?;
+        assert!(matches!(outcome.status, Status::NothingSent { dry_run: true }));
+        assert_eq!(outcome.updates[0].mode, Mode::FastForward);
+        assert_eq!(id_of(&server, "refs/heads/main"), before);
+        Ok(())
+    }
+
+    #[test]
+    fn negative_specs_exclude_remote_refs() -> crate::Result {
+        let (repo, server, _tmp) = repo_rw()?;
+        let server_diverged = id_of(&server, "refs/heads/diverged");
+        let outcome = push(&repo, ["refs/heads/*:refs/heads/*", "^refs/heads/div*"], |p| p)?;
+        assert!(outcome.is_success());
+        assert_eq!(
+            outcome
+                .updates
+                .iter()
+                .map(|u| (u.remote_ref.as_bstr().to_string(), u.mode))
+                .collect::<Vec<_>>(),
+            [("refs/heads/main".to_string(), Mode::FastForward)],
+            "the negation is matched against the remote ref, excluding the diverged branch"
+        );
+        assert_eq!(
+            id_of(&server, "refs/heads/diverged"),
+            server_diverged,
+            "nothing changed"
+        );
+
+        let outcome = push(&repo, ["main:refs/heads/main", "^refs/heads/main"], |p| p)?;
+        assert!(outcome.updates.is_empty(), "single refs can be excluded as well");
+        Ok(())
+    }
+
+    #[test]
+    fn up_to_date() -> crate::Result {
+        let (repo, _server, _tmp) = repo_rw()?;
+        let outcome = push(&repo, ["main~1:to-delete"], |p| p)?;
+        assert!(outcome.is_success());
+        assert_eq!(outcome.updates[0].mode, Mode::UpToDate);
+        assert!(matches!(outcome.status, Status::NothingSent { dry_run: false }));
+        Ok(())
+    }
+
+    #[test]
+    fn push_options() -> crate::Result {
+        let (repo, server, _tmp) = repo_rw()?;
+        let outcome = push(&repo, ["main"], |p| p.with_push_options(Some("ci.skip")))?;
+        assert!(outcome.is_success());
+        assert_eq!(
+            std::fs::read_to_string(server.path().join("push-options"))?,
+            "1 ci.skip\n",
+            "the pre-receive hook sees the options"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use gix::bstr::ByteSlice;
+
+#[test]
+fn blame_file_follows_renames_and_ignores_revisions() -> crate::Result {
+    let repo = crate::named_repo("make_blame_repo.sh")?;
+    let id = |spec: &str| -> crate::Result<gix::ObjectId> { Ok(repo.rev_parse_single(spec)?.detach()) };
+    let head = repo.head_id()?;
+
+    let outcome = repo.blame_file(
+        "renamed".into(),
+        head,
+        gix::blame::file::Options {
+            rewrites: Some(Default::default()),
+            ..Default::default()
+        },
+    )?;
+    let commits: Vec<_> = outcome
+        .entries_with_lines()
+        .flat_map(|(entry, lines)| {
+            lines
+                .into_iter()
+                .map(move |line| (entry.commit_id, line.trim().to_owned()))
+        })
+        .collect();
+    assert_eq!(
+        commits,
+        [
+            (id("first")?, b"1".to_vec()),
+            (id("reformat")?, b"TWO".to_vec()),
+            (id("first")?, b"3".to_vec()),
+            (id("second")?, b"4".to_vec()),
+        ]
+    );
+    assert_eq!(outcome.entries[0].source_file_name.as_ref().expect("renamed"), "file");
+
+    let outcome = repo.blame_file(
+        "renamed".into(),
+        head,
+        gix::blame::file::Options {
+            rewrites: Some(Default::default()),
+            ignore_revs: repo.blame_ignore_revs(None)?,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        outcome.entries[1].commit_id,
+        id("second")?,
+        "the line is passed to the parent of the ignored commit"
+    );
+    Ok(())
+}
+
+#[test]
+fn blame_ignore_revs() -> crate::Result {
+    let repo = crate::named_repo("make_blame_repo.sh")?;
+    let id = |spec: &str| -> crate::Result<gix::ObjectId> { Ok(repo.rev_parse_single(spec)?.detach()) };
+
+    let revs = repo.blame_ignore_revs(None)?;
+    assert_eq!(revs.len(), 1, "comments and empty lines are skipped");
+    assert!(revs.contains(&id("reformat")?), "read from `blame.ignoreRevsFile`");
+
+    let revs = repo.blame_ignore_revs([".git/more-revs-to-ignore".into()])?;
+    assert_eq!(revs.len(), 2, "additional files add to the configured ones");
+    assert!(revs.contains(&id("second")?));
+
+    let revs = repo.blame_ignore_revs(["".into(), ".git/more-revs-to-ignore".into()])?;
+    assert_eq!(revs.len(), 1, "an empty path clears all previous revisions");
+    assert!(revs.contains(&id("second")?));
+
+    let err = repo.blame_ignore_revs(["renamed".into()]).unwrap_err();
+    assert!(err.to_string().starts_with("Invalid object name '1' in "));
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+use gix::bundle;
+
+fn fixture() -> crate::Result<PathBuf> {
+    gix_testtools::scripted_fixture_read_only("make_bundle_repos.sh")
+}
+
+fn repo() -> crate::Result<gix::Repository> {
+    Ok(gix::open_opts(fixture()?.join("repo"), crate::restricted())?)
+}
+
+fn create(
+    repo: &gix::Repository,
+    specs: &[&str],
+    path: &std::path::Path,
+) -> Result<bundle::create::Outcome, bundle::create::Error> {
+    let mut out = std::fs::File::create(path)?;
+    repo.create_bundle(
+        specs.iter().copied(),
+        &mut out,
+        Default::default(),
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )
+}
+
+fn git_bundle_verify(repo: &gix::Repository, path: &std::path::Path) -> crate::Result<bool> {
+    Ok(std::process::Command::new("git")
+        .args(["bundle", "verify", "-q"])
+        .arg(path)
+        .current_dir(repo.git_dir())
+        .stderr(std::process::Stdio::null())
+        .status()?
+        .success())
+}
+
+mod create {
+    use gix::bundle::{self, Version};
+
+    use super::{create, git_bundle_verify, repo};
+
+    #[test]
+    fn all_references_without_prerequisites() -> crate::Result {
+        let repo = repo()?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let path = tmp.path().join("all.bundle");
+        let outcome = create(&repo, &["HEAD", "main", "other", "v1", "main"], &path)?;
+        assert_eq!(outcome.header.version, Version::V2);
+        assert!(outcome.header.prerequisites.is_empty());
+        let refs: Vec<_> = outcome.header.refs.iter().map(|r| (r.name.to_string(), r.id)).collect();
+        assert_eq!(
+            refs,
+            [
+                ("HEAD".into(), repo.head_id()?.detach()),
+                ("refs/heads/main".into(), repo.rev_parse_single("main")?.detach()),
+                ("refs/heads/other".into(), repo.rev_parse_single("other")?.detach()),
+                ("refs/tags/v1".into(), repo.rev_parse_single("v1")?.detach()),
+            ],
+            "references are deduplicated, and tags aren't peeled"
+        );
+        assert_eq!(
+            outcome.num_objects, 13,
+            "4 commits with one tree and blob each, along with the annotated tag"
+        );
+        assert_eq!(
+            bundle::File::at(&path)?.header,
+            outcome.header,
+            "the header was written"
+        );
+        assert!(git_bundle_verify(&repo, &path)?, "git can read the bundle");
+        Ok(())
+    }
+
+    #[test]
+    fn ranges_turn_boundary_commits_into_prerequisites() -> crate::Result {
+        let repo = repo()?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let path = tmp.path().join("incremental.bundle");
+        let outcome = create(&repo, &["v1..main"], &path)?;
+        assert_eq!(outcome.header.prerequisites.len(), 1);
+        let prerequisite = &outcome.header.prerequisites[0];
+        assert_eq!(prerequisite.id, repo.rev_parse_single("v1^{commit}")?);
+        assert_eq!(
+            prerequisite.comment, "c2",
+            "the summary of the commit is used as comment"
+        );
+        assert_eq!(outcome.header.refs.len(), 1);
+        assert_eq!(outcome.header.refs[0].name, "refs/heads/main");
+        assert_eq!(
+            outcome.num_objects, 3,
+            "only the commit, tree and blob that aren't reachable from the prerequisite"
+        );
+        assert!(git_bundle_verify(&repo, &path)?);
+        Ok(())
+    }
+
+    #[test]
+    fn without_references_it_fails() -> crate::Result {
+        let repo = repo()?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let err = create(&repo, &["main~1", "^other"], &tmp.path().join("empty.bundle")).unwrap_err();
+        assert!(matches!(err, bundle::create::Error::NoReferences), "{err:?}");
+        Ok(())
+    }
+}
+
+mod verify {
+    use gix::bundle;
+
+    use super::{fixture, repo};
+
+    #[test]
+    fn prerequisites_must_be_present() -> crate::Result {
+        let bundle = bundle::File::at(fixture()?.join("incremental.bundle"))?;
+        repo()?.verify_bundle(&bundle.header)?;
+
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let empty = gix::init_bare(tmp.path())?;
+        let err = empty.verify_bundle(&bundle.header).unwrap_err();
+        assert!(
+            matches!(&err, bundle::verify::Error::MissingPrerequisites { ids } if *ids == [bundle.header.prerequisites[0].id]),
+            "{err:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn object_hash_must_match() -> crate::Result {
+        let header = bundle::Header::new(gix::hash::Kind::Sha256);
+        let err = repo()?.verify_bundle(&header).unwrap_err();
+        assert!(matches!(err, bundle::verify::Error::ObjectHash { .. }), "{err:?}");
+        Ok(())
+    }
+}
+
+#[cfg(feature = "blocking-network-client")]
+mod fetch {
+    use std::sync::atomic::AtomicBool;
+
+    use gix::remote::Direction;
+
+    use super::{create, fixture, repo};
+
+    fn fetch(repo: &gix::Repository, bundle: &std::path::Path) -> crate::Result<gix::remote::fetch::Outcome> {
+        Ok(repo
+            .remote_at(bundle)?
+            .with_refspecs(Some("+refs/heads/*:refs/remotes/origin/*"), Direction::Fetch)?
+            .connect(Direction::Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .receive(gix::progress::Discard, &AtomicBool::default())?)
+    }
+
+    #[test]
+    fn clone_from_bundle_written_by_git_and_gix() -> crate::Result {
+        let source = repo()?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let gix_bundle = tmp.path().join("gix.bundle");
+        create(&source, &["HEAD", "main", "other", "v1"], &gix_bundle)?;
+        for (idx, bundle) in [fixture()?.join("all.bundle"), gix_bundle].into_iter().enumerate() {
+            let (repo, _outcome) = gix::prepare_clone_bare(bundle, tmp.path().join(format!("clone-{idx}")))?
+                .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+            assert_eq!(repo.head_name()?.expect("not detached").as_bstr(), "refs/heads/main");
+            for name in ["main", "other"] {
+                assert_eq!(
+                    repo.find_reference(format!("refs/remotes/origin/{name}").as_str())?
+                        .id(),
+                    source.rev_parse_single(name)?,
+                    "remote-tracking branches are created just like when cloning a repository"
+                );
+            }
+            assert_eq!(repo.find_reference("v1")?.id(), source.rev_parse_single("v1")?);
+            assert!(repo.has_object(source.rev_parse_single("other:other")?));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn prerequisites_are_verified_before_fetching() -> crate::Result {
+        let source = repo()?;
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let repo = gix::init_bare(tmp.path())?;
+        let err = fetch(&repo, &fixture()?.join("incremental.bundle")).unwrap_err();
+        assert!(err.to_string().contains("can't be used with this repository"), "{err}");
+
+        fetch(&repo, &fixture()?.join("base.bundle"))?;
+        assert_eq!(
+            repo.find_reference("refs/remotes/origin/base")?.id(),
+            source.rev_parse_single("base")?
+        );
+        fetch(&repo, &fixture()?.join("incremental.bundle"))?;
+        let main = repo.find_reference("refs/remotes/origin/main")?.id();
+        assert_eq!(main, source.rev_parse_single("main")?);
+        assert_eq!(
+            main.ancestors().all()?.count(),
+            3,
+            "the thin pack was completed with local objects"
+        );
+        Ok(())
+    }
+}
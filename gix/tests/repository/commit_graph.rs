@@ -0,0 +1,73 @@
+use std::process::Command;
+
+use gix::commitgraph::{bloom, write};
+
+#[test]
+fn write_commit_graph_is_the_same_as_the_one_written_by_git() -> crate::Result {
+    let (repo, tmp) = crate::repo_rw("make_diff_repo.sh")?;
+    let head = repo.head_id()?.detach();
+    let outcome = repo.write_commit_graph(
+        Some(head),
+        write::Options {
+            changed_paths: Some(bloom::Settings::default()),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.num_files, 1);
+    let actual = std::fs::read(outcome.path.expect("always written"))?;
+
+    let graph = repo.commit_graph()?;
+    assert_eq!(graph.num_commits(), outcome.num_commits);
+    assert!(graph
+        .commit_by_id(head)
+        .expect("present")
+        .changed_paths_filter()
+        .is_some());
+
+    let status = Command::new("git")
+        .args([
+            "commit-graph",
+            "write",
+            "--no-progress",
+            "--reachable",
+            "--changed-paths",
+        ])
+        .current_dir(tmp.path())
+        .env_remove("GIT_DIR")
+        .status()?;
+    assert!(status.success());
+    let expected = std::fs::read(repo.objects.store_ref().path().join("info/commit-graph"))?;
+    assert!(
+        actual == expected,
+        "changed-path filters are computed just like git does"
+    );
+    Ok(())
+}
+
+#[test]
+fn write_commit_graph_with_split_only_adds_new_commits() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_diff_repo.sh")?;
+    let options = write::Options {
+        split: Some(write::Split {
+            size_multiple: 0,
+            max_commits: None,
+        }),
+        ..Default::default()
+    };
+    let parent = repo.rev_parse_single("HEAD~1")?.detach();
+    let first = repo.write_commit_graph(Some(parent), options)?;
+    assert_eq!(first.num_files, 1);
+
+    let head = repo.head_id()?.detach();
+    let outcome = repo.write_commit_graph(Some(head), options)?;
+    assert_eq!(outcome.num_commits, 1, "only HEAD is new");
+    assert_eq!(outcome.num_files, 2);
+
+    let graph = repo.commit_graph()?;
+    assert_eq!(graph.num_commits(), first.num_commits + 1);
+    assert!(graph.lookup(head).is_some());
+
+    let outcome = repo.write_commit_graph(Some(head), options)?;
+    assert_eq!(outcome.path, None, "nothing to do");
+    Ok(())
+}
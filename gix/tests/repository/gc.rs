@@ -0,0 +1,382 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool, time::SystemTime};
+
+use gix::{
+    config::tree::Gc,
+    gc::{reflog, repack, Trigger},
+};
+
+fn packs(repo: &gix::Repository) -> crate::Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    for entry in std::fs::read_dir(repo.objects.store_ref().path().join("pack"))? {
+        let path = entry?.path();
+        if matches!(path.extension(), Some(ext) if ext == "pack") {
+            out.push(path);
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+fn num_loose_objects(repo: &gix::Repository) -> usize {
+    gix::odb::loose::Store::at(repo.objects.store_ref().path(), repo.object_hash())
+        .iter()
+        .count()
+}
+
+/// Return a repository with a recent unreachable commit that refers to an old blob, along with another old unreachable blob.
+///
+/// The script runs in place as modification times aren't kept when copying fixtures.
+fn repo_with_recent_unreachable() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let dir = gix_testtools::scripted_fixture_writable_with_args(
+        "make_gc_repo_with_recent_unreachable.sh",
+        None::<String>,
+        gix_testtools::Creation::ExecuteScript,
+    )?;
+    let repo = gix::open_opts(dir.path(), crate::restricted())?;
+    Ok((repo, dir))
+}
+
+/// A time after the old objects of [`repo_with_recent_unreachable()`] were modified, but before all others.
+fn expire_old_objects() -> SystemTime {
+    SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(40 * 365 * 24 * 60 * 60)
+}
+
+fn blob_id(content: &str) -> gix::ObjectId {
+    gix::objs::compute_hash(gix::hash::Kind::Sha1, gix::object::Kind::Blob, content.as_bytes())
+}
+
+/// Reopen `repo` to see all changes to the object database, and assert that all objects reachable from `HEAD` can be read.
+fn assert_history_is_intact(repo: &gix::Repository) -> crate::Result<gix::Repository> {
+    let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+    let mut num_commits = 0;
+    for info in repo.head_id()?.ancestors().all()? {
+        let commit = info?.object()?;
+        let mut recorder = gix::traverse::tree::Recorder::default();
+        commit.tree()?.traverse().breadthfirst(&mut recorder)?;
+        for entry in recorder.records {
+            repo.find_object(entry.oid)?;
+        }
+        num_commits += 1;
+    }
+    assert_eq!(num_commits, 5, "all commits of `main` are still present");
+    Ok(repo)
+}
+
+fn repack(repo: &gix::Repository, mode: repack::Mode) -> crate::Result<repack::Outcome> {
+    Ok(repo.repack(
+        &repack::Options {
+            mode,
+            ..Default::default()
+        },
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?)
+}
+
+mod repack_ {
+    use gix::gc::repack::{Mode, Unreachable};
+
+    use super::{
+        assert_history_is_intact, blob_id, expire_old_objects, num_loose_objects, packs, repack,
+        repo_with_recent_unreachable,
+    };
+
+    #[test]
+    fn incremental_packs_reachable_loose_objects() -> crate::Result {
+        let (repo, _tmp) = crate::repo_rw("make_gc_repo.sh")?;
+        assert_eq!(num_loose_objects(&repo), 7);
+        let outcome = repack(&repo, Mode::Incremental)?;
+        assert_eq!(
+            outcome.num_objects, 6,
+            "the objects of the last commit and of the commit that is only reachable through reflogs"
+        );
+        assert!(outcome.removed_packs.is_empty());
+        assert_eq!(outcome.num_removed_loose_objects, 6);
+        assert_eq!(packs(&repo)?.len(), 4);
+
+        let repo = assert_history_is_intact(&repo)?;
+        assert_eq!(num_loose_objects(&repo), 1, "only the unreachable loose object is left");
+        assert!(repo.has_object(blob_id("unreachable-loose\n")));
+        Ok(())
+    }
+
+    #[test]
+    fn all_into_one_with_cruft_pack() -> crate::Result {
+        let (repo, _tmp) = crate::repo_rw("make_gc_repo.sh")?;
+        let previous_packs = packs(&repo)?;
+        let outcome = repack(
+            &repo,
+            Mode::AllIntoOne {
+                unreachable: Unreachable::Cruft { expire: None },
+            },
+        )?;
+        assert_eq!(
+            outcome.num_objects, 19,
+            "all reachable objects, including those in reflogs"
+        );
+        assert_eq!(
+            outcome.num_cruft_objects, 2,
+            "one loose and one packed unreachable object"
+        );
+        assert_eq!(outcome.removed_packs, previous_packs);
+        assert_eq!(outcome.num_removed_loose_objects, 7);
+        let cruft_pack = outcome.cruft_pack.expect("written");
+        assert!(cruft_pack.with_extension("mtimes").is_file());
+        assert!(outcome.pack.expect("written").with_extension("rev").is_file());
+
+        let repo = assert_history_is_intact(&repo)?;
+        assert_eq!(packs(&repo)?.len(), 2);
+        assert_eq!(num_loose_objects(&repo), 0);
+        for content in ["unreachable-loose\n", "unreachable-packed\n"] {
+            assert!(repo.has_object(blob_id(content)), "unreachable objects are kept");
+        }
+
+        let outcome = repack(
+            &repo,
+            Mode::AllIntoOne {
+                unreachable: Unreachable::Cruft {
+                    expire: Some(std::time::SystemTime::now()),
+                },
+            },
+        )?;
+        assert_eq!(outcome.num_objects, 19);
+        assert_eq!(outcome.cruft_pack, None, "all unreachable objects expired");
+        assert!(outcome.removed_packs.contains(&cruft_pack));
+        let repo = assert_history_is_intact(&repo)?;
+        assert_eq!(packs(&repo)?.len(), 1);
+        assert!(!repo.has_object(blob_id("unreachable-packed\n")));
+        Ok(())
+    }
+
+    #[test]
+    fn all_into_one_keeps_kept_packs() -> crate::Result {
+        let (repo, _tmp) = crate::repo_rw("make_gc_repo.sh")?;
+        let mut previous_packs = packs(&repo)?;
+        previous_packs.sort_by_key(|path| std::fs::metadata(path).map(|m| m.len()).unwrap_or_default());
+        let kept_pack = previous_packs.pop().expect("three packs");
+        std::fs::write(kept_pack.with_extension("keep"), b"")?;
+        previous_packs.sort();
+
+        let outcome = repack(
+            &repo,
+            Mode::AllIntoOne {
+                unreachable: Unreachable::Drop,
+            },
+        )?;
+        assert_eq!(
+            outcome.num_objects, 10,
+            "objects of the largest pack with 9 objects are kept and not repacked"
+        );
+        assert_eq!(outcome.removed_packs, previous_packs);
+        assert_eq!(outcome.cruft_pack, None);
+        assert_eq!(outcome.num_loosened_objects, 0);
+
+        let repo = assert_history_is_intact(&repo)?;
+        assert!(kept_pack.is_file());
+        assert_eq!(packs(&repo)?.len(), 2);
+        assert!(
+            !repo.has_object(blob_id("unreachable-packed\n")),
+            "unreachable packed objects are dropped"
+        );
+        assert!(
+            repo.has_object(blob_id("unreachable-loose\n")),
+            "unreachable loose objects are left to pruning"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cruft_pack_keeps_old_objects_referred_to_by_recent_unreachable_ones() -> crate::Result {
+        let (repo, _tmp) = repo_with_recent_unreachable()?;
+        let outcome = repack(
+            &repo,
+            Mode::AllIntoOne {
+                unreachable: Unreachable::Cruft {
+                    expire: Some(expire_old_objects()),
+                },
+            },
+        )?;
+        assert_eq!(outcome.num_objects, 3);
+        assert_eq!(
+            outcome.num_cruft_objects, 3,
+            "the recent commit and tree, along with the old blob they refer to"
+        );
+
+        let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+        assert_eq!(
+            num_loose_objects(&repo),
+            1,
+            "the expired object is left to pruning, all others are packed"
+        );
+        assert!(repo.has_object(blob_id("old-referenced\n")));
+        Ok(())
+    }
+
+    #[test]
+    fn all_into_one_can_loosen_unreachable_objects() -> crate::Result {
+        let (repo, _tmp) = crate::repo_rw("make_gc_repo.sh")?;
+        let outcome = repack(
+            &repo,
+            Mode::AllIntoOne {
+                unreachable: Unreachable::Loosen { expire: None },
+            },
+        )?;
+        assert_eq!(outcome.num_objects, 19);
+        assert_eq!(outcome.num_loosened_objects, 1);
+
+        let repo = assert_history_is_intact(&repo)?;
+        assert_eq!(packs(&repo)?.len(), 1);
+        assert_eq!(num_loose_objects(&repo), 2, "both unreachable objects are loose now");
+        assert!(repo.has_object(blob_id("unreachable-packed\n")));
+        Ok(())
+    }
+
+    #[test]
+    fn geometric_rolls_up_small_packs() -> crate::Result {
+        let (repo, _tmp) = crate::repo_rw("make_gc_repo.sh")?;
+        let outcome = repack(&repo, Mode::Geometric { factor: 2 })?;
+        assert_eq!(
+            outcome.num_objects, 7,
+            "packs of 1, 4 and 9 objects already form a progression, so only loose objects are packed"
+        );
+        assert!(outcome.removed_packs.is_empty());
+        assert_eq!(num_loose_objects(&repo), 0);
+
+        let outcome = repack(&repo, Mode::Geometric { factor: 2 })?;
+        assert_eq!(
+            outcome.num_objects, 21,
+            "9 isn't twice as much as 7, so everything is rolled up"
+        );
+        assert_eq!(outcome.removed_packs.len(), 4);
+
+        let repo = assert_history_is_intact(&repo)?;
+        assert_eq!(packs(&repo)?.len(), 1);
+        let outcome = repack(&repo, Mode::Geometric { factor: 2 })?;
+        assert_eq!(outcome.pack, None, "a single pack doesn't need to be rolled up");
+        Ok(())
+    }
+}
+
+#[test]
+fn prune_loose_objects() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_gc_repo.sh")?;
+    let outcome = repo.prune_loose_objects(
+        SystemTime::UNIX_EPOCH,
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(outcome.num_removed_objects, 0);
+    assert_eq!(outcome.num_kept_objects, 1, "the unreachable object is too recent");
+
+    let outcome = repo.prune_loose_objects(SystemTime::now(), &mut gix::progress::Discard, &AtomicBool::default())?;
+    assert_eq!(outcome.num_removed_objects, 1);
+    assert_eq!(num_loose_objects(&repo), 6);
+    assert!(!repo.has_object(blob_id("unreachable-loose\n")));
+    assert_history_is_intact(&repo)?;
+    Ok(())
+}
+
+#[test]
+fn prune_keeps_old_objects_referred_to_by_recent_unreachable_ones() -> crate::Result {
+    let (repo, _tmp) = repo_with_recent_unreachable()?;
+    let outcome = repo.prune_loose_objects(
+        expire_old_objects(),
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(outcome.num_removed_objects, 1);
+    assert_eq!(
+        outcome.num_kept_objects, 3,
+        "the recent commit and tree, along with the old blob they refer to"
+    );
+    assert!(repo.has_object(blob_id("old-referenced\n")));
+    assert!(!repo.has_object(blob_id("old-unreferenced\n")));
+    Ok(())
+}
+
+#[test]
+fn expire_reflogs() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_gc_repo.sh")?;
+    let dropped = repo.rev_parse_single("main@{2}")?.detach();
+    let outcome = repo.expire_reflogs(reflog::Options {
+        expire: None,
+        expire_unreachable: Some(SystemTime::now()),
+    })?;
+    assert_eq!(outcome.num_reflogs, 2, "HEAD and main");
+    assert_eq!(
+        outcome.num_removed_entries, 4,
+        "the commit and the reset of the dropped commit, in both reflogs"
+    );
+    let mut buf = Vec::new();
+    let log = repo
+        .refs
+        .reflog_iter("refs/heads/main", &mut buf)?
+        .expect("present")
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(log.len(), 5);
+    assert!(log.iter().all(|line| line.new_oid() != dropped));
+
+    let outcome = repo.expire_reflogs(reflog::Options {
+        expire: Some(SystemTime::now()),
+        expire_unreachable: None,
+    })?;
+    assert_eq!(outcome.num_removed_entries, 10, "all remaining entries are old enough");
+    Ok(())
+}
+
+#[test]
+fn pack_refs() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_gc_repo.sh")?;
+    let tag = repo.find_reference("v1")?.id().detach();
+    let outcome = repo.pack_refs()?;
+    assert_eq!(outcome.num_packed_refs, 2, "main and v1");
+    assert!(!repo.git_dir().join("refs/heads/main").exists());
+    assert!(!repo.git_dir().join("refs/tags/v1").exists());
+    assert_eq!(
+        repo.find_reference("v1")?.id(),
+        tag,
+        "the packed reference isn't peeled"
+    );
+    assert_history_is_intact(&repo)?;
+    assert_eq!(repo.pack_refs()?.num_packed_refs, 0, "nothing left to pack");
+    Ok(())
+}
+
+#[test]
+fn needs_gc_and_gc_auto() -> crate::Result {
+    let (mut repo, _tmp) = crate::repo_rw("make_gc_repo.sh")?;
+    assert_eq!(repo.needs_gc()?, None, "defaults are far from being reached");
+    assert_eq!(
+        repo.gc_auto(&mut gix::progress::Discard, &AtomicBool::default())?
+            .map(|_| ()),
+        None
+    );
+
+    repo.config_snapshot_mut().set_value(&Gc::AUTO_PACK_LIMIT, "2")?;
+    assert_eq!(repo.needs_gc()?, Some(Trigger::TooManyPacks { num_packs: 3, limit: 2 }));
+    repo.config_snapshot_mut().set_value(&Gc::AUTO, "0")?;
+    assert_eq!(repo.needs_gc()?, None, "auto-gc is disabled entirely");
+
+    repo.config_snapshot_mut().set_value(&Gc::AUTO, "6700")?;
+    repo.config_snapshot_mut().set_value(&Gc::PRUNE_EXPIRE, "never")?;
+    let outcome = repo
+        .gc_auto(&mut gix::progress::Discard, &AtomicBool::default())?
+        .expect("too many packs");
+    assert_eq!(outcome.repack.removed_packs.len(), 3);
+    assert_eq!(
+        outcome.reflog.num_removed_entries, 14,
+        "all reflog entries are older than 90 days"
+    );
+    assert_eq!(outcome.pack_refs.map(|outcome| outcome.num_packed_refs), Some(2));
+    assert_eq!(
+        outcome.repack.num_objects, 16,
+        "the dropped commit isn't reachable anymore once reflogs expired"
+    );
+    assert!(outcome.commit_graph.is_some());
+    assert_eq!(outcome.repack.num_cruft_objects, 5, "unreachable objects never expire");
+    assert_eq!(outcome.prune, None);
+    let repo = assert_history_is_intact(&repo)?;
+    assert_eq!(packs(&repo)?.len(), 2, "one for reachable objects, and a cruft pack");
+    assert_eq!(repo.needs_gc()?, None, "cruft packs don't count towards the pack limit");
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use gix::{
+    bstr::ByteSlice,
+    config::tree::Merge,
+    merge::{blob::Labels, tree::ConflictKind},
+};
+
+fn labels() -> Labels<'static> {
+    Labels {
+        ancestor: Some("base".into()),
+        current: Some("ours".into()),
+        other: Some("theirs".into()),
+    }
+}
+
+#[test]
+fn merge_trees_without_conflicts() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_merge_trees_repo.sh")?;
+    let tree =
+        |spec: &str| -> crate::Result<gix::ObjectId> { Ok(repo.rev_parse_single(spec)?.object()?.peel_to_tree()?.id) };
+    let outcome = repo.merge_trees(
+        tree("base")?,
+        tree("ours")?,
+        tree("theirs-clean")?,
+        labels(),
+        repo.tree_merge_options()?,
+    )?;
+    assert!(!outcome.has_unresolved_conflicts());
+    assert!(outcome.index.entries().iter().all(|e| e.stage() == 0));
+
+    let expected = std::fs::read_to_string(repo.git_dir().join("clean-merge-tree.expected"))?;
+    assert_eq!(
+        outcome.tree.to_string(),
+        expected.trim(),
+        "their change was merged into our renamed file, just like `git merge-tree` does it"
+    );
+    Ok(())
+}
+
+#[test]
+fn merge_trees_with_conflicts_and_configured_style() -> crate::Result {
+    let (mut repo, _tmp) = crate::repo_rw("make_merge_trees_repo.sh")?;
+    repo.config_snapshot_mut().set_value(&Merge::CONFLICT_STYLE, "diff3")?;
+    let tree =
+        |spec: &str| -> crate::Result<gix::ObjectId> { Ok(repo.rev_parse_single(spec)?.object()?.peel_to_tree()?.id) };
+    let outcome = repo.merge_trees(
+        tree("base")?,
+        tree("ours")?,
+        tree("theirs")?,
+        labels(),
+        repo.tree_merge_options()?,
+    )?;
+    assert_eq!(
+        outcome
+            .conflicts
+            .iter()
+            .map(|c| (c.kind, c.location.to_str_lossy().into_owned()))
+            .collect::<Vec<_>>(),
+        [(ConflictKind::Content, "conflict".to_string())]
+    );
+    assert_eq!(
+        outcome
+            .index
+            .entries()
+            .iter()
+            .map(|e| (e.path(&outcome.index).to_string(), e.stage()))
+            .collect::<Vec<_>>(),
+        [
+            ("b".to_string(), 0),
+            ("conflict".into(), 1),
+            ("conflict".into(), 2),
+            ("conflict".into(), 3),
+            ("renamed".into(), 0)
+        ]
+    );
+
+    let merged = repo
+        .find_object(outcome.tree)?
+        .into_tree()
+        .peel_to_entry_by_path("conflict")?
+        .expect("present");
+    assert_eq!(
+        merged.object()?.data.as_bstr(),
+        "1\n<<<<<<< ours\nours\n||||||| base\n2\n=======\ntheirs\n>>>>>>> theirs\n3\n",
+        "the merged blob was written with markers in the configured style"
+    );
+    Ok(())
+}
+
+#[test]
+fn tree_merge_options_respect_rename_configuration() -> crate::Result {
+    let mut repo = crate::named_repo("make_merge_trees_repo.sh")?;
+    assert!(
+        repo.tree_merge_options()?.rewrites.is_some(),
+        "renames are tracked by default"
+    );
+
+    repo.config_snapshot_mut()
+        .set_raw_value("diff", None, "renames", "false")?;
+    assert!(
+        repo.tree_merge_options()?.rewrites.is_none(),
+        "`diff.renames` is used as fallback"
+    );
+
+    let mut config = repo.config_snapshot_mut();
+    config.set_raw_value("merge", None, "renames", "true")?;
+    config.set_raw_value("merge", None, "renameLimit", "42")?;
+    drop(config);
+    assert_eq!(
+        repo.tree_merge_options()?.rewrites.map(|r| r.limit),
+        Some(42),
+        "`merge.renames` has precedence, and so has `merge.renameLimit`"
+    );
+    Ok(())
+}
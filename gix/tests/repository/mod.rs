@@ -1,18 +1,36 @@
 use gix::Repository;
 
+#[cfg(feature = "blame")]
+mod blame;
+#[cfg(feature = "bundle")]
+mod bundle;
+mod commit_graph;
 mod config;
 #[cfg(feature = "attributes")]
 mod filter;
+#[cfg(feature = "gc")]
+mod gc;
+#[cfg(feature = "merge")]
+mod merge;
+#[cfg(feature = "notes")]
+mod notes;
 mod object;
 mod open;
 #[cfg(feature = "attributes")]
 mod pathspec;
+#[cfg(feature = "rebase")]
+mod rebase;
 mod reference;
 mod remote;
+mod sha256;
 mod shallow;
+#[cfg(feature = "worktree-mutation")]
+mod sparse;
 mod state;
 #[cfg(feature = "attributes")]
 mod submodule;
+#[cfg(feature = "worktree-mutation")]
+mod switch;
 mod worktree;
 
 #[cfg(feature = "dirwalk")]
@@ -0,0 +1,197 @@
+use gix::{
+    bstr::ByteSlice,
+    config::tree::{Core, Notes},
+    note::merge::Strategy,
+    notes::merge::Outcome,
+};
+
+fn id(repo: &gix::Repository, spec: &str) -> crate::Result<gix::ObjectId> {
+    Ok(repo.rev_parse_single(spec)?.detach())
+}
+
+fn note(repo: &gix::Repository, notes: &gix::Notes<'_>, spec: &str) -> crate::Result<Option<String>> {
+    Ok(notes
+        .find(id(repo, spec)?)?
+        .map(|blob| blob.data.to_str_lossy().into_owned()))
+}
+
+#[test]
+fn notes_are_read_from_the_configured_ref() -> crate::Result {
+    let mut repo = crate::named_repo("make_notes_repo.sh")?;
+    let notes = repo.notes()?;
+    assert_eq!(notes.ref_name().as_bstr(), "refs/notes/commits", "the default");
+    assert_eq!(note(&repo, &notes, "first")?.as_deref(), Some("first note\n"));
+    assert_eq!(note(&repo, &notes, "second")?, None);
+    assert_eq!(notes.load()?.notes.len(), 1);
+
+    repo.config_snapshot_mut()
+        .set_value(&Core::NOTES_REF, "refs/notes/other")?;
+    let notes = repo.notes()?;
+    assert_eq!(notes.ref_name().as_bstr(), "refs/notes/other");
+    assert_eq!(note(&repo, &notes, "second")?.as_deref(), Some("other note\n"));
+
+    let notes = repo.notes_at("missing")?;
+    assert!(notes.tip()?.is_none());
+    assert_eq!(
+        note(&repo, &notes, "first")?,
+        None,
+        "notes refs that don't exist have no notes"
+    );
+    assert!(notes.load()?.notes.is_empty());
+    Ok(())
+}
+
+#[test]
+fn notes_at_expands_short_names() -> crate::Result {
+    let repo = crate::named_repo("make_notes_repo.sh")?;
+    for name in ["other", "notes/other", "refs/notes/other"] {
+        assert_eq!(repo.notes_at(name)?.ref_name().as_bstr(), "refs/notes/other");
+    }
+    assert!(repo.notes_at("in valid").is_err());
+    Ok(())
+}
+
+#[test]
+fn notes_to_display_expands_globs() -> crate::Result {
+    let mut repo = crate::named_repo("make_notes_repo.sh")?;
+    let names = |repo: &gix::Repository| -> crate::Result<Vec<String>> {
+        Ok(repo
+            .notes_to_display()?
+            .iter()
+            .map(|notes| notes.ref_name().as_bstr().to_string())
+            .collect())
+    };
+    assert_eq!(names(&repo)?, ["refs/notes/commits"]);
+
+    {
+        let mut config = repo.config_snapshot_mut();
+        let mut section = config.section_mut_or_create_new("notes", None)?;
+        for pattern in [
+            "refs/notes/review/*",
+            "notes/other",
+            "refs/notes/commits",
+            "refs/notes/missing",
+        ] {
+            section.push("displayRef".try_into()?, Some(pattern.into()));
+        }
+    }
+    assert_eq!(
+        names(&repo)?,
+        ["refs/notes/commits", "refs/notes/review/alice", "refs/notes/other"],
+        "patterns are expanded in order, and missing or duplicate refs are skipped"
+    );
+    Ok(())
+}
+
+#[test]
+fn add_append_and_remove() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_notes_repo.sh")?;
+    let notes = repo.notes()?;
+    let previous = notes.tip()?.expect("present").id;
+    let second = id(&repo, "second")?;
+
+    let commit = notes.add(second, "added\n", false)?.object()?.into_commit();
+    assert_eq!(commit.message_raw()?, "Notes added by 'git notes add'");
+    assert_eq!(commit.parent_ids().collect::<Vec<_>>(), [previous]);
+    assert_eq!(note(&repo, &notes, "second")?.as_deref(), Some("added\n"));
+    assert_eq!(
+        note(&repo, &notes, "first")?.as_deref(),
+        Some("first note\n"),
+        "other notes are kept"
+    );
+
+    let err = notes.add(second, "again\n", false).unwrap_err();
+    assert!(matches!(err, gix::notes::edit::Error::NoteExists { .. }));
+    notes.add(second, "again\n", true)?;
+    assert_eq!(note(&repo, &notes, "second")?.as_deref(), Some("again\n"));
+
+    notes.append(second, "appended\n")?;
+    assert_eq!(note(&repo, &notes, "second")?.as_deref(), Some("again\n\nappended\n"));
+
+    assert!(notes.remove(second)?.is_some());
+    assert_eq!(note(&repo, &notes, "second")?, None);
+    assert!(
+        notes.remove(second)?.is_none(),
+        "nothing is written if there is nothing to remove"
+    );
+
+    let new_notes = repo.notes_at("new")?;
+    new_notes.append(id(&repo, "first")?, "appended\n")?;
+    assert_eq!(
+        note(&repo, &new_notes, "first")?.as_deref(),
+        Some("appended\n"),
+        "appending without a note adds one, and notes refs are created on demand"
+    );
+    assert_eq!(
+        new_notes.tip()?.expect("created").parent_ids().count(),
+        0,
+        "the first notes commit has no parent"
+    );
+    Ok(())
+}
+
+#[test]
+fn merge() -> crate::Result {
+    let (mut repo, _tmp) = crate::repo_rw("make_notes_repo.sh")?;
+    let theirs = "refs/notes/theirs".try_into()?;
+    let their_tip = repo.notes_at("theirs")?.tip()?.expect("present").id;
+
+    let outcome = repo.notes_at("missing")?.merge(theirs, None)?;
+    assert!(matches!(outcome, Outcome::FastForward { commit } if commit == their_tip));
+    let outcome = repo.notes_at("base")?.merge(theirs, None)?;
+    assert!(
+        matches!(outcome, Outcome::FastForward { commit } if commit == their_tip),
+        "our notes are an ancestor of theirs"
+    );
+    assert_eq!(repo.notes_at("base")?.tip()?.expect("present").id, their_tip);
+    let outcome = repo.notes_at("theirs")?.merge("refs/notes/base".try_into()?, None)?;
+    assert!(matches!(outcome, Outcome::UpToDate));
+    let outcome = repo.notes_at("theirs")?.merge("refs/notes/missing".try_into()?, None)?;
+    assert!(matches!(outcome, Outcome::UpToDate), "there is nothing to merge");
+
+    {
+        let ours = repo.notes_at("ours")?;
+        let our_tip = ours.tip()?.expect("present").id;
+        let Outcome::Conflicts(conflicts) = ours.merge(theirs, None)? else {
+            panic!("the manual strategy is the default")
+        };
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].target, id(&repo, "first")?);
+        assert_eq!(ours.tip()?.expect("present").id, our_tip, "nothing was written");
+
+        let Outcome::Merged { commit } = ours.merge(theirs, Some(Strategy::Theirs))? else {
+            panic!("the conflict is resolved by the strategy")
+        };
+        assert_eq!(note(&repo, &ours, "first")?.as_deref(), Some("theirs\n"));
+        assert_eq!(note(&repo, &ours, "second")?.as_deref(), Some("theirs\n"));
+        let commit = commit.object()?.into_commit();
+        assert_eq!(commit.parent_ids().collect::<Vec<_>>(), [our_tip, their_tip]);
+        assert_eq!(
+            commit.message_raw()?,
+            "notes: Merged notes from refs/notes/theirs into refs/notes/ours"
+        );
+    }
+
+    {
+        let mut config = repo.config_snapshot_mut();
+        config.set_value(&Notes::MERGE_STRATEGY, "cat_sort_uniq")?;
+        config.set_subsection_value(&Notes::REF_MERGE_STRATEGY, "review/alice", "union")?;
+    }
+    let alice = repo.notes_at("review/alice")?;
+    assert!(matches!(alice.merge(theirs, None)?, Outcome::Merged { .. }));
+    assert_eq!(
+        note(&repo, &alice, "first")?.as_deref(),
+        Some("looks good\n\ntheirs\n"),
+        "the strategy configured for the notes ref is used first"
+    );
+    assert_eq!(note(&repo, &alice, "second")?.as_deref(), Some("theirs\n"));
+
+    let other = repo.notes_at("other")?;
+    assert!(matches!(other.merge(theirs, None)?, Outcome::Merged { .. }));
+    assert_eq!(
+        note(&repo, &other, "second")?.as_deref(),
+        Some("other note\ntheirs\n"),
+        "otherwise the globally configured strategy is used"
+    );
+    Ok(())
+}
@@ -0,0 +1,346 @@
+use std::path::Path;
+
+use gix::{
+    bstr::{BString, ByteSlice},
+    config::tree::Rebase,
+    rebase::{Editor, EditorError, FixupMessage, Instruction, NoEdit, Options, Outcome, Stop},
+};
+
+fn id(repo: &gix::Repository, spec: &str) -> crate::Result<gix::ObjectId> {
+    Ok(repo.rev_parse_single(spec)?.detach())
+}
+
+/// Return the summaries of all commits reachable from `HEAD`, following first parents.
+fn summaries(repo: &gix::Repository) -> crate::Result<Vec<String>> {
+    let mut out = Vec::new();
+    let mut commit = Some(repo.head_commit()?);
+    while let Some(current) = commit {
+        out.push(current.message()?.summary().to_string());
+        commit = current
+            .parent_ids()
+            .next()
+            .map(|id| id.object().map(gix::Object::into_commit))
+            .transpose()?;
+    }
+    Ok(out)
+}
+
+fn read(repo: &gix::Repository, path: &str) -> crate::Result<String> {
+    Ok(std::fs::read_to_string(repo.work_dir().expect("non-bare").join(path))?)
+}
+
+fn git(workdir: &Path, args: &[&str]) -> crate::Result<std::process::Output> {
+    Ok(std::process::Command::new("git")
+        .args(args)
+        .current_dir(workdir)
+        .env("GIT_EDITOR", "true")
+        .env("GIT_AUTHOR_NAME", "author")
+        .env("GIT_AUTHOR_EMAIL", "author@example.com")
+        .env("GIT_COMMITTER_NAME", "committer")
+        .env("GIT_COMMITTER_EMAIL", "committer@example.com")
+        .output()?)
+}
+
+/// Replace the instructions and all messages to edit.
+struct Replace {
+    instructions: Option<Vec<Instruction>>,
+    message: &'static str,
+}
+
+impl Editor for Replace {
+    fn edit_instructions(&mut self, instructions: &mut Vec<Instruction>) -> Result<(), EditorError> {
+        *instructions = self.instructions.take().expect("called once");
+        Ok(())
+    }
+
+    fn edit_message(&mut self, message: &mut BString) -> Result<(), EditorError> {
+        *message = self.message.into();
+        Ok(())
+    }
+}
+
+fn assert_clean(repo: &gix::Repository) -> crate::Result {
+    assert!(repo.rebase_state()?.is_none(), "the rebase is complete");
+    assert!(repo.try_find_reference("REBASE_HEAD")?.is_none());
+    assert!(!repo.is_dirty()?, "the worktree matches the index");
+    Ok(())
+}
+
+#[test]
+fn autosquash_and_update_refs_as_configured() -> crate::Result {
+    let (mut repo, _tmp) = crate::repo_rw("make_rebase_repo.sh")?;
+    let orig_head = id(&repo, "HEAD")?;
+    assert_eq!(repo.rebase_options()?, Options::default());
+    {
+        let mut config = repo.config_snapshot_mut();
+        config.set_value(&Rebase::AUTO_SQUASH, "true")?;
+        config.set_value(&Rebase::UPDATE_REFS, "true")?;
+    }
+    let options = repo.rebase_options()?;
+    assert!(options.autosquash && options.update_refs && !options.interactive);
+
+    let outcome = repo.rebase(id(&repo, "main")?, None, options, &mut NoEdit)?;
+    assert_eq!(
+        outcome,
+        Outcome::Done {
+            head: id(&repo, "HEAD")?
+        }
+    );
+    assert_eq!(
+        repo.head_name()?.expect("on a branch").as_bstr(),
+        "refs/heads/feature",
+        "the branch is checked out again"
+    );
+    assert_eq!(
+        summaries(&repo)?,
+        ["add dir/new", "change a", "upstream changes b and adds c", "base"],
+        "the fixup was melded into the commit it refers to"
+    );
+    assert_eq!(
+        id(&repo, "feature-middle")?,
+        id(&repo, "HEAD")?,
+        "other branches that pointed to rebased commits were updated"
+    );
+    assert_eq!(id(&repo, "ORIG_HEAD")?, orig_head);
+    assert_eq!(read(&repo, "a")?, "a\na1\na2\n");
+    assert_eq!(read(&repo, "c")?, "c\n");
+    assert_eq!(read(&repo, "dir/new")?, "new\n");
+    assert_clean(&repo)?;
+
+    let outcome = repo.rebase(id(&repo, "main")?, None, options, &mut NoEdit)?;
+    assert_eq!(
+        outcome,
+        Outcome::Done {
+            head: id(&repo, "HEAD")?
+        },
+        "rebasing again changes nothing"
+    );
+    assert_eq!(summaries(&repo)?.len(), 4);
+    Ok(())
+}
+
+#[test]
+fn conflicts_stop_and_can_be_continued_by_git() -> crate::Result {
+    let (repo, tmp) = crate::repo_rw("make_rebase_repo.sh")?;
+    assert!(git(tmp.path(), &["checkout", "-q", "conflicting"])?.status.success());
+
+    let change_b = id(&repo, "conflicting~1")?;
+    let outcome = repo.rebase(id(&repo, "main")?, None, Options::default(), &mut NoEdit)?;
+    assert_eq!(
+        outcome,
+        Outcome::Stopped(Stop::Conflict {
+            commit: change_b,
+            paths: vec!["b".into()],
+        })
+    );
+    assert_eq!(repo.head_name()?, None, "HEAD is detached while rebasing");
+    assert_eq!(id(&repo, "REBASE_HEAD")?, change_b);
+    let stopped = repo.rebase_state()?.expect("in progress").stopped.expect("stopped");
+    assert_eq!(stopped.commit, Some(change_b));
+    assert_eq!(stopped.message.expect("present"), "change b\n");
+    let index = repo.open_index()?;
+    assert_eq!(
+        index
+            .entries()
+            .iter()
+            .filter(|entry| entry.path(&index) == "b")
+            .map(gix::index::Entry::stage)
+            .collect::<Vec<_>>(),
+        [1, 2, 3],
+        "conflicts are recorded in the index"
+    );
+    assert!(read(&repo, "b")?.contains("<<<<<<< HEAD\nb main\n======="));
+    assert_eq!(read(&repo, "c")?, "c\n");
+
+    std::fs::write(tmp.path().join("b"), "resolved\n")?;
+    assert!(git(tmp.path(), &["add", "b"])?.status.success());
+    let out = git(tmp.path(), &["rebase", "--continue"])?;
+    assert!(out.status.success(), "{}", out.stderr.as_bstr());
+
+    assert_eq!(
+        repo.head_name()?.expect("on a branch").as_bstr(),
+        "refs/heads/conflicting"
+    );
+    assert_eq!(
+        summaries(&repo)?,
+        ["add d", "change b", "upstream changes b and adds c", "base"]
+    );
+    assert_eq!(read(&repo, "b")?, "resolved\n");
+    assert_eq!(read(&repo, "d")?, "d\n");
+    assert_clean(&repo)?;
+    Ok(())
+}
+
+#[test]
+fn conflicts_of_git_can_be_continued() -> crate::Result {
+    let (repo, tmp) = crate::repo_rw("make_rebase_repo.sh")?;
+    assert!(git(tmp.path(), &["checkout", "-q", "conflicting"])?.status.success());
+    let original_author = repo
+        .rev_parse_single("conflicting~1")?
+        .object()?
+        .into_commit()
+        .author()?
+        .to_owned();
+    assert!(
+        !git(tmp.path(), &["rebase", "main"])?.status.success(),
+        "git stops on the conflict"
+    );
+    std::fs::write(tmp.path().join("b"), "resolved\n")?;
+    assert!(git(tmp.path(), &["add", "b"])?.status.success());
+
+    assert!(matches!(repo.rebase_continue(&mut NoEdit)?, Outcome::Done { .. }));
+    assert_eq!(
+        repo.head_name()?.expect("on a branch").as_bstr(),
+        "refs/heads/conflicting"
+    );
+    assert_eq!(
+        summaries(&repo)?,
+        ["add d", "change b", "upstream changes b and adds c", "base"]
+    );
+    let change_b = repo
+        .head_commit()?
+        .parent_ids()
+        .next()
+        .expect("present")
+        .object()?
+        .into_commit();
+    assert_eq!(
+        change_b.author()?.to_owned(),
+        original_author,
+        "the author that git recorded is used"
+    );
+    assert_eq!(read(&repo, "b")?, "resolved\n");
+    assert_clean(&repo)?;
+    Ok(())
+}
+
+#[test]
+fn abort_restores_the_branch_and_the_worktree() -> crate::Result {
+    let (repo, tmp) = crate::repo_rw("make_rebase_repo.sh")?;
+    assert!(git(tmp.path(), &["checkout", "-q", "conflicting"])?.status.success());
+    let orig_head = id(&repo, "HEAD")?;
+    assert!(matches!(
+        repo.rebase(id(&repo, "main")?, None, Options::default(), &mut NoEdit)?,
+        Outcome::Stopped(Stop::Conflict { .. })
+    ));
+    assert!(matches!(
+        repo.rebase(id(&repo, "main")?, None, Options::default(), &mut NoEdit),
+        Err(gix::rebase::Error::InProgress)
+    ));
+
+    repo.rebase_abort()?;
+    assert_eq!(
+        repo.head_name()?.expect("on a branch").as_bstr(),
+        "refs/heads/conflicting"
+    );
+    assert_eq!(id(&repo, "HEAD")?, orig_head);
+    assert_eq!(read(&repo, "b")?, "b theirs\n");
+    assert!(
+        !tmp.path().join("c").exists(),
+        "files that were added by the rebase are removed"
+    );
+    assert_clean(&repo)?;
+    assert!(matches!(repo.rebase_abort(), Err(gix::rebase::Error::NotInProgress)));
+    Ok(())
+}
+
+#[test]
+fn skip_drops_the_conflicting_commit() -> crate::Result {
+    let (repo, tmp) = crate::repo_rw("make_rebase_repo.sh")?;
+    assert!(git(tmp.path(), &["checkout", "-q", "conflicting"])?.status.success());
+    assert!(matches!(
+        repo.rebase(id(&repo, "main")?, None, Options::default(), &mut NoEdit)?,
+        Outcome::Stopped(Stop::Conflict { .. })
+    ));
+    assert!(matches!(
+        repo.rebase_continue(&mut NoEdit),
+        Err(gix::rebase::Error::UnresolvedConflicts)
+    ));
+
+    assert!(matches!(repo.rebase_skip(&mut NoEdit)?, Outcome::Done { .. }));
+    assert_eq!(summaries(&repo)?, ["add d", "upstream changes b and adds c", "base"]);
+    assert_eq!(read(&repo, "b")?, "b main\n");
+    assert_clean(&repo)?;
+    Ok(())
+}
+
+#[test]
+fn interactive_reword_exec_edit_and_fixup() -> crate::Result {
+    let (repo, tmp) = crate::repo_rw("make_rebase_repo.sh")?;
+    let hex = |spec: &str| -> crate::Result<BString> { Ok(id(&repo, spec)?.to_string().into()) };
+    let mut editor = Replace {
+        instructions: Some(vec![
+            Instruction::Reword {
+                commit: hex("HEAD~2")?,
+                summary: "change a".into(),
+            },
+            Instruction::Exec {
+                command: "exit 3".into(),
+            },
+            Instruction::Edit {
+                commit: hex("HEAD~1")?,
+                summary: "add dir/new".into(),
+            },
+            Instruction::Fixup {
+                commit: hex("HEAD")?,
+                summary: "fixup! change a".into(),
+                message: FixupMessage::Discard,
+            },
+        ]),
+        message: "reworded\n",
+    };
+    let options = Options {
+        interactive: true,
+        ..Default::default()
+    };
+    let outcome = repo.rebase(id(&repo, "main")?, None, options, &mut editor)?;
+    let Outcome::Stopped(Stop::Exec { command, status }) = outcome else {
+        panic!("expected exec to fail, got {outcome:?}")
+    };
+    assert_eq!(command, "exit 3");
+    assert_eq!(status.code(), Some(3));
+    assert_eq!(summaries(&repo)?[..2], ["reworded", "upstream changes b and adds c"]);
+
+    let add_dir = id(&repo, "feature~1")?;
+    assert_eq!(
+        repo.rebase_continue(&mut editor)?,
+        Outcome::Stopped(Stop::Edit { commit: add_dir })
+    );
+    let state = repo.rebase_state()?.expect("in progress");
+    assert_eq!(state.stopped.expect("stopped").amend, Some(id(&repo, "HEAD")?));
+    assert_eq!(state.todo.len(), 1);
+
+    std::fs::write(tmp.path().join("dir/new"), "amended\n")?;
+    assert!(git(tmp.path(), &["add", "dir/new"])?.status.success());
+    assert!(matches!(repo.rebase_continue(&mut editor)?, Outcome::Done { .. }));
+    assert_eq!(
+        summaries(&repo)?,
+        ["add dir/new", "reworded", "upstream changes b and adds c", "base"],
+        "the fixup was melded into the amended commit"
+    );
+    assert_eq!(read(&repo, "dir/new")?, "amended\n");
+    assert_eq!(read(&repo, "a")?, "a\na1\na2\n");
+    assert_clean(&repo)?;
+    Ok(())
+}
+
+#[test]
+fn removing_all_instructions_does_nothing() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_rebase_repo.sh")?;
+    let head = id(&repo, "HEAD")?;
+    let mut editor = Replace {
+        instructions: Some(Vec::new()),
+        message: "unused",
+    };
+    let options = Options {
+        interactive: true,
+        ..Default::default()
+    };
+    assert!(matches!(
+        repo.rebase(id(&repo, "main")?, None, options, &mut editor),
+        Err(gix::rebase::Error::NothingToDo)
+    ));
+    assert_eq!(id(&repo, "HEAD")?, head);
+    assert!(repo.rebase_state()?.is_none());
+    Ok(())
+}
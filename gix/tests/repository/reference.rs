@@ -224,3 +224,43 @@ mod head {
         Ok(())
     }
 }
+
+mod reftable {
+    use gix::refs::transaction::PreviousValue;
+    use gix_testtools::tempfile;
+
+    #[test]
+    fn is_used_if_configured_as_ref_storage() -> crate::Result {
+        let tmp = tempfile::tempdir()?;
+        let repo = gix::init_bare(tmp.path())?;
+        let config_path = repo.git_dir().join("config");
+        let mut config = std::fs::read_to_string(&config_path)?;
+        config = config.replace("repositoryformatversion = 0", "repositoryformatversion = 1");
+        config.push_str("[extensions]\n\trefStorage = reftable\n");
+        std::fs::write(&config_path, config)?;
+
+        let repo = gix::open_opts(tmp.path(), crate::restricted())?;
+        let id = repo.write_object(gix::objs::Tree::empty())?.detach();
+        repo.reference("refs/heads/main", id, PreviousValue::MustNotExist, "message")?;
+        repo.edit_reference(gix::refs::transaction::RefEdit {
+            change: gix::refs::transaction::Change::Update {
+                log: Default::default(),
+                expected: PreviousValue::Any,
+                new: gix::refs::Target::Symbolic("refs/heads/main".try_into()?),
+            },
+            name: "HEAD".try_into()?,
+            deref: false,
+        })?;
+        assert!(
+            repo.git_dir().join("reftable").join("tables.list").is_file(),
+            "references are written into the reftable"
+        );
+        assert!(!repo.git_dir().join("refs/heads/main").exists());
+
+        let repo = gix::open_opts(tmp.path(), crate::restricted())?;
+        assert_eq!(repo.find_reference("main")?.id(), id);
+        assert_eq!(repo.head_id()?, id, "HEAD is stored in the reftable as well");
+        assert_eq!(repo.references()?.all()?.count(), 1);
+        Ok(())
+    }
+}
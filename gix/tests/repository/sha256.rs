@@ -0,0 +1,118 @@
+use std::sync::atomic::AtomicBool;
+
+use gix::bstr::ByteSlice;
+use gix_testtools::tempfile;
+
+fn repo(name: &str) -> crate::Result<gix::Repository> {
+    Ok(crate::named_subrepo_opts(
+        "make_sha256_repos.sh",
+        name,
+        gix::open::Options::isolated(),
+    )?)
+}
+
+fn assert_history(repo: &gix::Repository) -> crate::Result {
+    assert_eq!(repo.object_hash(), gix_hash::Kind::Sha256);
+    let head = repo.head_commit()?;
+    assert_eq!(head.id.kind(), gix_hash::Kind::Sha256);
+    assert_eq!(head.id.to_string().len(), 64);
+    assert_eq!(head.message()?.summary().as_ref(), "second");
+
+    let tree = head.tree()?;
+    assert_eq!(
+        tree.iter()
+            .map(|e| e.map(|e| e.filename().to_owned()))
+            .collect::<Result<Vec<_>, _>>()?,
+        ["a", "dir"]
+    );
+    let entry = tree.lookup_entry_by_path("dir/b", &mut Vec::new())?.expect("present");
+    assert_eq!(entry.oid().kind(), gix_hash::Kind::Sha256);
+    assert_eq!(entry.object()?.data.as_bstr(), "b\n");
+
+    let parents: Vec<_> = head.parent_ids().collect();
+    assert_eq!(parents.len(), 1);
+    let ancestors = head
+        .id()
+        .ancestors()
+        .all()?
+        .map(|info| info.map(|info| info.id))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(ancestors, [head.id, parents[0].detach()]);
+
+    let tag = repo.find_reference("v1")?.peel_to_id_in_place()?;
+    assert_eq!(tag, head.id, "annotated tags can be peeled");
+
+    let index = repo.index()?;
+    let paths: Vec<_> = index.entries().iter().map(|e| e.path(&index).to_owned()).collect();
+    assert_eq!(paths, ["a", "dir/b"]);
+    assert_eq!(index.entries()[1].id, entry.oid());
+    Ok(())
+}
+
+#[test]
+fn loose_objects_can_be_read() -> crate::Result {
+    assert_history(&repo("loose")?)
+}
+
+#[test]
+fn packed_objects_can_be_read_and_verified() -> crate::Result {
+    let repo = repo("packed")?;
+    assert_history(&repo)?;
+
+    let outcome = repo.objects.store_ref().verify_integrity(
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+        Default::default(),
+    )?;
+    assert_eq!(outcome.index_statistics.len(), 1, "the multi-pack index");
+    assert_eq!(outcome.loose_object_stores.len(), 1);
+
+    let graph = repo.commit_graph()?;
+    assert!(graph.iter_ids().all(|id| id.kind() == gix_hash::Kind::Sha256));
+    assert_eq!(graph.num_commits(), 2);
+    assert!(graph.lookup(repo.head_id()?).is_some());
+    graph.verify_integrity(|_| Ok::<_, std::convert::Infallible>(()))?;
+    Ok(())
+}
+
+#[test]
+fn init_and_write_objects() -> crate::Result {
+    let tmp = tempfile::tempdir()?;
+    let repo: gix::Repository = gix::ThreadSafeRepository::init_opts(
+        tmp.path(),
+        gix::create::Kind::Bare,
+        gix::create::Options {
+            object_hash: gix_hash::Kind::Sha256,
+            ..Default::default()
+        },
+        gix::open::Options::isolated(),
+    )?
+    .into();
+    assert_eq!(repo.object_hash(), gix_hash::Kind::Sha256);
+    assert_eq!(
+        repo.config_snapshot().string("extensions.objectFormat").as_deref(),
+        Some("sha256".into())
+    );
+
+    let empty_blob = repo.write_blob([])?;
+    assert_eq!(empty_blob, gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha256));
+    let blob = repo.write_blob(b"hello\n")?;
+    assert_eq!(
+        blob.to_string(),
+        "2cf8d83d9ee29543b34a87727421fdecb7e3f3a183d337639025de576db9ebb4",
+        "same as `echo hello | git hash-object --stdin` in a SHA256 repository"
+    );
+
+    let mut tree = gix::objs::Tree::empty();
+    tree.entries.push(gix::objs::tree::Entry {
+        mode: gix::objs::tree::EntryKind::Blob.into(),
+        filename: "hello".into(),
+        oid: blob.detach(),
+    });
+    let tree_id = repo.write_object(&tree)?;
+    let tree = repo.find_object(tree_id)?.into_tree();
+    let entries = tree.decode()?.entries.clone();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].oid, blob.detach());
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::sync::atomic::AtomicBool;
+
+use gix::{
+    bstr::BStr,
+    config::tree::Index,
+    sparse::{set::Error, Patterns},
+};
+
+fn exists(repo: &gix::Repository, path: &str) -> bool {
+    repo.work_dir().expect("non-bare").join(path).exists()
+}
+
+fn set(repo: &mut gix::Repository, patterns: &[&str], cone: bool) -> Result<gix::sparse::set::Outcome, Error> {
+    repo.sparse_checkout_set(
+        patterns.iter().map(|p| BStr::new(*p)),
+        cone,
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )
+}
+
+fn add(repo: &mut gix::Repository, patterns: &[&str]) -> Result<gix::sparse::set::Outcome, Error> {
+    repo.sparse_checkout_add(
+        patterns.iter().map(|p| BStr::new(*p)),
+        &mut gix::progress::Discard,
+        &AtomicBool::default(),
+    )
+}
+
+#[test]
+fn set_and_add_in_cone_mode() -> crate::Result {
+    let (mut repo, _tmp) = crate::repo_rw("make_sparse_checkout_repo.sh")?;
+    assert_eq!(repo.sparse_checkout_patterns()?, None, "not enabled by default");
+
+    let outcome = set(&mut repo, &["a/b"], true)?;
+    assert_eq!(outcome.worktree.files_removed, 2, "c/file and c/d/file");
+    assert_eq!(outcome.sparse_directories, 0, "index.sparse isn't set");
+    assert!(exists(&repo, "root") && exists(&repo, "a/file") && exists(&repo, "a/b/file"));
+    assert!(!exists(&repo, "c"), "empty directories are removed as well");
+    assert_eq!(
+        std::fs::read_to_string(repo.sparse_checkout_path())?,
+        "/*\n!/*/\n/a/\n!/a/*/\n/a/b/\n",
+        "the patterns are written like git does"
+    );
+    assert!(!repo.is_dirty()?, "skipped files don't show up as deleted");
+
+    let outcome = add(&mut repo, &["c/d"])?;
+    assert_eq!(
+        outcome.worktree.checkout.files_updated, 2,
+        "c/d/file, and c/file as files in parent directories are included"
+    );
+    assert!(exists(&repo, "c/d/file") && exists(&repo, "c/file"));
+
+    let repo = gix::open(repo.path())?;
+    match repo
+        .sparse_checkout_patterns()?
+        .expect("enabled in the configuration file")
+    {
+        Patterns::Cone(cone) => assert_eq!(cone.directories().collect::<Vec<_>>(), ["a/b", "c/d"]),
+        Patterns::NonCone(_) => unreachable!("cone mode was enabled"),
+    }
+    assert!(!repo.is_dirty()?);
+    Ok(())
+}
+
+#[test]
+fn set_and_add_in_non_cone_mode() -> crate::Result {
+    let (mut repo, _tmp) = crate::repo_rw("make_sparse_checkout_repo.sh")?;
+    assert!(
+        matches!(add(&mut repo, &["a"]), Err(Error::NotEnabled)),
+        "there must be patterns to add to"
+    );
+
+    set(&mut repo, &["/*", "!/a/"], false)?;
+    assert!(exists(&repo, "root") && exists(&repo, "c/d/file"));
+    assert!(!exists(&repo, "a"));
+
+    let outcome = add(&mut repo, &["/a/b/"])?;
+    assert_eq!(outcome.worktree.checkout.files_updated, 1, "a/b/file");
+    assert!(exists(&repo, "a/b/file") && !exists(&repo, "a/file"));
+    assert_eq!(
+        std::fs::read_to_string(repo.sparse_checkout_path())?,
+        "/*\n!/a/\n/a/b/\n"
+    );
+    assert!(!repo.sparse_checkout_patterns()?.expect("enabled").is_cone());
+    assert!(!repo.is_dirty()?);
+    Ok(())
+}
+
+#[test]
+fn sparse_index_stores_excluded_directories_as_single_entry() -> crate::Result {
+    let (mut repo, _tmp) = crate::repo_rw("make_sparse_checkout_repo.sh")?;
+    repo.config_snapshot_mut().set_value(&Index::SPARSE, "true")?;
+
+    let outcome = set(&mut repo, &["a/b"], true)?;
+    assert_eq!(outcome.sparse_directories, 1, "c");
+    let index = repo.open_index()?;
+    assert!(index.is_sparse());
+    assert_eq!(
+        index
+            .entries()
+            .iter()
+            .map(|e| e.path(&index).to_string())
+            .collect::<Vec<_>>(),
+        ["a/b/file", "a/file", "c/", "root"]
+    );
+    assert!(!repo.is_dirty()?);
+
+    let outcome = set(&mut repo, &["c"], true)?;
+    assert_eq!(outcome.worktree.checkout.files_updated, 2, "c/file and c/d/file");
+    assert_eq!(
+        outcome.sparse_directories, 1,
+        "a, which isn't a parent directory anymore"
+    );
+    assert!(!exists(&repo, "a"));
+    assert!(!repo.is_dirty()?);
+    Ok(())
+}
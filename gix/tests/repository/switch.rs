@@ -0,0 +1,98 @@
+use std::sync::atomic::AtomicBool;
+
+use gix::switch::{Error, Options, Target};
+
+fn read(repo: &gix::Repository, path: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(repo.work_dir().expect("non-bare").join(path))
+}
+
+fn switch(repo: &gix::Repository, target: Target, options: Options) -> Result<gix::switch::Outcome, Error> {
+    repo.switch(target, &mut gix::progress::Discard, &AtomicBool::default(), options)
+}
+
+#[test]
+fn to_branch_updates_head_index_and_worktree() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_rebase_repo.sh")?;
+    let previous = repo.head_id()?.detach();
+    let main = repo.rev_parse_single("main")?.detach();
+
+    let outcome = switch(&repo, Target::Branch("refs/heads/main".try_into()?), Options::default())?;
+    assert_eq!(outcome.previous_head, Some(previous));
+    assert_eq!(outcome.head, main);
+    assert_eq!(outcome.worktree.files_removed, 1, "dir/new");
+    assert_eq!(outcome.worktree.checkout.files_updated, 3, "a, b and c");
+
+    assert_eq!(repo.head_name()?.expect("on a branch").as_bstr(), "refs/heads/main");
+    let head = repo.head()?;
+    let mut log = head.log_iter();
+    let log = log.rev()?.expect("log exists").next().expect("entry")?;
+    assert_eq!(log.message, "checkout: moving from feature to main");
+    assert_eq!(log.new_oid, main);
+    assert_eq!(read(&repo, "a")?, "a\n");
+    assert_eq!(read(&repo, "b")?, "b main\n");
+    assert_eq!(read(&repo, "c")?, "c\n");
+    assert!(!repo.work_dir().expect("non-bare").join("dir").exists());
+    assert!(!repo.is_dirty()?, "the index matches both HEAD and the worktree");
+
+    Ok(())
+}
+
+#[test]
+fn to_detached_commit() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_rebase_repo.sh")?;
+    let target = repo.rev_parse_single("conflicting~1")?.detach();
+
+    switch(&repo, Target::Detached(target), Options::default())?;
+    assert!(repo.head()?.is_detached());
+    assert_eq!(repo.head_id()?, target);
+    let head = repo.head()?;
+    let mut log = head.log_iter();
+    let log = log.rev()?.expect("log exists").next().expect("entry")?;
+    assert_eq!(log.message, format!("checkout: moving from feature to {target}"));
+    assert_eq!(log.new_oid, target);
+    assert_eq!(read(&repo, "b")?, "b theirs\n");
+    assert!(!repo.work_dir().expect("non-bare").join("d").exists());
+    assert!(!repo.is_dirty()?);
+    Ok(())
+}
+
+#[test]
+fn local_modifications_prevent_the_switch_unless_forced() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_rebase_repo.sh")?;
+    let previous = repo.head_id()?.detach();
+    let workdir = repo.work_dir().expect("non-bare");
+    std::fs::write(workdir.join("a"), "local change\n")?;
+
+    let err = switch(&repo, Target::Branch("refs/heads/main".try_into()?), Options::default()).unwrap_err();
+    assert!(
+        matches!(&err, Error::Switch(gix_worktree_state::switch::Error::WouldOverwrite { paths }) if paths == &["a"]),
+        "{err:?}"
+    );
+    assert_eq!(repo.head_id()?, previous, "HEAD wasn't changed");
+    assert!(
+        !repo.git_dir().join("HEAD.lock").exists() && !repo.git_dir().join("index.lock").exists(),
+        "all locks were released"
+    );
+
+    switch(
+        &repo,
+        Target::Branch("refs/heads/main".try_into()?),
+        Options { force: true },
+    )?;
+    assert_eq!(read(&repo, "a")?, "a\n", "local changes were discarded");
+    assert!(!repo.is_dirty()?);
+    Ok(())
+}
+
+#[test]
+fn unknown_branches_are_an_error() -> crate::Result {
+    let (repo, _tmp) = crate::repo_rw("make_rebase_repo.sh")?;
+    let err = switch(
+        &repo,
+        Target::Branch("refs/heads/does-not-exist".try_into()?),
+        Options::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, Error::BranchNotFound { .. }), "{err:?}");
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use crate::util::hex_to_id;
+
+#[test]
+fn merge_base() -> crate::Result {
+    let repo = crate::named_repo("make_repo_with_fork_and_dates.sh")?;
+    let c1 = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+    let b1c1 = hex_to_id("bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac");
+    let c2 = hex_to_id("9902e3c3e8f0c569b4ab295ddf473e6de763e1e7");
+    let m1b1 = hex_to_id("288e509293165cb5630d08f4185bdf2445bf6170");
+
+    assert_eq!(repo.merge_base(c2, b1c1)?, c1, "the fork point of both branches");
+    assert_eq!(repo.merge_base(b1c1, c2)?, c1, "the order doesn't matter");
+    assert_eq!(
+        repo.merge_base(m1b1, b1c1)?,
+        b1c1,
+        "a merged commit is its own merge-base, even if the merge isn't in the commit-graph"
+    );
+    assert_eq!(repo.merge_base(c1, c1)?, c1);
+
+    assert_eq!(repo.merge_bases_many(c2, &[b1c1])?, [c1]);
+    assert_eq!(
+        repo.merge_bases_many(m1b1, &[c2, b1c1])?,
+        [b1c1, c2],
+        "others are treated like one merge commit, so both are merge-bases, sorted by commit-time with the youngest first"
+    );
+    Ok(())
+}
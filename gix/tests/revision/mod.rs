@@ -1 +1,2 @@
+mod merge_base;
 mod spec;
@@ -16,6 +16,74 @@ pub fn repo(name: &str) -> crate::Result<gix::Repository> {
     )?)
 }
 
+mod iter {
+    use crate::status::repo;
+    use gix::status::{
+        index_worktree::iter::Summary as IndexWorktree, iter::Item, plumbing::tree_index::Summary as TreeIndex,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn tree_index_changes_come_first() -> crate::Result {
+        let repo = repo("staged-changes")?;
+        let mut status = repo
+            .status(gix::progress::Discard)?
+            .index_worktree_options_mut(|opts| {
+                opts.sorting = Some(gix::status::plumbing::index_as_worktree_with_renames::Sorting::ByPathCaseSensitive)
+            })
+            .into_iter(Vec::new())?;
+        let items: Vec<_> = status.by_ref().filter_map(Result::ok).collect();
+        let actual: Vec<_> = items
+            .iter()
+            .map(|item| {
+                let (tree_index, index_worktree) = match item {
+                    Item::TreeIndex(change) => (Some(change.summary()), None),
+                    Item::IndexWorktree(item) => (None, item.summary()),
+                };
+                (tree_index, index_worktree, item.location().to_string())
+            })
+            .collect();
+        assert_eq!(
+            actual,
+            [
+                (Some(TreeIndex::Added), None, "added".into()),
+                (Some(TreeIndex::Modified), None, "modified".into()),
+                (Some(TreeIndex::Removed), None, "removed".into()),
+                (Some(TreeIndex::Renamed), None, "renamed-destination".into()),
+                (None, Some(IndexWorktree::Added), "removed".into()),
+                (None, Some(IndexWorktree::Modified), "unchanged".into()),
+            ],
+            "renames are tracked as configured by default, and changes between HEAD and the index come first"
+        );
+        assert_eq!(status.tree_index_outcome().index_entries, 4);
+        assert!(
+            status.index_worktree_outcome_mut().is_some(),
+            "the iteration is complete"
+        );
+
+        let items = repo
+            .status(gix::progress::Discard)?
+            .tree_index_rewrites(None)
+            .untracked_files(gix::status::UntrackedFiles::None)
+            .into_iter(vec!["renamed*".into()])?
+            .filter_map(Result::ok)
+            .filter_map(|item| match item {
+                Item::TreeIndex(change) => Some((change.summary(), change.location().to_string())),
+                Item::IndexWorktree(_) => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            items,
+            [
+                (TreeIndex::Removed, "renamed".into()),
+                (TreeIndex::Added, "renamed-destination".into())
+            ],
+            "rewrite tracking can be disabled, and pathspecs apply"
+        );
+        Ok(())
+    }
+}
+
 mod index_worktree {
     mod iter {
         use crate::status::{repo, submodule_repo};
@@ -26,7 +94,7 @@ mod index_worktree {
         fn item_size() {
             assert_eq!(
                 std::mem::size_of::<gix::status::index_worktree::iter::Item>(),
-                264,
+                320,
                 "The size is pretty huge and goes down ideally"
             );
         }
@@ -166,8 +234,18 @@ mod is_dirty {
     }
 
     #[test]
-    fn no_changes() -> crate::Result {
+    fn staged_changes_are_picked_up() -> crate::Result {
+        let repo = crate::status::repo("staged-changes")?;
+        assert!(repo.is_dirty()?, "changes between HEAD and the index count as well");
+
         let repo = submodule_repo("with-submodules")?;
+        assert!(repo.is_dirty()?, "a submodule was added, but not committed yet");
+        Ok(())
+    }
+
+    #[test]
+    fn no_changes() -> crate::Result {
+        let repo = submodule_repo("with-submodules-after-clone")?;
         assert!(!repo.is_dirty()?, "there are no changes");
         Ok(())
     }
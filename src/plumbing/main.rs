@@ -16,8 +16,8 @@ use gix::bstr::{io::BufReadExt, BString};
 
 use crate::plumbing::{
     options::{
-        attributes, commit, commitgraph, config, credential, exclude, free, fsck, index, mailmap, odb, revision, tree,
-        Args, Subcommands,
+        attributes, bundle, commit, commitgraph, config, credential, diff, exclude, free, fsck, gc, index, mailmap,
+        odb, revision, sparse_checkout, tree, Args, Subcommands,
     },
     show_progress,
 };
@@ -233,6 +233,9 @@ pub fn main() -> Result<()> {
                             crate::plumbing::options::status::Format::Simplified => {
                                 core::repository::status::Format::Simplified
                             }
+                            crate::plumbing::options::status::Format::PorcelainV1 => {
+                                core::repository::status::Format::PorcelainV1
+                            }
                             crate::plumbing::options::status::Format::PorcelainV2 => {
                                 core::repository::status::Format::PorcelainV2
                             }
@@ -378,6 +381,37 @@ pub fn main() -> Result<()> {
                 },
             )
             .map(|_| ()),
+            commitgraph::Subcommands::Write {
+                split,
+                size_multiple,
+                max_commits,
+                changed_paths,
+                no_generation_data,
+                specs,
+            } => prepare_and_run(
+                "commitgraph-write",
+                trace,
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::commitgraph::write(
+                        repository(Mode::Lenient)?,
+                        specs,
+                        out,
+                        core::repository::commitgraph::write::Options {
+                            split,
+                            size_multiple,
+                            max_commits,
+                            changed_paths,
+                            generation_data: !no_generation_data,
+                            format,
+                        },
+                    )
+                },
+            )
+            .map(|_| ()),
             commitgraph::Subcommands::Verify { statistics } => prepare_and_run(
                 "commitgraph-verify",
                 trace,
@@ -425,6 +459,61 @@ pub fn main() -> Result<()> {
                 move |progress, out, err| core::repository::clone(remote, directory, config, progress, out, err, opts),
             )
         }
+        #[cfg(feature = "gitoxide-core-tools-serve")]
+        Subcommands::Serve(crate::plumbing::options::serve::Subcommands::UploadPack {
+            advertise_refs,
+            stateless_rpc,
+            directory,
+        }) => prepare_and_run(
+            "serve-upload-pack",
+            trace,
+            false,
+            false,
+            false,
+            None,
+            move |_progress, out, _err| {
+                core::repository::serve::upload_pack(
+                    gix::open(directory)?,
+                    std::io::stdin().lock(),
+                    out,
+                    core::repository::serve::upload_pack::Options {
+                        protocol: std::env::var("GIT_PROTOCOL")
+                            .map(|value| core::repository::serve::Protocol::from_env_value(&value))
+                            .unwrap_or_default(),
+                        advertise_refs,
+                        stateless_rpc,
+                    },
+                )
+            },
+        ),
+        #[cfg(feature = "gitoxide-core-tools-serve")]
+        Subcommands::Serve(crate::plumbing::options::serve::Subcommands::ReceivePack {
+            advertise_refs,
+            stateless_rpc,
+            directory,
+        }) => prepare_and_run(
+            "serve-receive-pack",
+            trace,
+            false,
+            false,
+            false,
+            None,
+            move |_progress, out, err| {
+                core::repository::serve::receive_pack(
+                    gix::open(directory)?,
+                    std::io::stdin().lock(),
+                    out,
+                    err,
+                    core::repository::serve::receive_pack::Options {
+                        protocol: std::env::var("GIT_PROTOCOL")
+                            .map(|value| core::repository::serve::Protocol::from_env_value(&value))
+                            .unwrap_or_default(),
+                        advertise_refs,
+                        stateless_rpc,
+                    },
+                )
+            },
+        ),
         #[cfg(feature = "gitoxide-core-blocking-client")]
         Subcommands::Fetch(crate::plumbing::options::fetch::Platform {
             dry_run,
@@ -458,6 +547,46 @@ pub fn main() -> Result<()> {
             )
         }
         Subcommands::ConfigTree => show_progress(),
+        Subcommands::Blame {
+            statistics,
+            porcelain,
+            incremental,
+            ranges,
+            ignore_rev,
+            ignore_revs_file,
+            rev,
+            file,
+        } => prepare_and_run(
+            "blame",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, err| {
+                core::repository::blame_file(
+                    repository(Mode::Lenient)?,
+                    rev.as_deref(),
+                    &file,
+                    out,
+                    err,
+                    core::repository::blame::Options {
+                        format,
+                        output: if porcelain {
+                            core::repository::blame::Output::Porcelain
+                        } else if incremental {
+                            core::repository::blame::Output::Incremental
+                        } else {
+                            core::repository::blame::Output::Human
+                        },
+                        ranges,
+                        ignore_revs: ignore_rev,
+                        ignore_revs_files: ignore_revs_file,
+                        statistics,
+                    },
+                )
+            },
+        ),
         Subcommands::Credential(cmd) => core::repository::credential(
             repository(Mode::StrictWithGitInstallConfig)?,
             match cmd {
@@ -686,6 +815,8 @@ pub fn main() -> Result<()> {
                     pack_cache_size_mb,
                     counting_threads,
                     object_cache_size_mb,
+                    window,
+                    depth,
                     output_directory,
                 } => {
                     let has_tips = !tips.is_empty();
@@ -705,6 +836,8 @@ pub fn main() -> Result<()> {
                                 nondeterministic_thread_count: nondeterministic_count.then_some(counting_threads),
                                 pack_cache_size_in_bytes: pack_cache_size_mb.unwrap_or(0) * 1_000_000,
                                 object_cache_size_in_bytes: object_cache_size_mb.unwrap_or(0) * 1_000_000,
+                                delta_window: window,
+                                delta_depth: depth,
                                 statistics: if statistics { Some(format) } else { None },
                                 out,
                                 expansion: expansion.unwrap_or(if has_tips {
@@ -1003,6 +1136,40 @@ pub fn main() -> Result<()> {
                     core::repository::revision::previous_branches(repository(Mode::Lenient)?, out, format)
                 },
             ),
+            revision::Subcommands::MergeBase {
+                all,
+                octopus,
+                independent,
+                is_ancestor,
+                specs,
+            } => prepare_and_run(
+                "revision-merge-base",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::revision::merge_base(
+                        repository(Mode::Lenient)?,
+                        specs,
+                        out,
+                        core::repository::revision::merge_base::Options {
+                            format,
+                            all,
+                            mode: if octopus {
+                                core::repository::revision::merge_base::Mode::Octopus
+                            } else if independent {
+                                core::repository::revision::merge_base::Mode::Independent
+                            } else if is_ancestor {
+                                core::repository::revision::merge_base::Mode::IsAncestor
+                            } else {
+                                core::repository::revision::merge_base::Mode::Default
+                            },
+                        },
+                    )
+                },
+            ),
             revision::Subcommands::Explain { spec } => prepare_and_run(
                 "revision-explain",
                 trace,
@@ -1198,6 +1365,155 @@ pub fn main() -> Result<()> {
             None,
             move |_progress, out, _err| core::repository::fsck(repository(Mode::Strict)?, spec, out),
         ),
+        Subcommands::Gc(gc::Platform {
+            auto,
+            prune,
+            no_prune,
+            no_cruft,
+            geometric,
+        }) => prepare_and_run(
+            "gc",
+            trace,
+            auto_verbose,
+            progress,
+            progress_keep_open,
+            core::repository::gc::PROGRESS_RANGE,
+            move |progress, out, _err| {
+                core::repository::gc(
+                    repository(Mode::Lenient)?,
+                    progress,
+                    out,
+                    core::repository::gc::Options {
+                        format,
+                        auto,
+                        prune,
+                        no_prune,
+                        no_cruft,
+                        geometric,
+                    },
+                )
+            },
+        ),
+        Subcommands::Diff(diff::Platform {
+            unified,
+            word_diff,
+            no_renames,
+            old,
+            new,
+        }) => prepare_and_run(
+            "diff",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, _err| {
+                core::repository::diff(
+                    repository(Mode::Lenient)?,
+                    old,
+                    new,
+                    out,
+                    core::repository::diff::Options {
+                        format,
+                        context_lines: unified,
+                        word_diff,
+                        no_renames,
+                    },
+                )
+            },
+        ),
+        Subcommands::Bundle(cmd) => match cmd {
+            bundle::Subcommands::Create {
+                all,
+                version,
+                path,
+                specs,
+            } => prepare_and_run(
+                "bundle-create",
+                trace,
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                core::repository::bundle::PROGRESS_RANGE,
+                move |progress, out, _err| {
+                    core::repository::bundle::create(
+                        repository(Mode::Lenient)?,
+                        progress,
+                        path,
+                        specs,
+                        out,
+                        core::repository::bundle::Options {
+                            format,
+                            version: version.map(|version| match version {
+                                2 => gix::bundle::Version::V2,
+                                _ => gix::bundle::Version::V3,
+                            }),
+                            all,
+                        },
+                    )
+                },
+            ),
+            bundle::Subcommands::Verify { path } => prepare_and_run(
+                "bundle-verify",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| core::repository::bundle::verify(repository(Mode::Lenient)?, path, out),
+            ),
+            bundle::Subcommands::ListHeads { path, names } => prepare_and_run(
+                "bundle-list-heads",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| core::repository::bundle::list_heads(path, names, format, out),
+            ),
+        },
+        Subcommands::SparseCheckout(cmd) => match cmd {
+            sparse_checkout::Subcommands::Set { no_cone, patterns } => prepare_and_run(
+                "sparse-checkout-set",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                core::repository::sparse_checkout::PROGRESS_RANGE,
+                move |progress, out, _err| {
+                    core::repository::sparse_checkout::set(
+                        repository(Mode::Lenient)?,
+                        progress,
+                        patterns,
+                        !no_cone,
+                        out,
+                        format,
+                    )
+                },
+            ),
+            sparse_checkout::Subcommands::Add { patterns } => prepare_and_run(
+                "sparse-checkout-add",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                core::repository::sparse_checkout::PROGRESS_RANGE,
+                move |progress, out, _err| {
+                    core::repository::sparse_checkout::add(repository(Mode::Lenient)?, progress, patterns, out, format)
+                },
+            ),
+            sparse_checkout::Subcommands::List => prepare_and_run(
+                "sparse-checkout-list",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::sparse_checkout::list(repository(Mode::Lenient)?, out, format)
+                },
+            ),
+        },
         Subcommands::Mailmap(cmd) => match cmd {
             mailmap::Subcommands::Entries => prepare_and_run(
                 "mailmap-entries",
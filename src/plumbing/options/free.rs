@@ -169,6 +169,16 @@ pub mod pack {
             /// Packs produced with this option enabled are only valid in transit, but not at rest.
             thin: bool,
 
+            #[clap(long, default_value_t = 0)]
+            /// The amount of similar objects to try as delta base for each object that isn't yet deltified in a pack.
+            ///
+            /// If 0, these objects are written as base objects, which is fast but produces large packs.
+            window: usize,
+
+            #[clap(long, default_value_t = 50, requires = "window")]
+            /// The maximum length of the delta chains created when delta-compressing objects.
+            depth: usize,
+
             /// The directory into which to write the pack file.
             #[clap(long, short = 'o')]
             output_directory: Option<PathBuf>,
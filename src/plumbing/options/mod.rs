@@ -91,12 +91,49 @@ pub enum Subcommands {
     Odb(odb::Subcommands),
     /// Check for missing objects.
     Fsck(fsck::Platform),
+    /// Repack objects, prune unreachable objects, expire reflogs and pack references, similar to `git gc`.
+    Gc(gc::Platform),
+    /// Show the changes between the trees of two revisions as patch, similar to `git diff <old> <new>`.
+    Diff(diff::Platform),
+    /// Create, verify and inspect bundle files, similar to `git bundle`.
+    #[clap(subcommand)]
+    Bundle(bundle::Subcommands),
     /// Interact with tree objects.
     #[clap(subcommand)]
     Tree(tree::Subcommands),
     /// Interact with commit objects.
     #[clap(subcommand)]
     Commit(commit::Subcommands),
+    /// Attribute each line of a file to the commit that introduced it, similar to `git blame`.
+    Blame {
+        /// Print additional statistics to help understanding performance.
+        #[clap(long, short = 's')]
+        statistics: bool,
+        /// Print the output in the machine-readable format of `git blame --porcelain`.
+        #[clap(long, short = 'p', conflicts_with = "incremental")]
+        porcelain: bool,
+        /// Print the output in the machine-readable format of `git blame --incremental`, without the lines themselves.
+        #[clap(long)]
+        incremental: bool,
+        /// Only blame the lines in the given range, as `<start>,<end>` or `<start>,+<count>` with line numbers starting at 1.
+        ///
+        /// Can be given multiple times.
+        #[clap(short = 'L', value_parser = gitoxide::shared::AsRange)]
+        ranges: Vec<std::ops::Range<u32>>,
+        /// Attribute the changes of the given revision to its parent instead. Can be given multiple times.
+        #[clap(long)]
+        ignore_rev: Vec<String>,
+        /// Ignore all revisions listed in the given file, in addition to those in files configured with `blame.ignoreRevsFile`.
+        ///
+        /// Can be given multiple times, and an empty path clears all files given before, including the configured ones.
+        #[clap(long)]
+        ignore_revs_file: Vec<PathBuf>,
+        /// The revision to start the blame from, or `HEAD` if unset.
+        #[clap(long)]
+        rev: Option<std::ffi::OsString>,
+        /// The path of the file to blame, relative to the root of the repository.
+        file: std::ffi::OsString,
+    },
     /// Verify the integrity of the entire repository
     Verify {
         #[clap(flatten)]
@@ -111,6 +148,10 @@ pub enum Subcommands {
     /// Fetch data from remotes and store it in the repository
     #[cfg(feature = "gitoxide-core-blocking-client")]
     Fetch(fetch::Platform),
+    /// Serve repositories to `git` clients via stdin and stdout, for use with `ssh` or `git daemon`.
+    #[cfg(feature = "gitoxide-core-tools-serve")]
+    #[clap(subcommand)]
+    Serve(serve::Subcommands),
     #[cfg(feature = "gitoxide-core-blocking-client")]
     Clone(clone::Platform),
     /// Interact with the mailmap.
@@ -130,6 +171,9 @@ pub enum Subcommands {
     /// Interact with submodules.
     #[clap(alias = "submodules")]
     Submodule(submodule::Platform),
+    /// Only populate the worktree with some of the files of the index, similar to `git sparse-checkout`.
+    #[clap(subcommand)]
+    SparseCheckout(sparse_checkout::Subcommands),
     IsClean,
     IsChanged,
     /// Show which git configuration values are used or planned.
@@ -236,6 +280,8 @@ pub mod status {
         /// A basic format that is easy to read, and useful for a first glimpse as flat list.
         #[default]
         Simplified,
+        /// Output very similar to `git status --porcelain=1`.
+        PorcelainV1,
         /// Output very similar to `git status --porcelain=2`.
         PorcelainV2,
     }
@@ -577,6 +623,41 @@ pub mod clean {
     }
 }
 
+#[cfg(feature = "gitoxide-core-tools-serve")]
+pub mod serve {
+    use std::path::PathBuf;
+
+    #[derive(Debug, clap::Subcommand)]
+    pub enum Subcommands {
+        /// Send objects to a client that fetches or clones, just like `git upload-pack`.
+        ///
+        /// The protocol version is taken from the `GIT_PROTOCOL` environment variable.
+        UploadPack {
+            /// Only advertise references or capabilities and exit, as used by the first request of stateless clients.
+            #[clap(long, alias = "http-backend-info-refs")]
+            advertise_refs: bool,
+            /// Handle a single request without advertising references first, and exit.
+            #[clap(long)]
+            stateless_rpc: bool,
+            /// The repository to serve.
+            directory: PathBuf,
+        },
+        /// Receive objects and reference updates from a client that pushes, just like `git receive-pack`.
+        ///
+        /// The `pre-receive`, `update` and `post-receive` hooks are run if they exist.
+        ReceivePack {
+            /// Only advertise references and exit, as used by the first request of stateless clients.
+            #[clap(long, alias = "http-backend-info-refs")]
+            advertise_refs: bool,
+            /// Handle a single request without advertising references first, and exit.
+            #[clap(long)]
+            stateless_rpc: bool,
+            /// The repository to receive the push.
+            directory: PathBuf,
+        },
+    }
+}
+
 pub mod odb {
     #[derive(Debug, clap::Subcommand)]
     pub enum Subcommands {
@@ -598,6 +679,116 @@ pub mod fsck {
     }
 }
 
+pub mod gc {
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Only perform maintenance if there are too many loose objects or packs, as configured with `gc.auto`
+        /// and `gc.autoPackLimit`.
+        #[clap(long)]
+        pub auto: bool,
+        /// Prune unreachable loose objects older than the given date, like `2.weeks.ago`, `now` or `never`,
+        /// instead of the value of `gc.pruneExpire`.
+        #[clap(long, conflicts_with = "no_prune")]
+        pub prune: Option<String>,
+        /// Don't prune unreachable objects at all.
+        #[clap(long)]
+        pub no_prune: bool,
+        /// Write unreachable objects as loose objects instead of putting them into a cruft pack.
+        #[clap(long)]
+        pub no_cruft: bool,
+        /// Combine packs so that each pack has at least the given factor as many objects as the next smaller one,
+        /// instead of repacking all objects into one pack.
+        #[clap(long, value_name = "FACTOR", value_parser = clap::value_parser!(u32).range(2..))]
+        pub geometric: Option<u32>,
+    }
+}
+
+pub mod diff {
+    use gix::bstr::BString;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Show the given amount of unchanged lines around each change instead of 3.
+        #[clap(long, short = 'U', value_name = "LINES")]
+        pub unified: Option<u32>,
+        /// Show changed words instead of changed lines, like `git diff --word-diff=plain`.
+        #[clap(long)]
+        pub word_diff: bool,
+        /// Don't detect renames and copies, even if configured.
+        #[clap(long)]
+        pub no_renames: bool,
+        /// The revision whose tree is the old state.
+        #[clap(value_parser = gitoxide::shared::AsBString)]
+        pub old: BString,
+        /// The revision whose tree is the new state.
+        #[clap(value_parser = gitoxide::shared::AsBString)]
+        pub new: BString,
+    }
+}
+
+pub mod bundle {
+    use std::path::PathBuf;
+
+    use gix::bstr::BString;
+
+    #[derive(Debug, clap::Subcommand)]
+    pub enum Subcommands {
+        /// Write a bundle with all objects and references selected by the given rev-specs.
+        Create {
+            /// Add `HEAD` and all references to the bundle.
+            #[clap(long)]
+            all: bool,
+            /// The version of the bundle, 2 or 3, which defaults to the lowest version able to store the objects.
+            #[clap(long, value_parser = clap::value_parser!(u8).range(2..=3))]
+            version: Option<u8>,
+            /// The path to write the bundle to.
+            path: PathBuf,
+            /// Rev-specs like `main`, `v1.0..main` or `^v1.0` to select the references and objects of the bundle.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            specs: Vec<BString>,
+        },
+        /// Check if the bundle can be fetched from by the current repository, which needs to have all its prerequisites.
+        Verify {
+            /// The path to the bundle to verify.
+            path: PathBuf,
+        },
+        /// List the references in a bundle.
+        ListHeads {
+            /// The path to the bundle.
+            path: PathBuf,
+            /// Only list references whose name ends with one of these names.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            names: Vec<BString>,
+        },
+    }
+}
+
+pub mod sparse_checkout {
+    use gix::bstr::BString;
+
+    #[derive(Debug, clap::Subcommand)]
+    pub enum Subcommands {
+        /// Enable the sparse checkout and only keep what's included by the given directories or patterns in the worktree.
+        Set {
+            /// Interpret the arguments as patterns like in `.gitignore` files, instead of as directories to include
+            /// along with the files in all of their parent directories.
+            #[clap(long)]
+            no_cone: bool,
+            /// The directories to include, or the patterns to use with `--no-cone`.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            patterns: Vec<BString>,
+        },
+        /// Add directories or patterns to the current sparse checkout.
+        Add {
+            /// The directories or patterns to add, depending on whether or not the sparse checkout is in cone mode.
+            #[clap(value_parser = gitoxide::shared::AsBString, required = true)]
+            patterns: Vec<BString>,
+        },
+        /// List the directories included in cone mode, or the patterns otherwise.
+        List,
+    }
+}
+
 pub mod tree {
     #[derive(Debug, clap::Subcommand)]
     pub enum Subcommands {
@@ -705,6 +896,26 @@ pub mod commitgraph {
             #[clap(default_value = "@")]
             spec: std::ffi::OsString,
         },
+        /// Write a commit-graph with all commits reachable from the given revisions, or from all references if none are given.
+        Write {
+            /// Add a new file to a chain of commit-graph files instead of rewriting a single file with all commits.
+            #[clap(long)]
+            split: bool,
+            /// Merge the top of the chain into the new file if it doesn't have more than this multiple of its commits.
+            #[clap(long, requires = "split")]
+            size_multiple: Option<u32>,
+            /// Merge the top of the chain into the new file as long as it has more than this amount of commits.
+            #[clap(long, requires = "split")]
+            max_commits: Option<u32>,
+            /// Compute and write changed-path Bloom filters to accelerate path-limited history traversals.
+            #[clap(long)]
+            changed_paths: bool,
+            /// Don't write corrected commit dates, also known as generation numbers v2.
+            #[clap(long)]
+            no_generation_data: bool,
+            /// The rev-specs of the commits to start the traversal from.
+            specs: Vec<std::ffi::OsString>,
+        },
     }
 }
 
@@ -779,6 +990,27 @@ pub mod revision {
         /// Return the names and hashes of all previously checked-out branches.
         #[clap(visible_alias = "prev")]
         PreviousBranches,
+        /// Find the best common ancestors of the given commits, similar to `git merge-base`.
+        ///
+        /// By default, the merge-bases between the first commit and a hypothetical merge of all other commits are found.
+        #[clap(visible_alias = "mb")]
+        MergeBase {
+            /// Print all merge-bases instead of only the best one.
+            #[clap(short = 'a', long)]
+            all: bool,
+            /// Find the merge-bases of all commits, as needed for an octopus merge.
+            #[clap(long, conflicts_with_all = ["independent", "is_ancestor"])]
+            octopus: bool,
+            /// Print the commits that can't be reached from any other of the given commits.
+            #[clap(long, conflicts_with_all = ["all", "is_ancestor"])]
+            independent: bool,
+            /// Succeed if the first commit is an ancestor of the second commit, and fail otherwise.
+            #[clap(long, conflicts_with = "all")]
+            is_ancestor: bool,
+            /// rev-specs like `@`, `main` or `origin/main` that point to commits.
+            #[clap(required = true)]
+            specs: Vec<std::ffi::OsString>,
+        },
     }
 }
 
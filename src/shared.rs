@@ -408,16 +408,67 @@ mod clap {
                 .parse_ref(cmd, arg, value)
         }
     }
+
+    /// Parse one-based, inclusive line ranges like `<start>,<end>` or `<start>,+<count>` into zero-based, half-open ones.
+    #[derive(Clone)]
+    pub struct AsRange;
+
+    impl TypedValueParser for AsRange {
+        type Value = std::ops::Range<u32>;
+
+        fn parse_ref(&self, cmd: &Command, arg: Option<&Arg>, value: &OsStr) -> Result<Self::Value, Error> {
+            StringValueParser::new()
+                .try_map(|arg: String| -> Result<_, Box<dyn std::error::Error + Send + Sync>> {
+                    let (start, end) = arg
+                        .split_once(',')
+                        .ok_or("expected '<start>,<end>' or '<start>,+<count>'")?;
+                    let start = u32::from_str(start)?;
+                    if start == 0 {
+                        return Err("line numbers start at 1".into());
+                    }
+                    let end = match end.strip_prefix('+') {
+                        Some(count) => (start - 1) + u32::from_str(count)?,
+                        None => u32::from_str(end)?,
+                    };
+                    if end < start {
+                        return Err("the range must contain at least one line".into());
+                    }
+                    Ok(start - 1..end)
+                })
+                .parse_ref(cmd, arg, value)
+        }
+    }
 }
 pub use self::clap::{
-    AsBString, AsHashKind, AsOutputFormat, AsPartialRefName, AsPathSpec, AsTime, CheckPathSpec, ParseRenameFraction,
+    AsBString, AsHashKind, AsOutputFormat, AsPartialRefName, AsPathSpec, AsRange, AsTime, CheckPathSpec,
+    ParseRenameFraction,
 };
 
 #[cfg(test)]
 mod value_parser_tests {
-    use super::ParseRenameFraction;
+    use super::{AsRange, ParseRenameFraction};
     use clap::Parser;
 
+    #[test]
+    fn range() {
+        #[derive(Debug, clap::Parser)]
+        pub struct Cmd {
+            #[clap(short = 'L', value_parser = AsRange)]
+            pub ranges: Vec<std::ops::Range<u32>>,
+        }
+
+        let c = Cmd::parse_from(["cmd", "-L", "1,1", "-L", "3,+2"]);
+        assert_eq!(
+            c.ranges,
+            [0..1, 2..4],
+            "inclusive one-based lines become zero-based ranges"
+        );
+
+        for invalid in ["0,1", "2,1", "1,+0", "1", "a,b"] {
+            assert!(Cmd::try_parse_from(["cmd", "-L", invalid]).is_err(), "{invalid}");
+        }
+    }
+
     #[test]
     fn rename_fraction() {
         #[derive(Debug, clap::Parser)]
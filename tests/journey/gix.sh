@@ -156,6 +156,135 @@ title "gix (with repository)"
       )
     )
   )
+  if test "$kind" = "max" || test "$kind" = "max-pure"; then
+  title "gix serve"
+  (when "running 'serve'"
+    title "gix serve upload-pack"
+    (with "the 'upload-pack' subcommand"
+      (small-repo-in-sandbox
+        for version in 0 1 2; do
+        (with "protocol version $version"
+          it "serves a full clone to git" && {
+            expect_run $SUCCESSFULLY git -c protocol.version=$version clone --upload-pack="$exe_plumbing serve upload-pack" "file://$PWD" clone-v$version
+          }
+          it "produces a complete repository" && {
+            expect_run $SUCCESSFULLY git -C clone-v$version fsck --connectivity-only
+          }
+          it "serves a shallow clone to git" && {
+            expect_run $SUCCESSFULLY git -c protocol.version=$version clone --depth 1 --upload-pack="$exe_plumbing serve upload-pack" "file://$PWD" shallow-v$version
+          }
+          it "produces a repository with a single commit" && {
+            expect_run $SUCCESSFULLY test "$(git -C shallow-v$version rev-list --count HEAD)" = 1
+          }
+        )
+        done
+      )
+    )
+    title "gix serve receive-pack"
+    (with "the 'receive-pack' subcommand"
+      (small-repo-in-sandbox
+        git init --bare -q remote.git
+        remote="file://$PWD/remote.git"
+        (with "a push of a new branch"
+          it "succeeds" && {
+            expect_run $SUCCESSFULLY git push --receive-pack="$exe_plumbing serve receive-pack" "$remote" HEAD:refs/heads/main
+          }
+          it "produces a complete repository with the branch pointing to the pushed commit" && {
+            expect_run $SUCCESSFULLY git --git-dir=remote.git fsck --connectivity-only
+            expect_run $SUCCESSFULLY test "$(git --git-dir=remote.git rev-parse main)" = "$(git rev-parse HEAD)"
+          }
+        )
+        (with "a forced push and a deletion"
+          git push -q --receive-pack="$exe_plumbing serve receive-pack" "$remote" HEAD:refs/heads/other
+          it "succeeds" && {
+            expect_run $SUCCESSFULLY git push -f --receive-pack="$exe_plumbing serve receive-pack" "$remote" HEAD~1:refs/heads/main :refs/heads/other
+          }
+          it "updates and deletes the branches" && {
+            expect_run $SUCCESSFULLY test "$(git --git-dir=remote.git rev-parse main)" = "$(git rev-parse HEAD~1)"
+            expect_run $WITH_FAILURE git --git-dir=remote.git rev-parse --verify -q refs/heads/other
+          }
+        )
+        (with "an 'update' hook that declines"
+          printf '#!/bin/sh\ntest "$1" != refs/heads/declined\n' > remote.git/hooks/update
+          chmod +x remote.git/hooks/update
+          it "fails" && {
+            expect_run $WITH_FAILURE git push --receive-pack="$exe_plumbing serve receive-pack" "$remote" HEAD:refs/heads/declined
+          }
+          it "doesn't create the branch" && {
+            expect_run $WITH_FAILURE git --git-dir=remote.git rev-parse --verify -q refs/heads/declined
+          }
+        )
+      )
+    )
+  )
+  fi
+  title "gix revision merge-base"
+  (when "running 'revision merge-base'"
+    (small-repo-in-sandbox
+      {
+        git checkout dev
+        git commit --allow-empty -m "fourth"
+        git checkout main
+      } &>/dev/null
+      it "finds the same merge-base as git" && {
+        expect_run $SUCCESSFULLY test "$("$exe_plumbing" --no-verbose revision merge-base main dev)" = "$(git merge-base main dev)"
+      }
+      (with "--is-ancestor"
+        it "succeeds if the first commit is an ancestor of the second one" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose revision merge-base --is-ancestor unannotated main
+        }
+        it "fails otherwise" && {
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose revision merge-base --is-ancestor main dev
+        }
+      )
+    )
+  )
+  title "gix blame"
+  (when "running 'blame'"
+    (small-repo-in-sandbox
+      {
+        printf 'one\ntwo\n' > blamed
+        git add blamed && git commit -m "add blamed file"
+        printf 'one\nTWO\nthree\n' > blamed
+        git commit -am "change blamed file"
+      } &>/dev/null
+      it "produces the same porcelain output as git" && {
+        expect_run $SUCCESSFULLY test "$("$exe_plumbing" --no-verbose blame --porcelain blamed)" = "$(git blame --porcelain blamed)"
+      }
+      (with "-L"
+        it "produces the same output as git" && {
+          expect_run $SUCCESSFULLY test "$("$exe_plumbing" --no-verbose blame -L 2,+2 blamed)" = "$(git blame -L 2,+2 blamed)"
+        }
+      )
+      (with "a file that doesn't exist"
+        it "fails" && {
+          expect_run $WITH_FAILURE "$exe_plumbing" --no-verbose blame does-not-exist
+        }
+      )
+    )
+  )
+  title "gix status"
+  (when "running 'status' on paths that need quoting"
+    (small-repo-in-sandbox
+      {
+        touch "with space" "non-ascii-ü" 'with"quote'
+        git add "with space" && git commit -m "add file with space"
+        echo changed > "with space"
+        git add "non-ascii-ü"
+      } &>/dev/null
+      it "produces the same porcelain v1 output as git" && {
+        expect_run $SUCCESSFULLY test "$("$exe_plumbing" --no-verbose status --format porcelain-v1)" = "$(git status --porcelain=v1)"
+      }
+      it "produces the same porcelain v2 output as git" && {
+        expect_run $SUCCESSFULLY test "$("$exe_plumbing" --no-verbose status --format porcelain-v2)" = "$(git status --porcelain=v2)"
+      }
+      (with "core.quotePath=false"
+        it "produces the same porcelain v1 output as git" && {
+          expect_run $SUCCESSFULLY test "$("$exe_plumbing" --no-verbose -c core.quotePath=false status --format porcelain-v1)" = "$(git -c core.quotePath=false status --porcelain=v1)"
+        }
+      )
+    )
+  )
 )
 
 title "gix attributes"
@@ -194,6 +323,20 @@ title "gix commit-graph"
       )
     )
   )
+  title "gix commit-graph write"
+  (with "the 'write' sub-command"
+    snapshot="$snapshot/write"
+
+    (small-repo-in-sandbox
+      it "generates the correct output" && {
+        WITH_SNAPSHOT="$snapshot/success" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose commit-graph write
+      }
+      it "writes a commit-graph that git can verify" && {
+        expect_run $SUCCESSFULLY git commit-graph verify
+      }
+    )
+  )
 )
 
 (with "gix free"